@@ -6,6 +6,7 @@ pub struct Options {
     pub handshake_info: bool,
     pub no_tags: bool,
     pub shallow: gix::remote::fetch::Shallow,
+    pub filter: Option<gix::remote::fetch::Filter>,
 }
 
 pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
@@ -32,6 +33,7 @@ pub(crate) mod function {
             bare,
             no_tags,
             shallow,
+            filter,
         }: Options,
     ) -> anyhow::Result<()>
     where
@@ -68,6 +70,9 @@ pub(crate) mod function {
         if no_tags {
             prepare = prepare.configure_remote(|r| Ok(r.with_fetch_tags(gix::remote::fetch::Tags::None)));
         }
+        if let Some(filter) = filter {
+            prepare = prepare.with_filter(filter);
+        }
         let (mut checkout, fetch_outcome) = prepare
             .with_shallow(shallow)
             .fetch_then_checkout(&mut progress, &gix::interrupt::IS_INTERRUPTED)?;
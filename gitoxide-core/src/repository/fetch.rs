@@ -9,6 +9,7 @@ pub struct Options {
     /// If non-empty, override all ref-specs otherwise configured in the remote
     pub ref_specs: Vec<BString>,
     pub shallow: gix::remote::fetch::Shallow,
+    pub filter: Option<gix::remote::fetch::Filter>,
     pub handshake_info: bool,
 }
 
@@ -32,6 +33,7 @@ pub(crate) mod function {
             remote,
             handshake_info,
             shallow,
+            filter,
             ref_specs,
         }: Options,
     ) -> anyhow::Result<()>
@@ -48,12 +50,15 @@ pub(crate) mod function {
             remote.replace_refspecs(ref_specs.iter(), gix::remote::Direction::Fetch)?;
             remote = remote.with_fetch_tags(gix::remote::fetch::Tags::None);
         }
-        let res: gix::remote::fetch::Outcome = remote
+        let mut prepare = remote
             .connect(gix::remote::Direction::Fetch)?
             .prepare_fetch(&mut progress, Default::default())?
             .with_dry_run(dry_run)
-            .with_shallow(shallow)
-            .receive(&mut progress, &gix::interrupt::IS_INTERRUPTED)?;
+            .with_shallow(shallow);
+        if let Some(filter) = filter {
+            prepare = prepare.with_filter(filter);
+        }
+        let res: gix::remote::fetch::Outcome = prepare.receive(&mut progress, &gix::interrupt::IS_INTERRUPTED)?;
 
         if handshake_info {
             writeln!(out, "Handshake Information")?;
@@ -27,7 +27,15 @@ pub use fetch::function::fetch;
 pub mod index;
 pub mod mailmap;
 pub mod odb;
+#[cfg(feature = "blocking-client")]
+mod receive_pack;
+#[cfg(feature = "blocking-client")]
+pub use receive_pack::function as receive_pack;
 pub mod remote;
 pub mod revision;
 pub mod tree;
+#[cfg(feature = "blocking-client")]
+mod upload_pack;
+#[cfg(feature = "blocking-client")]
+pub use upload_pack::function as upload_pack;
 pub mod verify;
@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use gix::upload_pack::{Options, Version};
+
+pub fn function(
+    directory: PathBuf,
+    stateless_rpc: bool,
+    advertise_refs: bool,
+    thread_limit: Option<usize>,
+) -> anyhow::Result<()> {
+    let repo = gix::open(&directory).with_context(|| format!("Could not open repository at {directory:?}"))?;
+    let version = match std::env::var_os("GIT_PROTOCOL") {
+        Some(value) => Version::from_git_protocol(gix::path::os_str_into_bstr(&value)?),
+        None => Version::default(),
+    };
+    repo.upload_pack(
+        std::io::stdin().lock(),
+        std::io::stdout().lock(),
+        Options {
+            version,
+            stateless_rpc,
+            advertise_refs,
+            thread_limit,
+        },
+    )
+    .map_err(Into::into)
+}
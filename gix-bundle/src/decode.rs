@@ -0,0 +1,122 @@
+use std::{io::BufRead, path::Path};
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::{Header, Prerequisite, Ref, Version};
+
+/// The error returned by [`Header::from_read()`] and [`Header::at()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read the bundle header")]
+    Io(#[from] std::io::Error),
+    #[error("The data does not start with the signature of a bundle in version 2 or 3")]
+    MissingSignature,
+    #[error("The bundle ended before the end of its header")]
+    UnexpectedEof,
+    #[error("The capability {capability:?} is unknown or not allowed in bundles of version 2")]
+    UnsupportedCapability { capability: BString },
+    #[error("The object format {name:?} is unsupported")]
+    UnsupportedObjectFormat { name: BString },
+    #[error("Could not parse header line {line:?}")]
+    InvalidLine { line: BString },
+}
+
+impl Header {
+    /// Read the header from `read`, which is positioned right at the beginning of the pack afterwards.
+    pub fn from_read(read: &mut dyn BufRead) -> Result<Self, Error> {
+        let mut line = Vec::new();
+        read.read_until(b'\n', &mut line)?;
+        let version = [Version::V2, Version::V3]
+            .into_iter()
+            .find(|version| version.signature() == line)
+            .ok_or(Error::MissingSignature)?;
+        let mut header = Header {
+            version,
+            object_hash: gix_hash::Kind::Sha1,
+            filter: None,
+            prerequisites: Vec::new(),
+            refs: Vec::new(),
+        };
+        loop {
+            line.clear();
+            read.read_until(b'\n', &mut line)?;
+            let line = line.strip_suffix(b"\n").ok_or(Error::UnexpectedEof)?;
+            if line.is_empty() {
+                break;
+            }
+            match line.strip_prefix(b"@") {
+                Some(capability) if version == Version::V3 => header.parse_capability(capability)?,
+                Some(_) => {
+                    return Err(Error::UnsupportedCapability {
+                        capability: line.into(),
+                    })
+                }
+                None => header.parse_prerequisite_or_ref(line)?,
+            }
+        }
+        Ok(header)
+    }
+
+    /// Read the header of the bundle at `path`.
+    pub fn at(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut read = std::io::BufReader::new(std::fs::File::open(path)?);
+        Self::from_read(&mut read)
+    }
+
+    fn parse_capability(&mut self, capability: &[u8]) -> Result<(), Error> {
+        let (name, value) = capability
+            .split_once_str("=")
+            .map_or((capability, None), |(name, value)| (name, Some(value)));
+        match (name, value) {
+            (b"object-format", Some(value)) => {
+                self.object_hash = value
+                    .to_str()
+                    .ok()
+                    .and_then(|name| name.parse().ok())
+                    .ok_or_else(|| Error::UnsupportedObjectFormat { name: value.into() })?;
+            }
+            (b"filter", Some(value)) => self.filter = Some(value.into()),
+            _ => {
+                return Err(Error::UnsupportedCapability {
+                    capability: capability.into(),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_prerequisite_or_ref(&mut self, line: &[u8]) -> Result<(), Error> {
+        let invalid = || Error::InvalidLine { line: line.into() };
+        match line.strip_prefix(b"-") {
+            Some(prerequisite) => {
+                let (hex, comment) = prerequisite
+                    .split_once_str(" ")
+                    .map_or((prerequisite, &b""[..]), |(hex, comment)| (hex, comment));
+                self.prerequisites.push(Prerequisite {
+                    id: self.parse_id(hex).ok_or_else(invalid)?,
+                    comment: comment.into(),
+                });
+            }
+            None => {
+                let (hex, name) = line.split_once_str(" ").ok_or_else(invalid)?;
+                if name.is_empty() {
+                    return Err(invalid());
+                }
+                self.refs.push(Ref {
+                    id: self.parse_id(hex).ok_or_else(invalid)?,
+                    name: name.into(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_id(&self, hex: &[u8]) -> Option<ObjectId> {
+        if hex.len() != self.object_hash.len_in_hex() {
+            return None;
+        }
+        ObjectId::from_hex(hex).ok()
+    }
+}
@@ -0,0 +1,38 @@
+use std::io;
+
+use crate::{Header, Version};
+
+impl Header {
+    /// Write this header to `out`, after which the pack has to be written to complete the bundle.
+    ///
+    /// Note that the [object hash][Header::object_hash] and [filter][Header::filter] are only written in
+    /// [version 3][Version::V3] of the format.
+    pub fn write_to(&self, mut out: impl io::Write) -> io::Result<()> {
+        out.write_all(self.version.signature())?;
+        if self.version == Version::V3 {
+            let object_format = match self.object_hash {
+                gix_hash::Kind::Sha1 => "sha1",
+            };
+            writeln!(out, "@object-format={object_format}")?;
+            if let Some(filter) = &self.filter {
+                out.write_all(b"@filter=")?;
+                out.write_all(filter)?;
+                out.write_all(b"\n")?;
+            }
+        }
+        for prerequisite in &self.prerequisites {
+            write!(out, "-{}", prerequisite.id)?;
+            if !prerequisite.comment.is_empty() {
+                out.write_all(b" ")?;
+                out.write_all(&prerequisite.comment)?;
+            }
+            out.write_all(b"\n")?;
+        }
+        for r in &self.refs {
+            write!(out, "{} ", r.id)?;
+            out.write_all(&r.name)?;
+            out.write_all(b"\n")?;
+        }
+        out.write_all(b"\n")
+    }
+}
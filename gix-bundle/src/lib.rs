@@ -0,0 +1,76 @@
+//! Read and write the header of git bundles, files which contain a pack along with the refs pointing into it and the
+//! commits it depends on, similar to what `git bundle` does.
+//!
+//! The header is followed by the pack, which is neither read nor written here.
+#![deny(rust_2018_idioms, missing_docs)]
+#![forbid(unsafe_code)]
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+/// The version of the bundle format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Version {
+    /// The original format, which only supports `sha1` object ids.
+    V2,
+    /// A format which adds capabilities like the object format and the filter used to create the pack.
+    V3,
+}
+
+impl Version {
+    /// Return the line with which bundles of this version start, including the trailing newline.
+    pub fn signature(&self) -> &'static [u8] {
+        match self {
+            Version::V2 => b"# v2 git bundle\n",
+            Version::V3 => b"# v3 git bundle\n",
+        }
+    }
+}
+
+/// A commit the pack in a bundle depends on, which has to be present in a repository to unbundle it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Prerequisite {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// Information for humans, typically the subject of the commit, which may be empty.
+    pub comment: BString,
+}
+
+/// A reference stored in a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ref {
+    /// The object the reference points to, which is contained in the pack or one of the prerequisites.
+    pub id: ObjectId,
+    /// The full name of the reference, like `refs/heads/main` or `HEAD`.
+    pub name: BString,
+}
+
+/// The header of a bundle, which precedes the pack.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Header {
+    /// The version of the bundle format.
+    pub version: Version,
+    /// The kind of hash used for all object ids in the bundle.
+    ///
+    /// It's only written in [version 3][Version::V3].
+    pub object_hash: gix_hash::Kind,
+    /// The object filter specification used to create the pack, like `blob:none`, which makes it incomplete.
+    ///
+    /// It's only written in [version 3][Version::V3].
+    pub filter: Option<BString>,
+    /// The commits the pack depends on.
+    pub prerequisites: Vec<Prerequisite>,
+    /// The references whose objects are contained in the bundle.
+    pub refs: Vec<Ref>,
+}
+
+impl Header {
+    /// Return the ref with the given full `name`, like `refs/heads/main` or `HEAD`.
+    pub fn find_ref(&self, name: &[u8]) -> Option<&Ref> {
+        self.refs.iter().find(|r| r.name == name)
+    }
+}
+
+///
+pub mod decode;
+mod encode;
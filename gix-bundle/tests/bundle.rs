@@ -0,0 +1,155 @@
+use gix_bundle::{Header, Prerequisite, Ref, Version};
+use gix_hash::ObjectId;
+
+fn hex_to_id(hex: &str) -> ObjectId {
+    ObjectId::from_hex(hex.as_bytes()).expect("40 bytes hex")
+}
+
+fn decode(data: &[u8]) -> std::result::Result<(Header, &[u8]), gix_bundle::decode::Error> {
+    let mut read = data;
+    let header = Header::from_read(&mut read)?;
+    Ok((header, read))
+}
+
+mod decode {
+    use gix_bundle::{decode::Error, Prerequisite, Ref, Version};
+
+    use crate::{decode, hex_to_id};
+
+    #[test]
+    fn v2_with_prerequisites_and_refs() -> crate::Result {
+        let (header, pack) = decode(
+            b"# v2 git bundle
+-e69de29bb2d1d6434b8b29ae775ad8c2e48c5391 the subject
+-4b825dc642cb6eb9a060e54bf8d69288fbee4904
+8d7e9d0bd3bba7b5d7a2bfea1c4e5d7e5c3e7a21 refs/heads/main
+8d7e9d0bd3bba7b5d7a2bfea1c4e5d7e5c3e7a21 HEAD
+
+PACK",
+        )?;
+        assert_eq!(header.version, Version::V2);
+        assert_eq!(header.object_hash, gix_hash::Kind::Sha1);
+        assert_eq!(header.filter, None);
+        assert_eq!(
+            header.prerequisites,
+            vec![
+                Prerequisite {
+                    id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                    comment: "the subject".into()
+                },
+                Prerequisite {
+                    id: hex_to_id("4b825dc642cb6eb9a060e54bf8d69288fbee4904"),
+                    comment: "".into()
+                }
+            ]
+        );
+        assert_eq!(header.refs.len(), 2);
+        assert_eq!(
+            header.find_ref(b"HEAD"),
+            Some(&Ref {
+                id: hex_to_id("8d7e9d0bd3bba7b5d7a2bfea1c4e5d7e5c3e7a21"),
+                name: "HEAD".into()
+            })
+        );
+        assert_eq!(pack, b"PACK", "the reader is positioned at the beginning of the pack");
+        Ok(())
+    }
+
+    #[test]
+    fn v3_with_capabilities() -> crate::Result {
+        let (header, _) = decode(
+            b"# v3 git bundle
+@object-format=sha1
+@filter=blob:none
+8d7e9d0bd3bba7b5d7a2bfea1c4e5d7e5c3e7a21 refs/heads/main
+
+",
+        )?;
+        assert_eq!(header.version, Version::V3);
+        assert_eq!(header.filter.as_ref().map(|f| f.as_slice()), Some(&b"blob:none"[..]));
+        assert!(header.prerequisites.is_empty());
+        assert_eq!(header.refs.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() {
+        for (input, expected) in [
+            (&b"# v4 git bundle\n\n"[..], "MissingSignature"),
+            (b"PACK", "MissingSignature"),
+            (
+                b"# v2 git bundle\n8d7e9d0bd3bba7b5d7a2bfea1c4e5d7e5c3e7a21 refs/heads/main\n",
+                "UnexpectedEof",
+            ),
+            (b"# v2 git bundle\n@object-format=sha1\n\n", "UnsupportedCapability"),
+            (b"# v3 git bundle\n@unknown\n\n", "UnsupportedCapability"),
+            (b"# v3 git bundle\n@object-format=sha256\n\n", "UnsupportedObjectFormat"),
+            (
+                b"# v2 git bundle\n8d7e9d0bd3bba7b5d7a2bfea1c4e5d7e5c3e7a21\n\n",
+                "InvalidLine",
+            ),
+            (b"# v2 git bundle\n8d7e9d0 refs/heads/main\n\n", "InvalidLine"),
+            (b"# v2 git bundle\n-nothex\n\n", "InvalidLine"),
+        ] {
+            let err = decode(input).unwrap_err();
+            assert!(
+                format!("{err:?}").starts_with(expected),
+                "{err:?} should be {expected} for {input:?}"
+            );
+            assert!(!matches!(err, Error::Io(_)));
+        }
+    }
+}
+
+mod encode {
+    use super::*;
+
+    #[test]
+    fn round_trips_in_all_versions() -> crate::Result {
+        for version in [Version::V2, Version::V3] {
+            let header = Header {
+                version,
+                object_hash: gix_hash::Kind::Sha1,
+                filter: (version == Version::V3).then(|| "tree:0".into()),
+                prerequisites: vec![
+                    Prerequisite {
+                        id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                        comment: "subject".into(),
+                    },
+                    Prerequisite {
+                        id: hex_to_id("4b825dc642cb6eb9a060e54bf8d69288fbee4904"),
+                        comment: "".into(),
+                    },
+                ],
+                refs: vec![Ref {
+                    id: hex_to_id("8d7e9d0bd3bba7b5d7a2bfea1c4e5d7e5c3e7a21"),
+                    name: "refs/tags/v1.0".into(),
+                }],
+            };
+            let mut buf = Vec::new();
+            header.write_to(&mut buf)?;
+            buf.extend_from_slice(b"PACK");
+            let (decoded, pack) = decode(&buf)?;
+            assert_eq!(decoded, header);
+            assert_eq!(pack, b"PACK");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn v3_writes_the_object_format() -> crate::Result {
+        let mut buf = Vec::new();
+        Header {
+            version: Version::V3,
+            object_hash: gix_hash::Kind::Sha1,
+            filter: None,
+            prerequisites: vec![],
+            refs: vec![],
+        }
+        .write_to(&mut buf)?;
+        assert_eq!(buf, b"# v3 git bundle\n@object-format=sha1\n\n");
+        Ok(())
+    }
+}
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
@@ -0,0 +1,187 @@
+//! Access to changed-path bloom filters as written by `git commit-graph write --changed-paths`.
+//!
+//! Each commit may have a filter which records all paths that changed compared to its first parent, along with
+//! all of their leading directories. A filter can only tell for sure that a path did *not* change, which is what
+//! makes it useful to skip commits during history traversals that are limited to a path.
+use std::convert::TryInto;
+
+use bstr::{BStr, ByteSlice};
+
+/// The seed used for the first hash of each path.
+const SEED0: u32 = 0x293a_e76f;
+/// The seed used for the second hash of each path.
+const SEED1: u32 = 0x7e64_6e2c;
+/// The amount of bits in each byte of the filter data.
+const BITS_PER_WORD: u64 = 8;
+
+/// The settings stored in the header of the bloom filter data chunk, which are needed to create compatible [keys][Key].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Settings {
+    /// The version of the hash function.
+    ///
+    /// Version 1 treats path bytes as signed, which is what git did until version 2 was introduced.
+    pub hash_version: u32,
+    /// The amount of hashes computed for each path.
+    pub num_hashes: u32,
+    /// The amount of bits reserved per changed path, informational only.
+    pub bits_per_entry: u32,
+}
+
+impl Settings {
+    pub(crate) const HEADER_LEN: usize = 3 * 4;
+
+    pub(crate) fn from_bytes(header: &[u8]) -> Self {
+        let read = |ofs: usize| u32::from_be_bytes(header[ofs..][..4].try_into().expect("4 bytes"));
+        Settings {
+            hash_version: read(0),
+            num_hashes: read(4),
+            bits_per_entry: read(8),
+        }
+    }
+
+    /// Return `true` if we know how to produce keys that are compatible with filters written with these settings.
+    pub fn is_supported(&self) -> bool {
+        matches!(self.hash_version, 1 | 2) && self.num_hashes > 0
+    }
+}
+
+/// A set of hashes for a single path, to be tested against a [`Filter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    hashes: Vec<u32>,
+}
+
+impl Key {
+    /// Compute the key for `path`, which must be relative to the root of the repository and use slashes as separators,
+    /// without leading or trailing slash.
+    pub fn new(path: &BStr, settings: &Settings) -> Self {
+        let signed = settings.hash_version == 1;
+        let hash0 = murmur3(SEED0, path, signed);
+        let hash1 = murmur3(SEED1, path, signed);
+        Key {
+            hashes: (0..settings.num_hashes)
+                .map(|i| hash0.wrapping_add(i.wrapping_mul(hash1)))
+                .collect(),
+        }
+    }
+
+    /// Compute keys for `path` and each of its leading directories, the way git does when limiting a traversal to
+    /// a path.
+    ///
+    /// A path can only have changed if all of these keys are [contained][Filter::maybe_contains_all()] in a filter.
+    pub fn for_path_and_leading_directories(path: &BStr, settings: &Settings) -> Vec<Self> {
+        let mut keys = vec![Key::new(path, settings)];
+        let mut cursor = path;
+        while let Some(pos) = cursor.rfind_byte(b'/') {
+            cursor = cursor[..pos].as_bstr();
+            keys.push(Key::new(cursor, settings));
+        }
+        keys
+    }
+}
+
+/// The bloom filter of a single commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Filter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Filter<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Filter { data }
+    }
+
+    /// Return `false` if the path represented by `key` definitely did not change in this commit, or `true` if it might have.
+    ///
+    /// Empty filters can't make any statement and always return `true`.
+    pub fn maybe_contains(&self, key: &Key) -> bool {
+        let modulo = self.data.len() as u64 * BITS_PER_WORD;
+        if modulo == 0 {
+            return true;
+        }
+        key.hashes.iter().all(|hash| {
+            let bit = u64::from(*hash) % modulo;
+            let byte = self.data[(bit / BITS_PER_WORD) as usize];
+            byte & (1 << (bit % BITS_PER_WORD)) != 0
+        })
+    }
+
+    /// Return `true` if each of the `keys` might be contained in this filter, typically obtained with
+    /// [`Key::for_path_and_leading_directories()`].
+    pub fn maybe_contains_all<'k>(&self, keys: impl IntoIterator<Item = &'k Key>) -> bool {
+        keys.into_iter().all(|key| self.maybe_contains(key))
+    }
+
+    /// Return the raw data of the filter.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// The 32 bit variant of murmur3, as implemented by git, including its treatment of bytes as signed when `signed` is `true`.
+fn murmur3(mut seed: u32, data: &[u8], signed: bool) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    const R1: u32 = 15;
+    const R2: u32 = 13;
+    const M: u32 = 5;
+    const N: u32 = 0xe654_6b64;
+
+    let widen = |b: u8| if signed { b as i8 as i32 as u32 } else { u32::from(b) };
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = widen(chunk[0]) | widen(chunk[1]) << 8 | widen(chunk[2]) << 16 | widen(chunk[3]) << 24;
+        k = k.wrapping_mul(C1);
+        k = k.rotate_left(R1);
+        k = k.wrapping_mul(C2);
+
+        seed ^= k;
+        seed = seed.rotate_left(R2).wrapping_mul(M).wrapping_add(N);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut k1 = 0u32;
+        for (shift, byte) in tail.iter().enumerate().rev() {
+            k1 ^= widen(*byte) << (shift * 8);
+        }
+        k1 = k1.wrapping_mul(C1);
+        k1 = k1.rotate_left(R1);
+        k1 = k1.wrapping_mul(C2);
+        seed ^= k1;
+    }
+
+    seed ^= data.len() as u32;
+    seed ^= seed >> 16;
+    seed = seed.wrapping_mul(0x85eb_ca6b);
+    seed ^= seed >> 13;
+    seed = seed.wrapping_mul(0xc2b2_ae35);
+    seed ^= seed >> 16;
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::murmur3;
+
+    #[test]
+    fn murmur3_matches_git_test_vectors() {
+        // See `t/helper/test-bloom.c` and `t0095-bloom.sh` in git.
+        assert_eq!(murmur3(0, b"", false), 0x0000_0000);
+        assert_eq!(murmur3(0, b"Hello world!", false), 0x627b_0c2c);
+        assert_eq!(
+            murmur3(0, b"The quick brown fox jumps over the lazy dog", false),
+            0x2e4f_f723
+        );
+    }
+
+    #[test]
+    fn murmur3_signedness_only_matters_for_high_bytes() {
+        assert_eq!(murmur3(0, b"ascii", true), murmur3(0, b"ascii", false));
+        assert_ne!(
+            murmur3(0, b"\x99\xaa\xbb\xcc\xdd", true),
+            murmur3(0, b"\x99\xaa\xbb\xcc\xdd", false)
+        );
+    }
+}
@@ -24,6 +24,46 @@ impl File {
         Commit::new(self, pos)
     }
 
+    /// Returns the settings needed to create [keys][crate::bloom::Key] for the changed-path bloom filters in this file,
+    /// or `None` if this file doesn't contain bloom filters.
+    pub fn bloom_filter_settings(&self) -> Option<crate::bloom::Settings> {
+        self.bloom_filter_index_offset?;
+        self.bloom_filter_data.as_ref().map(|(_, settings)| *settings)
+    }
+
+    /// Returns the changed-path bloom filter for the commit at the given lexigraphical position, or `None` if this file
+    /// doesn't contain bloom filters or if the stored filter is invalid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is out of bounds.
+    pub fn bloom_filter_at(&self, pos: file::Position) -> Option<crate::bloom::Filter<'_>> {
+        assert!(
+            pos.0 < self.num_commits(),
+            "expected lexigraphical position less than {}, got {}",
+            self.num_commits(),
+            pos.0
+        );
+        let index_offset = self.bloom_filter_index_offset?;
+        let (data_range, _) = self.bloom_filter_data.as_ref()?;
+        let read_end = |pos: usize| -> usize {
+            let start = index_offset + pos * 4;
+            u32::from_be_bytes(self.data[start..][..4].try_into().expect("4 bytes")) as usize
+        };
+        let pos: usize = pos
+            .0
+            .try_into()
+            .expect("an architecture able to hold 32 bits of integer");
+        let end = read_end(pos);
+        let start = if pos == 0 { 0 } else { read_end(pos - 1) };
+        if start > end || end > data_range.len() {
+            return None;
+        }
+        Some(crate::bloom::Filter::new(
+            &self.data[data_range.start + start..data_range.start + end],
+        ))
+    }
+
     /// The kind of hash used in this File.
     ///
     /// Note that it is always conforming to the hash used in the owning repository.
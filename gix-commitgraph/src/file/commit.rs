@@ -56,6 +56,13 @@ impl<'a> Commit<'a> {
         }
     }
 
+    /// Returns the changed-path bloom filter of this commit, if the owning file contains bloom filters.
+    ///
+    /// Note that the filter records changes relative to the first parent only.
+    pub fn bloom_filter(&self) -> Option<crate::bloom::Filter<'a>> {
+        self.file.bloom_filter_at(self.pos)
+    }
+
     /// Returns the committer timestamp of this commit.
     ///
     /// The value is the number of seconds since 1970-01-01 00:00:00 UTC.
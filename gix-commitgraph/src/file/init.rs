@@ -7,8 +7,9 @@ use bstr::ByteSlice;
 use memmap2::Mmap;
 
 use crate::file::{
-    ChunkId, File, BASE_GRAPHS_LIST_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
-    EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+    ChunkId, File, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_INDEX_CHUNK_ID,
+    COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, HEADER_LEN,
+    OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
 };
 
 /// The error used in [`File::at()`].
@@ -196,6 +197,39 @@ impl TryFrom<&Path> for File {
 
         let extra_edges_list_range = chunks.usize_offset_by_id(EXTENDED_EDGES_LIST_CHUNK_ID).ok();
 
+        let bloom_filter_index_offset = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_INDEX_CHUNK_ID, |chunk_range| {
+                let chunk_size = chunk_range.len();
+                if chunk_size != commit_data_count as usize * 4 {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_FILTER_INDEX_CHUNK_ID,
+                        msg: format!(
+                            "expected chunk length {}, got {chunk_size}",
+                            commit_data_count as usize * 4
+                        ),
+                    });
+                }
+                Ok(chunk_range.start)
+            })
+            .ok()
+            .transpose()?;
+        let bloom_filter_data = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_DATA_CHUNK_ID, |chunk_range| {
+                if chunk_range.len() < crate::bloom::Settings::HEADER_LEN {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_FILTER_DATA_CHUNK_ID,
+                        msg: format!("chunk length {} is too small to hold the header", chunk_range.len()),
+                    });
+                }
+                let settings = crate::bloom::Settings::from_bytes(&data[chunk_range.start..]);
+                Ok((
+                    chunk_range.start + crate::bloom::Settings::HEADER_LEN..chunk_range.end,
+                    settings,
+                ))
+            })
+            .ok()
+            .transpose()?;
+
         let trailer = &data[chunks.highest_offset() as usize..];
         if trailer.len() != object_hash.len_in_bytes() {
             return Err(Error::Trailer(format!(
@@ -232,6 +266,8 @@ impl TryFrom<&Path> for File {
         Ok(File {
             base_graph_count,
             base_graphs_list_offset,
+            bloom_filter_index_offset,
+            bloom_filter_data,
             commit_data_offset,
             data,
             extra_edges_list_range,
@@ -5,6 +5,19 @@ use crate::{
 
 /// Access
 impl Graph {
+    /// Returns the settings to use when creating [keys][crate::bloom::Key] for the changed-path bloom filters of
+    /// this graph, or `None` if there are no usable bloom filters.
+    ///
+    /// As keys are shared by all files, this is also `None` if files with bloom filters don't agree on their settings.
+    pub fn bloom_filter_settings(&self) -> Option<crate::bloom::Settings> {
+        let mut settings = self.files.iter().filter_map(File::bloom_filter_settings);
+        let first = settings.next()?;
+        settings
+            .all(|settings| settings == first)
+            .then_some(first)
+            .filter(crate::bloom::Settings::is_supported)
+    }
+
     /// Returns the commit at the given position `pos`.
     ///
     /// # Panics
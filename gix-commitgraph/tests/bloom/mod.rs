@@ -0,0 +1,73 @@
+use gix_commitgraph::{bloom::Key, file::File, Graph};
+
+use crate::{inspect_refs, make_readonly_repo};
+
+#[test]
+fn changed_paths_are_found_along_with_their_leading_directories() -> crate::Result {
+    let repo_dir = make_readonly_repo("changed_paths.sh");
+    let refs = inspect_refs(&repo_dir, &["root", "nested", "unicode", "empty"]);
+    let cg = Graph::from_info_dir(repo_dir.join(".git").join("objects").join("info"))?;
+    let settings = cg.bloom_filter_settings().expect("bloom filters were written");
+    assert_eq!(settings.hash_version, 1, "this is what git writes by default");
+    assert_eq!(settings.num_hashes, 7);
+    assert_eq!(settings.bits_per_entry, 10);
+
+    let keys = |path: &str| Key::for_path_and_leading_directories(path.into(), &settings);
+    let filter = |name: &str| {
+        cg.commit_at(refs[name].pos())
+            .bloom_filter()
+            .expect("each commit has a filter")
+    };
+
+    assert_eq!(
+        keys("dir/sub/file").len(),
+        3,
+        "the path itself and two leading directories"
+    );
+    assert!(filter("root").maybe_contains_all(&keys("root.txt")));
+    assert!(filter("nested").maybe_contains_all(&keys("dir/sub/file")));
+    assert!(filter("nested").maybe_contains_all(&keys("dir/sub")));
+    assert!(
+        !filter("nested").maybe_contains_all(&keys("root.txt")),
+        "unchanged files are not contained (this is probabilistic, but deterministic for this fixture)"
+    );
+    assert!(
+        filter("unicode").maybe_contains_all(&keys("été.txt")),
+        "version 1 hashes treat bytes as signed, just like git does"
+    );
+    assert!(!filter("empty").maybe_contains_all(&keys("root.txt")));
+    assert!(!filter("empty").maybe_contains_all(&keys("été.txt")));
+    Ok(())
+}
+
+#[test]
+fn graphs_without_changed_paths_have_no_bloom_filters() -> crate::Result {
+    let repo_dir = make_readonly_repo("single_parent.sh");
+    let refs = inspect_refs(&repo_dir, &["parent", "child"]);
+    let cg = Graph::from_info_dir(repo_dir.join(".git").join("objects").join("info"))?;
+    assert_eq!(cg.bloom_filter_settings(), None);
+    assert!(cg.commit_at(refs["child"].pos()).bloom_filter().is_none());
+    Ok(())
+}
+
+#[test]
+fn bloom_filters_are_unusable_if_files_disagree_on_their_settings() -> crate::Result {
+    let file = |script: &str| File::at(make_readonly_repo(script).join(".git/objects/info/commit-graph"));
+    let cg = Graph::new(vec![file("single_parent.sh")?, file("changed_paths.sh")?])?;
+    assert_eq!(
+        cg.bloom_filter_settings().map(|settings| settings.num_hashes),
+        Some(7),
+        "files without bloom filters don't affect the settings"
+    );
+
+    let cg = Graph::new(vec![
+        file("changed_paths.sh")?,
+        file("changed_paths_with_other_settings.sh")?,
+    ])?;
+    assert_eq!(
+        cg.bloom_filter_settings(),
+        None,
+        "keys for one file wouldn't work with the filters of the other"
+    );
+    Ok(())
+}
@@ -12,6 +12,7 @@ use gix_commitgraph::{graph::Position as GraphPosition, Graph};
 type Result = std::result::Result<(), Box<dyn std::error::Error>>;
 
 mod access;
+mod bloom;
 
 pub fn check_common(cg: &Graph, expected: &HashMap<String, RefInfo, impl BuildHasher>) {
     assert_eq!(
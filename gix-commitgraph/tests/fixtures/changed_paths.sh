@@ -0,0 +1,27 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config core.quotePath false
+
+git checkout -q -b root
+echo root > root.txt
+git add root.txt
+git commit -q -m root
+
+git checkout -q -b nested root
+mkdir -p dir/sub
+echo nested > dir/sub/file
+git add dir
+git commit -q -m nested
+
+git checkout -q -b unicode nested
+echo unicode > "été.txt"
+git add "été.txt"
+git commit -q -m unicode
+
+git checkout -q -b empty unicode
+git commit -q --allow-empty -m empty
+
+git commit-graph write --no-progress --reachable --changed-paths
+git repack -adq
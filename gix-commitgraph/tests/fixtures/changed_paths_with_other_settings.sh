@@ -0,0 +1,10 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+echo root > root.txt
+git add root.txt
+git commit -q -m root
+
+GIT_TEST_BLOOM_SETTINGS_NUM_HASHES=5 git commit-graph write --no-progress --reachable --changed-paths
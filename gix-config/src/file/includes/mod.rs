@@ -38,7 +38,58 @@ impl File<'static> {
 }
 
 pub(crate) fn resolve(config: &mut File<'static>, buf: &mut Vec<u8>, options: init::Options<'_>) -> Result<(), Error> {
-    resolve_includes_recursive(config, 0, buf, options)
+    let mut state = State {
+        unresolved: (options.includes.max_depth != 0 && has_includes(config)).then(|| config.clone()),
+        remote_urls: None,
+        unconditional_remote_url: false,
+    };
+    resolve_includes_recursive(config, 0, buf, options, &mut state)
+}
+
+/// State shared while following the includes of a configuration and all the files it includes.
+struct State {
+    /// The configuration before any of its includes were followed, needed to find all remote URLs.
+    unresolved: Option<File<'static>>,
+    /// All values of `remote.<name>.url` in the configuration and its includes, computed when first needed
+    /// to evaluate a `hasconfig:remote.*.url:` condition.
+    remote_urls: Option<Vec<BString>>,
+    /// If `true`, `hasconfig:remote.*.url:` conditions are always true, which is the case while finding all remote URLs.
+    unconditional_remote_url: bool,
+}
+
+impl State {
+    /// Like `git`, follow all includes of the unresolved configuration, including those with `hasconfig:remote.*.url:`
+    /// conditions, to find all remote URLs without applying any of their values.
+    fn remote_urls(&mut self, buf: &mut Vec<u8>, options: init::Options<'_>) -> Result<&[BString], Error> {
+        if self.remote_urls.is_none() {
+            let mut config = self
+                .unresolved
+                .take()
+                .expect("BUG: conditions are only evaluated if there are includes");
+            let mut state = State {
+                unresolved: None,
+                remote_urls: None,
+                unconditional_remote_url: true,
+            };
+            resolve_includes_recursive(&mut config, 0, buf, options, &mut state)?;
+            self.remote_urls = Some(remote_urls(&config).collect());
+        }
+        Ok(self.remote_urls.as_deref().expect("just set"))
+    }
+}
+
+fn has_includes(config: &File<'static>) -> bool {
+    config.sections_by_name("include").is_some() || config.sections_by_name("includeIf").is_some()
+}
+
+fn remote_urls<'a>(config: &'a File<'static>) -> impl Iterator<Item = BString> + 'a {
+    config
+        .sections_by_name("remote")
+        .into_iter()
+        .flatten()
+        .filter(|section| section.header.subsection_name.is_some())
+        .flat_map(|section| section.body.values("url"))
+        .map(Cow::into_owned)
 }
 
 fn resolve_includes_recursive(
@@ -46,6 +97,7 @@ fn resolve_includes_recursive(
     depth: u8,
     buf: &mut Vec<u8>,
     options: init::Options<'_>,
+    state: &mut State,
 ) -> Result<(), Error> {
     if depth == options.includes.max_depth {
         return if options.includes.err_on_max_depth_exceeded {
@@ -66,28 +118,30 @@ fn resolve_includes_recursive(
         let header = &section.header;
         let header_name = header.name.as_ref();
         if header_name == "include" && header.subsection_name.is_none() {
-            detach_include_paths(&mut section_ids_and_include_paths, section, id)
+            detach_include_paths(&mut section_ids_and_include_paths, section, id, false)
         } else if header_name == "includeIf" {
             if let Some(condition) = &header.subsection_name {
                 let target_config_path = section.meta.path.as_deref();
-                if include_condition_match(condition.as_ref(), target_config_path, options.includes)? {
-                    detach_include_paths(&mut section_ids_and_include_paths, section, id)
+                if include_condition_match(condition.as_ref(), target_config_path, options, state, buf)? {
+                    let forbid_remote_urls = condition.starts_with(HAS_REMOTE_URL_PREFIX.as_bytes());
+                    detach_include_paths(&mut section_ids_and_include_paths, section, id, forbid_remote_urls)
                 }
             }
         }
     }
 
-    append_followed_includes_recursively(section_ids_and_include_paths, target_config, depth, options, buf)
+    append_followed_includes_recursively(section_ids_and_include_paths, target_config, depth, options, buf, state)
 }
 
 fn append_followed_includes_recursively(
-    section_ids_and_include_paths: Vec<(SectionId, crate::Path<'_>)>,
+    section_ids_and_include_paths: Vec<(SectionId, crate::Path<'_>, bool)>,
     target_config: &mut File<'static>,
     depth: u8,
     options: init::Options<'_>,
     buf: &mut Vec<u8>,
+    state: &mut State,
 ) -> Result<(), Error> {
-    for (section_id, config_path) in section_ids_and_include_paths {
+    for (section_id, config_path, forbid_remote_urls) in section_ids_and_include_paths {
         let meta = OwnShared::clone(&target_config.sections[&section_id].meta);
         let target_config_path = meta.path.as_deref();
         let config_path = match resolve_path(config_path, target_config_path, options.includes)? {
@@ -117,7 +171,12 @@ fn append_followed_includes_recursively(
                 init::Error::Interpolate(err) => Error::Interpolate(err),
                 init::Error::Includes(_) => unreachable!("BUG: {:?} not possible due to no-follow options", err),
             })?;
-        resolve_includes_recursive(&mut include_config, depth + 1, buf, options)?;
+        resolve_includes_recursive(&mut include_config, depth + 1, buf, options, state)?;
+        if forbid_remote_urls && remote_urls(&include_config).next().is_some() {
+            return Err(Error::RemoteUrlInHasConfigInclude {
+                path: include_config.meta.path.clone().expect("set as we just read the file"),
+            });
+        }
 
         target_config.append_or_insert(include_config, Some(section_id));
     }
@@ -125,24 +184,44 @@ fn append_followed_includes_recursively(
 }
 
 fn detach_include_paths(
-    include_paths: &mut Vec<(SectionId, crate::Path<'static>)>,
+    include_paths: &mut Vec<(SectionId, crate::Path<'static>, bool)>,
     section: &file::Section<'_>,
     id: SectionId,
+    forbid_remote_urls: bool,
 ) {
     include_paths.extend(
         section
             .body
             .values("path")
             .into_iter()
-            .map(|path| (id, crate::Path::from(Cow::Owned(path.into_owned())))),
+            .map(|path| (id, crate::Path::from(Cow::Owned(path.into_owned())), forbid_remote_urls)),
     )
 }
 
+/// The only supported `hasconfig` condition.
+const HAS_REMOTE_URL_PREFIX: &str = "hasconfig:remote.*.url:";
+
 fn include_condition_match(
     condition: &BStr,
     target_config_path: Option<&Path>,
-    options: Options<'_>,
+    init_options: init::Options<'_>,
+    state: &mut State,
+    buf: &mut Vec<u8>,
 ) -> Result<bool, Error> {
+    let options = init_options.includes;
+    if let Some(pattern) = condition.strip_prefix(HAS_REMOTE_URL_PREFIX.as_bytes()) {
+        if state.unconditional_remote_url {
+            return Ok(true);
+        }
+        return Ok(state.remote_urls(buf, init_options)?.iter().any(|url| {
+            gix_glob::wildmatch(
+                pattern.as_bstr(),
+                url.as_bstr(),
+                gix_glob::wildmatch::Mode::NO_MATCH_SLASH_LITERAL,
+            )
+        }));
+    }
+
     let mut tokens = condition.splitn(2, |b| *b == b':');
     let (prefix, condition) = match (tokens.next(), tokens.next()) {
         (Some(a), Some(b)) => (a, b),
@@ -209,7 +288,10 @@ fn gitdir_matches(
     }
     let git_dir = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(git_dir.ok_or(Error::MissingGitDir)?));
 
-    let mut pattern_path: Cow<'_, _> = {
+    // NOTE: an empty pattern can't be interpolated, but like in git, it turns into `**/**` to match everything.
+    let mut pattern_path: Cow<'_, _> = if condition_path.is_empty() {
+        condition_path.into()
+    } else {
         let path = match check_interpolation_result(
             err_on_interpolation_failure,
             crate::Path::from(Cow::Borrowed(condition_path)).interpolate(context),
@@ -224,16 +306,18 @@ fn gitdir_matches(
         pattern_path = gix_path::to_unix_separators_on_windows(pattern_path);
     }
 
+    // The directory of the including file is matched literally, so glob characters in it have no effect.
+    let mut literal_prefix_len = 0;
     if let Some(relative_pattern_path) = pattern_path.strip_prefix(b"./") {
         if !err_on_missing_config_path && target_config_path.is_none() {
             return Ok(false);
         }
-        let parent_dir = target_config_path
-            .ok_or(Error::MissingConfigPath)?
-            .parent()
-            .expect("config path can never be /");
+        // NOTE: like git, use the directory the including file actually lives in, even if it was reached through a symlink.
+        let config_path = gix_path::realpath(target_config_path.ok_or(Error::MissingConfigPath)?)?;
+        let parent_dir = config_path.parent().expect("config path can never be /");
         let mut joined_path = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(parent_dir)).into_owned();
         joined_path.push(b'/');
+        literal_prefix_len = joined_path.len();
         joined_path.extend_from_slice(relative_pattern_path);
         pattern_path = joined_path.into();
     }
@@ -253,17 +337,25 @@ fn gitdir_matches(
     }
 
     let match_mode = gix_glob::wildmatch::Mode::NO_MATCH_SLASH_LITERAL | wildmatch_mode;
-    let is_match = gix_glob::wildmatch(pattern_path.as_bstr(), git_dir.as_bstr(), match_mode);
-    if is_match {
+    let is_match = |git_dir: &BStr| {
+        if git_dir.len() < literal_prefix_len {
+            return false;
+        }
+        let (pattern_prefix, pattern) = pattern_path.split_at(literal_prefix_len);
+        let (git_dir_prefix, git_dir) = git_dir.split_at(literal_prefix_len);
+        let prefix_matches = if wildmatch_mode.contains(gix_glob::wildmatch::Mode::IGNORE_CASE) {
+            pattern_prefix.eq_ignore_ascii_case(git_dir_prefix)
+        } else {
+            pattern_prefix == git_dir_prefix
+        };
+        prefix_matches && gix_glob::wildmatch(pattern.as_bstr(), git_dir.as_bstr(), match_mode)
+    };
+    if is_match(git_dir.as_bstr()) {
         return Ok(true);
     }
 
     let expanded_git_dir = gix_path::into_bstr(gix_path::realpath(gix_path::from_byte_slice(&git_dir))?);
-    Ok(gix_glob::wildmatch(
-        pattern_path.as_bstr(),
-        expanded_git_dir.as_bstr(),
-        match_mode,
-    ))
+    Ok(is_match(expanded_git_dir.as_bstr()))
 }
 
 fn check_interpolation_result(
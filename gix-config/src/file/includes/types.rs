@@ -18,6 +18,8 @@ pub enum Error {
     MissingGitDir,
     #[error(transparent)]
     Realpath(#[from] gix_path::realpath::Error),
+    #[error("Remote URLs cannot be configured in file {path:?} as it is directly or indirectly included by includeIf.hasconfig:remote.*.url")]
+    RemoteUrlInHasConfigInclude { path: std::path::PathBuf },
 }
 
 /// Options to handle includes, like `include.path` or `includeIf.<condition>.path`,
@@ -60,6 +62,7 @@ impl<'a> Options<'a> {
     }
     /// Provide options to follow includes like git does, provided the required `conditional` and `interpolate` contexts
     /// to support `gitdir` and `onbranch` based `includeIf` directives as well as standard `include.path` resolution.
+    /// `hasconfig:remote.*.url` based `includeIf` directives don't need any context.
     /// Note that the follow-mode is `git`-style, following at most 10 indirections while
     /// producing an error if the depth is exceeded.
     pub fn follow(interpolate: interpolate::Context<'a>, conditional: conditional::Context<'a>) -> Self {
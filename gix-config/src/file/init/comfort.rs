@@ -7,8 +7,8 @@ use crate::{
 ///
 /// ### Limitations
 ///
-/// Note that `includeIf` conditions in global files will cause failure as the required information
-/// to resolve them isn't present without a repository.
+/// Note that `gitdir` and `onbranch` conditions of `includeIf` directives in global files never match as the required
+/// information to resolve them isn't present without a repository, while `hasconfig:remote.*.url` conditions work as usual.
 ///
 /// Also note that relevant information to interpolate paths will be obtained from the environment or other
 /// source on unix.
@@ -23,6 +23,15 @@ impl File<'static> {
     ///
     /// Note that the file might [be empty][File::is_void()] in case no configuration file was found.
     pub fn from_globals() -> Result<File<'static>, init::from_paths::Error> {
+        let home = gix_path::env::home_dir();
+        let options = init::Options {
+            includes: init::includes::Options::follow_without_conditional(home.as_deref()),
+            ..Default::default()
+        };
+        Self::from_globals_with_options(options)
+    }
+
+    fn from_globals_with_options(options: init::Options<'_>) -> Result<File<'static>, init::from_paths::Error> {
         let metas = [source::Kind::System, source::Kind::Global]
             .iter()
             .flat_map(|kind| kind.sources())
@@ -41,11 +50,6 @@ impl File<'static> {
                 .into()
             });
 
-        let home = gix_path::env::home_dir();
-        let options = init::Options {
-            includes: init::includes::Options::follow_without_conditional(home.as_deref()),
-            ..Default::default()
-        };
         File::from_paths_metadata(metas, options).map(Option::unwrap_or_default)
     }
 
@@ -81,7 +85,7 @@ impl File<'static> {
     /// Includes will be resolved within limits as some information like the git installation directory is missing to interpolate
     /// paths with as well as git repository information like the branch name.
     pub fn from_git_dir(dir: impl Into<std::path::PathBuf>) -> Result<File<'static>, from_git_dir::Error> {
-        let (mut local, git_dir) = {
+        let (local, git_dir) = {
             let source = Source::Local;
             let mut path = dir.into();
             path.push(
@@ -123,15 +127,12 @@ impl File<'static> {
             ..Default::default()
         };
 
-        let mut globals = Self::from_globals()?;
-        globals.resolve_includes(options)?;
-        local.resolve_includes(options)?;
-
+        let mut globals = Self::from_globals_with_options(Default::default())?;
         globals.append(local);
-        if let Some(mut worktree) = worktree {
-            worktree.resolve_includes(options)?;
+        if let Some(worktree) = worktree {
             globals.append(worktree);
         }
+        globals.resolve_includes(options)?;
         globals.append(Self::from_environment_overrides()?);
 
         Ok(globals)
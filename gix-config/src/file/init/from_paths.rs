@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 
 use crate::{
-    file::{init, init::Options, Metadata},
+    file::{includes, init, init::Options, Metadata},
     File,
 };
 
@@ -54,6 +54,9 @@ impl File<'static> {
     /// [`Metadata::path`] being an `Option`.
     /// If an input path doesn't exist, the entire operation will abort. See [`from_paths_metadata_buf()`][Self::from_paths_metadata_buf()]
     /// for a more powerful version of this method.
+    ///
+    /// Includes are followed once all files are loaded, which allows `hasconfig:remote.*.url` conditions to see the remote
+    /// URLs of all files.
     pub fn from_paths_metadata(
         path_meta: impl IntoIterator<Item = impl Into<Metadata>>,
         options: Options<'_>,
@@ -118,7 +121,14 @@ impl File<'static> {
             };
             meta.path = Some(path);
 
-            let config = Self::from_bytes_owned(buf, meta, options)?;
+            let config = Self::from_bytes_owned(
+                buf,
+                meta,
+                Options {
+                    includes: includes::Options::no_follow(),
+                    ..options
+                },
+            )?;
             match &mut target {
                 None => {
                     target = Some(config);
@@ -128,6 +138,9 @@ impl File<'static> {
                 }
             }
         }
+        if let Some(target) = &mut target {
+            includes::resolve(target, buf, options).map_err(init::Error::from)?;
+        }
         Ok(target)
     }
 }
@@ -186,8 +186,8 @@ impl<'borrow, 'lookup, 'event> MultiValueMut<'borrow, 'lookup, 'event> {
         section.insert(offset, Event::SectionKey(key.to_owned()));
     }
 
-    /// Removes the value at the given index. Does nothing when called multiple
-    /// times in succession.
+    /// Removes the value at the given index along with the whitespace before it and the newline after it.
+    /// Does nothing when called multiple times in succession.
     ///
     /// # Safety
     ///
@@ -196,43 +196,61 @@ impl<'borrow, 'lookup, 'event> MultiValueMut<'borrow, 'lookup, 'event> {
         let EntryData {
             section_id,
             offset_index,
-        } = &self.indices_and_sizes[index];
-        let (offset, size) = MultiValueMut::index_and_size(&self.offsets, *section_id, *offset_index);
-        if size == 0 {
-            return;
-        }
-        self.section
-            .get_mut(section_id)
-            .expect("known section id")
-            .body
-            .as_mut()
-            .drain(offset..offset + size);
-
-        Self::set_offset(&mut self.offsets, *section_id, *offset_index, 0);
+        } = self.indices_and_sizes[index];
+        Self::delete_entry(self.section, &mut self.offsets, section_id, offset_index);
         self.indices_and_sizes.remove(index);
     }
 
-    /// Removes all values. Does nothing when called multiple times in
-    /// succession.
+    /// Removes all values along with the whitespace before and the newline after them. Does nothing when called multiple
+    /// times in succession.
     pub fn delete_all(&mut self) {
         for EntryData {
             section_id,
             offset_index,
         } in &self.indices_and_sizes
         {
-            let (offset, size) = MultiValueMut::index_and_size(&self.offsets, *section_id, *offset_index);
-            if size == 0 {
-                continue;
+            Self::delete_entry(self.section, &mut self.offsets, *section_id, *offset_index);
+        }
+        self.indices_and_sizes.clear();
+    }
+
+    fn delete_entry(
+        sections: &mut HashMap<SectionId, Section<'event>>,
+        offsets: &mut HashMap<SectionId, Vec<usize>>,
+        section_id: SectionId,
+        offset_index: usize,
+    ) {
+        let (offset, size) = MultiValueMut::index_and_size(offsets, section_id, offset_index);
+        if size == 0 {
+            return;
+        }
+        let events = sections.get_mut(&section_id).expect("known section id").body.as_mut();
+        let mut range = offset..offset + size;
+        // The newline is part of the gap to the next entry, and the whitespace is part of the gap to the previous one.
+        let line_ends_after_value = matches!(events.get(range.end), Some(Event::Newline(_)));
+        let line_starts_with_whitespace = line_ends_after_value
+            && range.start > 0
+            && matches!(events.get(range.start - 1), Some(Event::Whitespace(_)));
+        if line_ends_after_value {
+            range.end += 1;
+            if let Some(gap) = offsets
+                .get_mut(&section_id)
+                .expect("known section id")
+                .get_mut(offset_index + 1)
+            {
+                *gap -= 1;
             }
-            self.section
-                .get_mut(section_id)
+        }
+        if line_starts_with_whitespace {
+            range.start -= 1;
+            *offsets
+                .get_mut(&section_id)
                 .expect("known section id")
-                .body
-                .as_mut()
-                .drain(offset..offset + size);
-            Self::set_offset(&mut self.offsets, *section_id, *offset_index, 0);
+                .get_mut(offset_index - 1)
+                .expect("key is always preceded by a gap") -= 1;
         }
-        self.indices_and_sizes.clear();
+        events.drain(range);
+        Self::set_offset(offsets, section_id, offset_index, 0);
     }
 
     fn index_and_size(
@@ -50,22 +50,22 @@ impl<'a, 'event> SectionMut<'a, 'event> {
     }
 
     fn push_with_comment_inner(&mut self, key: Key<'event>, value: Option<&BStr>, comment: Option<&BStr>) {
-        let body = &mut self.section.body.0;
+        let mut events = Vec::new();
         if let Some(ws) = &self.whitespace.pre_key {
-            body.push(Event::Whitespace(ws.clone()));
+            events.push(Event::Whitespace(ws.clone()));
         }
 
-        body.push(Event::SectionKey(key));
+        events.push(Event::SectionKey(key));
         match value {
             Some(value) => {
-                body.extend(self.whitespace.key_value_separators());
-                body.push(Event::Value(escape_value(value).into()));
+                events.extend(self.whitespace.key_value_separators());
+                events.push(Event::Value(escape_value(value).into()));
             }
-            None => body.push(Event::Value(Cow::Borrowed("".into()))),
+            None => events.push(Event::Value(Cow::Borrowed("".into()))),
         }
         if let Some(comment) = comment {
-            body.push(Event::Whitespace(Cow::Borrowed(" ".into())));
-            body.push(Event::Comment(parse::Comment {
+            events.push(Event::Whitespace(Cow::Borrowed(" ".into())));
+            events.push(Event::Comment(parse::Comment {
                 tag: b'#',
                 text: Cow::Owned({
                     let mut c = Vec::with_capacity(comment.len());
@@ -79,8 +79,26 @@ impl<'a, 'event> SectionMut<'a, 'event> {
             }));
         }
         if self.implicit_newline {
-            body.push(Event::Newline(BString::from(self.newline.to_vec()).into()));
+            events.push(Event::Newline(BString::from(self.newline.to_vec()).into()));
         }
+
+        let pos = self.insertion_point();
+        self.section.body.0.insert_many(pos, events);
+    }
+
+    /// Return the position right after the line of the last value, or the section header if there is no value,
+    /// so that comments and empty lines at the end of the section stay in front of the next section.
+    /// Return the end of the section if there is no such line.
+    fn insertion_point(&self) -> usize {
+        let body = &self.section.body.0;
+        let line_start = body
+            .iter()
+            .rposition(|e| matches!(e, Event::Value(_) | Event::ValueDone(_)))
+            .unwrap_or(0);
+        body[line_start..]
+            .iter()
+            .position(|e| matches!(e, Event::Newline(_)))
+            .map_or(body.len(), |newline| line_start + newline + 1)
     }
 
     /// Removes all events until a key value pair is removed. This will also
@@ -65,6 +65,16 @@ fn from_git_dir() -> crate::Result {
         "from-user.config",
         "per-user configuration"
     );
+    assert_eq!(
+        config.strings("a", None, "user-include").expect("present"),
+        [crate::file::cow_str("from-d.config")],
+        "includes of the user configuration are resolved once"
+    );
+    assert_eq!(
+        config.string("a", None, "corp").expect("present").as_ref(),
+        "from-corp.config",
+        "user configuration can include files depending on remote URLs of the repository"
+    );
     assert_eq!(
         config.string("env", None, "override").expect("present").as_ref(),
         "from-c.config",
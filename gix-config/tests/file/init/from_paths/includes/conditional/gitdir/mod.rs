@@ -4,7 +4,7 @@ use gix_testtools::Env;
 use serial_test::serial;
 use util::{assert_section_value, Condition, GitEnv};
 
-use crate::file::init::from_paths::escape_backslashes;
+use crate::file::init::from_paths::{escape_backslashes, includes::conditional::create_symlink};
 
 #[test]
 fn relative_path_with_trailing_slash_matches_like_star_star() -> crate::Result {
@@ -244,6 +244,58 @@ fn dot_path_matching_symlink_with_icase() -> crate::Result {
     )
 }
 
+#[test]
+#[cfg(not(windows))]
+fn dot_slash_path_is_relative_to_the_real_location_of_the_including_config_file() -> crate::Result {
+    let env = GitEnv::repo_name("dotfiles/worktree")?;
+    create_symlink(
+        env.home_dir().join(".gitconfig"),
+        env.home_dir().join("dotfiles").join("gitconfig"),
+    );
+    assert_section_value(
+        Condition::new("gitdir:./worktree/").set_user_config_instead_of_repo_config(),
+        env,
+    )
+}
+
+#[test]
+#[cfg(not(windows))]
+fn dot_slash_path_matches_the_directory_of_the_including_config_file_literally() -> crate::Result {
+    let env = GitEnv::repo_name("dot[files]/worktree")?;
+    create_symlink(
+        env.home_dir().join(".gitconfig"),
+        env.home_dir().join("dot[files]").join("gitconfig"),
+    );
+    assert_section_value(
+        Condition::new("gitdir:./worktree/").set_user_config_instead_of_repo_config(),
+        env,
+    )
+}
+
+#[test]
+fn empty_pattern_matches_everything() -> crate::Result {
+    assert_section_value(Condition::new("gitdir:"), GitEnv::repo_name("worktree")?)?;
+    assert_section_value(Condition::new("gitdir/i:"), GitEnv::repo_name("worktree")?)
+}
+
+#[test]
+fn case_insensitive_tilde_and_dot_slash_paths() -> crate::Result {
+    assert_section_value(
+        Condition::new("gitdir/i:~/WORKTREE/").set_user_config_instead_of_repo_config(),
+        GitEnv::repo_name("worktree")?,
+    )?;
+    assert_section_value(
+        Condition::new("gitdir/i:./WORKTREE/").set_user_config_instead_of_repo_config(),
+        GitEnv::repo_name("worktree")?,
+    )?;
+    assert_section_value(
+        Condition::new("gitdir:./WORKTREE/")
+            .set_user_config_instead_of_repo_config()
+            .expect_no_value(),
+        GitEnv::repo_name("worktree")?,
+    )
+}
+
 fn original_value_on_windows(c: Condition) -> Condition {
     if cfg!(windows) {
         c.expect_original_value()
@@ -0,0 +1,209 @@
+use std::{fs, path::Path};
+
+use bstr::{BString, ByteSlice};
+use gix_config::file::{includes, init};
+use tempfile::tempdir;
+
+use crate::file::{cow_str, init::from_paths::includes::conditional::git_init};
+
+#[test]
+fn remote_urls_of_all_files_are_considered() -> crate::Result {
+    assert_include(
+        r#"[includeIf "hasconfig:remote.*.url:https://corp.example/**"]
+path = include.config"#,
+        r#"[remote "origin"]
+url = https://corp.example/org/repo"#,
+        Some("from-include"),
+    )?;
+    assert_include(
+        r#"[remote "origin"]
+url = https://corp.example/org/repo"#,
+        r#"[includeIf "hasconfig:remote.*.url:https://corp.example/**"]
+path = include.config"#,
+        Some("from-include"),
+    )
+}
+
+#[test]
+fn remote_urls_in_included_files_are_considered() -> crate::Result {
+    let dir = repo_dir()?;
+    fs::write(
+        dir.path().join("remote.config"),
+        "[remote \"origin\"]\nurl = https://corp.example/org/repo",
+    )?;
+    assert_include_in(
+        dir,
+        r#"[includeIf "hasconfig:remote.*.url:https://corp.example/**"]
+path = include.config
+[include]
+path = remote.config"#,
+        "",
+        Some("from-include"),
+    )
+}
+
+#[test]
+fn patterns_are_matched_like_paths() -> crate::Result {
+    for (pattern, expected) in [
+        ("https://corp.example/*", None),
+        ("https://corp.example/*/repo", Some("from-include")),
+        ("https://corp.example/**", Some("from-include")),
+        ("**/repo", Some("from-include")),
+        ("https://corp.example/org/", None),
+        ("https://CORP.example/**", None),
+    ] {
+        assert_include(
+            &format!("[includeIf \"hasconfig:remote.*.url:{pattern}\"]\npath = include.config"),
+            "[remote \"origin\"]\nurl = https://corp.example/org/repo",
+            expected,
+        )?;
+    }
+    Ok(())
+}
+
+#[test]
+fn only_remote_urls_are_supported() -> crate::Result {
+    for condition in [
+        "hasconfig:remote.*.pushurl:https://corp.example/**",
+        "hasconfig:remote.origin.url:https://corp.example/**",
+    ] {
+        assert_include(
+            &format!("[includeIf \"{condition}\"]\npath = include.config"),
+            "[remote \"origin\"]\nurl = https://corp.example/org/repo\npushurl = https://corp.example/org/repo",
+            None,
+        )?;
+    }
+    assert_include(
+        "[includeIf \"hasconfig:remote.*.url:https://corp.example/**\"]\npath = include.config",
+        "[remote]\nurl = https://corp.example/org/repo",
+        None,
+    )
+}
+
+#[test]
+fn remote_urls_in_files_included_by_hasconfig_are_an_error() -> crate::Result {
+    let dir = repo_dir()?;
+    fs::write(
+        dir.path().join("include.config"),
+        "[user]\nname = from-include\n[remote \"other\"]\nurl = https://other.example/repo",
+    )?;
+    fs::write(
+        dir.path().join(".gitconfig"),
+        "[includeIf \"hasconfig:remote.*.url:https://corp.example/**\"]\npath = include.config",
+    )?;
+    fs::write(
+        dir.path().join("config"),
+        "[remote \"origin\"]\nurl = https://corp.example/org/repo",
+    )?;
+
+    let err = load(dir.path()).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            gix_config::file::init::from_paths::Error::Init(gix_config::file::init::Error::Includes(
+                includes::Error::RemoteUrlInHasConfigInclude { .. }
+            ))
+        ),
+        "{err:?}"
+    );
+    assert!(!git_config_user_name(dir.path())?.status.success(), "git fails as well");
+    Ok(())
+}
+
+#[test]
+fn no_repository_is_needed() -> crate::Result {
+    let dir = tempdir()?;
+    fs::write(dir.path().join("include.config"), "[user]\nname = from-include")?;
+    let global = dir.path().join(".gitconfig");
+    fs::write(
+        &global,
+        "[includeIf \"hasconfig:remote.*.url:https://corp.example/**\"]\npath = include.config\n[remote \"origin\"]\nurl = https://corp.example/org/repo",
+    )?;
+    let config = gix_config::File::from_paths_metadata(
+        Some(gix_config::file::Metadata::try_from_path(
+            global,
+            gix_config::Source::User,
+        )?),
+        init::Options {
+            includes: includes::Options::follow_without_conditional(None),
+            ..Default::default()
+        },
+    )?
+    .expect("non-empty");
+    assert_eq!(config.string("user", None, "name"), Some(cow_str("from-include")));
+    Ok(())
+}
+
+/// Write `global` as user configuration and `local` as configuration of a bare repository, to see if the value of
+/// the included configuration is `expected`.
+fn assert_include(global: &str, local: &str, expected: Option<&str>) -> crate::Result {
+    assert_include_in(repo_dir()?, global, local, expected)
+}
+
+fn repo_dir() -> crate::Result<tempfile::TempDir> {
+    let dir = tempdir()?;
+    git_init(dir.path(), true)?;
+    Ok(dir)
+}
+
+fn assert_include_in(dir: tempfile::TempDir, global: &str, local: &str, expected: Option<&str>) -> crate::Result {
+    fs::write(dir.path().join("include.config"), "[user]\nname = from-include")?;
+    fs::write(dir.path().join(".gitconfig"), global)?;
+    fs::write(dir.path().join("config"), local)?;
+
+    let config = load(dir.path())?;
+    assert_eq!(
+        config.string("user", None, "name"),
+        expected.map(cow_str),
+        "global: {global:?}, local: {local:?}"
+    );
+
+    let output = git_config_user_name(dir.path())?;
+    assert_eq!(
+        output.status.success(),
+        expected.is_some(),
+        "{output:?}, {:?} for debugging",
+        dir.into_path()
+    );
+    let git_output: BString = output.stdout.trim_end().into();
+    assert_eq!(
+        git_output,
+        expected.unwrap_or_default(),
+        "git disagrees with gix-config, global: {global:?}, local: {local:?}"
+    );
+    Ok(())
+}
+
+fn load(dir: &Path) -> Result<gix_config::File<'static>, gix_config::file::init::from_paths::Error> {
+    gix_config::File::from_paths_metadata(
+        [
+            gix_config::file::Metadata::try_from_path(dir.join(".gitconfig"), gix_config::Source::User)
+                .expect("file exists"),
+            gix_config::file::Metadata::try_from_path(dir.join("config"), gix_config::Source::Local)
+                .expect("file exists"),
+        ],
+        init::Options {
+            includes: includes::Options::follow(
+                Default::default(),
+                includes::conditional::Context {
+                    git_dir: Some(dir),
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        },
+    )
+    .map(|config| config.expect("non-empty"))
+}
+
+fn git_config_user_name(dir: &Path) -> std::io::Result<std::process::Output> {
+    std::process::Command::new("git")
+        .args(["config", "--get", "user.name"])
+        .env("GIT_DIR", dir)
+        .env("HOME", dir)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env_remove("GIT_CONFIG_COUNT")
+        .env_remove("XDG_CONFIG_HOME")
+        .current_dir(dir)
+        .output()
+}
@@ -32,6 +32,19 @@ fn literal_branch_names_match() -> Result {
     Ok(())
 }
 
+#[test]
+fn empty_patterns_never_match() -> Result {
+    assert_section_value(
+        Options {
+            condition: "",
+            branch_name: "refs/heads/main",
+            expect: Value::Base,
+        },
+        GitEnv::new()?,
+    )?;
+    Ok(())
+}
+
 #[test]
 fn full_ref_names_do_not_match() -> Result {
     assert_section_value(
@@ -129,13 +129,14 @@ mod delete {
             values.delete(0);
             assert_eq!(
                 config.to_string(),
-                "[core]\n    \n    [core]\n        a =d\n        a= f\n",
+                "[core]\n    [core]\n        a =d\n        a= f\n",
+                "the whitespace before and the newline after the value are removed as well"
             );
         }
 
         let mut values = config.raw_values_mut("core", None, "a")?;
         values.delete(1);
-        assert_eq!(config.to_string(), "[core]\n    \n    [core]\n        a =d\n        ");
+        assert_eq!(config.to_string(), "[core]\n    [core]\n        a =d\n        ");
         Ok(())
     }
 
@@ -146,7 +147,7 @@ mod delete {
         values.delete_all();
         values.delete_all();
         assert!(values.get().is_err());
-        assert_eq!(config.to_string(), "[core]\n    \n    [core]\n        \n        ");
+        assert_eq!(config.to_string(), "[core]\n    [core]\n        ");
         Ok(())
     }
 }
@@ -180,6 +180,26 @@ mod push {
         Ok(())
     }
 
+    #[test]
+    fn values_are_added_after_the_last_value_and_before_trailing_comments() -> crate::Result {
+        for (input, expected) in [
+            (
+                "[a]\n\tb = c\n# about the next section\n\n[d]\n",
+                "[a]\n\tb = c\n\tk = v\n# about the next section\n\n[d]\n",
+            ),
+            (
+                "[a] # header comment\n; trailing comment\n",
+                "[a] # header comment\n\tk = v\n; trailing comment\n",
+            ),
+            ("[a]\n\tb = c ; comment\n", "[a]\n\tb = c ; comment\n\tk = v\n"),
+        ] {
+            let mut config: gix_config::File = input.parse()?;
+            config.section_mut("a", None)?.push("k".try_into()?, Some("v".into()));
+            assert_eq!(config.to_bstring(), expected, "{input:?}");
+        }
+        Ok(())
+    }
+
     #[test]
     fn values_are_escaped() {
         for (value, expected) in [
@@ -9,6 +9,9 @@ cat <<EOF >>.git/config
 
 [include]
   path = ../a.config
+
+[remote "origin"]
+  url = https://corp.example/org/repo
 EOF
 
 
@@ -28,6 +31,20 @@ EOF
 cat <<EOF >>.gitconfig
 [a]
   user = from-user.config
+[include]
+  path = ./d.config
+[includeIf "hasconfig:remote.*.url:https://corp.example/**"]
+  path = ./corp.config
+EOF
+
+cat <<EOF >>d.config
+[a]
+  user-include = from-d.config
+EOF
+
+cat <<EOF >>corp.config
+[a]
+  corp = from-corp.config
 EOF
 
 cat <<EOF >>b.config
@@ -0,0 +1,215 @@
+//! The daemon of the credential cache, keeping credentials in memory and handing them out to [clients][super::invoke()]
+//! connecting through its socket.
+//!
+//! Credentials are forgotten once their timeout expires, and the daemon exits once it has no credentials left, or when
+//! asked to exit by a client.
+use std::{
+    io::{self, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+use bstr::ByteSlice;
+
+use crate::protocol::Context;
+
+/// The time to wait for a client to store credentials after starting up, before exiting for having nothing to do.
+const INITIAL_GRACE_PERIOD: Duration = Duration::from_secs(30);
+/// The time a client has to send its request or receive the response, so a stuck client can't keep others from being served.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+struct Entry {
+    credential: Context,
+    /// The time at which the credential is forgotten, or `None` if the timeout is too large to represent it.
+    expires_at: Option<Instant>,
+}
+
+#[derive(Default)]
+struct State {
+    entries: Vec<Entry>,
+    /// Set if the reaper thread woke up the accept loop to let it exit for lack of entries.
+    idle: bool,
+    /// Set when the daemon is shutting down.
+    stopped: bool,
+}
+
+impl State {
+    fn forget_expired(&mut self) {
+        let now = Instant::now();
+        self.entries
+            .retain(|entry| entry.expires_at.map_or(true, |expires_at| expires_at > now));
+    }
+}
+
+type Shared = Arc<(Mutex<State>, Condvar)>;
+
+/// Listen on `socket` and serve credentials until there are none left or a client asks to exit, writing `ok` to `ready`
+/// as soon as clients can connect.
+///
+/// `ready` is standard output when running as daemon started by a [client][super::invoke()], which waits for it to
+/// signal the daemon is ready. As `git` does, the directory containing `socket` is created with permissions only for
+/// the current user, and it's an error if it is accessible to others.
+pub fn serve(socket: &Path, mut ready: impl Write) -> io::Result<()> {
+    init_socket_directory(socket)?;
+    match std::fs::remove_file(socket) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    let listener = UnixListener::bind(socket)?;
+    ready.write_all(b"ok\n")?;
+    ready.flush()?;
+    drop(ready);
+
+    let state: Shared = Default::default();
+    let res = std::thread::scope(|scope| {
+        scope.spawn(|| reap(&state, socket));
+        let res = accept(&listener, &state);
+        state.0.lock().expect("not poisoned").stopped = true;
+        state.1.notify_all();
+        res
+    });
+    std::fs::remove_file(socket).ok();
+    res
+}
+
+/// Serve credentials like [`serve()`] and return the result if the current program was started as daemon by a
+/// [client][super::invoke()] that uses the default [daemon program][super::Options::daemon], or return `None` otherwise.
+///
+/// Programs that use the credential cache this way should call it first thing in their `main()` function, as they are run
+/// again with [`DAEMON_ARG`][super::DAEMON_ARG] and the path to the socket as arguments.
+pub fn serve_if_requested() -> Option<io::Result<()>> {
+    let mut args = std::env::args_os().skip(1);
+    match (args.next(), args.next(), args.next()) {
+        (Some(arg), Some(socket), None) if arg == super::DAEMON_ARG => Some(serve(Path::new(&socket), io::stdout())),
+        _ => None,
+    }
+}
+
+fn accept(listener: &UnixListener, (state, wakeup): &(Mutex<State>, Condvar)) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let exit = handle(stream, state).unwrap_or_default();
+        let mut state = state.lock().expect("not poisoned");
+        state.forget_expired();
+        if exit || (state.idle && state.entries.is_empty()) {
+            break;
+        }
+        state.idle = false;
+        wakeup.notify_all();
+    }
+    Ok(())
+}
+
+/// Handle the request of a client on `stream`, and return `true` if the daemon should exit.
+fn handle(mut stream: UnixStream, state: &Mutex<State>) -> io::Result<bool> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut request = Vec::new();
+    stream.read_to_end(&mut request)?;
+    let mut lines = request.splitn(3, |b| *b == b'\n');
+    let action = lines.next().and_then(|line| line.strip_prefix(b"action="));
+    let timeout = lines
+        .next()
+        .and_then(|line| line.strip_prefix(b"timeout="))
+        .and_then(|timeout| timeout.to_str().ok()?.parse::<u64>().ok());
+    let (action, timeout) = match action.zip(timeout) {
+        Some(request) => request,
+        None => return Ok(false),
+    };
+    let mut query = Context::from_bytes(lines.next().unwrap_or_default())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    if query.url.is_some() && query.protocol.is_none() {
+        query.destructure_url_in_place(false).ok();
+    }
+
+    let mut state = state.lock().expect("not poisoned");
+    state.forget_expired();
+    match action {
+        b"get" => {
+            if let Some(entry) = state.entries.iter().find(|entry| query.matches(&entry.credential)) {
+                Context {
+                    username: entry.credential.username.clone(),
+                    password: entry.credential.password.clone(),
+                    ..Default::default()
+                }
+                .write_to(&mut stream)?;
+            }
+        }
+        b"store" if query.username.is_some() && query.password.is_some() => {
+            state.entries.retain(|entry| !query.matches(&entry.credential));
+            query.url = None;
+            state.entries.push(Entry {
+                credential: query,
+                expires_at: Instant::now().checked_add(Duration::from_secs(timeout)),
+            });
+        }
+        b"erase" => state.entries.retain(|entry| !query.matches(&entry.credential)),
+        b"exit" => return Ok(true),
+        _ => {}
+    }
+    Ok(false)
+}
+
+/// Forget credentials as they expire, and wake up the accept loop to exit once there are none left.
+fn reap(shared: &Shared, socket: &Path) {
+    let (state, wakeup) = &**shared;
+    let exit_when_idle_after = Instant::now() + INITIAL_GRACE_PERIOD;
+    let mut state = state.lock().expect("not poisoned");
+    loop {
+        if state.stopped {
+            return;
+        }
+        if state.idle {
+            state = wakeup.wait(state).expect("not poisoned");
+            continue;
+        }
+        state.forget_expired();
+        let now = Instant::now();
+        let next_wakeup = state
+            .entries
+            .iter()
+            .filter_map(|entry| entry.expires_at)
+            .min()
+            .or_else(|| (now < exit_when_idle_after && state.entries.is_empty()).then_some(exit_when_idle_after));
+        match next_wakeup {
+            Some(at) => state = wakeup.wait_timeout(state, at - now).expect("not poisoned").0,
+            // Credentials that never expire keep us running until we are asked to exit.
+            None if !state.entries.is_empty() => state = wakeup.wait(state).expect("not poisoned"),
+            None => {
+                state.idle = true;
+                drop(state);
+                // An empty request is ignored, and just gives the accept loop a chance to see that it's idle.
+                UnixStream::connect(socket).ok();
+                state = shared.0.lock().expect("not poisoned");
+            }
+        }
+    }
+}
+
+fn init_socket_directory(socket: &Path) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    let dir = match socket.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => dir,
+        None => return Ok(()),
+    };
+    match dir.metadata() {
+        Ok(md) => {
+            if md.permissions().mode() & 0o077 != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!(
+                        "The permissions on the credential cache socket directory \"{}\" are too loose, use `chmod 0700` to allow access only to the current user",
+                        dir.display()
+                    ),
+                ));
+            }
+            Ok(())
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            std::fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)
+        }
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,179 @@
+//! A credential helper keeping credentials in the memory of a daemon for a limited time, the equivalent of `git credential-cache`.
+//!
+//! The [daemon] listens on a Unix domain socket and speaks the same protocol as `git credential-cache--daemon`, so either
+//! implementation of the client can talk to either implementation of the daemon.
+//!
+//! The daemon is started when credentials are stored the first time. By default, this is done by running the current executable
+//! with [`DAEMON_ARG`] and the path to the socket as arguments, which is expected to call [`daemon::serve()`] in response, for
+//! instance with [`daemon::serve_if_requested()`]. Use [`Options::daemon`] to run a different program instead, like `git` with
+//! [`DAEMON_ARG`] to use the daemon of `git`.
+use std::{
+    ffi::OsString,
+    io::{self, Read, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use crate::{program::main::Action, protocol::Context};
+
+/// The argument passed to the current executable to [run the daemon][daemon::serve()], followed by the path to its socket.
+pub const DAEMON_ARG: &str = "credential-cache--daemon";
+
+/// The options of the credential cache, as configured with `credential.helper=cache [--timeout=<seconds>] [--socket=<path>]`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Options {
+    /// The path to the socket of the daemon, or `None` to use `~/.git-credential-cache/socket` if its directory exists,
+    /// or `$XDG_CACHE_HOME/git/credential/socket` (or `~/.cache/git/credential/socket`) otherwise.
+    pub socket: Option<PathBuf>,
+    /// The amount of time for which credentials are kept, 900 seconds by default.
+    pub timeout: Duration,
+    /// The program and its arguments to start the daemon with, to which the path to the socket is appended,
+    /// or `None` to start the current executable with [`DAEMON_ARG`].
+    pub daemon: Option<Vec<OsString>>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            socket: None,
+            timeout: Duration::from_secs(900),
+            daemon: None,
+        }
+    }
+}
+
+impl Options {
+    /// Return the path to the socket of the daemon, or fail if there is no home directory to put it into.
+    pub fn socket_path(&self) -> io::Result<PathBuf> {
+        if let Some(socket) = &self.socket {
+            return Ok(socket.clone());
+        }
+        let home = gix_path::env::home_dir();
+        home.as_ref()
+            .map(|home| home.join(".git-credential-cache"))
+            .filter(|dir| dir.is_dir())
+            .or_else(|| {
+                std::env::var_os("XDG_CACHE_HOME")
+                    .map(|cache| PathBuf::from(cache).join("git"))
+                    .or_else(|| home.map(|home| home.join(".cache").join("git")))
+                    .map(|git| git.join("credential"))
+            })
+            .map(|dir| dir.join("socket"))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "Could not determine the home directory to place the credential cache socket into",
+                )
+            })
+    }
+}
+
+/// Perform `action` with `ctx` by asking the daemon of the credential cache configured with `options`, with the same semantics
+/// as [credential helper programs][crate::program::main()] and `git credential-cache`.
+///
+/// If the daemon isn't running, nothing is returned when getting credentials, and it is started to store them.
+pub fn invoke(options: &Options, action: Action, ctx: Context) -> io::Result<Option<Context>> {
+    let socket = options.socket_path()?;
+    let mut request = format!("action={}\ntimeout={}\n", action.as_str(), options.timeout.as_secs()).into_bytes();
+    ctx.write_to(&mut request)?;
+    let response = match send(&socket, &request) {
+        Ok(response) => response,
+        Err(err) if is_not_running(&err) => match action {
+            Action::Store => {
+                spawn_daemon(options, &socket)?;
+                send(&socket, &request)?
+            }
+            Action::Get | Action::Erase => return Ok(None),
+        },
+        Err(err) => return Err(err),
+    };
+    match action {
+        Action::Get if !response.is_empty() => Context::from_bytes(&response)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        _ => Ok(None),
+    }
+}
+
+/// Ask the daemon listening on the socket configured in `options` to exit, forgetting all credentials, and return `true`
+/// if it was running.
+pub fn exit(options: &Options) -> io::Result<bool> {
+    match send(&options.socket_path()?, b"action=exit\ntimeout=0\n") {
+        Ok(_) => Ok(true),
+        Err(err) if is_not_running(&err) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Send `request` to the daemon at `socket` and return its response.
+fn send(socket: &Path, request: &[u8]) -> io::Result<Vec<u8>> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.write_all(request)?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+    Ok(response)
+}
+
+fn is_not_running(err: &io::Error) -> bool {
+    matches!(err.kind(), io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused)
+}
+
+/// Start the daemon and wait for it to signal that it listens on `socket`.
+fn spawn_daemon(options: &Options, socket: &Path) -> io::Result<()> {
+    let mut cmd = match &options.daemon {
+        Some(program_and_args) => {
+            let (program, args) = program_and_args.split_first().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "The program to start the daemon with is empty",
+                )
+            })?;
+            let mut cmd = Command::new(program);
+            cmd.args(args);
+            cmd
+        }
+        None => {
+            let mut cmd = Command::new(std::env::current_exe()?);
+            cmd.arg(DAEMON_ARG);
+            cmd
+        }
+    };
+    {
+        use std::os::unix::process::CommandExt;
+        // Don't let signals sent to our process group, like on Ctrl+C, end the daemon.
+        cmd.process_group(0);
+    }
+    let mut child = cmd
+        .arg(socket)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let mut stdout = child.stdout.take().expect("configured");
+    let mut buf = Vec::new();
+    let mut chunk = [0; 128];
+    while buf.len() < 3 {
+        match stdout.read(&mut chunk)? {
+            0 => break,
+            read => buf.extend_from_slice(&chunk[..read]),
+        }
+    }
+    if buf != b"ok\n" {
+        child.kill().ok();
+        child.wait().ok();
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!(
+                "The credential cache daemon did not start: {:?}",
+                String::from_utf8_lossy(&buf)
+            ),
+        ));
+    }
+    Ok(())
+}
+
+pub mod daemon;
@@ -1,3 +1,5 @@
+use bstr::ByteSlice;
+
 use crate::{helper, helper::Cascade, protocol, protocol::Context, Program};
 
 impl Default for Cascade {
@@ -7,6 +9,8 @@ impl Default for Cascade {
             stderr: true,
             use_http_path: false,
             query_user_only: false,
+            sanitize_prompt: true,
+            protect_protocol: true,
         }
     }
 }
@@ -59,6 +63,20 @@ impl Cascade {
         self.query_user_only = toggle;
         self
     }
+
+    /// If `toggle` is true, the default, control characters and characters that could mislead users are percent-encoded
+    /// in the URLs shown in prompts, as controlled by `credential.sanitizePrompt` in `git`.
+    pub fn sanitize_prompt(mut self, toggle: bool) -> Self {
+        self.sanitize_prompt = toggle;
+        self
+    }
+
+    /// If `toggle` is true, the default, it's an error if the credential information passed to helpers contains carriage returns,
+    /// as controlled by `credential.protectProtocol` in `git`.
+    pub fn protect_protocol(mut self, toggle: bool) -> Self {
+        self.protect_protocol = toggle;
+        self
+    }
 }
 
 /// Finalize
@@ -70,6 +88,23 @@ impl Cascade {
     /// When _storing_ or _erasing_ all programs are instructed in order.
     #[allow(clippy::result_large_err)]
     pub fn invoke(&mut self, mut action: helper::Action, mut prompt: gix_prompt::Options<'_>) -> protocol::Result {
+        if self.protect_protocol {
+            let payload = match &action {
+                helper::Action::Get(ctx) => ctx.to_bstring(),
+                helper::Action::Store(payload) | helper::Action::Erase(payload) => payload.clone(),
+            };
+            if let Some(line) = payload.lines().find(|line| line.contains(&b'\r')) {
+                return Err(protocol::Error::CarriageReturn {
+                    key: line
+                        .split(|b| *b == b'=')
+                        .next()
+                        .unwrap_or_default()
+                        .as_bstr()
+                        .to_string(),
+                });
+            }
+        }
+
         let mut url = action
             .context_mut()
             .map(|ctx| {
@@ -126,7 +161,7 @@ impl Cascade {
             if let Some(ctx) = action.context_mut() {
                 ctx.url = url;
                 if ctx.username.is_none() {
-                    let message = ctx.to_prompt("Username");
+                    let message = self.prompt(ctx, "Username");
                     prompt.mode = gix_prompt::Mode::Visible;
                     ctx.username = gix_prompt::ask(&message, &prompt)
                         .map_err(|err| protocol::Error::Prompt {
@@ -136,7 +171,7 @@ impl Cascade {
                         .into();
                 }
                 if ctx.password.is_none() {
-                    let message = ctx.to_prompt("Password");
+                    let message = self.prompt(ctx, "Password");
                     prompt.mode = gix_prompt::Mode::Hidden;
                     ctx.password = gix_prompt::ask(&message, &prompt)
                         .map_err(|err| protocol::Error::Prompt {
@@ -159,3 +194,13 @@ impl Cascade {
         )
     }
 }
+
+impl Cascade {
+    fn prompt(&self, ctx: &Context, field: &str) -> String {
+        if self.sanitize_prompt {
+            ctx.to_sanitized_prompt(field)
+        } else {
+            ctx.to_prompt(field)
+        }
+    }
+}
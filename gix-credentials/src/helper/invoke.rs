@@ -1,6 +1,9 @@
 use std::io::Read;
 
-use crate::helper::{Action, Context, Error, NextAction, Outcome, Result};
+use crate::{
+    helper::{Action, Context, Error, NextAction, Outcome, Result},
+    program::{main, Kind},
+};
 
 impl Action {
     /// Send ourselves to the given `write` which is expected to be credentials-helper compatible
@@ -40,6 +43,12 @@ pub fn invoke(helper: &mut crate::Program, action: &Action) -> Result {
 }
 
 pub(crate) fn raw(helper: &mut crate::Program, action: &Action) -> std::result::Result<Option<Vec<u8>>, Error> {
+    match &helper.kind {
+        Kind::Store(options) => return in_process(action, |action, ctx| crate::store::invoke(options, action, ctx)),
+        #[cfg(unix)]
+        Kind::Cache(options) => return in_process(action, |action, ctx| crate::cache::invoke(options, action, ctx)),
+        _ => {}
+    }
     let (stdin, stdout) = helper.start(action)?;
     if let (Action::Get(_), None) = (&action, &stdout) {
         panic!("BUG: `Helper` impls must return an output handle to read output from if Action::Get is provided")
@@ -65,3 +74,27 @@ pub(crate) fn raw(helper: &mut crate::Program, action: &Action) -> std::result::
         Some(stdout) => Ok(Some(stdout)),
     }
 }
+
+/// Run a helper implemented by this crate with `action`, producing the same output as the equivalent helper program would.
+fn in_process(
+    action: &Action,
+    helper: impl FnOnce(main::Action, Context) -> std::io::Result<Option<Context>>,
+) -> std::result::Result<Option<Vec<u8>>, Error> {
+    let (kind, mut ctx) = match action {
+        Action::Get(ctx) => (main::Action::Get, ctx.clone()),
+        Action::Store(payload) => (main::Action::Store, Context::from_bytes(payload)?),
+        Action::Erase(payload) => (main::Action::Erase, Context::from_bytes(payload)?),
+    };
+    if ctx.url.is_some() && ctx.protocol.is_none() {
+        ctx.destructure_url_in_place(false)
+            .map_err(|err| Error::CredentialsHelperFailed {
+                source: std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string()),
+            })?;
+    }
+    ctx.url = None;
+    let outcome = helper(kind, ctx).map_err(|err| Error::CredentialsHelperFailed { source: err })?;
+    Ok(match outcome {
+        Some(ctx) if action.expects_output() => Some(ctx.to_bstring().into()),
+        _ => action.expects_output().then(Vec::new),
+    })
+}
@@ -18,6 +18,12 @@ pub struct Cascade {
     /// If true, default false, when getting credentials, we will set a bogus password to only obtain the user name.
     /// Storage and cancellation work the same, but without a password set.
     pub query_user_only: bool,
+    /// If true, the default, URLs shown in prompts are [sanitized][crate::protocol::Context::to_sanitized_url()]
+    /// to not contain control characters.
+    pub sanitize_prompt: bool,
+    /// If true, the default, helpers are not invoked if the credential information contains carriage returns, which could
+    /// make them misinterpret it.
+    pub protect_protocol: bool,
 }
 
 /// The outcome of the credentials helper [invocation][crate::helper::invoke()].
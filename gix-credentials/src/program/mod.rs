@@ -1,6 +1,6 @@
 use std::process::{Command, Stdio};
 
-use bstr::{BString, ByteSlice, ByteVec};
+use bstr::{BStr, BString, ByteSlice, ByteVec};
 
 use crate::{helper, Program};
 
@@ -23,6 +23,11 @@ pub enum Kind {
     },
     /// A script to execute with `sh`.
     ExternalShellScript(BString),
+    /// The [credential store][crate::store] implemented by this crate, which runs in-process and is used in place of `git credential-store`.
+    Store(crate::store::Options),
+    /// The [credential cache][crate::cache] implemented by this crate, which runs in-process and is used in place of `git credential-cache`.
+    #[cfg(unix)]
+    Cache(crate::cache::Options),
 }
 
 /// Initialization
@@ -38,11 +43,16 @@ impl Program {
 
     /// Parse the given input as per the custom helper definition, supporting `!<script>`, `name` and `/absolute/name`, the latter two
     /// also support arguments which are ignored here.
+    ///
+    /// The `store` and `cache` (on unix) helpers are implemented by this crate and don't need `git` to be installed, unless
+    /// they are passed arguments we don't know, which is when `git` will handle them.
     pub fn from_custom_definition(input: impl Into<BString>) -> Self {
         let mut input = input.into();
         let kind = if input.starts_with(b"!") {
             input.remove(0);
             Kind::ExternalShellScript(input)
+        } else if let Some(kind) = builtin_kind(input.as_bstr()) {
+            kind
         } else {
             let path = gix_path::from_bstr(
                 input
@@ -96,6 +106,9 @@ impl Program {
                 .with_shell()
                 .arg(action.as_arg(true))
                 .into(),
+            Kind::Store(_) => unreachable!("BUG: in-process helpers are never started"),
+            #[cfg(unix)]
+            Kind::Cache(_) => unreachable!("BUG: in-process helpers are never started"),
         };
         cmd.stdin(Stdio::piped())
             .stdout(if action.expects_output() {
@@ -126,6 +139,57 @@ impl Program {
     }
 }
 
+/// Parse `input` as invocation of a helper implemented by this crate, or return `None` if it isn't one or
+/// if it has arguments we don't understand.
+fn builtin_kind(input: &BStr) -> Option<Kind> {
+    let mut words = input.to_str().ok()?.split_whitespace().map(|word| {
+        word.strip_prefix('"')
+            .and_then(|word| word.strip_suffix('"'))
+            .or_else(|| word.strip_prefix('\'').and_then(|word| word.strip_suffix('\'')))
+            .unwrap_or(word)
+    });
+    let name = words.next()?;
+    let mut options = Vec::new();
+    while let Some(word) = words.next() {
+        let option = word.strip_prefix("--")?;
+        options.push(match option.split_once('=') {
+            Some((name, value)) => (name, value),
+            None => (option, words.next()?),
+        });
+    }
+    fn path(value: &str) -> std::path::PathBuf {
+        match value.strip_prefix("~/").zip(gix_path::env::home_dir()) {
+            Some((relative, home)) => home.join(relative),
+            None => value.into(),
+        }
+    }
+    Some(match name {
+        "store" => {
+            let mut store = crate::store::Options::default();
+            for (name, value) in options {
+                match name {
+                    "file" => store.file = Some(path(value)),
+                    _ => return None,
+                }
+            }
+            Kind::Store(store)
+        }
+        #[cfg(unix)]
+        "cache" => {
+            let mut cache = crate::cache::Options::default();
+            for (name, value) in options {
+                match name {
+                    "socket" => cache.socket = Some(path(value)),
+                    "timeout" => cache.timeout = std::time::Duration::from_secs(value.parse().ok()?),
+                    _ => return None,
+                }
+            }
+            Kind::Cache(cache)
+        }
+        _ => return None,
+    })
+}
+
 ///
 pub mod main;
 pub use main::function::main;
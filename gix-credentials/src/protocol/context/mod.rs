@@ -34,6 +34,52 @@ mod access {
             }
             buf.into()
         }
+        /// Return `true` if the credential `other`, typically one that was stored previously, can be used for what `self` asks for.
+        ///
+        /// Like in `git`, this is the case if `protocol`, `host`, `path` and `username` of `other` are equal to the ones in `self`,
+        /// unless these aren't set in `self`.
+        pub fn matches(&self, other: &Context) -> bool {
+            fn check<T: PartialEq + ?Sized>(want: Option<&T>, have: Option<&T>) -> bool {
+                want.map_or(true, |want| have == Some(want))
+            }
+            check(self.protocol.as_deref(), other.protocol.as_deref())
+                && check(self.host.as_deref(), other.host.as_deref())
+                && check(self.path.as_ref(), other.path.as_ref())
+                && check(self.username.as_deref(), other.username.as_deref())
+        }
+
+        /// Like [`to_url()`][Self::to_url()], but with control characters and characters that could mislead users percent-encoded,
+        /// which is what `git` shows in prompts unless `credential.sanitizePrompt` is disabled.
+        pub fn to_sanitized_url(&self) -> Option<BString> {
+            use bstr::ByteVec;
+            fn encode(out: &mut BString, input: &[u8], needs_encoding: impl Fn(u8) -> bool) {
+                for &byte in input {
+                    if byte <= 0x1f || byte >= 0x7f || needs_encoding(byte) {
+                        out.push_str(format!("%{byte:02X}"));
+                    } else {
+                        out.push(byte);
+                    }
+                }
+            }
+            let mut buf: BString = self.protocol.clone()?.into();
+            buf.push_str(b"://");
+            if let Some(user) = self.username.as_ref().filter(|user| !user.is_empty()) {
+                encode(&mut buf, user.as_bytes(), |b| matches!(b, b'/' | b' ' | b'%'));
+                buf.push(b'@');
+            }
+            if let Some(host) = &self.host {
+                encode(&mut buf, host.as_bytes(), |b| {
+                    !b.is_ascii_alphanumeric() && !matches!(b, b'-' | b'.' | b':' | b'[' | b']')
+                });
+            }
+            if let Some(path) = &self.path {
+                let path = path.strip_prefix(b"/").unwrap_or(path);
+                buf.push(b'/');
+                encode(&mut buf, path, |b| matches!(b, b' ' | b'%'));
+            }
+            buf.into()
+        }
+
         /// Compute a prompt to obtain the given value.
         pub fn to_prompt(&self, field: &str) -> String {
             match self.to_url() {
@@ -41,6 +87,14 @@ mod access {
                 None => format!("{field}: "),
             }
         }
+
+        /// Like [`to_prompt()`][Self::to_prompt()], but uses the [sanitized url][Self::to_sanitized_url()].
+        pub fn to_sanitized_prompt(&self, field: &str) -> String {
+            match self.to_sanitized_url() {
+                Some(url) => format!("{field} for {url}: "),
+                None => format!("{field}: "),
+            }
+        }
     }
 }
 
@@ -77,3 +131,4 @@ mod mutate {
 
 mod serde;
 pub use self::serde::decode;
+pub(crate) mod url;
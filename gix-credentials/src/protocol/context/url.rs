@@ -0,0 +1,78 @@
+use bstr::ByteSlice;
+
+use crate::protocol::Context;
+
+impl Context {
+    /// Parse `url` like `git` does with URLs in `credential.<url>.*` configuration that it can't interpret as actual URL,
+    /// which is then [matched][Context::matches()] against the credentials to obtain.
+    ///
+    /// These may lack the protocol, like `example.com` or `example.com/path`, in which case it remains unset, as does the host if
+    /// it is empty. Percent-encoded characters are decoded, and slashes around the path are removed.
+    pub fn from_partial_url(url: &[u8]) -> Option<Self> {
+        parse(url, true)
+    }
+}
+
+/// Parse `url` in the format `<protocol>://[<username>[:<password>]@]<host>[/<path>]`, or return `None` if it isn't valid.
+/// If `allow_partial` is `true`, the protocol may be missing.
+pub(crate) fn parse(url: &[u8], allow_partial: bool) -> Option<Context> {
+    let (protocol, rest) = match url.find("://") {
+        Some(proto_end) if proto_end != 0 => (Some(&url[..proto_end]), &url[proto_end + 3..]),
+        Some(proto_end) if allow_partial => (None, &url[proto_end + 3..]),
+        None if allow_partial => (None, url),
+        _ => return None,
+    };
+    let authority_end = rest
+        .iter()
+        .position(|b| matches!(b, b'/' | b'?' | b'#'))
+        .unwrap_or(rest.len());
+
+    let (username, password, host) = match rest[..authority_end].find_byte(b'@') {
+        None => (None, None, &rest[..authority_end]),
+        Some(at) => {
+            let host = &rest[at + 1..authority_end];
+            match rest[..at].find_byte(b':') {
+                None => (Some(&rest[..at]), None, host),
+                Some(colon) => (Some(&rest[..colon]), Some(&rest[colon + 1..at]), host),
+            }
+        }
+    };
+    let path = percent_decode(rest[authority_end..].trim_start_with(|c| c == '/'))?;
+    let path = path.trim_end_with(|c| c == '/');
+
+    fn decode_string(input: &[u8]) -> Option<String> {
+        percent_decode(input).and_then(|value| String::from_utf8(value).ok())
+    }
+    fn decode_optional(input: Option<&[u8]>) -> Option<Option<String>> {
+        input.map_or(Some(None), |input| decode_string(input).map(Some))
+    }
+    Some(Context {
+        protocol: decode_optional(protocol)?,
+        host: decode_optional((!allow_partial || !host.is_empty()).then_some(host))?,
+        path: (!path.is_empty()).then(|| path.into()),
+        username: decode_optional(username)?,
+        password: decode_optional(password)?,
+        ..Default::default()
+    })
+}
+
+fn percent_decode(input: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut bytes = input.iter().copied().enumerate();
+    while let Some((pos, byte)) = bytes.next() {
+        let decoded = (byte == b'%')
+            .then(|| input.get(pos + 1..pos + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(decoded) => {
+                bytes.nth(1);
+                out.push(decoded);
+            }
+            None => out.push(byte),
+        }
+    }
+    // Credentials can't be passed on if they contain newlines.
+    (!out.contains(&b'\n')).then_some(out)
+}
@@ -30,6 +30,8 @@ pub enum Error {
     IdentityMissing { context: Context },
     #[error("The handler asked to stop trying to obtain credentials")]
     Quit,
+    #[error("The credential value for '{key}' contains a carriage return, which is refused unless credential.protectProtocol is disabled")]
+    CarriageReturn { key: String },
     #[error("Couldn't obtain {prompt}")]
     Prompt { prompt: String, source: gix_prompt::Error },
 }
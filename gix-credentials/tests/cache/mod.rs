@@ -0,0 +1,244 @@
+use std::{
+    io::{Read, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use gix_credentials::{
+    cache::{daemon, exit, invoke, Options},
+    program::main::Action,
+    protocol::Context,
+};
+
+fn ctx(host: &str, username: Option<&str>, password: Option<&str>) -> Context {
+    Context {
+        protocol: Some("https".into()),
+        host: Some(host.into()),
+        username: username.map(Into::into),
+        password: password.map(Into::into),
+        ..Default::default()
+    }
+}
+
+fn identity(username: &str, password: &str) -> Option<Context> {
+    Some(Context {
+        username: Some(username.into()),
+        password: Some(password.into()),
+        ..Default::default()
+    })
+}
+
+fn options(socket: &Path) -> Options {
+    Options {
+        socket: Some(socket.to_owned()),
+        daemon: Some(vec!["false".into()]),
+        ..Default::default()
+    }
+}
+
+/// Run our daemon on `socket` in a thread, and return once it's ready.
+fn serve(socket: &Path) -> crate::Result<std::thread::JoinHandle<std::io::Result<()>>> {
+    let (ready, mut wait) = UnixStream::pair()?;
+    let handle = std::thread::spawn({
+        let socket = socket.to_owned();
+        move || daemon::serve(&socket, ready)
+    });
+    let mut buf = Vec::new();
+    wait.read_to_end(&mut buf)?;
+    assert_eq!(buf, b"ok\n");
+    Ok(handle)
+}
+
+fn socket_in(dir: &Path) -> PathBuf {
+    dir.join("cache").join("socket")
+}
+
+#[test]
+fn get_store_and_erase() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let socket = socket_in(dir.path());
+    let options = options(&socket);
+    assert_eq!(invoke(&options, Action::Get, ctx("example.com", None, None))?, None);
+    assert!(!exit(&options)?, "the daemon doesn't run yet");
+    assert!(
+        invoke(&options, Action::Store, ctx("example.com", Some("user"), Some("pass"))).is_err(),
+        "the daemon is started on demand, which fails here"
+    );
+
+    let daemon = serve(&socket)?;
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(
+            socket.parent().expect("dir").metadata()?.permissions().mode() & 0o777,
+            0o700
+        );
+    }
+    invoke(&options, Action::Store, ctx("example.com", Some("user"), None))?;
+    assert_eq!(
+        invoke(&options, Action::Get, ctx("example.com", None, None))?,
+        None,
+        "incomplete credentials aren't stored"
+    );
+    invoke(&options, Action::Store, ctx("example.com", Some("user"), Some("pass")))?;
+    invoke(&options, Action::Store, ctx("example.com", Some("user"), Some("new")))?;
+    invoke(
+        &options,
+        Action::Store,
+        ctx("example.com", Some("other"), Some("secret")),
+    )?;
+    assert_eq!(
+        invoke(&options, Action::Get, ctx("example.com", None, None))?,
+        identity("user", "new"),
+        "matching credentials are replaced and the first match is returned"
+    );
+    assert_eq!(
+        invoke(&options, Action::Get, ctx("example.com", Some("other"), None))?,
+        identity("other", "secret")
+    );
+    assert_eq!(invoke(&options, Action::Get, ctx("example.org", None, None))?, None);
+
+    invoke(&options, Action::Erase, ctx("example.com", Some("user"), None))?;
+    assert_eq!(
+        invoke(&options, Action::Get, ctx("example.com", None, None))?,
+        identity("other", "secret")
+    );
+
+    assert!(exit(&options)?);
+    daemon.join().expect("no panic")?;
+    assert!(!socket.exists(), "the socket is removed on exit");
+    Ok(())
+}
+
+#[test]
+fn credentials_expire() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let socket = socket_in(dir.path());
+    let options = Options {
+        timeout: Duration::from_secs(1),
+        ..options(&socket)
+    };
+    let daemon = serve(&socket)?;
+    invoke(&options, Action::Store, ctx("example.com", Some("user"), Some("pass")))?;
+    assert_eq!(
+        invoke(&options, Action::Get, ctx("example.com", None, None))?,
+        identity("user", "pass")
+    );
+    std::thread::sleep(Duration::from_millis(1500));
+    assert_eq!(invoke(&options, Action::Get, ctx("example.com", None, None))?, None);
+
+    exit(&options)?;
+    daemon.join().expect("no panic")?;
+    Ok(())
+}
+
+#[test]
+fn timeouts_too_large_to_represent_never_expire() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let socket = socket_in(dir.path());
+    let daemon = serve(&socket)?;
+    let mut stream = UnixStream::connect(&socket)?;
+    stream.write_all(
+        format!(
+            "action=store\ntimeout={}\nprotocol=https\nhost=example.com\nusername=user\npassword=pass\n",
+            u64::MAX
+        )
+        .as_bytes(),
+    )?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+    stream.read_to_end(&mut Vec::new())?;
+
+    assert_eq!(
+        invoke(&options(&socket), Action::Get, ctx("example.com", None, None))?,
+        identity("user", "pass"),
+        "the daemon is still running and keeps the credentials"
+    );
+    assert!(exit(&options(&socket))?);
+    daemon.join().expect("no panic")?;
+    Ok(())
+}
+
+#[test]
+fn clients_that_send_nothing_do_not_block_others() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let socket = socket_in(dir.path());
+    let daemon = serve(&socket)?;
+    let _stuck = UnixStream::connect(&socket)?;
+
+    let options = options(&socket);
+    invoke(&options, Action::Store, ctx("example.com", Some("user"), Some("pass")))?;
+    assert_eq!(
+        invoke(&options, Action::Get, ctx("example.com", None, None))?,
+        identity("user", "pass")
+    );
+    assert!(exit(&options)?);
+    daemon.join().expect("no panic")?;
+    Ok(())
+}
+
+#[test]
+fn refuses_to_use_directories_accessible_by_others() -> crate::Result {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o755))?;
+    let err = daemon::serve(&dir.path().join("socket"), std::io::sink()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+    Ok(())
+}
+
+#[test]
+fn git_can_use_our_daemon() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let socket = socket_in(dir.path());
+    let daemon = serve(&socket)?;
+    let git = |action: &str, input: &str| -> crate::Result<String> {
+        let mut child = Command::new("git")
+            .args(["credential-cache", "--socket"])
+            .arg(&socket)
+            .arg(action)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        child.stdin.take().expect("configured").write_all(input.as_bytes())?;
+        let out = child.wait_with_output()?;
+        assert!(out.status.success());
+        Ok(String::from_utf8(out.stdout)?)
+    };
+
+    git(
+        "store",
+        "protocol=https\nhost=example.com\nusername=user\npassword=pass\n",
+    )?;
+    assert_eq!(
+        invoke(&options(&socket), Action::Get, ctx("example.com", None, None))?,
+        identity("user", "pass")
+    );
+    assert_eq!(
+        git("get", "protocol=https\nhost=example.com\n")?,
+        "username=user\npassword=pass\n"
+    );
+    git("exit", "")?;
+    daemon.join().expect("no panic")?;
+    Ok(())
+}
+
+#[test]
+fn we_can_use_the_daemon_of_git() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let socket = socket_in(dir.path());
+    let options = Options {
+        daemon: Some(vec!["git".into(), "credential-cache--daemon".into()]),
+        ..options(&socket)
+    };
+    invoke(&options, Action::Store, ctx("example.com", Some("user"), Some("pass")))?;
+    assert_eq!(
+        invoke(&options, Action::Get, ctx("example.com", None, None))?,
+        identity("user", "pass"),
+        "the daemon was started on demand"
+    );
+    invoke(&options, Action::Erase, ctx("example.com", None, None))?;
+    assert_eq!(invoke(&options, Action::Get, ctx("example.com", None, None))?, None);
+    assert!(exit(&options)?);
+    Ok(())
+}
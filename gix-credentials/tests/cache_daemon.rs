@@ -0,0 +1,50 @@
+//! Use the credential cache with its default daemon program, which is this very program, while `git` isn't available.
+//!
+//! This needs its own program without test harness to be able to serve credentials when it's started as daemon.
+#[cfg(unix)]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    use gix_credentials::{
+        cache::{daemon, exit, invoke, Options},
+        program::main::Action,
+        protocol::Context,
+    };
+
+    if let Some(res) = daemon::serve_if_requested() {
+        return Ok(res?);
+    }
+    std::env::set_var("PATH", "");
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let options = Options {
+        socket: Some(dir.path().join("cache").join("socket")),
+        ..Default::default()
+    };
+    let url = Context {
+        protocol: Some("https".into()),
+        host: Some("example.com".into()),
+        ..Default::default()
+    };
+    let identity = Context {
+        username: Some("user".into()),
+        password: Some("pass".into()),
+        ..Default::default()
+    };
+    invoke(
+        &options,
+        Action::Store,
+        Context {
+            username: identity.username.clone(),
+            password: identity.password.clone(),
+            ..url.clone()
+        },
+    )?;
+    assert_eq!(
+        invoke(&options, Action::Get, url)?,
+        Some(identity),
+        "the daemon was started on demand"
+    );
+    assert!(exit(&options)?);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn main() {}
@@ -138,6 +138,30 @@ mod invoke {
         assert_eq!(actual.identity, identity("user", ""));
     }
 
+    #[test]
+    fn carriage_returns_are_refused_unless_protocol_protection_is_disabled() {
+        let url = "https://example.com/pa\rth";
+        let err = invoke_cascade(["custom-helper"], Action::get_for_url(url)).unwrap_err();
+        assert!(
+            matches!(err, protocol::Error::CarriageReturn { ref key } if key == "url"),
+            "{err:?}"
+        );
+
+        let actual = Cascade::default()
+            .protect_protocol(false)
+            .extend(fixtures(["custom-helper"]))
+            .invoke(
+                Action::get_for_url(url),
+                gix_prompt::Options {
+                    mode: gix_prompt::Mode::Disable,
+                    askpass: None,
+                },
+            )
+            .unwrap()
+            .expect("credentials");
+        assert_eq!(actual.identity, identity("user-script", "pass-script"));
+    }
+
     fn action_get() -> Action {
         Action::get_for_url("does/not/matter")
     }
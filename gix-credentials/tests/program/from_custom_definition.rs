@@ -40,3 +40,50 @@ fn path() {
         matches!(Program::from_custom_definition(input).kind, Kind::ExternalPath{path_and_args} if path_and_args == input)
     );
 }
+
+#[test]
+fn store() {
+    assert_eq!(
+        Program::from_custom_definition("store").kind,
+        Kind::Store(Default::default()),
+        "it's implemented by us"
+    );
+    let home = gix_path::env::home_dir().expect("home is set in tests");
+    for input in ["store --file ~/creds", "store --file=~/creds", "store '--file=~/creds'"] {
+        assert_eq!(
+            Program::from_custom_definition(input).kind,
+            Kind::Store(gix_credentials::store::Options {
+                file: Some(home.join("creds"))
+            }),
+            "{input}"
+        );
+    }
+    assert!(
+        matches!(Program::from_custom_definition("store --unknown").kind, Kind::ExternalName{name_and_args} if name_and_args == "git credential-store --unknown"),
+        "git is used with arguments we don't know"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn cache() {
+    assert_eq!(
+        Program::from_custom_definition("cache").kind,
+        Kind::Cache(Default::default())
+    );
+    assert_eq!(
+        Program::from_custom_definition("cache --timeout=60 --socket /tmp/socket").kind,
+        Kind::Cache(gix_credentials::cache::Options {
+            timeout: std::time::Duration::from_secs(60),
+            socket: Some("/tmp/socket".into()),
+            daemon: None,
+        })
+    );
+    assert!(
+        matches!(
+            Program::from_custom_definition("cache --timeout=never").kind,
+            Kind::ExternalName { .. }
+        ),
+        "git is used with arguments we don't understand"
+    );
+}
@@ -87,6 +87,97 @@ mod to_prompt {
     }
 }
 
+mod to_sanitized_prompt {
+    use gix_credentials::protocol::Context;
+
+    #[test]
+    fn control_characters_and_misleading_characters_are_encoded() {
+        let ctx = Context {
+            protocol: Some("https".into()),
+            username: Some("us er/%".into()),
+            host: Some("exa\u{1b}[1mmple.com:8080".into()),
+            path: Some("a path/%\u{7f}".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            ctx.to_sanitized_prompt("Username"),
+            "Username for https://us%20er%2F%25@exa%1B[1mmple.com:8080/a%20path/%25%7F: "
+        );
+        assert_eq!(
+            ctx.to_prompt("Username"),
+            "Username for https://us er/%@exa\u{1b}[1mmple.com:8080/a path/%\u{7f}: ",
+            "the unsanitized version is verbatim"
+        );
+    }
+
+    #[test]
+    fn non_ascii_characters_are_encoded_as_utf8_bytes() {
+        let ctx = Context {
+            protocol: Some("https".into()),
+            host: Some("exämple.com".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            ctx.to_sanitized_prompt("Password"),
+            "Password for https://ex%C3%A4mple.com: "
+        );
+    }
+}
+
+mod from_partial_url {
+    use gix_credentials::protocol::Context;
+
+    #[test]
+    fn missing_fields_remain_unset() {
+        assert_eq!(
+            Context::from_partial_url(b"example.com").expect("valid"),
+            Context {
+                host: Some("example.com".into()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            Context::from_partial_url(b"user@example.com:8080//a/b%20c/").expect("valid"),
+            Context {
+                host: Some("example.com:8080".into()),
+                username: Some("user".into()),
+                path: Some("a/b c".into()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            Context::from_partial_url(b"https://").expect("valid"),
+            Context {
+                protocol: Some("https".into()),
+                ..Default::default()
+            },
+            "empty hosts are unset"
+        );
+        assert_eq!(
+            Context::from_partial_url(b"example.com/%0a"),
+            None,
+            "newlines are invalid"
+        );
+    }
+
+    #[test]
+    fn matching_uses_only_the_fields_that_are_set() {
+        let have = Context {
+            protocol: Some("https".into()),
+            host: Some("example.com".into()),
+            path: Some("org/repo".into()),
+            ..Default::default()
+        };
+        let pattern = |url: &str| Context::from_partial_url(url.as_bytes()).expect("valid");
+        assert!(pattern("example.com").matches(&have));
+        assert!(pattern("https://").matches(&have));
+        assert!(pattern("example.com/org/repo").matches(&have));
+        assert!(!pattern("example.com/org").matches(&have), "paths must match exactly");
+        assert!(!pattern("user@example.com").matches(&have));
+        assert!(!pattern("http://example.com").matches(&have));
+    }
+}
+
 mod to_url {
     use gix_credentials::protocol::Context;
 
@@ -0,0 +1,139 @@
+//! Post-processing of the changes found by any algorithm, to make them look like those of `git`.
+use crate::blob::{intern::Token, Sink};
+
+/// Pass the changes in `changed_before` and `changed_after` to `sink` as ranges of changed lines.
+pub(crate) fn emit<S: Sink>(changed_before: &[bool], changed_after: &[bool], mut sink: S) -> S::Out {
+    let is_changed = |changed: &[bool], index: usize| changed.get(index).copied().unwrap_or(false);
+    let (mut index_before, mut index_after) = (0, 0);
+    while index_before < changed_before.len() || index_after < changed_after.len() {
+        if is_changed(changed_before, index_before) || is_changed(changed_after, index_after) {
+            let (start_before, start_after) = (index_before, index_after);
+            while is_changed(changed_before, index_before) {
+                index_before += 1;
+            }
+            while is_changed(changed_after, index_after) {
+                index_after += 1;
+            }
+            sink.process_change(
+                start_before as u32..index_before as u32,
+                start_after as u32..index_after as u32,
+            );
+        } else {
+            index_before += 1;
+            index_after += 1;
+        }
+    }
+    sink.finish()
+}
+
+/// A group of changed lines, which is empty if `start == end`.
+#[derive(Clone, Copy)]
+struct Group {
+    start: usize,
+    end: usize,
+}
+
+/// Slide groups of changes in `tokens` as far down as possible while merging them with adjacent groups, and then up again
+/// to align them with a group of changes in the `other` file if possible, like `xdl_change_compact()` in `git` does
+/// when the indent heuristic is disabled.
+pub(crate) fn compact(tokens: &[Token], changed: &mut [bool], other: &[bool]) {
+    fn is_changed(changed: &[bool], index: usize) -> bool {
+        changed.get(index).copied().unwrap_or(false)
+    }
+    fn first_group(changed: &[bool]) -> Group {
+        let mut end = 0;
+        while is_changed(changed, end) {
+            end += 1;
+        }
+        Group { start: 0, end }
+    }
+    fn next_group(changed: &[bool], group: &mut Group) -> bool {
+        if group.end == changed.len() {
+            return false;
+        }
+        group.start = group.end + 1;
+        group.end = group.start;
+        while is_changed(changed, group.end) {
+            group.end += 1;
+        }
+        true
+    }
+    fn previous_group(changed: &[bool], group: &mut Group) -> bool {
+        if group.start == 0 {
+            return false;
+        }
+        group.end = group.start - 1;
+        group.start = group.end;
+        while group.start > 0 && is_changed(changed, group.start - 1) {
+            group.start -= 1;
+        }
+        true
+    }
+    let slide_down = |changed: &mut [bool], group: &mut Group| -> bool {
+        if group.end < changed.len() && tokens[group.start] == tokens[group.end] {
+            changed[group.start] = false;
+            changed[group.end] = true;
+            group.start += 1;
+            group.end += 1;
+            while is_changed(changed, group.end) {
+                group.end += 1;
+            }
+            true
+        } else {
+            false
+        }
+    };
+    let slide_up = |changed: &mut [bool], group: &mut Group| -> bool {
+        if group.start > 0 && tokens[group.start - 1] == tokens[group.end - 1] {
+            group.start -= 1;
+            group.end -= 1;
+            changed[group.start] = true;
+            changed[group.end] = false;
+            while group.start > 0 && is_changed(changed, group.start - 1) {
+                group.start -= 1;
+            }
+            true
+        } else {
+            false
+        }
+    };
+
+    let mut group = first_group(changed);
+    let mut other_group = first_group(other);
+    loop {
+        if group.end != group.start {
+            let mut earliest_end;
+            let mut end_matching_other;
+            loop {
+                let size = group.end - group.start;
+                end_matching_other = None;
+                while slide_up(changed, &mut group) {
+                    previous_group(other, &mut other_group);
+                }
+                earliest_end = group.end;
+                if other_group.end > other_group.start {
+                    end_matching_other = Some(group.end);
+                }
+                while slide_down(changed, &mut group) {
+                    next_group(other, &mut other_group);
+                    if other_group.end > other_group.start {
+                        end_matching_other = Some(group.end);
+                    }
+                }
+                if size == group.end - group.start {
+                    break;
+                }
+            }
+            if group.end != earliest_end && end_matching_other.is_some() {
+                while other_group.end == other_group.start {
+                    slide_up(changed, &mut group);
+                    previous_group(other, &mut other_group);
+                }
+            }
+        }
+        if !next_group(changed, &mut group) {
+            break;
+        }
+        next_group(other, &mut other_group);
+    }
+}
@@ -0,0 +1,87 @@
+//! For using text diffs, please have a look at the [`imara-diff` documentation](https://docs.rs/imara-diff),
+//! maintained by [Pascal Kuthe](https://github.com/pascalkuthe).
+//!
+//! The [`Algorithm`] and [`diff()`] provided here shadow those of `imara-diff` to add the `patience` algorithm that `git` offers,
+//! and to post-process changes like `git` does.
+use std::hash::Hash;
+
+pub use imara_diff::*;
+
+use crate::blob::intern::{InternedInput, Token};
+
+mod compact;
+mod patience;
+
+/// The diff algorithm to use when calling [`diff()`], which maps to the values of the `diff.algorithm` configuration of `git`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Algorithm {
+    /// A variation of the `patience` algorithm that uses a histogram to find the least common lines,
+    /// producing readable diffs while performing better than all other algorithms, see [`imara_diff::Algorithm::Histogram`].
+    Histogram,
+    /// The classic Myers algorithm, with heuristics to limit its runtime for large inputs.
+    Myers,
+    /// The Myers algorithm without heuristics, which produces the smallest possible diff at the cost of performance.
+    MyersMinimal,
+    /// The [`patience` algorithm described by Bram Cohen](https://bramcohen.livejournal.com/73318.html), which aligns
+    /// lines that are unique in both inputs first, implemented like it is in `git`.
+    ///
+    /// Ranges without such lines are diffed using the `Myers` algorithm.
+    Patience,
+}
+
+impl Default for Algorithm {
+    fn default() -> Self {
+        Algorithm::Histogram
+    }
+}
+
+/// Computes an edit-script that transforms `input.before` into `input.after` using the specified `algorithm`, passing each
+/// change to `sink`.
+pub fn diff<S: Sink, T: Eq + Hash>(algorithm: Algorithm, input: &InternedInput<T>, sink: S) -> S::Out {
+    diff_with_tokens(
+        algorithm,
+        &input.before,
+        &input.after,
+        input.interner.num_tokens(),
+        sink,
+    )
+}
+
+/// Computes an edit-script that transforms `before` into `after` using the specified `algorithm`, passing each
+/// change to `sink`.
+///
+/// Like in `git`, groups of changed lines are slid down as far as possible if the lines around them allow it, unless
+/// that would prevent them from being aligned with a change in the other file. The indent heuristic of `git` isn't
+/// implemented, so results match those of `git` with `diff.indentHeuristic=false`.
+pub fn diff_with_tokens<S: Sink>(
+    algorithm: Algorithm,
+    before: &[Token],
+    after: &[Token],
+    num_tokens: u32,
+    sink: S,
+) -> S::Out {
+    let mut changed_before = vec![false; before.len()];
+    let mut changed_after = vec![false; after.len()];
+    let algorithm = match algorithm {
+        Algorithm::Histogram => Some(imara_diff::Algorithm::Histogram),
+        Algorithm::Myers => Some(imara_diff::Algorithm::Myers),
+        Algorithm::MyersMinimal => Some(imara_diff::Algorithm::MyersMinimal),
+        Algorithm::Patience => None,
+    };
+    match algorithm {
+        Some(algorithm) => imara_diff::diff_with_tokens(
+            algorithm,
+            before,
+            after,
+            num_tokens,
+            |removed: std::ops::Range<u32>, added: std::ops::Range<u32>| {
+                changed_before[removed.start as usize..removed.end as usize].fill(true);
+                changed_after[added.start as usize..added.end as usize].fill(true);
+            },
+        ),
+        None => patience::diff(before, after, num_tokens, &mut changed_before, &mut changed_after),
+    }
+    compact::compact(before, &mut changed_before, &changed_after);
+    compact::compact(after, &mut changed_after, &changed_before);
+    compact::emit(&changed_before, &changed_after, sink)
+}
@@ -0,0 +1,196 @@
+//! An implementation of the patience diff algorithm that follows `xdiff/xpatience.c` in `git` closely to produce the same output.
+use std::{collections::HashMap, ops::Range};
+
+use crate::blob::intern::Token;
+
+/// Mark all lines of `before` and `after` that changed in `changed_before` and `changed_after` respectively.
+pub(crate) fn diff(
+    before: &[Token],
+    after: &[Token],
+    num_tokens: u32,
+    changed_before: &mut [bool],
+    changed_after: &mut [bool],
+) {
+    State {
+        before,
+        after,
+        num_tokens,
+        changed_before,
+        changed_after,
+    }
+    .diff(0..before.len(), 0..after.len());
+}
+
+struct State<'a> {
+    before: &'a [Token],
+    after: &'a [Token],
+    num_tokens: u32,
+    changed_before: &'a mut [bool],
+    changed_after: &'a mut [bool],
+}
+
+/// A line of the `before` range along with its counterpart in the `after` range.
+struct Entry {
+    before: usize,
+    after: Match,
+    /// The index of the previous entry in the longest common sequence.
+    previous: Option<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Match {
+    None,
+    Unique(usize),
+    NonUnique,
+}
+
+impl<'a> State<'a> {
+    fn diff(&mut self, before: Range<usize>, after: Range<usize>) {
+        if before.is_empty() {
+            self.changed_after[after].fill(true);
+            return;
+        } else if after.is_empty() {
+            self.changed_before[before].fill(true);
+            return;
+        }
+
+        // Find lines that are unique in both ranges, ordered by their position in `before`.
+        let mut entries = Vec::<Entry>::new();
+        let mut entry_by_token = HashMap::<Token, usize>::new();
+        for index in before.clone() {
+            match entry_by_token.get(&self.before[index]) {
+                Some(&entry) => entries[entry].after = Match::NonUnique,
+                None => {
+                    entry_by_token.insert(self.before[index], entries.len());
+                    entries.push(Entry {
+                        before: index,
+                        after: Match::None,
+                        previous: None,
+                    });
+                }
+            }
+        }
+        let mut has_matches = false;
+        for index in after.clone() {
+            if let Some(&entry) = entry_by_token.get(&self.after[index]) {
+                has_matches = true;
+                let entry = &mut entries[entry];
+                entry.after = match entry.after {
+                    Match::None => Match::Unique(index),
+                    _ => Match::NonUnique,
+                };
+            }
+        }
+        if !has_matches {
+            self.changed_before[before].fill(true);
+            self.changed_after[after].fill(true);
+            return;
+        }
+
+        match longest_common_sequence(&mut entries) {
+            Some(sequence) => self.walk_common_sequence(&entries, &sequence, before, after),
+            None => self.fall_back_to_myers(before, after),
+        }
+    }
+
+    /// Diff the ranges between the lines in `sequence`, after extending the common lines around them.
+    fn walk_common_sequence(
+        &mut self,
+        entries: &[Entry],
+        sequence: &[usize],
+        before: Range<usize>,
+        after: Range<usize>,
+    ) {
+        let (mut line_before, mut line_after) = (before.start, after.start);
+        let mut next_entry = sequence.iter().map(|&index| &entries[index]).peekable();
+        loop {
+            let (mut next_before, mut next_after) = match next_entry.peek() {
+                Some(entry) => (entry.before, entry.after.unique()),
+                None => (before.end, after.end),
+            };
+            if next_entry.peek().is_some() {
+                while next_before > line_before
+                    && next_after > line_after
+                    && self.before[next_before - 1] == self.after[next_after - 1]
+                {
+                    next_before -= 1;
+                    next_after -= 1;
+                }
+            }
+            while line_before < next_before
+                && line_after < next_after
+                && self.before[line_before] == self.after[line_after]
+            {
+                line_before += 1;
+                line_after += 1;
+            }
+            if next_before > line_before || next_after > line_after {
+                self.diff(line_before..next_before, line_after..next_after);
+            }
+
+            let mut entry = match next_entry.next() {
+                Some(entry) => entry,
+                None => return,
+            };
+            while let Some(next) = next_entry
+                .next_if(|next| next.before == entry.before + 1 && next.after.unique() == entry.after.unique() + 1)
+            {
+                entry = next;
+            }
+            line_before = entry.before + 1;
+            line_after = entry.after.unique() + 1;
+        }
+    }
+
+    fn fall_back_to_myers(&mut self, before: Range<usize>, after: Range<usize>) {
+        let (changed_before, changed_after) = (&mut *self.changed_before, &mut *self.changed_after);
+        imara_diff::diff_with_tokens(
+            imara_diff::Algorithm::Myers,
+            &self.before[before.clone()],
+            &self.after[after.clone()],
+            self.num_tokens,
+            |removed: Range<u32>, added: Range<u32>| {
+                changed_before[before.start + removed.start as usize..before.start + removed.end as usize].fill(true);
+                changed_after[after.start + added.start as usize..after.start + added.end as usize].fill(true);
+            },
+        );
+    }
+}
+
+impl Match {
+    fn unique(self) -> usize {
+        match self {
+            Match::Unique(index) => index,
+            Match::None | Match::NonUnique => unreachable!("only entries with unique matches are in the sequence"),
+        }
+    }
+}
+
+/// Return the indices of `entries` that form the longest sequence of unique lines that are in the same order in both ranges,
+/// using patience sorting, or `None` if there is no unique line.
+fn longest_common_sequence(entries: &mut [Entry]) -> Option<Vec<usize>> {
+    // `tails[n]` is the entry ending the sequence of length `n + 1` whose last line in `after` is the smallest.
+    let mut tails = Vec::<usize>::new();
+    for index in 0..entries.len() {
+        let after = match entries[index].after {
+            Match::Unique(after) => after,
+            Match::None | Match::NonUnique => continue,
+        };
+        let position = tails.partition_point(|&tail| entries[tail].after.unique() < after);
+        entries[index].previous = position.checked_sub(1).map(|previous| tails[previous]);
+        if position == tails.len() {
+            tails.push(index);
+        } else {
+            tails[position] = index;
+        }
+    }
+
+    let mut sequence = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(index) = next {
+        sequence.push(index);
+        next = entries[index].previous;
+    }
+    sequence.reverse();
+    (!sequence.is_empty()).then_some(sequence)
+}
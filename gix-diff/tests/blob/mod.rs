@@ -1,2 +1,73 @@
+use std::ops::Range;
+
+use gix_diff::blob::{intern::InternedInput, sources::byte_lines_with_terminator, Algorithm};
+
+type Hunk = (Range<u32>, Range<u32>);
+
+/// Parse the hunk headers of a patch produced with `-U0` into ranges of lines, as passed to a `Sink`.
+fn baseline_hunks(patch: &[u8]) -> Vec<Hunk> {
+    let range = |spec: &str| -> Range<u32> {
+        let (start, count) = match spec.split_once(',') {
+            Some((start, count)) => (start.parse::<u32>().unwrap(), count.parse::<u32>().unwrap()),
+            None => (spec.parse().unwrap(), 1),
+        };
+        if count == 0 {
+            start..start
+        } else {
+            start - 1..start - 1 + count
+        }
+    };
+    std::str::from_utf8(patch)
+        .expect("utf8")
+        .lines()
+        .filter_map(|line| line.strip_prefix("@@ -"))
+        .map(|header| {
+            let mut ranges = header.split(' ');
+            let before = range(ranges.next().expect("before"));
+            let after = range(ranges.next().expect("after").trim_start_matches('+'));
+            (before, after)
+        })
+        .collect()
+}
+
+fn hunks(algorithm: Algorithm, before: &[u8], after: &[u8]) -> Vec<Hunk> {
+    let input = InternedInput::new(byte_lines_with_terminator(before), byte_lines_with_terminator(after));
+    let mut hunks = Vec::new();
+    gix_diff::blob::diff(algorithm, &input, |before: Range<u32>, after: Range<u32>| {
+        hunks.push((before, after))
+    });
+    hunks
+}
+
 #[test]
-fn currently_there_is_no_api_surface_to_test_as_it_is_reexporting_imara_diff() {}
+fn algorithms_match_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_blob_diff_baselines.sh")?;
+    for case in ["c-code", "moved", "repeated"] {
+        let dir = root.join(case);
+        let (before, after) = (std::fs::read(dir.join("before"))?, std::fs::read(dir.join("after"))?);
+        for (algorithm, name) in [
+            (Algorithm::Myers, "myers"),
+            (Algorithm::MyersMinimal, "minimal"),
+            (Algorithm::Patience, "patience"),
+        ] {
+            let expected = baseline_hunks(&std::fs::read(dir.join(format!("{name}.baseline")))?);
+            assert_eq!(hunks(algorithm, &before, &after), expected, "{case} with {name}");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn patience_aligns_unique_lines_first() {
+    let (before, after) = (b"a\nx\nb\nx\nc\n", b"c\nx\na\nx\nb\n");
+    assert_eq!(
+        hunks(Algorithm::Patience, before, after),
+        vec![(0..0, 0..2), (3..5, 5..5)],
+        "`a` and `b` are the longest sequence of unique lines, and the `x` between them is matched as well"
+    );
+    assert_eq!(
+        hunks(Algorithm::Patience, b"", b"a\n"),
+        vec![(0..0, 0..1)],
+        "empty inputs are handled"
+    );
+}
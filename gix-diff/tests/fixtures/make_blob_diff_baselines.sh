@@ -0,0 +1,79 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# The example of `git`'s own tests for alternative diff algorithms, which differ between `myers` and `patience`.
+mkdir c-code && cd c-code
+cat <<EOT > before
+#include <stdio.h>
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("Your answer is: ");
+        printf("%d\n", foo);
+    }
+}
+
+int fact(int n)
+{
+    if(n > 1)
+    {
+        return fact(n-1) * n;
+    }
+    return 1;
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fact(10));
+}
+EOT
+cat <<EOT > after
+#include <stdio.h>
+
+int fib(int n)
+{
+    if(n > 2)
+    {
+        return fib(n-1) + fib(n-2);
+    }
+    return 1;
+}
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("%d\n", foo);
+    }
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fib(10));
+}
+EOT
+cd ..
+
+# Unique lines that moved, and a range without unique lines which needs a fallback.
+mkdir moved && cd moved
+printf '%s\n' a b c d e f x x y x z g h > before
+printf '%s\n' e f a b c d x y x x z h g > after
+cd ..
+
+# Repeated lines only.
+mkdir repeated && cd repeated
+printf '%s\n' a b a b a b c a b > before
+printf '%s\n' b a b c a c b a > after
+cd ..
+
+for dir in c-code moved repeated; do
+  for algorithm in myers minimal patience; do
+    git diff --no-index --no-indent-heuristic --diff-algorithm=$algorithm -U0 $dir/before $dir/after > $dir/$algorithm.baseline || test $? = 1
+  done
+done
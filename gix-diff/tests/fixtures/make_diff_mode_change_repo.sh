@@ -0,0 +1,13 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config core.fileMode true
+git checkout -q -b main
+
+echo content > f
+git add f
+git commit -qm 'f added'
+
+chmod +x f
+git commit -qam 'f made executable'
@@ -714,5 +714,28 @@ mod changes {
             );
             Ok(())
         }
+
+        #[test]
+        fn changes_of_the_mode_only_are_modifications() -> crate::Result {
+            let db = gix_odb::at(
+                gix_testtools::scripted_fixture_read_only_standalone("make_diff_mode_change_repo.sh")?
+                    .join(".git")
+                    .join("objects"),
+            )?;
+            let all_commits = all_commits(&db);
+
+            assert_eq!(
+                diff_with_previous_commit_from(&db, &all_commits["f made executable"])?,
+                vec![Modification {
+                    previous_entry_mode: EntryMode::Blob,
+                    previous_oid: hex_to_id("d95f3ad14dee633a758d2e331151e950dd13e4ed"),
+                    entry_mode: EntryMode::BlobExecutable,
+                    oid: hex_to_id("d95f3ad14dee633a758d2e331151e950dd13e4ed"),
+                    path: "f".into()
+                }],
+                ":100644 100755 d95f3ad14dee633a758d2e331151e950dd13e4ed d95f3ad14dee633a758d2e331151e950dd13e4ed M	f"
+            );
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,205 @@
+use bstr::ByteSlice;
+use gix_attributes::StateRef;
+
+/// The value of the `core.autocrlf` configuration variable.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AutoCrlf {
+    /// Convert line endings to LF when storing files, and to CRLF when checking them out.
+    Enabled,
+    /// Convert line endings to LF when storing files, but don't convert them when checking them out.
+    Input,
+    /// Don't convert line endings unless attributes say so.
+    Disabled,
+}
+
+impl Default for AutoCrlf {
+    fn default() -> Self {
+        AutoCrlf::Disabled
+    }
+}
+
+/// How line endings of a file should be converted, derived from its attributes and [`AutoCrlf`].
+///
+/// This is what `git` calls the `crlf_action`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AttributesDigest {
+    /// The file is binary and its line endings must not be converted.
+    Binary,
+    /// The file is text, and line endings are converted according to `core.eol` on checkout.
+    Text,
+    /// The file is text, and line endings are kept as LF on checkout.
+    TextInput,
+    /// The file is text, and line endings are converted to CRLF on checkout.
+    TextCrlf,
+    /// Guess whether the file is text, and if so, convert line endings according to `core.eol` on checkout.
+    TextAuto,
+    /// Guess whether the file is text, and keep line endings as LF on checkout.
+    TextAutoInput,
+    /// Guess whether the file is text, and if so, convert line endings to CRLF on checkout.
+    TextAutoCrlf,
+}
+
+impl AttributesDigest {
+    /// Derive the way line endings are converted from the state of the `text`, `crlf` and `eol` attributes of a file,
+    /// along with the value of `core.autocrlf` for use if attributes don't say anything.
+    ///
+    /// The `crlf` attribute is the deprecated predecessor of `text` and is only used if `text` is unspecified.
+    pub fn from_attributes(text: StateRef<'_>, crlf: StateRef<'_>, eol: StateRef<'_>, auto_crlf: AutoCrlf) -> Self {
+        let digest = match Self::from_text_attribute(text) {
+            Some(digest) => Some(digest),
+            None => Self::from_text_attribute(crlf),
+        };
+        let digest = match digest {
+            Some(AttributesDigest::Binary) => Some(AttributesDigest::Binary),
+            digest => match (digest, eol_value(eol)) {
+                (Some(AttributesDigest::TextAuto), Some(b"lf")) => Some(AttributesDigest::TextAutoInput),
+                (Some(AttributesDigest::TextAuto), Some(b"crlf")) => Some(AttributesDigest::TextAutoCrlf),
+                (_, Some(b"lf")) => Some(AttributesDigest::TextInput),
+                (_, Some(b"crlf")) => Some(AttributesDigest::TextCrlf),
+                (digest, _) => digest,
+            },
+        };
+        digest.unwrap_or(match auto_crlf {
+            AutoCrlf::Disabled => AttributesDigest::Binary,
+            AutoCrlf::Enabled => AttributesDigest::TextAutoCrlf,
+            AutoCrlf::Input => AttributesDigest::TextAutoInput,
+        })
+    }
+
+    fn from_text_attribute(state: StateRef<'_>) -> Option<Self> {
+        match state {
+            StateRef::Set => Some(AttributesDigest::Text),
+            StateRef::Unset => Some(AttributesDigest::Binary),
+            StateRef::Value(value) => match value.as_bstr().as_bytes() {
+                b"input" => Some(AttributesDigest::TextInput),
+                b"auto" => Some(AttributesDigest::TextAuto),
+                _ => None,
+            },
+            StateRef::Unspecified => None,
+        }
+    }
+
+    fn is_auto(&self) -> bool {
+        matches!(
+            self,
+            AttributesDigest::TextAuto | AttributesDigest::TextAutoInput | AttributesDigest::TextAutoCrlf
+        )
+    }
+}
+
+fn eol_value(state: StateRef<'_>) -> Option<&[u8]> {
+    match state {
+        StateRef::Value(value) => match value.as_bstr().as_bytes() {
+            b"lf" => Some(b"lf"),
+            b"crlf" => Some(b"crlf"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Statistics about the kind of bytes in a buffer, used to determine if it's binary and which line endings it has.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Stats {
+    /// The amount of null bytes.
+    pub null: usize,
+    /// The amount of carriage returns that aren't followed by a newline.
+    pub lone_cr: usize,
+    /// The amount of newlines that aren't preceded by a carriage return.
+    pub lone_lf: usize,
+    /// The amount of carriage returns directly followed by a newline.
+    pub crlf: usize,
+    /// The amount of printable bytes.
+    pub printable: usize,
+    /// The amount of non-printable bytes.
+    pub non_printable: usize,
+}
+
+impl Stats {
+    /// Gather statistics about `buf`, in the same way `git` does.
+    pub fn from_bytes(buf: &[u8]) -> Self {
+        let mut stats = Stats::default();
+        let mut bytes = buf.iter().peekable();
+        while let Some(&byte) = bytes.next() {
+            match byte {
+                b'\r' => {
+                    if bytes.peek() == Some(&&b'\n') {
+                        bytes.next();
+                        stats.crlf += 1;
+                    } else {
+                        stats.lone_cr += 1;
+                    }
+                }
+                b'\n' => stats.lone_lf += 1,
+                127 => stats.non_printable += 1,
+                b'\x08' | b'\t' | b'\x1b' | b'\x0c' => stats.printable += 1,
+                0 => {
+                    stats.null += 1;
+                    stats.non_printable += 1;
+                }
+                byte if byte < 32 => stats.non_printable += 1,
+                _ => stats.printable += 1,
+            }
+        }
+        // A trailing end-of-file marker isn't considered non-printable.
+        if buf.last() == Some(&b'\x1a') {
+            stats.non_printable -= 1;
+        }
+        stats
+    }
+
+    /// Return `true` if the buffer these statistics were gathered from is considered binary.
+    pub fn is_binary(&self) -> bool {
+        self.lone_cr > 0 || self.null > 0 || (self.printable >> 7) < self.non_printable
+    }
+}
+
+/// Convert the line endings of the worktree file `src` to LF according to `digest` and write the result to `buf`, just
+/// like `git` does when storing files in the object database.
+///
+/// If `digest` leaves it to guessing, `src` isn't converted if it looks binary, or if the version of the file that is
+/// currently in the index contains CRLF line endings, so files that were committed with CRLF aren't changed unexpectedly.
+/// That version is obtained by calling `index_object(buf)`, which returns `Ok(true)` if it placed the object
+/// in `buf`, or `Ok(false)` if there is no such file in the index.
+///
+/// Return `true` if `buf` was written as `src` needed conversion, or `false` if `src` can be used as is.
+pub fn convert_to_git<E>(
+    src: &[u8],
+    digest: AttributesDigest,
+    buf: &mut Vec<u8>,
+    index_object: impl FnOnce(&mut Vec<u8>) -> Result<bool, E>,
+) -> Result<bool, E> {
+    if digest == AttributesDigest::Binary || src.is_empty() {
+        return Ok(false);
+    }
+    let stats = Stats::from_bytes(src);
+    if stats.crlf == 0 {
+        return Ok(false);
+    }
+    if digest.is_auto() {
+        if stats.is_binary() {
+            return Ok(false);
+        }
+        buf.clear();
+        if index_object(buf)? && buf.contains(&b'\r') {
+            let index_stats = Stats::from_bytes(buf);
+            if !index_stats.is_binary() && index_stats.crlf > 0 {
+                return Ok(false);
+            }
+        }
+    }
+
+    buf.clear();
+    buf.reserve(src.len() - stats.crlf);
+    if digest.is_auto() {
+        buf.extend(src.iter().filter(|&&byte| byte != b'\r'));
+    } else {
+        let mut bytes = src.iter().peekable();
+        while let Some(&byte) = bytes.next() {
+            if !(byte == b'\r' && bytes.peek() == Some(&&b'\n')) {
+                buf.push(byte);
+            }
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,39 @@
+use bstr::ByteSlice;
+
+/// Undo identifiers like `$Id: <hexsha>$` to `$Id$` in `src` and write the result to `buf`, which is what `git` does when
+/// storing files with the `ident` attribute set.
+///
+/// Return `true` if `buf` was written as there was something to undo, or `false` if `src` can be used as is,
+/// in which case `buf` isn't touched.
+pub fn undo(src: &[u8], buf: &mut Vec<u8>) -> bool {
+    let mut out: Option<Vec<u8>> = None;
+    let mut copied_until = 0;
+    let mut pos = 0;
+    while let Some(dollar) = src[pos..].find_byte(b'$').map(|offset| pos + offset) {
+        pos = dollar + 1;
+        if !src[pos..].starts_with(b"Id:") {
+            continue;
+        }
+        let end = match src[pos + 3..].find_byte(b'$') {
+            Some(offset) => pos + 3 + offset,
+            None => break,
+        };
+        if src[pos + 3..end].contains(&b'\n') {
+            continue;
+        }
+        let out = out.get_or_insert_with(|| Vec::with_capacity(src.len()));
+        out.extend_from_slice(&src[copied_until..pos]);
+        out.extend_from_slice(b"Id$");
+        pos = end + 1;
+        copied_until = pos;
+    }
+
+    match out {
+        Some(mut out) => {
+            out.extend_from_slice(&src[copied_until..]);
+            *buf = out;
+            true
+        }
+        None => false,
+    }
+}
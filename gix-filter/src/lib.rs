@@ -1,2 +1,13 @@
-#![deny(rust_2018_idioms)]
+//! A library for implementing everything needed to deal with git filters, which transform file content when it moves
+//! between the worktree and the object database.
+//!
+//! Currently, only the conversions into the object database are provided, that is line-ending conversions driven by
+//! attributes and `core.autocrlf`, and the undoing of `ident` expansions.
+#![deny(rust_2018_idioms, missing_docs)]
 #![forbid(unsafe_code)]
+
+///
+pub mod eol;
+
+///
+pub mod ident;
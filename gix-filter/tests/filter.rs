@@ -0,0 +1,144 @@
+mod eol {
+    use std::convert::Infallible;
+
+    use gix_attributes::{state::ValueRef, StateRef};
+    use gix_filter::eol::{convert_to_git, AttributesDigest, AutoCrlf, Stats};
+
+    #[test]
+    fn attributes_digest() {
+        use AttributesDigest::*;
+        let value = |v: &'static str| StateRef::Value(ValueRef::from_bytes(v.as_bytes()));
+        let unspecified = StateRef::Unspecified;
+        for (text, crlf, eol, auto_crlf, expected) in [
+            (unspecified, unspecified, unspecified, AutoCrlf::Disabled, Binary),
+            (unspecified, unspecified, unspecified, AutoCrlf::Enabled, TextAutoCrlf),
+            (unspecified, unspecified, unspecified, AutoCrlf::Input, TextAutoInput),
+            (StateRef::Set, unspecified, unspecified, AutoCrlf::Disabled, Text),
+            (StateRef::Unset, unspecified, value("crlf"), AutoCrlf::Enabled, Binary),
+            (value("auto"), unspecified, unspecified, AutoCrlf::Disabled, TextAuto),
+            (
+                value("auto"),
+                unspecified,
+                value("lf"),
+                AutoCrlf::Disabled,
+                TextAutoInput,
+            ),
+            (
+                value("auto"),
+                unspecified,
+                value("crlf"),
+                AutoCrlf::Disabled,
+                TextAutoCrlf,
+            ),
+            (unspecified, unspecified, value("crlf"), AutoCrlf::Disabled, TextCrlf),
+            (StateRef::Set, unspecified, value("lf"), AutoCrlf::Disabled, TextInput),
+            (unspecified, StateRef::Unset, unspecified, AutoCrlf::Enabled, Binary),
+            (unspecified, value("input"), unspecified, AutoCrlf::Disabled, TextInput),
+            (StateRef::Set, StateRef::Unset, unspecified, AutoCrlf::Disabled, Text),
+        ] {
+            assert_eq!(
+                AttributesDigest::from_attributes(text, crlf, eol, auto_crlf),
+                expected,
+                "{text:?} {crlf:?} {eol:?} {auto_crlf:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn stats() {
+        let stats = Stats::from_bytes(b"a\r\nb\nc\rd\0\x1a");
+        assert_eq!(
+            stats,
+            Stats {
+                null: 1,
+                lone_cr: 1,
+                lone_lf: 1,
+                crlf: 1,
+                printable: 4,
+                non_printable: 1,
+            },
+            "the trailing EOF marker isn't counted"
+        );
+        assert!(stats.is_binary());
+        assert!(!Stats::from_bytes(b"text\r\nwith\ttab\n").is_binary());
+    }
+
+    #[test]
+    fn to_git() -> Result<(), Infallible> {
+        let mut buf = Vec::new();
+        let no_index = |_: &mut Vec<u8>| Ok::<_, Infallible>(false);
+        assert!(!convert_to_git(
+            b"a\r\nb\r\n",
+            AttributesDigest::Binary,
+            &mut buf,
+            no_index
+        )?);
+        assert!(!convert_to_git(b"a\nb\n", AttributesDigest::Text, &mut buf, no_index)?);
+
+        assert!(convert_to_git(
+            b"a\r\nb\rc\r\n",
+            AttributesDigest::Text,
+            &mut buf,
+            no_index
+        )?);
+        assert_eq!(buf, b"a\nb\rc\n", "lone carriage returns are kept for text");
+
+        assert!(
+            !convert_to_git(b"a\r\nb\rc\r\n", AttributesDigest::TextAuto, &mut buf, no_index)?,
+            "lone carriage returns make it binary when guessing"
+        );
+        assert!(convert_to_git(
+            b"a\r\nb\n",
+            AttributesDigest::TextAutoCrlf,
+            &mut buf,
+            no_index
+        )?);
+        assert_eq!(buf, b"a\nb\n");
+
+        let index_with_crlf = |buf: &mut Vec<u8>| {
+            buf.extend_from_slice(b"a\r\n");
+            Ok::<_, Infallible>(true)
+        };
+        assert!(
+            !convert_to_git(
+                b"a\r\nb\r\n",
+                AttributesDigest::TextAutoInput,
+                &mut buf,
+                index_with_crlf
+            )?,
+            "files committed with CRLF stay that way when guessing"
+        );
+        assert!(convert_to_git(
+            b"a\r\nb\r\n",
+            AttributesDigest::TextInput,
+            &mut buf,
+            index_with_crlf
+        )?);
+        assert_eq!(buf, b"a\nb\n");
+        Ok(())
+    }
+}
+
+mod ident {
+    use gix_filter::ident::undo;
+
+    #[test]
+    fn undo_expanded_ids() {
+        let mut buf = Vec::new();
+        assert!(!undo(b"$Id$ and $Id no colon$ and $Id: unterminated", &mut buf));
+        assert!(!undo(b"$Id: with\nnewline$", &mut buf));
+
+        assert!(undo(
+            b"a $Id: 2188d1cdee2b93a80084b61af431a49d21bc7cc0 $ b $Id: x$$Id: y$",
+            &mut buf
+        ));
+        assert_eq!(buf, b"a $Id$ b $Id$$Id$".as_slice());
+
+        assert!(undo(b"$Id: x\n$Id: y$", &mut buf));
+        assert_eq!(
+            buf,
+            b"$Id: x\n$Id$".as_slice(),
+            "the search continues after a line break"
+        );
+    }
+}
@@ -55,6 +55,8 @@ impl State {
         })
     }
     /// Return mutable entries along with their path, as obtained from `backing`.
+    ///
+    /// Changing the `id` or `mode` of entries requires [invalidating][State::invalidate_tree_path()] their paths.
     pub fn entries_mut_with_paths_in<'state, 'backing>(
         &'state mut self,
         backing: &'backing PathStorage,
@@ -129,16 +131,22 @@ impl State {
     }
 
     /// Return mutable entries in a slice.
+    ///
+    /// Changing the `id` or `mode` of entries requires [invalidating][State::invalidate_tree_path()] their paths.
     pub fn entries_mut(&mut self) -> &mut [Entry] {
         &mut self.entries
     }
 
     /// Return a writable slice to entries and read-access to their path storage at the same time.
+    ///
+    /// Changing the `id` or `mode` of entries requires [invalidating][State::invalidate_tree_path()] their paths.
     pub fn entries_mut_and_pathbacking(&mut self) -> (&mut [Entry], &PathStorage) {
         (&mut self.entries, &self.path_backing)
     }
 
     /// Return mutable entries along with their paths in an iterator.
+    ///
+    /// Changing the `id` or `mode` of entries requires [invalidating][State::invalidate_tree_path()] their paths.
     pub fn entries_mut_with_paths(&mut self) -> impl Iterator<Item = (&mut Entry, &BStr)> {
         let paths = &self.path_backing;
         self.entries.iter_mut().map(move |e| {
@@ -160,9 +168,12 @@ impl State {
 
     /// Like [`entry_index_by_path_and_stage()`][State::entry_index_by_path_and_stage()],
     /// but returns the mutable entry instead of the index.
+    ///
+    /// The `path` is [invalidated][State::invalidate_tree_path()] in the tree extension if the entry exists.
     pub fn entry_mut_by_path_and_stage(&mut self, path: &BStr, stage: entry::Stage) -> Option<&mut Entry> {
-        self.entry_index_by_path_and_stage(path, stage)
-            .map(|idx| &mut self.entries[idx])
+        let idx = self.entry_index_by_path_and_stage(path, stage)?;
+        self.invalidate_tree_path(path);
+        Some(&mut self.entries[idx])
     }
 
     /// Push a new entry containing `stat`, `id`, `flags` and `mode` and `path` to the end of our storage, without performing
@@ -175,6 +186,8 @@ impl State {
     ///
     /// Alternatively, make sure to call [sort_entries()][State::sort_entries()] before entry lookup by path to restore
     /// the invariant.
+    ///
+    /// The `path` is [invalidated][State::invalidate_tree_path()] in the tree extension.
     pub fn dangerously_push_entry(
         &mut self,
         stat: entry::Stat,
@@ -183,6 +196,7 @@ impl State {
         mode: entry::Mode,
         path: &BStr,
     ) {
+        self.invalidate_tree_path(path);
         let path = {
             let path_start = self.path_backing.len();
             self.path_backing.push_str(path);
@@ -198,6 +212,26 @@ impl State {
         });
     }
 
+    /// Remove all entries for which `should_remove(entry_index, path, entry)` returns `true`.
+    ///
+    /// The paths of removed entries are [invalidated][State::invalidate_tree_path()] in the tree extension.
+    pub fn remove_entries(&mut self, mut should_remove: impl FnMut(usize, &BStr, &mut Entry) -> bool) {
+        let mut index = 0;
+        let paths = &self.path_backing;
+        let tree = &mut self.tree;
+        self.entries.retain_mut(|e| {
+            let path = e.path_in(paths);
+            let res = !should_remove(index, path, e);
+            index += 1;
+            if !res {
+                if let Some(tree) = tree {
+                    tree.invalidate_path(path);
+                }
+            }
+            res
+        });
+    }
+
     /// Unconditionally sort entries as needed to perform lookups quickly.
     pub fn sort_entries(&mut self) {
         let path_backing = &self.path_backing;
@@ -225,6 +259,19 @@ impl State {
     pub fn tree(&self) -> Option<&extension::Tree> {
         self.tree.as_ref()
     }
+
+    /// Remove the `tree` extension and return it, which is needed once entries were changed and it may have become stale.
+    pub fn remove_tree(&mut self) -> Option<extension::Tree> {
+        self.tree.take()
+    }
+
+    /// Mark all trees in the `tree` extension that contain `path` as invalid, like `git` does when the entry at `path` is
+    /// added, removed or changed, so that they are written again by [`write_tree()`][State::write_tree()].
+    pub fn invalidate_tree_path(&mut self, path: &BStr) {
+        if let Some(tree) = &mut self.tree {
+            tree.invalidate_path(path);
+        }
+    }
     /// Access the `link` extension.
     pub fn link(&self) -> Option<&extension::Link> {
         self.link.as_ref()
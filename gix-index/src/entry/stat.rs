@@ -78,12 +78,11 @@ impl Stat {
     /// Creates stat information from the result of symlink_metadata.
     pub fn from_fs(fstat: &std::fs::Metadata) -> Result<Stat, SystemTimeError> {
         let mtime = fstat.modified().unwrap_or(std::time::UNIX_EPOCH);
-        let ctime = fstat.created().unwrap_or(std::time::UNIX_EPOCH);
 
         #[cfg(not(unix))]
         let res = Stat {
             mtime: mtime.try_into()?,
-            ctime: ctime.try_into()?,
+            ctime: fstat.created().unwrap_or(std::time::UNIX_EPOCH).try_into()?,
             dev: 0,
             ino: 0,
             uid: 0,
@@ -96,7 +95,12 @@ impl Stat {
         #[cfg(unix)]
         let res = Stat {
             mtime: mtime.try_into()?,
-            ctime: ctime.try_into()?,
+            // the time of the last status change, which git uses, as opposed to the time of creation.
+            ctime: Time {
+                // truncation to 32 bits is on purpose (we only compare the low bits)
+                secs: fstat.ctime() as u32,
+                nsecs: fstat.ctime_nsec() as u32,
+            },
             // truncating to 32 bits is fine here because
             // that's what the linux syscalls returns
             // just rust upcasts to 64 bits for some reason?
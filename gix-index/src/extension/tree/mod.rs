@@ -1,4 +1,6 @@
-use crate::extension::Signature;
+use bstr::{BStr, ByteSlice};
+
+use crate::extension::{Signature, Tree};
 
 /// The signature for tree extensions
 pub const SIGNATURE: Signature = *b"TREE";
@@ -11,6 +13,19 @@ pub use decode::decode;
 
 mod write;
 
+impl Tree {
+    /// Mark this tree and all of its children leading to `path` as invalid.
+    pub(crate) fn invalidate_path(&mut self, path: &BStr) {
+        self.num_entries = None;
+        if let Some((dir, rest)) = path.split_once_str(b"/") {
+            // Children aren't necessarily sorted by name if they were written by `git`.
+            if let Some(child) = self.children.iter_mut().find(|child| child.name.as_slice() == dir) {
+                child.invalidate_path(rest.as_bstr());
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
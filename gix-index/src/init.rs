@@ -1,5 +1,5 @@
 mod from_tree {
-    use std::collections::VecDeque;
+    use std::collections::{HashMap, VecDeque};
 
     use bstr::{BStr, BString, ByteSlice, ByteVec};
     use gix_object::{
@@ -10,7 +10,7 @@ mod from_tree {
 
     use crate::{
         entry::{Flags, Mode, Stat},
-        Entry, PathStorage, State, Version,
+        extension, Entry, PathStorage, PathStorageRef, State, Version,
     };
 
     /// Initialization
@@ -34,7 +34,8 @@ mod from_tree {
         /// Create an index [`State`][crate::State] by traversing `tree` recursively, accessing sub-trees
         /// with `find`.
         ///
-        /// **No extension data is currently produced**.
+        /// The `tree` [extension][crate::extension::Tree] is produced as well, so [writing the tree][State::write_tree()]
+        /// again is free.
         pub fn from_tree<Find>(tree: &gix_hash::oid, mut find: Find) -> Result<Self, breadthfirst::Error>
         where
             Find: for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
@@ -50,9 +51,11 @@ mod from_tree {
                 path_backing,
                 path: _,
                 path_deque: _,
+                tree_ids,
             } = delegate;
 
             entries.sort_by(|a, b| Entry::cmp_filepaths(a.path_in(&path_backing), b.path_in(&path_backing)));
+            let tree_extension = tree_extension("".into(), tree.to_owned(), &entries, &path_backing, 0, &tree_ids);
 
             Ok(State {
                 object_hash: tree.kind(),
@@ -61,7 +64,7 @@ mod from_tree {
                 entries,
                 path_backing,
                 is_sparse: false,
-                tree: None,
+                tree: Some(tree_extension),
                 link: None,
                 resolve_undo: None,
                 untracked: None,
@@ -70,11 +73,58 @@ mod from_tree {
         }
     }
 
+    /// Build the tree extension for the tree `name` with `id` containing all `entries` whose paths start at `dir_len`,
+    /// using `tree_ids` to find the ids of its sub-trees.
+    fn tree_extension(
+        name: &BStr,
+        id: gix_hash::ObjectId,
+        entries: &[Entry],
+        backing: &PathStorageRef,
+        dir_len: usize,
+        tree_ids: &HashMap<BString, gix_hash::ObjectId>,
+    ) -> extension::Tree {
+        let mut children = Vec::new();
+        let mut idx = 0;
+        while idx < entries.len() {
+            let path = entries[idx].path_in(backing);
+            match path[dir_len..].find_byte(b'/') {
+                Some(pos) => {
+                    let child_dir = &path[..dir_len + pos + 1];
+                    let end = idx
+                        + entries[idx..]
+                            .iter()
+                            .take_while(|entry| entry.path_in(backing).starts_with(child_dir))
+                            .count();
+                    let child_id = tree_ids[child_dir[..child_dir.len() - 1].as_bstr()];
+                    children.push(tree_extension(
+                        path[dir_len..dir_len + pos].as_bstr(),
+                        child_id,
+                        &entries[idx..end],
+                        backing,
+                        child_dir.len(),
+                        tree_ids,
+                    ));
+                    idx = end;
+                }
+                None => idx += 1,
+            }
+        }
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        extension::Tree {
+            name: name.as_bytes().into(),
+            id,
+            num_entries: Some(entries.len() as u32),
+            children,
+        }
+    }
+
     struct CollectEntries {
         entries: Vec<Entry>,
         path_backing: PathStorage,
         path: BString,
         path_deque: VecDeque<BString>,
+        /// The ids of all trees by their path, to produce the tree extension.
+        tree_ids: HashMap<BString, gix_hash::ObjectId>,
     }
 
     impl CollectEntries {
@@ -84,6 +134,7 @@ mod from_tree {
                 path_backing: Vec::new(),
                 path: BString::default(),
                 path_deque: VecDeque::new(),
+                tree_ids: HashMap::new(),
             }
         }
 
@@ -143,7 +194,8 @@ mod from_tree {
             }
         }
 
-        fn visit_tree(&mut self, _entry: &gix_object::tree::EntryRef<'_>) -> gix_traverse::tree::visit::Action {
+        fn visit_tree(&mut self, entry: &gix_object::tree::EntryRef<'_>) -> gix_traverse::tree::visit::Action {
+            self.tree_ids.insert(self.path.clone(), entry.oid.to_owned());
             Action::Continue
         }
 
@@ -33,6 +33,12 @@ pub mod verify;
 ///
 pub mod write;
 
+///
+pub mod write_tree;
+
+///
+pub mod merge;
+
 /// All known versions of a git index file.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
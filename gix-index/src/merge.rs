@@ -0,0 +1,244 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_object::TreeRefIter;
+use gix_traverse::tree::breadthfirst;
+
+use crate::{
+    entry::{Flags, Stage, Stat},
+    Entry, PathStorage, State,
+};
+
+/// The error returned by [`State::merge_one_way()`], [`State::merge_two_way()`] and [`State::merge_three_way()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Traverse(#[from] breadthfirst::Error),
+    #[error("The index needs to be resolved first as '{path}' is unmerged")]
+    Unmerged { path: BString },
+    #[error("Sparse indices can't be merged with trees yet")]
+    Sparse,
+    #[error("Entry '{path}' would be overwritten by merge")]
+    WouldOverwrite { path: BString },
+    #[error("'{path}' would be a file and a directory at the same time")]
+    DirectoryFileConflict { path: BString },
+}
+
+/// Merging trees into the index, like `git read-tree -m` does.
+///
+/// Note that only the index is considered, similar to `git read-tree -m -i`, so callers who want to update the worktree
+/// have to assure it won't loose changes themselves.
+impl State {
+    /// Replace all entries with the ones in `tree`, but keep the entries that already match the tree along with their
+    /// stat information, so they aren't considered changed. Sub-trees are obtained with `find`.
+    ///
+    /// This is equivalent to `git read-tree -m <tree>`, and produces the `tree` [extension][crate::extension::Tree].
+    pub fn merge_one_way<Find>(&mut self, tree: &gix_hash::oid, find: Find) -> Result<(), Error>
+    where
+        Find: for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+    {
+        let tree_extension = self.merge(&[tree], find, |_path, _index, trees| {
+            Ok(trees[0].map(|entry| (0, entry)).into_iter().collect())
+        })?;
+        self.tree = tree_extension;
+        Ok(())
+    }
+
+    /// Move the index from the `current` tree to the `target` tree, keeping all changes in the index that don't conflict
+    /// with changes between `current` and `target`, and return an error if they do. Sub-trees are obtained with `find`.
+    ///
+    /// This is equivalent to `git read-tree -m <current> <target>`, as used when switching branches.
+    /// If the index is empty, it's considered an initial checkout which takes all entries from `target`.
+    pub fn merge_two_way<Find>(
+        &mut self,
+        current: &gix_hash::oid,
+        target: &gix_hash::oid,
+        find: Find,
+    ) -> Result<(), Error>
+    where
+        Find: for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+    {
+        let is_initial_checkout = self.entries.is_empty();
+        self.merge(&[current, target], find, |path, index, trees| {
+            let (current, target) = (trees[0], trees[1]);
+            let keep_index = || Ok(index.map(|entry| (0, entry)).into_iter().collect());
+            let take_target = || Ok(target.map(|entry| (0, entry)).into_iter().collect());
+            match index {
+                Some(_) => {
+                    if (current.is_none() && (target.is_none() || same(index, target)))
+                        || (current.is_some() && target.is_some() && (same(current, target) || same(index, target)))
+                    {
+                        keep_index()
+                    } else if current.is_some() && same(index, current) {
+                        take_target()
+                    } else {
+                        Err(would_overwrite(path))
+                    }
+                }
+                None => match (current, target) {
+                    (Some(_), Some(_)) if !is_initial_checkout => {
+                        if same(current, target) {
+                            Ok(Vec::new())
+                        } else {
+                            Err(would_overwrite(path))
+                        }
+                    }
+                    _ => take_target(),
+                },
+            }
+        })?;
+        self.tree = None;
+        Ok(())
+    }
+
+    /// Merge the changes from `base` to `theirs` into the index, which has to match `ours` where it's affected.
+    /// Sub-trees are obtained with `find`.
+    ///
+    /// This is equivalent to `git read-tree -m <base> <ours> <theirs>`, so only trivial merges are performed, like
+    /// taking their version if ours didn't change it. All other changed paths are left unmerged, with the `base`, `ours` and
+    /// `theirs` versions in stage 1, 2 and 3 respectively.
+    pub fn merge_three_way<Find>(
+        &mut self,
+        base: &gix_hash::oid,
+        ours: &gix_hash::oid,
+        theirs: &gix_hash::oid,
+        find: Find,
+    ) -> Result<(), Error>
+    where
+        Find: for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+    {
+        self.merge(&[base, ours, theirs], find, |path, index, trees| {
+            let (base, ours, theirs) = (trees[0], trees[1], trees[2]);
+            let is_changed = !same(ours, theirs);
+            let ours_match = is_changed && same(base, ours);
+            let theirs_match = is_changed && same(base, theirs);
+
+            if let Some(theirs) = theirs.filter(|_| ours_match && !theirs_match) {
+                if index.is_some() && !same(index, Some(theirs)) && !same(index, ours) {
+                    return Err(would_overwrite(path));
+                }
+                return Ok(vec![(0, theirs)]);
+            }
+            if !same(index, ours) {
+                return Err(would_overwrite(path));
+            }
+            if let Some(ours) = ours {
+                if same(Some(ours), theirs) || (theirs_match && !ours_match) {
+                    return Ok(vec![(0, ours)]);
+                }
+            }
+            if ours.is_none() && theirs.is_none() && base.is_none() {
+                return Ok(Vec::new());
+            }
+
+            let mut stages = Vec::with_capacity(3);
+            if !ours_match || !theirs_match {
+                stages.extend(base.map(|entry| (1, entry)));
+            }
+            stages.extend(ours.map(|entry| (2, entry)));
+            stages.extend(theirs.map(|entry| (3, entry)));
+            Ok(stages)
+        })?;
+        self.tree = None;
+        Ok(())
+    }
+
+    /// Merge all `trees` with our entries by calling `merge(path, index_entry, tree_entries)` for each path, which returns
+    /// the entries for the path in the new index along with their stage.
+    ///
+    /// Entries at stage 0 keep the stat information of the index entry if they are the same.
+    /// Return the tree extension of the last tree.
+    fn merge<Find>(
+        &mut self,
+        trees: &[&gix_hash::oid],
+        mut find: Find,
+        mut merge: impl for<'a> FnMut(
+            &BStr,
+            Option<&'a Entry>,
+            &[Option<&'a Entry>],
+        ) -> Result<Vec<(Stage, &'a Entry)>, Error>,
+    ) -> Result<Option<crate::extension::Tree>, Error>
+    where
+        Find: for<'b> FnMut(&gix_hash::oid, &'b mut Vec<u8>) -> Option<TreeRefIter<'b>>,
+    {
+        if self.is_sparse {
+            return Err(Error::Sparse);
+        }
+        if let Some(entry) = self.entries.iter().find(|entry| entry.stage() != 0) {
+            return Err(Error::Unmerged {
+                path: entry.path_in(&self.path_backing).to_owned(),
+            });
+        }
+        let mut tree_states = trees
+            .iter()
+            .map(|tree| State::from_tree(tree, &mut find))
+            .collect::<Result<Vec<_>, _>>()?;
+        let tree_extension = tree_states.last_mut().and_then(|state| state.tree.take());
+
+        let (entries, path_backing) = {
+            let index: &State = self;
+            let mut by_path = BTreeMap::<&BStr, (Option<&Entry>, Vec<Option<&Entry>>)>::new();
+            for (path, entry) in index.entries.iter().map(|entry| (entry.path(index), entry)) {
+                by_path.entry(path).or_insert_with(|| (None, vec![None; trees.len()])).0 = Some(entry);
+            }
+            for (tree_idx, state) in tree_states.iter().enumerate() {
+                for entry in &state.entries {
+                    by_path
+                        .entry(entry.path(state))
+                        .or_insert_with(|| (None, vec![None; trees.len()]))
+                        .1[tree_idx] = Some(entry);
+                }
+            }
+
+            let mut entries = Vec::with_capacity(index.entries.len());
+            let mut path_backing = PathStorage::with_capacity(index.path_backing.len());
+            for (path, (index_entry, tree_entries)) in by_path {
+                for (stage, entry) in merge(path, index_entry, &tree_entries)? {
+                    let mut entry = match index_entry {
+                        Some(index_entry) if stage == 0 && same(Some(index_entry), Some(entry)) => index_entry.clone(),
+                        _ => Entry {
+                            stat: Stat::default(),
+                            id: entry.id,
+                            flags: Flags::from_bits_retain(stage << 12),
+                            mode: entry.mode,
+                            path: 0..0,
+                        },
+                    };
+                    let start = path_backing.len();
+                    path_backing.extend_from_slice(path);
+                    entry.path = start..path_backing.len();
+                    entries.push(entry);
+                }
+            }
+            (entries, path_backing)
+        };
+
+        let paths: HashSet<_> = entries.iter().map(|entry| entry.path_in(&path_backing)).collect();
+        for path in &paths {
+            for pos in path.find_iter("/") {
+                let dir = path[..pos].as_bstr();
+                if paths.contains(dir) {
+                    return Err(Error::DirectoryFileConflict { path: dir.to_owned() });
+                }
+            }
+        }
+
+        self.entries = entries;
+        self.path_backing = path_backing;
+        Ok(tree_extension)
+    }
+}
+
+/// Return `true` if `a` and `b` are both missing or both present with the same id and mode.
+fn same(a: Option<&Entry>, b: Option<&Entry>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.id == b.id && a.mode == b.mode,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn would_overwrite(path: &BStr) -> Error {
+    Error::WouldOverwrite { path: path.to_owned() }
+}
@@ -0,0 +1,178 @@
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+use crate::{
+    entry::{Flags, Mode},
+    extension::Tree,
+    Entry, PathStorageRef, State,
+};
+
+/// The error returned by [`State::write_tree()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error<E: std::error::Error + 'static> {
+    #[error("Cannot write a tree from an index with unmerged entry '{path}'")]
+    Unmerged { path: BString },
+    #[error("The entry at '{path}' has mode {mode:o} which can't be stored in a tree")]
+    InvalidMode { path: BString, mode: u32 },
+    #[error("Could not write a tree object")]
+    WriteTree(#[source] E),
+}
+
+/// Writing trees
+impl State {
+    /// Write the tree objects representing the entries of this index with `write(tree) -> Result<id, E>` and return the
+    /// id of the root tree, just like `git write-tree` does.
+    ///
+    /// Trees recorded as valid in the `tree` [extension][crate::extension::Tree] are reused without writing them again,
+    /// which is why their paths must be [invalidated][State::invalidate_tree_path()] if entries are changed by other
+    /// means than the mutators that do so. The extension is updated to represent the written trees afterwards.
+    ///
+    /// Entries marked for removal or as intent-to-add are skipped, and directories which end up being empty are omitted.
+    /// It's an error if the index contains unmerged entries.
+    pub fn write_tree<E>(
+        &mut self,
+        mut write: impl FnMut(&gix_object::Tree) -> Result<ObjectId, E>,
+    ) -> Result<ObjectId, Error<E>>
+    where
+        E: std::error::Error + 'static,
+    {
+        if let Some(entry) = self.entries.iter().find(|entry| entry.stage() != 0) {
+            return Err(Error::Unmerged {
+                path: entry.path_in(&self.path_backing).to_owned(),
+            });
+        }
+
+        let cached = self.tree.take();
+        let tree = match write_recursive(
+            b"".as_bstr(),
+            &self.entries,
+            &self.path_backing,
+            0,
+            cached.as_ref(),
+            &mut write,
+        ) {
+            Ok(Some(tree)) => tree,
+            Ok(None) => Tree {
+                name: Default::default(),
+                id: write(&gix_object::Tree::empty()).map_err(Error::WriteTree)?,
+                num_entries: Some(0),
+                children: Vec::new(),
+            },
+            Err(err) => {
+                self.tree = cached;
+                return Err(err);
+            }
+        };
+        let id = tree.id;
+        self.tree = Some(tree);
+        Ok(id)
+    }
+}
+
+/// Write the tree named `name` containing all `entries` whose paths start at `dir_len`, and return its representation in the
+/// tree extension, or `None` if it would be empty.
+fn write_recursive<E>(
+    name: &BStr,
+    entries: &[Entry],
+    backing: &PathStorageRef,
+    dir_len: usize,
+    cached: Option<&Tree>,
+    write: &mut impl FnMut(&gix_object::Tree) -> Result<ObjectId, E>,
+) -> Result<Option<Tree>, Error<E>>
+where
+    E: std::error::Error + 'static,
+{
+    let num_entries = entries
+        .iter()
+        .filter(|entry| !entry.flags.contains(Flags::REMOVE))
+        .count() as u32;
+    if let Some(cached) = cached.filter(|cached| cached.num_entries == Some(num_entries)) {
+        return Ok(Some(cached.clone()));
+    }
+
+    let mut tree = gix_object::Tree::empty();
+    let mut children = Vec::new();
+    let mut is_valid = true;
+    let mut idx = 0;
+    while idx < entries.len() {
+        let entry = &entries[idx];
+        let path = entry.path_in(backing);
+        let rela_path = &path[dir_len..];
+        match rela_path.find_byte(b'/') {
+            Some(pos) if !(entry.mode.is_sparse() && pos + 1 == rela_path.len()) => {
+                let child_name = rela_path[..pos].as_bstr();
+                let child_dir = &path[..dir_len + pos + 1];
+                let end = idx
+                    + entries[idx..]
+                        .iter()
+                        .take_while(|entry| entry.path_in(backing).starts_with(child_dir))
+                        .count();
+                let cached_child = cached.and_then(|cached| {
+                    cached
+                        .children
+                        .binary_search_by(|child| child.name.as_slice().cmp(child_name.as_bytes()))
+                        .ok()
+                        .map(|pos| &cached.children[pos])
+                });
+                if let Some(child) = write_recursive(
+                    child_name,
+                    &entries[idx..end],
+                    backing,
+                    child_dir.len(),
+                    cached_child,
+                    write,
+                )? {
+                    is_valid &= child.num_entries.is_some();
+                    tree.entries.push(gix_object::tree::Entry {
+                        mode: EntryMode::Tree,
+                        filename: child_name.to_owned(),
+                        oid: child.id,
+                    });
+                    children.push(child);
+                }
+                idx = end;
+            }
+            _ => {
+                idx += 1;
+                if entry.flags.contains(Flags::REMOVE) {
+                    continue;
+                }
+                if entry.flags.contains(Flags::INTENT_TO_ADD) {
+                    is_valid = false;
+                    continue;
+                }
+                let mode = match entry.mode {
+                    Mode::FILE => EntryMode::Blob,
+                    Mode::FILE_EXECUTABLE => EntryMode::BlobExecutable,
+                    Mode::SYMLINK => EntryMode::Link,
+                    Mode::COMMIT => EntryMode::Commit,
+                    Mode::DIR => EntryMode::Tree,
+                    mode => {
+                        return Err(Error::InvalidMode {
+                            path: path.to_owned(),
+                            mode: mode.bits(),
+                        })
+                    }
+                };
+                tree.entries.push(gix_object::tree::Entry {
+                    mode,
+                    filename: rela_path.trim_end_with(|c| c == '/').into(),
+                    oid: entry.id,
+                });
+            }
+        }
+    }
+
+    if tree.entries.is_empty() {
+        return Ok(None);
+    }
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Some(Tree {
+        name: name.as_bytes().into(),
+        id: write(&tree).map_err(Error::WriteTree)?,
+        num_entries: is_valid.then_some(num_entries),
+        children,
+    }))
+}
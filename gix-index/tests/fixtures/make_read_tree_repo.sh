@@ -0,0 +1,46 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config index.threads 1
+
+mkdir dir
+echo base > a; echo base > dir/b; echo base > dir/c; echo base > d; echo base > e; echo base > f
+git add . && git commit -q -m base && git tag base
+
+git checkout -q -b ours
+echo ours > a; echo ours > d; rm dir/c f; echo new > ours-new
+git add -A && git commit -q -m ours
+
+git checkout -q -b theirs base
+mkdir dir/sub
+echo theirs > dir/b; echo theirs > d; rm f; echo new > dir/sub/g; echo new > theirs-new
+git add -A && git commit -q -m theirs
+
+git checkout -q ours
+echo staged > e && git add e
+echo intent > intent-to-add && git add -N intent-to-add
+
+git write-tree > write-tree.expected
+
+function read_tree() {
+  local expected=${1:?first argument is the name of the expected output}
+  shift
+  cp .git/index .git/read-tree.index
+  GIT_INDEX_FILE=.git/read-tree.index git read-tree -m -i "$@"
+  GIT_INDEX_FILE=.git/read-tree.index git ls-files -s > "$expected"
+  rm .git/read-tree.index
+}
+
+read_tree one-way.expected theirs
+read_tree two-way.expected ours theirs
+cp .git/index .git/read-tree.index
+if GIT_INDEX_FILE=.git/read-tree.index git read-tree -m -i base ours theirs 2>/dev/null; then
+  echo "BUG: staged changes to paths not matching 'ours' must prevent three-way merges" && exit 1
+fi
+rm .git/read-tree.index
+
+GIT_INDEX_FILE=.git/clean.index git read-tree ours
+GIT_INDEX_FILE=.git/clean.index git read-tree -m -i base ours theirs
+GIT_INDEX_FILE=.git/clean.index git ls-files -s > three-way.expected
+rm .git/clean.index
@@ -19,6 +19,12 @@ fn from_tree() -> crate::Result {
 
         let expected_state = repo.index()?;
         let actual_state = State::from_tree(&tree_id, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+        assert_eq!(
+            actual_state.tree().map(|tree| tree.id),
+            Some(tree_id.detach()),
+            "the tree extension is produced as well"
+        );
+        actual_state.verify_extensions(true, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
 
         compare_states(&actual_state, &expected_state, fixture)
     }
@@ -0,0 +1,142 @@
+use gix::prelude::FindExt;
+use gix_hash::ObjectId;
+use gix_index::{merge::Error, State};
+use gix_testtools::scripted_fixture_read_only_standalone;
+
+struct Fixture {
+    repo: gix::Repository,
+    dir: std::path::PathBuf,
+}
+
+impl Fixture {
+    fn new() -> crate::Result<Self> {
+        let dir = scripted_fixture_read_only_standalone("make_read_tree_repo.sh")?;
+        Ok(Fixture {
+            repo: gix::open(&dir)?,
+            dir,
+        })
+    }
+
+    fn tree(&self, name: &str) -> crate::Result<ObjectId> {
+        Ok(self.repo.rev_parse_single(name)?.object()?.peel_to_tree()?.id)
+    }
+
+    fn find(&self) -> impl for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Option<gix::objs::TreeRefIter<'a>> + '_ {
+        move |oid, buf| self.repo.objects.find_tree_iter(oid, buf).ok()
+    }
+
+    fn expected(&self, name: &str) -> crate::Result<String> {
+        Ok(std::fs::read_to_string(self.dir.join(name).with_extension("expected"))?)
+    }
+}
+
+/// Produce the output of `git ls-files -s`.
+fn ls_files(state: &State) -> String {
+    state
+        .entries()
+        .iter()
+        .map(|entry| {
+            format!(
+                "{:06o} {} {}\t{}\n",
+                entry.mode.bits(),
+                entry.id,
+                entry.stage(),
+                entry.path(state)
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn one_way() -> crate::Result {
+    let fixture = Fixture::new()?;
+    let mut index = fixture.repo.open_index()?;
+    index.merge_one_way(&fixture.tree("theirs")?, fixture.find())?;
+    assert_eq!(ls_files(&index), fixture.expected("one-way")?);
+    assert_eq!(
+        index.tree().map(|tree| tree.id),
+        Some(fixture.tree("theirs")?),
+        "the tree extension is set"
+    );
+
+    let mut index = fixture.repo.open_index()?;
+    let previous = index.clone();
+    index.merge_one_way(&fixture.tree("ours")?, fixture.find())?;
+    for entry in index.entries() {
+        let path = entry.path(&index);
+        if path == "e" {
+            assert_eq!(entry.stat, Default::default(), "changed entries are replaced");
+        } else {
+            let previous = previous.entry_by_path_and_stage(path, 0).expect("present");
+            assert_eq!(
+                (entry.stat, entry.id, entry.flags, entry.mode),
+                (previous.stat, previous.id, previous.flags, previous.mode),
+                "unchanged entries are kept along with their stat information"
+            );
+        }
+    }
+    assert!(
+        index.entry_by_path_and_stage("intent-to-add".into(), 0).is_none(),
+        "entries not in the tree are removed"
+    );
+    Ok(())
+}
+
+#[test]
+fn two_way() -> crate::Result {
+    let fixture = Fixture::new()?;
+    let mut index = fixture.repo.open_index()?;
+    index.merge_two_way(&fixture.tree("ours")?, &fixture.tree("theirs")?, fixture.find())?;
+    assert_eq!(
+        ls_files(&index),
+        fixture.expected("two-way")?,
+        "changes in the index are kept if they don't conflict"
+    );
+    assert!(index.tree().is_none());
+
+    let mut index = State::new(fixture.repo.object_hash());
+    index.merge_two_way(&fixture.tree("ours")?, &fixture.tree("theirs")?, fixture.find())?;
+    assert_eq!(
+        ls_files(&index),
+        ls_files(&State::from_tree(&fixture.tree("theirs")?, fixture.find())?),
+        "an empty index is an initial checkout of the target tree"
+    );
+
+    let mut index = fixture.repo.open_index()?;
+    let err = index
+        .merge_two_way(&fixture.tree("base")?, &fixture.tree("theirs")?, fixture.find())
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::WouldOverwrite { path } if path == "d"),
+        "changes to paths that change between trees prevent the merge: {err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn three_way() -> crate::Result {
+    let fixture = Fixture::new()?;
+    let (base, ours, theirs) = (fixture.tree("base")?, fixture.tree("ours")?, fixture.tree("theirs")?);
+    let mut index = State::from_tree(&ours, fixture.find())?;
+    index.merge_three_way(&base, &ours, &theirs, fixture.find())?;
+    assert_eq!(ls_files(&index), fixture.expected("three-way")?);
+    assert!(index.tree().is_none());
+
+    let err = index
+        .merge_three_way(&base, &ours, &theirs, fixture.find())
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Unmerged { path } if path == "d"),
+        "unmerged entries must be resolved first: {err:?}"
+    );
+
+    let mut index = fixture.repo.open_index()?;
+    let err = index
+        .merge_three_way(&base, &ours, &theirs, fixture.find())
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::WouldOverwrite { path } if path == "e"),
+        "the index must match 'ours': {err:?}"
+    );
+    Ok(())
+}
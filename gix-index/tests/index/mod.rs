@@ -6,6 +6,8 @@ mod access;
 mod entry;
 mod file;
 mod init;
+mod merge;
+mod write_tree;
 
 pub fn hex_to_id(hex: &str) -> ObjectId {
     ObjectId::from_hex(hex.as_bytes()).expect("40 bytes hex")
@@ -0,0 +1,147 @@
+use std::convert::Infallible;
+
+use gix::prelude::FindExt;
+use gix_hash::ObjectId;
+use gix_index::{
+    entry::{Flags, Mode, Stage},
+    verify::extensions::no_find,
+    State,
+};
+use gix_testtools::scripted_fixture_read_only_standalone;
+
+/// Compute the ids of trees without writing them, and count how many trees were written.
+fn hash_tree(count: &mut usize) -> impl FnMut(&gix::objs::Tree) -> Result<ObjectId, Infallible> + '_ {
+    move |tree| {
+        use gix::objs::WriteTo;
+        *count += 1;
+        let mut buf = Vec::new();
+        tree.write_to(&mut buf).expect("in-memory writes don't fail");
+        Ok(gix::objs::compute_hash(
+            gix_hash::Kind::Sha1,
+            gix::objs::Kind::Tree,
+            &buf,
+        ))
+    }
+}
+
+#[test]
+fn like_git_and_reusing_the_tree_extension() -> crate::Result {
+    let repo_dir = scripted_fixture_read_only_standalone("make_read_tree_repo.sh")?;
+    let repo = gix::open(&repo_dir)?;
+    let expected = ObjectId::from_hex(
+        std::fs::read_to_string(repo_dir.join("write-tree.expected"))?
+            .trim()
+            .as_bytes(),
+    )?;
+
+    let mut index = repo.open_index()?;
+    index.remove_tree();
+    let mut count = 0;
+    assert_eq!(
+        index.write_tree(hash_tree(&mut count))?,
+        expected,
+        "intent-to-add entries are skipped"
+    );
+    assert_eq!(count, 2, "all trees are written");
+    let tree = index.tree().expect("tree extension was added");
+    assert_eq!(tree.id, expected);
+    assert_eq!(
+        tree.num_entries, None,
+        "the intent-to-add entry makes the root tree invalid"
+    );
+    index.verify_extensions(false, no_find)?;
+
+    let mut count = 0;
+    assert_eq!(index.write_tree(hash_tree(&mut count))?, expected);
+    assert_eq!(count, 1, "only the root tree is written again as it's invalid");
+
+    let tree_id = repo.head_commit()?.tree_id()?.detach();
+    let mut index = State::from_tree(&tree_id, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    let mut count = 0;
+    assert_eq!(index.write_tree(hash_tree(&mut count))?, tree_id);
+    assert_eq!(count, 0, "the tree extension produced with the index is used entirely");
+    Ok(())
+}
+
+#[test]
+fn mutators_invalidate_the_tree_extension() -> crate::Result {
+    let repo_dir = scripted_fixture_read_only_standalone("make_read_tree_repo.sh")?;
+    let repo = gix::open(&repo_dir)?;
+    let tree_id = repo.rev_parse_single("theirs")?.object()?.peel_to_tree()?.id;
+    let mut index = State::from_tree(&tree_id, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    let without_tree_extension = |index: &State| {
+        let mut index = index.clone();
+        index.remove_tree();
+        index.write_tree(hash_tree(&mut 0))
+    };
+
+    let other = index.entry_by_path_and_stage("a".into(), 0).expect("present").id;
+    index
+        .entry_mut_by_path_and_stage("dir/sub/g".into(), 0)
+        .expect("present")
+        .id = other;
+    let expected = without_tree_extension(&index)?;
+    assert_ne!(expected, tree_id);
+    let mut count = 0;
+    assert_eq!(
+        index.write_tree(hash_tree(&mut count))?,
+        expected,
+        "the blob changed in place is picked up"
+    );
+    assert_eq!(
+        count, 3,
+        "only the trees leading to the changed entry are written again"
+    );
+
+    index.remove_entries(|_, path, _| path == "dir/b");
+    let expected = without_tree_extension(&index)?;
+    let mut count = 0;
+    assert_eq!(index.write_tree(hash_tree(&mut count))?, expected);
+    assert_eq!(count, 2, "'dir/sub' is still valid");
+
+    index.dangerously_push_entry(
+        Default::default(),
+        other,
+        Flags::empty(),
+        Mode::FILE,
+        "dir/sub/h".into(),
+    );
+    index.sort_entries();
+    let expected = without_tree_extension(&index)?;
+    let mut count = 0;
+    assert_eq!(index.write_tree(hash_tree(&mut count))?, expected);
+    assert_eq!(count, 3);
+    Ok(())
+}
+
+#[test]
+fn empty_index_produces_empty_tree() -> crate::Result {
+    let mut index = State::new(gix_hash::Kind::Sha1);
+    let mut count = 0;
+    assert_eq!(
+        index.write_tree(hash_tree(&mut count))?,
+        ObjectId::empty_tree(gix_hash::Kind::Sha1)
+    );
+    assert_eq!(count, 1);
+    Ok(())
+}
+
+#[test]
+fn unmerged_entries_are_an_error() -> crate::Result {
+    let repo_dir = scripted_fixture_read_only_standalone("make_read_tree_repo.sh")?;
+    let repo = gix::open(&repo_dir)?;
+    let find =
+        |name: &str| -> crate::Result<ObjectId> { Ok(repo.rev_parse_single(name)?.object()?.peel_to_tree()?.id) };
+    let mut index = State::from_tree(&find("ours")?, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    index.merge_three_way(&find("base")?, &find("ours")?, &find("theirs")?, |oid, buf| {
+        repo.objects.find_tree_iter(oid, buf).ok()
+    })?;
+    assert!(index.entries().iter().any(|entry| entry.stage() != Stage::default()));
+
+    let err = index.write_tree(hash_tree(&mut 0)).unwrap_err();
+    assert!(
+        matches!(&err, gix_index::write_tree::Error::Unmerged { path } if path == "d"),
+        "{err:?}"
+    );
+    Ok(())
+}
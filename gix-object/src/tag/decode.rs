@@ -43,10 +43,15 @@ pub fn git_tag<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(i: &'a [u8]
     ))
 }
 
-pub fn message<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], (&'a BStr, Option<&'a BStr>), E> {
-    const PGP_SIGNATURE_BEGIN: &[u8] = b"\n-----BEGIN PGP SIGNATURE-----";
-    const PGP_SIGNATURE_END: &[u8] = b"-----END PGP SIGNATURE-----";
+/// The markers at the beginning and the end of the signatures which may follow the message, created by `gpg`, `gpgsm`
+/// or `ssh-keygen` respectively.
+const SIGNATURE_MARKERS: &[(&[u8], &[u8])] = &[
+    (b"\n-----BEGIN PGP SIGNATURE-----", b"-----END PGP SIGNATURE-----"),
+    (b"\n-----BEGIN SIGNED MESSAGE-----", b"-----END SIGNED MESSAGE-----"),
+    (b"\n-----BEGIN SSH SIGNATURE-----", b"-----END SSH SIGNATURE-----"),
+];
 
+pub fn message<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], (&'a BStr, Option<&'a BStr>), E> {
     if i.is_empty() {
         return Ok((i, (i.as_bstr(), None)));
     }
@@ -57,24 +62,32 @@ pub fn message<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], (&
             return Ok((&[], (&[], &[])));
         }
         // an empty signature message signals that there is none - the function signature is needed
-        // to work with 'alt(…)'. PGP signatures are never empty
+        // to work with 'alt(…)'. Signatures are never empty
         Ok((&[], (i, &[])))
     }
-    let (i, (message, signature)) = alt((
-        tuple((
-            take_until(PGP_SIGNATURE_BEGIN),
-            preceded(
-                tag(NL),
-                recognize(tuple((
-                    tag(&PGP_SIGNATURE_BEGIN[1..]),
-                    take_until(PGP_SIGNATURE_END),
-                    tag(PGP_SIGNATURE_END),
-                    take_while(|_| true),
-                ))),
-            ),
-        )),
-        all_to_end,
-    ))(i)?;
+    fn message_and_signature<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], (&'a [u8], &'a [u8]), E> {
+        // The signature that starts first wins, even if the message happens to contain another kind of signature.
+        SIGNATURE_MARKERS
+            .iter()
+            .filter_map(|(begin, end)| {
+                tuple::<_, _, E, _>((
+                    take_until(*begin),
+                    preceded(
+                        tag(NL),
+                        recognize(tuple((
+                            tag(&begin[1..]),
+                            take_until(*end),
+                            tag(*end),
+                            take_while(|_| true),
+                        ))),
+                    ),
+                ))(i)
+                .ok()
+            })
+            .min_by_key(|(_, (message, _))| message.len())
+            .ok_or_else(|| nom::Err::Error(E::from_error_kind(i, nom::error::ErrorKind::TakeUntil)))
+    }
+    let (i, (message, signature)) = alt((message_and_signature, all_to_end))(i)?;
     let (i, _) = opt(tag(NL))(i)?;
     Ok((
         i,
@@ -131,6 +131,31 @@ mod from_bytes {
         Ok(())
     }
 
+    #[test]
+    fn signed_with_ssh() -> crate::Result {
+        assert_eq!(
+            TagRef::from_bytes(&fixture_bytes("tag", "signed-ssh.txt"))?,
+            TagRef {
+                target: b"de7f3fea7468f6822e91bc9ed7e4643b529d990a".as_bstr(),
+                name: b"v1".as_bstr(),
+                target_kind: Kind::Commit,
+                message: b"signed with ssh".as_bstr(),
+                tagger: Some(signature(1592381636)),
+                pgp_signature: Some(
+                    b"-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAADMAAAALc3NoLWVkMjU1MTkAAAAgB/5EiIyxQAV4na8g555LVq83Xy
+YymsFmDVo62rdns8wAAAADZ2l0AAAAAAAAAAZzaGE1MTIAAABTAAAAC3NzaC1lZDI1NTE5
+AAAAQLWD5+RT3ZKHX1O0NTC+03MleMkYpjQHsQBeWe3yqmJ4vnvl7Zj9tiLwEY7IjH4Q1K
+Fc9egKLnySkn+6mMkAzgM=
+-----END SSH SIGNATURE-----
+"
+                    .as_bstr()
+                )
+            }
+        );
+        Ok(())
+    }
+
     #[test]
     fn empty() -> crate::Result {
         assert_eq!(
@@ -130,6 +130,33 @@ mod from_bytes {
     }
 }
 
+mod entry {
+    use gix_object::tree::{Entry, EntryMode};
+
+    #[test]
+    fn sorts_like_git() {
+        let entry = |mode, filename: &str| Entry {
+            mode,
+            filename: filename.into(),
+            oid: gix_hash::ObjectId::null(gix_hash::Kind::Sha1),
+        };
+        let mut entries = vec![
+            entry(EntryMode::Tree, "a"),
+            entry(EntryMode::Blob, "a.b"),
+            entry(EntryMode::Blob, "a0"),
+            entry(EntryMode::Blob, "b"),
+            entry(EntryMode::Blob, "a-b"),
+        ];
+        entries.sort();
+        assert_eq!(
+            entries.iter().map(|e| e.filename.to_string()).collect::<Vec<_>>(),
+            ["a-b", "a.b", "a", "a0", "b"],
+            "trees compare as if their name ended with a slash"
+        );
+        assert!(entry(EntryMode::Blob, "a") < entry(EntryMode::Blob, "a.b"));
+    }
+}
+
 mod entry_mode {
     use gix_object::tree::EntryMode;
 
@@ -95,6 +95,7 @@ use store::types;
 ///   enter the same branch.
 /// - sync with the state on disk if objects aren't found to catch up with changes if an object seems to be missing.
 ///    - turn off the behaviour above for all handles if objects are expected to be missing due to spare checkouts.
+/// - obtain missing objects from elsewhere using a [lazy fetch function][Store::set_lazy_fetch()], as needed for partial clones.
 pub struct Store {
     /// The central write lock without which the slotmap index can't be changed.
     write: parking_lot::Mutex<()>,
@@ -130,6 +131,8 @@ pub struct Store {
     use_multi_pack_index: bool,
     /// The hash kind to use for some operations
     object_hash: gix_hash::Kind,
+    /// A function to call if an object couldn't be found, to make it available in the object database.
+    pub(crate) lazy_fetch: parking_lot::Mutex<Option<Arc<store::LazyFetch>>>,
 }
 
 /// Create a new cached handle to the object store with support for additional options.
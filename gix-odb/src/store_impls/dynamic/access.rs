@@ -1,4 +1,6 @@
-use crate::Store;
+use std::sync::Arc;
+
+use crate::{store::LazyFetch, Store};
 
 impl Store {
     /// The root path at which we expect to find all objects and packs, and which is the source of the
@@ -21,4 +23,19 @@ impl Store {
     pub fn replacements(&self) -> impl Iterator<Item = (gix_hash::ObjectId, gix_hash::ObjectId)> + '_ {
         self.replacements.iter().cloned()
     }
+
+    /// Set the function to call if an object can't be found to `lazy_fetch`, or unset it with `None`, returning the previous one.
+    ///
+    /// Handles only call it when looking up objects or their headers, but not to check if an object is contained, and only if
+    /// they may [refresh][super::RefreshMode::AfterAllIndicesLoaded] to learn about the new objects and don't
+    /// [skip it][super::Handle::skip_lazy_fetch]. Use [`Handle::lazy_fetch_missing()`][super::Handle::lazy_fetch_missing()]
+    /// to fetch many objects at once.
+    pub fn set_lazy_fetch(&self, lazy_fetch: Option<Arc<LazyFetch>>) -> Option<Arc<LazyFetch>> {
+        std::mem::replace(&mut *self.lazy_fetch.lock(), lazy_fetch)
+    }
+
+    /// Return the function to call if an object can't be found, if one was set.
+    pub fn lazy_fetch(&self) -> Option<Arc<LazyFetch>> {
+        self.lazy_fetch.lock().clone()
+    }
 }
@@ -33,6 +33,20 @@ pub(crate) mod error {
             /// The original object to lookup
             id: gix_hash::ObjectId,
         },
+        #[error("The missing object {id} could not be fetched")]
+        LazyFetch {
+            #[source]
+            source: Box<dyn std::error::Error + Send + Sync + 'static>,
+            /// The object that was missing
+            id: gix_hash::ObjectId,
+        },
+        #[error("{} missing objects could not be fetched", .ids.len())]
+        LazyFetchMany {
+            #[source]
+            source: Box<dyn std::error::Error + Send + Sync + 'static>,
+            /// The objects that were missing
+            ids: Vec<gix_hash::ObjectId>,
+        },
         #[error("An error occurred when looking up a ref delta base object {} to decode {}", .base_id, .id)]
         DeltaBaseLookup {
             #[source]
@@ -107,6 +121,7 @@ where
             }
         }
 
+        let mut fetched = false;
         'outer: loop {
             {
                 let marker = snapshot.marker;
@@ -292,11 +307,59 @@ where
                     *snapshot = new_snapshot;
                     self.clear_cache();
                 }
-                None => return Ok(None),
+                None => {
+                    // Fetch objects that are missing only once, and only for the object that was asked for, not for its bases.
+                    if recursion.is_none() && !fetched && self.lazy_fetch_one(id)? {
+                        fetched = true;
+                        continue;
+                    }
+                    return Ok(None);
+                }
             }
         }
     }
 
+    /// Obtain all objects with `ids` that are missing with a single call to the [lazy fetch function][super::Store::set_lazy_fetch()]
+    /// of the store, if one is set and may be used, which is preferable to having each lookup fetch its object one at a time.
+    pub fn lazy_fetch_missing(&self, ids: impl IntoIterator<Item = gix_hash::ObjectId>) -> Result<(), Error> {
+        let lazy_fetch = match self.lazy_fetch() {
+            Some(lazy_fetch) => lazy_fetch,
+            None => return Ok(()),
+        };
+        let mut missing: Vec<_> = ids
+            .into_iter()
+            .filter(|id| !gix_pack::Find::contains(self, id))
+            .collect();
+        missing.sort();
+        missing.dedup();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        lazy_fetch(&missing).map_err(|source| Error::LazyFetchMany { source, ids: missing })
+    }
+
+    /// Return the lazy fetch function of the store if it is set and this handle may use it.
+    fn lazy_fetch(&self) -> Option<std::sync::Arc<super::LazyFetch>> {
+        match self.refresh {
+            super::RefreshMode::AfterAllIndicesLoaded if !self.skip_lazy_fetch => self.store.lazy_fetch(),
+            _ => None,
+        }
+    }
+
+    /// Call the lazy fetch function of the store for the missing object `id` if it is set and this handle may use it,
+    /// and return `true` if it was called and the lookup should be tried once more.
+    pub(crate) fn lazy_fetch_one(&self, id: &gix_hash::oid) -> Result<bool, Error> {
+        match self.lazy_fetch() {
+            Some(lazy_fetch) => lazy_fetch(&[id.to_owned()])
+                .map(|()| true)
+                .map_err(|source| Error::LazyFetch {
+                    source,
+                    id: id.to_owned(),
+                }),
+            None => Ok(false),
+        }
+    }
+
     pub(crate) fn clear_cache(&self) {
         self.packed_object_count.borrow_mut().take();
     }
@@ -35,6 +35,7 @@ where
             }
         }
 
+        let mut fetched = false;
         'outer: loop {
             {
                 let marker = snapshot.marker;
@@ -169,7 +170,14 @@ where
                     *snapshot = new_snapshot;
                     self.clear_cache();
                 }
-                None => return Ok(None),
+                None => {
+                    // Fetch objects that are missing only once, and only for the object that was asked for, not for its bases.
+                    if recursion.is_none() && !fetched && self.lazy_fetch_one(id)? {
+                        fetched = true;
+                        continue;
+                    }
+                    return Ok(None);
+                }
             }
         }
     }
@@ -21,6 +21,9 @@ where
 
     /// If true, replacements will not be performed even if these are available.
     pub ignore_replacements: bool,
+    /// If true, missing objects are not obtained with the [lazy fetch function][Store::set_lazy_fetch()] of the store,
+    /// which is useful if their absence is expected or merely checked for.
+    pub skip_lazy_fetch: bool,
 
     pub(crate) token: Option<handle::Mode>,
     snapshot: RefCell<load_index::Snapshot>,
@@ -45,6 +48,13 @@ impl Default for RefreshMode {
     }
 }
 
+/// A function to obtain the objects with the given ids from elsewhere and write them into the object database,
+/// like the promisor remote of a partial clone.
+///
+/// It's called by [`Handle`]s if an object couldn't be found, and they try to find it again if it returns `Ok(())`.
+pub type LazyFetch =
+    dyn Fn(&[gix_hash::ObjectId]) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> + Send + Sync;
+
 impl RefreshMode {
     /// Set this refresh mode to never refresh.
     pub fn never(&mut self) {
@@ -215,6 +215,88 @@ fn write() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn lazy_fetch() -> crate::Result {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let dir = tempfile::tempdir()?;
+    let mut handle = gix_odb::at(dir.path())?;
+    let id = hex_to_id("95d09f2b10159347eece71399a7e2e907ea3df4f");
+    let calls = Arc::new(AtomicUsize::default());
+    handle.store_ref().set_lazy_fetch(Some(Arc::new({
+        let calls = calls.clone();
+        let objects_dir = dir.path().to_owned();
+        move |ids: &[ObjectId]| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(ids, [hex_to_id("95d09f2b10159347eece71399a7e2e907ea3df4f")]);
+            gix_odb::loose::Store::at(&objects_dir, gix_hash::Kind::Sha1)
+                .write_buf(gix_object::Kind::Blob, b"hello world")?;
+            Ok(())
+        }
+    })));
+
+    assert!(!handle.contains(id), "checking for existence doesn't fetch");
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    let mut buf = Vec::new();
+    assert_eq!(handle.find(id, &mut buf)?.data, b"hello world");
+    assert_eq!(calls.load(Ordering::SeqCst), 1, "the missing object was fetched");
+    assert_eq!(handle.try_header(id)?.map(|h| h.size()), Some(11));
+    assert_eq!(calls.load(Ordering::SeqCst), 1, "objects that exist aren't fetched");
+
+    handle
+        .store_ref()
+        .set_lazy_fetch(Some(Arc::new(|_ids: &[ObjectId]| Err("offline".into()))));
+    let missing = hex_to_id("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+    let err = handle.try_find(missing, &mut buf).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The missing object aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa could not be fetched"
+    );
+
+    handle.skip_lazy_fetch = true;
+    assert!(
+        handle.try_find(missing, &mut buf)?.is_none(),
+        "lookups may skip fetching, for instance to merely learn if an object exists"
+    );
+    handle.skip_lazy_fetch = false;
+
+    let fetched = Arc::new(std::sync::Mutex::new(Vec::new()));
+    handle.store_ref().set_lazy_fetch(Some(Arc::new({
+        let fetched = fetched.clone();
+        move |ids: &[ObjectId]| {
+            fetched.lock().unwrap().push(ids.to_vec());
+            Ok(())
+        }
+    })));
+    let other_missing = hex_to_id("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+    handle.lazy_fetch_missing([other_missing, id, missing, other_missing])?;
+    assert_eq!(
+        *fetched.lock().unwrap(),
+        [vec![missing, other_missing]],
+        "all missing objects are fetched at once, without duplicates"
+    );
+
+    fetched.lock().unwrap().clear();
+    assert!(handle.try_find(missing, &mut buf)?.is_none());
+    assert!(handle.try_header(missing)?.is_none());
+    assert_eq!(
+        *fetched.lock().unwrap(),
+        [vec![missing], vec![missing]],
+        "objects that the fetch doesn't provide are looked up once more, but not fetched again"
+    );
+
+    handle.refresh_never();
+    assert!(
+        handle.try_find(missing, &mut buf)?.is_none(),
+        "handles that can't refresh don't fetch as they wouldn't see the new objects"
+    );
+    Ok(())
+}
+
 #[test]
 fn object_replacement() -> crate::Result {
     let dir = gix_testtools::scripted_fixture_read_only("make_replaced_history.sh")?;
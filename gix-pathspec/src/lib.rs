@@ -9,6 +9,8 @@ use bstr::BString;
 ///
 pub mod parse;
 
+mod matching;
+
 /// The output of a pathspec [parsing][parse()] operation. It can be used to match against a one or more paths.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
 pub struct Pattern {
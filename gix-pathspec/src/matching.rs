@@ -0,0 +1,44 @@
+use bstr::{BStr, ByteSlice};
+
+use crate::{MagicSignature, MatchMode, Pattern};
+
+/// Matching
+impl Pattern {
+    /// Return `true` if the repository-relative and `/`-separated `path` is matched by this pattern, which is the case
+    /// if it's the same path, if `path` is inside of the directory this pattern names, or if the pattern matches it as
+    /// glob according to its [search mode][MatchMode].
+    ///
+    /// An empty pattern matches all paths. Note that [exclusion][MagicSignature::EXCLUDE] and attributes aren't
+    /// considered here, and that the pattern is expected to be relative to the root of the repository already.
+    pub fn matches_path(&self, path: &BStr) -> bool {
+        let pattern = self.path.as_bstr();
+        if pattern.is_empty() {
+            return true;
+        }
+        let ignore_case = self.signature.contains(MagicSignature::ICASE);
+
+        if path.len() >= pattern.len() {
+            let (prefix, rest) = path.split_at(pattern.len());
+            let prefix_matches = if ignore_case {
+                prefix.eq_ignore_ascii_case(pattern)
+            } else {
+                prefix == pattern.as_bytes()
+            };
+            if prefix_matches && (rest.is_empty() || rest[0] == b'/' || pattern.ends_with(b"/")) {
+                return true;
+            }
+        }
+
+        let mode = match self.search_mode {
+            MatchMode::Literal => return false,
+            MatchMode::ShellGlob => gix_glob::wildmatch::Mode::empty(),
+            MatchMode::PathAwareGlob => gix_glob::wildmatch::Mode::NO_MATCH_SLASH_LITERAL,
+        };
+        let mode = if ignore_case {
+            mode | gix_glob::wildmatch::Mode::IGNORE_CASE
+        } else {
+            mode
+        };
+        gix_glob::wildmatch(pattern, path, mode)
+    }
+}
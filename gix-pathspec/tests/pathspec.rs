@@ -471,3 +471,45 @@ mod parse {
         *base == 0
     }
 }
+
+mod matching {
+    use gix_pathspec::parse;
+
+    fn matches(spec: &str, path: &str) -> bool {
+        parse(spec.as_bytes()).expect("valid").matches_path(path.into())
+    }
+
+    #[test]
+    fn paths_and_directories() {
+        for (spec, path, expected) in [
+            ("a", "a", true),
+            ("a", "a/b/c", true),
+            ("a", "ab", false),
+            ("a/", "a/b", true),
+            ("a/", "a", false),
+            ("a/b", "a", false),
+            ("a/b", "A/B", false),
+            (":(icase)a/b", "A/B/c", true),
+        ] {
+            assert_eq!(matches(spec, path), expected, "{spec:?} {path:?}");
+        }
+    }
+
+    #[test]
+    fn globs() {
+        for (spec, path, expected) in [
+            ("*.rs", "lib.rs", true),
+            ("*.rs", "src/lib.rs", true),
+            (":(glob)*.rs", "src/lib.rs", false),
+            (":(glob)**/*.rs", "src/lib.rs", true),
+            (":(glob)src/*", "src/lib.rs", true),
+            (":(literal)*.rs", "lib.rs", false),
+            (":(literal)*.rs", "*.rs", true),
+            (":(icase)*.RS", "src/lib.rs", true),
+            ("s?c", "src/lib.rs", false),
+            ("s*", "src/lib.rs", true),
+        ] {
+            assert_eq!(matches(spec, path), expected, "{spec:?} {path:?}");
+        }
+    }
+}
@@ -51,12 +51,17 @@ impl<'s, 'p> Transaction<'s, 'p> {
                         let log_update = match new {
                             Target::Symbolic(_) => {
                                 // no reflog for symref changes, unless the ref is new and we can obtain a peeled id
-                                // identified by the expectation of what could be there, as is the case when cloning.
+                                // identified by the expectation of what could be there, as is the case when cloning,
+                                // or if both the previous and the new target point to an object, as is the case when
+                                // switching branches.
                                 match expected {
                                     PreviousValue::ExistingMustMatch(Target::Peeled(oid)) => {
                                         Some((Some(gix_hash::ObjectId::null(oid.kind())), oid))
                                     }
-                                    _ => None,
+                                    _ => change
+                                        .previous_resolved_oid
+                                        .zip(change.new_resolved_oid.as_ref())
+                                        .map(|(previous, new)| (Some(previous), new)),
                                 }
                             }
                             Target::Peeled(new_oid) => {
@@ -64,12 +69,17 @@ impl<'s, 'p> Transaction<'s, 'p> {
                                     PreviousValue::MustExistAndMatch(Target::Peeled(oid)) => Some(oid.to_owned()),
                                     _ => None,
                                 }
-                                .or(change.leaf_referent_previous_oid);
+                                .or(change.leaf_referent_previous_oid)
+                                .or(change.previous_resolved_oid);
                                 Some((previous, new_oid))
                             }
                         };
                         if let Some((previous, new_oid)) = log_update {
-                            let do_update = previous.as_ref().map_or(true, |previous| previous != new_oid);
+                            // Changing the target always leaves a trace, even if the object stays the same like when
+                            // detaching `HEAD` at the commit of its branch.
+                            let do_update = matches!(new, Target::Symbolic(_))
+                                || change.previous_resolved_oid.is_some()
+                                || previous.as_ref().map_or(true, |previous| previous != new_oid);
                             if do_update {
                                 self.store.reflog_create_or_append(
                                     change.update.name.as_ref(),
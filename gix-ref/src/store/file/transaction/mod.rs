@@ -46,6 +46,11 @@ pub(in crate::store_impl::file) struct Edit {
     /// For symbolic refs, this is the previous OID to put into the reflog instead of our own previous value. It's the
     /// peeled value of the leaf referent.
     leaf_referent_previous_oid: Option<ObjectId>,
+    /// If the reference is changed, this is the OID its previous target resolved to, for use in the reflog
+    /// if there is no other previous value.
+    previous_resolved_oid: Option<ObjectId>,
+    /// If the reference is changed to a new symbolic target, this is the OID the new target resolves to, for use in the reflog.
+    new_resolved_oid: Option<ObjectId>,
 }
 
 impl Edit {
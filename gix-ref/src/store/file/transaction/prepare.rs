@@ -171,6 +171,13 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     }
                 }
 
+                if let Some(existing) = existing_ref.as_ref().filter(|existing| existing.target != *new) {
+                    change.previous_resolved_oid = resolve_to_id(store, packed, existing.target.clone());
+                    if let Target::Symbolic(_) = new {
+                        change.new_resolved_oid = resolve_to_id(store, packed, new.clone());
+                    }
+                }
+
                 let (is_effective, is_symbolic) = if let Some(existing) = existing_ref {
                     let (effective, is_symbolic) = new_would_change_existing(new, &existing.target);
                     *expected = PreviousValue::MustExistAndMatch(existing.target);
@@ -218,6 +225,8 @@ impl<'s, 'p> Transaction<'s, 'p> {
                 lock: None,
                 parent_index: None,
                 leaf_referent_previous_oid: None,
+                previous_resolved_oid: None,
+                new_resolved_oid: None,
             })
             .collect();
         updates
@@ -234,6 +243,8 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     lock: None,
                     parent_index: Some(idx),
                     leaf_referent_previous_oid: None,
+                    previous_resolved_oid: None,
+                    new_resolved_oid: None,
                 },
             )
             .map_err(Error::PreprocessingFailed)?;
@@ -391,6 +402,16 @@ impl<'s, 'p> Transaction<'s, 'p> {
         Ok(self)
     }
 
+    /// Return the edits of this prepared transaction, or nothing if it wasn't prepared yet.
+    ///
+    /// Just like the edits returned by [`rollback()`][Self::rollback()], they have been split and their previous values
+    /// are the ones seen in storage after the references were locked.
+    pub fn edits(&self) -> impl Iterator<Item = &RefEdit> {
+        self.updates
+            .iter()
+            .flat_map(|updates| updates.iter().map(|u| &u.update))
+    }
+
     /// Rollback all intermediate state and return the `RefEdits` as we know them thus far.
     ///
     /// Note that they have been altered compared to what was initially provided as they have
@@ -476,3 +497,20 @@ mod error {
 }
 
 pub use error::Error;
+
+/// Follow `target` through symbolic references and return the id it finally points to, or `None` if a reference
+/// along the way doesn't exist.
+fn resolve_to_id(
+    store: &file::Store,
+    packed: Option<&packed::Buffer>,
+    mut target: Target,
+) -> Option<gix_hash::ObjectId> {
+    const MAX_REF_DEPTH: usize = 5;
+    for _ in 0..MAX_REF_DEPTH {
+        match target {
+            Target::Peeled(id) => return Some(id),
+            Target::Symbolic(name) => target = store.find_existing_inner(name.as_ref(), packed).ok()?.target,
+        }
+    }
+    None
+}
@@ -163,6 +163,41 @@ fn reference_with_explicit_value_must_match_the_value_on_update() -> crate::Resu
     Ok(())
 }
 
+#[test]
+fn prepared_edits_know_the_previous_value_of_their_reference() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let head = store.try_find_loose("HEAD")?.expect("head exists already");
+
+    let transaction = store.transaction();
+    assert_eq!(transaction.edits().count(), 0, "nothing is known before preparation");
+    let transaction = transaction.prepare(
+        Some(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                new: Target::Peeled(gix_hash::Kind::Sha1.null()),
+                expected: PreviousValue::Any,
+            },
+            name: "HEAD".try_into()?,
+            deref: false,
+        }),
+        Fail::Immediately,
+        Fail::Immediately,
+    )?;
+    let edits: Vec<_> = transaction.edits().cloned().collect();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].change.previous_value(),
+        Some(head.target.to_ref()),
+        "the value seen while locking is recorded"
+    );
+    assert_eq!(
+        transaction.rollback(),
+        edits,
+        "these are the same as the ones on rollback"
+    );
+    Ok(())
+}
+
 #[test]
 fn the_existing_must_match_constraint_allow_non_existing_references_to_be_created() -> crate::Result {
     let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
@@ -431,6 +466,95 @@ fn symbolic_reference_writes_reflog_if_previous_value_is_set() -> crate::Result
     Ok(())
 }
 
+#[test]
+fn symbolic_reference_changes_write_reflog_with_the_ids_of_their_targets() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let main_id = store.find_loose("main")?.target.into_id();
+    let other_id = ObjectId::empty_tree(gix_hash::Kind::Sha1);
+    store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange::default(),
+                    new: Target::Peeled(other_id),
+                    expected: PreviousValue::MustNotExist,
+                },
+                name: "refs/heads/other".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    let previous_reflog_count = reflog_lines(&store, "HEAD")?.len();
+
+    let message = "checkout: moving from main to other";
+    store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: message.into(),
+                    },
+                    new: Target::Symbolic("refs/heads/other".try_into()?),
+                    expected: PreviousValue::MustExist,
+                },
+                name: "HEAD".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let head = store.find_loose("HEAD")?;
+    assert_eq!(head.kind(), gix_ref::Kind::Symbolic);
+    let lines = reflog_lines(&store, "HEAD")?;
+    assert_eq!(lines.len(), previous_reflog_count + 1, "a new reflog is added");
+    assert_eq!(
+        lines.last().expect("present"),
+        &log_line(main_id, other_id, message),
+        "it records the ids both targets point to"
+    );
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/other")?.len(),
+        1,
+        "the referent's reflog is unaffected"
+    );
+
+    let message = "checkout: moving from other to main";
+    store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: message.into(),
+                    },
+                    new: Target::Peeled(main_id),
+                    expected: PreviousValue::MustExist,
+                },
+                name: "HEAD".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(
+        reflog_lines(&store, "HEAD")?.last().expect("present"),
+        &log_line(other_id, main_id, message),
+        "detaching a symbolic ref also knows the id it pointed to"
+    );
+    Ok(())
+}
+
 #[test]
 fn symbolic_head_missing_referent_then_update_referent() -> crate::Result {
     for reflog_writemode in &[WriteReflog::Normal, WriteReflog::Disable, WriteReflog::Always] {
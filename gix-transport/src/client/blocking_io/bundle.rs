@@ -0,0 +1,167 @@
+use std::io::{self, BufRead, Read};
+
+use bstr::{BString, ByteSlice};
+use gix_packetline::{encode, Channel};
+
+use crate::{client::git, Protocol};
+
+/// The maximum amount of pack data in a side-band packet line, excluding the band itself.
+const MAX_BAND_DATA_LEN: usize = 65515;
+
+/// The error used in [`connect()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open the bundle at {path:?}")]
+    Open { path: BString, source: io::Error },
+    #[error("Could not read the header of the bundle at {path:?}")]
+    Decode {
+        path: BString,
+        source: gix_bundle::decode::Error,
+    },
+}
+
+/// Connect to the bundle file at `path`, which is served like a repository advertising the refs of the bundle
+/// in protocol version 1, independently of the `desired_version`, and which sends the pack of the bundle in response to any
+/// fetch request.
+///
+/// Note that it's up to the caller to assure the prerequisites of the bundle are present, or else the received pack
+/// can't be resolved.
+pub fn connect(
+    path: impl Into<BString>,
+    desired_version: Protocol,
+) -> Result<git::Connection<Box<dyn Read + Send>, io::Sink>, Error> {
+    let path = path.into();
+    let file = std::fs::File::open(path.to_os_str_lossy()).map_err(|source| Error::Open {
+        path: path.clone(),
+        source,
+    })?;
+    let mut pack = io::BufReader::new(file);
+    let header = gix_bundle::Header::from_read(&mut pack).map_err(|source| Error::Decode {
+        path: path.clone(),
+        source,
+    })?;
+
+    let mut advertisement = Vec::new();
+    advertise_refs(&header, &mut advertisement).expect("writing to a vector never fails");
+    let read = io::Cursor::new(advertisement).chain(PackResponse {
+        pack,
+        buf: Vec::new(),
+        pos: 0,
+        state: State::Nak,
+    });
+    Ok(git::Connection::new_for_spawned_process(
+        Box::new(read) as Box<dyn Read + Send>,
+        io::sink(),
+        desired_version,
+        path,
+    ))
+}
+
+/// Write the refs of the bundle in `header` like a V1 server would, along with the capabilities clients require to fetch.
+///
+/// `include-tag` is advertised as all tags of the bundle are part of its pack anyway.
+fn advertise_refs(header: &gix_bundle::Header, out: &mut Vec<u8>) -> io::Result<()> {
+    let mut capabilities = BString::from("multi_ack_detailed side-band-64k ofs-delta include-tag");
+    if let Some(target) = head_target(header) {
+        capabilities.extend_from_slice(b" symref=HEAD:");
+        capabilities.extend_from_slice(target);
+    }
+    let mut refs: Vec<_> = header.refs.iter().filter(|r| r.name == "HEAD").collect();
+    refs.extend(header.refs.iter().filter(|r| r.name != "HEAD"));
+    match refs.split_first() {
+        Some((first, rest)) => {
+            let mut line = BString::from(format!("{} ", first.id));
+            line.extend_from_slice(&first.name);
+            line.push(0);
+            line.extend_from_slice(&capabilities);
+            encode::text_to_write(&line, &mut *out)?;
+            for r in rest {
+                let mut line = BString::from(format!("{} ", r.id));
+                line.extend_from_slice(&r.name);
+                encode::text_to_write(&line, &mut *out)?;
+            }
+        }
+        None => {
+            let mut line = BString::from(format!("{} capabilities^{{}}\0", header.object_hash.null()));
+            line.extend_from_slice(&capabilities);
+            encode::text_to_write(&line, &mut *out)?;
+        }
+    }
+    encode::flush_to_write(out).map(|_| ())
+}
+
+/// Bundles don't know which branch `HEAD` points to, so like `git`, we guess it's the branch pointing to the same commit,
+/// preferring the typical names of default branches.
+fn head_target(header: &gix_bundle::Header) -> Option<&BString> {
+    let head = header.find_ref(b"HEAD")?;
+    let mut branches = header
+        .refs
+        .iter()
+        .filter(|r| r.id == head.id && r.name.starts_with(b"refs/heads/"));
+    let first = branches.next()?;
+    Some(
+        &std::iter::once(first)
+            .chain(branches)
+            .find(|r| r.name == "refs/heads/main" || r.name == "refs/heads/master")
+            .unwrap_or(first)
+            .name,
+    )
+}
+
+enum State {
+    Nak,
+    Pack,
+    Flush,
+    Done,
+}
+
+/// The response to the fetch request, which is a `NAK` followed by the pack on the data band and a flush packet.
+struct PackResponse<R> {
+    pack: R,
+    buf: Vec<u8>,
+    pos: usize,
+    state: State,
+}
+
+impl<R: BufRead> PackResponse<R> {
+    fn fill_buf(&mut self) -> io::Result<()> {
+        self.buf.clear();
+        self.pos = 0;
+        match self.state {
+            State::Nak => {
+                encode::text_to_write(b"NAK", &mut self.buf)?;
+                self.state = State::Pack;
+            }
+            State::Pack => {
+                let mut chunk = Vec::with_capacity(MAX_BAND_DATA_LEN);
+                (&mut self.pack)
+                    .take(MAX_BAND_DATA_LEN as u64)
+                    .read_to_end(&mut chunk)?;
+                if chunk.is_empty() {
+                    self.state = State::Flush;
+                    return self.fill_buf();
+                }
+                encode::band_to_write(Channel::Data, &chunk, &mut self.buf)?;
+            }
+            State::Flush => {
+                encode::flush_to_write(&mut self.buf)?;
+                self.state = State::Done;
+            }
+            State::Done => {}
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for PackResponse<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.buf.len() {
+            self.fill_buf()?;
+        }
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..][..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
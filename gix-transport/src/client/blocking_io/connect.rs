@@ -3,12 +3,15 @@ pub use crate::client::non_io_types::connect::{Error, Options};
 pub(crate) mod function {
     use std::convert::TryInto;
 
+    use bstr::ByteSlice;
+
     use crate::client::{non_io_types::connect::Error, Transport};
 
     /// A general purpose connector connecting to a repository identified by the given `url`.
     ///
     /// This includes connections to
     /// [local repositories][crate::client::file::connect()],
+    /// [bundle files][crate::client::bundle::connect()],
     /// [repositories over ssh][crate::client::ssh::connect()],
     /// [git daemons][crate::client::git::connect()],
     /// and if compiled in connections to [git repositories over https][crate::client::http::connect()].
@@ -29,10 +32,17 @@ pub(crate) mod function {
                         scheme: url.scheme,
                     });
                 }
-                Box::new(
-                    crate::client::blocking_io::file::connect(url.path, options.version)
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
-                )
+                if std::path::Path::new(&url.path.to_os_str_lossy()).is_file() {
+                    Box::new(
+                        crate::client::blocking_io::bundle::connect(url.path, options.version)
+                            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
+                    )
+                } else {
+                    Box::new(
+                        crate::client::blocking_io::file::connect(url.path, options.version)
+                            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
+                    )
+                }
             }
             gix_url::Scheme::Ssh => Box::new({
                 crate::client::blocking_io::ssh::connect(url, options.version, options.ssh)
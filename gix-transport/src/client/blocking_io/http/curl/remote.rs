@@ -156,6 +156,8 @@ pub fn new() -> (
                     proxy_authenticate,
                     verbose,
                     ssl_ca_info,
+                    ssl_verify,
+                    cookie_file,
                     ssl_version,
                     http_version,
                     backend,
@@ -176,6 +178,11 @@ pub fn new() -> (
             if let Some(ca_info) = ssl_ca_info {
                 handle.cainfo(ca_info)?;
             }
+            handle.ssl_verify_peer(ssl_verify)?;
+            handle.ssl_verify_host(ssl_verify)?;
+            if let Some(cookie_file) = cookie_file {
+                handle.cookie_file(cookie_file)?;
+            }
 
             if let Some(ref mut curl_options) = backend.as_ref().and_then(|backend| backend.lock().ok()) {
                 if let Some(opts) = curl_options.downcast_mut::<super::Options>() {
@@ -130,7 +130,7 @@ pub mod options {
 
 /// Options to configure http requests.
 // TODO: testing most of these fields requires a lot of effort, unless special flags to introspect ongoing requests are added.
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct Options {
     /// Headers to be added to every request.
     /// They are applied unconditionally and are expected to be valid as they occur in an HTTP request, like `header: value`, without newlines.
@@ -144,7 +144,7 @@ pub struct Options {
     /// Used in conjunction with `low_speed_time_seconds`, any non-0 value signals the amount of bytes per second at least to avoid
     /// aborting the connection.
     ///
-    /// Refers to `http.lowSpeedLimit`.
+    /// Refers to `http.lowSpeedLimit`. Only supported by the `curl` backend.
     pub low_speed_limit_bytes_per_second: u32,
     /// Used in conjunction with `low_speed_bytes_per_second`, any non-0 value signals the amount seconds the minimal amount
     /// of bytes per second isn't reached.
@@ -187,6 +187,14 @@ pub struct Options {
     pub verbose: bool,
     /// If set, use this path to point to a file with CA certificates to verify peers.
     pub ssl_ca_info: Option<PathBuf>,
+    /// If `false`, the certificate of peers and whether it matches their host name won't be verified, which is insecure.
+    ///
+    /// Refers to `http.sslVerify`, and is `true` by default.
+    pub ssl_verify: bool,
+    /// If set, the path to a file in the Netscape cookie file format to read cookies from, to be sent to matching hosts.
+    ///
+    /// Refers to `http.cookieFile`.
+    pub cookie_file: Option<PathBuf>,
     /// The SSL version or version range to use, or `None` to let the TLS backend determine which versions are acceptable.
     pub ssl_version: Option<SslVersionRangeInclusive>,
     /// The HTTP version to enforce. If unset, it is implementation defined.
@@ -195,6 +203,30 @@ pub struct Options {
     pub backend: Option<Arc<Mutex<dyn Any + Send + Sync + 'static>>>,
 }
 
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            extra_headers: Vec::new(),
+            follow_redirects: Default::default(),
+            low_speed_limit_bytes_per_second: 0,
+            low_speed_time_seconds: 0,
+            proxy: None,
+            no_proxy: None,
+            proxy_auth_method: Default::default(),
+            proxy_authenticate: None,
+            user_agent: None,
+            connect_timeout: None,
+            verbose: false,
+            ssl_ca_info: None,
+            ssl_verify: true,
+            cookie_file: None,
+            ssl_version: None,
+            http_version: None,
+            backend: None,
+        }
+    }
+}
+
 /// The actual http client implementation, using curl
 #[cfg(feature = "http-client-curl")]
 pub type Impl = curl::Curl;
@@ -0,0 +1,121 @@
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bstr::ByteSlice;
+
+/// Return the value of the `Cookie` header with all cookies in the Netscape cookie `file` that apply to `url`, or `None`
+/// if there are none.
+///
+/// Like `curl`, a file that can't be read is ignored, as are lines that can't be parsed.
+pub(crate) fn header_value(file: &Path, url: &str) -> Option<String> {
+    let content = std::fs::read(file).ok()?;
+    let url = gix_url::parse(url.into()).ok()?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+    let cookies: Vec<_> = content
+        .lines()
+        .filter_map(|line| line.to_str().ok())
+        .filter_map(|line| {
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            if line.starts_with('#') {
+                return None;
+            }
+            Cookie::parse(line)
+        })
+        .filter(|cookie| cookie.applies_to(&url, now))
+        .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+        .collect();
+    (!cookies.is_empty()).then(|| cookies.join("; "))
+}
+
+struct Cookie<'a> {
+    domain: &'a str,
+    include_subdomains: bool,
+    path: &'a str,
+    secure: bool,
+    expires_at: u64,
+    name: &'a str,
+    value: &'a str,
+}
+
+impl<'a> Cookie<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        let domain = fields.next()?;
+        let include_subdomains = fields.next()? == "TRUE" || domain.starts_with('.');
+        let cookie = Cookie {
+            domain: domain.trim_start_matches('.'),
+            include_subdomains,
+            path: fields.next()?,
+            secure: fields.next()? == "TRUE",
+            expires_at: fields.next()?.parse().ok()?,
+            name: fields.next()?,
+            value: fields.next().unwrap_or_default(),
+        };
+        (!cookie.domain.is_empty()).then_some(cookie)
+    }
+
+    fn applies_to(&self, url: &gix_url::Url, now: u64) -> bool {
+        let host = match url.host() {
+            Some(host) => host,
+            None => return false,
+        };
+        let domain_matches = host.eq_ignore_ascii_case(self.domain)
+            || (self.include_subdomains
+                && host.len() > self.domain.len()
+                && host[host.len() - self.domain.len()..].eq_ignore_ascii_case(self.domain)
+                && host.as_bytes()[host.len() - self.domain.len() - 1] == b'.');
+        let path = url.path.as_bstr();
+        let path_matches = path.starts_with(self.path.as_bytes())
+            && (self.path.ends_with('/') || matches!(path.get(self.path.len()), None | Some(b'/' | b'?')));
+        domain_matches
+            && path_matches
+            && (!self.secure || url.scheme == gix_url::Scheme::Https)
+            && (self.expires_at == 0 || self.expires_at > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookies_for(content: &str, url: &str) -> Option<String> {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("cookies");
+        std::fs::write(&file, content).unwrap();
+        header_value(&file, url)
+    }
+
+    #[test]
+    fn cookies_are_matched_by_domain_path_and_scheme() {
+        let content = "# Netscape HTTP Cookie File\n\
+            example.com\tFALSE\t/\tFALSE\t0\tall\t1\n\
+            .example.com\tTRUE\t/\tFALSE\t0\tsub\t2\n\
+            #HttpOnly_example.com\tFALSE\t/repo\tTRUE\t0\tsecure\t3\n\
+            example.com\tFALSE\t/\tFALSE\t1\texpired\t4\n\
+            invalid line\n";
+        assert_eq!(
+            cookies_for(content, "https://example.com/repo/info/refs").as_deref(),
+            Some("all=1; sub=2; secure=3")
+        );
+        assert_eq!(
+            cookies_for(content, "http://example.com/repo").as_deref(),
+            Some("all=1; sub=2"),
+            "secure cookies are only sent via https"
+        );
+        assert_eq!(
+            cookies_for(content, "https://a.example.com/repository").as_deref(),
+            Some("sub=2"),
+            "paths match on component boundaries"
+        );
+        assert_eq!(cookies_for(content, "https://notexample.com/repo"), None);
+    }
+
+    #[test]
+    fn missing_files_are_ignored() {
+        assert_eq!(header_value(Path::new("does-not-exist"), "https://example.com"), None);
+    }
+}
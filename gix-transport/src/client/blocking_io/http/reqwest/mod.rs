@@ -17,12 +17,30 @@ pub type ConfigureRequestFn = dyn FnMut(&mut reqwest::blocking::Request) -> Resu
     + Sync
     + 'static;
 
+/// A function to configure the client with the given options before it is built, to apply configuration that depends on
+/// features of `reqwest` that can't be enabled here, like the ones controlling TLS.
+pub type ConfigureClientFn = dyn FnMut(
+        reqwest::blocking::ClientBuilder,
+        &crate::client::http::Options,
+    ) -> Result<reqwest::blocking::ClientBuilder, Box<dyn std::error::Error + Send + Sync + 'static>>
+    + Send
+    + Sync
+    + 'static;
+
 /// Options to configure the reqwest HTTP handler.
 #[derive(Default)]
 pub struct Options {
     /// A function to configure the request that is about to be made.
     pub configure_request: Option<Box<ConfigureRequestFn>>,
+    /// A function to configure the client, called each time it is built, which happens before the first request
+    /// and whenever the options affecting it change.
+    ///
+    /// This is the place to apply [`ssl_ca_info`][crate::client::http::Options::ssl_ca_info] and
+    /// [`ssl_verify`][crate::client::http::Options::ssl_verify], which are otherwise ignored.
+    pub configure_client: Option<Box<ConfigureClientFn>>,
 }
 
+mod cookies;
+
 ///
 pub mod remote;
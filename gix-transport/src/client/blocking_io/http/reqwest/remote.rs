@@ -40,19 +40,32 @@ impl Default for Remote {
         let (req_send, req_recv) = std::sync::mpsc::sync_channel(0);
         let (res_send, res_recv) = std::sync::mpsc::sync_channel(0);
         let handle = std::thread::spawn(move || -> Result<(), Error> {
-            // We may error while configuring, which is expected as part of the internal protocol. The error will be
-            // received and the sender of the request might restart us.
-            let client = reqwest::blocking::ClientBuilder::new()
-                .connect_timeout(std::time::Duration::from_secs(20))
-                .http1_title_case_headers()
-                .build()?;
+            let mut client_and_options = None::<(reqwest::blocking::Client, ClientOptions)>;
             for Request {
                 url,
-                headers,
+                mut headers,
                 upload_body_kind,
                 config,
             } in req_recv
             {
+                // We may error while configuring, which is expected as part of the internal protocol. The error will be
+                // received and the sender of the request might restart us.
+                let client = match &client_and_options {
+                    Some((client, options)) if *options == ClientOptions::from(&config) => client.clone(),
+                    _ => {
+                        let client = new_client(&config)?;
+                        client_and_options = Some((client.clone(), ClientOptions::from(&config)));
+                        client
+                    }
+                };
+                if let Some(cookies) = config
+                    .cookie_file
+                    .as_deref()
+                    .and_then(|file| super::cookies::header_value(file, &url))
+                    .and_then(|cookies| reqwest::header::HeaderValue::try_from(cookies).ok())
+                {
+                    headers.insert(reqwest::header::COOKIE, cookies);
+                }
                 let mut req_builder = if upload_body_kind.is_some() {
                     client.post(url)
                 } else {
@@ -150,6 +163,61 @@ impl Default for Remote {
     }
 }
 
+/// The options that are used to build a client, which is rebuilt whenever they change.
+#[derive(PartialEq)]
+struct ClientOptions {
+    proxy: Option<String>,
+    no_proxy: Option<String>,
+    connect_timeout: Option<std::time::Duration>,
+    ssl_ca_info: Option<std::path::PathBuf>,
+    ssl_verify: bool,
+}
+
+impl From<&http::Options> for ClientOptions {
+    fn from(options: &http::Options) -> Self {
+        ClientOptions {
+            proxy: options.proxy.clone(),
+            no_proxy: options.no_proxy.clone(),
+            connect_timeout: options.connect_timeout,
+            ssl_ca_info: options.ssl_ca_info.clone(),
+            ssl_verify: options.ssl_verify,
+        }
+    }
+}
+
+fn new_client(config: &http::Options) -> Result<reqwest::blocking::Client, Error> {
+    let mut builder = reqwest::blocking::ClientBuilder::new()
+        .connect_timeout(config.connect_timeout.unwrap_or(std::time::Duration::from_secs(20)))
+        .http1_title_case_headers();
+    match config.proxy.as_deref() {
+        Some("") => builder = builder.no_proxy(),
+        Some(proxy) => {
+            builder = builder.proxy(
+                reqwest::Proxy::all(proxy)?
+                    .no_proxy(config.no_proxy.as_deref().and_then(reqwest::NoProxy::from_string)),
+            )
+        }
+        None => {}
+    }
+    if let Some(ref mut request_options) = config.backend.as_ref().and_then(|backend| backend.lock().ok()) {
+        if let Some(options) = request_options.downcast_mut::<super::Options>() {
+            if let Some(configure_client) = &mut options.configure_client {
+                builder = configure_client(builder, config)?;
+            }
+        }
+    }
+    Ok(builder.build()?)
+}
+
+fn insert_header(header_map: &mut reqwest::header::HeaderMap, header_name: &str, value: &str) {
+    if let Some((key, val)) = reqwest::header::HeaderName::from_str(header_name)
+        .ok()
+        .zip(reqwest::header::HeaderValue::try_from(value.trim()).ok())
+    {
+        header_map.append(key, val);
+    }
+}
+
 /// utilities
 impl Remote {
     fn make_request(
@@ -165,16 +233,16 @@ impl Remote {
             let colon_pos = header_line
                 .find(':')
                 .expect("header line must contain a colon to separate key and value");
-            let header_name = &header_line[..colon_pos];
-            let value = &header_line[colon_pos + 1..];
-
-            match reqwest::header::HeaderName::from_str(header_name)
-                .ok()
-                .zip(reqwest::header::HeaderValue::try_from(value.trim()).ok())
-            {
-                Some((key, val)) => header_map.insert(key, val),
-                None => continue,
-            };
+            insert_header(
+                &mut header_map,
+                &header_line[..colon_pos],
+                &header_line[colon_pos + 1..],
+            );
+        }
+        for header_line in &self.config.extra_headers {
+            if let Some((header_name, value)) = header_line.split_once(':') {
+                insert_header(&mut header_map, header_name, value);
+            }
         }
         self.request
             .send(Request {
@@ -1,3 +1,6 @@
+///
+pub mod bundle;
+
 ///
 pub mod connect;
 
@@ -0,0 +1,77 @@
+use std::io::Write;
+
+use gix_transport::{
+    client,
+    client::{Transport, TransportWithoutIO},
+    Protocol, Service,
+};
+
+const BUNDLE: &str = "tests/fixtures/bundle/all.bundle";
+
+#[test]
+fn connect_dispatches_on_files_and_serves_refs_and_pack_in_v1() -> crate::Result {
+    let mut c = client::connect(
+        BUNDLE,
+        client::connect::Options {
+            version: Protocol::V2,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(c.to_url().as_ref(), "file://tests/fixtures/bundle/all.bundle");
+
+    let mut res = c.handshake(Service::UploadPack, &[])?;
+    assert_eq!(res.actual_protocol, Protocol::V1, "bundles are always served in V1");
+    assert_eq!(
+        res.capabilities
+            .capability("symref")
+            .and_then(|c| c.value().map(ToOwned::to_owned)),
+        Some("HEAD:refs/heads/main".into()),
+        "HEAD is assumed to point to the branch with the same commit"
+    );
+    assert!(res.capabilities.contains("side-band-64k"));
+    let refs = std::io::BufRead::lines(res.refs.as_mut().expect("refs in V1")).collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        refs,
+        [
+            "a71c7ee29fb30ece58c46cf3eed79be69502145c HEAD",
+            "a71c7ee29fb30ece58c46cf3eed79be69502145c refs/heads/main",
+            "a71c7ee29fb30ece58c46cf3eed79be69502145c refs/tags/v1"
+        ],
+        "HEAD comes first, like in advertisements of servers"
+    );
+    drop(res);
+
+    let mut writer = c.request(
+        client::WriteMode::OneLfTerminatedLinePerWriteCall,
+        client::MessageKind::Text(b"done"),
+    )?;
+    writer.write_all(b"want a71c7ee29fb30ece58c46cf3eed79be69502145c")?;
+    let mut reader = writer.into_read()?;
+    let mut line = String::new();
+    reader.readline_str(&mut line)?;
+    assert_eq!(line, "NAK\n", "the request is ignored");
+    reader.set_progress_handler(Some(Box::new(|_, _| gix_packetline::read::ProgressAction::Continue)));
+
+    use gix_pack::data::input;
+    let entries = input::BytesToEntriesIter::new_from_header(
+        reader,
+        input::Mode::Verify,
+        input::EntryDataMode::Crc32,
+        gix_hash::Kind::Sha1,
+    )?;
+    assert_eq!(entries.count(), 3, "the commit, its tree and the blob");
+    Ok(())
+}
+
+#[test]
+fn non_bundle_files_fail_to_connect() {
+    let err = client::connect("Cargo.toml", Default::default())
+        .err()
+        .expect("not a bundle");
+    assert_eq!(err.to_string(), "connection failed");
+    let source = std::error::Error::source(&err).expect("bundle error");
+    assert_eq!(
+        source.to_string(),
+        "Could not read the header of the bundle at \"Cargo.toml\""
+    );
+}
@@ -217,6 +217,38 @@ fn http_error_results_in_observable_error() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn extra_headers_and_cookies_are_sent() -> crate::Result {
+    let (server, mut c) = mock::serve_and_connect(
+        "v1/http-handshake.response",
+        "path/not/important/due/to/mock",
+        Protocol::V1,
+    )?;
+    let dir = tempfile::tempdir()?;
+    let cookie_file = dir.path().join("cookies");
+    std::fs::write(
+        &cookie_file,
+        "127.0.0.1\tFALSE\t/path\tFALSE\t0\tname\tvalue\n127.0.0.1\tFALSE\t/other\tFALSE\t0\tother\tvalue\n",
+    )?;
+    c.configure(&http::Options {
+        extra_headers: vec!["X-Extra: value".into(), "X-Extra: other".into()],
+        cookie_file: Some(cookie_file),
+        ..Default::default()
+    })
+    .expect("http options are always accepted");
+    c.handshake(Service::UploadPack, &[])?;
+
+    let received: HashSet<_> = server.received_as_string().lines().map(|l| l.to_lowercase()).collect();
+    for expected in ["x-extra: value", "x-extra: other", "cookie: name=value"] {
+        assert!(received.contains(expected), "{expected:?} not in {received:?}");
+    }
+    assert!(
+        !received.iter().any(|l| l.contains("other=value")),
+        "cookies of other paths aren't sent"
+    );
+    Ok(())
+}
+
 #[test]
 fn handshake_v1() -> crate::Result {
     let (server, mut c) = mock::serve_and_connect(
@@ -1,2 +1,3 @@
+mod bundle;
 #[cfg(any(feature = "http-client-curl", feature = "http-client-reqwest"))]
 mod http;
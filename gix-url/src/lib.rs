@@ -20,6 +20,9 @@ pub mod expand_path;
 #[doc(inline)]
 pub use expand_path::expand_path;
 
+///
+pub mod matching;
+
 mod scheme;
 pub use scheme::Scheme;
 
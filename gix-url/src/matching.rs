@@ -0,0 +1,89 @@
+use bstr::BStr;
+
+use crate::{Scheme, Url};
+
+/// How specifically a URL was matched by a pattern, with more specific matches comparing greater than less specific ones.
+///
+/// Like in `git`, the length of the host in the pattern is most significant, followed by the length of the matched path,
+/// and matching by user name breaks ties.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Specificity {
+    /// The length of the host in the pattern.
+    pub host_len: usize,
+    /// The length of the matched path, including its trailing slash even if it is implied.
+    pub path_len: usize,
+    /// If `true`, the pattern specified the user name of the URL.
+    pub user_matched: bool,
+}
+
+/// Matching
+impl Url {
+    /// Return how specifically `self` is matched by the URL `pattern`, or `None` if it doesn't match, in the way `git` matches
+    /// URLs to configuration like `http.<url>.*` and `credential.<url>.*`.
+    ///
+    /// * the scheme must be equal.
+    /// * the host must be equal, ignoring case, with each `*` label in the pattern matching any single label.
+    /// * the port must be equal, with the default port of `http` and `https` URLs implied if unset.
+    /// * the path of the pattern must be a prefix of the path, ending at a `/` or the end of the path.
+    /// * the user must be equal if the pattern has one.
+    pub fn matches(&self, pattern: &Url) -> Option<Specificity> {
+        if self.scheme != pattern.scheme {
+            return None;
+        }
+        if let Some(user) = pattern.user() {
+            if self.user() != Some(user) {
+                return None;
+            }
+        }
+        if !host_matches(self.host(), pattern.host()) {
+            return None;
+        }
+        let ports = if matches!(self.scheme, Scheme::Http | Scheme::Https) {
+            (self.port_or_default(), pattern.port_or_default())
+        } else {
+            (self.port, pattern.port)
+        };
+        if ports.0 != ports.1 {
+            return None;
+        }
+        Some(Specificity {
+            host_len: pattern.host().map_or(0, str::len),
+            path_len: path_prefix_len(self.path.as_ref(), pattern.path.as_ref())?,
+            user_matched: pattern.user().is_some(),
+        })
+    }
+}
+
+fn host_matches(host: Option<&str>, pattern: Option<&str>) -> bool {
+    match (host, pattern) {
+        (Some(host), Some(pattern)) => {
+            let mut labels = host.split('.');
+            let mut patterns = pattern.split('.');
+            loop {
+                match (labels.next(), patterns.next()) {
+                    (Some(label), Some(pattern)) => {
+                        if pattern != "*" && !label.eq_ignore_ascii_case(pattern) {
+                            return false;
+                        }
+                    }
+                    (None, None) => return true,
+                    _ => return false,
+                }
+            }
+        }
+        (None, None) => true,
+        (Some(_), None) | (None, Some(_)) => false,
+    }
+}
+
+/// Return the length of the match of the `prefix` path in `path` if it ends at a path component boundary, with both paths
+/// having an implied trailing slash.
+fn path_prefix_len(path: &BStr, prefix: &BStr) -> Option<usize> {
+    let path = path.strip_prefix(b"/").unwrap_or(path);
+    let prefix = prefix.strip_prefix(b"/").unwrap_or(prefix);
+    let prefix = prefix.strip_suffix(b"/").unwrap_or(prefix);
+    if prefix.is_empty() {
+        return Some(1);
+    }
+    (path.starts_with(prefix) && matches!(path.get(prefix.len()), None | Some(b'/'))).then(|| prefix.len() + 2)
+}
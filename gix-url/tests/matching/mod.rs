@@ -0,0 +1,86 @@
+use gix_url::matching::Specificity;
+
+fn matches(url: &str, pattern: &str) -> Option<Specificity> {
+    let url = gix_url::parse(url.into()).expect("valid url");
+    let pattern = gix_url::parse(pattern.into()).expect("valid pattern");
+    url.matches(&pattern)
+}
+
+#[test]
+fn scheme_host_and_port_must_be_equal() {
+    assert!(matches("https://example.com/repo", "https://example.com").is_some());
+    assert!(matches("https://EXAMPLE.com/repo", "https://example.COM").is_some());
+    assert!(matches("http://example.com/repo", "https://example.com").is_none());
+    assert!(matches("https://example.org/repo", "https://example.com").is_none());
+    assert!(matches("https://example.com:8080/repo", "https://example.com").is_none());
+    assert!(
+        matches("https://example.com:443/repo", "https://example.com").is_some(),
+        "default ports are implied for http"
+    );
+    assert!(
+        matches("ssh://example.com:22/repo", "ssh://example.com/repo").is_none(),
+        "…but not for other schemes"
+    );
+}
+
+#[test]
+fn host_wildcards_match_one_label() {
+    assert!(matches("https://a.example.com", "https://*.example.com").is_some());
+    assert!(matches("https://a.b.example.com", "https://*.example.com").is_none());
+    assert!(matches("https://example.com", "https://*.example.com").is_none());
+    assert!(matches("https://a.b.example.com", "https://a.*.example.com").is_some());
+    assert!(matches("https://example.com", "https://*.*").is_some());
+    assert!(
+        matches("https://example.com", "https://ex*.com").is_none(),
+        "only entire labels can be wildcards"
+    );
+}
+
+#[test]
+fn paths_must_match_by_prefix_at_component_boundaries() {
+    assert!(matches("https://example.com/org/repo", "https://example.com/org").is_some());
+    assert!(matches("https://example.com/org/repo", "https://example.com/org/").is_some());
+    assert!(matches("https://example.com/org", "https://example.com/org/").is_some());
+    assert!(matches("https://example.com/organization", "https://example.com/org").is_none());
+    assert!(matches("https://example.com/ORG", "https://example.com/org").is_none());
+    assert!(matches("https://example.com/org", "https://example.com/org/repo").is_none());
+}
+
+#[test]
+fn users_must_match_if_specified_in_the_pattern() {
+    assert!(matches("https://user@example.com", "https://example.com").is_some());
+    assert!(matches("https://user@example.com", "https://user@example.com").is_some());
+    assert!(matches("https://example.com", "https://user@example.com").is_none());
+    assert!(matches("https://other@example.com", "https://user@example.com").is_none());
+}
+
+#[test]
+fn specificity_orders_by_host_then_path_then_user() {
+    let url = "https://user@a.example.com/org/repo";
+    let host = matches(url, "https://a.example.com").expect("match");
+    let wildcard = matches(url, "https://*.example.com").expect("match");
+    let path = matches(url, "https://a.example.com/org").expect("match");
+    let user = matches(url, "https://user@a.example.com").expect("match");
+    assert_eq!(
+        host,
+        Specificity {
+            host_len: 13,
+            path_len: 1,
+            user_matched: false
+        }
+    );
+    assert_eq!(path.path_len, 5, "the implied trailing slash counts as well");
+    assert_eq!(
+        host, wildcard,
+        "like in git, wildcards are as specific as the labels they match"
+    );
+    assert!(path > host);
+    assert!(user > host);
+    assert!(path > user);
+
+    let url = "https://ab.example.com/org/repo";
+    assert!(
+        matches(url, "https://ab.example.com") > matches(url, "https://*.example.com/org/repo"),
+        "the length of the host pattern is most significant"
+    );
+}
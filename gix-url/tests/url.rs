@@ -3,4 +3,5 @@ pub type Result = std::result::Result<(), Error>;
 
 mod access;
 mod expand_path;
+mod matching;
 mod parse;
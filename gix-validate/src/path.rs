@@ -0,0 +1,44 @@
+use bstr::BStr;
+
+///
+pub mod component {
+    /// The error returned by [`component()`][super::component()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("A path component must not be empty")]
+        Empty,
+        #[error("A path component must not contain a path separator")]
+        PathSeparator,
+        #[error("A path component must not contain a null byte")]
+        NullByte,
+        #[error("A path component must not be '.' or '..'")]
+        RelativeComponent,
+        #[error("A path component must not be '.git', in any case")]
+        DotGitDir,
+    }
+}
+
+/// Assure the given `input` is a valid name of an entry in a tree, like a single component of a path in the index,
+/// which is returned unchanged on success.
+///
+/// Like `git`, names that would refer to the `.git` directory are rejected regardless of their case, as these could be
+/// used to overwrite the repository on case-insensitive file systems when checked out.
+pub fn component(input: &BStr) -> Result<&BStr, component::Error> {
+    if input.is_empty() {
+        return Err(component::Error::Empty);
+    }
+    if input.contains(&b'/') {
+        return Err(component::Error::PathSeparator);
+    }
+    if input.contains(&0) {
+        return Err(component::Error::NullByte);
+    }
+    if input == "." || input == ".." {
+        return Err(component::Error::RelativeComponent);
+    }
+    if input.eq_ignore_ascii_case(b".git") {
+        return Err(component::Error::DotGitDir);
+    }
+    Ok(input)
+}
@@ -0,0 +1,46 @@
+mod component {
+    use bstr::ByteSlice;
+    use gix_validate::path::component::Error;
+
+    #[test]
+    fn valid() {
+        for input in [
+            "a",
+            "file.txt",
+            ".gitignore",
+            ".gitmodules",
+            "..a",
+            "a.",
+            "git",
+            "你好吗",
+            "with space",
+        ] {
+            assert_eq!(
+                gix_validate::path::component(input.as_bytes().as_bstr()).expect("valid"),
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for (input, expected) in [
+            (&b""[..], Error::Empty),
+            (b"a/b", Error::PathSeparator),
+            (b"/", Error::PathSeparator),
+            (b"a\0b", Error::NullByte),
+            (b".", Error::RelativeComponent),
+            (b"..", Error::RelativeComponent),
+            (b".git", Error::DotGitDir),
+            (b".GIT", Error::DotGitDir),
+            (b".GiT", Error::DotGitDir),
+        ] {
+            let err = gix_validate::path::component(input.as_bstr()).unwrap_err();
+            assert_eq!(
+                std::mem::discriminant(&err),
+                std::mem::discriminant(&expected),
+                "{input:?}: {err:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,675 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use gix_hash::ObjectId;
+use gix_index::entry::{Flags, Mode, Stat};
+use gix_odb::FindExt;
+
+use crate::{
+    add::{Error, Options, Outcome},
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{Core, Filter},
+    },
+    diff::driver::Resolver,
+    Repository,
+};
+
+/// An entry to put into the index at stage 0, replacing all entries at its path.
+struct Update {
+    stat: Stat,
+    id: ObjectId,
+    mode: Mode,
+    flags: Flags,
+}
+
+pub(crate) fn add(
+    repo: &Repository,
+    pathspecs: impl IntoIterator<Item = impl AsRef<BStr>>,
+    options: Options,
+) -> Result<Outcome, Error> {
+    let index_path = repo.index_path();
+    let lock = gix_lock::File::acquire_to_update_resource(&index_path, gix_lock::acquire::Fail::Immediately, None)?;
+    let mut index = gix_index::File::at_or_default(index_path, repo.object_hash(), Default::default())?;
+    let (outcome, is_changed) = add_to_state(repo, &mut index, pathspecs, options)?;
+    if !is_changed {
+        return Ok(outcome);
+    }
+
+    let mut lock = std::io::BufWriter::new(lock);
+    index
+        .write_to(&mut lock, Default::default())
+        .map_err(Error::WriteIndex)?;
+    lock.into_inner()
+        .map_err(|err| Error::WriteIndex(err.into_error()))?
+        .commit()?;
+    Ok(outcome)
+}
+
+/// Like [`add()`], but change the entries of `index` in memory only, and return `true` along with the outcome if they changed.
+pub(crate) fn add_to_state(
+    repo: &Repository,
+    index: &mut gix_index::State,
+    pathspecs: impl IntoIterator<Item = impl AsRef<BStr>>,
+    options: Options,
+) -> Result<(Outcome, bool), Error> {
+    let workdir = repo.work_dir().ok_or(Error::BareRepository)?;
+    let pathspecs = Pathspecs::new(pathspecs)?;
+    if index.is_sparse() {
+        return Err(Error::SparseIndex);
+    }
+
+    let checkout_options = repo.config.checkout_options(repo.git_dir())?;
+    let config = &repo.config.resolved;
+    let auto_crlf = Core::AUTO_CRLF
+        .try_into_autocrlf(config.boolean_by_key("core.autocrlf"), || {
+            config.string_by_key("core.autocrlf")
+        })
+        .with_leniency(repo.config.lenient_config)?
+        .unwrap_or_default();
+    let mut stager = Stager {
+        repo,
+        workdir,
+        attributes: Resolver::new(repo)?,
+        auto_crlf,
+        executable_bit: checkout_options.fs.executable_bit,
+        leading_dirs: Default::default(),
+    };
+
+    let mut seen = vec![false; pathspecs.specs.len()];
+    let mut updates = BTreeMap::<BString, Option<Update>>::new();
+    let mut refreshed_stats = Vec::new();
+    let stat_options = checkout_options.stat_options;
+    let timestamp = index.timestamp();
+
+    let entries = index.entries();
+    let mut idx = 0;
+    while idx < entries.len() {
+        let path = entries[idx].path(index);
+        let num_stages = entries[idx..]
+            .iter()
+            .take_while(|entry| entry.path(index) == path)
+            .count();
+        let stages = &entries[idx..idx + num_stages];
+        let entry_idx = idx;
+        idx += num_stages;
+
+        let matching = pathspecs.matching(path);
+        if matching.is_empty() {
+            continue;
+        }
+        for spec_idx in matching {
+            seen[spec_idx] = true;
+        }
+        let entry = match stages {
+            [entry] if entry.stage() == 0 => Some(entry),
+            _ => None,
+        };
+        let is_intent_to_add = entry.map_or(false, |entry| entry.flags.contains(Flags::INTENT_TO_ADD));
+
+        let meta = match stager.metadata(path)? {
+            Some(meta) => meta,
+            None => {
+                if !options.ignore_removal {
+                    updates.insert(path.to_owned(), None);
+                }
+                continue;
+            }
+        };
+        if options.intent_to_add {
+            continue;
+        }
+        if meta.is_dir() {
+            match entry {
+                Some(entry) if entry.mode == Mode::COMMIT => {
+                    if let Some(id) = stager.embedded_repository_head(path)? {
+                        if id != entry.id {
+                            updates.insert(path.to_owned(), Some(stager.gitlink(id)));
+                        }
+                    }
+                }
+                _ => {
+                    if !options.ignore_removal {
+                        updates.insert(path.to_owned(), None);
+                    }
+                }
+            }
+            continue;
+        }
+
+        let mode = stager.mode(&meta, entry.map(|entry| entry.mode));
+        let stat = Stat::from_fs(&meta).unwrap_or_default();
+        let entry = entry.filter(|entry| !is_intent_to_add && entry.mode == mode);
+        if let Some(entry) = entry {
+            if entry.stat.matches(&stat, stat_options) && !entry.stat.is_racy(timestamp, stat_options) {
+                continue;
+            }
+        }
+        let id = stager.write_blob(path, &meta, mode, entry.map(|entry| entry.id))?;
+        match entry {
+            Some(entry) if entry.id == id => refreshed_stats.push((entry_idx, stat)),
+            _ => {
+                updates.insert(
+                    path.to_owned(),
+                    Some(Update {
+                        stat,
+                        id,
+                        mode,
+                        flags: Flags::empty(),
+                    }),
+                );
+            }
+        }
+    }
+
+    let mut ignored = Vec::new();
+    if !options.update {
+        let tracked: HashMap<&BStr, Mode> = index
+            .entries()
+            .iter()
+            .map(|entry| (entry.path(index), entry.mode))
+            .filter(|(path, _)| !matches!(updates.get(*path), Some(None)))
+            .collect();
+        let mut excludes = repo
+            .worktree()
+            .expect("present as we have a working tree")
+            .excludes(index, None)?;
+        let mut is_excluded = |path: &BStr, is_dir: bool| -> Result<bool, Error> {
+            Ok(excludes
+                .at_entry(path, Some(is_dir), |id, buf| repo.objects.find_blob(id, buf))
+                .map_err(|err| Error::Io {
+                    source: err,
+                    path: workdir.join(gix_path::from_bstr(path)),
+                })?
+                .is_excluded())
+        };
+
+        let mut directories = vec![BString::default()];
+        while let Some(directory) = directories.pop() {
+            for (name, file_type) in stager.read_dir(directory.as_ref())? {
+                if name == ".git" || (repo.config.ignore_case && name.eq_ignore_ascii_case(b".git")) {
+                    continue;
+                }
+                let mut path = directory.clone();
+                if !path.is_empty() {
+                    path.push(b'/');
+                }
+                path.push_str(&name);
+                let path_ref = path.as_bstr();
+
+                if file_type.is_dir() {
+                    if tracked.get(path_ref) == Some(&Mode::COMMIT) || !pathspecs.may_match_within(path_ref) {
+                        continue;
+                    }
+                    if !options.force && is_excluded(path_ref, true)? {
+                        if pathspecs.names(path_ref, true, &mut seen) {
+                            ignored.push(path);
+                        }
+                        continue;
+                    }
+                    if workdir.join(gix_path::from_bstr(path_ref)).join(".git").exists() {
+                        let matching = pathspecs.matching(path_ref);
+                        if !matching.is_empty() {
+                            if let Some(id) = stager.embedded_repository_head(path_ref)? {
+                                for spec_idx in matching {
+                                    seen[spec_idx] = true;
+                                }
+                                let update = stager.gitlink(id);
+                                updates.insert(path, Some(update));
+                            }
+                        }
+                        continue;
+                    }
+                    directories.push(path);
+                    continue;
+                }
+
+                if tracked.contains_key(path_ref) {
+                    continue;
+                }
+                let matching = pathspecs.matching(path_ref);
+                if matching.is_empty() {
+                    continue;
+                }
+                if !options.force && is_excluded(path_ref, false)? {
+                    if pathspecs.names(path_ref, false, &mut seen) {
+                        ignored.push(path);
+                    }
+                    continue;
+                }
+                for spec_idx in matching {
+                    seen[spec_idx] = true;
+                }
+                let meta = match stager.metadata(path_ref)? {
+                    Some(meta) => meta,
+                    None => continue,
+                };
+                let mode = stager.mode(&meta, None);
+                let stat = Stat::from_fs(&meta).unwrap_or_default();
+                let update = if options.intent_to_add {
+                    Update {
+                        stat,
+                        id: ObjectId::empty_blob(repo.object_hash()),
+                        mode,
+                        flags: Flags::EXTENDED | Flags::INTENT_TO_ADD,
+                    }
+                } else {
+                    Update {
+                        stat,
+                        id: stager.write_blob(path_ref, &meta, mode, None)?,
+                        mode,
+                        flags: Flags::empty(),
+                    }
+                };
+                updates.insert(path, Some(update));
+            }
+        }
+    }
+
+    if let Some((spec, _)) = pathspecs
+        .specs
+        .iter()
+        .zip(&seen)
+        .find(|(spec, seen)| !**seen && !spec.is_exclusion() && !spec.path.is_empty())
+    {
+        return Err(Error::PathspecMatchedNothing {
+            spec: spec.path.clone(),
+        });
+    }
+
+    // Like `git`, remove entries that would be a file and a directory at the same time as the new entries.
+    let added: HashSet<&BStr> = updates
+        .iter()
+        .filter_map(|(path, update)| update.as_ref().map(|_| path.as_bstr()))
+        .collect();
+    let added_dirs: HashSet<&BStr> = added
+        .iter()
+        .flat_map(|path| path.find_iter("/").map(move |pos| path[..pos].as_bstr()))
+        .collect();
+    let replaced: Vec<BString> = index
+        .entries()
+        .iter()
+        .map(|entry| entry.path(index))
+        .filter(|path| !updates.contains_key(*path))
+        .filter(|path| {
+            added_dirs.contains(path) || path.find_iter("/").any(|pos| added.contains(path[..pos].as_bstr()))
+        })
+        .map(ToOwned::to_owned)
+        .collect();
+    updates.extend(replaced.into_iter().map(|path| (path, None)));
+
+    let mut outcome = Outcome {
+        ignored,
+        ..Default::default()
+    };
+    if updates.is_empty() && refreshed_stats.is_empty() {
+        return Ok((outcome, false));
+    }
+    for (entry_idx, stat) in refreshed_stats {
+        index.entries_mut()[entry_idx].stat = stat;
+    }
+    if !updates.is_empty() {
+        index.remove_entries(|_, path, _| updates.contains_key(path));
+        for (path, update) in &updates {
+            match update {
+                Some(update) => {
+                    index.dangerously_push_entry(update.stat, update.id, update.flags, update.mode, path.as_bstr());
+                    outcome.added.push(path.clone());
+                }
+                None => outcome.removed.push(path.clone()),
+            }
+        }
+        index.sort_entries();
+    }
+    Ok((outcome, true))
+}
+
+/// The parsed pathspecs to match paths with.
+struct Pathspecs {
+    specs: Vec<gix_pathspec::Pattern>,
+}
+
+impl Pathspecs {
+    fn new(pathspecs: impl IntoIterator<Item = impl AsRef<BStr>>) -> Result<Self, Error> {
+        let mut specs = pathspecs
+            .into_iter()
+            .map(|spec| {
+                let spec = spec.as_ref();
+                let mut pattern = gix_pathspec::parse(spec).map_err(|source| Error::ParsePathspec {
+                    spec: spec.to_owned(),
+                    source,
+                })?;
+                while let Some(path) = pattern.path.strip_prefix(b"./") {
+                    pattern.path = path.into();
+                }
+                if pattern.path == "." {
+                    pattern.path.clear();
+                }
+                Ok(pattern)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        if specs.iter().all(|spec| spec.is_exclusion()) {
+            specs.push(gix_pathspec::Pattern {
+                path: BString::default(),
+                signature: gix_pathspec::MagicSignature::empty(),
+                search_mode: Default::default(),
+                attributes: Vec::new(),
+            });
+        }
+        Ok(Pathspecs { specs })
+    }
+
+    /// Return the indices of all pathspecs matching `path`, or nothing if it doesn't match or is excluded.
+    fn matching(&self, path: &BStr) -> Vec<usize> {
+        let mut matching = Vec::new();
+        for (idx, spec) in self.specs.iter().enumerate() {
+            if spec.matches_path(path) {
+                if spec.is_exclusion() {
+                    return Vec::new();
+                }
+                matching.push(idx);
+            }
+        }
+        matching
+    }
+
+    /// Return `true` if the directory at `path` may contain paths matching one of our pathspecs.
+    fn may_match_within(&self, path: &BStr) -> bool {
+        let mut directory = path.to_owned();
+        directory.push(b'/');
+        self.specs.iter().filter(|spec| !spec.is_exclusion()).any(|spec| {
+            let prefix = spec.literal_prefix();
+            let (a, b) = if prefix.len() < directory.len() {
+                (&directory[..prefix.len()], prefix)
+            } else {
+                (&prefix[..directory.len()], directory.as_slice())
+            };
+            if spec.signature.contains(gix_pathspec::MagicSignature::ICASE) {
+                a.eq_ignore_ascii_case(b)
+            } else {
+                a == b
+            }
+        })
+    }
+
+    /// Return `true` and mark the respective pathspecs as `seen` if the file or directory at `path` is named by one of
+    /// our pathspecs without globbing, which is either exactly or as leading directory if `is_dir` is `true`.
+    fn names(&self, path: &BStr, is_dir: bool, seen: &mut [bool]) -> bool {
+        let mut named = false;
+        for (spec, seen) in self.specs.iter().zip(seen) {
+            if spec.is_exclusion() {
+                continue;
+            }
+            let spec_path = spec.path.as_bstr();
+            let is_named = spec_path == path
+                || (is_dir && spec_path.starts_with(path) && spec_path.get(path.len()) == Some(&b'/'));
+            if is_named {
+                *seen = true;
+                named = true;
+            }
+        }
+        named
+    }
+}
+
+/// Utilities for pathspecs that don't belong into `gix-pathspec`.
+trait PatternExt {
+    fn is_exclusion(&self) -> bool;
+    fn literal_prefix(&self) -> &[u8];
+}
+
+impl PatternExt for gix_pathspec::Pattern {
+    fn is_exclusion(&self) -> bool {
+        self.signature.contains(gix_pathspec::MagicSignature::EXCLUDE)
+    }
+
+    /// The part of the path that has to match literally.
+    fn literal_prefix(&self) -> &[u8] {
+        let path = self.path.as_slice();
+        match self.search_mode {
+            gix_pathspec::MatchMode::Literal => path,
+            _ => path
+                .iter()
+                .position(|b| matches!(b, b'*' | b'?' | b'[' | b'\\'))
+                .map_or(path, |pos| &path[..pos]),
+        }
+    }
+}
+
+/// The state needed to turn files in the working tree into index entries.
+struct Stager<'repo> {
+    repo: &'repo Repository,
+    workdir: &'repo Path,
+    attributes: Resolver<'repo>,
+    auto_crlf: gix_filter::eol::AutoCrlf,
+    executable_bit: bool,
+    /// Whether leading directories are actual directories, and not files or symlinks, by their path.
+    leading_dirs: HashMap<BString, bool>,
+}
+
+impl<'repo> Stager<'repo> {
+    fn path(&self, rela_path: &BStr) -> PathBuf {
+        self.workdir.join(gix_path::from_bstr(rela_path))
+    }
+
+    /// Return the metadata of the file at `rela_path` without following symlinks, or `None` if it doesn't exist.
+    ///
+    /// Like `git`, a file is considered missing if a leading directory of it is a symlink.
+    fn metadata(&mut self, rela_path: &BStr) -> Result<Option<std::fs::Metadata>, Error> {
+        for pos in rela_path.find_iter("/") {
+            let directory = rela_path[..pos].as_bstr();
+            let is_dir = match self.leading_dirs.get(directory) {
+                Some(is_dir) => *is_dir,
+                None => {
+                    let is_dir = std::fs::symlink_metadata(self.path(directory)).map_or(false, |meta| meta.is_dir());
+                    self.leading_dirs.insert(directory.to_owned(), is_dir);
+                    is_dir
+                }
+            };
+            if !is_dir {
+                return Ok(None);
+            }
+        }
+        let path = self.path(rela_path);
+        match std::fs::symlink_metadata(&path) {
+            Ok(meta) => Ok(Some(meta)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(Error::Io { source: err, path }),
+        }
+    }
+
+    /// Return the names and types of all entries in the directory at `rela_path`, sorted by name.
+    fn read_dir(&self, rela_path: &BStr) -> Result<Vec<(BString, std::fs::FileType)>, Error> {
+        let path = self.path(rela_path);
+        let io_err = |source| Error::Io {
+            source,
+            path: path.clone(),
+        };
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(&path).map_err(io_err)? {
+            let entry = entry.map_err(io_err)?;
+            let name = gix_path::os_string_into_bstring(entry.file_name())
+                .map_err(|_| Error::IllformedUtf8 { path: entry.path() })?;
+            entries.push((name, entry.file_type().map_err(io_err)?));
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(entries)
+    }
+
+    /// Return the mode of the file with `meta`, using the `previous` mode of its entry if the executable bit
+    /// can't be trusted.
+    fn mode(&self, meta: &std::fs::Metadata, previous: Option<Mode>) -> Mode {
+        if meta.file_type().is_symlink() {
+            return Mode::SYMLINK;
+        }
+        let is_executable = if self.executable_bit {
+            is_executable(meta)
+        } else {
+            previous == Some(Mode::FILE_EXECUTABLE)
+        };
+        if is_executable {
+            Mode::FILE_EXECUTABLE
+        } else {
+            Mode::FILE
+        }
+    }
+
+    /// Return the id of the commit checked out in the repository at `rela_path`, or `None` if it doesn't have one.
+    fn embedded_repository_head(&self, rela_path: &BStr) -> Result<Option<ObjectId>, Error> {
+        let repo = crate::open(self.path(rela_path)).map_err(|err| Error::OpenEmbeddedRepository {
+            path: rela_path.to_owned(),
+            source: Box::new(err),
+        })?;
+        let head = repo.head_id().ok().map(crate::Id::detach);
+        Ok(head)
+    }
+
+    fn gitlink(&self, id: ObjectId) -> Update {
+        Update {
+            stat: Stat::default(),
+            id,
+            mode: Mode::COMMIT,
+            flags: Flags::empty(),
+        }
+    }
+
+    /// Write the content of the file at `rela_path` with `meta` and `mode` as blob after applying all filters,
+    /// and return its id. `index_id` is the id of the current version in the index, if there is one.
+    fn write_blob(
+        &mut self,
+        rela_path: &BStr,
+        meta: &std::fs::Metadata,
+        mode: Mode,
+        index_id: Option<ObjectId>,
+    ) -> Result<ObjectId, Error> {
+        let path = self.path(rela_path);
+        let io_err = |source| Error::Io {
+            source,
+            path: path.clone(),
+        };
+        if mode == Mode::SYMLINK {
+            let target = std::fs::read_link(&path).map_err(io_err)?;
+            let target = gix_path::try_into_bstr(target).map_err(|_| Error::IllformedUtf8 { path: path.clone() })?;
+            let target = gix_path::to_unix_separators_on_windows(target);
+            return Ok(self.repo.write_blob(target.as_ref())?.detach());
+        }
+
+        let mut data = Vec::with_capacity(meta.len() as usize);
+        std::fs::File::open(&path)
+            .and_then(|mut file| file.read_to_end(&mut data))
+            .map_err(io_err)?;
+        let data = self.clean(rela_path, data, index_id)?;
+        Ok(self.repo.write_blob(&data)?.detach())
+    }
+
+    /// Apply the `clean` program of the filter driver, line-ending conversions and `ident` undoing to `data` of the file
+    /// at `rela_path`, in that order, just like `git` does.
+    fn clean(&mut self, rela_path: &BStr, mut data: Vec<u8>, index_id: Option<ObjectId>) -> Result<Vec<u8>, Error> {
+        let mut states = self
+            .attributes
+            .attributes(rela_path, &["filter", "text", "crlf", "eol", "ident"])?
+            .into_iter();
+        let mut next = || states.next().expect("one state per name");
+        let (filter, text, crlf, eol, ident) = (next(), next(), next(), next(), next());
+
+        if let gix_attributes::State::Value(driver) = &filter {
+            if let Some(filtered) = self.run_clean_filter(driver.as_ref().as_bstr(), rela_path, &data)? {
+                data = filtered;
+            }
+        }
+
+        let digest = gix_filter::eol::AttributesDigest::from_attributes(
+            text.as_ref(),
+            crlf.as_ref(),
+            eol.as_ref(),
+            self.auto_crlf,
+        );
+        let mut buf = Vec::new();
+        let repo = self.repo;
+        if gix_filter::eol::convert_to_git(&data, digest, &mut buf, |buf| match index_id {
+            Some(id) => {
+                buf.extend_from_slice(&repo.find_object(id)?.data);
+                Ok::<_, Error>(true)
+            }
+            None => Ok(false),
+        })? {
+            std::mem::swap(&mut data, &mut buf);
+        }
+
+        if ident == gix_attributes::State::Set && gix_filter::ident::undo(&data, &mut buf) {
+            std::mem::swap(&mut data, &mut buf);
+        }
+        Ok(data)
+    }
+
+    /// Run the `clean` program of `driver` with `data` of the file at `rela_path` and return its output, or `None` if
+    /// the driver isn't configured or its program failed and the driver isn't required.
+    fn run_clean_filter(&self, driver: &BStr, rela_path: &BStr, data: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let config = &self.repo.config.resolved;
+        let required = config
+            .boolean("filter", Some(driver), Filter::DRIVER_REQUIRED.name)
+            .map(|value| Filter::DRIVER_REQUIRED.enrich_error(value))
+            .transpose()
+            .with_leniency(self.repo.config.lenient_config)?
+            .unwrap_or(false);
+        let failed = || {
+            if required {
+                Err(Error::RequiredFilterFailed {
+                    driver: driver.to_owned(),
+                    path: rela_path.to_owned(),
+                })
+            } else {
+                Ok(None)
+            }
+        };
+        let program = match config.string("filter", Some(driver), Filter::DRIVER_CLEAN.name) {
+            Some(program) => program.replace("%f", gix_quote::single(rela_path)),
+            None => return failed(),
+        };
+
+        let spawn_err = |source| Error::SpawnFilter {
+            source,
+            command: program.as_bstr().to_owned(),
+        };
+        let mut child = std::process::Command::from(
+            gix_command::prepare(gix_path::from_bstr(program.as_bstr()).into_owned().into_os_string())
+                .with_shell()
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped()),
+        )
+        .current_dir(self.workdir)
+        .spawn()
+        .map_err(spawn_err)?;
+        let mut stdin = child.stdin.take().expect("configured");
+        let output = std::thread::scope(|scope| {
+            scope.spawn(move || {
+                // The program may not read all of its input, which is fine.
+                stdin.write_all(data).ok();
+            });
+            child.wait_with_output()
+        })
+        .map_err(spawn_err)?;
+        if output.status.success() {
+            Ok(Some(output.stdout))
+        } else {
+            failed()
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o100 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_meta: &std::fs::Metadata) -> bool {
+    false
+}
@@ -0,0 +1,88 @@
+//! Stage files of the working tree in the index, similar to `git add`.
+use std::path::PathBuf;
+
+use crate::bstr::BString;
+
+pub(crate) mod function;
+
+/// Options to control which files are staged with [`Repository::add_to_index()`][crate::Repository::add_to_index()].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// If `true`, only stage changes of files that are already tracked without adding new ones, similar to `git add --update`.
+    pub update: bool,
+    /// If `true`, only record that untracked files will be added later, similar to `git add --intent-to-add`.
+    ///
+    /// Their entries contain no data, and changes to tracked files aren't staged.
+    pub intent_to_add: bool,
+    /// If `true`, keep the entries of tracked files that were removed from the working tree, similar to
+    /// `git add --ignore-removal`.
+    pub ignore_removal: bool,
+    /// If `true`, add untracked files even if they are ignored, similar to `git add --force`.
+    pub force: bool,
+}
+
+/// The result of [`Repository::add_to_index()`][crate::Repository::add_to_index()].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The paths of all entries that were added or changed, in index order.
+    pub added: Vec<BString>,
+    /// The paths of all entries that were removed as their files are gone, in index order.
+    pub removed: Vec<BString>,
+    /// The paths of ignored files or directories that were explicitly named by a pathspec, but weren't added
+    /// as [force][Options::force] wasn't set. `git add` considers this an error, but still adds all other files.
+    pub ignored: Vec<BString>,
+}
+
+/// The error returned by [`Repository::add_to_index()`][crate::Repository::add_to_index()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Files can't be added in a bare repository")]
+    BareRepository,
+    #[error("The pathspec '{spec}' could not be parsed")]
+    ParsePathspec {
+        spec: BString,
+        source: gix_pathspec::parse::Error,
+    },
+    #[error("The pathspec '{spec}' did not match any files")]
+    PathspecMatchedNothing { spec: BString },
+    #[error("Sparse indices are not supported yet")]
+    SparseIndex,
+    #[error("Could not lock the index for writing")]
+    AcquireLock(#[from] gix_lock::acquire::Error),
+    #[error("Could not read the index")]
+    ReadIndex(#[from] gix_index::file::init::Error),
+    #[error("Could not write the index")]
+    WriteIndex(#[source] std::io::Error),
+    #[error("Could not commit the lock of the index")]
+    CommitLock(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    ConfigAutoCrlf(#[from] crate::config::key::GenericErrorWithValue),
+    #[error(transparent)]
+    ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    Excludes(#[from] crate::worktree::excludes::Error),
+    #[error(transparent)]
+    AttributesInit(#[from] crate::diff::driver::init::Error),
+    #[error(transparent)]
+    Attributes(#[from] crate::diff::driver::Error),
+    #[error("Could not access '{}' in the working tree", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error("The path '{}' isn't valid UTF-8", path.display())]
+    IllformedUtf8 { path: PathBuf },
+    #[error("Could not run the clean filter {command:?}")]
+    SpawnFilter { source: std::io::Error, command: BString },
+    #[error("The clean filter of the required filter driver '{driver}' failed for '{path}'")]
+    RequiredFilterFailed { driver: BString, path: BString },
+    #[error("Could not open the embedded repository at '{path}'")]
+    OpenEmbeddedRepository {
+        path: BString,
+        source: Box<crate::open::Error>,
+    },
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+}
@@ -0,0 +1,432 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+use crate::{
+    apply::{
+        hunks, outcome,
+        patch::{self, BinaryData, Content},
+        tree, Error, Options, Outcome, Target,
+    },
+    bstr::{BStr, BString, ByteSlice},
+    Repository,
+};
+
+/// The mode and content of a file, or `None` if it doesn't exist.
+///
+/// Submodules are represented by their textual form as seen in patches, `Subproject commit <hex>`.
+type FileState = Option<(EntryMode, Vec<u8>)>;
+
+pub(crate) fn apply(
+    repo: &Repository,
+    files: &[patch::File],
+    target: Target,
+    options: Options,
+) -> Result<Outcome, Error> {
+    let mut source = Source::new(repo, target)?;
+    let mut changes = BTreeMap::<BString, FileState>::new();
+    let mut rejected = Vec::new();
+    for file in files {
+        let old = match &file.old_path {
+            Some(path) => Some(
+                current_state(&mut source, &changes, path.as_ref())?
+                    .ok_or_else(|| Error::NotFound { path: path.clone() })?,
+            ),
+            None => None,
+        };
+        if let Some(new_path) = file
+            .new_path
+            .as_ref()
+            .filter(|path| file.old_path.as_ref() != Some(*path))
+        {
+            if current_state(&mut source, &changes, new_path.as_ref())?.is_some() {
+                return Err(Error::AlreadyExists { path: new_path.clone() });
+            }
+        }
+
+        let (old_mode, old_data) = old.unwrap_or((EntryMode::Blob, Vec::new()));
+        let mut has_rejections = false;
+        let new_data = match &file.content {
+            Content::Hunks(file_hunks) if file_hunks.is_empty() => old_data,
+            Content::Hunks(file_hunks) => {
+                let out = hunks::apply(&old_data, file_hunks, &options);
+                if !out.rejected.is_empty() {
+                    has_rejections = true;
+                    rejected.push(outcome::Rejected {
+                        path: file.path().to_owned(),
+                        hunks: out
+                            .rejected
+                            .into_iter()
+                            .map(|index| outcome::RejectedHunk {
+                                index,
+                                hunk: file_hunks[index].clone(),
+                            })
+                            .collect(),
+                    });
+                }
+                out.data
+            }
+            Content::Binary(binary) => {
+                let mismatch = || Error::BinaryMismatch {
+                    path: file.path().to_owned(),
+                };
+                if let Some(old_id) = file.old_id.as_ref().and_then(|hex| ObjectId::from_hex(hex).ok()) {
+                    if file.old_path.is_some()
+                        && gix_object::compute_hash(repo.object_hash(), gix_object::Kind::Blob, &old_data) != old_id
+                    {
+                        return Err(mismatch());
+                    }
+                }
+                match &binary.forward {
+                    BinaryData::Literal(data) => data.clone(),
+                    BinaryData::Delta(delta) => patch::binary::apply_delta(&old_data, delta).ok_or_else(mismatch)?,
+                }
+            }
+            Content::BinaryWithoutData if file.is_deletion() => Vec::new(),
+            Content::BinaryWithoutData => {
+                let missing = || Error::BinaryWithoutData {
+                    path: file.path().to_owned(),
+                };
+                let id = file
+                    .new_id
+                    .as_ref()
+                    .and_then(|hex| ObjectId::from_hex(hex).ok())
+                    .ok_or_else(missing)?;
+                match repo.try_find_object(id).ok().flatten() {
+                    Some(obj) if obj.kind == gix_object::Kind::Blob => obj.detach().data,
+                    _ => return Err(missing()),
+                }
+            }
+        };
+
+        match (&file.old_path, &file.new_path) {
+            (Some(old_path), None) => {
+                if !new_data.is_empty() && !has_rejections {
+                    return Err(Error::DeletedFileNotEmpty { path: old_path.clone() });
+                }
+                changes.insert(old_path.clone(), None);
+            }
+            (old_path, Some(new_path)) => {
+                let new_mode = file
+                    .new_mode
+                    .or_else(|| old_path.as_ref().map(|_| old_mode))
+                    .unwrap_or(EntryMode::Blob);
+                if new_mode == EntryMode::Commit && submodule_commit(&new_data).is_none() {
+                    return Err(Error::InvalidSubmoduleCommit { path: new_path.clone() });
+                }
+                if let Some(old_path) = old_path.as_ref().filter(|_| {
+                    file.rewrite
+                        .map_or(false, |rewrite| rewrite.kind == patch::RewriteKind::Rename)
+                }) {
+                    changes.insert(old_path.clone(), None);
+                }
+                changes.insert(new_path.clone(), Some((new_mode, new_data)));
+            }
+            (None, None) => unreachable!("the parser always sets at least one path"),
+        }
+    }
+
+    if !rejected.is_empty() && !options.partial {
+        return Ok(Outcome {
+            rejected,
+            applied: false,
+            tree: None,
+        });
+    }
+
+    let mut tree = None;
+    match target {
+        Target::WorkTree => write_worktree(source.workdir()?, &changes)?,
+        Target::Index => write_index(repo, source.index()?, &changes, None)?,
+        Target::IndexAndWorkTree => {
+            let workdir = source.workdir()?.to_owned();
+            write_worktree(&workdir, &changes)?;
+            write_index(repo, source.index()?, &changes, Some(&workdir))?;
+        }
+        Target::Tree(_) => {
+            let root = source.tree.as_ref().map(|(id, _)| *id);
+            let mut edits = BTreeMap::new();
+            for (path, state) in changes {
+                let state = match state {
+                    Some((mode, data)) => Some((mode, object_id(repo, mode, &data)?)),
+                    None => None,
+                };
+                edits.insert(path, state);
+            }
+            tree = Some(tree::write_with_edits(repo, root, &edits)?);
+        }
+    }
+    Ok(Outcome {
+        rejected,
+        applied: true,
+        tree,
+    })
+}
+
+/// Return the state of the file at `path` after all `changes` that were already applied.
+fn current_state(
+    source: &mut Source<'_>,
+    changes: &BTreeMap<BString, FileState>,
+    path: &BStr,
+) -> Result<FileState, Error> {
+    match changes.get(path) {
+        Some(state) => Ok(state.clone()),
+        None => source.read(path),
+    }
+}
+
+/// Access to the files that patches apply to, before they are changed.
+struct Source<'repo> {
+    repo: &'repo Repository,
+    target: Target,
+    index: Option<gix_index::File>,
+    tree: Option<(ObjectId, Vec<u8>)>,
+}
+
+impl<'repo> Source<'repo> {
+    fn new(repo: &'repo Repository, target: Target) -> Result<Self, Error> {
+        let tree = match target {
+            Target::Tree(id) => {
+                let mut tree = repo.find_object(id)?.peel_to_tree()?;
+                Some((tree.id, std::mem::take(&mut tree.data)))
+            }
+            Target::WorkTree | Target::Index | Target::IndexAndWorkTree => None,
+        };
+        let source = Source {
+            repo,
+            target,
+            index: None,
+            tree,
+        };
+        if matches!(target, Target::WorkTree | Target::IndexAndWorkTree) {
+            source.workdir()?;
+        }
+        Ok(source)
+    }
+
+    fn workdir(&self) -> Result<&'repo Path, Error> {
+        self.repo.work_dir().ok_or(Error::MissingWorkTree)
+    }
+
+    fn index(&mut self) -> Result<&mut gix_index::File, Error> {
+        if self.index.is_none() {
+            let index = match self.repo.open_index() {
+                Ok(index) => index,
+                Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+                    if err.kind() == std::io::ErrorKind::NotFound =>
+                {
+                    gix_index::File::from_state(gix_index::State::new(self.repo.object_hash()), self.repo.index_path())
+                }
+                Err(err) => return Err(err.into()),
+            };
+            self.index = Some(index);
+        }
+        Ok(self.index.as_mut().expect("just set"))
+    }
+
+    fn read(&mut self, path: &BStr) -> Result<FileState, Error> {
+        match self.target {
+            Target::WorkTree => read_worktree(self.workdir()?, path),
+            Target::Index => self.read_index(path),
+            Target::IndexAndWorkTree => {
+                let entry = self.index_entry(path)?;
+                if entry.map_or(true, |(mode, _)| mode != EntryMode::Commit) {
+                    let worktree_id = read_worktree(self.workdir()?, path)?.map(|(_, data)| {
+                        gix_object::compute_hash(self.repo.object_hash(), gix_object::Kind::Blob, &data)
+                    });
+                    if worktree_id != entry.map(|(_, id)| id) {
+                        return Err(Error::DiffersFromIndex { path: path.to_owned() });
+                    }
+                }
+                self.read_index(path)
+            }
+            Target::Tree(_) => {
+                let (id, data) = self.tree.as_ref().expect("set for tree targets");
+                let entry = crate::Tree::from_data(*id, data.clone(), self.repo)
+                    .lookup_entry(path.split(|b| *b == b'/'))?
+                    .map(|entry| (entry.mode(), entry.object_id()));
+                self.load(entry)
+            }
+        }
+    }
+
+    fn index_entry(&mut self, path: &BStr) -> Result<Option<(EntryMode, ObjectId)>, Error> {
+        Ok(self.index()?.entry_by_path_and_stage(path, 0).and_then(|entry| {
+            let mode = match entry.mode {
+                gix_index::entry::Mode::FILE => EntryMode::Blob,
+                gix_index::entry::Mode::FILE_EXECUTABLE => EntryMode::BlobExecutable,
+                gix_index::entry::Mode::SYMLINK => EntryMode::Link,
+                gix_index::entry::Mode::COMMIT => EntryMode::Commit,
+                _ => return None,
+            };
+            Some((mode, entry.id))
+        }))
+    }
+
+    fn read_index(&mut self, path: &BStr) -> Result<FileState, Error> {
+        let entry = self.index_entry(path)?;
+        self.load(entry)
+    }
+
+    fn load(&self, entry: Option<(EntryMode, ObjectId)>) -> Result<FileState, Error> {
+        Ok(match entry {
+            None => None,
+            Some((EntryMode::Tree, _)) => None,
+            Some((EntryMode::Commit, id)) => {
+                Some((EntryMode::Commit, format!("Subproject commit {id}\n").into_bytes()))
+            }
+            Some((mode, id)) => Some((mode, self.repo.find_object(id)?.detach().data)),
+        })
+    }
+}
+
+/// Parse the textual representation of a submodule in a patch.
+fn submodule_commit(data: &[u8]) -> Option<ObjectId> {
+    let hex = data.strip_prefix(b"Subproject commit ")?.trim_end();
+    ObjectId::from_hex(hex).ok()
+}
+
+/// Return the id of the object that `data` represents, writing it to the object database if it's a blob.
+fn object_id(repo: &Repository, mode: EntryMode, data: &[u8]) -> Result<ObjectId, Error> {
+    Ok(match mode {
+        EntryMode::Commit => submodule_commit(data).expect("validated when applying"),
+        _ => repo.write_blob(data)?.detach(),
+    })
+}
+
+fn io_err(path: PathBuf) -> impl FnOnce(std::io::Error) -> Error {
+    move |source| Error::Io { source, path }
+}
+
+fn read_worktree(workdir: &Path, path: &BStr) -> Result<FileState, Error> {
+    let fs_path = workdir.join(gix_path::from_bstr(path));
+    let meta = match std::fs::symlink_metadata(&fs_path) {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(io_err(fs_path)(err)),
+    };
+    Ok(if meta.file_type().is_symlink() {
+        let target = std::fs::read_link(&fs_path).map_err(io_err(fs_path))?;
+        Some((EntryMode::Link, gix_path::into_bstr(target).into_owned().into()))
+    } else if meta.is_dir() {
+        None
+    } else {
+        let data = std::fs::read(&fs_path).map_err(io_err(fs_path))?;
+        let mode = if is_executable(&meta) {
+            EntryMode::BlobExecutable
+        } else {
+            EntryMode::Blob
+        };
+        Some((mode, data))
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_meta: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Write all `changes` to the working tree at `workdir`, removing files first to allow directories and files to
+/// replace each other. Submodules are left untouched.
+fn write_worktree(workdir: &Path, changes: &BTreeMap<BString, FileState>) -> Result<(), Error> {
+    for path in changes
+        .iter()
+        .filter_map(|(path, state)| state.is_none().then_some(path))
+    {
+        let fs_path = workdir.join(gix_path::from_bstr(path.as_bstr()));
+        match std::fs::remove_file(&fs_path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(io_err(fs_path)(err)),
+        }
+        let mut dir = fs_path.parent();
+        while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+            if std::fs::remove_dir(parent).is_err() {
+                break;
+            }
+            dir = parent.parent();
+        }
+    }
+
+    for (path, (mode, data)) in changes
+        .iter()
+        .filter_map(|(path, state)| state.as_ref().map(|state| (path, state)))
+    {
+        if *mode == EntryMode::Commit {
+            continue;
+        }
+        let fs_path = workdir.join(gix_path::from_bstr(path.as_bstr()));
+        if let Some(parent) = fs_path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err(parent.to_owned()))?;
+        }
+        if std::fs::symlink_metadata(&fs_path).map_or(false, |meta| !meta.is_dir()) {
+            std::fs::remove_file(&fs_path).map_err(io_err(fs_path.clone()))?;
+        }
+        match mode {
+            #[cfg(unix)]
+            EntryMode::Link => {
+                let target = gix_path::from_bstr(data.as_bstr());
+                std::os::unix::fs::symlink(target, &fs_path).map_err(io_err(fs_path))?;
+            }
+            _ => {
+                std::fs::write(&fs_path, data).map_err(io_err(fs_path.clone()))?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    let mut permissions = std::fs::metadata(&fs_path)
+                        .map_err(io_err(fs_path.clone()))?
+                        .permissions();
+                    let file_mode = permissions.mode();
+                    permissions.set_mode(if *mode == EntryMode::BlobExecutable {
+                        file_mode | ((file_mode & 0o444) >> 2)
+                    } else {
+                        file_mode & !0o111
+                    });
+                    std::fs::set_permissions(&fs_path, permissions).map_err(io_err(fs_path))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write all `changes` to `index`, taking stat information from the files in `workdir` if set.
+fn write_index(
+    repo: &Repository,
+    index: &mut gix_index::File,
+    changes: &BTreeMap<BString, FileState>,
+    workdir: Option<&Path>,
+) -> Result<(), Error> {
+    index.remove_entries(|_, path, _| changes.contains_key(path));
+    for (path, (mode, data)) in changes
+        .iter()
+        .filter_map(|(path, state)| state.as_ref().map(|state| (path, state)))
+    {
+        let id = object_id(repo, *mode, data)?;
+        let stat = workdir
+            .filter(|_| *mode != EntryMode::Commit)
+            .and_then(|workdir| std::fs::symlink_metadata(workdir.join(gix_path::from_bstr(path.as_bstr()))).ok())
+            .and_then(|meta| gix_index::entry::Stat::from_fs(&meta).ok())
+            .unwrap_or_default();
+        let mode = match mode {
+            EntryMode::BlobExecutable => gix_index::entry::Mode::FILE_EXECUTABLE,
+            EntryMode::Link => gix_index::entry::Mode::SYMLINK,
+            EntryMode::Commit => gix_index::entry::Mode::COMMIT,
+            EntryMode::Blob | EntryMode::Tree => gix_index::entry::Mode::FILE,
+        };
+        index.dangerously_push_entry(stat, id, gix_index::entry::Flags::empty(), mode, path.as_bstr());
+    }
+    index.sort_entries();
+    index.write(Default::default())?;
+    Ok(())
+}
@@ -0,0 +1,257 @@
+use std::borrow::Cow;
+
+use crate::{
+    apply::{
+        patch::{Hunk, Line},
+        Options,
+    },
+    bstr::ByteSlice,
+};
+
+/// The result of applying hunks to the content of a file.
+pub(crate) struct Outcome {
+    /// The content with all applicable hunks applied.
+    pub data: Vec<u8>,
+    /// The indices of the hunks that couldn't be applied.
+    pub rejected: Vec<usize>,
+}
+
+/// Apply `hunks` to `data`, searching for the place to apply each hunk starting at the position given in its header,
+/// and moving further away from it until the lines it expects are found.
+pub(crate) fn apply(data: &[u8], hunks: &[Hunk], options: &Options) -> Outcome {
+    let mut image: Vec<Cow<'_, [u8]>> = data.lines_with_terminator().map(Cow::Borrowed).collect();
+    let mut rejected = Vec::new();
+    // The difference between line numbers in the original file and positions in `image`.
+    let mut offset = 0isize;
+    // Hunks can only apply after the end of the previously applied one.
+    let mut min_pos = 0;
+    for (hunk_index, hunk) in hunks.iter().enumerate() {
+        let leading = hunk.lines.iter().take_while(|l| matches!(l, Line::Context(_))).count();
+        let trailing = if leading == hunk.lines.len() {
+            0
+        } else {
+            hunk.lines
+                .iter()
+                .rev()
+                .take_while(|l| matches!(l, Line::Context(_)))
+                .count()
+        };
+        let mut applied = false;
+        for fuzz in 0..=options.fuzz as usize {
+            if fuzz > 0 && fuzz > leading.max(trailing) {
+                break;
+            }
+            let (skip_leading, skip_trailing) = (fuzz.min(leading), fuzz.min(trailing));
+            let lines = &hunk.lines[skip_leading..hunk.lines.len() - skip_trailing];
+            let preimage: Vec<&[u8]> = lines
+                .iter()
+                .filter_map(|line| match line {
+                    Line::Context(line) | Line::Removal(line) => Some(line.as_slice()),
+                    Line::Addition(_) => None,
+                })
+                .collect();
+            // A hunk that doesn't remove lines gives the line after which to insert lines.
+            let start = if hunk.old_lines == 0 {
+                hunk.old_start as isize
+            } else {
+                hunk.old_start as isize - 1
+            };
+            let expected = (start + skip_leading as isize + offset).max(0) as usize;
+            // Without context on one side, the hunk is anchored to the beginning or end of the file, unless it doesn't have
+            // context at all.
+            let match_beginning = skip_leading == 0 && leading == 0 && trailing != 0 && hunk.old_start <= 1;
+            let match_end = skip_trailing == 0 && trailing == 0 && leading != 0;
+
+            let pos = match find_position(
+                &image,
+                &preimage,
+                expected,
+                min_pos,
+                match_beginning,
+                match_end,
+                options,
+            ) {
+                Some(pos) => pos,
+                None => continue,
+            };
+            let mut replacement = Vec::new();
+            let mut cursor = pos;
+            for line in lines {
+                match line {
+                    Line::Context(_) => {
+                        replacement.push(image[cursor].clone());
+                        cursor += 1;
+                    }
+                    Line::Removal(_) => cursor += 1,
+                    Line::Addition(line) => replacement.push(if options.fix_whitespace {
+                        Cow::Owned(strip_trailing_whitespace(line))
+                    } else {
+                        Cow::Owned(line.to_vec())
+                    }),
+                }
+            }
+            let replacement_len = replacement.len();
+            image.splice(pos..pos + preimage.len(), replacement);
+            offset += pos as isize - expected as isize + replacement_len as isize - preimage.len() as isize;
+            min_pos = pos + replacement_len;
+            applied = true;
+            break;
+        }
+        if !applied {
+            rejected.push(hunk_index);
+        }
+    }
+    Outcome {
+        data: image.concat(),
+        rejected,
+    }
+}
+
+/// Find the position at which `preimage` matches `image`, starting at `expected` and moving away from it in both
+/// directions, but never before `min_pos`.
+fn find_position(
+    image: &[Cow<'_, [u8]>],
+    preimage: &[&[u8]],
+    expected: usize,
+    min_pos: usize,
+    match_beginning: bool,
+    match_end: bool,
+    options: &Options,
+) -> Option<usize> {
+    let max_pos = image.len().checked_sub(preimage.len())?;
+    if min_pos > max_pos {
+        return None;
+    }
+    let matches = |pos: usize| {
+        image[pos..pos + preimage.len()]
+            .iter()
+            .zip(preimage)
+            .all(|(actual, expected)| lines_match(actual, expected, options.ignore_whitespace))
+    };
+    if match_beginning || match_end {
+        let pos = if match_beginning { 0 } else { max_pos };
+        return (pos >= min_pos && (!match_beginning || !match_end || pos == max_pos) && matches(pos)).then_some(pos);
+    }
+    let expected = expected.clamp(min_pos, max_pos);
+    for distance in 0..=max_pos - min_pos {
+        if let Some(pos) = expected.checked_add(distance).filter(|pos| *pos <= max_pos) {
+            if matches(pos) {
+                return Some(pos);
+            }
+        }
+        if distance > 0 {
+            if let Some(pos) = expected.checked_sub(distance).filter(|pos| *pos >= min_pos) {
+                if matches(pos) {
+                    return Some(pos);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn lines_match(actual: &[u8], expected: &[u8], ignore_whitespace: bool) -> bool {
+    if !ignore_whitespace {
+        return actual == expected;
+    }
+    // Like `git apply --ignore-whitespace`, treat runs of whitespace as equal regardless of their length, and ignore
+    // whitespace at the end of lines.
+    let normalize = |line: &[u8]| {
+        line.fields_with(|c| c.is_whitespace())
+            .flat_map(|word| word.iter().copied().chain(Some(b' ')))
+            .collect::<Vec<_>>()
+    };
+    normalize(actual) == normalize(expected)
+}
+
+fn strip_trailing_whitespace(line: &[u8]) -> Vec<u8> {
+    let (content, newline) = match line.strip_suffix(b"\n") {
+        Some(content) => (content, "\n"),
+        None => (line, ""),
+    };
+    let mut out = content.trim_end_with(|c| c.is_whitespace()).to_vec();
+    out.extend_from_slice(newline.as_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::apply;
+    use crate::apply::{
+        patch::{Hunk, Line},
+        Options,
+    };
+
+    fn hunk(old_start: u32, lines: &[(char, &str)]) -> Hunk {
+        let lines: Vec<_> = lines
+            .iter()
+            .map(|(kind, line)| match kind {
+                ' ' => Line::Context(format!("{line}\n").into()),
+                '-' => Line::Removal(format!("{line}\n").into()),
+                _ => Line::Addition(format!("{line}\n").into()),
+            })
+            .collect();
+        Hunk {
+            old_start,
+            old_lines: lines.iter().filter(|l| !matches!(l, Line::Addition(_))).count() as u32,
+            new_start: old_start,
+            new_lines: lines.iter().filter(|l| !matches!(l, Line::Removal(_))).count() as u32,
+            function_name: Default::default(),
+            lines,
+        }
+    }
+
+    #[test]
+    fn hunks_are_applied_with_offset() {
+        let data = "0\n1\n2\n3\n4\n5\n";
+        let hunks = [hunk(2, &[(' ', "2"), ('-', "3"), ('+', "three"), (' ', "4")])];
+        let outcome = apply(data.as_bytes(), &hunks, &Options::default());
+        assert!(outcome.rejected.is_empty());
+        assert_eq!(
+            outcome.data, b"0\n1\n2\nthree\n4\n5\n",
+            "the hunk is found one line later"
+        );
+    }
+
+    #[test]
+    fn fuzz_allows_to_ignore_context() {
+        let data = "1\n2\nchanged\n";
+        let hunks = [hunk(1, &[(' ', "1"), ('-', "2"), ('+', "two"), (' ', "3")])];
+        let outcome = apply(data.as_bytes(), &hunks, &Options::default());
+        assert_eq!(outcome.rejected, [0]);
+        assert_eq!(
+            outcome.data,
+            data.as_bytes(),
+            "rejected hunks leave the content unchanged"
+        );
+
+        let outcome = apply(
+            data.as_bytes(),
+            &hunks,
+            &Options {
+                fuzz: 1,
+                ..Default::default()
+            },
+        );
+        assert!(outcome.rejected.is_empty());
+        assert_eq!(outcome.data, b"1\ntwo\nchanged\n");
+    }
+
+    #[test]
+    fn whitespace_can_be_ignored_and_fixed() {
+        let data = "a  b\nc\n";
+        let hunks = [hunk(1, &[(' ', "a b"), ('-', "c"), ('+', "d  ")])];
+        assert_eq!(apply(data.as_bytes(), &hunks, &Options::default()).rejected, [0]);
+        let outcome = apply(
+            data.as_bytes(),
+            &hunks,
+            &Options {
+                ignore_whitespace: true,
+                fix_whitespace: true,
+                ..Default::default()
+            },
+        );
+        assert!(outcome.rejected.is_empty());
+        assert_eq!(outcome.data, b"a  b\nd\n", "context is retained as it is in the file");
+    }
+}
@@ -0,0 +1,366 @@
+//! Read patches from mailboxes as produced by `git format-patch` and turn them into commits, similar to `git am`.
+use gix_hash::ObjectId;
+
+use crate::{
+    apply::{outcome, patch},
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+};
+
+/// A single message of a mailbox, containing a patch along with the information needed to commit it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// The author of the patch, taken from the `From` and `Date` headers.
+    pub author: gix_actor::Signature,
+    /// The subject of the message, without prefixes like `[PATCH 1/2]`.
+    pub subject: BString,
+    /// The text between the headers and the patch, without the separating `---` line and the diff statistics after it.
+    pub body: BString,
+    /// All file patches contained in the message.
+    pub files: Vec<patch::File>,
+}
+
+impl Message {
+    /// Return the commit message, which is made of the subject and the body separated by an empty line.
+    pub fn commit_message(&self) -> BString {
+        let mut message = self.subject.clone();
+        let body = self.body.trim();
+        if !body.is_empty() {
+            message.push_str("\n\n");
+            message.push_str(body);
+        }
+        message.push(b'\n');
+        message
+    }
+}
+
+///
+pub mod parse {
+    use crate::bstr::BString;
+
+    /// The error returned by [`parse()`][crate::apply::mailbox::parse()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Message {message} lacks the '{name}' header")]
+        MissingHeader { name: &'static str, message: usize },
+        #[error("The author {value:?} in message {message} isn't of the form 'Name <email>'")]
+        InvalidAuthor { value: BString, message: usize },
+        #[error("The date {value:?} in message {message} could not be parsed")]
+        InvalidDate {
+            value: BString,
+            message: usize,
+            source: gix_date::parse::Error,
+        },
+        #[error("The patch in message {message} could not be parsed")]
+        Patch {
+            message: usize,
+            source: crate::apply::patch::parse::Error,
+        },
+    }
+}
+
+/// Parse all messages in `data`, a mailbox in the `mbox` format as written by `git format-patch --stdout`, or a single
+/// message without the leading `From ` line.
+///
+/// Header values encoded according to RFC 2047 are decoded, and so are bodies with `quoted-printable` transfer encoding.
+/// Headers at the beginning of the body, as written by `git format-patch` if the sender isn't the author, take precedence.
+/// Message numbers in errors are zero-based.
+pub fn parse(data: &[u8]) -> Result<Vec<Message>, parse::Error> {
+    let mut messages = Vec::new();
+    let mut current = Vec::new();
+    let mut previous_line_empty = true;
+    for line in data.lines_with_terminator() {
+        if previous_line_empty && line.starts_with(b"From ") {
+            if !current.is_empty() {
+                messages.push(std::mem::take(&mut current));
+            }
+            previous_line_empty = false;
+            continue;
+        }
+        previous_line_empty = line.trim_end().is_empty();
+        current.extend_from_slice(line);
+    }
+    if !current.trim().is_empty() {
+        messages.push(current);
+    }
+    messages
+        .iter()
+        .enumerate()
+        .map(|(index, message)| parse_message(message, index))
+        .collect()
+}
+
+fn parse_message(data: &[u8], index: usize) -> Result<Message, parse::Error> {
+    let mut lines = data.lines_with_terminator().peekable();
+    let mut headers = Headers::default();
+    headers.read(&mut lines);
+
+    let mut body: BString = lines.flat_map(|line| line.iter().copied()).collect::<Vec<_>>().into();
+    if headers.get("content-transfer-encoding").map_or(false, |encoding| {
+        encoding.trim().eq_ignore_ascii_case(b"quoted-printable")
+    }) {
+        body = decode_quoted_printable(body.as_ref());
+    }
+
+    let body = {
+        let mut lines = body.lines_with_terminator().peekable();
+        while lines.next_if(|line| line.trim().is_empty()).is_some() {}
+        if lines.peek().map_or(false, |line| {
+            [&b"From:"[..], b"Subject:", b"Date:"]
+                .iter()
+                .any(|h| line.starts_with(h))
+        }) {
+            headers.read(&mut lines);
+        }
+        lines.flat_map(|line| line.iter().copied()).collect::<Vec<_>>()
+    };
+
+    let from = headers.get("from").ok_or(parse::Error::MissingHeader {
+        name: "From",
+        message: index,
+    })?;
+    let (name, email) = parse_author(from.as_ref()).ok_or_else(|| parse::Error::InvalidAuthor {
+        value: from.clone(),
+        message: index,
+    })?;
+    let date = headers.get("date").ok_or(parse::Error::MissingHeader {
+        name: "Date",
+        message: index,
+    })?;
+    let time = gix_date::parse(date.to_str_lossy().trim(), None).map_err(|source| parse::Error::InvalidDate {
+        value: date.clone(),
+        message: index,
+        source,
+    })?;
+    let subject = headers
+        .get("subject")
+        .map(|s| clean_subject(s.as_ref()))
+        .unwrap_or_default();
+
+    let message_end = body
+        .lines_with_terminator()
+        .scan(0, |pos, line| {
+            let start = *pos;
+            *pos += line.len();
+            Some((start, line))
+        })
+        .find(|(_, line)| line.trim_end() == b"---" || line.starts_with(b"diff --git ") || line.starts_with(b"Index: "))
+        .map_or(body.len(), |(start, _)| start);
+    let files =
+        patch::parse(&body[message_end..], 1).map_err(|source| parse::Error::Patch { message: index, source })?;
+
+    Ok(Message {
+        author: gix_actor::Signature { name, email, time },
+        subject,
+        body: body[..message_end].trim_end().as_bstr().to_owned(),
+        files,
+    })
+}
+
+/// Mail headers with lowercase names, unfolded and decoded.
+#[derive(Default)]
+struct Headers(Vec<(String, BString)>);
+
+impl Headers {
+    /// Read headers up to and including the next empty line.
+    fn read<'a>(&mut self, lines: &mut std::iter::Peekable<impl Iterator<Item = &'a [u8]>>) {
+        for line in lines.by_ref() {
+            let line = line.trim_end_with(|c| c == '\r' || c == '\n');
+            if line.is_empty() {
+                break;
+            }
+            if line[0] == b' ' || line[0] == b'\t' {
+                if let Some((_, value)) = self.0.last_mut() {
+                    value.push(b' ');
+                    value.push_str(line.trim_start());
+                }
+                continue;
+            }
+            if let Some(colon) = line.find_byte(b':') {
+                let name = line[..colon].to_str_lossy().trim().to_ascii_lowercase();
+                self.0.push((name, line[colon + 1..].trim().into()));
+            }
+        }
+    }
+
+    /// Return the decoded value of the last header with `name`, which must be lowercase.
+    fn get(&self, name: &str) -> Option<BString> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| decode_header_value(value.as_ref()))
+    }
+}
+
+/// Split `Name <email>` into its parts, removing quotes around the name.
+fn parse_author(value: &BStr) -> Option<(BString, BString)> {
+    let open = value.rfind_byte(b'<')?;
+    let close = open + value[open..].find_byte(b'>')?;
+    let email = value[open + 1..close].trim();
+    let mut name = value[..open].trim();
+    if name.len() >= 2 && name.starts_with(b"\"") && name.ends_with(b"\"") {
+        name = &name[1..name.len() - 1];
+    }
+    if name.is_empty() {
+        name = email;
+    }
+    Some((name.into(), email.into()))
+}
+
+/// Remove reply markers and bracketed prefixes like `[PATCH 1/2]` from the beginning of `subject`.
+fn clean_subject(subject: &BStr) -> BString {
+    let mut subject = subject.trim();
+    loop {
+        if subject.starts_with(b"[") {
+            match subject.find_byte(b']') {
+                Some(end) => subject = subject[end + 1..].trim_start(),
+                None => break,
+            }
+        } else if subject.len() >= 3 && subject[..3].eq_ignore_ascii_case(b"re:") {
+            subject = subject[3..].trim_start();
+        } else {
+            break;
+        }
+    }
+    subject.into()
+}
+
+/// Decode all RFC 2047 encoded words like `=?UTF-8?q?J=C3=B6rg?=` in `value`, dropping whitespace between encoded words.
+fn decode_header_value(value: &BStr) -> BString {
+    let mut out = BString::default();
+    let mut rest: &[u8] = value.as_bytes();
+    let mut previous_was_encoded = false;
+    while !rest.is_empty() {
+        let start = rest.find(b"=?").unwrap_or(rest.len());
+        let (before, candidate) = rest.split_at(start);
+        match decode_encoded_word(candidate) {
+            Some((decoded, consumed)) => {
+                if !(previous_was_encoded && before.trim().is_empty()) {
+                    out.push_str(before);
+                }
+                out.push_str(decoded);
+                rest = &candidate[consumed..];
+                previous_was_encoded = true;
+            }
+            None => {
+                let end = (start + 2).min(rest.len());
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+                previous_was_encoded = false;
+            }
+        }
+    }
+    out
+}
+
+/// Decode the encoded word at the beginning of `input` and return it along with the amount of consumed bytes.
+fn decode_encoded_word(input: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let inner = input.strip_prefix(b"=?")?;
+    let charset_end = inner.find_byte(b'?')?;
+    let charset = &inner[..charset_end];
+    let encoding = *inner.get(charset_end + 1)?;
+    if inner.get(charset_end + 2) != Some(&b'?') {
+        return None;
+    }
+    let text = &inner[charset_end + 3..];
+    let text_end = text.find(b"?=")?;
+    let text = &text[..text_end];
+    let decoded = match encoding.to_ascii_lowercase() {
+        b'q' => {
+            let underscores_as_spaces: Vec<u8> = text.iter().map(|b| if *b == b'_' { b' ' } else { *b }).collect();
+            decode_quoted_printable(&underscores_as_spaces).into()
+        }
+        b'b' => decode_base64(text)?,
+        _ => return None,
+    };
+    let decoded = if charset.eq_ignore_ascii_case(b"iso-8859-1") || charset.eq_ignore_ascii_case(b"latin1") {
+        decoded.iter().map(|b| char::from(*b)).collect::<String>().into_bytes()
+    } else {
+        decoded
+    };
+    Some((decoded, 2 + charset_end + 3 + text_end + 2))
+}
+
+fn decode_quoted_printable(input: &[u8]) -> BString {
+    let mut out = Vec::with_capacity(input.len());
+    let mut pos = 0;
+    while pos < input.len() {
+        let b = input[pos];
+        if b == b'=' {
+            let hex = input.get(pos + 1..pos + 3);
+            match hex.and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()) {
+                Some(byte) => {
+                    out.push(byte);
+                    pos += 3;
+                }
+                // A soft line break.
+                None if input[pos + 1..].starts_with(b"\r\n") => pos += 3,
+                None if input[pos + 1..].starts_with(b"\n") => pos += 2,
+                None => {
+                    out.push(b);
+                    pos += 1;
+                }
+            }
+        } else {
+            out.push(b);
+            pos += 1;
+        }
+    }
+    out.into()
+}
+
+fn decode_base64(input: &[u8]) -> Option<Vec<u8>> {
+    let value = |b: u8| -> Option<u32> {
+        Some(match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        } as u32)
+    };
+    let input = input.trim_end_with(|c| c == '=');
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    for chunk in input.chunks(4) {
+        let mut acc = 0u32;
+        for b in chunk {
+            acc = acc << 6 | value(*b)?;
+        }
+        acc <<= 6 * (4 - chunk.len() as u32);
+        let bytes = acc.to_be_bytes();
+        out.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+    Some(out)
+}
+
+/// The result of [`Repository::apply_mailbox()`][crate::Repository::apply_mailbox()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The ids of all commits that were created, one for each message that applied.
+    pub commits: Vec<ObjectId>,
+    /// If set, the message with the given index had hunks that didn't apply, which stopped the application of all
+    /// messages at this point.
+    pub rejected: Option<(usize, Vec<outcome::Rejected>)>,
+}
+
+///
+pub mod apply {
+    /// The error returned by [`Repository::apply_mailbox()`][crate::Repository::apply_mailbox()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        PeelHead(#[from] crate::head::peel::Error),
+        #[error("Failed to apply the patch of message {message}")]
+        Apply {
+            message: usize,
+            source: crate::apply::Error,
+        },
+        #[error(transparent)]
+        Commit(#[from] crate::commit::Error),
+    }
+}
@@ -0,0 +1,127 @@
+//! Apply patches to the working tree, the index or a tree similar to `git apply`, and create commits from mailboxes
+//! similar to `git am`.
+use gix_hash::ObjectId;
+
+use crate::bstr::BString;
+
+///
+pub mod patch;
+
+///
+pub mod mailbox;
+
+mod hunks;
+mod tree;
+
+pub(crate) mod function;
+
+/// The location to which [patches][patch::File] are applied with [`Repository::apply_patch()`][crate::Repository::apply_patch()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// Change the files in the working tree only, similar to `git apply`.
+    WorkTree,
+    /// Change the entries of the index only without touching the working tree, similar to `git apply --cached`.
+    Index,
+    /// Change the files in the working tree and the index, which must match for all affected files,
+    /// similar to `git apply --index`.
+    IndexAndWorkTree,
+    /// Change the tree with the given id, or the tree the given commit refers to, and write all changed trees to the
+    /// object database. The resulting tree is available as [`Outcome::tree`].
+    Tree(ObjectId),
+}
+
+/// Options to control how patches are applied.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The maximum amount of context lines that may be ignored at the beginning and end of a hunk if it doesn't apply
+    /// with all of its context, similar to `git apply -C`, which leaves at least `<n>` lines of context.
+    ///
+    /// With the default of 0, all context lines have to match.
+    pub fuzz: u32,
+    /// If `true`, whitespace is ignored when matching context and removed lines, similar to `git apply --ignore-whitespace`.
+    pub ignore_whitespace: bool,
+    /// If `true`, remove trailing whitespace from added lines, similar to `git apply --whitespace=fix`.
+    pub fix_whitespace: bool,
+    /// If `true`, apply all hunks that apply cleanly even if others are rejected, similar to `git apply --reject`.
+    ///
+    /// Otherwise, nothing is changed if a single hunk is rejected.
+    pub partial: bool,
+}
+
+///
+pub mod outcome {
+    use crate::{apply::patch::Hunk, bstr::BString};
+
+    /// A hunk that couldn't be applied.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct RejectedHunk {
+        /// The index of the hunk within the hunks of its file patch.
+        pub index: usize,
+        /// The hunk itself.
+        pub hunk: Hunk,
+    }
+
+    /// A file with hunks that couldn't be applied.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct Rejected {
+        /// The path of the file to which the hunks should have been applied.
+        pub path: BString,
+        /// All hunks that didn't apply, in order.
+        pub hunks: Vec<RejectedHunk>,
+    }
+}
+
+/// The result of [`Repository::apply_patch()`][crate::Repository::apply_patch()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// All files with hunks that couldn't be applied, in the order of the patch.
+    pub rejected: Vec<outcome::Rejected>,
+    /// If `true`, changes were written to the target. This is only `false` if hunks were rejected and
+    /// [partial application][Options::partial] wasn't allowed.
+    pub applied: bool,
+    /// The id of the resulting tree if the [target][Target::Tree] was a tree and changes were applied.
+    pub tree: Option<ObjectId>,
+}
+
+/// The error returned by [`Repository::apply_patch()`][crate::Repository::apply_patch()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("A working tree is required to apply patches to it")]
+    MissingWorkTree,
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToTree(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    DecodeTree(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    EditTree(#[from] crate::object::tree::editor::Error),
+    #[error("Could not access '{}'", .path.display())]
+    Io {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("'{path}' doesn't exist but is changed by the patch")]
+    NotFound { path: BString },
+    #[error("'{path}' already exists but is added by the patch")]
+    AlreadyExists { path: BString },
+    #[error("'{path}' in the working tree doesn't match the index")]
+    DiffersFromIndex { path: BString },
+    #[error(
+        "The patch doesn't contain the data to change the binary file '{path}', and its new version isn't available"
+    )]
+    BinaryWithoutData { path: BString },
+    #[error("The binary patch for '{path}' doesn't apply to its current version")]
+    BinaryMismatch { path: BString },
+    #[error("'{path}' is deleted by the patch but would still have content afterwards")]
+    DeletedFileNotEmpty { path: BString },
+    #[error("The patch for submodule '{path}' doesn't result in a valid commit")]
+    InvalidSubmoduleCommit { path: BString },
+}
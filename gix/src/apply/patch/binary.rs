@@ -0,0 +1,122 @@
+/// The alphabet of git's base85 encoding, which differs from the one described in RFC 1924 only in name.
+const BASE85_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+fn base85_value(b: u8) -> Option<u32> {
+    BASE85_ALPHABET.iter().position(|c| *c == b).map(|pos| pos as u32)
+}
+
+/// Decode a single line of base85 data as found in binary patches, which starts with a character encoding the amount
+/// of decoded bytes, and append the decoded bytes to `out`.
+pub(crate) fn decode_base85_line(line: &[u8], out: &mut Vec<u8>) -> Option<()> {
+    let (len, data) = line.split_first()?;
+    let len = match len {
+        b'A'..=b'Z' => len - b'A' + 1,
+        b'a'..=b'z' => len - b'a' + 27,
+        _ => return None,
+    } as usize;
+    if data.len() != (len + 3) / 4 * 5 {
+        return None;
+    }
+    let start = out.len();
+    for chunk in data.chunks(5) {
+        let mut acc = 0u32;
+        for b in chunk {
+            acc = acc.checked_mul(85)?.checked_add(base85_value(*b)?)?;
+        }
+        out.extend_from_slice(&acc.to_be_bytes());
+    }
+    out.truncate(start + len);
+    Some(())
+}
+
+/// Decompress the zlib stream in `data`, which is expected to decompress to exactly `size` bytes.
+pub(crate) fn inflate(data: &[u8], size: usize) -> Option<Vec<u8>> {
+    // Leave room for one more byte to detect streams that decompress to more than `size` bytes.
+    let mut out = vec![0; size + 1];
+    let mut inflate = gix_features::zlib::Inflate::default();
+    let (status, _consumed_in, written) = inflate.once(data, &mut out).ok()?;
+    out.truncate(size);
+    (status == gix_features::zlib::Status::StreamEnd && written == size).then_some(out)
+}
+
+/// Apply `delta` in git's delta format to `base` and return the result, or `None` if `delta` is invalid or doesn't
+/// apply to `base`.
+pub(crate) fn apply_delta(base: &[u8], delta: &[u8]) -> Option<Vec<u8>> {
+    fn size(data: &mut &[u8]) -> Option<usize> {
+        let mut size = 0usize;
+        let mut shift = 0;
+        loop {
+            let (b, rest) = data.split_first()?;
+            *data = rest;
+            size |= usize::from(b & 0x7f).checked_shl(shift)?;
+            shift += 7;
+            if b & 0x80 == 0 {
+                return Some(size);
+            }
+        }
+    }
+    let mut data = delta;
+    if size(&mut data)? != base.len() {
+        return None;
+    }
+    let result_size = size(&mut data)?;
+    let mut out = Vec::with_capacity(result_size);
+    while let Some((cmd, rest)) = data.split_first() {
+        data = rest;
+        if cmd & 0x80 != 0 {
+            let mut offset = 0usize;
+            let mut len = 0usize;
+            for (bit, shift) in [(0x01, 0), (0x02, 8), (0x04, 16), (0x08, 24)] {
+                if cmd & bit != 0 {
+                    let (b, rest) = data.split_first()?;
+                    data = rest;
+                    offset |= usize::from(*b) << shift;
+                }
+            }
+            for (bit, shift) in [(0x10, 0), (0x20, 8), (0x40, 16)] {
+                if cmd & bit != 0 {
+                    let (b, rest) = data.split_first()?;
+                    data = rest;
+                    len |= usize::from(*b) << shift;
+                }
+            }
+            if len == 0 {
+                len = 0x10000;
+            }
+            out.extend_from_slice(base.get(offset..offset.checked_add(len)?)?);
+        } else if *cmd != 0 {
+            let len = usize::from(*cmd);
+            out.extend_from_slice(data.get(..len)?);
+            data = &data[len..];
+        } else {
+            return None;
+        }
+    }
+    (out.len() == result_size).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn decode_base85_line() {
+        let mut out = Vec::new();
+        super::decode_base85_line(b"B00961", &mut out).expect("valid");
+        assert_eq!(out, [0, 1], "the first character determines the length");
+        assert!(
+            super::decode_base85_line(b"B0096", &mut out).is_none(),
+            "the data must be complete"
+        );
+    }
+
+    #[test]
+    fn apply_delta() {
+        // base size 5, result size 8, copy 5 bytes at offset 0, then insert "!!!".
+        let delta = [5, 8, 0x80 | 0x10, 5, 3, b'!', b'!', b'!'];
+        assert_eq!(
+            super::apply_delta(b"hello", &delta).as_deref(),
+            Some(b"hello!!!".as_slice())
+        );
+        assert_eq!(super::apply_delta(b"hell", &delta), None, "the base size must match");
+    }
+}
@@ -0,0 +1,167 @@
+//! Types describing a patch as parsed from the output of `git diff`, `git format-patch` or `diff -u`.
+use gix_object::tree::EntryMode;
+
+use crate::bstr::{BStr, BString, ByteSlice};
+
+pub(crate) mod binary;
+///
+pub mod parse;
+pub use parse::function::parse;
+
+/// The way a file was rewritten, as indicated by the extended headers of a git patch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RewriteKind {
+    /// The old file was renamed to the new one.
+    Rename,
+    /// The new file is a copy of the old one, which remains in place.
+    Copy,
+}
+
+/// Information about a rename or copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rewrite {
+    /// Whether the file was renamed or copied.
+    pub kind: RewriteKind,
+    /// The similarity between the old and the new file in percent, if it was given.
+    pub similarity: Option<u8>,
+}
+
+/// A single line of a [`Hunk`].
+///
+/// Each line includes its terminator, unless it's the last line of a file that doesn't end with a newline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Line {
+    /// A line that is present in both versions of the file.
+    Context(BString),
+    /// A line that is only present in the old version of the file.
+    Removal(BString),
+    /// A line that is only present in the new version of the file.
+    Addition(BString),
+}
+
+impl Line {
+    /// Return the content of the line, including its terminator if it has one.
+    pub fn content(&self) -> &BStr {
+        match self {
+            Line::Context(line) | Line::Removal(line) | Line::Addition(line) => line.as_bstr(),
+        }
+    }
+
+    fn content_mut(&mut self) -> &mut BString {
+        match self {
+            Line::Context(line) | Line::Removal(line) | Line::Addition(line) => line,
+        }
+    }
+}
+
+/// A set of changed lines along with the unchanged lines around them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hunk {
+    /// The one-based line number in the old file at which the hunk starts, or the line after which lines are inserted
+    /// if `old_lines` is 0.
+    pub old_start: u32,
+    /// The amount of lines in the old file covered by this hunk.
+    pub old_lines: u32,
+    /// The one-based line number in the new file at which the hunk starts, or the line after which lines were removed
+    /// if `new_lines` is 0.
+    pub new_start: u32,
+    /// The amount of lines in the new file covered by this hunk.
+    pub new_lines: u32,
+    /// The text after the line ranges in the hunk header, typically the name of the function the hunk is in.
+    pub function_name: BString,
+    /// The lines of the hunk, in order.
+    pub lines: Vec<Line>,
+}
+
+impl Hunk {
+    /// Return an iterator over the lines as they are expected in the old version of the file.
+    pub fn old_lines(&self) -> impl Iterator<Item = &BStr> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Context(line) | Line::Removal(line) => Some(line.as_bstr()),
+            Line::Addition(_) => None,
+        })
+    }
+
+    /// Return an iterator over the lines as they will be in the new version of the file.
+    pub fn new_lines(&self) -> impl Iterator<Item = &BStr> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Context(line) | Line::Addition(line) => Some(line.as_bstr()),
+            Line::Removal(_) => None,
+        })
+    }
+}
+
+/// The data of a binary patch, already decoded and decompressed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BinaryData {
+    /// The complete content of the resulting file.
+    Literal(Vec<u8>),
+    /// Instructions in git's delta format to produce the resulting file from the original one.
+    Delta(Vec<u8>),
+}
+
+/// A binary patch as produced by `git diff --binary`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Binary {
+    /// The data to turn the old file into the new one.
+    pub forward: BinaryData,
+    /// The data to turn the new file into the old one, if present.
+    pub reverse: Option<BinaryData>,
+}
+
+/// The change to the content of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Content {
+    /// The content changed by lines, with hunks in the order they apply to the file.
+    ///
+    /// The list of hunks is empty if the content of the file didn't change, for instance if only its mode was changed,
+    /// or if an empty file was added or deleted.
+    Hunks(Vec<Hunk>),
+    /// The content changed in a binary file, along with the data needed to apply the change.
+    Binary(Binary),
+    /// The content changed in a binary file, but the patch doesn't contain the data needed to apply it, as indicated by
+    /// `Binary files a/… and b/… differ`.
+    BinaryWithoutData,
+}
+
+/// A change to a single file in a patch.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct File {
+    /// The path of the file before the change relative to the root of the repository, or `None` if the file was added.
+    pub old_path: Option<BString>,
+    /// The path of the file after the change relative to the root of the repository, or `None` if the file was deleted.
+    pub new_path: Option<BString>,
+    /// The mode of the file before the change, if known.
+    pub old_mode: Option<EntryMode>,
+    /// The mode of the file after the change, if known.
+    pub new_mode: Option<EntryMode>,
+    /// Set if the new file is a renamed or copied version of the old one.
+    pub rewrite: Option<Rewrite>,
+    /// The possibly abbreviated hexadecimal id of the old file as given in the `index` line of a git patch.
+    pub old_id: Option<BString>,
+    /// The possibly abbreviated hexadecimal id of the new file as given in the `index` line of a git patch.
+    pub new_id: Option<BString>,
+    /// The change to the content.
+    pub content: Content,
+}
+
+impl File {
+    /// Return `true` if this file is added by the patch.
+    pub fn is_addition(&self) -> bool {
+        self.old_path.is_none()
+    }
+
+    /// Return `true` if this file is deleted by the patch.
+    pub fn is_deletion(&self) -> bool {
+        self.new_path.is_none()
+    }
+
+    /// Return the path to show for this file, which is the new path unless the file was deleted.
+    pub fn path(&self) -> &BStr {
+        self.new_path
+            .as_ref()
+            .or(self.old_path.as_ref())
+            .expect("at least one path is always set")
+            .as_bstr()
+    }
+}
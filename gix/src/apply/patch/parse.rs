@@ -0,0 +1,475 @@
+use crate::bstr::BString;
+
+/// The error returned by [`parse()`][crate::apply::patch::parse()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The hunk header in line {line} is invalid: {header:?}")]
+    InvalidHunkHeader { line: usize, header: BString },
+    #[error("The hunk starting in line {line} ended unexpectedly")]
+    TruncatedHunk { line: usize },
+    #[error("Line {line} isn't valid within a hunk: {content:?}")]
+    InvalidHunkLine { line: usize, content: BString },
+    #[error("The mode {mode:?} in line {line} is invalid")]
+    InvalidMode { line: usize, mode: BString },
+    #[error("Could not unquote the path in line {line}")]
+    Unquote {
+        line: usize,
+        source: gix_quote::ansi_c::undo::Error,
+    },
+    #[error(
+        "The patch header in line {line} lacks file names, possibly after removing {strip} leading path components"
+    )]
+    MissingPath { line: usize, strip: usize },
+    #[error("The binary patch in line {line} is invalid: {message}")]
+    InvalidBinaryPatch { line: usize, message: &'static str },
+}
+
+pub(crate) mod function {
+    use gix_object::tree::EntryMode;
+
+    use super::Error;
+    use crate::{
+        apply::patch::{binary, Binary, BinaryData, Content, File, Hunk, Line, Rewrite, RewriteKind},
+        bstr::{BString, ByteSlice},
+    };
+
+    /// Parse all file patches in `data`, which may be the output of `git diff`, `git format-patch` or `diff -u`,
+    /// with the paths of all files stripped of `strip` leading path components. Use 1 to remove the `a/` and `b/`
+    /// prefixes, just like `git apply` does by default.
+    ///
+    /// Text before, between and after patches is ignored, which allows to parse the patch within an email.
+    /// Note that patches which don't change the content of a file, like mode changes, are only recognized in the
+    /// format produced by `git`.
+    pub fn parse(data: &[u8], strip: usize) -> Result<Vec<File>, Error> {
+        let lines: Vec<&[u8]> = data.lines_with_terminator().collect();
+        let mut parser = Parser { lines, pos: 0, strip };
+        let mut files = Vec::new();
+        while let Some(line) = parser.peek() {
+            if let Some(names) = line.strip_prefix(b"diff --git ") {
+                files.push(parser.git_patch(names)?);
+            } else if line.starts_with(b"--- ")
+                && parser.peek_at(1).map_or(false, |l| l.starts_with(b"+++ "))
+                && parser.peek_at(2).map_or(false, |l| l.starts_with(b"@@ -"))
+            {
+                files.push(parser.traditional_patch()?);
+            } else {
+                parser.pos += 1;
+            }
+        }
+        Ok(files)
+    }
+
+    struct Parser<'a> {
+        lines: Vec<&'a [u8]>,
+        pos: usize,
+        strip: usize,
+    }
+
+    impl<'a> Parser<'a> {
+        fn peek(&self) -> Option<&'a [u8]> {
+            self.lines.get(self.pos).copied()
+        }
+
+        fn peek_at(&self, offset: usize) -> Option<&'a [u8]> {
+            self.lines.get(self.pos + offset).copied()
+        }
+
+        /// The one-based number of the current line.
+        fn line_number(&self) -> usize {
+            self.pos + 1
+        }
+
+        fn git_patch(&mut self, names: &[u8]) -> Result<File, Error> {
+            let header_line = self.line_number();
+            let (mut old_path, mut new_path) = match self.git_header_names(trim_newline(names))? {
+                Some((old, new)) => (Some(old), Some(new)),
+                None => (None, None),
+            };
+            let mut file = File {
+                old_path: None,
+                new_path: None,
+                old_mode: None,
+                new_mode: None,
+                rewrite: None,
+                old_id: None,
+                new_id: None,
+                content: Content::Hunks(Vec::new()),
+            };
+            let (mut is_addition, mut is_deletion) = (false, false);
+            self.pos += 1;
+            while let Some(line) = self.peek() {
+                let line_number = self.line_number();
+                let line = trim_newline(line);
+                if let Some(mode) = line.strip_prefix(b"old mode ") {
+                    file.old_mode = Some(parse_mode(mode, line_number)?);
+                } else if let Some(mode) = line.strip_prefix(b"new mode ") {
+                    file.new_mode = Some(parse_mode(mode, line_number)?);
+                } else if let Some(mode) = line.strip_prefix(b"deleted file mode ") {
+                    file.old_mode = Some(parse_mode(mode, line_number)?);
+                    is_deletion = true;
+                } else if let Some(mode) = line.strip_prefix(b"new file mode ") {
+                    file.new_mode = Some(parse_mode(mode, line_number)?);
+                    is_addition = true;
+                } else if let Some(path) = line
+                    .strip_prefix(b"rename from ")
+                    .or_else(|| line.strip_prefix(b"rename old "))
+                {
+                    old_path = Some(unquote(path, line_number)?);
+                    set_rewrite_kind(&mut file, RewriteKind::Rename);
+                } else if let Some(path) = line
+                    .strip_prefix(b"rename to ")
+                    .or_else(|| line.strip_prefix(b"rename new "))
+                {
+                    new_path = Some(unquote(path, line_number)?);
+                    set_rewrite_kind(&mut file, RewriteKind::Rename);
+                } else if let Some(path) = line.strip_prefix(b"copy from ") {
+                    old_path = Some(unquote(path, line_number)?);
+                    set_rewrite_kind(&mut file, RewriteKind::Copy);
+                } else if let Some(path) = line.strip_prefix(b"copy to ") {
+                    new_path = Some(unquote(path, line_number)?);
+                    set_rewrite_kind(&mut file, RewriteKind::Copy);
+                } else if let Some(similarity) = line.strip_prefix(b"similarity index ") {
+                    let similarity = similarity
+                        .strip_suffix(b"%")
+                        .and_then(|n| n.to_str().ok())
+                        .and_then(|n| n.parse().ok());
+                    file.rewrite.get_or_insert(Rewrite {
+                        kind: RewriteKind::Rename,
+                        similarity: None,
+                    });
+                    if let Some(rewrite) = file.rewrite.as_mut() {
+                        rewrite.similarity = similarity;
+                    }
+                } else if line.starts_with(b"dissimilarity index ") {
+                } else if let Some(ids) = line.strip_prefix(b"index ") {
+                    let (ids, mode) = match ids.find_byte(b' ') {
+                        Some(pos) => (&ids[..pos], Some(parse_mode(&ids[pos + 1..], line_number)?)),
+                        None => (ids, None),
+                    };
+                    if let Some((old, new)) = ids.split_once_str("..") {
+                        file.old_id = Some(old.into());
+                        file.new_id = Some(new.into());
+                    }
+                    if let Some(mode) = mode {
+                        file.old_mode.get_or_insert(mode);
+                        file.new_mode.get_or_insert(mode);
+                    }
+                } else if let Some(name) = line.strip_prefix(b"--- ") {
+                    if old_path.is_none() && !is_addition {
+                        old_path = self.traditional_name(name, line_number)?;
+                    }
+                } else if let Some(name) = line.strip_prefix(b"+++ ") {
+                    if new_path.is_none() && !is_deletion {
+                        new_path = self.traditional_name(name, line_number)?;
+                    }
+                } else if line.starts_with(b"@@ -") {
+                    file.content = Content::Hunks(self.hunks()?);
+                    break;
+                } else if line == b"GIT binary patch" {
+                    self.pos += 1;
+                    file.content = Content::Binary(self.binary_patch()?);
+                    break;
+                } else if line.starts_with(b"Binary files ") && line.ends_with(b" differ") {
+                    self.pos += 1;
+                    file.content = Content::BinaryWithoutData;
+                    break;
+                } else {
+                    break;
+                }
+                self.pos += 1;
+            }
+
+            let missing_path = || Error::MissingPath {
+                line: header_line,
+                strip: self.strip,
+            };
+            if is_addition {
+                old_path = None;
+                new_path = Some(new_path.ok_or_else(missing_path)?);
+            } else if is_deletion {
+                new_path = None;
+                old_path = Some(old_path.ok_or_else(missing_path)?);
+            } else if old_path.is_none() || new_path.is_none() {
+                return Err(missing_path());
+            }
+            file.old_path = old_path;
+            file.new_path = new_path;
+            if file.is_deletion() {
+                file.new_mode = None;
+            }
+            if file.is_addition() {
+                file.old_mode = None;
+            }
+            Ok(file)
+        }
+
+        fn traditional_patch(&mut self) -> Result<File, Error> {
+            let line_number = self.line_number();
+            let old_line = trim_newline(self.peek().expect("checked by caller"));
+            let new_line = trim_newline(self.peek_at(1).expect("checked by caller"));
+            let old_path = self.traditional_name(&old_line[4..], line_number)?;
+            let new_path = self.traditional_name(&new_line[4..], line_number + 1)?;
+            if old_path.is_none() && new_path.is_none() {
+                return Err(Error::MissingPath {
+                    line: line_number,
+                    strip: self.strip,
+                });
+            }
+            self.pos += 2;
+            Ok(File {
+                old_path,
+                new_path,
+                old_mode: None,
+                new_mode: None,
+                rewrite: None,
+                old_id: None,
+                new_id: None,
+                content: Content::Hunks(self.hunks()?),
+            })
+        }
+
+        /// Parse the names in `diff --git <old> <new>`, which are ambiguous if they aren't quoted and contain spaces,
+        /// in which case the names are only returned if both are the same.
+        fn git_header_names(&self, names: &[u8]) -> Result<Option<(BString, BString)>, Error> {
+            let line = self.line_number();
+            let strip = |name: &[u8]| strip_components(name, self.strip);
+            if names.starts_with(b"\"") {
+                let (old, consumed) =
+                    gix_quote::ansi_c::undo(names.as_bstr()).map_err(|source| Error::Unquote { line, source })?;
+                let new = names.get(consumed + 1..).unwrap_or_default();
+                let new = unquote(new, line)?;
+                return Ok(strip(&old).zip(strip(&new)));
+            }
+            if names.ends_with(b"\"") {
+                if let Some(pos) = names.rfind(b" \"") {
+                    let new = unquote(&names[pos + 1..], line)?;
+                    return Ok(strip(&names[..pos]).zip(strip(&new)));
+                }
+            }
+            for pos in names.find_iter(b" ") {
+                let (old, new) = (strip(&names[..pos]), strip(&names[pos + 1..]));
+                if old.is_some() && old == new {
+                    return Ok(old.zip(new));
+                }
+            }
+            Ok(None)
+        }
+
+        /// Parse the name in a `---` or `+++` line, returning `None` if it's `/dev/null`.
+        fn traditional_name(&self, name: &[u8], line: usize) -> Result<Option<BString>, Error> {
+            let name = if name.starts_with(b"\"") {
+                unquote(name, line)?
+            } else {
+                name[..name.find_byte(b'\t').unwrap_or(name.len())].trim_end().into()
+            };
+            if name == "/dev/null" {
+                return Ok(None);
+            }
+            strip_components(&name, self.strip).map(Some).ok_or(Error::MissingPath {
+                line,
+                strip: self.strip,
+            })
+        }
+
+        fn hunks(&mut self) -> Result<Vec<Hunk>, Error> {
+            let mut hunks = Vec::new();
+            while let Some(header) = self.peek().filter(|line| line.starts_with(b"@@ -")) {
+                let header_line = self.line_number();
+                let (old_start, old_lines, new_start, new_lines, function_name) =
+                    parse_hunk_header(trim_newline(header)).ok_or_else(|| Error::InvalidHunkHeader {
+                        line: header_line,
+                        header: trim_newline(header).into(),
+                    })?;
+                self.pos += 1;
+                let mut lines = Vec::new();
+                let (mut old_left, mut new_left) = (old_lines, new_lines);
+                while old_left > 0 || new_left > 0 {
+                    let line = self.peek().ok_or(Error::TruncatedHunk { line: header_line })?;
+                    let invalid = || Error::InvalidHunkLine {
+                        line: self.line_number(),
+                        content: line.into(),
+                    };
+                    match line[0] {
+                        b' ' | b'\n' | b'\r' => {
+                            if old_left == 0 || new_left == 0 {
+                                return Err(invalid());
+                            }
+                            old_left -= 1;
+                            new_left -= 1;
+                            let content = if line[0] == b' ' { &line[1..] } else { line };
+                            lines.push(Line::Context(content.into()));
+                        }
+                        b'-' => {
+                            old_left = old_left.checked_sub(1).ok_or_else(invalid)?;
+                            lines.push(Line::Removal(line[1..].into()));
+                        }
+                        b'+' => {
+                            new_left = new_left.checked_sub(1).ok_or_else(invalid)?;
+                            lines.push(Line::Addition(line[1..].into()));
+                        }
+                        b'\\' => strip_last_newline(&mut lines),
+                        _ if line.starts_with(b"@@ -") || line.starts_with(b"diff ") => {
+                            return Err(Error::TruncatedHunk { line: header_line })
+                        }
+                        _ => return Err(invalid()),
+                    }
+                    self.pos += 1;
+                }
+                while self.peek().map_or(false, |line| line.starts_with(b"\\")) {
+                    strip_last_newline(&mut lines);
+                    self.pos += 1;
+                }
+                hunks.push(Hunk {
+                    old_start,
+                    old_lines,
+                    new_start,
+                    new_lines,
+                    function_name,
+                    lines,
+                });
+            }
+            Ok(hunks)
+        }
+
+        fn binary_patch(&mut self) -> Result<Binary, Error> {
+            let forward = self.binary_data()?.ok_or(Error::InvalidBinaryPatch {
+                line: self.line_number(),
+                message: "expected 'literal' or 'delta' line",
+            })?;
+            let reverse = self.binary_data()?;
+            Ok(Binary { forward, reverse })
+        }
+
+        /// Parse a `literal <size>` or `delta <size>` block along with its data, or return `None` if there is none.
+        fn binary_data(&mut self) -> Result<Option<BinaryData>, Error> {
+            let line = self.line_number();
+            let header = match self.peek() {
+                Some(header) => trim_newline(header),
+                None => return Ok(None),
+            };
+            let (is_literal, size) = match (header.strip_prefix(b"literal "), header.strip_prefix(b"delta ")) {
+                (Some(size), _) => (true, size),
+                (_, Some(size)) => (false, size),
+                _ => return Ok(None),
+            };
+            let invalid = |message| Error::InvalidBinaryPatch { line, message };
+            let size: usize = size
+                .to_str()
+                .ok()
+                .and_then(|size| size.parse().ok())
+                .ok_or_else(|| invalid("invalid size"))?;
+            self.pos += 1;
+            let mut compressed = Vec::new();
+            loop {
+                let data = trim_newline(self.peek().ok_or_else(|| invalid("unexpected end of binary data"))?);
+                self.pos += 1;
+                if data.is_empty() {
+                    break;
+                }
+                binary::decode_base85_line(data, &mut compressed).ok_or_else(|| invalid("invalid base85 data"))?;
+            }
+            let data = binary::inflate(&compressed, size).ok_or_else(|| invalid("could not decompress data"))?;
+            Ok(Some(if is_literal {
+                BinaryData::Literal(data)
+            } else {
+                BinaryData::Delta(data)
+            }))
+        }
+    }
+
+    fn set_rewrite_kind(file: &mut File, kind: RewriteKind) {
+        file.rewrite.get_or_insert(Rewrite { kind, similarity: None }).kind = kind;
+    }
+
+    fn strip_last_newline(lines: &mut [Line]) {
+        if let Some(line) = lines.last_mut() {
+            let content = line.content_mut();
+            if content.ends_with(b"\n") {
+                content.pop();
+            }
+        }
+    }
+
+    fn trim_newline(line: &[u8]) -> &[u8] {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        line.strip_suffix(b"\r").unwrap_or(line)
+    }
+
+    fn unquote(path: &[u8], line: usize) -> Result<BString, Error> {
+        gix_quote::ansi_c::undo(path.as_bstr())
+            .map(|(path, _)| path.into_owned())
+            .map_err(|source| Error::Unquote { line, source })
+    }
+
+    /// Remove `strip` leading components from `path`, or return `None` if it doesn't have enough of them.
+    fn strip_components(path: &[u8], strip: usize) -> Option<BString> {
+        let mut path = path;
+        for _ in 0..strip {
+            let slash = path.find_byte(b'/')?;
+            path = &path[slash + 1..];
+            while let Some(rest) = path.strip_prefix(b"/") {
+                path = rest;
+            }
+        }
+        (!path.is_empty()).then(|| path.into())
+    }
+
+    fn parse_mode(mode: &[u8], line: usize) -> Result<EntryMode, Error> {
+        let invalid = || Error::InvalidMode {
+            line,
+            mode: mode.into(),
+        };
+        let value = u32::from_str_radix(mode.to_str().map_err(|_| invalid())?, 8).map_err(|_| invalid())?;
+        Ok(match value & 0o170000 {
+            0o100000 if value & 0o111 != 0 => EntryMode::BlobExecutable,
+            0o100000 => EntryMode::Blob,
+            0o120000 => EntryMode::Link,
+            0o160000 => EntryMode::Commit,
+            0o040000 => EntryMode::Tree,
+            _ => return Err(invalid()),
+        })
+    }
+
+    /// Parse `@@ -<start>[,<count>] +<start>[,<count>] @@[ <function name>]`.
+    fn parse_hunk_header(header: &[u8]) -> Option<(u32, u32, u32, u32, BString)> {
+        let rest = header.strip_prefix(b"@@ -")?;
+        let (ranges, function_name) = rest.split_once_str(" @@")?;
+        let (old, new) = ranges.split_once_str(" +")?;
+        let range = |range: &[u8]| -> Option<(u32, u32)> {
+            let (start, count) = match range.split_once_str(",") {
+                Some((start, count)) => (start, count.to_str().ok()?.parse().ok()?),
+                None => (range, 1),
+            };
+            Some((start.to_str().ok()?.parse().ok()?, count))
+        };
+        let (old_start, old_lines) = range(old)?;
+        let (new_start, new_lines) = range(new)?;
+        let function_name = function_name.strip_prefix(b" ").unwrap_or(function_name);
+        Some((old_start, old_lines, new_start, new_lines, function_name.into()))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        #[test]
+        fn strip_components() {
+            assert_eq!(super::strip_components(b"a/dir/file", 1), Some("dir/file".into()));
+            assert_eq!(super::strip_components(b"a//file", 1), Some("file".into()));
+            assert_eq!(super::strip_components(b"file", 0), Some("file".into()));
+            assert_eq!(super::strip_components(b"file", 1), None);
+        }
+
+        #[test]
+        fn parse_hunk_header() {
+            assert_eq!(
+                super::parse_hunk_header(b"@@ -1,2 +3 @@ fn main()"),
+                Some((1, 2, 3, 1, "fn main()".into()))
+            );
+            assert_eq!(
+                super::parse_hunk_header(b"@@ -0,0 +1,5 @@"),
+                Some((0, 0, 1, 5, "".into()))
+            );
+            assert_eq!(super::parse_hunk_header(b"@@ -a +1 @@"), None);
+        }
+    }
+}
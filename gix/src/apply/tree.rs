@@ -0,0 +1,29 @@
+use std::collections::BTreeMap;
+
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+use crate::{apply::Error, bstr::BString, Repository};
+
+/// An edit to a path within a tree, which is removed if `None`.
+type Edit = Option<(EntryMode, ObjectId)>;
+
+/// Apply `edits` to the tree with id `root`, or to an empty tree, write all changed trees and return the id of the
+/// new root tree. Trees that become empty are removed.
+pub(crate) fn write_with_edits(
+    repo: &Repository,
+    root: Option<ObjectId>,
+    edits: &BTreeMap<BString, Edit>,
+) -> Result<ObjectId, Error> {
+    let mut editor = match root {
+        Some(id) => repo.edit_tree(id)?,
+        None => repo.empty_tree().edit()?,
+    };
+    for (path, edit) in edits {
+        match edit {
+            Some((mode, id)) => editor.upsert(path, *mode, *id)?,
+            None => editor.remove(path)?,
+        };
+    }
+    Ok(editor.write()?.detach())
+}
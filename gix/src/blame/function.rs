@@ -0,0 +1,545 @@
+#![allow(clippy::result_large_err)]
+use std::{collections::BinaryHeap, ops::Range};
+
+use gix_hash::ObjectId;
+use gix_hashtable::HashMap;
+
+use super::{Entry, Error, Outcome, Platform, Statistics};
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    config::tree::{Blame, Key},
+    object::tree::diff::{change::Event, Action, Rewrites},
+    Repository,
+};
+
+/// A range of lines in the blamed file which still have to be attributed to a commit, along with the range
+/// of the same lines in the version of the file at the commit currently looked at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UnblamedHunk {
+    blamed: Range<u32>,
+    current: Range<u32>,
+}
+
+/// Lines at `current` in a commit's version of a file which are unchanged in a parent's version, starting at `parent_start`.
+#[derive(Debug, Clone)]
+struct Segment {
+    current: Range<u32>,
+    parent_start: u32,
+}
+
+/// Hunks which have been passed on to a commit, waiting to be processed.
+struct Pending {
+    path: BString,
+    blob_id: ObjectId,
+    hunks: Vec<UnblamedHunk>,
+}
+
+impl<'repo> Platform<'repo> {
+    /// Attribute each line of the file to the commit that introduced it, walking the commit graph from the most recent
+    /// commits to the oldest ones.
+    ///
+    /// Commits are processed in order of their commit time, and lines are passed from a commit to the first parent in
+    /// which they are unchanged, which matches what `git blame` does.
+    pub fn compute(self) -> Result<Outcome, Error> {
+        let Platform {
+            repo,
+            path,
+            commit_id,
+            ranges,
+            mut ignored_revs,
+            ignore_revs_from_config,
+            rewrites,
+            use_commit_graph,
+        } = self;
+
+        if ignore_revs_from_config {
+            ignored_revs.extend(ignored_revs_from_config(repo)?);
+        }
+        ignored_revs.sort();
+        let rewrites = match rewrites {
+            Some(rewrites) => rewrites,
+            None => repo.config.diff_renames()?.unwrap_or_default().into(),
+        };
+        let algo = repo.config.diff_algorithm()?;
+        let graph = use_commit_graph.then(|| repo.commit_graph().ok()).flatten();
+        let bloom_settings = graph.as_ref().and_then(gix_commitgraph::Graph::bloom_filter_settings);
+
+        let mut stats = Statistics::default();
+        let blob_id = find_file(repo, commit_id, path.as_ref())?.ok_or_else(|| Error::FileNotFound {
+            path: path.clone(),
+            commit_id,
+        })?;
+        let blob = repo.find_object(blob_id)?.detach().data;
+        let line_count = tokens_for_diffing(&blob).count() as u32;
+        let hunks = initial_hunks(ranges, line_count).map_err(|range| Error::InvalidRange {
+            range,
+            path: path.clone(),
+            line_count,
+        })?;
+
+        let mut queue = Queue::default();
+        queue.add(repo, commit_id, Pending { path, blob_id, hunks })?;
+
+        let mut entries = Vec::new();
+        let mut bloom_keys = std::collections::HashMap::<BString, Vec<gix_commitgraph::bloom::Key>>::new();
+        while let Some(id) = queue.pop() {
+            let pendings = match queue.pending.remove(&id) {
+                Some(pendings) => pendings,
+                None => continue,
+            };
+            stats.commits_traversed += 1;
+            let commit = repo.find_object(id)?.try_into_commit()?;
+            let (tree_id, parent_ids) = {
+                let commit = commit.decode()?;
+                (commit.tree(), commit.parents().collect::<Vec<_>>())
+            };
+            let is_ignored = ignored_revs.binary_search(&id).is_ok();
+
+            for Pending {
+                path,
+                blob_id,
+                mut hunks,
+            } in pendings
+            {
+                if parent_ids.is_empty() {
+                    blame_hunks(&mut entries, hunks, id, path.as_ref());
+                    continue;
+                }
+
+                if let Some((graph, settings)) = graph.as_ref().zip(bloom_settings.as_ref()) {
+                    if let Some(filter) = graph.commit_by_id(id).and_then(|c| c.bloom_filter()) {
+                        stats.bloom_filter_queries += 1;
+                        let keys = bloom_keys.entry(path.clone()).or_insert_with(|| {
+                            gix_commitgraph::bloom::Key::for_path_and_leading_directories(path.as_ref(), settings)
+                        });
+                        if !filter.maybe_contains_all(keys.iter()) {
+                            stats.bloom_filter_skips += 1;
+                            queue.add(repo, parent_ids[0], Pending { path, blob_id, hunks })?;
+                            continue;
+                        }
+                    }
+                }
+
+                let mut parents = Vec::with_capacity(parent_ids.len());
+                for parent_id in &parent_ids {
+                    let file = find_file_in_parent(repo, *parent_id, tree_id, path.as_ref(), rewrites, &mut stats)?;
+                    parents.push((*parent_id, file));
+                }
+
+                if let Some((parent_id, (parent_path, parent_blob_id))) = parents.iter().find_map(|(id, file)| {
+                    file.as_ref()
+                        .filter(|(_, parent_blob_id)| *parent_blob_id == blob_id)
+                        .map(|file| (*id, file.clone()))
+                }) {
+                    queue.add(
+                        repo,
+                        parent_id,
+                        Pending {
+                            path: parent_path,
+                            blob_id: parent_blob_id,
+                            hunks,
+                        },
+                    )?;
+                    continue;
+                }
+
+                let data = repo.find_object(blob_id)?.detach().data;
+                let mut first_parent_changes = None;
+                for (parent_id, file) in parents {
+                    let (parent_path, parent_blob_id) = match file {
+                        Some(file) => file,
+                        None => continue,
+                    };
+                    let parent_data = repo.find_object(parent_blob_id)?.detach().data;
+                    let changes = line_changes(algo, &parent_data, &data);
+                    stats.blob_diffs += 1;
+                    let (passed, remaining) = split_hunks(hunks, &unchanged_segments(&changes, line_count_of(&data)));
+                    hunks = remaining;
+                    if is_ignored && first_parent_changes.is_none() {
+                        first_parent_changes = Some((parent_id, parent_path.clone(), parent_blob_id, changes));
+                    }
+                    if !passed.is_empty() {
+                        queue.add(
+                            repo,
+                            parent_id,
+                            Pending {
+                                path: parent_path,
+                                blob_id: parent_blob_id,
+                                hunks: passed,
+                            },
+                        )?;
+                    }
+                    if hunks.is_empty() {
+                        break;
+                    }
+                }
+
+                if let Some((parent_id, parent_path, parent_blob_id, changes)) =
+                    first_parent_changes.filter(|_| !hunks.is_empty())
+                {
+                    let (passed, remaining) = split_hunks(hunks, &modified_segments(&changes));
+                    hunks = remaining;
+                    if !passed.is_empty() {
+                        queue.add(
+                            repo,
+                            parent_id,
+                            Pending {
+                                path: parent_path,
+                                blob_id: parent_blob_id,
+                                hunks: passed,
+                            },
+                        )?;
+                    }
+                }
+                blame_hunks(&mut entries, hunks, id, path.as_ref());
+            }
+        }
+
+        Ok(Outcome {
+            entries: coalesce(entries),
+            blob,
+            statistics: stats,
+        })
+    }
+}
+
+/// A priority queue of commits to process, most recent commits first, along with the hunks passed to them.
+#[derive(Default)]
+struct Queue {
+    by_time: BinaryHeap<(u32, ObjectId)>,
+    pending: HashMap<ObjectId, Vec<Pending>>,
+}
+
+impl Queue {
+    fn add(&mut self, repo: &Repository, id: ObjectId, mut new: Pending) -> Result<(), Error> {
+        match self.pending.get_mut(&id) {
+            Some(pendings) => match pendings.iter_mut().find(|p| p.path == new.path) {
+                Some(existing) => existing.hunks.append(&mut new.hunks),
+                None => pendings.push(new),
+            },
+            None => {
+                let time = repo.find_object(id)?.try_into_commit()?.committer()?.time;
+                self.by_time.push((time.seconds_since_unix_epoch, id));
+                self.pending.insert(id, vec![new]);
+            }
+        }
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<ObjectId> {
+        self.by_time.pop().map(|(_, id)| id)
+    }
+}
+
+fn initial_hunks(mut ranges: Vec<Range<u32>>, line_count: u32) -> Result<Vec<UnblamedHunk>, Range<u32>> {
+    if ranges.is_empty() {
+        ranges.push(0..line_count);
+    }
+    if let Some(invalid) = ranges
+        .iter()
+        .find(|range| range.start >= range.end || range.end > line_count)
+    {
+        if line_count != 0 || *invalid != (0..0) {
+            return Err(invalid.clone());
+        }
+    }
+    ranges.retain(|range| range.start < range.end);
+    ranges.sort_by_key(|range| range.start);
+    let mut hunks: Vec<UnblamedHunk> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match hunks.last_mut() {
+            Some(last) if last.blamed.end >= range.start => {
+                last.blamed.end = last.blamed.end.max(range.end);
+                last.current.end = last.blamed.end;
+            }
+            _ => hunks.push(UnblamedHunk {
+                blamed: range.clone(),
+                current: range,
+            }),
+        }
+    }
+    Ok(hunks)
+}
+
+fn blame_hunks(out: &mut Vec<Entry>, hunks: Vec<UnblamedHunk>, commit_id: ObjectId, path: &BStr) {
+    out.extend(hunks.into_iter().map(|hunk| Entry {
+        range_in_blamed_file: hunk.blamed,
+        range_in_source_file: hunk.current,
+        commit_id,
+        source_path: path.to_owned(),
+    }));
+}
+
+/// Sort `entries` by their position in the blamed file and merge adjacent ones which are adjacent in their source as well.
+fn coalesce(mut entries: Vec<Entry>) -> Vec<Entry> {
+    entries.sort_by_key(|e| e.range_in_blamed_file.start);
+    let mut out: Vec<Entry> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match out.last_mut() {
+            Some(last)
+                if last.commit_id == entry.commit_id
+                    && last.source_path == entry.source_path
+                    && last.range_in_blamed_file.end == entry.range_in_blamed_file.start
+                    && last.range_in_source_file.end == entry.range_in_source_file.start =>
+            {
+                last.range_in_blamed_file.end = entry.range_in_blamed_file.end;
+                last.range_in_source_file.end = entry.range_in_source_file.end;
+            }
+            _ => out.push(entry),
+        }
+    }
+    out
+}
+
+/// Pass the parts of `hunks` which are covered by `segments` to the parent, returning `(passed, remaining)`.
+fn split_hunks(hunks: Vec<UnblamedHunk>, segments: &[Segment]) -> (Vec<UnblamedHunk>, Vec<UnblamedHunk>) {
+    let mut passed = Vec::new();
+    let mut remaining = Vec::new();
+    for hunk in hunks {
+        let mut cursor = hunk.current.start;
+        let first = segments.partition_point(|s| s.current.end <= hunk.current.start);
+        for segment in segments[first..]
+            .iter()
+            .take_while(|s| s.current.start < hunk.current.end)
+        {
+            let start = segment.current.start.max(hunk.current.start);
+            let end = segment.current.end.min(hunk.current.end);
+            if start >= end {
+                continue;
+            }
+            let to_blamed = |line: u32| hunk.blamed.start + (line - hunk.current.start);
+            if cursor < start {
+                remaining.push(UnblamedHunk {
+                    blamed: to_blamed(cursor)..to_blamed(start),
+                    current: cursor..start,
+                });
+            }
+            let parent_start = segment.parent_start + (start - segment.current.start);
+            passed.push(UnblamedHunk {
+                blamed: to_blamed(start)..to_blamed(end),
+                current: parent_start..parent_start + (end - start),
+            });
+            cursor = end;
+        }
+        if cursor < hunk.current.end {
+            remaining.push(UnblamedHunk {
+                blamed: hunk.blamed.start + (cursor - hunk.current.start)..hunk.blamed.end,
+                current: cursor..hunk.current.end,
+            });
+        }
+    }
+    (passed, remaining)
+}
+
+/// Return all line ranges between `changes`, which are unchanged between the parent and the current version of a file.
+fn unchanged_segments(changes: &[(Range<u32>, Range<u32>)], current_line_count: u32) -> Vec<Segment> {
+    let mut out = Vec::with_capacity(changes.len() + 1);
+    let (mut parent_cursor, mut current_cursor) = (0, 0);
+    for (before, after) in changes {
+        if current_cursor < after.start {
+            out.push(Segment {
+                current: current_cursor..after.start,
+                parent_start: parent_cursor,
+            });
+        }
+        parent_cursor = before.end;
+        current_cursor = after.end;
+    }
+    if current_cursor < current_line_count {
+        out.push(Segment {
+            current: current_cursor..current_line_count,
+            parent_start: parent_cursor,
+        });
+    }
+    out
+}
+
+/// Map lines which replaced other lines to the lines they replaced, line by line, to pass them through ignored commits.
+fn modified_segments(changes: &[(Range<u32>, Range<u32>)]) -> Vec<Segment> {
+    changes
+        .iter()
+        .filter(|(before, after)| !before.is_empty() && !after.is_empty())
+        .map(|(before, after)| {
+            let len = (before.end - before.start).min(after.end - after.start);
+            Segment {
+                current: after.start..after.start + len,
+                parent_start: before.start,
+            }
+        })
+        .collect()
+}
+
+fn tokens_for_diffing(data: &[u8]) -> gix_diff::blob::sources::ByteLines<'_, true> {
+    gix_diff::blob::sources::byte_lines_with_terminator(data)
+}
+
+fn line_count_of(data: &[u8]) -> u32 {
+    tokens_for_diffing(data).count() as u32
+}
+
+/// Return the changed line ranges between `old` and `new` as `(before, after)` pairs.
+fn line_changes(algo: gix_diff::blob::Algorithm, old: &[u8], new: &[u8]) -> Vec<(Range<u32>, Range<u32>)> {
+    let input = gix_diff::blob::intern::InternedInput::new(tokens_for_diffing(old), tokens_for_diffing(new));
+    let mut changes = Vec::new();
+    gix_diff::blob::diff(algo, &input, |before: Range<u32>, after: Range<u32>| {
+        changes.push((before, after))
+    });
+    changes
+}
+
+fn find_file(repo: &Repository, commit_id: ObjectId, path: &BStr) -> Result<Option<ObjectId>, Error> {
+    let tree_id = repo.find_object(commit_id)?.try_into_commit()?.tree_id()?;
+    find_file_in_tree(repo, tree_id.detach(), path)
+}
+
+fn find_file_in_tree(repo: &Repository, tree_id: ObjectId, path: &BStr) -> Result<Option<ObjectId>, Error> {
+    let tree = repo.find_object(tree_id)?.try_into_tree()?;
+    Ok(tree
+        .lookup_entry(path.split(|b| *b == b'/'))?
+        .filter(|entry| entry.mode().is_blob_or_symlink())
+        .map(|entry| entry.object_id()))
+}
+
+/// Find the file at `path` in `parent_id`, or the source of a rename if it is configured in `rewrites`, returning
+/// `(path, blob_id)` if found.
+fn find_file_in_parent(
+    repo: &Repository,
+    parent_id: ObjectId,
+    tree_id: ObjectId,
+    path: &BStr,
+    rewrites: Option<Rewrites>,
+    stats: &mut Statistics,
+) -> Result<Option<(BString, ObjectId)>, Error> {
+    let parent_tree_id = repo.find_object(parent_id)?.try_into_commit()?.tree_id()?.detach();
+    if let Some(blob_id) = find_file_in_tree(repo, parent_tree_id, path)? {
+        return Ok(Some((path.to_owned(), blob_id)));
+    }
+    let rewrites = match rewrites {
+        Some(rewrites) => rewrites,
+        None => return Ok(None),
+    };
+
+    let parent_tree = repo.find_object(parent_tree_id)?.try_into_tree()?;
+    let tree = repo.find_object(tree_id)?.try_into_tree()?;
+    let mut source = None;
+    stats.tree_diffs += 1;
+    parent_tree
+        .changes()?
+        .track_path()
+        .track_rewrites(Some(rewrites))
+        .for_each_to_obtain_tree(&tree, |change| {
+            if let Event::Rewrite {
+                source_location,
+                source_id,
+                entry_mode,
+                ..
+            } = change.event
+            {
+                if change.location == path && entry_mode.is_blob_or_symlink() {
+                    source = Some((source_location.to_owned(), source_id.detach()));
+                }
+            }
+            Ok::<_, std::convert::Infallible>(Action::Continue)
+        })?;
+    Ok(source)
+}
+
+/// Read all commits to ignore from files listed in `blame.ignoreRevsFile`.
+fn ignored_revs_from_config(repo: &Repository) -> Result<Vec<ObjectId>, Error> {
+    let mut out = Vec::new();
+    let values = match repo.config.resolved.strings_filter(
+        Blame::IGNORE_REVS_FILE.section().name(),
+        None,
+        Blame::IGNORE_REVS_FILE.name,
+        &mut repo.filter_config_section(),
+    ) {
+        Some(values) => values,
+        None => return Ok(out),
+    };
+    let mut paths = Vec::new();
+    for value in values {
+        if value.is_empty() {
+            paths.clear();
+        } else {
+            paths.push(value);
+        }
+    }
+
+    let install_dir = crate::path::install_dir().ok();
+    let home = repo.config.home_dir();
+    let base = repo.work_dir().unwrap_or_else(|| repo.git_dir());
+    for value in paths {
+        let path = gix_config::Path::from(value).interpolate(crate::config::cache::interpolate_context(
+            install_dir.as_deref(),
+            home.as_deref(),
+        ))?;
+        let path = base.join(path);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(Error::ReadIgnoreRevsFile { source: err, path }),
+        };
+        for (line_number, line) in data.lines().enumerate() {
+            let hex = line.split(|b| *b == b'#').next().unwrap_or_default().trim();
+            if hex.is_empty() {
+                continue;
+            }
+            out.push(ObjectId::from_hex(hex).map_err(|_| Error::ParseIgnoreRevsFile {
+                line_number: line_number + 1,
+                line: line.into(),
+                path: path.clone(),
+            })?);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(blamed: Range<u32>, current: Range<u32>) -> UnblamedHunk {
+        UnblamedHunk { blamed, current }
+    }
+
+    #[test]
+    fn unchanged_segments_are_between_changes() {
+        let changes = vec![(1..2, 1..3), (4..5, 5..5)];
+        let segments = unchanged_segments(&changes, 7);
+        let actual: Vec<_> = segments.iter().map(|s| (s.current.clone(), s.parent_start)).collect();
+        assert_eq!(actual, vec![(0..1, 0), (3..5, 2), (5..7, 5)]);
+    }
+
+    #[test]
+    fn split_hunks_passes_unchanged_lines_and_keeps_changed_ones() {
+        let changes = vec![(1..2, 1..3)];
+        let segments = unchanged_segments(&changes, 5);
+        let (passed, remaining) = split_hunks(vec![hunk(10..15, 0..5)], &segments);
+        assert_eq!(passed, vec![hunk(10..11, 0..1), hunk(13..15, 2..4)]);
+        assert_eq!(remaining, vec![hunk(11..13, 1..3)]);
+    }
+
+    #[test]
+    fn modified_segments_map_replaced_lines_one_by_one() {
+        let changes = vec![(1..2, 1..3), (4..4, 5..6)];
+        let segments = modified_segments(&changes);
+        let (passed, remaining) = split_hunks(vec![hunk(0..6, 0..6)], &segments);
+        assert_eq!(passed, vec![hunk(1..2, 1..2)]);
+        assert_eq!(
+            remaining,
+            vec![hunk(0..1, 0..1), hunk(2..6, 2..6)],
+            "additions can't be mapped"
+        );
+    }
+
+    #[test]
+    fn initial_hunks_merge_overlapping_ranges() {
+        assert_eq!(
+            initial_hunks(vec![4..6, 0..2, 1..3], 10).expect("valid"),
+            vec![hunk(0..3, 0..3), hunk(4..6, 4..6)]
+        );
+        assert_eq!(initial_hunks(vec![], 0).expect("empty files are fine"), vec![]);
+        assert_eq!(initial_hunks(vec![2..4, 0..11], 10), Err(0..11));
+        assert_eq!(initial_hunks(vec![1..2, 3..3], 10), Err(3..3));
+    }
+}
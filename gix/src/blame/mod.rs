@@ -0,0 +1,196 @@
+//! Attribute each line of a file to the commit that introduced it, similar to `git blame`.
+use std::ops::Range;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    object::tree::diff::Rewrites,
+    Repository,
+};
+
+/// A contiguous range of lines in the blamed file which were introduced by the same commit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    /// The zero-based lines in the blamed file as seen in the commit the blame was started from.
+    pub range_in_blamed_file: Range<u32>,
+    /// The zero-based lines in the file as seen in [`commit_id`][Entry::commit_id] at [`source_path`][Entry::source_path].
+    ///
+    /// It has the same length as [`range_in_blamed_file`][Entry::range_in_blamed_file].
+    pub range_in_source_file: Range<u32>,
+    /// The commit which introduced the lines.
+    pub commit_id: ObjectId,
+    /// The path of the file in [`commit_id`][Entry::commit_id], which differs from the blamed path if the file was
+    /// renamed since.
+    pub source_path: BString,
+}
+
+impl Entry {
+    /// Return the amount of lines covered by this entry.
+    pub fn len(&self) -> u32 {
+        self.range_in_blamed_file.end - self.range_in_blamed_file.start
+    }
+
+    /// Return `true` if this entry doesn't cover any line, which never happens for entries produced by a blame.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Statistics gathered while performing a blame, useful to understand its performance.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    /// The amount of commits that had to be looked at to attribute all lines.
+    pub commits_traversed: usize,
+    /// The amount of line diffs that were performed between blobs.
+    pub blob_diffs: usize,
+    /// The amount of tree diffs performed to find the source of a renamed file.
+    pub tree_diffs: usize,
+    /// The amount of commits for which a changed-path bloom filter was queried.
+    pub bloom_filter_queries: usize,
+    /// The amount of commits which could be skipped as their bloom filter proved that the blamed file didn't change.
+    pub bloom_filter_skips: usize,
+}
+
+/// The result of [`Platform::compute()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// One entry for each contiguous range of lines introduced by the same commit, sorted by their position in the
+    /// blamed file.
+    ///
+    /// If line ranges were specified, only the lines in these ranges are included.
+    pub entries: Vec<Entry>,
+    /// The data of the blamed file as seen in the commit the blame was started from.
+    pub blob: Vec<u8>,
+    /// Additional information about the work that was performed.
+    pub statistics: Statistics,
+}
+
+impl Outcome {
+    /// Return an iterator over all entries along with the lines they cover, each including its line terminator.
+    pub fn entries_with_lines(&self) -> impl Iterator<Item = (&Entry, Vec<&BStr>)> {
+        let lines: Vec<_> = self.blob.lines_with_terminator().map(ByteSlice::as_bstr).collect();
+        self.entries.iter().map(move |entry| {
+            let lines =
+                lines[entry.range_in_blamed_file.start as usize..entry.range_in_blamed_file.end as usize].to_vec();
+            (entry, lines)
+        })
+    }
+}
+
+/// The error returned by [`Platform::compute()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("There is no file at '{path}' in commit {commit_id}")]
+    FileNotFound { path: BString, commit_id: ObjectId },
+    #[error("Line range {}..{} is invalid as the file at '{path}' only has {line_count} lines", .range.start, .range.end)]
+    InvalidRange {
+        range: Range<u32>,
+        path: BString,
+        line_count: u32,
+    },
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+    #[error(transparent)]
+    RewritesConfiguration(#[from] crate::object::tree::diff::rewrites::Error),
+    #[error("Failed to find the source of a renamed file")]
+    TreeDiff(#[from] crate::object::tree::diff::for_each::Error),
+    #[error("Failed to interpolate the path of an ignore-revs file")]
+    InterpolateIgnoreRevsFile(#[from] gix_config::path::interpolate::Error),
+    #[error("Could not read the ignore-revs file at '{}'", .path.display())]
+    ReadIgnoreRevsFile {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("Line {line_number} of the ignore-revs file at '{}' is not a full object id: {line:?}", .path.display())]
+    ParseIgnoreRevsFile {
+        line_number: usize,
+        line: BString,
+        path: std::path::PathBuf,
+    },
+}
+
+/// A platform to configure a blame, returned by [`Repository::blame_file()`].
+pub struct Platform<'repo> {
+    pub(crate) repo: &'repo Repository,
+    pub(crate) path: BString,
+    pub(crate) commit_id: ObjectId,
+    pub(crate) ranges: Vec<Range<u32>>,
+    pub(crate) ignored_revs: Vec<ObjectId>,
+    pub(crate) ignore_revs_from_config: bool,
+    pub(crate) rewrites: Option<Option<Rewrites>>,
+    pub(crate) use_commit_graph: bool,
+}
+
+impl<'repo> Platform<'repo> {
+    pub(crate) fn new(path: BString, commit_id: ObjectId, repo: &'repo Repository) -> Self {
+        Platform {
+            repo,
+            path,
+            commit_id,
+            ranges: Vec::new(),
+            ignored_revs: Vec::new(),
+            ignore_revs_from_config: true,
+            rewrites: None,
+            use_commit_graph: true,
+        }
+    }
+}
+
+/// Create-time builder methods
+impl<'repo> Platform<'repo> {
+    /// Only blame the zero-based lines in `range`, similar to `git blame -L`.
+    ///
+    /// This method can be called multiple times to blame multiple ranges, which may overlap.
+    /// By default, all lines are blamed.
+    pub fn with_range(mut self, range: Range<u32>) -> Self {
+        self.ranges.push(range);
+        self
+    }
+
+    /// Pretend that the commits in `ids` didn't change any lines which they merely modified, and attribute these lines
+    /// to a previous commit instead, similar to `git blame --ignore-rev`.
+    ///
+    /// Lines that were added by an ignored commit without replacing other lines are still attributed to it.
+    pub fn ignore_revs(mut self, ids: impl IntoIterator<Item = impl Into<ObjectId>>) -> Self {
+        self.ignored_revs.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// If `toggle` is `true`, the default, read the commits to ignore from the files configured in `blame.ignoreRevsFile`
+    /// in addition to those provided by [`ignore_revs()`][Self::ignore_revs()].
+    ///
+    /// Relative paths are resolved against the working tree, or the `.git` directory in bare repositories,
+    /// and files which don't exist are skipped.
+    pub fn ignore_revs_from_config(mut self, toggle: bool) -> Self {
+        self.ignore_revs_from_config = toggle;
+        self
+    }
+
+    /// Provide `None` to never follow renames, or `Some(rewrites)` to control how the source of a renamed file is found.
+    ///
+    /// By default, renames are followed using the `diff.renames` configuration or git defaults if it is unset.
+    /// Note that renames are only searched for if the blamed file doesn't exist in a parent commit.
+    pub fn track_rewrites(mut self, rewrites: Option<Rewrites>) -> Self {
+        self.rewrites = Some(rewrites);
+        self
+    }
+
+    /// If `toggle` is `true`, the default, use changed-path bloom filters in the commit-graph, if available, to skip
+    /// commits which didn't change the blamed file.
+    pub fn use_commit_graph(mut self, toggle: bool) -> Self {
+        self.use_commit_graph = toggle;
+        self
+    }
+}
+
+mod function;
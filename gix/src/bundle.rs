@@ -0,0 +1,78 @@
+pub use gix_bundle::*;
+
+///
+#[cfg(feature = "blocking-network-client")]
+pub mod create {
+    use crate::bstr::BString;
+
+    /// Options for use in [`Repository::create_bundle()`][crate::Repository::create_bundle()].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Options {
+        /// The version of the bundle format to write.
+        pub version: crate::bundle::Version,
+        /// The amount of threads to use when creating the pack, or `None` to use all logical cores.
+        pub thread_limit: Option<usize>,
+    }
+
+    impl Default for Options {
+        fn default() -> Self {
+            Options {
+                version: crate::bundle::Version::V2,
+                thread_limit: None,
+            }
+        }
+    }
+
+    /// The error returned by [`Repository::create_bundle()`][crate::Repository::create_bundle()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not write the bundle")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        RevisionParse(#[from] crate::revision::spec::parse::Error),
+        #[error("The revision {spec:?} isn't supported, only single revisions, exclusions like '^a' and ranges like 'a..b' are")]
+        UnsupportedRevision { spec: BString },
+        #[error("Refusing to create an empty bundle as none of the revisions is a reference")]
+        Empty,
+        #[error(transparent)]
+        FindCommit(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        ToCommit(#[from] crate::object::try_into::Error),
+        #[error("Could not select or write the objects of the bundle")]
+        Pack(#[from] crate::upload_pack::Error),
+    }
+}
+
+///
+pub mod verify {
+    /// The outcome of [`Repository::verify_bundle()`][crate::Repository::verify_bundle()].
+    #[derive(Debug, Clone)]
+    pub struct Outcome {
+        /// The header of the bundle.
+        pub header: crate::bundle::Header,
+        /// The amount of objects in the pack of the bundle.
+        pub num_objects: usize,
+    }
+
+    /// The error returned by [`Repository::verify_bundle()`][crate::Repository::verify_bundle()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Decode(#[from] crate::bundle::decode::Error),
+        #[error("The bundle uses {actual} object ids, but the repository uses {expected}")]
+        ObjectHashMismatch {
+            actual: gix_hash::Kind,
+            expected: gix_hash::Kind,
+        },
+        #[error("The repository lacks the prerequisite commits {}", ids.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+        MissingPrerequisites { ids: Vec<gix_hash::ObjectId> },
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::Error),
+        #[error("The pack of the bundle is invalid")]
+        Pack(#[from] gix_pack::data::input::Error),
+    }
+}
@@ -0,0 +1,398 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix_features::progress;
+use gix_hash::ObjectId;
+use gix_index::entry::{Flags, Mode, Stat};
+use gix_odb::FindExt;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    checkout::{Error, Options, Outcome},
+    Repository,
+};
+
+pub(crate) fn checkout(repo: &Repository, target: &BStr, options: Options) -> Result<Outcome, Error> {
+    let workdir = repo.work_dir().ok_or_else(|| Error::BareRepository {
+        git_dir: repo.git_dir().to_owned(),
+    })?;
+
+    let commit = repo
+        .rev_parse_single(target)?
+        .object()?
+        .peel_to_kind(gix_object::Kind::Commit)?
+        .into_commit();
+    let commit_id = commit.id;
+    let target_tree = commit.tree_id()?.detach();
+
+    let new_branch = options
+        .new_branch
+        .as_ref()
+        .map(|name| {
+            FullName::try_from(format!("refs/heads/{name}")).map_err(|source| Error::InvalidBranchName {
+                name: name.clone(),
+                source,
+            })
+        })
+        .transpose()?;
+    if let Some(name) = &new_branch {
+        if repo.try_find_reference(name.as_ref())?.is_some() {
+            return Err(Error::BranchExists {
+                name: name.shorten().to_owned(),
+            });
+        }
+    }
+    let branch = match new_branch.clone() {
+        Some(name) => Some(name),
+        None if options.detach => None,
+        None => match FullName::try_from(format!("refs/heads/{target}")) {
+            Ok(name) if repo.try_find_reference(name.as_ref())?.is_some() => Some(name),
+            _ => None,
+        },
+    };
+
+    let mut head = repo.head()?;
+    let moving_from = head.referent_name().map(|name| name.shorten().to_owned());
+    let previous_head = head.peel_to_id_in_place().transpose()?.map(crate::Id::detach);
+    let moving_from = match (moving_from, previous_head) {
+        (Some(name), _) => name,
+        (None, Some(id)) => id.to_string().into(),
+        (None, None) => "HEAD".into(),
+    };
+    let current_tree = match previous_head {
+        Some(id) => repo.find_object(id)?.peel_to_tree()?.id,
+        None => ObjectId::empty_tree(repo.object_hash()),
+    };
+
+    let index_path = repo.index_path();
+    let lock = gix_lock::File::acquire_to_update_resource(&index_path, gix_lock::acquire::Fail::Immediately, None)?;
+    let mut index = gix_index::File::at_or_default(index_path, repo.object_hash(), Default::default())?;
+
+    let mut checkout_options = repo.config.checkout_options(repo.git_dir())?;
+    let objects = repo.objects.clone().into_arc().map_err(Error::OpenArcOdb)?;
+
+    // Learn which files were changed in the working tree, also refreshing the stat information of those that weren't.
+    let mut modified = HashSet::new();
+    let mut missing = HashSet::new();
+    {
+        let mut recorder = gix_worktree::status::Recorder::default();
+        gix_worktree::status(
+            &mut index,
+            workdir,
+            &mut recorder,
+            gix_worktree::status::content::FastEq,
+            {
+                let objects = objects.clone();
+                move |id, buf| objects.find_blob(id, buf)
+            },
+            gix_worktree::status::Options {
+                fs: checkout_options.fs,
+                thread_limit: checkout_options.thread_limit,
+                stat: checkout_options.stat_options,
+            },
+        )?;
+        for (path, change, _conflict) in recorder.records {
+            match change {
+                Some(gix_worktree::status::Change::Removed) => missing.insert(path.to_owned()),
+                Some(_) => modified.insert(path.to_owned()),
+                None => continue,
+            };
+        }
+    }
+
+    let previous: HashMap<BString, (ObjectId, Mode)> = index
+        .entries()
+        .iter()
+        .filter(|entry| entry.stage() == 0)
+        .map(|entry| (entry.path(&index).to_owned(), (entry.id, entry.mode)))
+        .collect();
+    if options.force {
+        index.remove_entries(|_, _, entry| entry.stage() != 0);
+        index.merge_one_way(&target_tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    } else {
+        index
+            .merge_two_way(&current_tree, &target_tree, |oid, buf| {
+                repo.objects.find_tree_iter(oid, buf).ok()
+            })
+            .map_err(|err| match err {
+                gix_index::merge::Error::WouldOverwrite { path } => Error::LocalChanges { paths: vec![path] },
+                err => err.into(),
+            })?;
+    }
+
+    let current: HashSet<&BStr> = index.entries().iter().map(|entry| entry.path(&index)).collect();
+    let mut removed: Vec<BString> = previous
+        .keys()
+        .filter(|path| !current.contains(path.as_bstr()))
+        .cloned()
+        .collect();
+    removed.sort();
+
+    let mut to_write = Vec::new();
+    let mut local_changes: Vec<BString> = removed
+        .iter()
+        .filter(|path| !options.force && modified.contains(*path))
+        .cloned()
+        .collect();
+    let mut untracked = Vec::new();
+    {
+        let mut excludes = (!options.force)
+            .then(|| {
+                repo.worktree()
+                    .expect("present as we have a working tree")
+                    .excludes(&index, None)
+            })
+            .transpose()?;
+        for (idx, entry) in index.entries().iter().enumerate() {
+            let path = entry.path(&index);
+            let is_changed_in_worktree = modified.contains(path) || missing.contains(path);
+            match previous.get(path) {
+                Some((id, mode)) if *id == entry.id && *mode == entry.mode => {
+                    if !(options.force && is_changed_in_worktree) {
+                        continue;
+                    }
+                }
+                Some(_) => {
+                    if !options.force && modified.contains(path) {
+                        local_changes.push(path.to_owned());
+                    }
+                }
+                None => {
+                    if let Some(excludes) = excludes.as_mut() {
+                        if let Some(in_the_way) =
+                            untracked_in_the_way(repo, workdir, path, entry.mode, &previous, excludes)?
+                        {
+                            untracked.push(in_the_way);
+                        }
+                    }
+                }
+            }
+            to_write.push(idx);
+        }
+    }
+    if !local_changes.is_empty() {
+        local_changes.sort();
+        return Err(Error::LocalChanges { paths: local_changes });
+    }
+    if !untracked.is_empty() {
+        return Err(Error::UntrackedFiles { paths: untracked });
+    }
+
+    for path in &removed {
+        remove_from_worktree(workdir, path.as_ref())?;
+    }
+
+    // Files are replaced instead of written into, so their mode is the one of the new entry and symlinks aren't followed.
+    // Submodules aren't checked out, only their directory is created.
+    let mut files = gix_index::State::new(repo.object_hash());
+    let mut written = Vec::with_capacity(to_write.len());
+    for &idx in &to_write {
+        let entry = &index.entries()[idx];
+        let path = workdir.join(gix_path::from_bstr(entry.path(&index)));
+        match path.symlink_metadata() {
+            Ok(meta) if !meta.is_dir() => std::fs::remove_file(&path).map_err(|err| Error::Io {
+                source: err,
+                path: path.clone(),
+            })?,
+            _ => {}
+        }
+        if entry.mode == Mode::COMMIT {
+            std::fs::create_dir_all(&path).map_err(|err| Error::Io { source: err, path })?;
+            continue;
+        }
+        files.dangerously_push_entry(
+            Stat::default(),
+            entry.id,
+            Flags::empty(),
+            entry.mode,
+            entry.path(&index),
+        );
+        written.push(idx);
+    }
+    checkout_options.destination_is_initially_empty = false;
+    checkout_options.overwrite_existing = true;
+    objects.lazy_fetch_missing(files.entries().iter().map(|entry| entry.id))?;
+    gix_worktree::checkout(
+        &mut files,
+        workdir,
+        move |oid, buf| objects.find_blob(oid, buf),
+        &mut progress::Discard,
+        &mut progress::Discard,
+        &AtomicBool::default(),
+        checkout_options,
+    )?;
+    let entries = index.entries_mut();
+    for (file, &idx) in files.entries().iter().zip(&written) {
+        entries[idx].stat = file.stat;
+    }
+    let updated = to_write
+        .iter()
+        .map(|&idx| index.entries()[idx].path(&index).to_owned())
+        .collect();
+
+    let mut lock = std::io::BufWriter::new(lock);
+    index
+        .write_to(&mut lock, Default::default())
+        .map_err(Error::WriteIndex)?;
+    lock.into_inner()
+        .map_err(|err| Error::WriteIndex(err.into_error()))?
+        .commit()?;
+
+    if let Some(name) = &new_branch {
+        repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: format!("branch: Created from {target}").into(),
+                },
+                expected: PreviousValue::MustNotExist,
+                new: Target::Peeled(commit_id),
+            },
+            name: name.clone(),
+            deref: false,
+        })?;
+    }
+    let moving_to = match &branch {
+        Some(name) => name.shorten().to_owned(),
+        None => target.to_owned(),
+    };
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: format!("checkout: moving from {moving_from} to {moving_to}").into(),
+            },
+            expected: PreviousValue::Any,
+            new: match &branch {
+                Some(name) => Target::Symbolic(name.clone()),
+                None => Target::Peeled(commit_id),
+            },
+        },
+        name: "HEAD".try_into().expect("valid"),
+        deref: false,
+    })?;
+
+    repo.run_hook(
+        crate::hook::name::POST_CHECKOUT,
+        &crate::hook::Input {
+            args: vec![
+                previous_head
+                    .unwrap_or_else(|| repo.object_hash().null())
+                    .to_string()
+                    .into(),
+                commit_id.to_string().into(),
+                "1".into(),
+            ],
+            ..Default::default()
+        },
+    )?;
+
+    Ok(Outcome {
+        previous_head,
+        head: commit_id,
+        branch,
+        updated,
+        removed,
+    })
+}
+
+/// An error accessing `path` in the working tree, which converts into the `Io` variant of the errors of all operations
+/// that change the working tree.
+pub(crate) struct IoError {
+    pub source: std::io::Error,
+    pub path: PathBuf,
+}
+
+impl From<IoError> for Error {
+    fn from(IoError { source, path }: IoError) -> Self {
+        Error::Io { source, path }
+    }
+}
+
+/// Return the path of the untracked file at `rela_path` or at one of its leading directories which would have to be overwritten
+/// to write an entry with `mode` there, unless it's ignored. Files of `tracked` entries are considered replaceable.
+pub(crate) fn untracked_in_the_way(
+    repo: &Repository,
+    workdir: &Path,
+    rela_path: &BStr,
+    mode: Mode,
+    tracked: &HashMap<BString, (ObjectId, Mode)>,
+    excludes: &mut gix_worktree::Cache,
+) -> Result<Option<BString>, IoError> {
+    let leading_dirs = rela_path.find_iter("/").map(|pos| rela_path[..pos].as_bstr());
+    for path in leading_dirs.chain(Some(rela_path)) {
+        let is_leading_dir = path.len() != rela_path.len();
+        if tracked.contains_key(path) {
+            // the tracked file will be replaced, and nothing can be below it.
+            return Ok(None);
+        }
+        let fs_path = workdir.join(gix_path::from_bstr(path));
+        let meta = match fs_path.symlink_metadata() {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(IoError {
+                    source: err,
+                    path: fs_path,
+                })
+            }
+        };
+        let is_in_the_way = if meta.is_dir() {
+            if is_leading_dir || mode == Mode::COMMIT {
+                continue;
+            }
+            let mut prefix = path.to_owned();
+            prefix.push(b'/');
+            !tracked.keys().any(|tracked| tracked.starts_with(&prefix))
+        } else {
+            true
+        };
+        if is_in_the_way {
+            let is_excluded = excludes
+                .at_entry(path, Some(meta.is_dir()), |id, buf| repo.objects.find_blob(id, buf))
+                .map_err(|err| IoError {
+                    source: err,
+                    path: fs_path,
+                })?
+                .is_excluded();
+            return Ok((!is_excluded).then(|| path.to_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Remove the file at `rela_path` along with the directories leading to it that become empty. Directories are left in place,
+/// just like files that don't exist anymore.
+pub(crate) fn remove_from_worktree(workdir: &Path, rela_path: &BStr) -> Result<(), IoError> {
+    let path = workdir.join(gix_path::from_bstr(rela_path));
+    match path.symlink_metadata() {
+        Ok(meta) if meta.is_dir() => {
+            std::fs::remove_dir(&path).ok();
+        }
+        Ok(_) => std::fs::remove_file(&path).map_err(|err| IoError {
+            source: err,
+            path: path.clone(),
+        })?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(IoError { source: err, path }),
+    }
+    let mut dir = path.parent();
+    while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+        if std::fs::remove_dir(parent).is_err() {
+            break;
+        }
+        dir = parent.parent();
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+//! Switch the working tree, index and `HEAD` from one commit to another, similar to `git checkout` or `git switch`.
+use std::path::PathBuf;
+
+use crate::bstr::BString;
+
+pub(crate) mod function;
+
+/// Options to control how [`Repository::checkout()`][crate::Repository::checkout()] switches to another commit.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// If `true`, discard local changes in the index and the working tree, and overwrite untracked files that are in the way,
+    /// similar to `git checkout --force`.
+    pub force: bool,
+    /// If `true`, let `HEAD` point to the target commit directly even if the target is a branch, similar to
+    /// `git checkout --detach`.
+    pub detach: bool,
+    /// If set, create a new branch with this name at the target commit and let `HEAD` point to it, similar to
+    /// `git checkout -b <name>`. It's an error if the branch already exists.
+    pub new_branch: Option<BString>,
+}
+
+/// The result of [`Repository::checkout()`][crate::Repository::checkout()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The commit `HEAD` pointed to before the checkout, or `None` if it was unborn.
+    pub previous_head: Option<gix_hash::ObjectId>,
+    /// The commit `HEAD` points to after the checkout.
+    pub head: gix_hash::ObjectId,
+    /// The branch `HEAD` points to after the checkout, or `None` if it is detached.
+    pub branch: Option<gix_ref::FullName>,
+    /// The paths of all files that were written to the working tree, in index order.
+    pub updated: Vec<BString>,
+    /// The paths of all files that were removed from the working tree, in index order.
+    pub removed: Vec<BString>,
+}
+
+/// The error returned by [`Repository::checkout()`][crate::Repository::checkout()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Repository at \"{}\" is a bare repository and cannot be checked out", git_dir.display())]
+    BareRepository { git_dir: PathBuf },
+    #[error(transparent)]
+    RevisionParse(#[from] crate::revision::spec::parse::single::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToKind(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error("The branch name '{name}' is invalid")]
+    InvalidBranchName {
+        name: BString,
+        source: gix_validate::refname::Error,
+    },
+    #[error("A branch named '{name}' already exists")]
+    BranchExists { name: BString },
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    PeelHeadToId(#[from] crate::head::peel::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error("Could not lock the index for writing")]
+    AcquireLock(#[from] gix_lock::acquire::Error),
+    #[error("Could not read the index")]
+    ReadIndex(#[from] gix_index::file::init::Error),
+    #[error("Could not write the index")]
+    WriteIndex(#[source] std::io::Error),
+    #[error("Could not commit the lock of the index")]
+    CommitLock(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error(transparent)]
+    MergeIntoIndex(#[from] gix_index::merge::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    Status(#[from] gix_worktree::status::Error),
+    #[error("Could not fetch the missing objects to check out")]
+    LazyFetch(#[from] gix_odb::store::find::Error),
+    #[error(transparent)]
+    Excludes(#[from] crate::worktree::excludes::Error),
+    #[error(
+        "Local changes to the following files would be overwritten by checkout: {}",
+        join(paths)
+    )]
+    LocalChanges { paths: Vec<BString> },
+    #[error(
+        "The following untracked working tree files would be overwritten by checkout: {}",
+        join(paths)
+    )]
+    UntrackedFiles { paths: Vec<BString> },
+    #[error("Could not access '{}' in the working tree", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error(transparent)]
+    Checkout(#[from] gix_worktree::checkout::Error<gix_odb::find::existing_object::Error<gix_odb::store::find::Error>>),
+    #[error(transparent)]
+    Hook(#[from] crate::hook::Error),
+}
+
+fn join(paths: &[BString]) -> String {
+    paths.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
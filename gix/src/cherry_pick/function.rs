@@ -0,0 +1,195 @@
+#![allow(clippy::result_large_err)]
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    cherry_pick::{Error, Options, Outcome},
+    merge, revert, Commit, Repository,
+};
+
+pub(crate) fn cherry_pick(repo: &Repository, id: ObjectId, onto: ObjectId, options: Options) -> Result<Outcome, Error> {
+    let commit = find_commit(repo, id)?;
+    let parent = parent(&commit, options.mainline)?;
+    let subject = commit.message()?.summary().into_owned();
+    let theirs_label = format!("{} ({subject})", commit.id().shorten_or_id());
+
+    let decoded = commit.decode()?;
+    let mut message: BString = decoded.message.to_owned();
+    if options.record_origin {
+        append_origin(&mut message, commit.id);
+    }
+    replay(
+        repo,
+        id,
+        onto,
+        [tree_of(repo, parent)?, commit.tree_id()?.detach()],
+        theirs_label.as_str().into(),
+        gix_object::Commit {
+            tree: ObjectId::null(repo.object_hash()),
+            parents: Default::default(),
+            author: decoded.author.to_owned(),
+            committer: decoded.committer.to_owned(),
+            encoding: decoded.encoding.map(ToOwned::to_owned),
+            message,
+            extra_headers: Default::default(),
+        },
+        options.allow_empty,
+        options.sign,
+    )
+}
+
+pub(crate) fn revert(
+    repo: &Repository,
+    id: ObjectId,
+    onto: ObjectId,
+    options: revert::Options,
+) -> Result<Outcome, Error> {
+    let commit = find_commit(repo, id)?;
+    let parent = parent(&commit, options.mainline)?;
+    let subject = commit.message()?.summary().into_owned();
+    let theirs_label = format!("parent of {} ({subject})", commit.id().shorten_or_id());
+
+    let mut message = format!("Revert \"{subject}\"\n\nThis reverts commit {id}");
+    match (options.mainline, parent) {
+        (Some(_), Some(parent)) => message.push_str(&format!(", reversing\nchanges made to {parent}.\n")),
+        _ => message.push_str(".\n"),
+    }
+    let author = repo
+        .author()
+        .ok_or(crate::commit::Error::AuthorMissing)?
+        .map_err(crate::commit::Error::from)?
+        .to_owned();
+    replay(
+        repo,
+        id,
+        onto,
+        [commit.tree_id()?.detach(), tree_of(repo, parent)?],
+        theirs_label.as_str().into(),
+        gix_object::Commit {
+            tree: ObjectId::null(repo.object_hash()),
+            parents: Default::default(),
+            author: author.clone(),
+            committer: author,
+            encoding: None,
+            message: message.into(),
+            extra_headers: Default::default(),
+        },
+        options.allow_empty,
+        options.sign,
+    )
+}
+
+/// Merge the changes from `base` to `theirs` of the commit with `id` into the tree of `onto`, and write `commit` with the merged
+/// tree, `onto` as parent and the current committer if there are no conflicts, and `sign` it if requested.
+#[allow(clippy::too_many_arguments)]
+fn replay(
+    repo: &Repository,
+    id: ObjectId,
+    onto: ObjectId,
+    [base, theirs]: [ObjectId; 2],
+    theirs_label: &BStr,
+    commit: gix_object::Commit,
+    allow_empty: bool,
+    sign: bool,
+) -> Result<Outcome, Error> {
+    let onto = find_commit(repo, onto)?;
+    let ours = onto.tree_id()?.detach();
+    let ours_label = onto.id().shorten_or_id().to_string();
+    let mut merge = repo.merge_trees(
+        base,
+        ours,
+        theirs,
+        merge::Labels {
+            ours: ours_label.as_str().into(),
+            theirs: theirs_label,
+        },
+    )?;
+    if !merge.conflicts.is_empty() {
+        return Ok(Outcome { commit: None, merge });
+    }
+
+    let tree = repo.write_tree_from_index(&mut merge.index)?.detach();
+    if tree == ours && !allow_empty {
+        return Err(Error::Empty { id });
+    }
+    let committer = repo
+        .committer()
+        .ok_or(crate::commit::Error::CommitterMissing)?
+        .map_err(crate::commit::Error::from)?
+        .to_owned();
+    let mut commit = gix_object::Commit {
+        tree,
+        parents: Some(onto.id).into_iter().collect(),
+        committer,
+        ..commit
+    };
+    if sign {
+        repo.sign_commit(&mut commit)?;
+    }
+    let id = repo.write_object(&commit).map_err(crate::commit::Error::from)?.detach();
+    Ok(Outcome {
+        commit: Some(id),
+        merge,
+    })
+}
+
+fn find_commit(repo: &Repository, id: ObjectId) -> Result<Commit<'_>, Error> {
+    Ok(repo
+        .find_object(id)?
+        .peel_to_kind(gix_object::Kind::Commit)?
+        .into_commit())
+}
+
+/// Return the parent of `commit` whose changes are replayed, or `None` if it's a root commit.
+fn parent(commit: &Commit<'_>, mainline: Option<usize>) -> Result<Option<ObjectId>, Error> {
+    let parents: Vec<_> = commit.parent_ids().map(crate::Id::detach).collect();
+    match (mainline, parents.len()) {
+        (None, 0 | 1) => Ok(parents.first().copied()),
+        (None, _) => Err(Error::MainlineMissing { id: commit.id }),
+        (Some(_), 0 | 1) => Err(Error::NotAMerge { id: commit.id }),
+        (Some(mainline), len) => mainline
+            .checked_sub(1)
+            .and_then(|idx| parents.get(idx).copied())
+            .map(Some)
+            .ok_or(Error::InvalidMainline {
+                id: commit.id,
+                mainline,
+                parents: len,
+            }),
+    }
+}
+
+/// Return the tree of `commit`, or the empty tree if there is no commit.
+fn tree_of(repo: &Repository, commit: Option<ObjectId>) -> Result<ObjectId, Error> {
+    Ok(match commit {
+        Some(id) => find_commit(repo, id)?.tree_id()?.detach(),
+        None => ObjectId::empty_tree(repo.object_hash()),
+    })
+}
+
+/// Append the line `git cherry-pick -x` adds to `message`, which is part of the trailers if the message ends with them.
+fn append_origin(message: &mut BString, id: ObjectId) {
+    if !message.ends_with(b"\n") {
+        message.push(b'\n');
+    }
+    if !ends_with_trailers(message.as_ref()) {
+        message.push(b'\n');
+    }
+    message.extend_from_slice(format!("(cherry picked from commit {id})\n").as_bytes());
+}
+
+/// Return `true` if the last paragraph of `message`, which isn't its title, only consists of trailers like `Signed-off-by: …`.
+fn ends_with_trailers(message: &BStr) -> bool {
+    let message = message.trim_end();
+    message.rfind(b"\n\n").map_or(false, |pos| {
+        message[pos + 2..].lines().all(|line| {
+            line.starts_with(b"(cherry picked from commit ")
+                || line.find(b": ").map_or(false, |pos| {
+                    pos > 0
+                        && line[..pos]
+                            .iter()
+                            .all(|byte| byte.is_ascii_alphanumeric() || *byte == b'-')
+                })
+        })
+    })
+}
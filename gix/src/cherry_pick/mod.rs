@@ -0,0 +1,67 @@
+//! Replay the changes of a commit onto another one, or undo them, similar to `git cherry-pick` and `git revert`.
+//!
+//! The changes are merged in memory with the [tree merge][crate::Repository::merge_trees()], using the parent of the commit
+//! as base, so neither the index nor a working tree are needed. References aren't changed either, so it's up to the caller to
+//! update a branch to the new commit.
+use gix_hash::ObjectId;
+
+pub(crate) mod function;
+
+/// Options to control how [`Repository::cherry_pick()`][crate::Repository::cherry_pick()] replays a commit.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The number of the parent, starting at `1`, whose changes to a merge commit are replayed, similar to
+    /// `git cherry-pick --mainline <parent>`. It must be set for merge commits, and it must not be set for any other commit.
+    pub mainline: Option<usize>,
+    /// If `true`, a line with the id of the commit is appended to the message of the new commit, similar to
+    /// `git cherry-pick -x`.
+    pub record_origin: bool,
+    /// If `true`, a commit is created even if it doesn't change anything, for instance because the changes were
+    /// replayed before. Otherwise, that's an error.
+    pub allow_empty: bool,
+    /// If `true`, the new commit is [signed][crate::Repository::sign()], similar to `git cherry-pick --gpg-sign`.
+    /// It's not signed otherwise, even if `commit.gpgSign` is set.
+    pub sign: bool,
+}
+
+/// The result of [`Repository::cherry_pick()`][crate::Repository::cherry_pick()] and
+/// [`Repository::revert()`][crate::Repository::revert()].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The id of the new commit whose parent is the commit the changes were replayed onto, or `None` if there were
+    /// [conflicts][crate::merge::Outcome::conflicts].
+    pub commit: Option<ObjectId>,
+    /// The merged entries of the new commit, with all conflicts in stages 1 to 3.
+    pub merge: crate::merge::Outcome,
+}
+
+/// The error returned by [`Repository::cherry_pick()`][crate::Repository::cherry_pick()] and
+/// [`Repository::revert()`][crate::Repository::revert()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Commit {id} is a merge but no mainline was given")]
+    MainlineMissing { id: ObjectId },
+    #[error("A mainline was given but commit {id} isn't a merge")]
+    NotAMerge { id: ObjectId },
+    #[error("Commit {id} doesn't have parent {mainline} as it only has {parents} parents")]
+    InvalidMainline {
+        id: ObjectId,
+        mainline: usize,
+        parents: usize,
+    },
+    #[error("The changes of commit {id} are already present, and empty commits aren't allowed")]
+    Empty { id: ObjectId },
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToKind(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Merge(#[from] crate::merge::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error<crate::object::write::Error>),
+    #[error(transparent)]
+    Commit(#[from] crate::commit::Error),
+}
@@ -36,6 +36,16 @@ impl PrepareFetch {
         self.shallow = shallow;
         self
     }
+
+    /// Make this clone a partial one which omits the objects described by `filter`, to be fetched on demand from the remote
+    /// we clone from once they are needed.
+    ///
+    /// The remote is configured as promisor remote with `remote.<name>.promisor` and `remote.<name>.partialCloneFilter`,
+    /// and named in `extensions.partialClone`.
+    pub fn with_filter(mut self, filter: crate::remote::fetch::Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
 }
 
 /// Consumption
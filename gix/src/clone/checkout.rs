@@ -31,10 +31,14 @@ pub mod main_worktree {
         ),
         #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
         OpenArcOdb(#[from] std::io::Error),
+        #[error("Could not fetch the missing objects to check out")]
+        LazyFetch(#[from] gix_odb::store::find::Error),
         #[error("The HEAD reference could not be located")]
         FindHead(#[from] crate::reference::find::existing::Error),
         #[error("The HEAD reference could not be located")]
         PeelHeadToId(#[from] crate::head::peel::Error),
+        #[error(transparent)]
+        Hook(#[from] crate::hook::Error),
     }
 
     /// The progress ids used in [`PrepareCheckout::main_worktree()`].
@@ -64,6 +68,9 @@ pub mod main_worktree {
         ///
         /// Note that this is a no-op if the remote was empty, leaving this repository empty as well. This can be validated by checking
         /// if the `head()` of the returned repository is not unborn.
+        ///
+        /// After the checkout, the `post-checkout` [hook][crate::hook::name::POST_CHECKOUT] runs, and if it fails, the checked out
+        /// repository can still be [persisted][PrepareCheckout::persist()].
         pub fn main_worktree(
             &mut self,
             mut progress: impl crate::Progress,
@@ -76,8 +83,8 @@ pub mod main_worktree {
             let workdir = repo.work_dir().ok_or_else(|| Error::BareRepository {
                 git_dir: repo.git_dir().to_owned(),
             })?;
-            let root_tree = match repo.head()?.peel_to_id_in_place().transpose()? {
-                Some(id) => id.object().expect("downloaded from remote").peel_to_tree()?.id,
+            let head_id = match repo.head()?.peel_to_id_in_place().transpose()? {
+                Some(id) => id,
                 None => {
                     return Ok((
                         self.repo.take().expect("still present"),
@@ -85,12 +92,11 @@ pub mod main_worktree {
                     ))
                 }
             };
-            let index = gix_index::State::from_tree(&root_tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())
-                .map_err(|err| Error::IndexFromTree {
-                    id: root_tree,
-                    source: err,
-                })?;
-            let mut index = gix_index::File::from_state(index, repo.index_path());
+            let root_tree = head_id.object().expect("downloaded from remote").peel_to_tree()?.id;
+            let mut index = repo.index_from_tree(&root_tree).map_err(|err| Error::IndexFromTree {
+                id: root_tree,
+                source: err,
+            })?;
 
             let mut opts = repo.config.checkout_options(repo.git_dir())?;
             opts.destination_is_initially_empty = true;
@@ -102,6 +108,14 @@ pub mod main_worktree {
             bytes.init(None, crate::progress::bytes());
 
             let start = std::time::Instant::now();
+            // Fetch all missing blobs of partial clones at once instead of one at a time while checking them out.
+            repo.objects.lazy_fetch_missing(
+                index
+                    .entries()
+                    .iter()
+                    .filter(|entry| entry.mode != gix_index::entry::Mode::COMMIT)
+                    .map(|entry| entry.id),
+            )?;
             let outcome = gix_worktree::checkout(
                 &mut index,
                 workdir,
@@ -118,6 +132,17 @@ pub mod main_worktree {
             bytes.show_throughput(start);
 
             index.write(Default::default())?;
+            repo.run_hook(
+                crate::hook::name::POST_CHECKOUT,
+                &crate::hook::Input {
+                    args: vec![
+                        repo.object_hash().null().to_string().into(),
+                        head_id.to_string().into(),
+                        "1".into(),
+                    ],
+                    ..Default::default()
+                },
+            )?;
             Ok((self.repo.take().expect("still present"), outcome))
         }
     }
@@ -121,15 +121,21 @@ impl PrepareFetch {
             b.insert_str(0, "clone: from ");
             b
         };
-        let outcome = pending_pack
+        let mut pending_pack = pending_pack
             .with_write_packed_refs_only(true)
             .with_reflog_message(RefLogMessage::Override {
                 message: reflog_message.clone(),
             })
-            .with_shallow(self.shallow.clone())
-            .receive(progress, should_interrupt)?;
+            .with_shallow(self.shallow.clone());
+        if let Some(filter) = self.filter {
+            pending_pack = pending_pack.with_filter(filter);
+        }
+        let outcome = pending_pack.receive(progress, should_interrupt)?;
 
         util::append_config_to_repo_config(repo, config);
+        if let Some(filter) = self.filter {
+            util::setup_partial_clone(repo, remote_name.as_ref(), filter)?;
+        }
         util::update_head(
             repo,
             &outcome.ref_map.remote_refs,
@@ -103,7 +103,8 @@ pub fn update_head(
                 head_ref_name: referent.to_owned(),
                 source: err,
             })?;
-            repo.refs
+            let transaction = repo
+                .refs
                 .transaction()
                 .packed_refs(gix_ref::file::transaction::PackedRefs::DeletionsAndNonSymbolicUpdates(
                     Box::new(|oid, buf| {
@@ -140,13 +141,13 @@ pub fn update_head(
                     gix_lock::acquire::Fail::Immediately,
                     gix_lock::acquire::Fail::Immediately,
                 )
-                .map_err(crate::reference::edit::Error::from)?
-                .commit(
-                    repo.committer()
-                        .transpose()
-                        .map_err(|err| Error::HeadUpdate(crate::reference::edit::Error::ParseCommitterTime(err)))?,
-                )
                 .map_err(crate::reference::edit::Error::from)?;
+            repo.commit_ref_transaction(
+                transaction,
+                repo.committer()
+                    .transpose()
+                    .map_err(|err| Error::HeadUpdate(crate::reference::edit::Error::ParseCommitterTime(err)))?,
+            )?;
 
             if let Some(head_peeled_id) = head_peeled_id {
                 let mut log = reflog_message();
@@ -183,6 +184,45 @@ pub fn update_head(
     Ok(())
 }
 
+/// Make `remote_name` the promisor remote of `repo` from which objects omitted due to `filter` are fetched on demand,
+/// and start doing so right away.
+pub fn setup_partial_clone(
+    repo: &mut Repository,
+    remote_name: &BStr,
+    filter: crate::remote::fetch::Filter,
+) -> Result<(), Error> {
+    let filter = filter.to_string();
+    let mut config = repo.config_snapshot_mut();
+    for (section, subsection, key, value) in [
+        ("core", None, "repositoryFormatVersion", "1".into()),
+        ("extensions", None, "partialClone", remote_name),
+        ("remote", Some(remote_name), "promisor", "true".into()),
+        (
+            "remote",
+            Some(remote_name),
+            "partialCloneFilter",
+            filter.as_str().into(),
+        ),
+    ] {
+        config
+            .set_raw_value_filter(section, subsection, key, value, &mut |meta| {
+                meta.source == gix_config::Source::Local
+            })
+            .expect("section and key names are valid");
+    }
+    write_to_local_config(&config, WriteMode::Overwrite)?;
+    config.commit().expect("configuration we set is valid");
+
+    repo.objects
+        .store_ref()
+        .set_lazy_fetch(Some(crate::remote::promisor::lazy_fetch(
+            repo.git_dir().to_owned(),
+            repo.options.clone(),
+            remote_name.to_owned(),
+        )));
+    Ok(())
+}
+
 /// Setup the remote configuration for `branch` so that it points to itself, but on the remote, if and only if currently
 /// saved refspecs are able to match it.
 /// For that we reload the remote of `remote_name` and use its ref_specs for match.
@@ -3,7 +3,7 @@
 /// An empty array of a type usable with the `gix::easy` API to help declaring no parents should be used
 pub const NO_PARENT_IDS: [gix_hash::ObjectId; 0] = [];
 
-/// The error returned by [`commit(…)`][crate::Repository::commit()].
+/// The error returned by [`commit(…)`][crate::Repository::commit()] and [`commit_index(…)`][crate::Repository::commit_index()].
 #[derive(Debug, thiserror::Error)]
 #[allow(missing_docs)]
 pub enum Error {
@@ -19,6 +19,27 @@ pub enum Error {
     WriteObject(#[from] crate::object::write::Error),
     #[error(transparent)]
     ReferenceEdit(#[from] crate::reference::edit::Error),
+    #[error("Could not serialize the commit to sign it")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Sign(#[from] crate::gpg::sign::Error),
+    #[error(transparent)]
+    Hook(#[from] crate::hook::Error),
+    #[error("Could not write or read the commit message at \"{}\" for use by hooks", path.display())]
+    MessageFile {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("The commit message left behind by the hooks isn't valid UTF-8")]
+    MessageEncoding,
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error<crate::object::write::Error>),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    PeelHead(#[from] crate::head::peel::Error),
 }
 
 ///
@@ -21,7 +21,6 @@ use crate::{
 /// Access
 impl Cache {
     pub(crate) fn diff_algorithm(&self) -> Result<gix_diff::blob::Algorithm, config::diff::algorithm::Error> {
-        use crate::config::diff::algorithm::Error;
         self.diff_algorithm
             .get_or_try_init(|| {
                 let name = self
@@ -30,10 +29,6 @@ impl Cache {
                     .unwrap_or_else(|| Cow::Borrowed("myers".into()));
                 config::tree::Diff::ALGORITHM
                     .try_into_algorithm(name)
-                    .or_else(|err| match err {
-                        Error::Unimplemented { .. } if self.lenient_config => Ok(gix_diff::blob::Algorithm::Histogram),
-                        err => Err(err),
-                    })
                     .with_lenient_default(self.lenient_config)
             })
             .copied()
@@ -177,7 +172,6 @@ impl Cache {
                 git_dir,
                 case,
                 gix_worktree::cache::state::attributes::Source::AttributeListThenWorktree,
-                self.attributes,
             )?,
             fs: capabilities,
             thread_limit,
@@ -205,15 +199,30 @@ impl Cache {
         git_dir: &std::path::Path,
         case: gix_glob::pattern::Case,
         source: gix_worktree::cache::state::attributes::Source,
-        attributes: crate::permissions::Attributes,
     ) -> Result<gix_worktree::cache::state::Attributes, config::attribute_stack::Error> {
+        let (globals, collection) = self.attribute_globals()?;
+        let info_attributes_path = git_dir.join("info").join("attributes");
+        Ok(gix_worktree::cache::state::Attributes::new(
+            globals,
+            Some(info_attributes_path),
+            case,
+            source,
+            collection,
+        ))
+    }
+
+    /// Load all global attribute files as permitted by our attribute permissions, along with the metadata needed to query them.
+    pub(crate) fn attribute_globals(
+        &self,
+    ) -> Result<(gix_attributes::Search, gix_attributes::search::MetadataCollection), config::attribute_stack::Error>
+    {
         let configured_or_user_attributes = match self
             .trusted_file_path("core", None, Core::ATTRIBUTES_FILE.name)
             .transpose()?
         {
             Some(attributes) => Some(attributes),
             None => {
-                if attributes.git {
+                if self.attributes.git {
                     self.xdg_config_path("attributes").ok().flatten().map(Cow::Owned)
                 } else {
                     None
@@ -223,22 +232,16 @@ impl Cache {
         let attribute_files = [gix_attributes::Source::GitInstallation, gix_attributes::Source::System]
             .into_iter()
             .filter(|source| match source {
-                Source::GitInstallation => attributes.git_binary,
-                Source::System => attributes.system,
+                Source::GitInstallation => self.attributes.git_binary,
+                Source::System => self.attributes.system,
                 Source::Git | Source::Local => unreachable!("we don't offer turning this off right now"),
             })
             .filter_map(|source| source.storage_location(&mut Self::make_source_env(self.environment)))
             .chain(configured_or_user_attributes);
-        let info_attributes_path = git_dir.join("info").join("attributes");
         let mut buf = Vec::new();
         let mut collection = gix_attributes::search::MetadataCollection::default();
-        Ok(gix_worktree::cache::state::Attributes::new(
-            gix_attributes::Search::new_globals(attribute_files, &mut buf, &mut collection)?,
-            Some(info_attributes_path),
-            case,
-            source,
-            collection,
-        ))
+        let globals = gix_attributes::Search::new_globals(attribute_files, &mut buf, &mut collection)?;
+        Ok((globals, collection))
     }
 
     pub(crate) fn xdg_config_path(
@@ -1,6 +1,9 @@
 #![allow(clippy::result_large_err)]
 use super::{util, Error};
-use crate::config::tree::{Core, Extensions};
+use crate::{
+    bstr::BString,
+    config::tree::{Core, Extensions},
+};
 
 /// A utility to deal with the cyclic dependency between the ref store and the configuration. The ref-store needs the
 /// object hash kind, and the configuration needs the current branch name to resolve conditional includes with `onbranch`.
@@ -10,7 +13,7 @@ pub(crate) struct StageOne {
 
     pub is_bare: bool,
     pub lossy: Option<bool>,
-    pub object_hash: gix_hash::Kind,
+    pub extensions: crate::Extensions,
     pub reflog: Option<gix_ref::store::WriteReflog>,
 }
 
@@ -33,32 +36,8 @@ impl StageOne {
             lenient,
         )?;
 
-        // Note that we assume the repo is bare by default unless we are told otherwise. This is relevant if
-        // the repo doesn't have a configuration file.
-        let is_bare = util::config_bool(&config, &Core::BARE, "core.bare", true, lenient)?;
-        let repo_format_version = config
-            .integer_by_key("core.repositoryFormatVersion")
-            .map(|version| Core::REPOSITORY_FORMAT_VERSION.try_into_usize(version))
-            .transpose()?
-            .unwrap_or_default();
-        let object_hash = (repo_format_version != 1)
-            .then_some(Ok(gix_hash::Kind::Sha1))
-            .or_else(|| {
-                config
-                    .string("extensions", None, "objectFormat")
-                    .map(|format| Extensions::OBJECT_FORMAT.try_into_object_format(format))
-            })
-            .transpose()?
-            .unwrap_or(gix_hash::Kind::Sha1);
-
-        let extension_worktree = util::config_bool(
-            &config,
-            &Extensions::WORKTREE_CONFIG,
-            "extensions.worktreeConfig",
-            false,
-            lenient,
-        )?;
-        if extension_worktree {
+        let extensions = extensions(&config, lenient)?;
+        if extensions.worktree_config {
             let worktree_config = load_config(
                 git_dir.join("config.worktree"),
                 &mut buf,
@@ -70,18 +49,99 @@ impl StageOne {
             config.append(worktree_config);
         };
 
+        // Note that we assume the repo is bare by default unless we are told otherwise. This is relevant if
+        // the repo doesn't have a configuration file.
+        // It's queried only now as `core.bare` may be moved into the per-worktree configuration.
+        let is_bare = util::config_bool(&config, &Core::BARE, "core.bare", true, lenient)?;
+
         let reflog = util::query_refupdates(&config, lenient)?;
         Ok(StageOne {
             git_dir_config: config,
             buf,
             is_bare,
             lossy,
-            object_hash,
+            extensions,
             reflog,
         })
     }
 }
 
+/// Validate `core.repositoryFormatVersion` and parse all `extensions.*` of the repository-local `config` like git does,
+/// failing if any of them isn't understood as the repository could be corrupted otherwise.
+/// Note that unknown extensions are ignored with repository format version 0.
+fn extensions(config: &gix_config::File<'static>, lenient: bool) -> Result<crate::Extensions, Error> {
+    let repository_format_version = config
+        .integer_by_key("core.repositoryFormatVersion")
+        .map(|version| Core::REPOSITORY_FORMAT_VERSION.try_into_usize(version))
+        .transpose()?
+        .unwrap_or_default();
+    if repository_format_version > 1 {
+        return Err(Error::UnsupportedRepositoryFormatVersion {
+            version: repository_format_version,
+        });
+    }
+
+    let mut unknown = Vec::<BString>::new();
+    let mut v1_only = Vec::<BString>::new();
+    for section in config.sections_by_name("extensions").into_iter().flatten() {
+        for key in section.keys() {
+            let name: BString = match section.header().subsection_name() {
+                Some(subsection) => format!("{subsection}.{key}").into(),
+                None => key.as_ref().into(),
+            };
+            let names = match name.to_ascii_lowercase().as_slice() {
+                b"noop" | b"preciousobjects" | b"partialclone" | b"worktreeconfig" => continue,
+                b"noop-v1" | b"objectformat" | b"refstorage" => &mut v1_only,
+                _ => &mut unknown,
+            };
+            if !names.iter().any(|existing| existing.eq_ignore_ascii_case(&name)) {
+                names.push(name);
+            }
+        }
+    }
+    match repository_format_version {
+        0 if !v1_only.is_empty() => return Err(Error::ExtensionsRequireFormatVersion1 { names: v1_only }),
+        1 if !unknown.is_empty() => return Err(Error::UnknownExtensions { names: unknown }),
+        _ => {}
+    }
+
+    let object_format = (repository_format_version == 1)
+        .then(|| {
+            config
+                .string("extensions", None, "objectFormat")
+                .map(|format| Extensions::OBJECT_FORMAT.try_into_object_format(format))
+        })
+        .flatten()
+        .transpose()?
+        .unwrap_or(gix_hash::Kind::Sha1);
+    if repository_format_version == 1 {
+        if let Some(ref_storage) = config.string("extensions", None, "refStorage") {
+            Extensions::REF_STORAGE.try_into_ref_storage(ref_storage)?;
+        }
+    }
+    Ok(crate::Extensions {
+        repository_format_version,
+        object_format,
+        partial_clone: config
+            .string("extensions", None, "partialClone")
+            .map(std::borrow::Cow::into_owned),
+        precious_objects: util::config_bool(
+            config,
+            &Extensions::PRECIOUS_OBJECTS,
+            "extensions.preciousObjects",
+            false,
+            lenient,
+        )?,
+        worktree_config: util::config_bool(
+            config,
+            &Extensions::WORKTREE_CONFIG,
+            "extensions.worktreeConfig",
+            false,
+            lenient,
+        )?,
+    })
+}
+
 fn load_config(
     config_path: std::path::PathBuf,
     buf: &mut Vec<u8>,
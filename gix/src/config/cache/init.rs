@@ -24,7 +24,7 @@ impl Cache {
             mut buf,
             lossy,
             is_bare,
-            object_hash,
+            extensions,
             reflog: _,
         }: StageOne,
         git_dir: &std::path::Path,
@@ -138,6 +138,7 @@ impl Cache {
             globals
         };
 
+        let object_hash = extensions.object_format;
         let hex_len = util::parse_core_abbrev(&config, object_hash).with_leniency(lenient_config)?;
 
         use util::config_bool;
@@ -158,6 +159,7 @@ impl Cache {
             resolved: config.into(),
             use_multi_pack_index,
             object_hash,
+            extensions,
             object_kind_hint,
             pack_cache_bytes,
             object_cache_bytes,
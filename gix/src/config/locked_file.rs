@@ -0,0 +1,88 @@
+use std::{
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
+
+use crate::config::LockedFile;
+
+/// The error returned by [`LockedFile::open()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Lock(#[from] gix_lock::acquire::Error),
+    #[error(transparent)]
+    Read(#[from] gix_config::file::init::from_paths::Error),
+}
+
+///
+pub mod commit {
+    /// The error returned by [`LockedFile::commit()`][crate::config::LockedFile::commit()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not write configuration to its lock file")]
+        Write(#[from] std::io::Error),
+        #[error("Could not move the lock file into place to replace the configuration file")]
+        Commit(#[from] gix_lock::commit::Error<gix_lock::File>),
+    }
+}
+
+/// Lifecycle
+impl LockedFile {
+    /// Lock the configuration file at `path` and read it as configuration of the given `source`, without following includes.
+    ///
+    /// The lock is acquired before reading to assure no other writer can interfere, and it fails immediately if the file
+    /// is locked already. If the file doesn't exist yet, the configuration starts out empty.
+    pub fn open(path: impl Into<PathBuf>, source: gix_config::Source) -> Result<Self, Error> {
+        let path = path.into();
+        let lock = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)?;
+        let config = match gix_config::File::from_path_no_includes(path.clone(), source) {
+            Ok(config) => config,
+            Err(gix_config::file::init::from_paths::Error::Io { source: err, .. })
+                if err.kind() == std::io::ErrorKind::NotFound =>
+            {
+                gix_config::File::new(gix_config::file::Metadata::from(source).at(path))
+            }
+            Err(err) => return Err(err.into()),
+        };
+        Ok(LockedFile { lock, config })
+    }
+
+    /// Write all changes to the lock file and atomically replace the configuration file with it, returning the written configuration.
+    ///
+    /// Note that repositories opened before won't see the changes, they have to be reopened.
+    pub fn commit(mut self) -> Result<gix_config::File<'static>, commit::Error> {
+        let config = self.config;
+        self.lock.with_mut(|out| config.write_to(out))?;
+        self.lock.commit()?;
+        Ok(config)
+    }
+}
+
+/// Access
+impl LockedFile {
+    /// The path to the configuration file that will be replaced on [commit][Self::commit()].
+    pub fn path(&self) -> PathBuf {
+        self.lock.resource_path()
+    }
+
+    /// The path to the lock file which receives all changes until they are committed.
+    pub fn lock_path(&self) -> &Path {
+        self.lock.lock_path()
+    }
+}
+
+impl Deref for LockedFile {
+    type Target = gix_config::File<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.config
+    }
+}
+
+impl DerefMut for LockedFile {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.config
+    }
+}
@@ -27,13 +27,25 @@ pub struct Snapshot<'repo> {
 /// Note that these values won't update even if the underlying file(s) change.
 ///
 /// Use [`forget()`][Self::forget()] to not apply any of the changes.
-// TODO: make it possible to load snapshots with reloading via .config() and write mutated snapshots back to disk which should be the way
-//       to affect all instances of a repo, probably via `config_mut()` and `config_mut_at()`.
+/// To persist changes in a particular configuration file, use [`Repository::config_file_mut()`][crate::Repository::config_file_mut()].
 pub struct SnapshotMut<'repo> {
     pub(crate) repo: Option<&'repo mut Repository>,
     pub(crate) config: gix_config::File<'static>,
 }
 
+/// A configuration file which is locked for writing, to be edited in memory before its changes are written back to disk
+/// with [`commit()`][Self::commit()].
+///
+/// It dereferences to the underlying configuration file to read and edit its values while preserving its formatting.
+/// Dropping it without committing leaves the configuration file unchanged.
+pub struct LockedFile {
+    pub(crate) lock: gix_lock::File,
+    pub(crate) config: gix_config::File<'static>,
+}
+
+///
+pub mod locked_file;
+
 /// A utility structure created by [`SnapshotMut::commit_auto_rollback()`] that restores the previous configuration on drop.
 pub struct CommitAutoRollback<'repo> {
     pub(crate) repo: Option<&'repo mut Repository>,
@@ -60,6 +72,12 @@ pub enum Error {
     ConfigTypedString(#[from] key::GenericErrorWithValue),
     #[error("Cannot handle objects formatted as {:?}", .name)]
     UnsupportedObjectFormat { name: BString },
+    #[error("Expected repository format version 0 or 1, found {version}")]
+    UnsupportedRepositoryFormatVersion { version: usize },
+    #[error("Refusing to open repository with unknown extension(s) {}", names.iter().map(|name| format!("'{name}'")).collect::<Vec<_>>().join(", "))]
+    UnknownExtensions { names: Vec<BString> },
+    #[error("The repository format version is 0, but extension(s) {} require version 1", names.iter().map(|name| format!("'{name}'")).collect::<Vec<_>>().join(", "))]
+    ExtensionsRequireFormatVersion1 { names: Vec<BString> },
     #[error(transparent)]
     CoreAbbrev(#[from] abbrev::Error),
     #[error("Could not read configuration file at \"{}\"", path.display())]
@@ -95,8 +113,6 @@ pub mod diff {
         pub enum Error {
             #[error("Unknown diff algorithm named '{name}'")]
             Unknown { name: BString },
-            #[error("The '{name}' algorithm is not yet implemented")]
-            Unimplemented { name: BString },
         }
     }
 }
@@ -118,6 +134,34 @@ pub mod checkout_options {
     }
 }
 
+///
+pub mod config_file_mut {
+    /// The error returned by [`Repository::config_file_mut()`][crate::Repository::config_file_mut()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Configuration from {0:?} isn't stored in a file, or its location is unknown or not permitted")]
+        NoStorageLocation(gix_config::Source),
+        #[error(transparent)]
+        Open(#[from] super::locked_file::Error),
+    }
+}
+
+///
+pub mod worktree_config_mut {
+    /// The error returned by [`Repository::worktree_config_mut()`][crate::Repository::worktree_config_mut()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not list linked worktrees")]
+        ListWorktrees(#[from] std::io::Error),
+        #[error("Per-worktree configuration can't be written with multiple worktrees unless `extensions.worktreeConfig` is enabled")]
+        WorktreeConfigDisabled,
+        #[error(transparent)]
+        Open(#[from] super::locked_file::Error),
+    }
+}
+
 ///
 pub mod attribute_stack {
     /// The error produced when setting up the attribute stack to query `gitattributes`.
@@ -431,6 +475,8 @@ pub(crate) struct Cache {
     pub is_bare: bool,
     /// The type of hash to use.
     pub object_hash: gix_hash::Kind,
+    /// The repository format and the extensions it uses.
+    pub extensions: crate::Extensions,
     /// If true, multi-pack indices, whether present or not, may be used by the object database.
     pub use_multi_pack_index: bool,
     /// The representation of `core.logallrefupdates`, or `None` if the variable wasn't set.
@@ -3,7 +3,7 @@ use std::{borrow::Cow, convert::TryFrom};
 pub use error::Error;
 
 use crate::{
-    bstr::{ByteSlice, ByteVec},
+    bstr::{BStr, ByteSlice, ByteVec},
     config::{
         tree::{credential, Core, Credential, Key},
         Snapshot,
@@ -17,8 +17,9 @@ mod error {
     #[derive(Debug, thiserror::Error)]
     #[allow(missing_docs)]
     pub enum Error {
-        #[error("Could not parse 'useHttpPath' key in section {section}")]
-        InvalidUseHttpPath {
+        #[error("Could not parse '{key}' key in section {section}")]
+        InvalidBoolean {
+            key: &'static str,
             section: BString,
             source: gix_config::value::Error,
         },
@@ -43,7 +44,6 @@ impl Snapshot<'_> {
     /// - Upper-case scheme and host will be lower-cased automatically when parsing into a url, so prompts differ compared to git.
     /// - A **difference in prompt might affect the matching of getting existing stored credentials**, and it's a question of this being
     ///   a feature or a bug.
-    // TODO: when dealing with `http.*.*` configuration, generalize this algorithm as needed and support precedence.
     pub fn credential_helpers(
         &self,
         mut url: gix_url::Url,
@@ -57,6 +57,8 @@ impl Snapshot<'_> {
     > {
         let mut programs = Vec::new();
         let mut use_http_path = false;
+        let mut sanitize_prompt = true;
+        let mut protect_protocol = true;
         let url_had_user_initially = url.user().is_some();
         normalize(&mut url);
 
@@ -66,39 +68,27 @@ impl Snapshot<'_> {
             .resolved
             .sections_by_name_and_filter("credential", &mut self.repo.filter_config_section())
         {
+            let mut partial_url_ctx = None;
             for section in credential_sections {
-                let section = match section.header().subsection_name() {
-                    Some(pattern) => gix_url::parse(pattern).ok().and_then(|mut pattern| {
-                        normalize(&mut pattern);
-                        let is_http = matches!(pattern.scheme, gix_url::Scheme::Https | gix_url::Scheme::Http);
-                        let scheme = &pattern.scheme;
-                        let host = pattern.host();
-                        let ports = is_http
-                            .then(|| (pattern.port_or_default(), url.port_or_default()))
-                            .unwrap_or((pattern.port, url.port));
-                        let path = (!(is_http && pattern.path_is_root())).then_some(&pattern.path);
-
-                        if !path.map_or(true, |path| path == &url.path) {
-                            return None;
-                        }
-                        if pattern.user().is_some() && pattern.user() != url.user() {
-                            return None;
-                        }
-                        (scheme == &url.scheme && host_matches(host, url.host()) && ports.0 == ports.1).then_some((
-                            section,
-                            &credential::UrlParameter::HELPER,
-                            &credential::UrlParameter::USERNAME,
-                            &credential::UrlParameter::USE_HTTP_PATH,
-                        ))
-                    }),
+                let keys = match section.header().subsection_name() {
+                    Some(pattern) => url_matches(&url, pattern, &mut partial_url_ctx).then_some((
+                        &credential::UrlParameter::HELPER,
+                        &credential::UrlParameter::USERNAME,
+                        &credential::UrlParameter::USE_HTTP_PATH,
+                        &credential::UrlParameter::SANITIZE_PROMPT,
+                        &credential::UrlParameter::PROTECT_PROTOCOL,
+                    )),
                     None => Some((
-                        section,
                         &Credential::HELPER,
                         &Credential::USERNAME,
                         &Credential::USE_HTTP_PATH,
+                        &Credential::SANITIZE_PROMPT,
+                        &Credential::PROTECT_PROTOCOL,
                     )),
                 };
-                if let Some((section, helper_key, username_key, use_http_path_key)) = section {
+                if let Some((helper_key, username_key, use_http_path_key, sanitize_prompt_key, protect_protocol_key)) =
+                    keys
+                {
                     for value in section.values(helper_key.name) {
                         if value.trim().is_empty() {
                             programs.clear();
@@ -117,19 +107,26 @@ impl Snapshot<'_> {
                     }) {
                         url.set_user(Some(user));
                     }
-                    if let Some(toggle) = section
-                        .value(use_http_path_key.name)
-                        .map(|val| {
-                            gix_config::Boolean::try_from(val)
-                                .map_err(|err| Error::InvalidUseHttpPath {
-                                    source: err,
-                                    section: section.header().to_bstring(),
-                                })
-                                .map(|b| b.0)
-                        })
-                        .transpose()?
-                    {
-                        use_http_path = toggle;
+                    for (key, value) in [
+                        (use_http_path_key, &mut use_http_path),
+                        (sanitize_prompt_key, &mut sanitize_prompt),
+                        (protect_protocol_key, &mut protect_protocol),
+                    ] {
+                        if let Some(toggle) = section
+                            .value(key.name)
+                            .map(|val| {
+                                gix_config::Boolean::try_from(val)
+                                    .map_err(|err| Error::InvalidBoolean {
+                                        key: key.name,
+                                        source: err,
+                                        section: section.header().to_bstring(),
+                                    })
+                                    .map(|b| b.0)
+                            })
+                            .transpose()?
+                        {
+                            *value = toggle;
+                        }
                     }
                 }
             }
@@ -137,7 +134,7 @@ impl Snapshot<'_> {
 
         let allow_git_env = self.repo.options.permissions.env.git_prefix.is_allowed();
         let allow_ssh_env = self.repo.options.permissions.env.ssh_prefix.is_allowed();
-        let prompt_options = gix_prompt::Options {
+        let mut prompt_options = gix_prompt::Options {
             askpass: self
                 .trusted_path(Core::ASKPASS.logical_name().as_str())
                 .transpose()?
@@ -145,10 +142,21 @@ impl Snapshot<'_> {
             ..Default::default()
         }
         .apply_environment(allow_git_env, allow_ssh_env, allow_git_env);
+        if self
+            .string(Credential::INTERACTIVE.logical_name().as_str())
+            .map_or(false, |value| {
+                value.eq_ignore_ascii_case(b"never")
+                    || gix_config::Boolean::try_from(value.as_ref()).map_or(false, |b| !b.0)
+            })
+        {
+            prompt_options.mode = gix_prompt::Mode::Disable;
+        }
         Ok((
             gix_credentials::helper::Cascade {
                 programs,
                 use_http_path,
+                sanitize_prompt,
+                protect_protocol,
                 // The default ssh implementation uses binaries that do their own auth, so our passwords aren't used.
                 query_user_only: url.scheme == gix_url::Scheme::Ssh,
                 ..Default::default()
@@ -159,20 +167,32 @@ impl Snapshot<'_> {
     }
 }
 
-fn host_matches(pattern: Option<&str>, host: Option<&str>) -> bool {
-    match (pattern, host) {
-        (Some(pattern), Some(host)) => {
-            let lfields = pattern.split('.');
-            let rfields = host.split('.');
-            if lfields.clone().count() != rfields.clone().count() {
-                return false;
-            }
-            lfields
-                .zip(rfields)
-                .all(|(pat, value)| gix_glob::wildmatch(pat.into(), value.into(), gix_glob::wildmatch::Mode::empty()))
+/// Return `true` if `url` is matched by the `pattern` of a `credential.<url>` section, like `git` does.
+///
+/// Patterns that aren't valid URLs, like `example.com`, are interpreted as partial URLs whose fields have to match the
+/// respective field of `url` if set. The credential context to compare them with is created on demand in `partial_url_ctx`.
+fn url_matches(
+    url: &gix_url::Url,
+    pattern: &BStr,
+    partial_url_ctx: &mut Option<Option<gix_credentials::protocol::Context>>,
+) -> bool {
+    if pattern.contains_str("://") {
+        if let Ok(mut pattern) = gix_url::parse(pattern) {
+            normalize(&mut pattern);
+            return url.matches(&pattern).is_some();
         }
-        (None, None) => true,
-        (Some(_), None) | (None, Some(_)) => false,
+    }
+    let have = partial_url_ctx.get_or_insert_with(|| {
+        let mut ctx = gix_credentials::protocol::Context {
+            url: Some(url.to_bstring()),
+            ..Default::default()
+        };
+        ctx.destructure_url_in_place(true).ok()?;
+        Some(ctx)
+    });
+    match (gix_credentials::protocol::Context::from_partial_url(pattern), have) {
+        (Some(want), Some(have)) => want.matches(have),
+        _ => false,
     }
 }
 
@@ -0,0 +1,20 @@
+use crate::{
+    config,
+    config::tree::{keys, Blame, Key, Section},
+};
+
+impl Blame {
+    /// The `blame.ignoreRevsFile` key.
+    pub const IGNORE_REVS_FILE: keys::Path = keys::Path::new_path("ignoreRevsFile", &config::Tree::BLAME)
+        .with_note("Multiple values are supported, and an empty value resets the list of files collected so far");
+}
+
+impl Section for Blame {
+    fn name(&self) -> &str {
+        "blame"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::IGNORE_REVS_FILE]
+    }
+}
@@ -6,6 +6,8 @@ use crate::{
 impl Core {
     /// The `core.abbrev` key.
     pub const ABBREV: Abbrev = Abbrev::new_with_validate("abbrev", &config::Tree::CORE, validate::Abbrev);
+    /// The `core.autocrlf` key.
+    pub const AUTO_CRLF: AutoCrlf = AutoCrlf::new_with_validate("autocrlf", &config::Tree::CORE, validate::AutoCrlf);
     /// The `core.bare` key.
     pub const BARE: keys::Boolean = keys::Boolean::new_boolean("bare", &config::Tree::CORE);
     /// The `core.checkStat` key.
@@ -21,6 +23,8 @@ impl Core {
         Disambiguate::new_with_validate("disambiguate", &config::Tree::CORE, validate::Disambiguate);
     /// The `core.fileMode` key.
     pub const FILE_MODE: keys::Boolean = keys::Boolean::new_boolean("fileMode", &config::Tree::CORE);
+    /// The `core.hooksPath` key.
+    pub const HOOKS_PATH: keys::Path = keys::Path::new_path("hooksPath", &config::Tree::CORE);
     /// The `core.ignoreCase` key.
     pub const IGNORE_CASE: keys::Boolean = keys::Boolean::new_boolean("ignoreCase", &config::Tree::CORE);
     /// The `core.filesRefLockTimeout` key.
@@ -39,6 +43,8 @@ impl Core {
     /// Needs application to use [env::args_os][crate::env::args_os()] to conform all input paths before they are used.
     pub const PRECOMPOSE_UNICODE: keys::Boolean = keys::Boolean::new_boolean("precomposeUnicode", &config::Tree::CORE)
         .with_note("application needs to conform all program input by using gix::env::args_os()");
+    /// The `core.quotePath` key.
+    pub const QUOTE_PATH: keys::Boolean = keys::Boolean::new_boolean("quotePath", &config::Tree::CORE);
     /// The `core.repositoryFormatVersion` key.
     pub const REPOSITORY_FORMAT_VERSION: keys::UnsignedInteger =
         keys::UnsignedInteger::new_unsigned_integer("repositoryFormatVersion", &config::Tree::CORE);
@@ -73,17 +79,20 @@ impl Section for Core {
     fn keys(&self) -> &[&dyn Key] {
         &[
             &Self::ABBREV,
+            &Self::AUTO_CRLF,
             &Self::BARE,
             &Self::CHECK_STAT,
             &Self::DELTA_BASE_CACHE_LIMIT,
             &Self::DISAMBIGUATE,
             &Self::FILE_MODE,
+            &Self::HOOKS_PATH,
             &Self::IGNORE_CASE,
             &Self::FILES_REF_LOCK_TIMEOUT,
             &Self::PACKED_REFS_TIMEOUT,
             &Self::MULTIPACK_INDEX,
             &Self::LOG_ALL_REF_UPDATES,
             &Self::PRECOMPOSE_UNICODE,
+            &Self::QUOTE_PATH,
             &Self::REPOSITORY_FORMAT_VERSION,
             &Self::SYMLINKS,
             &Self::TRUST_C_TIME,
@@ -108,6 +117,9 @@ pub type LogAllRefUpdates = keys::Any<validate::LogAllRefUpdates>;
 /// The `core.disambiguate` key.
 pub type Disambiguate = keys::Any<validate::Disambiguate>;
 
+/// The `core.autocrlf` key.
+pub type AutoCrlf = keys::Any<validate::AutoCrlf>;
+
 mod disambiguate {
     use std::borrow::Cow;
 
@@ -168,6 +180,35 @@ mod log_all_ref_updates {
     }
 }
 
+mod auto_crlf {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::core::AutoCrlf};
+
+    impl AutoCrlf {
+        /// Returns the line-ending conversion mode as parsed from `value`. If `value` is not a boolean, `string_on_failure`
+        /// will be called to obtain the key `core.autocrlf` as string instead, which may be `input`.
+        pub fn try_into_autocrlf<'a>(
+            &'static self,
+            value: Option<Result<bool, gix_config::value::Error>>,
+            string_on_failure: impl FnOnce() -> Option<Cow<'a, BStr>>,
+        ) -> Result<Option<gix_filter::eol::AutoCrlf>, config::key::GenericErrorWithValue> {
+            match value.transpose().ok().flatten() {
+                Some(bool) => Ok(Some(if bool {
+                    gix_filter::eol::AutoCrlf::Enabled
+                } else {
+                    gix_filter::eol::AutoCrlf::Disabled
+                })),
+                None => match string_on_failure() {
+                    Some(val) if val.eq_ignore_ascii_case(b"input") => Ok(Some(gix_filter::eol::AutoCrlf::Input)),
+                    Some(val) => Err(config::key::GenericErrorWithValue::from_value(self, val.into_owned())),
+                    None => Ok(None),
+                },
+            }
+        }
+    }
+}
+
 mod check_stat {
     use std::borrow::Cow;
 
@@ -283,6 +324,17 @@ mod validate {
         }
     }
 
+    pub struct AutoCrlf;
+    impl keys::Validate for AutoCrlf {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Core::AUTO_CRLF
+                .try_into_autocrlf(Some(gix_config::Boolean::try_from(value).map(|b| b.0)), || {
+                    Some(value.into())
+                })?;
+            Ok(())
+        }
+    }
+
     pub struct CheckStat;
     impl keys::Validate for CheckStat {
         fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
@@ -10,6 +10,13 @@ impl Credential {
     pub const USERNAME: keys::Any = keys::Any::new("username", &config::Tree::CREDENTIAL);
     /// The `credential.useHttpPath` key.
     pub const USE_HTTP_PATH: keys::Boolean = keys::Boolean::new_boolean("useHttpPath", &config::Tree::CREDENTIAL);
+    /// The `credential.sanitizePrompt` key.
+    pub const SANITIZE_PROMPT: keys::Boolean = keys::Boolean::new_boolean("sanitizePrompt", &config::Tree::CREDENTIAL);
+    /// The `credential.protectProtocol` key.
+    pub const PROTECT_PROTOCOL: keys::Boolean =
+        keys::Boolean::new_boolean("protectProtocol", &config::Tree::CREDENTIAL);
+    /// The `credential.interactive` key, which disables prompting if `false` or `never`.
+    pub const INTERACTIVE: keys::Any = keys::Any::new("interactive", &config::Tree::CREDENTIAL);
 
     /// The `credential.<url>` subsection
     pub const URL_PARAMETER: UrlParameter = UrlParameter;
@@ -25,6 +32,11 @@ impl UrlParameter {
     pub const USERNAME: keys::Any = keys::Any::new("username", &Credential::URL_PARAMETER);
     /// The `credential.<url>.useHttpPath` key.
     pub const USE_HTTP_PATH: keys::Boolean = keys::Boolean::new_boolean("useHttpPath", &Credential::URL_PARAMETER);
+    /// The `credential.<url>.sanitizePrompt` key.
+    pub const SANITIZE_PROMPT: keys::Boolean = keys::Boolean::new_boolean("sanitizePrompt", &Credential::URL_PARAMETER);
+    /// The `credential.<url>.protectProtocol` key.
+    pub const PROTECT_PROTOCOL: keys::Boolean =
+        keys::Boolean::new_boolean("protectProtocol", &Credential::URL_PARAMETER);
 }
 
 impl Section for UrlParameter {
@@ -33,7 +45,13 @@ impl Section for UrlParameter {
     }
 
     fn keys(&self) -> &[&dyn Key] {
-        &[&Self::HELPER, &Self::USERNAME, &Self::USE_HTTP_PATH]
+        &[
+            &Self::HELPER,
+            &Self::USERNAME,
+            &Self::USE_HTTP_PATH,
+            &Self::SANITIZE_PROMPT,
+            &Self::PROTECT_PROTOCOL,
+        ]
     }
 
     fn parent(&self) -> Option<&dyn Section> {
@@ -47,7 +65,14 @@ impl Section for Credential {
     }
 
     fn keys(&self) -> &[&dyn Key] {
-        &[&Self::HELPER, &Self::USERNAME, &Self::USE_HTTP_PATH]
+        &[
+            &Self::HELPER,
+            &Self::USERNAME,
+            &Self::USE_HTTP_PATH,
+            &Self::SANITIZE_PROMPT,
+            &Self::PROTECT_PROTOCOL,
+            &Self::INTERACTIVE,
+        ]
     }
 
     fn sub_sections(&self) -> &[&dyn Section] {
@@ -1,12 +1,24 @@
 use crate::{
     config,
-    config::tree::{keys, Diff, Key, Section},
+    config::tree::{keys, Diff, Key, Section, SubSectionRequirement},
 };
 
+const DRIVER_PARAMETER: Option<SubSectionRequirement> = Some(SubSectionRequirement::Parameter("driver"));
+
 impl Diff {
     /// The `diff.algorithm` key.
-    pub const ALGORITHM: Algorithm = Algorithm::new_with_validate("algorithm", &config::Tree::DIFF, validate::Algorithm)
-                                        .with_deviation("'patience' diff is not implemented and can default to 'histogram' if lenient config is used, and defaults to histogram if unset for fastest and best results");
+    pub const ALGORITHM: Algorithm =
+        Algorithm::new_with_validate("algorithm", &config::Tree::DIFF, validate::Algorithm);
+    /// The `diff.context` key.
+    pub const CONTEXT: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("context", &config::Tree::DIFF);
+    /// The `diff.interHunkContext` key.
+    pub const INTER_HUNK_CONTEXT: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("interHunkContext", &config::Tree::DIFF);
+    /// The `diff.mnemonicPrefix` key.
+    pub const MNEMONIC_PREFIX: keys::Boolean = keys::Boolean::new_boolean("mnemonicPrefix", &config::Tree::DIFF);
+    /// The `diff.noprefix` key.
+    pub const NO_PREFIX: keys::Boolean = keys::Boolean::new_boolean("noprefix", &config::Tree::DIFF);
     /// The `diff.renameLimit` key.
     pub const RENAME_LIMIT: keys::UnsignedInteger = keys::UnsignedInteger::new_unsigned_integer(
         "renameLimit",
@@ -17,6 +29,27 @@ impl Diff {
     );
     /// The `diff.renames` key.
     pub const RENAMES: Renames = Renames::new_renames("renames", &config::Tree::DIFF);
+    /// The `diff.wordRegex` key.
+    pub const WORD_REGEX: keys::String = keys::String::new_string("wordRegex", &config::Tree::DIFF)
+        .with_deviation("only used if the `regex` feature is enabled, otherwise words are separated by whitespace");
+
+    /// The `diff.<driver>.wordRegex` key.
+    pub const DRIVER_WORD_REGEX: keys::String = keys::String::new_string("wordRegex", &config::Tree::DIFF)
+        .with_subsection_requirement(DRIVER_PARAMETER)
+        .with_deviation("only used if the `regex` feature is enabled, otherwise words are separated by whitespace");
+    /// The `diff.<driver>.xfuncname` key.
+    pub const DRIVER_XFUNCNAME: keys::String = keys::String::new_string("xfuncname", &config::Tree::DIFF)
+        .with_subsection_requirement(DRIVER_PARAMETER)
+        .with_deviation("only used if the `regex` feature is enabled, otherwise the default heuristic to find function names is used");
+    /// The `diff.<driver>.textconv` key.
+    pub const DRIVER_TEXTCONV: keys::Program =
+        keys::Program::new_program("textconv", &config::Tree::DIFF).with_subsection_requirement(DRIVER_PARAMETER);
+    /// The `diff.<driver>.cachetextconv` key.
+    pub const DRIVER_CACHE_TEXTCONV: keys::Boolean =
+        keys::Boolean::new_boolean("cachetextconv", &config::Tree::DIFF).with_subsection_requirement(DRIVER_PARAMETER);
+    /// The `diff.<driver>.binary` key.
+    pub const DRIVER_BINARY: keys::Boolean =
+        keys::Boolean::new_boolean("binary", &config::Tree::DIFF).with_subsection_requirement(DRIVER_PARAMETER);
 }
 
 impl Section for Diff {
@@ -25,7 +58,21 @@ impl Section for Diff {
     }
 
     fn keys(&self) -> &[&dyn Key] {
-        &[&Self::ALGORITHM, &Self::RENAME_LIMIT, &Self::RENAMES]
+        &[
+            &Self::ALGORITHM,
+            &Self::CONTEXT,
+            &Self::INTER_HUNK_CONTEXT,
+            &Self::MNEMONIC_PREFIX,
+            &Self::NO_PREFIX,
+            &Self::RENAME_LIMIT,
+            &Self::RENAMES,
+            &Self::WORD_REGEX,
+            &Self::DRIVER_WORD_REGEX,
+            &Self::DRIVER_XFUNCNAME,
+            &Self::DRIVER_TEXTCONV,
+            &Self::DRIVER_CACHE_TEXTCONV,
+            &Self::DRIVER_BINARY,
+        ]
     }
 }
 
@@ -40,7 +87,6 @@ mod algorithm {
 
     use crate::{
         bstr::BStr,
-        config,
         config::{diff::algorithm::Error, tree::sections::diff::Algorithm},
     };
 
@@ -54,9 +100,7 @@ mod algorithm {
             } else if name.eq_ignore_ascii_case(b"histogram") {
                 gix_diff::blob::Algorithm::Histogram
             } else if name.eq_ignore_ascii_case(b"patience") {
-                return Err(config::diff::algorithm::Error::Unimplemented {
-                    name: name.into_owned(),
-                });
+                gix_diff::blob::Algorithm::Patience
             } else {
                 return Err(Error::Unknown {
                     name: name.into_owned(),
@@ -0,0 +1,38 @@
+use crate::{
+    config,
+    config::tree::{keys, Filter, Key, Section, SubSectionRequirement},
+};
+
+const DRIVER_PARAMETER: Option<SubSectionRequirement> = Some(SubSectionRequirement::Parameter("driver"));
+
+impl Filter {
+    /// The `filter.<driver>.clean` key.
+    pub const DRIVER_CLEAN: keys::Program =
+        keys::Program::new_program("clean", &config::Tree::FILTER).with_subsection_requirement(DRIVER_PARAMETER);
+    /// The `filter.<driver>.smudge` key.
+    pub const DRIVER_SMUDGE: keys::Program = keys::Program::new_program("smudge", &config::Tree::FILTER)
+        .with_subsection_requirement(DRIVER_PARAMETER)
+        .with_deviation("not yet used when checking out files");
+    /// The `filter.<driver>.process` key.
+    pub const DRIVER_PROCESS: keys::Program = keys::Program::new_program("process", &config::Tree::FILTER)
+        .with_subsection_requirement(DRIVER_PARAMETER)
+        .with_deviation("long-running filter processes aren't supported, only the `clean` program is used");
+    /// The `filter.<driver>.required` key.
+    pub const DRIVER_REQUIRED: keys::Boolean =
+        keys::Boolean::new_boolean("required", &config::Tree::FILTER).with_subsection_requirement(DRIVER_PARAMETER);
+}
+
+impl Section for Filter {
+    fn name(&self) -> &str {
+        "filter"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[
+            &Self::DRIVER_CLEAN,
+            &Self::DRIVER_SMUDGE,
+            &Self::DRIVER_PROCESS,
+            &Self::DRIVER_REQUIRED,
+        ]
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    config,
+    config::tree::{keys, Gc, Key, Section},
+};
+
+impl Gc {
+    /// The `gc.worktreePruneExpire` key.
+    pub const WORKTREE_PRUNE_EXPIRE: Expiry =
+        Expiry::new_with_validate("worktreePruneExpire", &config::Tree::GC, validate::Expiry);
+}
+
+/// A key for dates after which something expires, or the special values `never` and `now`.
+pub type Expiry = keys::Any<validate::Expiry>;
+
+mod expiry {
+    use std::{borrow::Cow, time::SystemTime};
+
+    use crate::{
+        bstr::{BStr, ByteSlice},
+        config,
+        config::tree::sections::gc::Expiry,
+    };
+
+    impl Expiry {
+        /// Convert `value` into the point in time before which items expire, with `now` as reference for relative dates,
+        /// or `None` if they never expire.
+        pub fn try_into_expiry(
+            &'static self,
+            value: Cow<'_, BStr>,
+            now: SystemTime,
+        ) -> Result<Option<SystemTime>, config::time::Error> {
+            match value.as_ref().as_bytes() {
+                b"never" | b"false" => return Ok(None),
+                b"now" | b"all" => return Ok(Some(now)),
+                _ => {}
+            }
+            let time = value
+                .to_str()
+                .map_err(|_| gix_date::parse::Error::InvalidDateString {
+                    input: value.to_string(),
+                })
+                .and_then(|value| gix_date::parse(value, Some(now)))
+                .map_err(|err| {
+                    config::time::Error::from(self)
+                        .with_value(value.into_owned())
+                        .with_source(err)
+                })?;
+            Ok(Some(
+                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(time.seconds_since_unix_epoch.into()),
+            ))
+        }
+    }
+}
+
+impl Section for Gc {
+    fn name(&self) -> &str {
+        "gc"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::WORKTREE_PRUNE_EXPIRE]
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct Expiry;
+
+    impl keys::Validate for Expiry {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Gc::WORKTREE_PRUNE_EXPIRE.try_into_expiry(value.into(), std::time::SystemTime::now())?;
+            Ok(())
+        }
+    }
+}
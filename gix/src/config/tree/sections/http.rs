@@ -45,6 +45,10 @@ impl Http {
     /// The `http.sslCAInfo` key.
     pub const SSL_CA_INFO: keys::Path =
         keys::Path::new_path("sslCAInfo", &config::Tree::HTTP).with_environment_override("GIT_SSL_CAINFO");
+    /// The `http.sslVerify` key.
+    pub const SSL_VERIFY: keys::Boolean = keys::Boolean::new_boolean("sslVerify", &config::Tree::HTTP);
+    /// The `http.cookieFile` key.
+    pub const COOKIE_FILE: keys::Path = keys::Path::new_path("cookieFile", &config::Tree::HTTP);
     /// The `http.schannelCheckRevoke` key.
     pub const SCHANNEL_CHECK_REVOKE: keys::Boolean =
         keys::Boolean::new_boolean("schannelCheckRevoke", &config::Tree::HTTP);
@@ -68,6 +72,8 @@ impl Section for Http {
             &Self::LOW_SPEED_LIMIT,
             &Self::SCHANNEL_USE_SSL_CA_INFO,
             &Self::SSL_CA_INFO,
+            &Self::SSL_VERIFY,
+            &Self::COOKIE_FILE,
             &Self::SCHANNEL_CHECK_REVOKE,
         ]
     }
@@ -5,6 +5,11 @@
 pub struct Author;
 mod author;
 
+/// The `blame` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Blame;
+mod blame;
+
 /// The `branch` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Branch;
@@ -20,6 +25,11 @@ pub mod checkout;
 pub struct Clone;
 mod clone;
 
+/// The `commit` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Commit;
+mod commit;
+
 /// The `committer` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Committer;
@@ -45,11 +55,26 @@ pub mod diff;
 pub struct Extensions;
 pub mod extensions;
 
+/// The `filter` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Filter;
+pub mod filter;
+
+/// The `gc` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Gc;
+pub mod gc;
+
 /// The `gitoxide` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Gitoxide;
 pub mod gitoxide;
 
+/// The `gpg` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Gpg;
+pub mod gpg;
+
 /// The `http` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Http;
@@ -85,6 +110,11 @@ mod safe;
 pub struct Ssh;
 pub mod ssh;
 
+/// The `tag` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Tag;
+mod tag;
+
 /// The `user` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct User;
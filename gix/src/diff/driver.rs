@@ -0,0 +1,292 @@
+//! Diff drivers are selected with the `diff` attribute in `gitattributes` files and configured with `diff.<driver>.*` keys.
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    Repository,
+};
+
+/// The configuration of a diff driver as found in the `diff.<driver>` configuration section.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Driver {
+    /// The name of the driver, as set by the `diff=<name>` attribute.
+    pub name: BString,
+    /// The value of `diff.<driver>.xfuncname`, one extended regular expression per line to find the function name to show in
+    /// hunk headers, with lines prefixed with `!` negating the match.
+    pub xfuncname: Option<BString>,
+    /// The value of `diff.<driver>.wordRegex`, a regular expression describing a word when producing word diffs.
+    pub word_regex: Option<BString>,
+    /// The value of `diff.<driver>.textconv`, a program to run with the path to a temporary file containing the file to diff,
+    /// whose output is diffed instead of the file itself.
+    pub textconv: Option<BString>,
+    /// The value of `diff.<driver>.cachetextconv`, if `true` the output of [`textconv`][Self::textconv] is cached in
+    /// `refs/notes/textconv/<driver>`.
+    pub cache_textconv: bool,
+    /// The value of `diff.<driver>.binary`, which forces files to be treated as binary if `true` or as text if `false`,
+    /// instead of detecting it by their content.
+    pub binary: Option<bool>,
+}
+
+/// Describes how a file should be diffed, as returned by [`Resolver::diffable()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diffable<'a> {
+    /// The file is text, either originally or after it was converted with `textconv`, and this is the data to diff.
+    Text(std::borrow::Cow<'a, [u8]>),
+    /// The file is binary, so its lines can't be diffed.
+    Binary,
+}
+
+/// Where to read `.gitattributes` files from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeSource {
+    /// Read `.gitattributes` files from the working tree, which is what `git diff` does.
+    WorkTree,
+    /// Read `.gitattributes` files from the tree with the given id, similar to `git diff --attr-source=<tree>`.
+    ///
+    /// The id may also point to a commit, which is peeled to its tree.
+    Tree(ObjectId),
+}
+
+/// A utility to find the diff [`Driver`] for paths in the repository, returned by [`Repository::diff_drivers()`].
+///
+/// `.gitattributes` files are read lazily and cached, making it cheap to query many paths.
+pub struct Resolver<'repo> {
+    repo: &'repo Repository,
+    source: Option<AttributeSource>,
+    case: gix_glob::pattern::Case,
+    globals: gix_attributes::Search,
+    collection: gix_attributes::search::MetadataCollection,
+    info_attributes: Option<(PathBuf, Vec<u8>)>,
+    /// The data of `.gitattributes` files by the directory that contains them, or `None` if there is no such file.
+    files_by_directory: HashMap<BString, Option<Vec<u8>>>,
+    drivers: HashMap<BString, Driver>,
+}
+
+///
+pub mod init {
+    /// The error returned by [`Repository::diff_drivers()`][crate::Repository::diff_drivers()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        AttributeGlobals(#[from] crate::config::attribute_stack::Error),
+        #[error("Could not read attributes at '{}'", path.display())]
+        ReadInfoAttributes {
+            source: std::io::Error,
+            path: std::path::PathBuf,
+        },
+    }
+}
+
+/// The error returned by [`Resolver::driver()`], [`Resolver::attribute()`] and [`Resolver::diffable()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read attributes at '{}'", path.display())]
+    ReadWorktreeAttributes {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToTree(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    Textconv(#[from] crate::diff::textconv::Error),
+}
+
+impl<'repo> Resolver<'repo> {
+    pub(crate) fn new(repo: &'repo Repository) -> Result<Self, init::Error> {
+        let (globals, collection) = repo.config.attribute_globals()?;
+        let info_attributes_path = repo.common_dir().join("info").join("attributes");
+        let info_attributes = match std::fs::read(&info_attributes_path) {
+            Ok(data) => Some((info_attributes_path, data)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(init::Error::ReadInfoAttributes {
+                    source: err,
+                    path: info_attributes_path,
+                })
+            }
+        };
+        Ok(Resolver {
+            repo,
+            source: repo.work_dir().is_some().then_some(AttributeSource::WorkTree),
+            case: if repo.config.ignore_case {
+                gix_glob::pattern::Case::Fold
+            } else {
+                gix_glob::pattern::Case::Sensitive
+            },
+            globals,
+            collection,
+            info_attributes,
+            files_by_directory: Default::default(),
+            drivers: Default::default(),
+        })
+    }
+}
+
+/// Builder
+impl<'repo> Resolver<'repo> {
+    /// Read `.gitattributes` files from `source`, or don't read them at all if `None`, in which case only global
+    /// attributes and those in `$GIT_DIR/info/attributes` are considered.
+    ///
+    /// By default, `.gitattributes` files are read from the working tree, or not at all in bare repositories.
+    pub fn with_attribute_source(mut self, source: Option<AttributeSource>) -> Self {
+        self.source = source;
+        self.files_by_directory.clear();
+        self
+    }
+}
+
+/// Access
+impl<'repo> Resolver<'repo> {
+    /// Return the state of the `diff` attribute for the repository-relative, slash-separated `path`.
+    pub fn attribute(&mut self, path: &BStr) -> Result<gix_attributes::State, Error> {
+        Ok(self.attributes(path, &["diff"])?.pop().expect("one state per name"))
+    }
+
+    /// Return the states of the attributes with the given `names` for the repository-relative, slash-separated `path`,
+    /// in the order of `names`.
+    pub(crate) fn attributes(&mut self, path: &BStr, names: &[&str]) -> Result<Vec<gix_attributes::State>, Error> {
+        let mut search = self.globals.clone();
+        let mut collection = self.collection.clone();
+        for directory_end in std::iter::once(0).chain(path.find_iter("/")) {
+            let directory = path[..directory_end].as_bstr();
+            if let Some(data) = self.gitattributes_in(directory)? {
+                let source = gix_path::from_bstr(directory).join(".gitattributes");
+                search.add_patterns_buffer(data, source, Some(Path::new("")), &mut collection);
+            }
+        }
+        if let Some((source, data)) = &self.info_attributes {
+            search.add_patterns_buffer(data, source.to_owned(), None, &mut collection);
+        }
+
+        let mut out = gix_attributes::search::Outcome::default();
+        out.initialize_with_selection(&collection, names.iter().copied());
+        search.pattern_matching_relative_path(path, self.case, &mut out);
+        Ok(out.iter_selected().map(|m| m.assignment.state.clone()).collect())
+    }
+
+    /// Return the driver configured for `path` with the `diff=<driver>` attribute, or `None` if the attribute isn't set to
+    /// a value.
+    ///
+    /// Note that a driver is returned even if no configuration exists for it, in which case none of its settings are set.
+    pub fn driver(&mut self, path: &BStr) -> Result<Option<&Driver>, Error> {
+        match self.attribute(path)? {
+            gix_attributes::State::Value(name) => self.driver_by_name(name.as_ref().as_bstr()).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Determine how the blob with `id` and `data` at the repository-relative, slash-separated `path` should be diffed,
+    /// similar to `git diff`.
+    ///
+    /// Files with the `-diff` or `binary` attributes are binary, and those with the `diff` attribute are always text.
+    /// If a driver is set with `diff=<driver>`, its `textconv` program is used to convert the data into text,
+    /// or it decides with `diff.<driver>.binary` if the file is binary.
+    /// Otherwise, the file is binary if it contains a null byte within the first 8000 bytes, just like in `git`.
+    pub fn diffable<'a>(&mut self, path: &BStr, id: &gix_hash::oid, data: &'a [u8]) -> Result<Diffable<'a>, Error> {
+        let driver = match self.attribute(path)? {
+            gix_attributes::State::Unset => return Ok(Diffable::Binary),
+            gix_attributes::State::Set => return Ok(Diffable::Text(data.into())),
+            gix_attributes::State::Unspecified => None,
+            gix_attributes::State::Value(name) => Some(self.driver_by_name(name.as_ref().as_bstr())?.clone()),
+        };
+        let binary = match driver {
+            Some(driver) => {
+                if let Some(program) = &driver.textconv {
+                    return Ok(Diffable::Text(
+                        crate::diff::textconv::convert(self.repo, &driver, program.as_bstr(), path, id, data)?.into(),
+                    ));
+                }
+                driver.binary.unwrap_or_else(|| is_binary(data))
+            }
+            None => is_binary(data),
+        };
+        Ok(if binary {
+            Diffable::Binary
+        } else {
+            Diffable::Text(data.into())
+        })
+    }
+}
+
+/// The amount of bytes `git` looks at to determine if a file is binary.
+const FIRST_FEW_BYTES: usize = 8000;
+
+/// Return `true` if `data` is considered binary, which is the case if there is a null-byte at its beginning, similar to `git`.
+pub(crate) fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(FIRST_FEW_BYTES)].contains(&0)
+}
+
+impl<'repo> Resolver<'repo> {
+    fn driver_by_name(&mut self, name: &BStr) -> Result<&Driver, Error> {
+        use crate::config::{cache::util::ApplyLeniency, tree::Diff};
+        if !self.drivers.contains_key(name) {
+            let repo = self.repo;
+            let config = &repo.config.resolved;
+            let string = |key: &str| config.string("diff", Some(name), key).map(std::borrow::Cow::into_owned);
+            let boolean = |key: &'static crate::config::tree::keys::Boolean| -> Result<Option<bool>, Error> {
+                Ok(config
+                    .boolean("diff", Some(name), key.name)
+                    .map(|value| key.enrich_error(value))
+                    .transpose()
+                    .with_leniency(repo.config.lenient_config)?)
+            };
+            let driver = Driver {
+                name: name.to_owned(),
+                xfuncname: string("xfuncname"),
+                word_regex: string("wordRegex"),
+                textconv: string("textconv"),
+                cache_textconv: boolean(&Diff::DRIVER_CACHE_TEXTCONV)?.unwrap_or(false),
+                binary: boolean(&Diff::DRIVER_BINARY)?,
+            };
+            self.drivers.insert(name.to_owned(), driver);
+        }
+        Ok(&self.drivers[name])
+    }
+}
+
+impl<'repo> Resolver<'repo> {
+    fn gitattributes_in(&mut self, directory: &BStr) -> Result<Option<&[u8]>, Error> {
+        if !self.files_by_directory.contains_key(directory) {
+            let data = match self.source {
+                None => None,
+                Some(AttributeSource::WorkTree) => {
+                    let path = self
+                        .repo
+                        .work_dir()
+                        .expect("worktree source is only used if there is a worktree")
+                        .join(gix_path::from_bstr(directory))
+                        .join(".gitattributes");
+                    match std::fs::read(&path) {
+                        Ok(data) => Some(data),
+                        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                        Err(err) => return Err(Error::ReadWorktreeAttributes { source: err, path }),
+                    }
+                }
+                Some(AttributeSource::Tree(id)) => {
+                    let tree = self.repo.find_object(id)?.peel_to_tree()?;
+                    let components = directory
+                        .split_str("/")
+                        .filter(|c| !c.is_empty())
+                        .chain(Some(b".gitattributes".as_slice()));
+                    match tree.lookup_entry(components)? {
+                        Some(entry) if entry.mode().is_blob() => Some(entry.object()?.detach().data),
+                        _ => None,
+                    }
+                }
+            };
+            self.files_by_directory.insert(directory.to_owned(), data);
+        }
+        Ok(self.files_by_directory[directory].as_deref())
+    }
+}
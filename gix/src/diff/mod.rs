@@ -15,3 +15,12 @@ pub mod rename {
         RenamesAndCopies,
     }
 }
+
+///
+pub mod driver;
+
+///
+pub mod patch;
+
+///
+pub mod textconv;
@@ -0,0 +1,124 @@
+#![allow(clippy::result_large_err)]
+use crate::bstr::BStr;
+#[cfg(feature = "regex")]
+use crate::bstr::{BString, ByteSlice};
+
+/// The maximum amount of bytes of a function name shown in a hunk header, like in `git`.
+const MAX_LEN: usize = 80;
+
+/// A way to find the line which contains the name of the function a hunk is in.
+pub(crate) enum Matcher {
+    /// Use `git`'s default heuristic, which picks lines starting with a letter, an underscore or a dollar sign.
+    Default,
+    /// Use the regular expressions configured with `diff.<driver>.xfuncname`, each one with a flag to tell if it negates
+    /// a match.
+    #[cfg(feature = "regex")]
+    Patterns(Vec<(regex::bytes::Regex, bool)>),
+}
+
+impl Matcher {
+    /// Create a new instance from the `xfuncname` value of a diff driver, if there is one.
+    #[cfg(feature = "regex")]
+    pub fn new(xfuncname: Option<&BStr>, driver: &BStr) -> Result<Self, super::Error> {
+        let xfuncname = match xfuncname {
+            Some(value) => value,
+            None => return Ok(Matcher::Default),
+        };
+        let patterns = xfuncname
+            .lines()
+            .map(|line| {
+                let (line, negate) = match line.strip_prefix(b"!") {
+                    Some(line) => (line, true),
+                    None => (line, false),
+                };
+                regex::bytes::RegexBuilder::new(line.to_str_lossy().as_ref())
+                    .unicode(false)
+                    .build()
+                    .map(|regex| (regex, negate))
+                    .map_err(|err| super::Error::InvalidRegex {
+                        source: err,
+                        pattern: BString::from(line),
+                        key: format!("diff.{driver}.xfuncname"),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Matcher::Patterns(patterns))
+    }
+
+    /// Create a new instance from the `xfuncname` value of a diff driver, which is ignored without the `regex` feature.
+    #[cfg(not(feature = "regex"))]
+    pub fn new(_xfuncname: Option<&BStr>, _driver: &BStr) -> Result<Self, super::Error> {
+        Ok(Matcher::Default)
+    }
+
+    /// Return the function name to show if `line`, including its terminator, is the line that contains it.
+    pub fn find<'a>(&self, line: &'a [u8]) -> Option<&'a [u8]> {
+        let name = match self {
+            Matcher::Default => {
+                let first = *line.first()?;
+                if !(first.is_ascii_alphabetic() || first == b'_' || first == b'$') {
+                    return None;
+                }
+                line
+            }
+            #[cfg(feature = "regex")]
+            Matcher::Patterns(patterns) => {
+                let line = line
+                    .strip_suffix(b"\r\n")
+                    .or_else(|| line.strip_suffix(b"\n"))
+                    .unwrap_or(line);
+                let captures = patterns
+                    .iter()
+                    .find_map(|(regex, negate)| regex.captures(line).map(|captures| (!negate).then_some(captures)))??;
+                captures
+                    .get(1)
+                    .or_else(|| captures.get(0))
+                    .expect("always matches")
+                    .as_bytes()
+            }
+        };
+        let mut name = &name[..name.len().min(MAX_LEN)];
+        while let Some((last, rest)) = name.split_last() {
+            if !is_space(*last) {
+                break;
+            }
+            name = rest;
+        }
+        Some(name)
+    }
+}
+
+/// Like `isspace()` in C.
+pub(crate) fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Matcher;
+
+    #[test]
+    fn default_heuristic() {
+        let m = Matcher::Default;
+        assert_eq!(m.find(b"fn main() {  \n"), Some(b"fn main() {".as_slice()));
+        assert_eq!(m.find(b"_private:\n"), Some(b"_private:".as_slice()));
+        assert_eq!(m.find(b"    indented\n"), None);
+        assert_eq!(m.find(b"\n"), None);
+        assert_eq!(
+            m.find(&[b'a'; 100]).map(<[u8]>::len),
+            Some(80),
+            "function names are truncated"
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn patterns_with_negation_and_capture_groups() -> Result<(), Box<dyn std::error::Error>> {
+        let m = Matcher::new(Some("!^fn test\n^fn ([a-z_]+)".into()), "rust".into())?;
+        assert_eq!(m.find(b"fn test_it() {\n"), None, "negations prevent a match");
+        assert_eq!(m.find(b"fn main() {\n"), Some(b"main".as_slice()));
+        assert_eq!(m.find(b"struct Foo;\n"), None);
+        assert!(Matcher::new(Some("(unclosed".into()), "rust".into()).is_err());
+        Ok(())
+    }
+}
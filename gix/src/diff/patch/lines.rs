@@ -0,0 +1,132 @@
+use std::ops::Range;
+
+use gix_diff::blob::{intern::InternedInput, sources::byte_lines_with_terminator};
+
+/// A contiguous range of changed lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Change {
+    /// The lines in the old version of the file.
+    pub before: Range<u32>,
+    /// The lines in the new version of the file.
+    pub after: Range<u32>,
+}
+
+/// The result of a line diff, with lines including their terminator.
+pub(crate) struct Diff<'a> {
+    pub before: Vec<&'a [u8]>,
+    pub after: Vec<&'a [u8]>,
+    pub changes: Vec<Change>,
+}
+
+impl<'a> Diff<'a> {
+    pub fn compute(algorithm: gix_diff::blob::Algorithm, old: &'a [u8], new: &'a [u8]) -> Self {
+        let input = InternedInput::new(byte_lines_with_terminator(old), byte_lines_with_terminator(new));
+        let mut changes = Vec::new();
+        gix_diff::blob::diff(algorithm, &input, |before: Range<u32>, after: Range<u32>| {
+            changes.push(Change { before, after });
+        });
+        changes.sort_by_key(|change| change.before.start);
+        Diff {
+            before: input.before.iter().map(|token| input.interner[*token]).collect(),
+            after: input.after.iter().map(|token| input.interner[*token]).collect(),
+            changes,
+        }
+    }
+
+    /// Return the amount of `(inserted, removed)` lines.
+    pub fn counts(&self) -> (u32, u32) {
+        self.changes.iter().fold((0, 0), |(insertions, removals), change| {
+            (
+                insertions + change.after.len() as u32,
+                removals + change.before.len() as u32,
+            )
+        })
+    }
+
+    /// Return the amount of bytes in the old version of the file which are part of unchanged lines.
+    pub fn unchanged_bytes_before(&self) -> usize {
+        let removed: usize = self
+            .changes
+            .iter()
+            .flat_map(|change| &self.before[change.before.start as usize..change.before.end as usize])
+            .map(|line| line.len())
+            .sum();
+        self.before.iter().map(|line| line.len()).sum::<usize>() - removed
+    }
+
+    /// Group our changes into hunks so that each hunk shows `context_lines` of unchanged lines around its changes, and
+    /// so that changes which are separated by no more than twice the context plus `inter_hunk_context` are merged into one hunk.
+    pub fn hunks(&self, context_lines: u32, inter_hunk_context: u32) -> Vec<Hunk<'_>> {
+        let max_gap = 2 * context_lines + inter_hunk_context;
+        let mut hunks = Vec::new();
+        let mut start = 0;
+        for idx in 1..=self.changes.len() {
+            let is_last = idx == self.changes.len();
+            if !is_last && self.changes[idx].before.start - self.changes[idx - 1].before.end <= max_gap {
+                continue;
+            }
+            let changes = &self.changes[start..idx];
+            start = idx;
+            let (first, last) = (&changes[0], &changes[changes.len() - 1]);
+            let leading = context_lines.min(first.before.start).min(first.after.start);
+            let trailing = context_lines
+                .min(self.before.len() as u32 - last.before.end)
+                .min(self.after.len() as u32 - last.after.end);
+            hunks.push(Hunk {
+                before: first.before.start - leading..last.before.end + trailing,
+                after: first.after.start - leading..last.after.end + trailing,
+                changes,
+            });
+        }
+        hunks
+    }
+}
+
+/// A group of changes along with their context lines.
+pub(crate) struct Hunk<'a> {
+    /// The lines of the old version of the file covered by this hunk, including context.
+    pub before: Range<u32>,
+    /// The lines of the new version of the file covered by this hunk, including context.
+    pub after: Range<u32>,
+    /// The changes within this hunk.
+    pub changes: &'a [Change],
+}
+
+/// A line within a hunk.
+pub(crate) enum Line<'a> {
+    Context(&'a [u8]),
+    Removed(&'a [u8]),
+    Added(&'a [u8]),
+}
+
+impl<'a> Hunk<'a> {
+    /// Return all lines of this hunk in order, taking the data from `diff`.
+    pub fn lines<'data>(&self, diff: &Diff<'data>) -> Vec<Line<'data>> {
+        let mut out = Vec::new();
+        let mut cursor = self.before.start;
+        for change in self.changes {
+            out.extend(
+                diff.before[cursor as usize..change.before.start as usize]
+                    .iter()
+                    .map(|line| Line::Context(line)),
+            );
+            out.extend(
+                diff.before[change.before.start as usize..change.before.end as usize]
+                    .iter()
+                    .map(|line| Line::Removed(line)),
+            );
+            out.extend(
+                diff.after[change.after.start as usize..change.after.end as usize]
+                    .iter()
+                    .map(|line| Line::Added(line)),
+            );
+            cursor = change.before.end;
+        }
+        out.extend(
+            diff.before[cursor as usize..self.before.end as usize]
+                .iter()
+                .map(|line| Line::Context(line)),
+        );
+        out
+    }
+}
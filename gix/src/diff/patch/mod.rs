@@ -0,0 +1,279 @@
+//! Produce patches in the unified diff format as written by `git diff`, along with statistics similar to `git diff --stat`.
+use gix_hash::oid;
+
+use crate::{
+    bstr::{BStr, BString},
+    diff::driver,
+    Repository,
+};
+
+/// The prefixes to put in front of the paths of the old and the new version of a file in a patch.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Prefix {
+    /// The prefix for the old version of a file, `a/` by default.
+    pub old: BString,
+    /// The prefix for the new version of a file, `b/` by default.
+    pub new: BString,
+}
+
+impl Default for Prefix {
+    fn default() -> Self {
+        Prefix {
+            old: "a/".into(),
+            new: "b/".into(),
+        }
+    }
+}
+
+impl Prefix {
+    /// Return prefixes which are empty, similar to `git diff --no-prefix`.
+    pub fn none() -> Self {
+        Prefix {
+            old: BString::default(),
+            new: BString::default(),
+        }
+    }
+
+    /// Return the prefixes `git` uses if `diff.mnemonicPrefix` is set, to indicate where the `old` and the `new` version
+    /// of each file are coming from.
+    pub fn mnemonic(old: Side, new: Side) -> Self {
+        Prefix {
+            old: old.mnemonic_prefix().into(),
+            new: new.mnemonic_prefix().into(),
+        }
+    }
+}
+
+/// Describe where a version of a file is coming from, which is used to produce [mnemonic prefixes][Prefix::mnemonic()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// The file is from a commit or a tree.
+    Commit,
+    /// The file is from the index.
+    Index,
+    /// The file is from the working tree.
+    WorkTree,
+    /// The file is an object that was named directly, like `HEAD:file`.
+    Object,
+}
+
+impl Side {
+    fn mnemonic_prefix(&self) -> &'static str {
+        match self {
+            Side::Commit => "c/",
+            Side::Index => "i/",
+            Side::WorkTree => "w/",
+            Side::Object => "o/",
+        }
+    }
+}
+
+/// The way changed words are shown, similar to `git diff --word-diff=<mode>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WordDiff {
+    /// Show removed words as `[-removed-]` and added words as `{+added+}`, inline with unchanged text.
+    Plain,
+    /// A line-based format for consumption by scripts, with each word on its own line prefixed with ` `, `-` or `+`,
+    /// and newlines of the input shown as lines consisting of `~`.
+    Porcelain,
+}
+
+/// Options to control the way patches are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The amount of unchanged lines to show around each change, similar to `git diff -U<n>`.
+    ///
+    /// Defaults to `diff.context` or 3 if unset.
+    pub context_lines: u32,
+    /// The amount of unchanged lines between two changes which may at most be shown to merge both changes into one hunk,
+    /// in addition to twice the amount of `context_lines`.
+    ///
+    /// Defaults to `diff.interHunkContext` or 0 if unset.
+    pub inter_hunk_context: u32,
+    /// The algorithm to use to find changed lines, defaulting to the value of `diff.algorithm`.
+    pub algorithm: gix_diff::blob::Algorithm,
+    /// The prefixes to put in front of the path of each file.
+    ///
+    /// It's empty if `diff.noprefix` is set, and `a/` and `b/` otherwise. See [`Writer::with_sides()`] to let
+    /// `diff.mnemonicPrefix` take effect.
+    pub prefix: Prefix,
+    /// If set, show changes by words instead of by lines.
+    pub word_diff: Option<WordDiff>,
+    /// If `true`, show full object ids in the `index` line of the patch header instead of abbreviating them,
+    /// similar to `git diff --full-index`.
+    pub full_index: bool,
+    /// If `true`, paths with unusual characters are quoted and escaped, which is the default unless `core.quotePath`
+    /// is set to `false`. Control characters, quotes and backslashes always cause paths to be quoted.
+    pub quote_path: bool,
+}
+
+/// A version of a file to produce a patch or statistics for.
+#[derive(Debug, Clone, Copy)]
+pub struct Resource<'a> {
+    /// The path of the file relative to the root of the repository.
+    pub location: &'a BStr,
+    /// The id of the file content, which is shown in the `index` line of the patch header.
+    pub id: &'a oid,
+    /// The mode of the file.
+    pub mode: gix_object::tree::EntryMode,
+    /// The content of the file, the target of a symbolic link, or `Subproject commit <hex-id>\n` for submodules.
+    pub data: &'a [u8],
+}
+
+/// Information about a file that was renamed or copied, to indicate it in the patch header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rewrite {
+    /// If `true`, the new file is a copy of the old one, and a rename otherwise.
+    pub copy: bool,
+}
+
+/// A utility to write patches and to compute diff statistics, returned by [`Repository::diff_patch_writer()`].
+///
+/// Patches are written similar to `git diff`, using the `diff.<driver>.xfuncname` configuration of the diff driver set
+/// with the `diff` attribute in `gitattributes` files to find the function name shown in hunk headers, and
+/// `diff.wordRegex` or `diff.<driver>.wordRegex` to find words when producing [word diffs][Options::word_diff].
+/// Note that regular expressions are only used if the `regex` feature is enabled. Without it, function names are found
+/// with `git`'s default heuristic, and words are separated by whitespace.
+///
+/// Whether files are binary and if they are converted with `diff.<driver>.textconv` before diffing is decided
+/// by [`driver::Resolver::diffable()`].
+///
+/// ### Deviation
+///
+/// * The similarity index of rewrites is computed based on the bytes in lines that didn't change, which is
+///   less sophisticated than what `git` does and may lead to different values for rewrites with modifications.
+/// * The built-in function name patterns `git` provides for known drivers like `rust` or `cpp` aren't available,
+///   these have to be configured explicitly.
+/// * External diff programs configured with `diff.<driver>.command` are not run.
+/// * Regular expressions use the syntax of the `regex` crate, which is similar to POSIX extended regular expressions
+///   but may yield different matches in some cases.
+pub struct Writer<'repo> {
+    repo: &'repo Repository,
+    drivers: driver::Resolver<'repo>,
+    word_regex: Option<BString>,
+    mnemonic_prefix: bool,
+    /// The options to use when writing patches.
+    pub options: Options,
+}
+
+///
+pub mod init {
+    /// The error returned by [`Repository::diff_patch_writer()`][crate::Repository::diff_patch_writer()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+        #[error(transparent)]
+        ConfigBoolean(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        ConfigUnsignedInteger(#[from] crate::config::unsigned_integer::Error),
+        #[error(transparent)]
+        Drivers(#[from] crate::diff::driver::init::Error),
+    }
+}
+
+/// The error returned when writing patches or computing statistics with a [`Writer`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    Driver(#[from] crate::diff::driver::Error),
+    #[cfg(feature = "regex")]
+    #[error("The regular expression {pattern:?} configured in {key} is invalid")]
+    InvalidRegex {
+        source: regex::Error,
+        pattern: BString,
+        key: String,
+    },
+}
+
+impl<'repo> Writer<'repo> {
+    pub(crate) fn new(repo: &'repo Repository) -> Result<Self, init::Error> {
+        use crate::config::{
+            cache::util::ApplyLeniency,
+            tree::{Core, Diff, Key},
+        };
+        let config = &repo.config.resolved;
+        let lenient = repo.config.lenient_config;
+        let boolean = |key: &'static crate::config::tree::keys::Boolean| -> Result<Option<bool>, init::Error> {
+            Ok(config
+                .boolean_by_key(key.logical_name().as_str())
+                .map(|value| key.enrich_error(value))
+                .transpose()
+                .with_leniency(lenient)?)
+        };
+        let unsigned = |key: &'static crate::config::tree::keys::UnsignedInteger| -> Result<Option<u32>, init::Error> {
+            Ok(config
+                .integer_by_key(key.logical_name().as_str())
+                .map(|value| {
+                    key.try_into_u64(value).and_then(|value| {
+                        value
+                            .try_into()
+                            .map_err(|_| crate::config::unsigned_integer::Error::from(key))
+                    })
+                })
+                .transpose()
+                .with_leniency(lenient)?)
+        };
+
+        let no_prefix = boolean(&Diff::NO_PREFIX)?.unwrap_or(false);
+        let options = Options {
+            context_lines: unsigned(&Diff::CONTEXT)?.unwrap_or(3),
+            inter_hunk_context: unsigned(&Diff::INTER_HUNK_CONTEXT)?.unwrap_or(0),
+            algorithm: repo.config.diff_algorithm()?,
+            prefix: if no_prefix { Prefix::none() } else { Prefix::default() },
+            word_diff: None,
+            full_index: false,
+            quote_path: boolean(&Core::QUOTE_PATH)?.unwrap_or(true),
+        };
+        Ok(Writer {
+            repo,
+            drivers: driver::Resolver::new(repo)?,
+            word_regex: config
+                .string_by_key(Diff::WORD_REGEX.logical_name().as_str())
+                .map(std::borrow::Cow::into_owned),
+            mnemonic_prefix: !no_prefix && boolean(&Diff::MNEMONIC_PREFIX)?.unwrap_or(false),
+            options,
+        })
+    }
+}
+
+/// Builder
+impl<'repo> Writer<'repo> {
+    /// Declare where the `old` and the `new` versions of the files are coming from, which changes the prefixes of paths to
+    /// [mnemonic prefixes][Prefix::mnemonic()] if `diff.mnemonicPrefix` is set.
+    ///
+    /// `git` only does that when comparing against the index or the working tree, so prefixes remain unchanged if both sides
+    /// are [commits][Side::Commit].
+    pub fn with_sides(mut self, old: Side, new: Side) -> Self {
+        if self.mnemonic_prefix && !(old == Side::Commit && new == Side::Commit) {
+            self.options.prefix = Prefix::mnemonic(old, new);
+        }
+        self
+    }
+
+    /// Set the `options` to use when writing patches.
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set the utility to find diff drivers, for instance to configure where to read `.gitattributes` files from.
+    pub fn with_drivers(mut self, drivers: driver::Resolver<'repo>) -> Self {
+        self.drivers = drivers;
+        self
+    }
+}
+
+mod function_name;
+mod lines;
+mod quote;
+///
+pub mod stat;
+mod word;
+mod write;
@@ -0,0 +1,66 @@
+use crate::bstr::{BStr, BString, ByteSlice, ByteVec};
+
+/// Write the concatenation of `prefix` and `path` into a new string, quoting it in C-style if it contains characters
+/// that need escaping, similar to how `git` does it.
+///
+/// If `quote_path` is `true`, bytes outside of the ASCII range also cause the path to be quoted and are written in octal.
+pub(crate) fn path(prefix: &BStr, path: &BStr, quote_path: bool) -> BString {
+    let needs_quoting = |b: u8| b < 0x20 || b == b'"' || b == b'\\' || b == 0x7f || (quote_path && b >= 0x80);
+    let mut out = BString::default();
+    if !prefix.iter().chain(path.iter()).any(|b| needs_quoting(*b)) {
+        out.push_str(prefix);
+        out.push_str(path);
+        return out;
+    }
+    out.push_byte(b'"');
+    for b in prefix.iter().chain(path.iter()).copied() {
+        if !needs_quoting(b) {
+            out.push_byte(b);
+            continue;
+        }
+        out.push_byte(b'\\');
+        match b {
+            b'\x07' => out.push_byte(b'a'),
+            b'\x08' => out.push_byte(b'b'),
+            b'\t' => out.push_byte(b't'),
+            b'\n' => out.push_byte(b'n'),
+            b'\x0b' => out.push_byte(b'v'),
+            b'\x0c' => out.push_byte(b'f'),
+            b'\r' => out.push_byte(b'r'),
+            b'"' | b'\\' => out.push_byte(b),
+            _ => out.push_str(format!("{b:03o}")),
+        }
+    }
+    out.push_byte(b'"');
+    out
+}
+
+/// Like [`path()`], but for a path without prefix.
+pub(crate) fn plain(path: &BStr, quote_path: bool) -> BString {
+    self::path("".into(), path, quote_path)
+}
+
+/// Return `true` if `path` would be quoted.
+pub(crate) fn is_needed(path: &BStr, quote_path: bool) -> bool {
+    plain(path, quote_path).as_bytes() != path.as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn path() {
+        assert_eq!(super::path("a/".into(), "plain".into(), true), "a/plain");
+        assert_eq!(super::path("a/".into(), "with space".into(), true), "a/with space");
+        assert_eq!(
+            super::path("a/".into(), "été".into(), true),
+            r#""a/\303\251t\303\251""#,
+            "non-ascii bytes are written in octal"
+        );
+        assert_eq!(super::path("a/".into(), "été".into(), false), "a/été");
+        assert_eq!(
+            super::path("".into(), "tab\there\"\\".into(), false),
+            r#""tab\there\"\\""#,
+            "control characters and quotes are always escaped"
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Statistics about changed files, as shown by `git diff --stat`, `git diff --numstat` and `git diff --shortstat`.
+use std::io::Write;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    diff::patch::quote,
+};
+
+/// The amount of change in a single file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Changes {
+    /// The file is a text file with lines that were changed.
+    Lines {
+        /// The amount of added lines.
+        insertions: u32,
+        /// The amount of removed lines.
+        deletions: u32,
+    },
+    /// The file is binary, so only its size can be shown.
+    ///
+    /// Both sizes are 0 if the content didn't change.
+    Binary {
+        /// The size of the old version of the file in bytes.
+        old_size: u64,
+        /// The size of the new version of the file in bytes.
+        new_size: u64,
+    },
+}
+
+/// Statistics for a single changed file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct File {
+    /// The location of the file in the old version, if it was renamed or copied.
+    pub source_location: Option<BString>,
+    /// The location of the file.
+    pub location: BString,
+    /// The amount of change.
+    pub changes: Changes,
+}
+
+impl File {
+    /// Return the name to display for this file, which indicates renames or copies as `old => new` with common leading and
+    /// trailing path components written only once, like `dir/{old => new}/file`.
+    pub fn display_name(&self, quote_path: bool) -> BString {
+        match &self.source_location {
+            Some(source) => rename(source.as_bstr(), self.location.as_bstr(), quote_path),
+            None => quote::plain(self.location.as_bstr(), quote_path),
+        }
+    }
+}
+
+/// The sum of all changes across multiple files.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Totals {
+    /// The amount of changed files.
+    pub files: usize,
+    /// The amount of added lines in text files.
+    pub insertions: u64,
+    /// The amount of removed lines in text files.
+    pub deletions: u64,
+}
+
+/// A collection of statistics for changed files, obtained from [`Writer::stats()`][super::Writer::stats()], which
+/// can be written in various formats.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stats {
+    /// The statistics of each file, in the order they should be shown in.
+    pub files: Vec<File>,
+    /// If `true`, paths with unusual characters are quoted.
+    pub quote_path: bool,
+}
+
+impl Stats {
+    /// Return the sum of changes across all files.
+    pub fn totals(&self) -> Totals {
+        self.files.iter().fold(Totals::default(), |mut totals, file| {
+            totals.files += 1;
+            if let Changes::Lines { insertions, deletions } = file.changes {
+                totals.insertions += u64::from(insertions);
+                totals.deletions += u64::from(deletions);
+            }
+            totals
+        })
+    }
+
+    /// Write one line per file with the amount of added and removed lines, separated by tabs, similar to `git diff --numstat`.
+    /// Binary files show `-` instead of line counts.
+    pub fn write_numstat(&self, mut out: impl Write) -> std::io::Result<()> {
+        for file in &self.files {
+            match file.changes {
+                Changes::Lines { insertions, deletions } => write!(out, "{insertions}\t{deletions}\t")?,
+                Changes::Binary { .. } => out.write_all(b"-\t-\t")?,
+            }
+            out.write_all(&file.display_name(self.quote_path))?;
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Write a line summarizing the [totals][Self::totals()], similar to `git diff --shortstat`.
+    pub fn write_summary(&self, mut out: impl Write) -> std::io::Result<()> {
+        let Totals {
+            files,
+            insertions,
+            deletions,
+        } = self.totals();
+        if files == 0 {
+            return out.write_all(b" 0 files changed\n");
+        }
+        write!(out, " {files} file{} changed", if files == 1 { "" } else { "s" })?;
+        if insertions != 0 || deletions == 0 {
+            write!(
+                out,
+                ", {insertions} insertion{}(+)",
+                if insertions == 1 { "" } else { "s" }
+            )?;
+        }
+        if deletions != 0 || insertions == 0 {
+            write!(
+                out,
+                ", {deletions} deletion{}(-)",
+                if deletions == 1 { "" } else { "s" }
+            )?;
+        }
+        out.write_all(b"\n")
+    }
+
+    /// Write one line per file with its name, the amount of changed lines and a graph of `+` and `-` characters scaled
+    /// to fit into `width` columns, followed by [a summary][Self::write_summary()], similar to `git diff --stat=<width>`.
+    ///
+    /// `git` uses a `width` of 80 unless the output is a terminal.
+    pub fn write_stat(&self, mut out: impl Write, width: usize) -> std::io::Result<()> {
+        let names: Vec<_> = self.files.iter().map(|f| f.display_name(self.quote_path)).collect();
+        let mut max_len = 0;
+        let mut max_change = 0;
+        let mut number_width = 0;
+        let mut bin_width = 0;
+        for (file, name) in self.files.iter().zip(&names) {
+            max_len = max_len.max(display_width(name.as_bstr()));
+            match file.changes {
+                Changes::Binary { old_size, new_size } => {
+                    bin_width = bin_width.max(14 + decimal_width(old_size) + decimal_width(new_size));
+                    number_width = 3;
+                }
+                Changes::Lines { insertions, deletions } => {
+                    max_change = max_change.max(u64::from(insertions) + u64::from(deletions));
+                }
+            }
+        }
+        number_width = number_width.max(decimal_width(max_change));
+
+        // This layout logic follows `show_stats()` in git's `diff.c`.
+        let width = width.max(16 + 6 + number_width);
+        let mut graph_width = if max_change as usize + 4 > bin_width {
+            max_change as usize
+        } else {
+            bin_width - 4
+        };
+        let mut name_width = max_len;
+        if name_width + number_width + 6 + graph_width > width {
+            if graph_width > (width * 3 / 8).saturating_sub(number_width + 6) {
+                graph_width = (width * 3 / 8).saturating_sub(number_width + 6).max(6);
+            }
+            if name_width > width - number_width - 6 - graph_width {
+                name_width = width - number_width - 6 - graph_width;
+            } else {
+                graph_width = width - number_width - 6 - name_width;
+            }
+        }
+
+        for (file, name) in self.files.iter().zip(&names) {
+            let (prefix, name) = truncate_name(name.as_bstr(), name_width);
+            let padding = name_width
+                .saturating_sub(prefix.len())
+                .saturating_sub(display_width(name));
+            out.write_all(b" ")?;
+            out.write_all(prefix.as_bytes())?;
+            out.write_all(name)?;
+            write!(out, "{:padding$} |", "")?;
+            match file.changes {
+                Changes::Binary { old_size, new_size } => {
+                    write!(out, " {:>number_width$}", "Bin")?;
+                    if old_size != 0 || new_size != 0 {
+                        write!(out, " {old_size} -> {new_size} bytes")?;
+                    }
+                }
+                Changes::Lines { insertions, deletions } => {
+                    let (mut add, mut del) = (u64::from(insertions), u64::from(deletions));
+                    if graph_width as u64 <= max_change {
+                        let mut total = scale_linear(add + del, graph_width as u64, max_change);
+                        if total < 2 && add != 0 && del != 0 {
+                            total = 2;
+                        }
+                        if add < del {
+                            add = scale_linear(add, graph_width as u64, max_change);
+                            del = total - add;
+                        } else {
+                            del = scale_linear(del, graph_width as u64, max_change);
+                            add = total - del;
+                        }
+                    }
+                    let changed = u64::from(insertions) + u64::from(deletions);
+                    write!(out, " {changed:>number_width$}{}", if changed != 0 { " " } else { "" })?;
+                    out.write_all("+".repeat(add as usize).as_bytes())?;
+                    out.write_all("-".repeat(del as usize).as_bytes())?;
+                }
+            }
+            out.write_all(b"\n")?;
+        }
+        self.write_summary(out)
+    }
+}
+
+/// Scale `it` linearly so that `max_change` fits into `width`, while making sure that anything non-zero remains visible.
+fn scale_linear(it: u64, width: u64, max_change: u64) -> u64 {
+    if it == 0 {
+        return 0;
+    }
+    1 + (it * (width.saturating_sub(1)) / max_change)
+}
+
+fn decimal_width(mut n: u64) -> usize {
+    let mut width = 1;
+    while n >= 10 {
+        n /= 10;
+        width += 1;
+    }
+    width
+}
+
+/// Return the amount of columns needed to display `name`.
+fn display_width(name: &BStr) -> usize {
+    name.chars().count()
+}
+
+/// Shorten `name` to fit into `width` columns by cutting it at the front, and return the prefix to show in front of it
+/// to indicate it was shortened.
+fn truncate_name(name: &BStr, width: usize) -> (&'static str, &BStr) {
+    if display_width(name) <= width {
+        return ("", name);
+    }
+    let len = width.saturating_sub(3);
+    let mut name = name;
+    while display_width(name) > len {
+        let skip = name.char_indices().nth(1).map_or(name.len(), |(start, _, _)| start);
+        name = name[skip..].as_bstr();
+    }
+    if let Some(slash) = name.find_byte(b'/') {
+        name = name[slash..].as_bstr();
+    }
+    ("...", name)
+}
+
+/// Produce a name like `a/{b => c}/d` to show that `old` was renamed to `new`, similar to `pprint_rename()` in `git`.
+fn rename(old: &BStr, new: &BStr, quote_path: bool) -> BString {
+    if quote::is_needed(old, quote_path) || quote::is_needed(new, quote_path) {
+        let mut out = quote::plain(old, quote_path);
+        out.push_str(" => ");
+        out.push_str(quote::plain(new, quote_path));
+        return out;
+    }
+
+    let prefix_len = old
+        .iter()
+        .zip(new.iter())
+        .enumerate()
+        .take_while(|(_, (a, b))| a == b)
+        .filter(|(_, (a, _))| **a == b'/')
+        .last()
+        .map_or(0, |(pos, _)| pos + 1);
+
+    // The common suffix has to start with a slash, and may reuse the slash that ends the common prefix.
+    let min_pos = prefix_len.saturating_sub(1);
+    let mut suffix_len = 0;
+    let (mut a, mut b) = (old.len(), new.len());
+    while a > min_pos && b > min_pos && old[a - 1] == new[b - 1] {
+        a -= 1;
+        b -= 1;
+        if old[a] == b'/' {
+            suffix_len = old.len() - a;
+        }
+    }
+
+    let old_mid = &old[prefix_len..old.len().saturating_sub(suffix_len).max(prefix_len)];
+    let new_mid = &new[prefix_len..new.len().saturating_sub(suffix_len).max(prefix_len)];
+    let mut out = BString::default();
+    let has_common_parts = prefix_len + suffix_len != 0;
+    if has_common_parts {
+        out.push_str(&old[..prefix_len]);
+        out.push_byte(b'{');
+    }
+    out.push_str(old_mid);
+    out.push_str(" => ");
+    out.push_str(new_mid);
+    if has_common_parts {
+        out.push_byte(b'}');
+        out.push_str(&old[old.len() - suffix_len..]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn rename() {
+        for (old, new, expected) in [
+            ("a.txt", "b.txt", "a.txt => b.txt"),
+            ("dir/a.txt", "dir/b.txt", "dir/{a.txt => b.txt}"),
+            ("a/file", "b/file", "{a => b}/file"),
+            ("dir/a/file", "dir/b/file", "dir/{a => b}/file"),
+            ("dir/file", "dir/sub/file", "dir/{ => sub}/file"),
+            ("dir/sub/file", "dir/file", "dir/{sub => }/file"),
+        ] {
+            assert_eq!(super::rename(old.into(), new.into(), true), expected, "{old} -> {new}");
+        }
+    }
+
+    #[test]
+    fn truncate_name() {
+        assert_eq!(super::truncate_name("short".into(), 10), ("", "short".into()));
+        assert_eq!(
+            super::truncate_name("some/very/long/path".into(), 12),
+            ("...", "/path".into()),
+            "names are cut at the next slash if possible"
+        );
+    }
+}
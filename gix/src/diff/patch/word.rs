@@ -0,0 +1,296 @@
+#![allow(clippy::result_large_err)]
+use std::{io::Write, ops::Range};
+
+#[cfg(feature = "regex")]
+use crate::bstr::{BStr, BString, ByteSlice};
+use crate::diff::patch::{function_name::is_space, WordDiff};
+
+/// The prefix and suffix to put around a run of text.
+struct Style {
+    prefix: &'static str,
+    suffix: &'static str,
+}
+
+/// The way to present words, along with the string to use to represent a newline in the input.
+struct Styles {
+    added: Style,
+    removed: Style,
+    context: Style,
+    newline: &'static str,
+}
+
+impl WordDiff {
+    fn styles(&self) -> Styles {
+        match self {
+            WordDiff::Plain => Styles {
+                added: Style {
+                    prefix: "{+",
+                    suffix: "+}",
+                },
+                removed: Style {
+                    prefix: "[-",
+                    suffix: "-]",
+                },
+                context: Style { prefix: "", suffix: "" },
+                newline: "\n",
+            },
+            WordDiff::Porcelain => Styles {
+                added: Style {
+                    prefix: "+",
+                    suffix: "\n",
+                },
+                removed: Style {
+                    prefix: "-",
+                    suffix: "\n",
+                },
+                context: Style {
+                    prefix: " ",
+                    suffix: "\n",
+                },
+                newline: "~\n",
+            },
+        }
+    }
+}
+
+/// A way to split text into words.
+pub(crate) enum Splitter {
+    /// Words are separated by whitespace.
+    Whitespace,
+    /// Words are matched by a regular expression.
+    #[cfg(feature = "regex")]
+    Regex(regex::bytes::Regex),
+}
+
+impl Splitter {
+    #[cfg(feature = "regex")]
+    pub fn new(word_regex: Option<&BStr>, key: impl FnOnce() -> String) -> Result<Self, super::Error> {
+        Ok(match word_regex {
+            None => Splitter::Whitespace,
+            Some(pattern) => Splitter::Regex(
+                regex::bytes::RegexBuilder::new(pattern.to_str_lossy().as_ref())
+                    .unicode(false)
+                    .multi_line(true)
+                    .build()
+                    .map_err(|err| super::Error::InvalidRegex {
+                        source: err,
+                        pattern: BString::from(pattern),
+                        key: key(),
+                    })?,
+            ),
+        })
+    }
+
+    /// Return the byte ranges of all words in `text`.
+    fn words(&self, text: &[u8]) -> Vec<Range<usize>> {
+        let mut out = Vec::new();
+        let mut pos = 0;
+        match self {
+            Splitter::Whitespace => loop {
+                while pos < text.len() && is_space(text[pos]) {
+                    pos += 1;
+                }
+                if pos >= text.len() {
+                    break;
+                }
+                let start = pos;
+                while pos < text.len() && !is_space(text[pos]) {
+                    pos += 1;
+                }
+                out.push(start..pos);
+            },
+            #[cfg(feature = "regex")]
+            Splitter::Regex(regex) => {
+                while pos < text.len() {
+                    let m = match regex.find_at(text, pos) {
+                        Some(m) => m,
+                        None => break,
+                    };
+                    // Like `git`, words never span multiple lines, and an empty match ends the search.
+                    let end = text[m.start()..m.end()]
+                        .find_byte(b'\n')
+                        .map_or(m.end(), |newline| m.start() + newline);
+                    if m.start() >= end {
+                        break;
+                    }
+                    out.push(m.start()..end);
+                    pos = end;
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Collects removed and added lines of consecutive changes to show them as word diff.
+pub(crate) struct Buffer {
+    removed: Vec<u8>,
+    added: Vec<u8>,
+}
+
+impl Buffer {
+    pub fn new() -> Self {
+        Buffer {
+            removed: Vec::new(),
+            added: Vec::new(),
+        }
+    }
+
+    pub fn push_removed(&mut self, line: &[u8]) {
+        push_line(&mut self.removed, line);
+    }
+
+    pub fn push_added(&mut self, line: &[u8]) {
+        push_line(&mut self.added, line);
+    }
+
+    /// Write a context `line`, after writing all pending changes.
+    pub fn write_context(
+        &mut self,
+        line: &[u8],
+        mode: WordDiff,
+        splitter: &Splitter,
+        out: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        self.flush(mode, splitter, out)?;
+        match mode {
+            WordDiff::Plain => {
+                out.write_all(line)?;
+                if !line.ends_with(b"\n") {
+                    out.write_all(b"\n")?;
+                }
+                Ok(())
+            }
+            WordDiff::Porcelain => {
+                out.write_all(b" ")?;
+                out.write_all(line.strip_suffix(b"\n").unwrap_or(line))?;
+                out.write_all(b"\n~\n")
+            }
+        }
+    }
+
+    /// Write all pending changes by words.
+    pub fn flush(&mut self, mode: WordDiff, splitter: &Splitter, out: &mut dyn Write) -> std::io::Result<()> {
+        if self.removed.is_empty() && self.added.is_empty() {
+            return Ok(());
+        }
+        let styles = mode.styles();
+        if self.added.is_empty() {
+            write_styled(out, &styles.removed, styles.newline, &self.removed)?;
+            self.removed.clear();
+            return Ok(());
+        }
+
+        let (removed_words, added_words) = (splitter.words(&self.removed), splitter.words(&self.added));
+        let mut input = gix_diff::blob::intern::InternedInput::default();
+        input.update_before(removed_words.iter().map(|range| &self.removed[range.clone()]));
+        input.update_after(added_words.iter().map(|range| &self.added[range.clone()]));
+        let mut changes = Vec::new();
+        gix_diff::blob::diff(
+            gix_diff::blob::Algorithm::Myers,
+            &input,
+            |before: Range<u32>, after: Range<u32>| changes.push((before, after)),
+        );
+        changes.sort_by_key(|(before, _)| before.start);
+
+        let span = |words: &[Range<usize>], range: Range<u32>| -> Range<usize> {
+            let (start, end) = (range.start as usize, range.end as usize);
+            if start == end {
+                let pos = start.checked_sub(1).map_or(0, |prev| words[prev].end);
+                pos..pos
+            } else {
+                words[start].start..words[end - 1].end
+            }
+        };
+        let mut added_pos = 0;
+        for (before, after) in changes {
+            let removed = span(&removed_words, before);
+            let added = span(&added_words, after);
+            if added_pos != added.start {
+                write_styled(
+                    out,
+                    &styles.context,
+                    styles.newline,
+                    &self.added[added_pos..added.start],
+                )?;
+            }
+            if !removed.is_empty() {
+                write_styled(out, &styles.removed, styles.newline, &self.removed[removed])?;
+            }
+            if !added.is_empty() {
+                write_styled(out, &styles.added, styles.newline, &self.added[added.clone()])?;
+            }
+            added_pos = added.end;
+        }
+        if added_pos != self.added.len() {
+            write_styled(out, &styles.context, styles.newline, &self.added[added_pos..])?;
+        }
+        self.removed.clear();
+        self.added.clear();
+        Ok(())
+    }
+}
+
+/// Append `line` to `buf`, adding a newline if it's missing as it's the last line of a file, which is what `git` shows
+/// in word diffs.
+fn push_line(buf: &mut Vec<u8>, line: &[u8]) {
+    buf.extend_from_slice(line);
+    if !line.ends_with(b"\n") {
+        buf.push(b'\n');
+    }
+}
+
+/// Write `text` line by line, each non-empty part surrounded by the prefix and suffix of `style`, and each newline
+/// written as `newline`.
+fn write_styled(out: &mut dyn Write, style: &Style, newline: &str, mut text: &[u8]) -> std::io::Result<()> {
+    while !text.is_empty() {
+        let newline_pos = text.iter().position(|b| *b == b'\n');
+        let part = &text[..newline_pos.unwrap_or(text.len())];
+        if !part.is_empty() {
+            out.write_all(style.prefix.as_bytes())?;
+            out.write_all(part)?;
+            out.write_all(style.suffix.as_bytes())?;
+        }
+        match newline_pos {
+            Some(pos) => {
+                out.write_all(newline.as_bytes())?;
+                text = &text[pos + 1..];
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Buffer, Splitter};
+    use crate::diff::patch::WordDiff;
+
+    fn word_diff(removed: &str, added: &str, mode: WordDiff) -> String {
+        let mut buf = Buffer::new();
+        buf.push_removed(removed.as_bytes());
+        buf.push_added(added.as_bytes());
+        let mut out = Vec::new();
+        buf.flush(mode, &Splitter::Whitespace, &mut out).expect("in-memory");
+        String::from_utf8(out).expect("valid input")
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(
+            word_diff("the quick fox\n", "the slow fox\n", WordDiff::Plain),
+            "the [-quick-]{+slow+} fox\n"
+        );
+        assert_eq!(word_diff("gone\n", "", WordDiff::Plain), "[-gone-]\n");
+        assert_eq!(word_diff("", "new words\n", WordDiff::Plain), "{+new words+}\n");
+    }
+
+    #[test]
+    fn porcelain() {
+        assert_eq!(
+            word_diff("the quick fox\n", "the slow fox\n", WordDiff::Porcelain),
+            " the \n-quick\n+slow\n  fox\n~\n"
+        );
+    }
+}
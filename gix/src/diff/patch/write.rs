@@ -0,0 +1,435 @@
+#![allow(clippy::result_large_err)]
+use std::{borrow::Cow, io::Write};
+
+use gix_hash::{oid, ObjectId};
+use gix_object::tree::EntryMode;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    diff::driver::Diffable,
+    diff::patch::{function_name, lines, quote, stat, word, Error, Resource, Rewrite, WordDiff, Writer},
+    ext::ObjectIdExt,
+    object::tree::diff::{change::Event, Change},
+};
+
+/// A version of a file whose data was loaded from the object database.
+struct Loaded {
+    location: BString,
+    id: ObjectId,
+    mode: EntryMode,
+    data: Vec<u8>,
+}
+
+impl Loaded {
+    fn as_resource(&self) -> Resource<'_> {
+        Resource {
+            location: self.location.as_bstr(),
+            id: &self.id,
+            mode: self.mode,
+            data: &self.data,
+        }
+    }
+}
+
+/// The old and new version of a file, along with information about a rewrite.
+type LoadedChange = (Option<Loaded>, Option<Loaded>, Option<Rewrite>);
+
+/// Changes
+impl<'repo> Writer<'repo> {
+    /// Write the patch for `change` to `out`, similar to `git diff`. Nothing is written for changes to trees.
+    ///
+    /// Note that [tracking of paths][crate::object::tree::diff::Platform::track_path()] must be enabled for the `change`
+    /// to have a location.
+    pub fn write_change(&mut self, change: &Change<'_, '_, '_>, mut out: impl Write) -> Result<(), Error> {
+        let (old, new, rewrite) = self.load(change)?;
+        self.write_to(
+            old.as_ref().map(Loaded::as_resource),
+            new.as_ref().map(Loaded::as_resource),
+            rewrite,
+            &mut out,
+        )
+    }
+
+    /// Compute statistics for `change`, or return `None` if it's a change to a tree.
+    ///
+    /// Note that [tracking of paths][crate::object::tree::diff::Platform::track_path()] must be enabled for the `change`
+    /// to have a location.
+    pub fn stat_change(&mut self, change: &Change<'_, '_, '_>) -> Result<Option<stat::File>, Error> {
+        let (old, new, rewrite) = self.load(change)?;
+        if old.is_none() && new.is_none() {
+            return Ok(None);
+        }
+        self.stat(
+            old.as_ref().map(Loaded::as_resource),
+            new.as_ref().map(Loaded::as_resource),
+            rewrite,
+        )
+        .map(Some)
+    }
+
+    /// Return an empty collection of statistics, to be filled with the results of [`stat_change()`][Self::stat_change()]
+    /// or [`stat()`][Self::stat()].
+    pub fn stats(&self) -> stat::Stats {
+        stat::Stats {
+            files: Vec::new(),
+            quote_path: self.options.quote_path,
+        }
+    }
+
+    fn load(&self, change: &Change<'_, '_, '_>) -> Result<LoadedChange, Error> {
+        let load = |location: &BStr, id: &oid, mode: EntryMode| -> Result<Option<Loaded>, Error> {
+            let data = match mode {
+                EntryMode::Tree => return Ok(None),
+                EntryMode::Commit => format!("Subproject commit {id}\n").into_bytes(),
+                EntryMode::Blob | EntryMode::BlobExecutable | EntryMode::Link => {
+                    self.repo.find_object(id)?.detach().data
+                }
+            };
+            Ok(Some(Loaded {
+                location: location.to_owned(),
+                id: id.to_owned(),
+                mode,
+                data,
+            }))
+        };
+        Ok(match change.event {
+            Event::Addition { entry_mode, id } => (None, load(change.location, &id, entry_mode)?, None),
+            Event::Deletion { entry_mode, id } => (load(change.location, &id, entry_mode)?, None, None),
+            Event::Modification {
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+            } => (
+                load(change.location, &previous_id, previous_entry_mode)?,
+                load(change.location, &id, entry_mode)?,
+                None,
+            ),
+            Event::Rewrite {
+                source_location,
+                source_entry_mode,
+                source_id,
+                entry_mode,
+                id,
+                copy,
+                ..
+            } => (
+                load(source_location, &source_id, source_entry_mode)?,
+                load(change.location, &id, entry_mode)?,
+                Some(Rewrite { copy }),
+            ),
+        })
+    }
+}
+
+/// Resources
+impl<'repo> Writer<'repo> {
+    /// Write the patch to turn `old` into `new` to `out`, similar to `git diff`, with `old` being `None` if the file was added,
+    /// and `new` being `None` if it was deleted. `rewrite` is set if `new` is a renamed or copied version of `old`.
+    ///
+    /// Nothing is written if both `old` and `new` are `None`.
+    pub fn write(
+        &mut self,
+        old: Option<Resource<'_>>,
+        new: Option<Resource<'_>>,
+        rewrite: Option<Rewrite>,
+        mut out: impl Write,
+    ) -> Result<(), Error> {
+        self.write_to(old, new, rewrite, &mut out)
+    }
+
+    /// Compute statistics for the change from `old` to `new`, with `old` being `None` if the file was added,
+    /// and `new` being `None` if it was deleted. `rewrite` is set if `new` is a renamed or copied version of `old`.
+    ///
+    /// # Panics
+    ///
+    /// If both `old` and `new` are `None`.
+    pub fn stat(
+        &mut self,
+        old: Option<Resource<'_>>,
+        new: Option<Resource<'_>>,
+        rewrite: Option<Rewrite>,
+    ) -> Result<stat::File, Error> {
+        let any = old.or(new).expect("at least one resource must be set");
+        let changes = match (self.diffable(old)?, self.diffable(new)?) {
+            (Diffable::Text(old_text), Diffable::Text(new_text)) => {
+                let (insertions, deletions) =
+                    lines::Diff::compute(self.options.algorithm, &old_text, &new_text).counts();
+                stat::Changes::Lines { insertions, deletions }
+            }
+            _ => {
+                let unchanged = matches!((old, new), (Some(old), Some(new)) if old.id == new.id);
+                stat::Changes::Binary {
+                    old_size: if unchanged { 0 } else { data(old).len() as u64 },
+                    new_size: if unchanged { 0 } else { data(new).len() as u64 },
+                }
+            }
+        };
+        Ok(stat::File {
+            source_location: rewrite
+                .and(old)
+                .map(|old| old.location.to_owned())
+                .filter(|source| new.map_or(true, |new| new.location != source)),
+            location: new.unwrap_or(any).location.to_owned(),
+            changes,
+        })
+    }
+
+    fn write_to(
+        &mut self,
+        old: Option<Resource<'_>>,
+        new: Option<Resource<'_>>,
+        rewrite: Option<Rewrite>,
+        out: &mut dyn Write,
+    ) -> Result<(), Error> {
+        let (old_location, new_location) = match (old, new) {
+            (None, None) => return Ok(()),
+            (Some(old), Some(new)) if rewrite.is_none() && kind(old.mode) != kind(new.mode) => {
+                // Like `git`, show changes of the type of an entry as deletion followed by an addition.
+                self.write_to(Some(old), None, None, out)?;
+                return self.write_to(None, Some(new), None, out);
+            }
+            (old_, new_) => {
+                let any = old_.or(new_).expect("at least one is set");
+                (old_.unwrap_or(any).location, new_.unwrap_or(any).location)
+            }
+        };
+        let quote_path = self.options.quote_path;
+        let prefix = &self.options.prefix;
+        out.write_all(b"diff --git ")?;
+        out.write_all(&quote::path(prefix.old.as_bstr(), old_location, quote_path))?;
+        out.write_all(b" ")?;
+        out.write_all(&quote::path(prefix.new.as_bstr(), new_location, quote_path))?;
+        out.write_all(b"\n")?;
+        match (old, new) {
+            (None, Some(new)) => writeln!(out, "new file mode {:06o}", new.mode as u16)?,
+            (Some(old), None) => writeln!(out, "deleted file mode {:06o}", old.mode as u16)?,
+            (Some(old), Some(new)) if old.mode != new.mode => {
+                writeln!(out, "old mode {:06o}", old.mode as u16)?;
+                writeln!(out, "new mode {:06o}", new.mode as u16)?;
+            }
+            _ => {}
+        }
+
+        let (old_data, new_data) = (data(old), data(new));
+        let old_id = old.map(|r| r.id);
+        let new_id = new.map(|r| r.id);
+        let content_changed = old_id != new_id;
+        let mut diff = None;
+        if let Some(Rewrite { copy }) = rewrite {
+            let similarity = if !content_changed || old_data.len().max(new_data.len()) == 0 {
+                100
+            } else {
+                let line_diff = lines::Diff::compute(self.options.algorithm, old_data, new_data);
+                let similarity = line_diff.unchanged_bytes_before() * 100 / old_data.len().max(new_data.len());
+                diff = Some(line_diff);
+                similarity
+            };
+            let kind = if copy { "copy" } else { "rename" };
+            writeln!(out, "similarity index {similarity}%")?;
+            write!(out, "{kind} from ")?;
+            out.write_all(&quote::plain(old_location, quote_path))?;
+            write!(out, "\n{kind} to ")?;
+            out.write_all(&quote::plain(new_location, quote_path))?;
+            out.write_all(b"\n")?;
+        }
+        if !content_changed {
+            return Ok(());
+        }
+        let (old_hex, new_hex) = self.abbreviate(old_id, new_id);
+        write!(out, "index {old_hex}..{new_hex}")?;
+        match (old, new) {
+            (Some(old), Some(new)) if old.mode == new.mode => writeln!(out, " {:06o}", old.mode as u16)?,
+            _ => out.write_all(b"\n")?,
+        }
+
+        let old_label = old.map_or_else(
+            || "/dev/null".into(),
+            |_| quote::path(prefix.old.as_bstr(), old_location, quote_path),
+        );
+        let new_label = new.map_or_else(
+            || "/dev/null".into(),
+            |_| quote::path(prefix.new.as_bstr(), new_location, quote_path),
+        );
+        let (old_text, new_text) = match (self.diffable(old)?, self.diffable(new)?) {
+            (Diffable::Text(old_text), Diffable::Text(new_text)) => (old_text, new_text),
+            _ => {
+                out.write_all(b"Binary files ")?;
+                out.write_all(&old_label)?;
+                out.write_all(b" and ")?;
+                out.write_all(&new_label)?;
+                out.write_all(b" differ\n")?;
+                return Ok(());
+            }
+        };
+
+        // The diff used to compute the similarity can only be reused if the data wasn't converted.
+        let diff = match diff {
+            Some(diff) if matches!((&old_text, &new_text), (Cow::Borrowed(_), Cow::Borrowed(_))) => diff,
+            _ => lines::Diff::compute(self.options.algorithm, &old_text, &new_text),
+        };
+        let hunks = diff.hunks(self.options.context_lines, self.options.inter_hunk_context);
+        if hunks.is_empty() {
+            return Ok(());
+        }
+        for (marker, label) in [("--- ", &old_label), ("+++ ", &new_label)] {
+            out.write_all(marker.as_bytes())?;
+            out.write_all(label)?;
+            if label.contains(&b' ') {
+                out.write_all(b"\t")?;
+            }
+            out.write_all(b"\n")?;
+        }
+
+        // Like `git`, prefer the driver of the old version of the file.
+        let mut driver = None;
+        for location in [old.map(|r| r.location), new.map(|r| r.location)].into_iter().flatten() {
+            if let Some(found) = self.drivers.driver(location)? {
+                driver = Some(found.clone());
+                break;
+            }
+        }
+        let function_names = function_name::Matcher::new(
+            driver.as_ref().and_then(|d| d.xfuncname.as_ref()).map(|v| v.as_bstr()),
+            driver.as_ref().map_or("".into(), |d| d.name.as_bstr()),
+        )?;
+        let words = match self.options.word_diff {
+            Some(mode) => Some((mode, self.word_splitter(driver.as_ref())?, word::Buffer::new())),
+            None => None,
+        };
+        write_hunks(&diff, &hunks, &function_names, words, out)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "regex")]
+    fn word_splitter(&self, driver: Option<&crate::diff::driver::Driver>) -> Result<word::Splitter, Error> {
+        match driver.and_then(|d| d.word_regex.as_ref().map(|regex| (regex, &d.name))) {
+            Some((regex, name)) => word::Splitter::new(Some(regex.as_bstr()), || format!("diff.{name}.wordRegex")),
+            None => word::Splitter::new(self.word_regex.as_ref().map(|r| r.as_bstr()), || {
+                "diff.wordRegex".into()
+            }),
+        }
+    }
+
+    #[cfg(not(feature = "regex"))]
+    fn word_splitter(&self, _driver: Option<&crate::diff::driver::Driver>) -> Result<word::Splitter, Error> {
+        let _ = &self.word_regex;
+        Ok(word::Splitter::Whitespace)
+    }
+
+    /// Decide how to diff `resource` according to its attributes and diff driver, with a missing resource being empty text.
+    /// Only regular files can be binary or converted with `textconv`.
+    fn diffable<'a>(&mut self, resource: Option<Resource<'a>>) -> Result<Diffable<'a>, Error> {
+        Ok(match resource {
+            Some(resource) if resource.mode.is_blob() => {
+                self.drivers.diffable(resource.location, resource.id, resource.data)?
+            }
+            resource => Diffable::Text(data(resource).into()),
+        })
+    }
+
+    /// Abbreviate both ids for use in the `index` line, writing missing ids as null ids of the same length as the other one.
+    fn abbreviate(&self, old: Option<&oid>, new: Option<&oid>) -> (String, String) {
+        let abbreviate = |id: Option<&oid>| -> Option<String> {
+            let id = id.filter(|id| id.as_bytes().iter().any(|b| *b != 0))?;
+            Some(if self.options.full_index {
+                id.to_hex().to_string()
+            } else {
+                let hex_len = self.repo.config.hex_len.unwrap_or(7);
+                id.to_owned()
+                    .attach(self.repo)
+                    .shorten()
+                    .map_or_else(|_| id.to_hex_with_len(hex_len).to_string(), |prefix| prefix.to_string())
+            })
+        };
+        let (old_hex, new_hex) = (abbreviate(old), abbreviate(new));
+        let len = old_hex.as_ref().or(new_hex.as_ref()).map_or(7, |hex| hex.len());
+        let null = || "0".repeat(len);
+        (old_hex.unwrap_or_else(null), new_hex.unwrap_or_else(null))
+    }
+}
+
+fn data<'a>(resource: Option<Resource<'a>>) -> &'a [u8] {
+    resource.map_or(&[], |r| r.data)
+}
+
+/// Return a number identifying the type of an entry, which is the same for executable and non-executable files.
+fn kind(mode: EntryMode) -> u8 {
+    match mode {
+        EntryMode::Blob | EntryMode::BlobExecutable => 0,
+        EntryMode::Link => 1,
+        EntryMode::Commit => 2,
+        EntryMode::Tree => 3,
+    }
+}
+
+fn write_hunks(
+    diff: &lines::Diff<'_>,
+    hunks: &[lines::Hunk<'_>],
+    function_names: &function_name::Matcher,
+    mut words: Option<(WordDiff, word::Splitter, word::Buffer)>,
+    out: &mut dyn Write,
+) -> std::io::Result<()> {
+    let mut function_name: &[u8] = &[];
+    let mut search_limit = 0;
+    for hunk in hunks {
+        // Like `git`, search the old version of the file backwards from the start of the hunk, but not further than the
+        // start of the previous hunk, keeping the previous function name if nothing was found.
+        if let Some(name) = diff.before[search_limit..hunk.before.start as usize]
+            .iter()
+            .rev()
+            .find_map(|line| function_names.find(line))
+        {
+            function_name = name;
+        }
+        search_limit = hunk.before.start as usize;
+
+        out.write_all(b"@@ -")?;
+        write_range(out, hunk.before.start, hunk.before.end - hunk.before.start)?;
+        out.write_all(b" +")?;
+        write_range(out, hunk.after.start, hunk.after.end - hunk.after.start)?;
+        out.write_all(b" @@")?;
+        if !function_name.is_empty() {
+            out.write_all(b" ")?;
+            out.write_all(function_name)?;
+        }
+        out.write_all(b"\n")?;
+
+        match words.as_mut() {
+            None => {
+                for line in hunk.lines(diff) {
+                    let (marker, line) = match line {
+                        lines::Line::Context(line) => (b' ', line),
+                        lines::Line::Removed(line) => (b'-', line),
+                        lines::Line::Added(line) => (b'+', line),
+                    };
+                    out.write_all(&[marker])?;
+                    out.write_all(line)?;
+                    if !line.ends_with(b"\n") {
+                        out.write_all(b"\n\\ No newline at end of file\n")?;
+                    }
+                }
+            }
+            Some((mode, splitter, buf)) => {
+                for line in hunk.lines(diff) {
+                    match line {
+                        lines::Line::Context(line) => buf.write_context(line, *mode, splitter, out)?,
+                        lines::Line::Removed(line) => buf.push_removed(line),
+                        lines::Line::Added(line) => buf.push_added(line),
+                    }
+                }
+                buf.flush(*mode, splitter, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write a line range of a hunk header the way `git` does, omitting the count if it's 1.
+fn write_range(out: &mut dyn Write, start: u32, count: u32) -> std::io::Result<()> {
+    match count {
+        0 => write!(out, "{start},0"),
+        1 => write!(out, "{}", start + 1),
+        _ => write!(out, "{},{count}", start + 1),
+    }
+}
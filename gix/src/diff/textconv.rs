@@ -0,0 +1,202 @@
+//! Run the `diff.<driver>.textconv` program to turn files into text for diffing, and cache its output in
+//! `refs/notes/textconv/<driver>` if `diff.<driver>.cachetextconv` is set, just like `git` does.
+use std::{
+    io::Write,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use gix_hash::{oid, ObjectId};
+use gix_object::tree::EntryMode;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    diff::driver::Driver,
+    Repository,
+};
+
+/// The error returned when running a `textconv` program or accessing its cache.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not write the data to convert to a temporary file at '{}'", path.display())]
+    WriteTempfile { source: std::io::Error, path: PathBuf },
+    #[error("Could not run the textconv program {command:?}")]
+    Spawn { source: std::io::Error, command: BString },
+    #[error("The textconv program {command:?} failed with {status}")]
+    Failed {
+        command: BString,
+        status: std::process::ExitStatus,
+    },
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    PeelReference(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    DecodeObject(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    ParseCommitterTime(#[from] crate::config::time::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+}
+
+/// Return the output of the `textconv` program of `driver` for `data` with the given `id`, found at `path`.
+pub(crate) fn convert(
+    repo: &Repository,
+    driver: &Driver,
+    program: &BStr,
+    path: &BStr,
+    id: &oid,
+    data: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let cache_ref = format!("refs/notes/textconv/{}", driver.name);
+    let notes = if driver.cache_textconv {
+        let notes = cached_notes(repo, &cache_ref, program)?;
+        if let Some(entry) = notes.iter().find(|entry| entry.filename == id.to_hex().to_string()) {
+            return Ok(repo.find_object(entry.oid)?.detach().data);
+        }
+        Some(notes)
+    } else {
+        None
+    };
+
+    let output = run(program, path, data)?;
+    if let Some(notes) = notes {
+        write_cache(repo, &cache_ref, program, notes, id, &output)?;
+    }
+    Ok(output)
+}
+
+/// Write `data` to a temporary file whose name ends with the file name of `path` so programs can detect the file type,
+/// and run `program` with the path to it, returning its output.
+fn run(program: &BStr, path: &BStr, data: &[u8]) -> Result<Vec<u8>, Error> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let file_name = path.rsplit_str("/").next().unwrap_or_default();
+    let tempfile_path = std::env::temp_dir().join(format!(
+        "gix-textconv-{}-{}_{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst),
+        file_name.to_str_lossy()
+    ));
+    let write_err = |source| Error::WriteTempfile {
+        source,
+        path: tempfile_path.clone(),
+    };
+    let mut tempfile = gix_tempfile::writable_at(
+        &tempfile_path,
+        gix_tempfile::ContainingDirectory::Exists,
+        gix_tempfile::AutoRemove::Tempfile,
+    )
+    .map_err(write_err)?;
+    tempfile.write_all(data).map_err(write_err)?;
+    tempfile.flush().map_err(write_err)?;
+
+    let spawn_err = |source| Error::Spawn {
+        source,
+        command: program.to_owned(),
+    };
+    let output = std::process::Command::from(
+        gix_command::prepare(gix_path::from_bstr(program).into_owned().into_os_string())
+            .with_shell()
+            .arg(&tempfile_path),
+    )
+    .output()
+    .map_err(spawn_err)?;
+    if !output.status.success() {
+        return Err(Error::Failed {
+            command: program.to_owned(),
+            status: output.status,
+        });
+    }
+    Ok(output.stdout)
+}
+
+/// Return all notes in the cache at `cache_ref`, or nothing if it doesn't exist or was created by a different `program`.
+fn cached_notes(repo: &Repository, cache_ref: &str, program: &BStr) -> Result<Vec<gix_object::tree::Entry>, Error> {
+    let mut reference = match repo.try_find_reference(cache_ref)? {
+        Some(reference) => reference,
+        None => return Ok(Vec::new()),
+    };
+    let commit = match reference.peel_to_id_in_place()?.object()?.try_into_commit() {
+        Ok(commit) => commit,
+        Err(_) => return Ok(Vec::new()),
+    };
+    // Like `git`, the cache is only valid if it was created with the same program, which is stored as commit message.
+    if commit.message_raw()?.trim() != program.trim() {
+        return Ok(Vec::new());
+    }
+    let mut notes = Vec::new();
+    collect_notes(repo, commit.tree_id()?.detach(), &mut BString::default(), &mut notes)?;
+    Ok(notes)
+}
+
+/// Collect all notes in the tree with `id` into `out` with their full hexadecimal name, flattening the fan-out directories
+/// that `git` creates once there are many notes.
+fn collect_notes(
+    repo: &Repository,
+    id: ObjectId,
+    prefix: &mut BString,
+    out: &mut Vec<gix_object::tree::Entry>,
+) -> Result<(), Error> {
+    let tree = repo.find_object(id)?.into_tree();
+    for entry in tree.decode()?.entries {
+        let prefix_len = prefix.len();
+        prefix.extend_from_slice(entry.filename);
+        if entry.mode.is_tree() {
+            collect_notes(repo, entry.oid.to_owned(), prefix, out)?;
+        } else if entry.mode.is_blob() {
+            out.push(gix_object::tree::Entry {
+                mode: EntryMode::Blob,
+                filename: prefix.clone(),
+                oid: entry.oid.to_owned(),
+            });
+        }
+        prefix.truncate(prefix_len);
+    }
+    Ok(())
+}
+
+/// Add `output` as note for `id` to `notes` and write them to `cache_ref` as a commit without parents, as `git` does.
+///
+/// Nothing is written if no committer is configured, as the cache is merely an optimization.
+fn write_cache(
+    repo: &Repository,
+    cache_ref: &str,
+    program: &BStr,
+    mut notes: Vec<gix_object::tree::Entry>,
+    id: &oid,
+    output: &[u8],
+) -> Result<(), Error> {
+    let committer = match repo.committer().transpose()? {
+        Some(committer) => committer.to_owned(),
+        None => return Ok(()),
+    };
+    notes.push(gix_object::tree::Entry {
+        mode: EntryMode::Blob,
+        filename: id.to_hex().to_string().into(),
+        oid: repo.write_blob(output)?.detach(),
+    });
+    notes.sort_by(|a, b| a.filename.cmp(&b.filename));
+    let tree = repo.write_object(gix_object::Tree { entries: notes })?.detach();
+    let commit = gix_object::Commit {
+        tree,
+        parents: Default::default(),
+        author: committer.clone(),
+        committer,
+        encoding: None,
+        message: program.to_owned(),
+        extra_headers: Default::default(),
+    };
+    let commit_id = repo.write_object(&commit)?.detach();
+    repo.reference(
+        cache_ref,
+        commit_id,
+        gix_ref::transaction::PreviousValue::Any,
+        "update notes cache",
+    )?;
+    Ok(())
+}
@@ -0,0 +1,455 @@
+//! Create and verify signatures of commits and tags by running `gpg`, `gpgsm` or `ssh-keygen`, just like `git` does.
+use std::{
+    ffi::OsString,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::bstr::{BStr, BString, ByteSlice};
+
+/// The kind of signature, as configured with `gpg.format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// An OpenPGP signature, created and verified with `gpg`.
+    OpenPgp,
+    /// An X.509 signature, created and verified with `gpgsm`.
+    X509,
+    /// An SSH signature, created and verified with `ssh-keygen`.
+    Ssh,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::OpenPgp
+    }
+}
+
+impl Format {
+    /// Determine the format of `signature` by the marker it starts with, or return `None` if it isn't known.
+    pub fn from_signature(signature: &[u8]) -> Option<Self> {
+        let signature = signature.trim_start();
+        if signature.starts_with(b"-----BEGIN PGP SIGNATURE-----")
+            || signature.starts_with(b"-----BEGIN PGP MESSAGE-----")
+        {
+            Some(Format::OpenPgp)
+        } else if signature.starts_with(b"-----BEGIN SIGNED MESSAGE-----") {
+            Some(Format::X509)
+        } else if signature.starts_with(b"-----BEGIN SSH SIGNATURE-----") {
+            Some(Format::Ssh)
+        } else {
+            None
+        }
+    }
+
+    /// The program to run if none is configured.
+    pub fn default_program(&self) -> &'static str {
+        match self {
+            Format::OpenPgp => "gpg",
+            Format::X509 => "gpgsm",
+            Format::Ssh => "ssh-keygen",
+        }
+    }
+}
+
+///
+pub mod sign {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Repository::sign()`][crate::Repository::sign()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Format(#[from] crate::config::key::GenericErrorWithValue),
+        #[error("user.signingKey needs to be set to create SSH signatures")]
+        MissingSigningKey,
+        #[error("Committer identity is not configured, but is needed as default signing key")]
+        CommitterMissing,
+        #[error(transparent)]
+        ParseTime(#[from] crate::config::time::Error),
+        #[error(transparent)]
+        PathInterpolation(#[from] gix_config::path::interpolate::Error),
+        #[error("Could not write the data to sign to a temporary file at '{}'", path.display())]
+        WriteTempfile { source: std::io::Error, path: PathBuf },
+        #[error("Could not run the signing program {command:?}")]
+        Spawn { source: std::io::Error, command: BString },
+        #[error("The signing program {command:?} failed with {status}: {stderr}")]
+        Failed {
+            command: BString,
+            status: std::process::ExitStatus,
+            stderr: BString,
+        },
+        #[error("Could not read the signature written by the signing program to '{}'", path.display())]
+        ReadSignature { source: std::io::Error, path: PathBuf },
+    }
+}
+
+///
+pub mod verify {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The verdict on a signature.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Status {
+        /// The signature is valid and was made by a known key.
+        Good,
+        /// The signature is invalid, as the data or the signature was altered.
+        Bad,
+        /// The key that made the signature is unknown, so the signature couldn't be checked or the signer isn't trusted.
+        ///
+        /// For SSH signatures, this means the key isn't listed in `gpg.ssh.allowedSignersFile`, even though the signature
+        /// itself is valid.
+        UnknownKey,
+        /// The signature is valid, but expired.
+        ExpiredSignature,
+        /// The signature is valid, but was made by a key that expired.
+        ExpiredKey,
+        /// The signature is valid, but was made by a key that was revoked.
+        RevokedKey,
+    }
+
+    /// The outcome of verifying a signature, for example with [`Repository::verify_signature()`][crate::Repository::verify_signature()].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Outcome {
+        /// The format of the signature.
+        pub format: crate::gpg::Format,
+        /// The verdict on the signature.
+        pub status: Status,
+        /// The signer, which is the user id of the key for OpenPGP and X.509 signatures, or the principal of
+        /// `gpg.ssh.allowedSignersFile` for SSH signatures.
+        pub signer: Option<BString>,
+        /// The id of the key that made the signature, for OpenPGP and X.509 signatures.
+        pub key: Option<BString>,
+        /// The fingerprint of the key that made the signature, if it is known.
+        pub fingerprint: Option<BString>,
+        /// The human-readable output of the verification program.
+        pub output: BString,
+    }
+
+    impl Outcome {
+        /// Return `true` if the signature is valid and was made by a known key.
+        pub fn is_good(&self) -> bool {
+            self.status == Status::Good
+        }
+    }
+
+    /// The error returned by [`Repository::verify_signature()`][crate::Repository::verify_signature()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The signature is in a format that isn't supported")]
+        UnknownFormat,
+        #[error(transparent)]
+        Decode(#[from] gix_object::decode::Error),
+        #[error("gpg.ssh.allowedSignersFile needs to be configured and exist to verify SSH signatures")]
+        MissingAllowedSignersFile,
+        #[error(transparent)]
+        PathInterpolation(#[from] gix_config::path::interpolate::Error),
+        #[error("Could not write the signature to a temporary file at '{}'", path.display())]
+        WriteTempfile { source: std::io::Error, path: PathBuf },
+        #[error("Could not run the verification program {command:?}")]
+        Spawn { source: std::io::Error, command: BString },
+        #[error("The verification program {command:?} failed with {status} without a verdict: {stderr}")]
+        Failed {
+            command: BString,
+            status: std::process::ExitStatus,
+            stderr: BString,
+        },
+    }
+}
+
+/// The namespace of SSH signatures, which assures signatures made for other purposes can't be used for `git` objects.
+const SSH_NAMESPACE: &str = "git";
+
+/// Run `program` with `format` to sign `payload` with `key`, which is a key id for OpenPGP and X.509, and the path
+/// to a key or a public key prefixed with `key::` for SSH.
+pub(crate) fn sign(format: Format, program: &BStr, key: &BStr, payload: &[u8]) -> Result<BString, sign::Error> {
+    let spawn_err = |source| sign::Error::Spawn {
+        source,
+        command: program.to_owned(),
+    };
+    let failed = |output: std::process::Output| sign::Error::Failed {
+        command: program.to_owned(),
+        status: output.status,
+        stderr: output.stderr.into(),
+    };
+    match format {
+        Format::OpenPgp | Format::X509 => {
+            let output = run(
+                program,
+                [
+                    OsString::from("--status-fd=2"),
+                    "-bsau".into(),
+                    gix_path::from_bstr(key).into_owned().into(),
+                ],
+                Some(payload),
+            )
+            .map_err(spawn_err)?;
+            // Like `git`, rely on the status line instead of the exit code as some programs exit with errors for
+            // unrelated reasons.
+            if output.stderr.find(b"[GNUPG:] SIG_CREATED ").is_none() {
+                return Err(failed(output));
+            }
+            Ok(output.stdout.into())
+        }
+        Format::Ssh => {
+            let write_err = |source, path: &Path| sign::Error::WriteTempfile {
+                source,
+                path: path.to_owned(),
+            };
+            let literal_key = key
+                .strip_prefix(b"key::")
+                .or_else(|| key.starts_with(b"ssh-").then_some(key.as_bytes()));
+            let key_file = literal_key
+                .map(|key| tempfile(key).map_err(|(source, path)| write_err(source, &path)))
+                .transpose()?;
+            let (_payload_file, payload_path) = tempfile(payload).map_err(|(source, path)| write_err(source, &path))?;
+
+            let mut args = vec![
+                OsString::from("-Y"),
+                "sign".into(),
+                "-n".into(),
+                SSH_NAMESPACE.into(),
+                "-f".into(),
+            ];
+            match &key_file {
+                Some((_, path)) => args.extend([path.into(), "-U".into()]),
+                None => args.push(gix_path::from_bstr(key).into_owned().into()),
+            }
+            args.push(payload_path.clone().into());
+            let output = run(program, args, None).map_err(spawn_err)?;
+            let mut signature_path = payload_path.into_os_string();
+            signature_path.push(".sig");
+            let signature_path = PathBuf::from(signature_path);
+            if !output.status.success() {
+                std::fs::remove_file(&signature_path).ok();
+                return Err(failed(output));
+            }
+            let signature = std::fs::read(&signature_path).map_err(|source| sign::Error::ReadSignature {
+                source,
+                path: signature_path.clone(),
+            });
+            std::fs::remove_file(&signature_path).ok();
+            Ok(signature?.into())
+        }
+    }
+}
+
+/// Run `program` to verify that `signature` in `format` was made for `payload`, using `allowed_signers` to learn about
+/// trusted keys for SSH signatures.
+pub(crate) fn verify(
+    format: Format,
+    program: &BStr,
+    allowed_signers: Option<&Path>,
+    payload: &[u8],
+    signature: &[u8],
+) -> Result<verify::Outcome, verify::Error> {
+    let spawn_err = |source| verify::Error::Spawn {
+        source,
+        command: program.to_owned(),
+    };
+    let (_signature_file, signature_path) =
+        tempfile(signature).map_err(|(source, path)| verify::Error::WriteTempfile { source, path })?;
+    match format {
+        Format::OpenPgp | Format::X509 => {
+            let mut args = Vec::<OsString>::new();
+            if format == Format::OpenPgp {
+                args.push("--keyid-format=long".into());
+            }
+            args.extend([
+                "--status-fd=1".into(),
+                "--verify".into(),
+                signature_path.into(),
+                "-".into(),
+            ]);
+            let output = run(program, args, Some(payload)).map_err(spawn_err)?;
+            parse_gpg_status(format, output.stdout.as_bstr(), output.stderr.clone().into()).ok_or_else(|| {
+                verify::Error::Failed {
+                    command: program.to_owned(),
+                    status: output.status,
+                    stderr: output.stderr.into(),
+                }
+            })
+        }
+        Format::Ssh => {
+            let allowed_signers = allowed_signers
+                .filter(|path| path.is_file())
+                .ok_or(verify::Error::MissingAllowedSignersFile)?;
+            let principals = run(
+                program,
+                [
+                    OsString::from("-Y"),
+                    "find-principals".into(),
+                    "-f".into(),
+                    allowed_signers.into(),
+                    "-s".into(),
+                    signature_path.clone().into(),
+                ],
+                None,
+            )
+            .map_err(spawn_err)?;
+
+            let mut output = None;
+            if principals.status.success() {
+                for principal in principals.stdout.lines().filter(|line| !line.is_empty()) {
+                    let res = run(
+                        program,
+                        [
+                            OsString::from("-Y"),
+                            "verify".into(),
+                            "-n".into(),
+                            SSH_NAMESPACE.into(),
+                            "-f".into(),
+                            allowed_signers.into(),
+                            "-I".into(),
+                            gix_path::from_bstr(principal.as_bstr()).into_owned().into(),
+                            "-s".into(),
+                            signature_path.clone().into(),
+                        ],
+                        Some(payload),
+                    )
+                    .map_err(spawn_err)?;
+                    let is_good = res.status.success();
+                    output = Some((res, Some(principal.as_bstr().to_owned())));
+                    if is_good {
+                        break;
+                    }
+                }
+            }
+            let (output, principal) = match output {
+                Some(output) => output,
+                None => {
+                    // The key isn't trusted, but we can still learn if the signature is valid.
+                    let output = run(
+                        program,
+                        [
+                            OsString::from("-Y"),
+                            "check-novalidate".into(),
+                            "-n".into(),
+                            SSH_NAMESPACE.into(),
+                            "-s".into(),
+                            signature_path.into(),
+                        ],
+                        Some(payload),
+                    )
+                    .map_err(spawn_err)?;
+                    (output, None)
+                }
+            };
+
+            let status = match (output.status.success(), principal.is_some()) {
+                (true, true) => verify::Status::Good,
+                (true, false) => verify::Status::UnknownKey,
+                (false, _) => verify::Status::Bad,
+            };
+            let fingerprint = output
+                .stdout
+                .lines()
+                .find(|line| line.starts_with(b"Good "))
+                .and_then(|line| {
+                    line.rfind(b" key ")
+                        .map(|pos| line[pos + 5..].trim().as_bstr().to_owned())
+                });
+            let mut text = BString::from(output.stdout);
+            text.extend_from_slice(&output.stderr);
+            Ok(verify::Outcome {
+                format,
+                status,
+                signer: principal.filter(|_| status == verify::Status::Good),
+                key: None,
+                fingerprint,
+                output: text,
+            })
+        }
+    }
+}
+
+/// Parse the lines written by `gpg` or `gpgsm` to the status file descriptor, or return `None` if there is no verdict.
+fn parse_gpg_status(format: Format, status: &BStr, output: BString) -> Option<verify::Outcome> {
+    use verify::Status::*;
+    let mut outcome = verify::Outcome {
+        format,
+        status: Bad,
+        signer: None,
+        key: None,
+        fingerprint: None,
+        output,
+    };
+    let mut has_verdict = false;
+    for line in status.lines() {
+        let line = match line.strip_prefix(b"[GNUPG:] ") {
+            Some(line) => line,
+            None => continue,
+        };
+        let (name, fields) = line.split_once_str(" ").unwrap_or((line, &[]));
+        let status = match name {
+            b"GOODSIG" => Good,
+            b"BADSIG" => Bad,
+            b"EXPSIG" => ExpiredSignature,
+            b"EXPKEYSIG" => ExpiredKey,
+            b"REVKEYSIG" => RevokedKey,
+            b"ERRSIG" => UnknownKey,
+            b"VALIDSIG" => {
+                outcome.fingerprint = fields.fields().next().map(|f| f.as_bstr().to_owned());
+                continue;
+            }
+            _ => continue,
+        };
+        // Any bad signature determines the verdict, even if there were good ones.
+        if has_verdict && outcome.status == Bad {
+            continue;
+        }
+        has_verdict = true;
+        outcome.status = status;
+        let (key, signer) = fields.split_once_str(" ").unwrap_or((fields, &[]));
+        outcome.key = Some(key.as_bstr().to_owned());
+        outcome.signer = (status != UnknownKey && !signer.is_empty()).then(|| signer.as_bstr().to_owned());
+    }
+    has_verdict.then_some(outcome)
+}
+
+/// Run `program` with `args`, passing `stdin` as its input, and return its output.
+fn run(
+    program: &BStr,
+    args: impl IntoIterator<Item = impl Into<OsString>>,
+    stdin: Option<&[u8]>,
+) -> std::io::Result<std::process::Output> {
+    use std::process::Stdio;
+    let mut child = gix_command::prepare(gix_path::from_bstr(program).into_owned().into_os_string())
+        .args(args)
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let input = child.stdin.take();
+    std::thread::scope(|scope| {
+        if let (Some(mut input), Some(data)) = (input, stdin) {
+            // Failing to write the input is visible in the outcome, as the program won't have seen all the data.
+            scope.spawn(move || input.write_all(data).ok());
+        }
+        child.wait_with_output()
+    })
+}
+
+/// Write `data` into a new temporary file with a random name, and return it along with its path.
+#[allow(clippy::type_complexity)]
+fn tempfile(
+    data: &[u8],
+) -> Result<(gix_tempfile::Handle<gix_tempfile::handle::Writable>, PathBuf), (std::io::Error, PathBuf)> {
+    let dir = std::env::temp_dir();
+    let mut file = gix_tempfile::new(
+        &dir,
+        gix_tempfile::ContainingDirectory::Exists,
+        gix_tempfile::AutoRemove::Tempfile,
+    )
+    .map_err(|err| (err, dir.clone()))?;
+    let path = file.with_mut(|file| file.path().to_owned()).map_err(|err| (err, dir))?;
+    file.write_all(data)
+        .and_then(|_| file.flush())
+        .map_err(|err| (err, path.clone()))?;
+    Ok((file, path))
+}
@@ -0,0 +1,82 @@
+//! Run hooks at specific points of certain operations, just like `git` does.
+//!
+//! Hooks are executable programs in the [hooks directory][crate::Repository::hooks_dir()] named after the hook they implement,
+//! or [functions][Function] registered with [`Repository::set_hook()`][crate::Repository::set_hook()] which take their place.
+//!
+//! The hooks run by `gix` itself are listed in [`name`], along with the operations running them.
+use std::{ffi::OsString, sync::Arc};
+
+use crate::bstr::BString;
+
+/// The signature of hooks implemented in Rust, see [`Repository::set_hook()`][crate::Repository::set_hook()].
+///
+/// It's called with the repository to run the hook for, the name of the hook and its input. Returning an error is the equivalent
+/// of a hook program exiting with a non-zero status.
+pub type Function = dyn Fn(&crate::Repository, &str, &Input) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>
+    + Send
+    + Sync;
+
+/// Hooks implemented as functions by their name.
+pub(crate) type Functions = std::collections::BTreeMap<String, Arc<Function>>;
+
+/// The input to a hook.
+#[derive(Default, Debug, Clone)]
+pub struct Input {
+    /// The arguments to pass to the hook.
+    pub args: Vec<OsString>,
+    /// The data to provide on standard input, which is empty if `None`.
+    pub stdin: Option<BString>,
+    /// Environment variables to set in addition to `GIT_DIR`.
+    pub env: Vec<(&'static str, OsString)>,
+}
+
+/// The names of the hooks run by `gix`, with semantics matching the ones of `git`.
+pub mod name {
+    /// Run before [creating a commit][crate::Repository::commit()], preventing it if it fails.
+    pub const PRE_COMMIT: &str = "pre-commit";
+    /// Run before [creating a commit][crate::Repository::commit()] with the path to a file containing its message, and `message`,
+    /// allowing to alter the message or to prevent the commit if it fails.
+    pub const PREPARE_COMMIT_MSG: &str = "prepare-commit-msg";
+    /// Run before [creating a commit][crate::Repository::commit()] with the path to a file containing its message,
+    /// allowing to alter the message or to prevent the commit if it fails.
+    pub const COMMIT_MSG: &str = "commit-msg";
+    /// Run after [creating a commit][crate::Repository::commit()], without affecting it.
+    pub const POST_COMMIT: &str = "post-commit";
+    /// Run after [checking out the main worktree][crate::clone::PrepareCheckout::main_worktree()] of a clone with the
+    /// null object id, the id of the checked out commit and `1`.
+    pub const POST_CHECKOUT: &str = "post-checkout";
+    /// Run for each [transaction][crate::Repository::edit_references()] that changes references, with its state as argument,
+    /// which is one of `prepared`, `committed` or `aborted`.
+    ///
+    /// Its standard input receives a line with `<previous-id> <new-id> <ref-name>` for each change, with the null object id
+    /// signalling that the reference didn't exist or is deleted. Failing in the `prepared` state aborts the transaction.
+    pub const REFERENCE_TRANSACTION: &str = "reference-transaction";
+}
+
+/// The error returned by [`Repository::run_hook()`][crate::Repository::run_hook()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not interpolate the path in core.hooksPath")]
+    HooksPath(#[from] gix_config::path::interpolate::Error),
+    #[error("Could not determine the current working directory to make hook paths absolute")]
+    CurrentDir(#[source] std::io::Error),
+    #[error("Could not run the '{name}' hook at \"{}\"", path.display())]
+    Spawn {
+        name: String,
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Failed to wait for the '{name}' hook to finish")]
+    Wait { name: String, source: std::io::Error },
+    #[error("The '{name}' hook failed with {status}")]
+    Failed {
+        name: String,
+        status: std::process::ExitStatus,
+    },
+    #[error("The '{name}' hook failed")]
+    Function {
+        name: String,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+}
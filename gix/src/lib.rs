@@ -68,11 +68,13 @@
 // APIs/instances anyway.
 pub use gix_actor as actor;
 pub use gix_attributes as attrs;
+pub use gix_commitgraph as commitgraph;
 pub use gix_credentials as credentials;
 pub use gix_date as date;
 pub use gix_features as features;
 use gix_features::threading::OwnShared;
 pub use gix_features::{parallel, progress::Progress, threading};
+pub use gix_filter as filter;
 pub use gix_fs as fs;
 pub use gix_glob as glob;
 pub use gix_hash as hash;
@@ -83,6 +85,7 @@ pub use gix_lock as lock;
 pub use gix_object as objs;
 pub use gix_object::bstr;
 pub use gix_odb as odb;
+pub use gix_pathspec as pathspec;
 pub use gix_prompt as prompt;
 #[cfg(all(feature = "gix-protocol"))]
 pub use gix_protocol as protocol;
@@ -136,6 +139,45 @@ pub mod progress;
 ///
 pub mod diff;
 
+///
+pub mod blame;
+
+///
+pub mod add;
+
+///
+pub mod apply;
+
+///
+pub mod bundle;
+
+///
+pub mod checkout;
+
+///
+pub mod merge;
+
+///
+pub mod stash;
+
+///
+pub mod cherry_pick;
+
+///
+pub mod revert;
+
+///
+pub mod gpg;
+
+pub mod hook;
+
+///
+#[cfg(feature = "blocking-network-client")]
+pub mod receive_pack;
+///
+#[cfg(feature = "blocking-network-client")]
+pub mod upload_pack;
+
 /// See [ThreadSafeRepository::discover()], but returns a [`Repository`] instead.
 ///
 /// # Note
@@ -228,7 +270,7 @@ pub mod permission;
 
 ///
 pub mod permissions;
-pub use repository::permissions::Permissions;
+pub use repository::{extensions::Extensions, permissions::Permissions};
 
 ///
 pub mod create;
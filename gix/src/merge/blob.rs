@@ -0,0 +1,140 @@
+use std::ops::Range;
+
+use crate::merge::Labels;
+
+/// The length of the conflict markers, which is what `git` uses by default.
+const MARKER_SIZE: usize = 7;
+
+/// Return `true` if `data` is binary like `git` sees it, which is if there is a null byte in its first 8000 bytes.
+pub(crate) fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(8000)].contains(&0)
+}
+
+/// Merge the changes from `base` to `ours` and from `base` to `theirs` line by line, and return the merged content along with
+/// `true` if there were conflicting changes, which are surrounded by conflict markers labelled with `labels`.
+///
+/// Like `git`, changes of both sides conflict if they overlap or if they are adjacent, unless they are the same, and lines both
+/// sides agree on at the beginning and the end of a conflict are moved out of it.
+pub(crate) fn merge(base: &[u8], ours: &[u8], theirs: &[u8], labels: Labels<'_>) -> (Vec<u8>, bool) {
+    let base_lines: Vec<_> = lines(base).collect();
+    let ours_lines: Vec<_> = lines(ours).collect();
+    let theirs_lines: Vec<_> = lines(theirs).collect();
+    let ours_changes = changes(base, ours);
+    let theirs_changes = changes(base, theirs);
+
+    let mut out = Vec::with_capacity(ours.len().max(theirs.len()));
+    let mut has_conflicts = false;
+    let (mut ours_idx, mut theirs_idx, mut base_pos) = (0, 0, 0);
+    loop {
+        let start = match (ours_changes.get(ours_idx), theirs_changes.get(theirs_idx)) {
+            (Some((ours, _)), Some((theirs, _))) => ours.start.min(theirs.start),
+            (Some((ours, _)), None) => ours.start,
+            (None, Some((theirs, _))) => theirs.start,
+            (None, None) => break,
+        };
+        let (ours_start, theirs_start) = (ours_idx, theirs_idx);
+        let mut end = start;
+        loop {
+            let mut grew = false;
+            for (changes, idx) in [(&ours_changes, &mut ours_idx), (&theirs_changes, &mut theirs_idx)] {
+                while let Some((before, _)) = changes.get(*idx).filter(|(before, _)| before.start <= end) {
+                    end = end.max(before.end);
+                    *idx += 1;
+                    grew = true;
+                }
+            }
+            if !grew {
+                break;
+            }
+        }
+
+        push_lines(&mut out, &base_lines[base_pos..start as usize]);
+        let ours_range = side_range(&ours_changes[ours_start..ours_idx], start, end);
+        let theirs_range = side_range(&theirs_changes[theirs_start..theirs_idx], start, end);
+        match (ours_range, theirs_range) {
+            (Some(range), None) => push_lines(&mut out, &ours_lines[range]),
+            (None, Some(range)) => push_lines(&mut out, &theirs_lines[range]),
+            (Some(ours_range), Some(theirs_range)) => {
+                let (ours, theirs) = (&ours_lines[ours_range], &theirs_lines[theirs_range]);
+                if ours == theirs {
+                    push_lines(&mut out, ours);
+                } else {
+                    has_conflicts = true;
+                    push_conflict(&mut out, ours, theirs, labels);
+                }
+            }
+            (None, None) => unreachable!("each group has at least one change"),
+        }
+        base_pos = end as usize;
+    }
+    push_lines(&mut out, &base_lines[base_pos..]);
+    (out, has_conflicts)
+}
+
+fn lines(data: &[u8]) -> gix_diff::blob::sources::ByteLines<'_, true> {
+    gix_diff::blob::sources::byte_lines_with_terminator(data)
+}
+
+/// Return the changed line ranges between `before` and `after` as `(before, after)` pairs, using the histogram algorithm
+/// just like `git` does for merges.
+fn changes(before: &[u8], after: &[u8]) -> Vec<(Range<u32>, Range<u32>)> {
+    let input = gix_diff::blob::intern::InternedInput::new(lines(before), lines(after));
+    let mut changes = Vec::new();
+    gix_diff::blob::diff(
+        gix_diff::blob::Algorithm::Histogram,
+        &input,
+        |before: Range<u32>, after: Range<u32>| changes.push((before, after)),
+    );
+    changes
+}
+
+/// Return the range of lines of a side that replaces the base lines `start..end`, if it has `changes` in this range.
+fn side_range(changes: &[(Range<u32>, Range<u32>)], start: u32, end: u32) -> Option<Range<usize>> {
+    let (first, last) = (changes.first()?, changes.last()?);
+    let side_start = first.1.start - (first.0.start - start);
+    let side_end = last.1.end + (end - last.0.end);
+    Some(side_start as usize..side_end as usize)
+}
+
+fn push_lines(out: &mut Vec<u8>, lines: &[&[u8]]) {
+    for line in lines {
+        out.extend_from_slice(line);
+    }
+}
+
+/// Write the conflicting `ours` and `theirs` lines surrounded by markers, but keep the lines they start or end with alike
+/// out of it.
+fn push_conflict(out: &mut Vec<u8>, ours: &[&[u8]], theirs: &[&[u8]], labels: Labels<'_>) {
+    let prefix = ours.iter().zip(theirs).take_while(|(a, b)| a == b).count();
+    let suffix = ours[prefix..]
+        .iter()
+        .rev()
+        .zip(theirs[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    push_lines(out, &ours[..prefix]);
+    push_marker(out, b'<', Some(labels.ours));
+    push_terminated_lines(out, &ours[prefix..ours.len() - suffix]);
+    push_marker(out, b'=', None);
+    push_terminated_lines(out, &theirs[prefix..theirs.len() - suffix]);
+    push_marker(out, b'>', Some(labels.theirs));
+    push_lines(out, &ours[ours.len() - suffix..]);
+}
+
+/// Like [`push_lines()`], but assure the last line ends with a newline so a conflict marker can follow.
+fn push_terminated_lines(out: &mut Vec<u8>, lines: &[&[u8]]) {
+    push_lines(out, lines);
+    if lines.last().map_or(false, |line| !line.ends_with(b"\n")) {
+        out.push(b'\n');
+    }
+}
+
+fn push_marker(out: &mut Vec<u8>, marker: u8, label: Option<&crate::bstr::BStr>) {
+    out.extend(std::iter::repeat(marker).take(MARKER_SIZE));
+    if let Some(label) = label.filter(|label| !label.is_empty()) {
+        out.push(b' ');
+        out.extend_from_slice(label);
+    }
+    out.push(b'\n');
+}
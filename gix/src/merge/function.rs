@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, HashSet};
+
+use gix_hash::ObjectId;
+use gix_index::entry::{Flags, Mode, Stat};
+use gix_odb::FindExt;
+
+use crate::{
+    bstr::{BStr, BString},
+    merge::{blob, conflict::Kind, Conflict, Error, Labels, Outcome},
+    Repository,
+};
+
+/// The id and mode of one version of a path, or `None` if it doesn't exist in that version.
+type Version = Option<(ObjectId, Mode)>;
+
+/// How a path that wasn't merged trivially was resolved.
+enum Resolution {
+    Merged(Version),
+    Conflict { kind: Kind, merged_blob: Option<ObjectId> },
+}
+
+pub(crate) fn trees(
+    repo: &Repository,
+    base: &gix_hash::oid,
+    ours: &gix_hash::oid,
+    theirs: &gix_hash::oid,
+    labels: Labels<'_>,
+) -> Result<Outcome, Error> {
+    let mut index = gix_index::State::from_tree(ours, |oid, buf| find_tree_iter(repo, oid, buf))?;
+    index.merge_three_way(base, ours, theirs, |oid, buf| find_tree_iter(repo, oid, buf))?;
+
+    let mut unmerged = BTreeMap::<BString, [Version; 3]>::new();
+    for entry in index.entries().iter().filter(|entry| entry.stage() != 0) {
+        unmerged.entry(entry.path(&index).to_owned()).or_insert([None; 3])[entry.stage() as usize - 1] =
+            Some((entry.id, entry.mode));
+    }
+
+    let mut resolved = Vec::new();
+    let mut conflicts = Vec::new();
+    for (path, [base, ours, theirs]) in unmerged {
+        match resolve(repo, base, ours, theirs, labels)? {
+            Resolution::Merged(version) => resolved.push((path, version)),
+            Resolution::Conflict { kind, merged_blob } => conflicts.push(Conflict {
+                path,
+                kind,
+                merged_blob,
+            }),
+        }
+    }
+    if !resolved.is_empty() {
+        let paths: HashSet<&BStr> = resolved.iter().map(|(path, _)| path.as_ref()).collect();
+        index.remove_entries(|_, path, _| paths.contains(path));
+        for (path, version) in &resolved {
+            if let Some((id, mode)) = version {
+                index.dangerously_push_entry(Stat::default(), *id, Flags::empty(), *mode, path.as_ref());
+            }
+        }
+        index.sort_entries();
+    }
+    Ok(Outcome { index, conflicts })
+}
+
+/// Find the tree with `id`, which may also be the empty tree used as base of root commits even if it doesn't exist in the
+/// object database.
+fn find_tree_iter<'a>(
+    repo: &Repository,
+    id: &gix_hash::oid,
+    buf: &'a mut Vec<u8>,
+) -> Option<gix_object::TreeRefIter<'a>> {
+    if id == ObjectId::empty_tree(repo.object_hash()) {
+        Some(gix_object::TreeRefIter::from_bytes(&[]))
+    } else {
+        repo.objects.find_tree_iter(id, buf).ok()
+    }
+}
+
+/// Resolve a path which was changed by both sides, similar to what `git merge-ort` does.
+fn resolve(
+    repo: &Repository,
+    base: Version,
+    ours: Version,
+    theirs: Version,
+    labels: Labels<'_>,
+) -> Result<Resolution, Error> {
+    let (ours, theirs) = match (ours, theirs) {
+        (Some(ours), Some(theirs)) => (ours, theirs),
+        (None, theirs) => {
+            return Ok(if theirs.is_none() || theirs == base {
+                Resolution::Merged(None)
+            } else {
+                Resolution::Conflict {
+                    kind: Kind::DeletedByUs,
+                    merged_blob: None,
+                }
+            })
+        }
+        (ours, None) => {
+            return Ok(if ours == base {
+                Resolution::Merged(None)
+            } else {
+                Resolution::Conflict {
+                    kind: Kind::DeletedByThem,
+                    merged_blob: None,
+                }
+            })
+        }
+    };
+
+    let kind = if base.is_some() { Kind::Content } else { Kind::BothAdded };
+    let is_file = |mode: Mode| matches!(mode, Mode::FILE | Mode::FILE_EXECUTABLE);
+    if !(is_file(ours.1) && is_file(theirs.1) && base.map_or(true, |(_, mode)| is_file(mode))) {
+        // Symbolic links and submodules can't be merged, and neither can a file that changed its type.
+        return Ok(Resolution::Conflict {
+            kind,
+            merged_blob: None,
+        });
+    }
+
+    let mode = merge_trivially(base.map(|(_, mode)| mode), ours.1, theirs.1);
+    let (id, merged_blob) = match merge_trivially(base.map(|(id, _)| id), ours.0, theirs.0) {
+        Some(id) => (Some(id), None),
+        None => {
+            let base = match base {
+                Some((id, _)) => repo.find_object(id)?.detach().data,
+                None => Vec::new(),
+            };
+            let ours = repo.find_object(ours.0)?.detach().data;
+            let theirs = repo.find_object(theirs.0)?.detach().data;
+            if blob::is_binary(&base) || blob::is_binary(&ours) || blob::is_binary(&theirs) {
+                (None, None)
+            } else {
+                let (merged, has_conflicts) = blob::merge(&base, &ours, &theirs, labels);
+                let merged_id = repo.write_blob(merged)?.detach();
+                if has_conflicts {
+                    (None, Some(merged_id))
+                } else {
+                    (Some(merged_id), None)
+                }
+            }
+        }
+    };
+    Ok(match (id, mode) {
+        (Some(id), Some(mode)) => Resolution::Merged(Some((id, mode))),
+        (id, _) => Resolution::Conflict {
+            kind,
+            merged_blob: merged_blob.or(id),
+        },
+    })
+}
+
+/// Return the value of the side that changed compared to `base`, or `None` if both changed it differently.
+fn merge_trivially<T: PartialEq + Copy>(base: Option<T>, ours: T, theirs: T) -> Option<T> {
+    if ours == theirs || base == Some(theirs) {
+        Some(ours)
+    } else if base == Some(ours) {
+        Some(theirs)
+    } else {
+        None
+    }
+}
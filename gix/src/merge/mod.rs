@@ -0,0 +1,71 @@
+//! Merge trees along with the content of the files within them in three ways, similar to `git merge-tree --write-tree`.
+use gix_hash::ObjectId;
+
+use crate::bstr::{BStr, BString};
+
+mod blob;
+pub(crate) mod function;
+
+/// The names of both sides of a merge which label them in conflict markers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Labels<'a> {
+    /// The name of our side, which is the one the changes are merged into, like `HEAD`.
+    pub ours: &'a BStr,
+    /// The name of their side, which is the one with the changes to merge.
+    pub theirs: &'a BStr,
+}
+
+///
+pub mod conflict {
+    /// The way both sides of a merge changed a path so that it couldn't be merged automatically.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Kind {
+        /// Both sides changed the same lines of the file differently, or changed a binary file, a symbolic link,
+        /// a submodule or the mode of the file differently.
+        Content,
+        /// Both sides added a different file at the same path.
+        BothAdded,
+        /// Our side deleted the file which their side changed.
+        DeletedByUs,
+        /// Their side deleted the file which our side changed.
+        DeletedByThem,
+    }
+}
+
+/// A path that couldn't be merged automatically, and which is present in stage 1, 2 and 3 of the merged index for the base,
+/// our and their version respectively, as far as they exist.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Conflict {
+    /// The path of the file relative to the root of the merged trees.
+    pub path: BString,
+    /// The way both sides changed the file.
+    pub kind: conflict::Kind,
+    /// The blob with the merged content of a text file changed by both sides, with conflict markers around all lines
+    /// that couldn't be merged, which is what `git` writes to the working tree.
+    pub merged_blob: Option<ObjectId>,
+}
+
+/// The result of [`Repository::merge_trees()`][crate::Repository::merge_trees()].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The merged entries of all three trees, with all [conflicts][Outcome::conflicts] in stages 1 to 3.
+    ///
+    /// If there are no conflicts, the merged tree can be [written][crate::Repository::write_tree_from_index()] from it.
+    pub index: gix_index::State,
+    /// All paths that couldn't be merged automatically, sorted by path.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// The error returned by [`Repository::merge_trees()`][crate::Repository::merge_trees()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Traverse(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    MergeIntoIndex(#[from] gix_index::merge::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+}
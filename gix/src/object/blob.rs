@@ -1,8 +1,11 @@
 ///
 pub mod diff {
-    use std::ops::Range;
+    use std::{borrow::Cow, ops::Range};
 
-    use crate::{bstr::ByteSlice, object::blob::diff::line::Change};
+    use crate::{
+        bstr::{BStr, ByteSlice},
+        object::blob::diff::line::Change,
+    };
 
     /// A platform to keep temporary information to perform line diffs on modified blobs.
     ///
@@ -14,11 +17,17 @@ pub mod diff {
         /// The algorithm to use when calling [imara_diff::diff()][gix_diff::blob::diff()].
         /// This value is determined by the `diff.algorithm` configuration.
         pub algo: gix_diff::blob::Algorithm,
+        /// The output of the `textconv` program of the diff driver for the `old` and `new` blob respectively, which is diffed
+        /// instead of their data if set.
+        pub textconv: Option<(Vec<u8>, Vec<u8>)>,
+        /// If `true`, one of the blobs is binary according to its attributes or content, which makes line diffs meaningless.
+        pub is_binary: bool,
     }
 
     ///
     pub mod init {
-        /// The error returned by [`Platform::from_ids()`][super::Platform::from_ids()].
+        /// The error returned by [`Platform::from_ids()`][super::Platform::from_ids()] and
+        /// [`Platform::from_ids_at()`][super::Platform::from_ids_at()].
         #[derive(Debug, thiserror::Error)]
         #[allow(missing_docs)]
         pub enum Error {
@@ -26,6 +35,10 @@ pub mod diff {
             FindExisting(#[from] crate::object::find::existing::Error),
             #[error("Could not obtain diff algorithm from configuration")]
             DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+            #[error(transparent)]
+            DriverResolver(#[from] crate::diff::driver::init::Error),
+            #[error(transparent)]
+            Driver(#[from] crate::diff::driver::Error),
         }
     }
 
@@ -33,6 +46,7 @@ pub mod diff {
         /// Produce a platform for performing various diffs after obtaining the object data of `previous_id` and `new_id`.
         ///
         /// Note that these objects are treated as raw data and are assumed to be blobs.
+        #[allow(clippy::result_large_err)]
         pub fn from_ids(
             previous_id: &crate::Id<'old>,
             new_id: &crate::Id<'new>,
@@ -46,11 +60,46 @@ pub mod diff {
                         Ok(algo) => algo,
                         Err(err) => return Err(err.into()),
                     };
-                    Ok(Platform { old, new, algo })
+                    let is_binary =
+                        crate::diff::driver::is_binary(&old.data) || crate::diff::driver::is_binary(&new.data);
+                    Ok(Platform {
+                        old,
+                        new,
+                        algo,
+                        textconv: None,
+                        is_binary,
+                    })
                 }
                 Err(err) => Err(err.into()),
             }
         }
+
+        /// Like [`from_ids()`][Self::from_ids()], but use `drivers` to determine if the blobs at the repository-relative
+        /// `location` are binary according to the `diff` attribute, and to convert them with the `textconv` program of
+        /// their diff driver, as described in [`Resolver::diffable()`][crate::diff::driver::Resolver::diffable()].
+        #[allow(clippy::result_large_err)]
+        pub fn from_ids_at(
+            previous_id: &crate::Id<'old>,
+            new_id: &crate::Id<'new>,
+            location: &BStr,
+            drivers: &mut crate::diff::driver::Resolver<'_>,
+        ) -> Result<Platform<'old, 'new>, init::Error> {
+            use crate::diff::driver::Diffable;
+            let mut platform = Self::from_ids(previous_id, new_id)?;
+            match (
+                drivers.diffable(location, previous_id, &platform.old.data)?,
+                drivers.diffable(location, new_id, &platform.new.data)?,
+            ) {
+                (Diffable::Text(old), Diffable::Text(new)) => {
+                    platform.is_binary = false;
+                    if matches!((&old, &new), (Cow::Owned(_), _) | (_, Cow::Owned(_))) {
+                        platform.textconv = Some((old.into_owned(), new.into_owned()));
+                    }
+                }
+                _ => platform.is_binary = true,
+            }
+            Ok(platform)
+        }
     }
 
     ///
@@ -139,10 +188,14 @@ pub mod diff {
         /// Return a tokenizer which treats lines as smallest unit for use in a [diff operation][gix_diff::blob::diff()].
         ///
         /// The line separator is determined according to normal git rules and filters.
+        /// If set, the [`textconv`][Self::textconv] output is used instead of the data of the blobs.
         pub fn line_tokens(&self) -> gix_diff::blob::intern::InternedInput<&[u8]> {
             // TODO: make use of `core.eol` and/or filters to do line-counting correctly. It's probably
             //       OK to just know how these objects are saved to know what constitutes a line.
-            gix_diff::blob::intern::InternedInput::new(self.old.data.as_bytes(), self.new.data.as_bytes())
+            match &self.textconv {
+                Some((old, new)) => gix_diff::blob::intern::InternedInput::new(old.as_slice(), new.as_slice()),
+                None => gix_diff::blob::intern::InternedInput::new(self.old.data.as_bytes(), self.new.data.as_bytes()),
+            }
         }
     }
 }
@@ -1,4 +1,8 @@
-use crate::{bstr, bstr::BStr, revision, Commit, ObjectDetached, Tree};
+use crate::{
+    bstr,
+    bstr::{BStr, BString},
+    revision, Commit, ObjectDetached, Tree,
+};
 
 mod error {
     use crate::object;
@@ -147,6 +151,57 @@ impl<'repo> Commit<'repo> {
             max_candidates: 10,
         }
     }
+
+    /// Return the signature of this commit along with the data it was made for, or `None` if the commit isn't signed.
+    ///
+    /// Like `git`, the signed data is the commit without any of its signature headers.
+    pub fn signature(&self) -> Result<Option<(BString, BString)>, gix_object::decode::Error> {
+        let header = signature_header(self.repo.object_hash());
+        let mut signature: BString = match self.decode()?.extra_headers().find(header) {
+            Some(signature) => signature.to_owned(),
+            None => return Ok(None),
+        };
+        signature.push(b'\n');
+
+        use bstr::ByteSlice;
+        let mut payload = BString::default();
+        let mut in_signature = false;
+        let mut lines = self.data.lines_with_terminator();
+        for line in lines.by_ref() {
+            if line == b"\n" {
+                payload.extend_from_slice(line);
+                break;
+            }
+            if (in_signature && line.starts_with(b" ")) || line.starts_with(b"gpgsig") {
+                in_signature = true;
+                continue;
+            }
+            in_signature = false;
+            payload.extend_from_slice(line);
+        }
+        for line in lines {
+            payload.extend_from_slice(line);
+        }
+        Ok(Some((signature, payload)))
+    }
+
+    /// Verify the [signature][Commit::signature()] of this commit and return the verdict, or `None` if it isn't signed,
+    /// similar to `git verify-commit`.
+    ///
+    /// See [`Repository::verify_signature()`][crate::Repository::verify_signature()] for details.
+    pub fn verify_signature(&self) -> Result<Option<crate::gpg::verify::Outcome>, crate::gpg::verify::Error> {
+        match self.signature()? {
+            Some((signature, payload)) => self.repo.verify_signature(&payload, &signature).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The name of the header containing the signature of commits in repositories using `object_hash`.
+pub(crate) fn signature_header(object_hash: gix_hash::Kind) -> &'static str {
+    match object_hash {
+        gix_hash::Kind::Sha1 => "gpgsig",
+    }
 }
 
 impl<'r> std::fmt::Debug for Commit<'r> {
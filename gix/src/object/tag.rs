@@ -1,4 +1,8 @@
-use crate::{ext::ObjectIdExt, Tag};
+use crate::{
+    bstr::{BStr, ByteSlice},
+    ext::ObjectIdExt,
+    Tag,
+};
 
 impl<'repo> Tag<'repo> {
     /// Decode the entire tag object and return it for accessing all tag information.
@@ -23,4 +27,24 @@ impl<'repo> Tag<'repo> {
     pub fn tagger(&self) -> Result<Option<gix_actor::SignatureRef<'_>>, gix_object::decode::Error> {
         gix_object::TagRefIter::from_bytes(&self.data).tagger()
     }
+
+    /// Return the signature of this tag along with the data it was made for, or `None` if the tag isn't signed.
+    pub fn signature(&self) -> Result<Option<(&BStr, &BStr)>, gix_object::decode::Error> {
+        Ok(self.decode()?.pgp_signature.map(|signature| {
+            // The signature is always at the end, and signs everything before it.
+            let payload = &self.data[..self.data.len() - signature.len()];
+            (signature, payload.as_bstr())
+        }))
+    }
+
+    /// Verify the [signature][Tag::signature()] of this tag and return the verdict, or `None` if it isn't signed,
+    /// similar to `git verify-tag`.
+    ///
+    /// See [`Repository::verify_signature()`][crate::Repository::verify_signature()] for details.
+    pub fn verify_signature(&self) -> Result<Option<crate::gpg::verify::Outcome>, crate::gpg::verify::Error> {
+        match self.signature()? {
+            Some((signature, payload)) => self.repo.verify_signature(payload, signature).map(Some),
+            None => Ok(None),
+        }
+    }
 }
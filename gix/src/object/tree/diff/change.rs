@@ -109,3 +109,40 @@ impl<'a, 'old, 'new> Event<'a, 'old, 'new> {
         }
     }
 }
+
+impl<'a, 'old, 'new> super::Change<'a, 'old, 'new> {
+    /// Produce a platform for performing a line-diff like [`Event::diff()`], but respect the `diff`, `-diff` and `binary`
+    /// attributes at the location of this change and run the `textconv` program of its diff driver, similar to `git diff`.
+    ///
+    /// Note that [tracking of paths][super::Platform::track_path()] must be enabled for attributes to match.
+    pub fn diff(
+        &self,
+    ) -> Option<Result<crate::object::blob::diff::Platform<'old, 'new>, crate::object::blob::diff::init::Error>> {
+        match self.event {
+            Event::Modification { id, .. } => match id.repo.diff_drivers() {
+                Ok(mut drivers) => self.diff_with_drivers(&mut drivers),
+                Err(err) => Some(Err(err.into())),
+            },
+            _ => None,
+        }
+    }
+
+    /// Like [`diff()`][Self::diff()], but use `drivers` as obtained with [`Repository::diff_drivers()`][crate::Repository::diff_drivers()]
+    /// to find attributes and diff drivers, which is more efficient when diffing many changes.
+    pub fn diff_with_drivers(
+        &self,
+        drivers: &mut crate::diff::driver::Resolver<'_>,
+    ) -> Option<Result<crate::object::blob::diff::Platform<'old, 'new>, crate::object::blob::diff::init::Error>> {
+        match self.event {
+            Event::Modification {
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+            } if entry_mode.is_blob() && previous_entry_mode.is_blob() => Some(
+                crate::object::blob::diff::Platform::from_ids_at(&previous_id, &id, self.location, drivers),
+            ),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    ext::ObjectIdExt,
+    Id, Repository,
+};
+
+/// The error returned by [`Editor`][super::Editor] operations.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The path '{path}' can't be used in a tree")]
+    InvalidPath {
+        path: BString,
+        source: gix_validate::path::component::Error,
+    },
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToTree(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    NotATree(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeTree(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+}
+
+/// A tree that is being edited.
+#[derive(Clone)]
+pub(super) struct Tree {
+    /// The id of the tree as it was loaded, or `None` if it was changed and needs to be written.
+    id: Option<ObjectId>,
+    /// All entries of the tree, in no particular order.
+    entries: Vec<Entry>,
+}
+
+#[derive(Clone)]
+struct Entry {
+    mode: EntryMode,
+    filename: BString,
+    oid: ObjectId,
+    /// The tree `oid` points to, once it was loaded for editing.
+    tree: Option<Tree>,
+}
+
+impl Tree {
+    pub(super) fn from_decoded(id: ObjectId, tree: gix_object::TreeRef<'_>) -> Self {
+        Tree {
+            id: Some(id),
+            entries: tree
+                .entries
+                .into_iter()
+                .map(|entry| Entry {
+                    mode: entry.mode,
+                    filename: entry.filename.to_owned(),
+                    oid: entry.oid.to_owned(),
+                    tree: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn new() -> Self {
+        Tree {
+            id: None,
+            entries: Vec::new(),
+        }
+    }
+
+    fn load(repo: &Repository, id: ObjectId) -> Result<Self, Error> {
+        let tree = repo.find_object(id)?.try_into_tree()?;
+        let decoded = tree.decode()?;
+        Ok(Tree::from_decoded(id, decoded))
+    }
+
+    fn position(&self, name: &BStr) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.filename == name)
+    }
+
+    fn upsert(&mut self, repo: &Repository, path: &[&BStr], mode: EntryMode, oid: ObjectId) -> Result<(), Error> {
+        let (name, rest) = path.split_first().expect("paths are never empty");
+        self.id = None;
+        let pos = self.position(name);
+        if rest.is_empty() {
+            match pos {
+                Some(pos) => {
+                    let entry = &mut self.entries[pos];
+                    entry.mode = mode;
+                    entry.oid = oid;
+                    entry.tree = None;
+                }
+                None => self.entries.push(Entry {
+                    mode,
+                    filename: (*name).to_owned(),
+                    oid,
+                    tree: None,
+                }),
+            }
+            return Ok(());
+        }
+
+        let entry = match pos {
+            Some(pos) => {
+                let entry = &mut self.entries[pos];
+                if !entry.mode.is_tree() {
+                    entry.mode = EntryMode::Tree;
+                    entry.tree = Some(Tree::new());
+                }
+                entry
+            }
+            None => {
+                self.entries.push(Entry {
+                    mode: EntryMode::Tree,
+                    filename: (*name).to_owned(),
+                    oid: ObjectId::null(repo.object_hash()),
+                    tree: Some(Tree::new()),
+                });
+                self.entries.last_mut().expect("just pushed")
+            }
+        };
+        let tree = match &mut entry.tree {
+            Some(tree) => tree,
+            None => entry.tree.insert(Tree::load(repo, entry.oid)?),
+        };
+        tree.upsert(repo, rest, mode, oid)
+    }
+
+    /// Return `true` if an entry was removed.
+    fn remove(&mut self, repo: &Repository, path: &[&BStr]) -> Result<bool, Error> {
+        let (name, rest) = path.split_first().expect("paths are never empty");
+        let pos = match self.position(name) {
+            Some(pos) => pos,
+            None => return Ok(false),
+        };
+        let removed = if rest.is_empty() {
+            self.entries.remove(pos);
+            true
+        } else {
+            let entry = &mut self.entries[pos];
+            if !entry.mode.is_tree() {
+                return Ok(false);
+            }
+            let tree = match &mut entry.tree {
+                Some(tree) => tree,
+                None => entry.tree.insert(Tree::load(repo, entry.oid)?),
+            };
+            tree.remove(repo, rest)?
+        };
+        if removed {
+            self.id = None;
+        }
+        Ok(removed)
+    }
+
+    /// Write this tree and all changed trees below it, and return its id, or `None` if it is empty.
+    fn write(&mut self, repo: &Repository) -> Result<Option<ObjectId>, Error> {
+        if let Some(id) = self.id {
+            return Ok(Some(id));
+        }
+        for entry in &mut self.entries {
+            if let Some(tree) = &mut entry.tree {
+                entry.oid = match tree.write(repo)? {
+                    Some(id) => id,
+                    None => ObjectId::null(repo.object_hash()),
+                };
+            }
+        }
+        self.entries
+            .retain(|entry| entry.tree.is_none() || !entry.oid.is_null());
+        if self.entries.is_empty() {
+            return Ok(None);
+        }
+
+        let mut tree = gix_object::Tree {
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| gix_object::tree::Entry {
+                    mode: entry.mode,
+                    filename: entry.filename.clone(),
+                    oid: entry.oid,
+                })
+                .collect(),
+        };
+        tree.entries.sort();
+        let id = repo.write_object(tree)?.detach();
+        self.id = Some(id);
+        Ok(Some(id))
+    }
+}
+
+/// Split `path` into its components, each of which must be valid as name of a tree entry.
+fn components(path: &BStr) -> Result<Vec<&BStr>, Error> {
+    path.split_str("/")
+        .map(|component| {
+            gix_validate::path::component(component.as_bstr()).map_err(|source| Error::InvalidPath {
+                path: path.to_owned(),
+                source,
+            })
+        })
+        .collect()
+}
+
+/// Lifecycle
+impl<'repo> super::Editor<'repo> {
+    pub(super) fn new(repo: &'repo Repository, root: Tree) -> Self {
+        super::Editor { repo, root }
+    }
+}
+
+/// Editing
+impl<'repo> super::Editor<'repo> {
+    /// Insert a new entry with `mode` and `id` at `rela_path`, a `/`-separated path relative to the root tree, or replace
+    /// the existing one.
+    ///
+    /// Trees leading up to the entry are created as needed, while other entries in their way are replaced with trees.
+    /// Note that `id` isn't checked for existence, nor whether its kind matches `mode`.
+    pub fn upsert(
+        &mut self,
+        rela_path: impl AsRef<BStr>,
+        mode: EntryMode,
+        id: impl Into<ObjectId>,
+    ) -> Result<&mut Self, Error> {
+        let path = components(rela_path.as_ref())?;
+        self.root.upsert(self.repo, &path, mode, id.into())?;
+        Ok(self)
+    }
+
+    /// Remove the entry at `rela_path`, a `/`-separated path relative to the root tree, along with everything below it if
+    /// it's a tree. It's not an error if there is no such entry.
+    ///
+    /// Trees that become empty are removed once the tree is [written][Self::write()].
+    pub fn remove(&mut self, rela_path: impl AsRef<BStr>) -> Result<&mut Self, Error> {
+        let path = components(rela_path.as_ref())?;
+        self.root.remove(self.repo, &path)?;
+        Ok(self)
+    }
+
+    /// Write all trees that were changed since they were loaded or last written, and return the id of the root tree.
+    ///
+    /// Trees without any entries are removed from their parent trees, but the root tree is written even if it's empty.
+    /// The editor can be used for further edits afterwards.
+    pub fn write(&mut self) -> Result<Id<'repo>, Error> {
+        Ok(match self.root.write(self.repo)? {
+            Some(id) => id.attach(self.repo),
+            None => self.repo.write_object(gix_object::Tree::empty())?,
+        })
+    }
+}
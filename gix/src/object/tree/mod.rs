@@ -27,6 +27,14 @@ impl<'repo> Tree<'repo> {
         gix_object::TreeRef::from_bytes(&self.data)
     }
 
+    /// Start editing a copy of this tree in memory, to [write][Editor::write()] the changed trees once done.
+    pub fn edit(&self) -> Result<Editor<'repo>, gix_object::decode::Error> {
+        Ok(Editor::new(
+            self.repo,
+            editor::Tree::from_decoded(self.id, self.decode()?),
+        ))
+    }
+
     // TODO: tests.
     /// Follow a sequence of `path` components starting from this instance, and look them up one by one until the last component
     /// is looked up and its tree entry is returned.
@@ -97,6 +105,19 @@ impl<'repo> Tree<'repo> {
 ///
 pub mod diff;
 
+/// An editor to change entries of a tree by path in memory, loading subtrees only as needed, and to write only the trees
+/// that changed.
+///
+/// It's obtained with [`Repository::edit_tree()`][crate::Repository::edit_tree()] or [`Tree::edit()`].
+#[derive(Clone)]
+pub struct Editor<'repo> {
+    repo: &'repo crate::Repository,
+    root: editor::Tree,
+}
+
+///
+pub mod editor;
+
 ///
 pub mod traverse;
 
@@ -159,10 +159,8 @@ impl ThreadSafeRepository {
         let current_dir = current_dir.as_deref().expect("BUG: current_dir must be set by caller");
         let git_dir_trust = git_dir_trust.expect("trust must be determined by now");
 
-        // TODO: assure we handle the worktree-dir properly as we can have config per worktree with an extension.
-        //       This would be something read in later as have to first check for extensions. Also this means
-        //       that each worktree, even if accessible through this instance, has to come in its own Repository instance
-        //       as it may have its own configuration. That's fine actually.
+        // Note that each worktree, even if accessible through this instance, has to come in its own Repository instance
+        // as it may have its own configuration in `config.worktree` if `extensions.worktreeConfig` is set.
         let common_dir = gix_discover::path::from_plain_file(git_dir.join("commondir"))
             .transpose()?
             .map(|cd| git_dir.join(cd));
@@ -177,7 +175,7 @@ impl ThreadSafeRepository {
         )?;
         let mut refs = {
             let reflog = repo_config.reflog.unwrap_or(gix_ref::store::WriteReflog::Disable);
-            let object_hash = repo_config.object_hash;
+            let object_hash = repo_config.extensions.object_format;
             match &common_dir {
                 Some(common_dir) => crate::RefStore::for_linked_worktree(&git_dir, common_dir, reflog, object_hash),
                 None => crate::RefStore::at(&git_dir, reflog, object_hash),
@@ -253,17 +251,32 @@ impl ThreadSafeRepository {
             })
             .unwrap_or_default();
 
+        let objects = OwnShared::new(gix_odb::Store::at_opts(
+            common_dir_ref.join("objects"),
+            replacements,
+            gix_odb::store::init::Options {
+                slots: object_store_slots,
+                object_hash: config.object_hash,
+                use_multi_pack_index: config.use_multi_pack_index,
+                current_dir: current_dir.to_owned().into(),
+            },
+        )?);
+        #[cfg(feature = "blocking-network-client")]
+        if let Some(remote_name) = config.resolved.string_filter(
+            "extensions",
+            None,
+            crate::config::tree::Extensions::PARTIAL_CLONE.name,
+            &mut filter_config_section,
+        ) {
+            objects.set_lazy_fetch(Some(crate::remote::promisor::lazy_fetch(
+                git_dir.clone(),
+                options.clone(),
+                remote_name.into_owned(),
+            )));
+        }
+
         Ok(ThreadSafeRepository {
-            objects: OwnShared::new(gix_odb::Store::at_opts(
-                common_dir_ref.join("objects"),
-                replacements,
-                gix_odb::store::init::Options {
-                    slots: object_store_slots,
-                    object_hash: config.object_hash,
-                    use_multi_pack_index: config.use_multi_pack_index,
-                    current_dir: current_dir.to_owned().into(),
-                },
-            )?),
+            objects,
             common_dir,
             refs,
             work_tree: worktree_dir,
@@ -272,6 +285,7 @@ impl ThreadSafeRepository {
             linked_worktree_options: options,
             index: gix_fs::SharedFileSnapshotMut::new().into(),
             shallow_commits: gix_fs::SharedFileSnapshotMut::new().into(),
+            hooks: Default::default(),
         })
     }
 }
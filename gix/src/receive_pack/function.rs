@@ -0,0 +1,538 @@
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+};
+
+use gix_hash::ObjectId;
+use gix_object::{tree::EntryMode, Kind};
+use gix_protocol::transport::packetline::{encode, Channel, PacketLineRef, StreamingPeekableIter};
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    receive_pack::{invalid_request, quarantine::Quarantine, Command, Error, Hooks, Options, Outcome, Report},
+    upload_pack::{
+        function::{object_format, read_line, text, Line, Lines},
+        refs, Version,
+    },
+    Repository,
+};
+
+pub(crate) fn receive_pack(
+    repo: &Repository,
+    input: impl Read,
+    mut output: impl Write,
+    options: Options,
+    hooks: impl Hooks,
+) -> Result<Outcome, Error> {
+    let lines = StreamingPeekableIter::new(input, &[PacketLineRef::Flush]);
+    let res = serve(repo, lines, &mut output, options, hooks);
+    if let Err(err) = &res {
+        if let Some(message) = err.client_message() {
+            encode::error_to_write(message.as_bytes(), &mut output).ok();
+        }
+    }
+    output.flush()?;
+    res
+}
+
+/// The capabilities the client asked for.
+#[derive(Default)]
+struct Capabilities {
+    report_status: bool,
+    side_band_64k: bool,
+    atomic: bool,
+    push_options: bool,
+}
+
+impl Capabilities {
+    fn parse(&mut self, capabilities: &[u8]) {
+        for capability in capabilities.split_str(" ") {
+            match capability {
+                b"report-status" | b"report-status-v2" => self.report_status = true,
+                b"side-band-64k" => self.side_band_64k = true,
+                b"atomic" => self.atomic = true,
+                b"push-options" => self.push_options = true,
+                _ => {}
+            }
+        }
+    }
+}
+
+const CAPABILITIES: &str =
+    "report-status report-status-v2 delete-refs side-band-64k quiet atomic ofs-delta push-options";
+
+/// The amount of bytes that fit into packets of `side-band-64k`, without the length prefix and band number.
+const SIDEBAND_64K_MAX_DATA: usize = 65520 - 5;
+
+fn serve(
+    repo: &Repository,
+    mut lines: Lines<impl Read>,
+    out: &mut impl Write,
+    options: Options,
+    mut hooks: impl Hooks,
+) -> Result<Outcome, Error> {
+    if !options.stateless_rpc || options.advertise_refs {
+        if options.version == Version::V1 {
+            text(out, "version 1")?;
+        }
+        let refs: Vec<_> = refs::collect(repo)
+            .map_err(Error::ListReferences)?
+            .into_iter()
+            .skip(1)
+            .map(|mut r| {
+                r.peeled = None;
+                r
+            })
+            .collect();
+        let capabilities = format!(
+            "{CAPABILITIES} object-format={} agent={}",
+            object_format(repo.object_hash()),
+            repo.config.user_agent_tuple().1.expect("set")
+        );
+        let shallow = repo.shallow_commits()?;
+        let shallow = shallow.as_ref().map_or(&[][..], |commits| commits.as_slice());
+        refs::advertise(&refs, &capabilities, shallow, repo.object_hash(), &mut *out)?;
+        out.flush()?;
+    }
+    if options.advertise_refs {
+        return Ok(Outcome::default());
+    }
+
+    let mut capabilities = Capabilities::default();
+    let mut commands = Vec::new();
+    loop {
+        let line = match read_line::<Error>(&mut lines)? {
+            Line::Data(line) => line,
+            Line::Stop(_) => break,
+            Line::Eof if commands.is_empty() => break,
+            Line::Eof => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+        };
+        if line.starts_with(b"shallow ") {
+            // The connectivity check rejects updates that would need the client's shallow commits.
+            continue;
+        }
+        let line = match line.find_byte(0) {
+            Some(pos) if commands.is_empty() => {
+                capabilities.parse(&line[pos + 1..]);
+                line[..pos].as_bstr()
+            }
+            _ => line.as_bstr(),
+        };
+        commands.push(parse_command(line)?);
+    }
+    if commands.is_empty() {
+        // The client only wanted to see our refs, or has nothing to push.
+        return Ok(Outcome::default());
+    }
+    let mut push_options = Vec::new();
+    if capabilities.push_options {
+        loop {
+            match read_line::<Error>(&mut lines)? {
+                Line::Data(line) => push_options.push(line),
+                Line::Stop(_) => break,
+                Line::Eof => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+            }
+        }
+    }
+
+    let mut rejections: Vec<Option<BString>> = vec![None; commands.len()];
+    let mut unpack_error = None;
+    let quarantine = if commands.iter().any(|cmd| !cmd.is_delete()) {
+        let quarantine = Quarantine::new(repo)?;
+        if let Err(err) = unpack(repo, lines.into_inner(), &quarantine, options) {
+            unpack_error = Some(err.to_string());
+            for rejection in &mut rejections {
+                *rejection = Some("unpacker error".into());
+            }
+        }
+        Some(quarantine)
+    } else {
+        None
+    };
+    let quarantined = quarantine
+        .as_ref()
+        .map(|quarantine| quarantine.repository(repo))
+        .transpose()?;
+    let quarantined = quarantined.as_ref().unwrap_or(repo);
+
+    let mut boundary = Boundary::new(repo)?;
+    let checked_out = match repo.work_dir() {
+        Some(_) => repo.head_name().ok().flatten().map(|name| name.as_bstr().to_owned()),
+        None => None,
+    };
+    for (cmd, rejection) in commands.iter().zip(rejections.iter_mut()) {
+        if rejection.is_some() {
+            continue;
+        }
+        if checked_out.as_ref().map_or(false, |name| name == cmd.name.as_bstr()) {
+            *rejection = Some(if cmd.is_delete() {
+                "deletion of the current branch prohibited".into()
+            } else {
+                "branch is currently checked out".into()
+            });
+        } else if !cmd.is_delete() && !is_connected(repo, quarantined, &mut boundary, cmd.new) {
+            *rejection = Some("missing necessary objects".into());
+        }
+    }
+    reject_all_if_atomic(&capabilities, &mut rejections);
+
+    let accepted = accepted_commands(&commands, &rejections);
+    if !accepted.is_empty() {
+        if let Err(reason) = hooks.pre_receive(quarantined, &accepted, &push_options) {
+            for rejection in rejections.iter_mut().filter(|rejection| rejection.is_none()) {
+                *rejection = Some(reason.clone());
+            }
+        }
+    }
+    for (cmd, rejection) in commands.iter().zip(rejections.iter_mut()) {
+        if rejection.is_none() {
+            if let Err(reason) = hooks.update(quarantined, cmd) {
+                *rejection = Some(reason);
+            }
+        }
+    }
+    reject_all_if_atomic(&capabilities, &mut rejections);
+
+    let accepted = accepted_commands(&commands, &rejections);
+    if let Some(quarantine) = quarantine {
+        if accepted.iter().any(|cmd| !cmd.is_delete()) {
+            quarantine.migrate()?;
+        }
+    }
+    update_refs(repo, &commands, &mut rejections, capabilities.atomic);
+    let updated = accepted_commands(&commands, &rejections);
+    if !updated.is_empty() {
+        hooks.post_receive(repo, &updated, &push_options);
+    }
+
+    let reports: Vec<_> = commands
+        .into_iter()
+        .zip(rejections)
+        .map(|(command, rejection)| Report { command, rejection })
+        .collect();
+    if capabilities.report_status {
+        let mut status = Vec::new();
+        match &unpack_error {
+            Some(err) => text(&mut status, format!("unpack {err}"))?,
+            None => text(&mut status, "unpack ok")?,
+        }
+        for report in &reports {
+            let mut line = BString::from(match report.rejection {
+                Some(_) => "ng ",
+                None => "ok ",
+            });
+            line.extend_from_slice(report.command.name.as_bstr());
+            if let Some(reason) = &report.rejection {
+                line.push(b' ');
+                line.extend_from_slice(reason);
+            }
+            text(&mut status, line)?;
+        }
+        encode::flush_to_write(&mut status)?;
+        if capabilities.side_band_64k {
+            for chunk in status.chunks(SIDEBAND_64K_MAX_DATA) {
+                encode::band_to_write(Channel::Data, chunk, &mut *out)?;
+            }
+        } else {
+            out.write_all(&status)?;
+        }
+    }
+    if capabilities.side_band_64k {
+        encode::flush_to_write(&mut *out)?;
+    }
+    out.flush()?;
+    Ok(Outcome { reports, push_options })
+}
+
+/// Parse a line like `<old> <new> <name>`.
+fn parse_command(line: &crate::bstr::BStr) -> Result<Command, Error> {
+    let mut tokens = line.splitn_str(3, " ");
+    let mut id = || {
+        tokens
+            .next()
+            .and_then(|hex| ObjectId::from_hex(hex).ok())
+            .ok_or_else(|| invalid_request(format!("invalid command {line:?}")))
+    };
+    let (old, new) = (id()?, id()?);
+    let name = tokens
+        .next()
+        .filter(|name| name.starts_with(b"refs/"))
+        .and_then(|name| FullName::try_from(name.as_bstr()).ok())
+        .ok_or_else(|| invalid_request(format!("invalid ref name in command {line:?}")))?;
+    Ok(Command { name, old, new })
+}
+
+/// Index the pack in `input` into the `quarantine` directory, with bases of thin packs looked up in `repo`.
+fn unpack(
+    repo: &Repository,
+    input: impl Read,
+    quarantine: &Quarantine,
+    options: Options,
+) -> Result<(), gix_pack::bundle::write::Error> {
+    gix_pack::Bundle::write_to_directory(
+        std::io::BufReader::new(input),
+        Some(quarantine.pack_dir()),
+        gix_features::progress::Discard,
+        &crate::interrupt::IS_INTERRUPTED,
+        Some(Box::new({
+            let repo = repo.clone();
+            move |id, buf| {
+                use gix_odb::FindExt;
+                repo.objects.find(id, buf).ok()
+            }
+        })),
+        gix_pack::bundle::write::Options {
+            thread_limit: options.thread_limit,
+            iteration_mode: gix_pack::data::input::Mode::Verify,
+            index_version: Default::default(),
+            object_hash: repo.object_hash(),
+        },
+    )
+    .map(|_| ())
+}
+
+/// The objects of our repository that are known to be complete, as they are reachable from its refs.
+struct Boundary {
+    /// The commits the refs point to.
+    tips: HashSet<ObjectId>,
+    /// All commits reachable from `tips`, computed only when needed as it's expensive.
+    commits: Option<HashSet<ObjectId>>,
+    /// Trees and blobs reachable from the commits that new history was built on, along with these commits.
+    objects: HashSet<ObjectId>,
+}
+
+impl Boundary {
+    fn new(repo: &Repository) -> Result<Self, Error> {
+        use gix_odb::Header;
+        let mut tips = HashSet::new();
+        let mut objects = HashSet::new();
+        for id in refs::collect(repo)
+            .map_err(Error::ListReferences)?
+            .into_iter()
+            .filter_map(|r| r.peeled.or(r.id))
+        {
+            match repo.objects.try_header(id) {
+                Ok(Some(header)) if header.kind() == Kind::Commit => tips.insert(id),
+                _ => objects.insert(id),
+            };
+        }
+        Ok(Boundary {
+            tips,
+            commits: None,
+            objects,
+        })
+    }
+
+    /// Return `true` if the commit `id` is reachable from the refs of `repo`.
+    fn contains_commit(&mut self, repo: &Repository, id: ObjectId) -> bool {
+        use gix_odb::{Find, FindExt};
+        if self.tips.contains(&id) {
+            return true;
+        }
+        if !repo.objects.contains(id) {
+            return false;
+        }
+        let tips = &self.tips;
+        self.commits
+            .get_or_insert_with(|| {
+                let mut buf = Vec::new();
+                let mut commits = HashSet::new();
+                let mut stack: Vec<_> = tips.iter().copied().collect();
+                while let Some(id) = stack.pop() {
+                    if !commits.insert(id) {
+                        continue;
+                    }
+                    if let Ok(commit) = repo.objects.find_commit_iter(id, &mut buf) {
+                        stack.extend(commit.parent_ids());
+                    }
+                }
+                commits
+            })
+            .contains(&id)
+    }
+
+    /// Add all objects reachable from `commit`, which is reachable from our refs, so they don't have to be checked.
+    fn add_objects_of(&mut self, repo: &Repository, commit: ObjectId) {
+        use gix_odb::FindExt;
+        if !self.objects.insert(commit) {
+            return;
+        }
+        let mut buf = Vec::new();
+        let mut stack = match repo.objects.find_commit_iter(commit, &mut buf).map(|mut c| c.tree_id()) {
+            Ok(Ok(tree)) => vec![tree],
+            _ => return,
+        };
+        while let Some(id) = stack.pop() {
+            if !self.objects.insert(id) {
+                continue;
+            }
+            if let Ok(tree) = repo.objects.find_tree_iter(id, &mut buf) {
+                for entry in tree.filter_map(Result::ok) {
+                    match entry.mode {
+                        EntryMode::Tree => stack.push(entry.oid.to_owned()),
+                        EntryMode::Commit => {}
+                        _ => {
+                            self.objects.insert(entry.oid.to_owned());
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Return `true` if all objects reachable from `tip` can be found in `quarantined`, assuming that only the objects
+/// reachable from the refs of `repo` are complete, like `git rev-list --objects <tip> --not --all` does.
+fn is_connected(repo: &Repository, quarantined: &Repository, boundary: &mut Boundary, tip: ObjectId) -> bool {
+    use gix_odb::Find;
+    let mut objects = quarantined.objects.clone();
+    // Missing objects make the pushed history incomplete, they must not be fetched from elsewhere.
+    objects.skip_lazy_fetch = true;
+    let mut buf = Vec::new();
+    let mut seen = HashSet::new();
+
+    // Walk the new commits first to learn which existing commits they build on, as only these mark the end of the
+    // trees and blobs that have to be checked.
+    let mut stack = vec![tip];
+    let mut new_objects = Vec::new();
+    let mut edges = Vec::new();
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        if boundary.contains_commit(repo, id) {
+            edges.push(id);
+            continue;
+        }
+        let object = match objects.try_find(id, &mut buf) {
+            Ok(Some(object)) => object,
+            _ => return false,
+        };
+        match object.kind {
+            Kind::Commit => {
+                let mut commit = gix_object::CommitRefIter::from_bytes(object.data);
+                match commit.tree_id() {
+                    Ok(tree) => new_objects.push(tree),
+                    Err(_) => return false,
+                }
+                stack.extend(commit.parent_ids());
+            }
+            Kind::Tag => match gix_object::TagRefIter::from_bytes(object.data).target_id() {
+                Ok(target) => stack.push(target),
+                Err(_) => return false,
+            },
+            Kind::Tree | Kind::Blob => new_objects.push(id),
+        }
+    }
+    for edge in edges {
+        boundary.add_objects_of(repo, edge);
+    }
+
+    let mut stack = new_objects;
+    seen.clear();
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id) || boundary.objects.contains(&id) {
+            continue;
+        }
+        let object = match objects.try_find(id, &mut buf) {
+            Ok(Some(object)) => object,
+            _ => return false,
+        };
+        match object.kind {
+            Kind::Tree => {
+                for entry in gix_object::TreeRefIter::from_bytes(object.data) {
+                    match entry {
+                        Ok(entry) if entry.mode == EntryMode::Commit => {}
+                        Ok(entry) => stack.push(entry.oid.to_owned()),
+                        Err(_) => return false,
+                    }
+                }
+            }
+            Kind::Blob => {}
+            Kind::Commit | Kind::Tag => return false,
+        }
+    }
+    true
+}
+
+fn accepted_commands(commands: &[Command], rejections: &[Option<BString>]) -> Vec<Command> {
+    commands
+        .iter()
+        .zip(rejections)
+        .filter(|(_, rejection)| rejection.is_none())
+        .map(|(cmd, _)| cmd.clone())
+        .collect()
+}
+
+/// Reject all commands if one of them was rejected and the client wants all or nothing, like `git` does.
+fn reject_all_if_atomic(capabilities: &Capabilities, rejections: &mut [Option<BString>]) {
+    if capabilities.atomic && rejections.iter().any(Option::is_some) {
+        for rejection in rejections.iter_mut().filter(|rejection| rejection.is_none()) {
+            *rejection = Some("atomic push failure".into());
+        }
+    }
+}
+
+/// Apply all accepted commands in a single transaction if `atomic` is set, or in one transaction per ref otherwise.
+fn update_refs(repo: &Repository, commands: &[Command], rejections: &mut [Option<BString>], atomic: bool) {
+    let mut edits = Vec::new();
+    for (index, (cmd, rejection)) in commands.iter().zip(rejections.iter()).enumerate() {
+        if rejection.is_some() {
+            continue;
+        }
+        let expected = if cmd.old.is_null() {
+            if cmd.is_delete() {
+                // Like `git`, we treat deleting a ref that doesn't exist as success.
+                continue;
+            }
+            PreviousValue::MustNotExist
+        } else {
+            PreviousValue::MustExistAndMatch(Target::Peeled(cmd.old))
+        };
+        let change = if cmd.is_delete() {
+            Change::Delete {
+                expected,
+                log: RefLog::AndReference,
+            }
+        } else {
+            Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: "push".into(),
+                },
+                expected,
+                new: Target::Peeled(cmd.new),
+            }
+        };
+        edits.push((
+            index,
+            RefEdit {
+                change,
+                name: cmd.name.clone(),
+                deref: false,
+            },
+        ));
+    }
+
+    if atomic {
+        if repo
+            .edit_references(edits.iter().map(|(_, edit)| edit.clone()))
+            .is_err()
+        {
+            for (index, _) in edits {
+                rejections[index] = Some("failed to update ref".into());
+            }
+        }
+    } else {
+        for (index, edit) in edits {
+            if repo.edit_reference(edit).is_err() {
+                rejections[index] = Some("failed to update ref".into());
+            }
+        }
+    }
+}
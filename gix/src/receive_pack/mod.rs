@@ -0,0 +1,173 @@
+//! Accept pushes of `git` clients similar to `git receive-pack`, using the pack protocol in version 0 or 1.
+//!
+//! The incoming pack is indexed into a quarantine object directory first, which is only moved into the object database once
+//! the pushed history was found to be complete. Ref updates can be accepted or rejected by [`Hooks`], which see
+//! the quarantined objects.
+//!
+//! ### Deviation
+//!
+//! * `receive.*` configuration is ignored. In particular, non-fast-forward updates and deletions are always allowed,
+//!   but the checked out branch of repositories with a worktree is never updated or deleted.
+//! * Objects that are already in the object database are assumed to have all objects they refer to as well when checking
+//!   the connectivity of the pushed history.
+//! * Signed pushes and updates of shallow commits aren't supported, nor are hooks run by `git` like `pre-receive`.
+//!   Their equivalents can be implemented with [`Hooks`].
+use gix_hash::ObjectId;
+use gix_ref::FullName;
+
+use crate::{bstr::BString, Repository};
+
+mod quarantine;
+
+pub(crate) mod function;
+
+/// Options for use in [`Repository::receive_pack()`][crate::Repository::receive_pack()].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The version of the protocol to serve, with [`V2`][crate::upload_pack::Version::V2] falling back to
+    /// [`V0`][crate::upload_pack::Version::V0] as pushes aren't supported by it, just like in `git`.
+    pub version: crate::upload_pack::Version,
+    /// If `true`, serve a single request and return, without advertising refs and capabilities first, similar to
+    /// `git receive-pack --stateless-rpc`.
+    pub stateless_rpc: bool,
+    /// If `true`, only advertise refs and capabilities and return, similar to `git receive-pack --advertise-refs`.
+    pub advertise_refs: bool,
+    /// The amount of threads to use when indexing the received pack, or `None` to use all logical cores.
+    pub thread_limit: Option<usize>,
+}
+
+/// A request of the client to change a ref.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Command {
+    /// The full name of the ref to change.
+    pub name: FullName,
+    /// The object the client expects the ref to point to, or the null id if it expects the ref not to exist.
+    pub old: ObjectId,
+    /// The object the ref should point to, or the null id if the ref should be deleted.
+    pub new: ObjectId,
+}
+
+impl Command {
+    /// Return `true` if the ref should be created.
+    pub fn is_create(&self) -> bool {
+        self.old.is_null() && !self.new.is_null()
+    }
+
+    /// Return `true` if the ref should be deleted.
+    pub fn is_delete(&self) -> bool {
+        self.new.is_null()
+    }
+}
+
+/// The outcome of a single [`Command`], as reported to the client.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Report {
+    /// The command the client sent.
+    pub command: Command,
+    /// The reason for rejecting the command, or `None` if the ref was changed.
+    pub rejection: Option<BString>,
+}
+
+/// The result of [`Repository::receive_pack()`][crate::Repository::receive_pack()].
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Outcome {
+    /// The outcome of each command in the order the client sent them, which is empty if the client didn't want to change anything.
+    pub reports: Vec<Report>,
+    /// The options the client sent along with its commands via `git push --push-option`.
+    pub push_options: Vec<BString>,
+}
+
+/// Callbacks to decide which ref updates to accept, similar to the hooks run by `git receive-pack`.
+///
+/// All methods do nothing by default, and `()` can be used if no ref update should be rejected.
+/// The `repo` passed to [`pre_receive()`][Hooks::pre_receive()] and [`update()`][Hooks::update()] can access the received
+/// objects, which are discarded if no ref is updated.
+pub trait Hooks {
+    /// Return `Err(reason)` to reject all `commands` for `reason`, after they were validated but before any ref is changed.
+    ///
+    /// This is the equivalent of the `pre-receive` hook.
+    fn pre_receive(
+        &mut self,
+        repo: &Repository,
+        commands: &[Command],
+        push_options: &[BString],
+    ) -> Result<(), BString> {
+        let _ = (repo, commands, push_options);
+        Ok(())
+    }
+
+    /// Return `Err(reason)` to reject `command` for `reason`, which is called for each command that passed
+    /// [`pre_receive()`][Hooks::pre_receive()].
+    ///
+    /// This is the equivalent of the `update` hook.
+    fn update(&mut self, repo: &Repository, command: &Command) -> Result<(), BString> {
+        let _ = (repo, command);
+        Ok(())
+    }
+
+    /// Called with the `commands` that changed refs after all changes were made.
+    ///
+    /// This is the equivalent of the `post-receive` hook.
+    fn post_receive(&mut self, repo: &Repository, commands: &[Command], push_options: &[BString]) {
+        let _ = (repo, commands, push_options);
+    }
+}
+
+impl Hooks for () {}
+
+impl<T: Hooks + ?Sized> Hooks for &mut T {
+    fn pre_receive(
+        &mut self,
+        repo: &Repository,
+        commands: &[Command],
+        push_options: &[BString],
+    ) -> Result<(), BString> {
+        (**self).pre_receive(repo, commands, push_options)
+    }
+
+    fn update(&mut self, repo: &Repository, command: &Command) -> Result<(), BString> {
+        (**self).update(repo, command)
+    }
+
+    fn post_receive(&mut self, repo: &Repository, commands: &[Command], push_options: &[BString]) {
+        (**self).post_receive(repo, commands, push_options)
+    }
+}
+
+/// The error returned by [`Repository::receive_pack()`][crate::Repository::receive_pack()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read from or write to the client")]
+    Io(#[from] std::io::Error),
+    #[error("The client sent an invalid packet line")]
+    DecodePacketLine(#[from] gix_protocol::transport::packetline::decode::Error),
+    #[error("The client sent an invalid request: {message}")]
+    InvalidRequest { message: BString },
+    #[error("Could not list the references to advertise")]
+    ListReferences(#[source] crate::upload_pack::Error),
+    #[error(transparent)]
+    ShallowCommits(#[from] crate::shallow::open::Error),
+    #[error("Could not create the quarantine directory for received objects")]
+    CreateQuarantine(#[source] std::io::Error),
+    #[error("Could not open the quarantine directory for received objects")]
+    OpenQuarantine(#[source] std::io::Error),
+    #[error("Could not move the received objects from the quarantine directory into the object database")]
+    MigrateQuarantine(#[source] std::io::Error),
+}
+
+impl Error {
+    /// Return the message to send to the client, which is only done for errors caused by the client.
+    fn client_message(&self) -> Option<String> {
+        match self {
+            Error::InvalidRequest { .. } => Some(format!("receive-pack: {self}")),
+            _ => None,
+        }
+    }
+}
+
+fn invalid_request(message: impl Into<BString>) -> Error {
+    Error::InvalidRequest {
+        message: message.into(),
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{receive_pack::Error, Repository};
+
+/// A directory within the object database to keep received objects in until they are known to be needed, similar to
+/// the `incoming-*` directories of `git`.
+///
+/// It's removed when dropped.
+pub(super) struct Quarantine {
+    dir: PathBuf,
+    objects_dir: PathBuf,
+}
+
+impl Quarantine {
+    /// Create a new quarantine directory in the object database of `repo`, which uses it as alternate.
+    pub fn new(repo: &Repository) -> Result<Self, Error> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let objects_dir = repo.objects.store_ref().path().to_owned();
+        let dir = objects_dir.join(format!(
+            "incoming-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        std::fs::create_dir(&dir).map_err(Error::CreateQuarantine)?;
+        let quarantine = Quarantine { dir, objects_dir };
+        (|| -> std::io::Result<()> {
+            std::fs::create_dir(quarantine.pack_dir())?;
+            std::fs::create_dir(quarantine.dir.join("info"))?;
+            let objects_dir = gix_path::realpath(&quarantine.objects_dir)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+            let mut alternates = gix_path::into_bstr(objects_dir).into_owned();
+            alternates.push(b'\n');
+            std::fs::write(quarantine.dir.join("info").join("alternates"), alternates)
+        })()
+        .map_err(Error::CreateQuarantine)?;
+        Ok(quarantine)
+    }
+
+    /// The directory to write received packs to.
+    pub fn pack_dir(&self) -> PathBuf {
+        self.dir.join("pack")
+    }
+
+    /// Return a copy of `repo` that sees the objects in quarantine along with all of its own.
+    pub fn repository(&self, repo: &Repository) -> Result<Repository, Error> {
+        let mut quarantined = repo.clone();
+        quarantined.objects = gix_odb::at_opts(
+            &self.dir,
+            Vec::new(),
+            gix_odb::store::init::Options {
+                object_hash: repo.object_hash(),
+                ..Default::default()
+            },
+        )
+        .map_err(Error::OpenQuarantine)?;
+        Ok(quarantined)
+    }
+
+    /// Move all received packs into the object database, with their indices last so they are never seen without their data.
+    ///
+    /// `.keep` files aren't moved, as the objects will be referenced once refs are updated.
+    pub fn migrate(self) -> Result<(), Error> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(self.pack_dir()).map_err(Error::MigrateQuarantine)? {
+            let path = entry.map_err(Error::MigrateQuarantine)?.path();
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("keep") => {}
+                Some("idx") => files.push((1, path)),
+                _ => files.push((0, path)),
+            }
+        }
+        files.sort();
+        let pack_dir = self.objects_dir.join("pack");
+        for (_, path) in files {
+            let file_name = path.file_name().expect("read from directory");
+            move_file(&path, &pack_dir.join(file_name)).map_err(Error::MigrateQuarantine)?;
+        }
+        Ok(())
+    }
+}
+
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if to.exists() {
+        // We have this pack already.
+        return std::fs::remove_file(from);
+    }
+    std::fs::rename(from, to)
+}
+
+impl Drop for Quarantine {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
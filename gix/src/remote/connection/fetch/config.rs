@@ -1,6 +1,11 @@
 use super::Error;
 use crate::{
-    config::{cache::util::ApplyLeniency, tree::Pack},
+    bstr::BStr,
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{Pack, Remote},
+    },
+    remote::fetch::Filter,
     Repository,
 };
 
@@ -24,3 +29,35 @@ pub fn pack_index_version(repo: &Repository) -> Result<gix_pack::index::Version,
         .with_leniency(repo.options.lenient_config)?
         .unwrap_or(gix_pack::index::Version::V2))
 }
+
+pub fn partial_clone_filter(repo: &Repository, remote_name: &BStr) -> Result<Option<Filter>, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .string_filter(
+            "remote",
+            Some(remote_name),
+            Remote::PARTIAL_CLONE_FILTER.name,
+            &mut repo.filter_config_section(),
+        )
+        .map(|spec| Remote::PARTIAL_CLONE_FILTER.try_into_filter(spec))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)?)
+}
+
+pub fn is_promisor(repo: &Repository, remote_name: &BStr) -> Result<bool, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .boolean_filter(
+            "remote",
+            Some(remote_name),
+            Remote::PROMISOR.name,
+            &mut repo.filter_config_section(),
+        )
+        .map(|value| Remote::PROMISOR.enrich_error(value))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)
+        .map_err(Error::PromisorConfig)?
+        .unwrap_or_default())
+}
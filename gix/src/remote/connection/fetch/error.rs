@@ -43,6 +43,15 @@ pub enum Error {
     RejectShallowRemoteConfig(#[from] config::boolean::Error),
     #[error("Receiving objects from shallow remotes is prohibited due to the value of `clone.rejectShallow`")]
     RejectShallowRemote,
+    #[error("The filter to use for fetching only some objects could not be obtained from configuration")]
+    PartialCloneFilter(#[from] config::key::GenericErrorWithValue<crate::remote::fetch::filter::Error>),
+    #[error("Could not obtain configuration to learn if the remote is a promisor remote")]
+    PromisorConfig(#[source] config::boolean::Error),
+    #[error("Failed to write .promisor file at \"{}\"", path.display())]
+    WritePromisorFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
 }
 
 impl gix_protocol::transport::IsSpuriousError for Error {
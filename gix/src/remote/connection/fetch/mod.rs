@@ -150,6 +150,7 @@ where
             reflog_message: None,
             write_packed_refs: WritePackedRefs::Never,
             shallow: Default::default(),
+            filter: None,
         })
     }
 }
@@ -181,6 +182,7 @@ where
     reflog_message: Option<RefLogMessage>,
     write_packed_refs: WritePackedRefs,
     shallow: remote::fetch::Shallow,
+    filter: Option<remote::fetch::Filter>,
 }
 
 /// Builder
@@ -222,6 +224,15 @@ where
         self.shallow = shallow;
         self
     }
+
+    /// Ask the remote to omit the objects described by `filter`, which makes it a promisor remote that is expected to provide
+    /// omitted objects on demand.
+    ///
+    /// If unset, the `remote.<name>.partialCloneFilter` configuration of named remotes is used.
+    pub fn with_filter(mut self, filter: remote::fetch::Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
 }
 
 impl<'remote, 'repo, T> Drop for Prepare<'remote, 'repo, T>
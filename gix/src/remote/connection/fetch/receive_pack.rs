@@ -62,6 +62,8 @@ where
     /// ### Configuration
     ///
     /// - `gitoxide.userAgent` is read to obtain the application user agent for git servers and for HTTP servers as well.
+    /// - `remote.<name>.partialCloneFilter` is used as [filter][Prepare::with_filter()] if none was set.
+    /// - `remote.<name>.promisor` causes received packs to be marked with a `.promisor` file, which also happens if a filter is used.
     ///
     #[gix_protocol::maybe_async::maybe_async]
     pub async fn receive<P>(mut self, mut progress: P, should_interrupt: &AtomicBool) -> Result<Outcome, Error>
@@ -98,6 +100,20 @@ where
             arguments.use_include_tag();
         }
         let (shallow_commits, mut shallow_lock) = add_shallow_args(&mut arguments, &self.shallow, repo)?;
+        let remote_name = con.remote.name().map(|name| name.as_bstr());
+        let filter = match (self.filter, remote_name) {
+            (Some(filter), _) => Some(filter),
+            (None, Some(name)) => config::partial_clone_filter(repo, name)?,
+            (None, None) => None,
+        };
+        if filter.is_some() && !arguments.can_use_filter() {
+            return Err(Error::MissingServerFeature {
+                feature: "filter",
+                description:
+                    "partial clones need server support to omit objects, and we won't receive all of them instead",
+            });
+        }
+        let is_promisor = filter.is_some() || remote_name.map_or(Ok(false), |name| config::is_promisor(repo, name))?;
 
         let mut previous_response = None::<gix_protocol::fetch::Response>;
         let mut round = 1;
@@ -148,6 +164,12 @@ where
                     return Err(err.into());
                 }
             };
+            if round == 1 {
+                if let Some(filter) = &filter {
+                    // It's added only now as V1 servers expect the `filter` capability to be requested first, with the first `want`.
+                    arguments.filter(&filter.to_string());
+                }
+            }
             round += 1;
             let mut reader = arguments.send(&mut con.transport, is_done).await?;
             if sideband_all {
@@ -216,6 +238,12 @@ where
             gix_protocol::indicate_end_of_interaction(&mut con.transport).await.ok();
         }
 
+        if is_promisor {
+            if let Some(data_path) = write_pack_bundle.as_ref().and_then(|bundle| bundle.data_path.as_ref()) {
+                write_promisor_file(data_path, &self.ref_map)?;
+            }
+        }
+
         if let Some(shallow_lock) = shallow_lock {
             if !previous_response.shallow_updates().is_empty() {
                 crate::shallow::write(shallow_lock, shallow_commits, previous_response.shallow_updates())?;
@@ -256,6 +284,21 @@ where
     }
 }
 
+/// Mark the pack at `data_path` as received from a promisor remote, which means that objects it refers to are expected to be
+/// missing and can be obtained from the remote, listing the refs it was fetched for like `git` does.
+fn write_promisor_file(data_path: &std::path::Path, ref_map: &fetch::RefMap) -> Result<(), Error> {
+    let mut refs = Vec::new();
+    for mapping in &ref_map.mappings {
+        if let (Some(id), Some(name)) = (mapping.remote.as_id(), mapping.remote.as_name()) {
+            refs.extend_from_slice(format!("{id} ").as_bytes());
+            refs.extend_from_slice(name);
+            refs.push(b'\n');
+        }
+    }
+    let path = data_path.with_extension("promisor");
+    std::fs::write(&path, refs).map_err(|source| Error::WritePromisorFile { path, source })
+}
+
 fn acquire_shallow_lock(repo: &Repository) -> Result<gix_lock::File, Error> {
     gix_lock::File::acquire_to_update_resource(repo.shallow_file(), gix_lock::acquire::Fail::Immediately, None)
         .map_err(Into::into)
@@ -231,7 +231,7 @@ pub(crate) fn update(
                 .config
                 .lock_timeout()
                 .map_err(crate::reference::edit::Error::from)?;
-            repo.refs
+            let transaction = repo.refs
                 .transaction()
                 .packed_refs(
                     match write_packed_refs {
@@ -246,9 +246,13 @@ pub(crate) fn update(
                     }
                 )
                 .prepare(edits, file_lock_fail, packed_refs_lock_fail)
-                .map_err(crate::reference::edit::Error::from)?
-                .commit(repo.committer().transpose().map_err(|err| update::Error::EditReferences(crate::reference::edit::Error::ParseCommitterTime(err)))?)
-                .map_err(crate::reference::edit::Error::from)?
+                .map_err(crate::reference::edit::Error::from)?;
+            repo.commit_ref_transaction(
+                transaction,
+                repo.committer().transpose().map_err(|err| {
+                    update::Error::EditReferences(crate::reference::edit::Error::ParseCommitterTime(err))
+                })?,
+            )?
         }
         fetch::DryRun::Yes => edits,
     };
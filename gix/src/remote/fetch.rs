@@ -1,3 +1,5 @@
+use crate::bstr::{BStr, ByteSlice};
+
 /// If `Yes`, don't really make changes but do as much as possible to get an idea of what would be done.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
@@ -98,6 +100,74 @@ impl Shallow {
     }
 }
 
+/// Describe which objects the remote should omit when fetching, as in `git fetch --filter`, to make a *partial clone*.
+///
+/// Omitted objects are fetched on demand from the remote that promised to have them, the *promisor remote*.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Omit all blobs, as in `blob:none`.
+    BlobNone,
+    /// Omit all blobs which are at least as large as the given amount of bytes, as in `blob:limit=<bytes>`.
+    BlobLimit(u64),
+    /// Omit all trees and blobs whose depth from the root tree of a commit is at least the given depth, as in `tree:<depth>`.
+    ///
+    /// `tree:0` thus omits all trees and blobs.
+    TreeDepth(u64),
+    /// Omit all objects which are not of the given kind, as in `object:type=<kind>`.
+    ObjectType(gix_object::Kind),
+}
+
+impl Filter {
+    /// Parse `spec` as used with `git fetch --filter=<spec>`, with `combine:` and `sparse:oid=` filters being unsupported.
+    pub fn from_spec(spec: &BStr) -> Result<Self, filter::Error> {
+        fn parse(spec: &BStr) -> Option<Filter> {
+            let spec = spec.to_str().ok()?;
+            Some(if spec == "blob:none" {
+                Filter::BlobNone
+            } else if let Some(limit) = spec.strip_prefix("blob:limit=") {
+                let (number, factor) = match limit.as_bytes().last()?.to_ascii_lowercase() {
+                    b'k' => (&limit[..limit.len() - 1], 1024),
+                    b'm' => (&limit[..limit.len() - 1], 1024 * 1024),
+                    b'g' => (&limit[..limit.len() - 1], 1024 * 1024 * 1024),
+                    _ => (limit, 1),
+                };
+                Filter::BlobLimit(number.parse::<u64>().ok()?.checked_mul(factor)?)
+            } else if let Some(depth) = spec.strip_prefix("tree:") {
+                Filter::TreeDepth(depth.parse().ok()?)
+            } else if let Some(kind) = spec.strip_prefix("object:type=") {
+                Filter::ObjectType(gix_object::Kind::from_bytes(kind.as_bytes()).ok()?)
+            } else {
+                return None;
+            })
+        }
+        parse(spec).ok_or_else(|| filter::Error { spec: spec.to_owned() })
+    }
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::BlobNone => f.write_str("blob:none"),
+            Filter::BlobLimit(bytes) => write!(f, "blob:limit={bytes}"),
+            Filter::TreeDepth(depth) => write!(f, "tree:{depth}"),
+            Filter::ObjectType(kind) => write!(f, "object:type={kind}"),
+        }
+    }
+}
+
+///
+pub mod filter {
+    use crate::bstr::BString;
+
+    /// The error returned by [`Filter::from_spec()`][super::Filter::from_spec()].
+    #[derive(Debug, thiserror::Error)]
+    #[error("The object filter specification \"{spec}\" is invalid or unsupported")]
+    pub struct Error {
+        /// The specification that couldn't be parsed.
+        pub spec: BString,
+    }
+}
+
 /// Information about the relationship between our refspecs, and remote references with their local counterparts.
 #[derive(Default, Debug, Clone)]
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
@@ -54,6 +54,9 @@ mod connection;
 #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
 pub use connection::{ref_map, AuthenticateFn, Connection};
 
+#[cfg(feature = "blocking-network-client")]
+pub(crate) mod promisor;
+
 ///
 pub mod save;
 
@@ -0,0 +1,85 @@
+use std::{
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Arc, Mutex},
+};
+
+use gix_odb::Find;
+
+use crate::{
+    bstr::{BStr, BString},
+    remote,
+};
+
+/// The error returned when lazily fetching objects in partial clones.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Open(#[from] crate::open::Error),
+    #[error(transparent)]
+    FindRemote(#[from] remote::find::existing::Error),
+    #[error(transparent)]
+    Connect(#[from] remote::connect::Error),
+    #[error(transparent)]
+    PrepareFetch(#[from] remote::fetch::prepare::Error),
+    #[error(transparent)]
+    Fetch(#[from] remote::fetch::Error),
+    #[error("Could not remove the .keep file of the fetched pack")]
+    RemovePackKeepFile(#[source] std::io::Error),
+}
+
+/// Return a function to fetch missing objects into the repository at `git_dir` from the remote named `remote_name`,
+/// opening it with `options` each time.
+///
+/// Fetches are performed one at a time and without tags, and no further object is fetched lazily while fetching, similar to
+/// what `git` does.
+pub(crate) fn lazy_fetch(
+    git_dir: PathBuf,
+    options: crate::open::Options,
+    remote_name: BString,
+) -> Arc<gix_odb::store::LazyFetch> {
+    let one_at_a_time = Mutex::new(());
+    Arc::new(move |ids| {
+        let _guard = one_at_a_time.lock().unwrap_or_else(|err| err.into_inner());
+        fetch(git_dir.clone(), options.clone(), remote_name.as_ref(), ids).map_err(Into::into)
+    })
+}
+
+fn fetch(
+    git_dir: PathBuf,
+    options: crate::open::Options,
+    remote_name: &BStr,
+    ids: &[gix_hash::ObjectId],
+) -> Result<(), Error> {
+    let repo = crate::ThreadSafeRepository::open_opts(git_dir, options)?.to_thread_local();
+    repo.objects.store_ref().set_lazy_fetch(None);
+    let missing: Vec<_> = ids.iter().filter(|id| !repo.objects.contains(id)).collect();
+    if missing.is_empty() {
+        // Another fetch brought them in the meantime.
+        return Ok(());
+    }
+
+    let mut remote = repo
+        .find_remote(remote_name)?
+        .with_fetch_tags(remote::fetch::Tags::None);
+    remote.fetch_specs = missing
+        .iter()
+        .map(|id| {
+            gix_refspec::parse(id.to_string().as_str().into(), gix_refspec::parse::Operation::Fetch)
+                .expect("object ids are valid refspecs")
+                .to_owned()
+        })
+        .collect();
+    let outcome = remote
+        .connect(remote::Direction::Fetch)?
+        .prepare_fetch(gix_features::progress::Discard, Default::default())?
+        .with_filter(remote::fetch::Filter::BlobNone)
+        .receive(gix_features::progress::Discard, &AtomicBool::default())?;
+    if let remote::fetch::Status::Change { write_pack_bundle, .. } = outcome.status {
+        // No ref points to the objects, they are only reachable via existing objects.
+        if let Some(keep_path) = write_pack_bundle.keep_path {
+            std::fs::remove_file(keep_path).map_err(Error::RemovePackKeepFile)?;
+        }
+    }
+    Ok(())
+}
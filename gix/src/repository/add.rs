@@ -0,0 +1,21 @@
+#![allow(clippy::result_large_err)]
+use crate::{add, bstr::BStr, Repository};
+
+impl Repository {
+    /// Stage the files in the working tree that match `pathspecs` in the index, similar to `git add --all <pathspec>…`.
+    ///
+    /// Pathspecs are relative to the root of the working tree, and if there are none, all files are considered.
+    /// New files are added unless they are ignored, changed files are updated and the entries of files that were removed
+    /// are removed, with `options` controlling each of these. File contents pass through the `clean` program of their
+    /// filter driver and are converted according to the `text`, `eol` and `ident` attributes and `core.autocrlf`
+    /// before they are written to the object database.
+    ///
+    /// The index is locked while it's read and changed, and written only if entries were changed.
+    pub fn add_to_index(
+        &self,
+        pathspecs: impl IntoIterator<Item = impl AsRef<BStr>>,
+        options: add::Options,
+    ) -> Result<add::Outcome, add::Error> {
+        add::function::add(self, pathspecs, options)
+    }
+}
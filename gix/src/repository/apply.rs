@@ -0,0 +1,106 @@
+#![allow(clippy::result_large_err)]
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{apply, commit, Repository};
+
+impl Repository {
+    /// Apply the changes of all `files` of a patch to `target`, similar to `git apply`.
+    ///
+    /// Files are read and written as they are, without applying filters or line-ending conversions.
+    /// If hunks don't apply, they are [reported][apply::Outcome::rejected], and nothing is changed unless
+    /// [partial application][apply::Options::partial] is enabled.
+    pub fn apply_patch(
+        &self,
+        files: &[apply::patch::File],
+        target: apply::Target,
+        options: apply::Options,
+    ) -> Result<apply::Outcome, apply::Error> {
+        apply::function::apply(self, files, target, options)
+    }
+
+    /// Apply the patches of all `messages` in order and create a commit for each of them on top of `HEAD`, similar to
+    /// `git am`, using the author of the message and the configured committer.
+    ///
+    /// If there is a working tree, the index and the working tree are updated as well, which requires the affected files
+    /// to match between both. Application stops at the first message with hunks that don't apply, leaving everything
+    /// as it was after the previous message, so [partial application][apply::Options::partial] is never performed.
+    pub fn apply_mailbox(
+        &self,
+        messages: &[apply::mailbox::Message],
+        options: apply::Options,
+    ) -> Result<apply::mailbox::Outcome, apply::mailbox::apply::Error> {
+        let options = apply::Options {
+            partial: false,
+            ..options
+        };
+        let mut commits = Vec::new();
+        for (index, message) in messages.iter().enumerate() {
+            let apply_err = |source| apply::mailbox::apply::Error::Apply { message: index, source };
+            let parent = self.head()?.peel_to_id_in_place().transpose()?.map(crate::Id::detach);
+            let base = parent.unwrap_or_else(|| gix_hash::ObjectId::empty_tree(self.object_hash()));
+            let outcome = self
+                .apply_patch(&message.files, apply::Target::Tree(base), options)
+                .map_err(apply_err)?;
+            let tree = match outcome.tree {
+                Some(tree) if outcome.applied => tree,
+                _ => {
+                    return Ok(apply::mailbox::Outcome {
+                        commits,
+                        rejected: Some((index, outcome.rejected)),
+                    })
+                }
+            };
+            if self.work_dir().is_some() {
+                let outcome = self
+                    .apply_patch(&message.files, apply::Target::IndexAndWorkTree, options)
+                    .map_err(apply_err)?;
+                if !outcome.applied {
+                    return Ok(apply::mailbox::Outcome {
+                        commits,
+                        rejected: Some((index, outcome.rejected)),
+                    });
+                }
+            }
+
+            let committer = self
+                .committer()
+                .ok_or(commit::Error::CommitterMissing)?
+                .map_err(commit::Error::from)?;
+            let commit = gix_object::Commit {
+                message: message.commit_message(),
+                tree,
+                author: message.author.clone(),
+                committer: committer.to_owned(),
+                encoding: None,
+                parents: parent.into_iter().collect(),
+                extra_headers: Default::default(),
+            };
+            let commit_id = self.write_object(&commit).map_err(commit::Error::from)?.detach();
+            self.edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: format!("am: {}", message.subject).into(),
+                    },
+                    expected: match parent {
+                        Some(parent) => PreviousValue::MustExistAndMatch(Target::Peeled(parent)),
+                        None => PreviousValue::MustNotExist,
+                    },
+                    new: Target::Peeled(commit_id),
+                },
+                name: "HEAD".try_into().expect("valid"),
+                deref: true,
+            })
+            .map_err(commit::Error::from)?;
+            commits.push(commit_id);
+        }
+        Ok(apply::mailbox::Outcome {
+            commits,
+            rejected: None,
+        })
+    }
+}
@@ -0,0 +1,12 @@
+use crate::{blame, bstr::BString, Repository};
+
+impl Repository {
+    /// Prepare to attribute each line of the file at `path`, as seen in `commit`, to the commit that introduced it,
+    /// similar to `git blame`.
+    ///
+    /// `path` is relative to the root of the repository and uses slashes as separators.
+    /// The returned platform can be configured further before [computing][blame::Platform::compute()] the blame.
+    pub fn blame_file(&self, path: impl Into<BString>, commit: impl Into<gix_hash::ObjectId>) -> blame::Platform<'_> {
+        blame::Platform::new(path.into(), commit.into(), self)
+    }
+}
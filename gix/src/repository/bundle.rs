@@ -0,0 +1,134 @@
+use crate::{bundle, Repository};
+
+impl Repository {
+    /// Write a bundle with all objects reachable from the given revision `specs` to `out`, similar to `git bundle create`.
+    ///
+    /// Each spec is a single revision like `main` or `v1.0`, whose objects will be included, an exclusion like `^v0.9` or
+    /// a range like `v0.9..main`. Commits which are excluded but are parents of included commits become prerequisites of
+    /// the bundle, which have to be present in repositories to unbundle it.
+    ///
+    /// Only the references among included revisions, like `refs/heads/main` for `main`, are stored in the bundle.
+    /// Return the header of the written bundle.
+    ///
+    /// ### Deviation
+    ///
+    /// The pack is never thin, which makes bundles with prerequisites larger than the ones created by `git`.
+    #[cfg(feature = "blocking-network-client")]
+    pub fn create_bundle(
+        &self,
+        specs: impl IntoIterator<Item = impl AsRef<crate::bstr::BStr>>,
+        mut out: impl std::io::Write,
+        options: bundle::create::Options,
+    ) -> Result<bundle::Header, bundle::create::Error> {
+        use std::collections::HashSet;
+
+        use crate::upload_pack::{graph, pack};
+
+        let mut refs = Vec::new();
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+        for spec in specs {
+            let spec = spec.as_ref();
+            let revision = self.rev_parse(spec)?;
+            let (first_ref, second_ref) = (
+                revision.first_reference().map(|r| r.name.as_bstr().to_owned()),
+                revision.second_reference().map(|r| r.name.as_bstr().to_owned()),
+            );
+            let (id, name) = match revision.detach() {
+                gix_revision::Spec::Include(id) => (id, first_ref),
+                gix_revision::Spec::Range { from, to } => {
+                    exclude.push(from);
+                    (to, second_ref)
+                }
+                gix_revision::Spec::Exclude(id) => {
+                    exclude.push(id);
+                    continue;
+                }
+                _ => return Err(bundle::create::Error::UnsupportedRevision { spec: spec.into() }),
+            };
+            include.push(id);
+            if let Some(name) = name {
+                if !refs.iter().any(|r: &bundle::Ref| r.name == name) {
+                    refs.push(bundle::Ref { id, name });
+                }
+            }
+        }
+        if refs.is_empty() {
+            return Err(bundle::create::Error::Empty);
+        }
+
+        let wants = pack::Wants::peel(self, &include).map_err(bundle::create::Error::Pack)?;
+        let excluded = pack::Wants::peel(self, &exclude).map_err(bundle::create::Error::Pack)?;
+        let mut graph = graph::Graph::new(self).map_err(bundle::create::Error::Pack)?;
+        let selection = graph::select_commits(
+            &mut graph,
+            &wants.commits,
+            &excluded.commits,
+            &HashSet::new(),
+            &HashSet::new(),
+        )
+        .map_err(bundle::create::Error::Pack)?;
+        let mut prerequisites = Vec::new();
+        for id in &selection.edges {
+            let commit = self.find_object(*id)?.try_into_commit()?;
+            prerequisites.push(bundle::Prerequisite {
+                id: *id,
+                comment: commit.message()?.summary().into_owned(),
+            });
+        }
+
+        let header = bundle::Header {
+            version: options.version,
+            object_hash: self.object_hash(),
+            filter: None,
+            prerequisites,
+            refs,
+        };
+        header.write_to(&mut out)?;
+        let objects = pack::objects_to_send(self, &wants, &selection, None).map_err(bundle::create::Error::Pack)?;
+        pack::write(self, objects, options.thread_limit, None, false, &mut out).map_err(bundle::create::Error::Pack)?;
+        out.flush()?;
+        Ok(header)
+    }
+
+    /// Read the bundle from `bundle` and assure it can be unbundled into this repository, similar to `git bundle verify`.
+    ///
+    /// This is the case if the repository has all of its prerequisite commits and the pack of the bundle is intact.
+    pub fn verify_bundle(
+        &self,
+        mut bundle: impl std::io::BufRead,
+    ) -> Result<bundle::verify::Outcome, bundle::verify::Error> {
+        let header = bundle::Header::from_read(&mut bundle)?;
+        if header.object_hash != self.object_hash() {
+            return Err(bundle::verify::Error::ObjectHashMismatch {
+                actual: header.object_hash,
+                expected: self.object_hash(),
+            });
+        }
+        let mut missing = Vec::new();
+        for prerequisite in &header.prerequisites {
+            let is_commit = self
+                .try_find_object(prerequisite.id)?
+                .map_or(false, |object| object.kind == gix_object::Kind::Commit);
+            if !is_commit {
+                missing.push(prerequisite.id);
+            }
+        }
+        if !missing.is_empty() {
+            return Err(bundle::verify::Error::MissingPrerequisites { ids: missing });
+        }
+
+        let entries = gix_pack::data::input::BytesToEntriesIter::new_from_header(
+            bundle,
+            gix_pack::data::input::Mode::Verify,
+            gix_pack::data::input::EntryDataMode::Ignore,
+            self.object_hash(),
+        )?;
+        let mut num_objects = 0;
+        for entry in entries {
+            entry?;
+            num_objects += 1;
+        }
+        Ok(bundle::verify::Outcome { header, num_objects })
+    }
+}
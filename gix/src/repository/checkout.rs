@@ -0,0 +1,22 @@
+#![allow(clippy::result_large_err)]
+use crate::{bstr::BStr, checkout, Repository};
+
+impl Repository {
+    /// Switch the working tree, the index and `HEAD` to the commit `target` points to, similar to `git checkout <target>`.
+    ///
+    /// `target` is a revision specification, and if it names a local branch, `HEAD` will point to that branch unless
+    /// [detached][checkout::Options::detach]. Only the files that differ between the current and the target commit are
+    /// removed, added or updated, while local changes to other files are kept. Unless [forced][checkout::Options::force],
+    /// it's an error if local changes to files or untracked files that aren't ignored would be overwritten, in which case
+    /// nothing is changed.
+    ///
+    /// Afterwards, the index is written, `HEAD` is updated with a reflog entry, and the `post-checkout`
+    /// [hook][crate::hook::name::POST_CHECKOUT] runs. If it fails, the checkout remains in place nonetheless.
+    pub fn checkout(
+        &self,
+        target: impl AsRef<BStr>,
+        options: checkout::Options,
+    ) -> Result<checkout::Outcome, checkout::Error> {
+        checkout::function::checkout(self, target.as_ref(), options)
+    }
+}
@@ -0,0 +1,38 @@
+#![allow(clippy::result_large_err)]
+use gix_hash::ObjectId;
+
+use crate::{cherry_pick, revert, Repository};
+
+/// Cherry-picking and reverting
+impl Repository {
+    /// Replay the changes the commit `commit` made to its parent onto the commit `onto`, similar to `git cherry-pick`, and return
+    /// the new commit along with the merged entries.
+    ///
+    /// The changes are merged with the tree of `onto` like [`merge_trees()`][Self::merge_trees()] does, using the tree of the
+    /// parent of `commit` as base. Without conflicts, a new commit with `onto` as parent is written which has the message and the
+    /// author of `commit`, while the committer is the current one. With conflicts, no commit is written, and the conflicts are
+    /// part of the returned outcome. Neither the index nor the working tree nor any reference are changed, so to cherry-pick onto
+    /// `HEAD`, pass its id as `onto` and update `HEAD` to the new commit.
+    pub fn cherry_pick(
+        &self,
+        commit: impl Into<ObjectId>,
+        onto: impl Into<ObjectId>,
+        options: cherry_pick::Options,
+    ) -> Result<cherry_pick::Outcome, cherry_pick::Error> {
+        cherry_pick::function::cherry_pick(self, commit.into(), onto.into(), options)
+    }
+
+    /// Undo the changes the commit `commit` made to its parent on top of the commit `onto`, similar to `git revert`, and return
+    /// the new commit along with the merged entries.
+    ///
+    /// It works like [`cherry_pick()`][Self::cherry_pick()] with the tree of `commit` as base and the one of its parent as the
+    /// side to merge, but the new commit has the current author and a message like `git revert` would write it.
+    pub fn revert(
+        &self,
+        commit: impl Into<ObjectId>,
+        onto: impl Into<ObjectId>,
+        options: revert::Options,
+    ) -> Result<revert::Outcome, revert::Error> {
+        cherry_pick::function::revert(self, commit.into(), onto.into(), options)
+    }
+}
@@ -22,6 +22,49 @@ impl crate::Repository {
         }
     }
 
+    /// Lock the configuration file of the given `source` for editing, to persist changes with [`commit()`][config::LockedFile::commit()].
+    ///
+    /// This is similar to `git config --local|--worktree|--global|--system`, with `Local` being the repository-local configuration
+    /// in the common directory and `Worktree` being `$GIT_DIR/config.worktree`, which is only read if `extensions.worktreeConfig`
+    /// is enabled. `User` is `~/.gitconfig` and `Git` is `$XDG_CONFIG_HOME/git/config`, and the locations of global and system
+    /// configuration files respect the environment as far as permitted by the [permissions][crate::open::Permissions] the repository
+    /// was opened with.
+    /// Sources without storage location, like `Api` or `Cli` overrides, cause an error.
+    /// To edit arbitrary files, use [`LockedFile::open()`][config::LockedFile::open()].
+    ///
+    /// Note that changes are only visible to repository instances opened after they were committed.
+    pub fn config_file_mut(
+        &self,
+        source: config::Source,
+    ) -> Result<config::LockedFile, config::config_file_mut::Error> {
+        let path = match source {
+            config::Source::Local => Some(self.common_dir().join("config")),
+            config::Source::Worktree => Some(self.git_dir().join("config.worktree")),
+            _ => source
+                .storage_location(&mut config::Cache::make_source_env(self.options.permissions.env))
+                .map(std::borrow::Cow::into_owned),
+        };
+        let path = path.ok_or(config::config_file_mut::Error::NoStorageLocation(source))?;
+        Ok(config::LockedFile::open(path, source)?)
+    }
+
+    /// Lock the configuration file private to the worktree of this repository for editing, similar to `git config --worktree`.
+    ///
+    /// With `extensions.worktreeConfig` enabled, this is `$GIT_DIR/config.worktree`, which is read after the repository-local
+    /// configuration and thus overrides it. Otherwise it's the repository-local configuration as long as there are no linked
+    /// worktrees that would be affected by it as well, which is an error.
+    ///
+    /// Note that changes are only visible to repository instances opened after [committing][config::LockedFile::commit()] them.
+    pub fn worktree_config_mut(&self) -> Result<config::LockedFile, config::worktree_config_mut::Error> {
+        Ok(if self.config.extensions.worktree_config {
+            config::LockedFile::open(self.git_dir().join("config.worktree"), config::Source::Worktree)?
+        } else if self.worktrees()?.is_empty() {
+            config::LockedFile::open(self.common_dir().join("config"), config::Source::Local)?
+        } else {
+            return Err(config::worktree_config_mut::Error::WorktreeConfigDisabled);
+        })
+    }
+
     /// The options used to open the repository.
     pub fn open_options(&self) -> &crate::open::Options {
         &self.options
@@ -68,6 +111,11 @@ impl crate::Repository {
     pub fn object_hash(&self) -> gix_hash::Kind {
         self.config.object_hash
     }
+
+    /// The repository format version and the extensions of the repository, as validated when it was opened.
+    pub fn extensions(&self) -> &crate::Extensions {
+        &self.config.extensions
+    }
 }
 
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
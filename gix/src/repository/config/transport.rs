@@ -138,15 +138,65 @@ impl crate::Repository {
                             }))
                     }
 
+                    /// Return configuration with a single `http` section containing the values of all trusted `http` and
+                    /// `http.<url>` sections in `config` that apply to `url`, in the order they are applied in, like `git` does.
+                    ///
+                    /// Values of `http.<url>` sections apply if `url` is matched by the URL in the section name, unless a value
+                    /// for the same key was seen earlier in a section whose URL matched more specifically. `http` sections
+                    /// without URL are the least specific, and values from environment variables always apply.
+                    fn http_config_for_url(
+                        config: &gix_config::File<'static>,
+                        url: &gix_url::Url,
+                        filter: &mut gix_config::file::MetadataFilter,
+                    ) -> gix_config::File<'static> {
+                        let mut out = gix_config::File::new(gix_config::file::Metadata::api());
+                        let mut out_section = out.new_section("http", None).expect("valid section name");
+                        let mut best_match_by_key =
+                            std::collections::HashMap::<String, gix_url::matching::Specificity>::new();
+                        for section in config.sections_by_name_and_filter("http", filter).into_iter().flatten() {
+                            let specificity = match section.header().subsection_name() {
+                                None => Default::default(),
+                                Some(pattern) => {
+                                    match gix_url::parse(pattern).ok().and_then(|pattern| url.matches(&pattern)) {
+                                        Some(specificity) => specificity,
+                                        None => continue,
+                                    }
+                                }
+                            };
+                            let always_applies = section.meta().source == gix_config::Source::EnvOverride;
+                            let body = section.body();
+                            let mut seen = std::collections::HashSet::new();
+                            for key in body.keys() {
+                                let name = key.as_ref().to_ascii_lowercase();
+                                if !seen.insert(name.clone()) {
+                                    continue;
+                                }
+                                let best_match = best_match_by_key.entry(name).or_default();
+                                if specificity < *best_match && !always_applies {
+                                    continue;
+                                }
+                                *best_match = specificity;
+                                for value in body.values(key.as_ref()) {
+                                    out_section.push(key.clone(), Some(value.as_ref()));
+                                }
+                                if let Some(None) = body.value_implicit(key.as_ref()) {
+                                    out_section.push(key.clone(), None);
+                                }
+                            }
+                        }
+                        out
+                    }
+
                     let mut opts = http::Options::default();
                     let config = &self.config.resolved;
                     let mut trusted_only = self.filter_config_section();
                     let lenient = self.config.lenient_config;
+                    let http_config = http_config_for_url(config, &url, &mut trusted_only);
                     opts.extra_headers = {
                         let key = "http.extraHeader";
                         debug_assert_eq!(key, &config::tree::Http::EXTRA_HEADER.logical_name());
-                        config
-                            .strings_filter_by_key(key, &mut trusted_only)
+                        http_config
+                            .strings_by_key(key)
                             .map(|values| config::tree::Http::EXTRA_HEADER.try_into_extra_header(values))
                             .transpose()
                             .map_err(|err| config::transport::Error::IllformedUtf8 {
@@ -160,27 +210,21 @@ impl crate::Repository {
                         let key = "http.followRedirects";
 
                         config::tree::Http::FOLLOW_REDIRECTS
-                            .try_into_follow_redirects(
-                                config.string_filter_by_key(key, &mut trusted_only).unwrap_or_default(),
-                                || {
-                                    config
-                                        .boolean_filter_by_key(key, &mut trusted_only)
-                                        .transpose()
-                                        .with_leniency(lenient)
-                                },
-                            )
+                            .try_into_follow_redirects(http_config.string_by_key(key).unwrap_or_default(), || {
+                                http_config.boolean_by_key(key).transpose().with_leniency(lenient)
+                            })
                             .map_err(config::transport::http::Error::InvalidFollowRedirects)?
                     };
 
-                    opts.low_speed_time_seconds = config
-                        .integer_filter_by_key("http.lowSpeedTime", &mut trusted_only)
+                    opts.low_speed_time_seconds = http_config
+                        .integer_by_key("http.lowSpeedTime")
                         .map(|value| config::tree::Http::LOW_SPEED_TIME.try_into_u64(value))
                         .transpose()
                         .with_leniency(lenient)
                         .map_err(config::transport::http::Error::from)?
                         .unwrap_or_default();
-                    opts.low_speed_limit_bytes_per_second = config
-                        .integer_filter_by_key("http.lowSpeedLimit", &mut trusted_only)
+                    opts.low_speed_limit_bytes_per_second = http_config
+                        .integer_by_key("http.lowSpeedLimit")
                         .map(|value| config::tree::Http::LOW_SPEED_LIMIT.try_into_u32(value))
                         .transpose()
                         .with_leniency(lenient)
@@ -196,8 +240,8 @@ impl crate::Repository {
                             .or_else(|| {
                                 let key = "http.proxy";
                                 debug_assert_eq!(key, config::tree::Http::PROXY.logical_name());
-                                let http_proxy = config
-                                    .string_filter_by_key(key, &mut trusted_only)
+                                let http_proxy = http_config
+                                    .string_by_key(key)
                                     .map(|v| (v, cow_bstr(key), &config::tree::Http::PROXY))
                                     .or_else(|| {
                                         let key = "gitoxide.http.proxy";
@@ -260,7 +304,7 @@ impl crate::Repository {
                                     .or_else(|| {
                                         let key = "http.proxyAuthMethod";
                                         debug_assert_eq!(key, config::tree::Http::PROXY_AUTH_METHOD.logical_name());
-                                        config.string_filter_by_key(key, &mut trusted_only).map(|v| {
+                                        http_config.string_by_key(key).map(|v| {
                                             (v, Cow::Borrowed(key.into()), &config::tree::Http::PROXY_AUTH_METHOD)
                                         })
                                     })
@@ -298,8 +342,8 @@ impl crate::Repository {
                     };
                     {
                         let key = "http.userAgent";
-                        opts.user_agent = config
-                            .string_filter_by_key(key, &mut trusted_only)
+                        opts.user_agent = http_config
+                            .string_by_key(key)
                             .and_then(|v| {
                                 try_cow_to_string(
                                     v,
@@ -315,8 +359,8 @@ impl crate::Repository {
 
                     {
                         let key = "http.version";
-                        opts.http_version = config
-                            .string_filter_by_key(key, &mut trusted_only)
+                        opts.http_version = http_config
+                            .string_by_key(key)
                             .map(|v| {
                                 config::tree::Http::VERSION
                                     .try_into_http_version(v)
@@ -339,8 +383,8 @@ impl crate::Repository {
 
                     let may_use_cainfo = {
                         let key = "http.schannelUseSSLCAInfo";
-                        config
-                            .boolean_filter_by_key(key, &mut trusted_only)
+                        http_config
+                            .boolean_by_key(key)
                             .map(|value| config::tree::Http::SCHANNEL_USE_SSL_CA_INFO.enrich_error(value))
                             .transpose()
                             .with_leniency(lenient)
@@ -351,8 +395,39 @@ impl crate::Repository {
                     if may_use_cainfo {
                         let key = "http.sslCAInfo";
                         debug_assert_eq!(key, config::tree::Http::SSL_CA_INFO.logical_name());
-                        opts.ssl_ca_info = config
-                            .path_filter_by_key(key, &mut trusted_only)
+                        opts.ssl_ca_info = http_config
+                            .path_by_key(key)
+                            .map(|p| {
+                                use crate::config::cache::interpolate_context;
+                                p.interpolate(interpolate_context(
+                                    self.install_dir().ok().as_deref(),
+                                    self.config.home_dir().as_deref(),
+                                ))
+                                .map(|cow| cow.into_owned())
+                            })
+                            .transpose()
+                            .with_leniency(lenient)
+                            .map_err(|err| config::transport::Error::InterpolatePath { source: err, key })?;
+                    }
+
+                    opts.ssl_verify = {
+                        let key = "http.sslVerify";
+                        debug_assert_eq!(key, config::tree::Http::SSL_VERIFY.logical_name());
+                        http_config
+                            .boolean_by_key(key)
+                            .map(|value| config::tree::Http::SSL_VERIFY.enrich_error(value))
+                            .transpose()
+                            .with_leniency(lenient)
+                            .map_err(config::transport::http::Error::from)?
+                            .unwrap_or(true)
+                    };
+
+                    {
+                        let key = "http.cookieFile";
+                        debug_assert_eq!(key, config::tree::Http::COOKIE_FILE.logical_name());
+                        opts.cookie_file = http_config
+                            .path_by_key(key)
+                            .filter(|p| !p.is_empty())
                             .map(|p| {
                                 use crate::config::cache::interpolate_context;
                                 p.interpolate(interpolate_context(
@@ -368,10 +443,10 @@ impl crate::Repository {
 
                     {
                         opts.ssl_version = ssl_version(
-                            config,
+                            &http_config,
                             "http.sslVersion",
                             &config::tree::Http::SSL_VERSION,
-                            trusted_only,
+                            |_| true,
                             lenient,
                         )?
                         .map(|v| SslVersionRangeInclusive { min: v, max: v });
@@ -405,8 +480,8 @@ impl crate::Repository {
                     #[cfg(feature = "blocking-http-transport-curl")]
                     {
                         let key = "http.schannelCheckRevoke";
-                        let schannel_check_revoke = config
-                            .boolean_filter_by_key(key, &mut trusted_only)
+                        let schannel_check_revoke = http_config
+                            .boolean_by_key(key)
                             .map(|value| config::tree::Http::SCHANNEL_CHECK_REVOKE.enrich_error(value))
                             .transpose()
                             .with_leniency(lenient)
@@ -416,6 +491,34 @@ impl crate::Repository {
                             Some(Arc::new(Mutex::new(backend)) as Arc<Mutex<dyn Any + Send + Sync + 'static>>);
                     }
 
+                    #[cfg(any(
+                        feature = "blocking-http-transport-reqwest-rust-tls",
+                        feature = "blocking-http-transport-reqwest-native-tls"
+                    ))]
+                    // Keep the backend the caller may have configured unless there is something to do for us.
+                    if opts.ssl_ca_info.is_some() || !opts.ssl_verify {
+                        fn configure_tls(
+                            mut builder: reqwest_for_configuration_only::blocking::ClientBuilder,
+                            opts: &http::Options,
+                        ) -> Result<
+                            reqwest_for_configuration_only::blocking::ClientBuilder,
+                            Box<dyn std::error::Error + Send + Sync + 'static>,
+                        > {
+                            if let Some(ca_info) = &opts.ssl_ca_info {
+                                let pem = std::fs::read(ca_info)?;
+                                builder = builder
+                                    .add_root_certificate(reqwest_for_configuration_only::Certificate::from_pem(&pem)?);
+                            }
+                            Ok(builder.danger_accept_invalid_certs(!opts.ssl_verify))
+                        }
+                        let backend = gix_protocol::transport::client::http::reqwest::Options {
+                            configure_request: None,
+                            configure_client: Some(Box::new(configure_tls)),
+                        };
+                        opts.backend =
+                            Some(Arc::new(Mutex::new(backend)) as Arc<Mutex<dyn Any + Send + Sync + 'static>>);
+                    }
+
                     Ok(Some(Box::new(opts)))
                 }
             }
@@ -0,0 +1,16 @@
+use crate::{diff, Repository};
+
+impl Repository {
+    /// Return a utility to find the diff driver of paths as configured with the `diff` attribute in `gitattributes` files.
+    ///
+    /// Attributes are read from the working tree by default, if there is one.
+    pub fn diff_drivers(&self) -> Result<diff::driver::Resolver<'_>, diff::driver::init::Error> {
+        diff::driver::Resolver::new(self)
+    }
+
+    /// Return a utility to write patches in the unified diff format and to compute diff statistics, configured
+    /// according to the `diff` section of the git configuration.
+    pub fn diff_patch_writer(&self) -> Result<diff::patch::Writer<'_>, diff::patch::init::Error> {
+        diff::patch::Writer::new(self)
+    }
+}
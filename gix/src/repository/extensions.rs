@@ -0,0 +1,21 @@
+use crate::bstr::BString;
+
+/// The extensions of a repository as configured in `extensions.*` of its local configuration, validated when it was opened.
+///
+/// Repositories using extensions that aren't understood, or extensions that require `core.repositoryFormatVersion = 1` with
+/// version 0, can't be opened to protect them from corruption.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Extensions {
+    /// The value of `core.repositoryFormatVersion`, which is either 0 or 1.
+    pub repository_format_version: usize,
+    /// The kind of hash used for all objects as configured by `extensions.objectFormat`, which is only respected with
+    /// repository format version 1.
+    pub object_format: gix_hash::Kind,
+    /// The name of the remote from which missing objects can be fetched, as configured by `extensions.partialClone`.
+    pub partial_clone: Option<BString>,
+    /// If `true`, objects must never be deleted from the object database, as configured by `extensions.preciousObjects`.
+    pub precious_objects: bool,
+    /// If `true`, `$GIT_DIR/config.worktree` is read after the repository-local configuration, as configured by
+    /// `extensions.worktreeConfig`.
+    pub worktree_config: bool,
+}
@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+
+use crate::{
+    bstr::{BStr, BString},
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{gpg, Gpg, User},
+    },
+    Repository,
+};
+
+impl Repository {
+    /// Sign `payload` in the format configured with `gpg.format` and return the signature, similar to what `git` does
+    /// for commits and tags.
+    ///
+    /// The signing program is `gpg.openpgp.program` or `gpg.program`, `gpg.x509.program` or `gpg.ssh.program`
+    /// respectively, and defaults to `gpg`, `gpgsm` or `ssh-keygen`. It signs with the key in `user.signingKey`,
+    /// which defaults to the committer for OpenPGP and X.509, and must be the path to a private key or `key::`
+    /// followed by a public key whose private key is known to `ssh-agent` for SSH.
+    ///
+    /// ### Deviation
+    ///
+    /// `gpg.ssh.defaultKeyCommand` isn't supported.
+    pub fn sign(&self, payload: &[u8]) -> Result<BString, crate::gpg::sign::Error> {
+        let format = self
+            .config
+            .resolved
+            .string_filter("gpg", None, Gpg::FORMAT.name, &mut self.filter_config_section())
+            .map(|value| Gpg::FORMAT.try_into_format(value))
+            .transpose()
+            .with_leniency(self.config.lenient_config)?
+            .unwrap_or_default();
+        let key: Cow<'_, BStr> = match format {
+            crate::gpg::Format::Ssh => {
+                let key = self
+                    .config
+                    .resolved
+                    .string_filter("user", None, User::SIGNING_KEY.name, &mut self.filter_config_section())
+                    .ok_or(crate::gpg::sign::Error::MissingSigningKey)?;
+                if key.starts_with(b"key::") || key.starts_with(b"ssh-") {
+                    key
+                } else {
+                    let path = self
+                        .config
+                        .trusted_file_path("user", None, User::SIGNING_KEY.name)
+                        .ok_or(crate::gpg::sign::Error::MissingSigningKey)??;
+                    Cow::Owned(gix_path::into_bstr(path).into_owned())
+                }
+            }
+            crate::gpg::Format::OpenPgp | crate::gpg::Format::X509 => {
+                match self.config.resolved.string_filter(
+                    "user",
+                    None,
+                    User::SIGNING_KEY.name,
+                    &mut self.filter_config_section(),
+                ) {
+                    Some(key) => key,
+                    None => {
+                        let committer = self.committer().ok_or(crate::gpg::sign::Error::CommitterMissing)??;
+                        Cow::Owned(format!("{} <{}>", committer.name, committer.email).into())
+                    }
+                }
+            }
+        };
+        crate::gpg::sign(format, self.gpg_program(format).as_ref(), key.as_ref(), payload)
+    }
+
+    /// Verify that `signature` was made for `payload` and return the verdict, similar to `git verify-commit`.
+    ///
+    /// The format of the signature is determined by the signature itself, and the program to run for it is configured
+    /// just like for [signing][Repository::sign()]. SSH signatures are only considered [good][crate::gpg::verify::Status::Good]
+    /// if their key is listed in the file at `gpg.ssh.allowedSignersFile`, which must be configured to verify them.
+    pub fn verify_signature(
+        &self,
+        payload: &[u8],
+        signature: &[u8],
+    ) -> Result<crate::gpg::verify::Outcome, crate::gpg::verify::Error> {
+        let format = crate::gpg::Format::from_signature(signature).ok_or(crate::gpg::verify::Error::UnknownFormat)?;
+        let allowed_signers = match format {
+            crate::gpg::Format::Ssh => self
+                .config
+                .trusted_file_path("gpg", Some("ssh".into()), gpg::Ssh::ALLOWED_SIGNERS_FILE.name)
+                .transpose()?,
+            crate::gpg::Format::OpenPgp | crate::gpg::Format::X509 => None,
+        };
+        crate::gpg::verify(
+            format,
+            self.gpg_program(format).as_ref(),
+            allowed_signers.as_deref(),
+            payload,
+            signature,
+        )
+    }
+
+    /// Return the program to run for signatures in `format`.
+    fn gpg_program(&self, format: crate::gpg::Format) -> Cow<'_, BStr> {
+        let config = &self.config.resolved;
+        let mut filter = self.filter_config_section();
+        match format {
+            crate::gpg::Format::OpenPgp => config
+                .string_filter("gpg", Some("openpgp".into()), gpg::OpenPgp::PROGRAM.name, &mut filter)
+                .or_else(|| config.string_filter("gpg", None, Gpg::PROGRAM.name, &mut filter)),
+            crate::gpg::Format::X509 => {
+                config.string_filter("gpg", Some("x509".into()), gpg::X509::PROGRAM.name, &mut filter)
+            }
+            crate::gpg::Format::Ssh => {
+                config.string_filter("gpg", Some("ssh".into()), gpg::Ssh::PROGRAM.name, &mut filter)
+            }
+        }
+        .unwrap_or_else(|| Cow::Borrowed(format.default_program().into()))
+    }
+}
@@ -0,0 +1,10 @@
+impl crate::Repository {
+    /// Return the commit-graph of this repository, if present, to accelerate traversals and to provide access to
+    /// changed-path bloom filters.
+    ///
+    /// Note that the graph is loaded from disk on each call, and that it may not contain the most recent commits
+    /// as it's only written during maintenance or when explicitly requested.
+    pub fn commit_graph(&self) -> Result<gix_commitgraph::Graph, gix_commitgraph::graph::Error> {
+        gix_commitgraph::Graph::from_info_dir(self.objects.store_ref().path().join("info"))
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+};
+
+use gix_ref::{
+    transaction::{Change, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{config::tree::Core, hook, Repository};
+
+/// Hooks
+impl Repository {
+    /// Return the directory containing the hook programs, which is `core.hooksPath` or the `hooks` directory in the
+    /// [common directory][Repository::common_dir()].
+    ///
+    /// A relative `core.hooksPath` is relative to the directory hooks run in, which is the work tree, or the `git_dir`
+    /// in bare repositories.
+    pub fn hooks_dir(&self) -> Result<PathBuf, gix_config::path::interpolate::Error> {
+        Ok(
+            match self
+                .config
+                .trusted_file_path(Core::HOOKS_PATH.section.name(), None, Core::HOOKS_PATH.name)
+                .transpose()?
+            {
+                Some(path) => self.hooks_cwd().join(path),
+                None => self.common_dir().join("hooks"),
+            },
+        )
+    }
+
+    /// Return the path to the executable program implementing the hook with `name`, or `None` if there is none.
+    ///
+    /// Note that programs are never run in repositories that aren't [fully trusted][gix_sec::Trust::Full], as they may be
+    /// owned by someone else, which is why `None` is returned for these as well.
+    pub fn find_hook(&self, name: &str) -> Result<Option<PathBuf>, gix_config::path::interpolate::Error> {
+        if self.git_dir_trust() != gix_sec::Trust::Full {
+            return Ok(None);
+        }
+        let path = self.hooks_dir()?.join(name);
+        Ok(is_executable(&path).then_some(path))
+    }
+
+    /// Run the hook with `name` with `input`, and return `true` if there was one to run, or `false` if there wasn't.
+    ///
+    /// [Functions][hook::Function] [registered][Repository::set_hook()] for `name` run in place of the hook program, which is
+    /// [looked up][Repository::find_hook()] otherwise. Programs run in the work tree, or in the `git_dir` of bare repositories, with
+    /// `GIT_DIR` set in their environment, and what they print to standard output is printed to standard error instead.
+    ///
+    /// It's an error if the hook fails, and whether that prevents the operation running it depends on the hook.
+    pub fn run_hook(&self, name: &str, input: &hook::Input) -> Result<bool, hook::Error> {
+        if let Some(function) = self.hooks.get(name) {
+            function(self, name, input).map_err(|source| hook::Error::Function {
+                name: name.into(),
+                source,
+            })?;
+            return Ok(true);
+        }
+        let path = match self.find_hook(name)? {
+            Some(path) => absolute(&path)?,
+            None => return Ok(false),
+        };
+
+        let mut child = gix_command::prepare(path.as_os_str())
+            .args(input.args.iter())
+            .env("GIT_DIR", absolute(self.git_dir())?)
+            .stdin(if input.stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(stderr())
+            .stderr(Stdio::inherit());
+        child
+            .env
+            .extend(input.env.iter().map(|(key, value)| (key.into(), value.clone())));
+        let mut cmd: std::process::Command = child.into();
+        let mut child = cmd
+            .current_dir(self.hooks_cwd())
+            .spawn()
+            .map_err(|source| hook::Error::Spawn {
+                name: name.into(),
+                path,
+                source,
+            })?;
+        let stdin = child.stdin.take();
+        let status = std::thread::scope(|scope| {
+            if let (Some(mut stdin), Some(data)) = (stdin, input.stdin.as_ref()) {
+                // Hooks are free to ignore their input.
+                scope.spawn(move || stdin.write_all(data).ok());
+            }
+            child.wait()
+        })
+        .map_err(|source| hook::Error::Wait {
+            name: name.into(),
+            source,
+        })?;
+        if !status.success() {
+            return Err(hook::Error::Failed {
+                name: name.into(),
+                status,
+            });
+        }
+        Ok(true)
+    }
+
+    /// Register `hook` to run in place of the hook program with `name`, or unregister it with `None`, and return the
+    /// previously registered one.
+    ///
+    /// Note that registered hooks are passed on to clones of this instance as well as [thread-safe repositories][Repository::into_sync()].
+    pub fn set_hook(
+        &mut self,
+        name: impl Into<String>,
+        hook: Option<Arc<hook::Function>>,
+    ) -> Option<Arc<hook::Function>> {
+        let name = name.into();
+        match hook {
+            Some(hook) => self.hooks.insert(name, hook),
+            None => self.hooks.remove(&name),
+        }
+    }
+
+    /// Return `true` if there is a hook with `name` to run.
+    pub(crate) fn has_hook(&self, name: &str) -> Result<bool, hook::Error> {
+        Ok(self.hooks.contains_key(name) || self.find_hook(name)?.is_some())
+    }
+
+    /// Commit the prepared `transaction` with `committer` for the reflog, and run the `reference-transaction` hook for it.
+    pub(crate) fn commit_ref_transaction(
+        &self,
+        transaction: gix_ref::file::Transaction<'_, '_>,
+        committer: Option<gix_actor::SignatureRef<'_>>,
+    ) -> Result<Vec<RefEdit>, crate::reference::edit::Error> {
+        if !self.has_hook(hook::name::REFERENCE_TRANSACTION)? {
+            return Ok(transaction.commit(committer)?);
+        }
+
+        let null = self.object_hash().null();
+        let mut updates = Vec::new();
+        for edit in transaction.edits() {
+            let (previous, new) = match &edit.change {
+                Change::Update {
+                    log,
+                    expected,
+                    new: Target::Peeled(new),
+                } if log.mode == RefLog::AndReference => (expected, *new),
+                Change::Delete {
+                    expected,
+                    log: RefLog::AndReference,
+                } => (expected, null),
+                _ => continue,
+            };
+            // Prepared edits of existing references expect the value they had when locked, all others didn't exist.
+            let previous = match previous {
+                PreviousValue::MustExistAndMatch(target) => self.resolve_to_id(target).unwrap_or(null),
+                _ => null,
+            };
+            writeln!(updates, "{previous} {new} {}", edit.name.as_bstr()).expect("cannot fail to write to memory");
+        }
+        let mut input = hook::Input {
+            args: vec!["prepared".into()],
+            stdin: Some(updates.into()),
+            env: Vec::new(),
+        };
+        let mut run_with_state = |state: &str| {
+            input.args[0] = state.into();
+            self.run_hook(hook::name::REFERENCE_TRANSACTION, &input)
+        };
+
+        if let Err(err) = run_with_state("prepared") {
+            transaction.rollback();
+            run_with_state("aborted").ok();
+            return Err(err.into());
+        }
+        match transaction.commit(committer) {
+            Ok(edits) => {
+                run_with_state("committed").ok();
+                Ok(edits)
+            }
+            Err(err) => {
+                run_with_state("aborted").ok();
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Follow `target` through symbolic references to the object id it points to, if possible.
+    fn resolve_to_id(&self, target: &Target) -> Option<gix_hash::ObjectId> {
+        use gix_ref::file::ReferenceExt;
+        let mut reference = match target {
+            Target::Peeled(id) => return Some(*id),
+            Target::Symbolic(name) => self.refs.try_find(name.as_ref()).ok()??,
+        };
+        // Like `git`, give up on chains of symbolic references that are too long or cyclic.
+        for _ in 0..5 {
+            match reference.follow(&self.refs) {
+                Some(next) => reference = next.ok()?,
+                None => return reference.target.try_id().map(ToOwned::to_owned),
+            }
+        }
+        None
+    }
+
+    /// The directory hooks run in.
+    fn hooks_cwd(&self) -> &Path {
+        self.work_dir().unwrap_or_else(|| self.git_dir())
+    }
+}
+
+fn absolute(path: &Path) -> Result<PathBuf, hook::Error> {
+    Ok(if path.is_relative() {
+        std::env::current_dir().map_err(hook::Error::CurrentDir)?.join(path)
+    } else {
+        path.to_owned()
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map_or(false, |md| md.is_file() && md.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Return our own standard error stream for use as standard output of hooks, as `git` does, or inherit it if that's not possible.
+fn stderr() -> Stdio {
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::AsFd;
+        std::io::stderr()
+            .as_fd()
+            .try_clone_to_owned()
+            .map_or_else(|_| Stdio::inherit(), Into::into)
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::io::AsHandle;
+        std::io::stderr()
+            .as_handle()
+            .try_clone_to_owned()
+            .map_or_else(|_| Stdio::inherit(), Into::into)
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        Stdio::inherit()
+    }
+}
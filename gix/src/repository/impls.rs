@@ -9,6 +9,7 @@ impl Clone for crate::Repository {
             self.options.clone(),
             self.index.clone(),
             self.shallow_commits.clone(),
+            self.hooks.clone(),
         )
     }
 }
@@ -42,6 +43,7 @@ impl From<&crate::ThreadSafeRepository> for crate::Repository {
             repo.linked_worktree_options.clone(),
             repo.index.clone(),
             repo.shallow_commits.clone(),
+            repo.hooks.clone(),
         )
     }
 }
@@ -57,6 +59,7 @@ impl From<crate::ThreadSafeRepository> for crate::Repository {
             repo.linked_worktree_options,
             repo.index,
             repo.shallow_commits,
+            repo.hooks,
         )
     }
 }
@@ -72,6 +75,7 @@ impl From<crate::Repository> for crate::ThreadSafeRepository {
             linked_worktree_options: r.options,
             index: r.index,
             shallow_commits: r.shallow_commits,
+            hooks: r.hooks,
         }
     }
 }
@@ -0,0 +1,20 @@
+use crate::{merge, Repository};
+
+impl Repository {
+    /// Merge the changes from `base` to `theirs` into `ours`, with all of them being trees, and return the merged entries
+    /// along with all paths that couldn't be merged, similar to `git merge-tree --write-tree`.
+    ///
+    /// Paths changed by only one side are taken from that side, while the content of text files changed by both sides is merged
+    /// line by line. Overlapping changes are conflicts, which are surrounded by conflict markers using `labels` in the merged
+    /// content of the file. Neither the index nor the working tree are touched, but the merged content of files is written
+    /// to the object database.
+    pub fn merge_trees(
+        &self,
+        base: impl AsRef<gix_hash::oid>,
+        ours: impl AsRef<gix_hash::oid>,
+        theirs: impl AsRef<gix_hash::oid>,
+        labels: merge::Labels<'_>,
+    ) -> Result<merge::Outcome, merge::Error> {
+        merge::function::trees(self, base.as_ref(), ours.as_ref(), theirs.as_ref(), labels)
+    }
+}
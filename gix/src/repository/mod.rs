@@ -19,19 +19,36 @@ impl crate::Repository {
     }
 }
 
+mod add;
+mod apply;
+mod blame;
+mod bundle;
 mod cache;
+mod checkout;
+mod cherry_pick;
 mod config;
+mod diff;
+pub(crate) mod extensions;
+mod gpg;
+mod graph;
+mod hook;
 pub(crate) mod identity;
 mod impls;
 mod init;
 mod location;
+mod merge;
 mod object;
 pub(crate) mod permissions;
+#[cfg(feature = "blocking-network-client")]
+mod receive_pack;
 mod reference;
 mod remote;
 mod revision;
 mod shallow;
 mod snapshots;
+mod stash;
 mod state;
 mod thread_safe;
+#[cfg(feature = "blocking-network-client")]
+mod upload_pack;
 mod worktree;
@@ -3,13 +3,19 @@ use std::convert::TryInto;
 use std::ops::DerefMut;
 
 use gix_hash::ObjectId;
+use gix_object::WriteTo;
 use gix_odb::{Find, FindExt, Write};
 use gix_ref::{
     transaction::{LogChange, PreviousValue, RefLog},
     FullName,
 };
 
-use crate::{commit, ext::ObjectIdExt, object, tag, Id, Object, Reference, Tree};
+use crate::{
+    bstr::{BString, ByteSlice},
+    commit,
+    ext::ObjectIdExt,
+    hook, object, tag, Id, Object, Reference, Tree,
+};
 
 /// Methods related to object creation.
 impl crate::Repository {
@@ -132,6 +138,8 @@ impl crate::Repository {
     ///
     /// It will be created with `constraint` which is most commonly to [only create it][PreviousValue::MustNotExist]
     /// or to [force overwriting a possibly existing tag](PreviousValue::Any).
+    ///
+    /// The tag object isn't signed, even if `tag.gpgSign` is set, use [`tag_signed()`][Self::tag_signed()] for that.
     pub fn tag(
         &self,
         name: impl AsRef<str>,
@@ -141,14 +149,40 @@ impl crate::Repository {
         message: impl AsRef<str>,
         constraint: PreviousValue,
     ) -> Result<Reference<'_>, tag::Error> {
-        let tag = gix_object::Tag {
-            target: target.as_ref().into(),
-            target_kind,
-            name: name.as_ref().into(),
-            tagger: tagger.map(|t| t.to_owned()),
-            message: message.as_ref().into(),
-            pgp_signature: None,
-        };
+        let tag = new_tag(name.as_ref(), target.as_ref(), target_kind, tagger, message.as_ref());
+        self.write_tag(name.as_ref(), tag, constraint, false)
+    }
+
+    /// Like [`tag()`][Self::tag()], but [sign][crate::Repository::sign()] the tag object, similar to `git tag --sign`.
+    ///
+    /// It's up to the caller to decide whether to sign, for instance by looking at `tag.gpgSign`.
+    pub fn tag_signed(
+        &self,
+        name: impl AsRef<str>,
+        target: impl AsRef<gix_hash::oid>,
+        target_kind: gix_object::Kind,
+        tagger: Option<gix_actor::SignatureRef<'_>>,
+        message: impl AsRef<str>,
+        constraint: PreviousValue,
+    ) -> Result<Reference<'_>, tag::Error> {
+        let tag = new_tag(name.as_ref(), target.as_ref(), target_kind, tagger, message.as_ref());
+        self.write_tag(name.as_ref(), tag, constraint, true)
+    }
+
+    fn write_tag(
+        &self,
+        name: &str,
+        mut tag: gix_object::Tag,
+        constraint: PreviousValue,
+        sign: bool,
+    ) -> Result<Reference<'_>, tag::Error> {
+        if sign {
+            let mut payload = Vec::new();
+            tag.write_to(&mut payload)?;
+            // The signature follows the message on its own line.
+            payload.push(b'\n');
+            tag.pgp_signature = Some(self.sign(&payload)?);
+        }
         let tag_id = self.write_object(&tag)?;
         self.tag_reference(name, tag_id, constraint).map_err(Into::into)
     }
@@ -156,6 +190,9 @@ impl crate::Repository {
     /// Similar to [`commit(…)`][crate::Repository::commit()], but allows to create the commit with `committer` and `author` specified.
     ///
     /// This forces setting the commit time and author time by hand. Note that typically, committer and author are the same.
+    ///
+    /// The commit object isn't signed, even if `commit.gpgSign` is set, use [`commit_as_signed()`][Self::commit_as_signed()]
+    /// for that.
     pub fn commit_as<'a, 'c, Name, E>(
         &self,
         committer: impl Into<gix_actor::SignatureRef<'c>>,
@@ -165,6 +202,44 @@ impl crate::Repository {
         tree: impl Into<ObjectId>,
         parents: impl IntoIterator<Item = impl Into<ObjectId>>,
     ) -> Result<Id<'_>, commit::Error>
+    where
+        Name: TryInto<FullName, Error = E>,
+        commit::Error: From<E>,
+    {
+        self.commit_as_inner(committer, author, reference, message, tree, parents, false)
+    }
+
+    /// Like [`commit_as()`][Self::commit_as()], but [sign][crate::Repository::sign()] the commit object, similar to
+    /// `git commit --gpg-sign`.
+    ///
+    /// It's up to the caller to decide whether to sign, for instance by looking at `commit.gpgSign`.
+    pub fn commit_as_signed<'a, 'c, Name, E>(
+        &self,
+        committer: impl Into<gix_actor::SignatureRef<'c>>,
+        author: impl Into<gix_actor::SignatureRef<'a>>,
+        reference: Name,
+        message: impl AsRef<str>,
+        tree: impl Into<ObjectId>,
+        parents: impl IntoIterator<Item = impl Into<ObjectId>>,
+    ) -> Result<Id<'_>, commit::Error>
+    where
+        Name: TryInto<FullName, Error = E>,
+        commit::Error: From<E>,
+    {
+        self.commit_as_inner(committer, author, reference, message, tree, parents, true)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn commit_as_inner<'a, 'c, Name, E>(
+        &self,
+        committer: impl Into<gix_actor::SignatureRef<'c>>,
+        author: impl Into<gix_actor::SignatureRef<'a>>,
+        reference: Name,
+        message: impl AsRef<str>,
+        tree: impl Into<ObjectId>,
+        parents: impl IntoIterator<Item = impl Into<ObjectId>>,
+        sign: bool,
+    ) -> Result<Id<'_>, commit::Error>
     where
         Name: TryInto<FullName, Error = E>,
         commit::Error: From<E>,
@@ -177,7 +252,7 @@ impl crate::Repository {
         // TODO: possibly use CommitRef to save a few allocations (but will have to allocate for object ids anyway.
         //       This can be made vastly more efficient though if we wanted to, so we lie in the API
         let reference = reference.try_into()?;
-        let commit = gix_object::Commit {
+        let mut commit = gix_object::Commit {
             message: message.as_ref().into(),
             tree: tree.into(),
             author: author.into().to_owned(),
@@ -186,6 +261,9 @@ impl crate::Repository {
             parents: parents.into_iter().map(|id| id.into()).collect(),
             extra_headers: Default::default(),
         };
+        if sign {
+            self.sign_commit(&mut commit)?;
+        }
 
         let commit_id = self.write_object(&commit)?;
         self.edit_reference(RefEdit {
@@ -242,6 +320,93 @@ impl crate::Repository {
         self.commit_as(committer, author, reference, message, tree, parents)
     }
 
+    /// Commit the tree of the index on top of `HEAD` with `message` like `git commit` does, and run the commit
+    /// [hooks][crate::hook::name] along the way.
+    ///
+    /// The `pre-commit` hook runs first and may prevent the commit or change the index, which is read afterwards to write its tree.
+    /// Then `prepare-commit-msg` and `commit-msg` may prevent the commit or change its message, and `post-commit` runs once `HEAD`
+    /// points to the new commit. `author` and `committer` are taken from the configuration just like in [`commit()`][Self::commit()],
+    /// which, like [`commit_as()`][Self::commit_as()], doesn't run any hooks. The commit isn't signed either.
+    pub fn commit_index(&self, message: impl AsRef<str>) -> Result<Id<'_>, commit::Error> {
+        let author = self.author().ok_or(commit::Error::AuthorMissing)??;
+        let committer = self.committer().ok_or(commit::Error::CommitterMissing)??;
+        let message = self.run_commit_hooks(author, message.as_ref().into())?;
+        let mut index = self.open_index()?;
+        let tree = self.write_tree_from_index(&mut index)?;
+        let parent = self.head()?.peel_to_id_in_place().transpose()?;
+        let commit_id = self.commit_as(
+            committer,
+            author,
+            "HEAD",
+            message.to_str().map_err(|_| commit::Error::MessageEncoding)?,
+            tree,
+            parent,
+        )?;
+        self.run_hook(hook::name::POST_COMMIT, &Default::default()).ok();
+        Ok(commit_id)
+    }
+
+    /// Run the hooks that are run before a commit by `author` with `message` is created, and return the message they leave behind.
+    fn run_commit_hooks(
+        &self,
+        author: gix_actor::SignatureRef<'_>,
+        message: BString,
+    ) -> Result<BString, commit::Error> {
+        let input = |args: Vec<std::ffi::OsString>| hook::Input {
+            args,
+            stdin: None,
+            env: vec![
+                ("GIT_INDEX_FILE", self.index_path().into()),
+                ("GIT_EDITOR", ":".into()),
+                ("GIT_AUTHOR_NAME", gix_path::from_bstr(author.name).into_owned().into()),
+                (
+                    "GIT_AUTHOR_EMAIL",
+                    gix_path::from_bstr(author.email).into_owned().into(),
+                ),
+                ("GIT_AUTHOR_DATE", format!("@{}", author.time.to_bstring()).into()),
+            ],
+        };
+        self.run_hook(hook::name::PRE_COMMIT, &input(Vec::new()))?;
+        if self.has_hook(hook::name::PREPARE_COMMIT_MSG)? || self.has_hook(hook::name::COMMIT_MSG)? {
+            let path = self.git_dir().join("COMMIT_EDITMSG");
+            let message_file_error = |source| commit::Error::MessageFile {
+                source,
+                path: path.clone(),
+            };
+            std::fs::write(&path, &message).map_err(message_file_error)?;
+            self.run_hook(
+                hook::name::PREPARE_COMMIT_MSG,
+                &input(vec![path.clone().into(), "message".into()]),
+            )?;
+            self.run_hook(hook::name::COMMIT_MSG, &input(vec![path.clone().into()]))?;
+            return Ok(std::fs::read(&path).map_err(message_file_error)?.into());
+        }
+        Ok(message)
+    }
+
+    /// Add a [signature][crate::Repository::sign()] of `commit` to its headers.
+    pub(crate) fn sign_commit(&self, commit: &mut gix_object::Commit) -> Result<(), commit::Error> {
+        let mut payload = Vec::new();
+        commit.write_to(&mut payload)?;
+        let mut signature = self.sign(&payload)?;
+        if signature.last() == Some(&b'\n') {
+            signature.pop();
+        }
+        commit.extra_headers.push((
+            crate::object::commit::signature_header(self.object_hash()).into(),
+            signature,
+        ));
+        Ok(())
+    }
+
+    /// Start editing the tree with `id`, or the tree of the commit or tag it points to, to change its entries by path
+    /// and [write][object::tree::Editor::write()] all changed trees at once.
+    ///
+    /// Use the id of the [empty tree][Self::empty_tree()] to build a tree from scratch.
+    pub fn edit_tree(&self, id: impl Into<ObjectId>) -> Result<object::tree::Editor<'_>, object::tree::editor::Error> {
+        Ok(self.find_object(id)?.peel_to_tree()?.edit()?)
+    }
+
     /// Return an empty tree object, suitable for [getting changes](crate::Tree::changes()).
     ///
     /// Note that it is special and doesn't physically exist in the object database even though it can be returned.
@@ -252,3 +417,20 @@ impl crate::Repository {
             .into_tree()
     }
 }
+
+fn new_tag(
+    name: &str,
+    target: &gix_hash::oid,
+    target_kind: gix_object::Kind,
+    tagger: Option<gix_actor::SignatureRef<'_>>,
+    message: &str,
+) -> gix_object::Tag {
+    gix_object::Tag {
+        target: target.into(),
+        target_kind,
+        name: name.into(),
+        tagger: tagger.map(|t| t.to_owned()),
+        message: message.into(),
+        pgp_signature: None,
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{receive_pack, Repository};
+
+impl Repository {
+    /// Accept a push of a `git` client by reading its commands and pack from `input` and writing our responses to `output`,
+    /// similar to `git receive-pack`, and return the outcome of each command.
+    ///
+    /// Ref updates are accepted or rejected by `hooks`, which may be `()` to accept all updates that are valid.
+    /// The [protocol version][receive_pack::Options::version] has to be provided by the caller, which typically obtains it
+    /// from the `GIT_PROTOCOL` environment variable with [`upload_pack::Version::from_git_protocol()`][crate::upload_pack::Version::from_git_protocol()].
+    pub fn receive_pack(
+        &self,
+        input: impl std::io::Read,
+        output: impl std::io::Write,
+        options: receive_pack::Options,
+        hooks: impl receive_pack::Hooks,
+    ) -> Result<receive_pack::Outcome, receive_pack::Error> {
+        receive_pack::function::receive_pack(self, input, output, options, hooks)
+    }
+}
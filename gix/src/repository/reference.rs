@@ -133,11 +133,12 @@ impl crate::Repository {
         edits: impl IntoIterator<Item = RefEdit>,
     ) -> Result<Vec<RefEdit>, reference::edit::Error> {
         let (file_lock_fail, packed_refs_lock_fail) = self.config.lock_timeout()?;
-        self.refs
-            .transaction()
-            .prepare(edits, file_lock_fail, packed_refs_lock_fail)?
-            .commit(self.committer().transpose()?)
-            .map_err(Into::into)
+        self.commit_ref_transaction(
+            self.refs
+                .transaction()
+                .prepare(edits, file_lock_fail, packed_refs_lock_fail)?,
+            self.committer().transpose()?,
+        )
     }
 
     /// Return the repository head, an abstraction to help dealing with the `HEAD` reference.
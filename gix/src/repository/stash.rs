@@ -0,0 +1,65 @@
+#![allow(clippy::result_large_err)]
+use gix_hash::ObjectId;
+
+use crate::{stash, Repository};
+
+/// Stashing
+impl Repository {
+    /// Save the local changes of the index and the working tree in a new stash entry, and reset both to the commit `HEAD` points to,
+    /// similar to `git stash push`. Return the id of the new stash entry, or `None` if there were no local changes to save.
+    ///
+    /// The entry is created just like `git` does, so it can be applied by `git stash apply` as well, and `refs/stash` points to it
+    /// with a new line in its reflog. Untracked files are only saved and removed from the working tree if
+    /// [desired][stash::push::Options::untracked].
+    pub fn stash_push(&self, options: stash::push::Options) -> Result<Option<ObjectId>, stash::Error> {
+        stash::function::push(self, options)
+    }
+
+    /// Return all stash entries, with the most recent one first, similar to `git stash list`.
+    ///
+    /// The position of an entry in the list is its index, like `0` in `stash@{0}`, which is used to [apply][Self::stash_apply()]
+    /// or [drop][Self::stash_drop()] it.
+    pub fn stash_list(&self) -> Result<Vec<stash::Entry>, stash::Error> {
+        stash::function::list(self)
+    }
+
+    /// Merge the changes saved in the stash entry at `index` into the index and the working tree, similar to
+    /// `git stash apply stash@{<index>}`.
+    ///
+    /// It's an error if local changes to files or untracked files that aren't ignored would be overwritten, in which case
+    /// nothing is changed. Conflicting changes are part of the returned outcome, with the conflicting paths being unmerged in
+    /// the index, and the entry isn't removed in any case. Untracked files saved with the entry are restored as well.
+    pub fn stash_apply(
+        &self,
+        index: usize,
+        options: stash::apply::Options,
+    ) -> Result<stash::apply::Outcome, stash::Error> {
+        stash::function::apply(self, index, options)
+    }
+
+    /// Like [`stash_apply()`][Self::stash_apply()], but [drop][Self::stash_drop()] the stash entry at `index` afterwards
+    /// if there were no conflicts, similar to `git stash pop`.
+    pub fn stash_pop(
+        &self,
+        index: usize,
+        options: stash::apply::Options,
+    ) -> Result<stash::apply::Outcome, stash::Error> {
+        let outcome = stash::function::apply(self, index, options)?;
+        if outcome.conflicts.is_empty() {
+            stash::function::drop(self, index)?;
+        }
+        Ok(outcome)
+    }
+
+    /// Remove the stash entry at `index` from the reflog of `refs/stash` and return its id, similar to `git stash drop stash@{<index>}`.
+    ///
+    /// If it was the most recent entry, `refs/stash` points to the one before it, and it's deleted if it was the only one.
+    pub fn stash_drop(&self, index: usize) -> Result<ObjectId, stash::Error> {
+        stash::function::drop(self, index)
+    }
+
+    /// Remove all stash entries by deleting `refs/stash` along with its reflog, similar to `git stash clear`.
+    pub fn stash_clear(&self) -> Result<(), stash::Error> {
+        stash::function::clear(self)
+    }
+}
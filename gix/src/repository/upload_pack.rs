@@ -0,0 +1,18 @@
+use crate::{upload_pack, Repository};
+
+impl Repository {
+    /// Serve a fetch or clone of a `git` client by reading its requests from `input` and writing our responses to `output`,
+    /// similar to `git upload-pack`.
+    ///
+    /// The [protocol version][upload_pack::Options::version] has to be provided by the caller, which typically obtains it
+    /// from the `GIT_PROTOCOL` environment variable with [`upload_pack::Version::from_git_protocol()`].
+    /// Errors caused by the client are also sent to it before they are returned.
+    pub fn upload_pack(
+        &self,
+        input: impl std::io::Read,
+        output: impl std::io::Write,
+        options: upload_pack::Options,
+    ) -> Result<(), upload_pack::Error> {
+        upload_pack::function::upload_pack(self, input, output, options)
+    }
+}
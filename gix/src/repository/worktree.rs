@@ -27,6 +27,50 @@ impl crate::Repository {
     }
 }
 
+/// Worktree management
+impl crate::Repository {
+    /// Create a new linked worktree at `path` and return it as repository, similar to `git worktree add <path>`.
+    ///
+    /// `path` must not exist or be an empty directory. The private git directory of the worktree is created in the `worktrees`
+    /// directory of the common git directory, named after the last component of `path` and made unique if needed.
+    /// `options` determine whether a branch or a detached `HEAD` is checked out, with the branch named after the last component
+    /// of `path` being the default, which is created at `HEAD` if it doesn't exist yet.
+    ///
+    /// On failure, everything that was created for the worktree is removed again, except for new branches. After the checkout,
+    /// the `post-checkout` [hook][crate::hook::name::POST_CHECKOUT] runs, and the worktree remains even if it fails.
+    #[allow(clippy::result_large_err)]
+    pub fn add_worktree(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        options: worktree::add::Options,
+    ) -> Result<crate::Repository, worktree::add::Error> {
+        worktree::add::add_worktree(self, path.as_ref(), options)
+    }
+
+    /// Remove the administrative files of linked worktrees that are unusable or whose location doesn't exist anymore,
+    /// similar to `git worktree prune`. Locked worktrees are never pruned.
+    ///
+    /// Use [`Proxy::remove()`][worktree::Proxy::remove()] to remove a worktree along with its files.
+    pub fn prune_worktrees(
+        &self,
+        options: worktree::prune::Options,
+    ) -> Result<worktree::prune::Outcome, worktree::prune::Error> {
+        worktree::prune::prune_worktrees(self, options)
+    }
+
+    /// Repair the links between linked worktrees and their private git directories, similar to `git worktree repair [<path>…]`.
+    ///
+    /// The `.git` file of all existing worktrees is made to point to the private git directory of the worktree.
+    /// Worktrees that were moved without [`Proxy::move_to()`][worktree::Proxy::move_to()] are unknown to the repository, so their
+    /// new location has to be passed in `paths` to let their private git directory point to them again.
+    pub fn repair_worktrees(
+        &self,
+        paths: impl IntoIterator<Item = impl AsRef<std::path::Path>>,
+    ) -> Result<worktree::repair::Outcome, worktree::repair::Error> {
+        worktree::repair::repair_worktrees(self, paths)
+    }
+}
+
 /// Interact with individual worktrees and their information.
 impl crate::Repository {
     /// Return the repository owning the main worktree, typically from a linked worktree.
@@ -116,4 +160,34 @@ impl crate::Repository {
                 )),
             })
     }
+
+    /// Create an index in memory from the entries of `tree`, which will be written to the location of this repository's
+    /// index file, like `git read-tree <tree>` does.
+    ///
+    /// The tree cache extension is produced as well, so [writing the tree][Self::write_tree_from_index()] of the unchanged index
+    /// won't write any object.
+    pub fn index_from_tree(
+        &self,
+        tree: &gix_hash::oid,
+    ) -> Result<gix_index::File, gix_traverse::tree::breadthfirst::Error> {
+        use gix_odb::FindExt;
+        Ok(gix_index::File::from_state(
+            gix_index::State::from_tree(tree, |oid, buf| self.objects.find_tree_iter(oid, buf).ok())?,
+            self.index_path(),
+        ))
+    }
+
+    /// Write the trees needed to represent the entries of `index` and return the id of the root tree, like `git write-tree` does.
+    ///
+    /// Trees that are valid according to the tree cache extension of `index` are reused, and the extension is updated
+    /// afterwards. Thus the paths of entries changed without updating it must be [invalidated][gix_index::State::invalidate_tree_path()].
+    pub fn write_tree_from_index(
+        &self,
+        index: &mut gix_index::State,
+    ) -> Result<crate::Id<'_>, gix_index::write_tree::Error<crate::object::write::Error>> {
+        use crate::ext::ObjectIdExt;
+        index
+            .write_tree(|tree| self.write_object(tree).map(crate::Id::detach))
+            .map(|id| id.attach(self))
+    }
 }
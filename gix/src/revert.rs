@@ -0,0 +1,17 @@
+//! Undo the changes of a commit, similar to `git revert`, by replaying them in reverse just like [cherry-picking][crate::cherry_pick]
+//! replays them.
+pub use crate::cherry_pick::{Error, Outcome};
+
+/// Options to control how [`Repository::revert()`][crate::Repository::revert()] undoes the changes of a commit.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The number of the parent, starting at `1`, whose changes to a merge commit are undone, similar to
+    /// `git revert --mainline <parent>`. It must be set for merge commits, and it must not be set for any other commit.
+    pub mainline: Option<usize>,
+    /// If `true`, a commit is created even if it doesn't change anything, for instance because the changes were
+    /// undone before. Otherwise, that's an error.
+    pub allow_empty: bool,
+    /// If `true`, the new commit is [signed][crate::Repository::sign()], similar to `git revert --gpg-sign`.
+    /// It's not signed otherwise, even if `commit.gpgSign` is set.
+    pub sign: bool,
+}
@@ -0,0 +1,518 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    path::Path,
+    sync::atomic::AtomicBool,
+};
+
+use gix_features::progress;
+use gix_hash::ObjectId;
+use gix_index::entry::{Flags, Mode, Stat};
+use gix_odb::FindExt;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullNameRef, Target,
+};
+
+use crate::{
+    bstr::{BStr, BString},
+    checkout::function::{remove_from_worktree, untracked_in_the_way},
+    merge,
+    stash::{apply, push, Entry, Error, REFERENCE},
+    Repository,
+};
+
+pub(crate) fn push(repo: &Repository, options: push::Options) -> Result<Option<ObjectId>, Error> {
+    let workdir = repo.work_dir().ok_or(Error::BareRepository)?;
+    let mut head = repo.head()?;
+    let branch = head
+        .referent_name()
+        .map_or_else(|| "(no branch)".into(), |name| name.shorten().to_owned());
+    let head_id = head.peel_to_id_in_place().transpose()?.ok_or(Error::NoInitialCommit)?;
+    let head_commit = head_id.object()?.peel_to_kind(gix_object::Kind::Commit)?.into_commit();
+    let head_tree = head_commit.tree_id()?.detach();
+    let on = format!(
+        "{branch}: {} {}",
+        head_id.shorten_or_id(),
+        head_commit.message()?.summary()
+    );
+
+    let index_path = repo.index_path();
+    let lock = gix_lock::File::acquire_to_update_resource(&index_path, gix_lock::acquire::Fail::Immediately, None)?;
+    let mut index = gix_index::File::at_or_default(index_path, repo.object_hash(), Default::default())?;
+    if let Some(entry) = index.entries().iter().find(|entry| entry.stage() != 0) {
+        return Err(Error::Unmerged {
+            path: entry.path(&index).to_owned(),
+        });
+    }
+    let index_tree = repo.write_tree_from_index(&mut index)?.detach();
+
+    // Stage all changes to tracked files, and untracked files if desired, to learn how to store the working tree.
+    let mut worktree = gix_index::State::clone(&index);
+    crate::add::function::add_to_state(
+        repo,
+        &mut worktree,
+        None::<&str>,
+        crate::add::Options {
+            update: options.untracked == push::Untracked::Exclude,
+            force: options.untracked == push::Untracked::IncludeIgnored,
+            ..Default::default()
+        },
+    )?;
+    let mut untracked = gix_index::State::new(repo.object_hash());
+    {
+        let tracked: HashSet<&BStr> = index.entries().iter().map(|entry| entry.path(&index)).collect();
+        for entry in worktree.entries() {
+            let path = entry.path(&worktree);
+            if !tracked.contains(path) {
+                untracked.dangerously_push_entry(entry.stat, entry.id, Flags::empty(), entry.mode, path);
+            }
+        }
+        if !untracked.entries().is_empty() {
+            worktree.remove_entries(|_, path, _| !tracked.contains(path));
+        }
+    }
+    let worktree_tree = repo.write_tree_from_index(&mut worktree)?.detach();
+    if index_tree == head_tree && worktree_tree == head_tree && untracked.entries().is_empty() {
+        return Ok(None);
+    }
+
+    let author = repo
+        .author()
+        .ok_or(crate::commit::Error::AuthorMissing)?
+        .map_err(crate::commit::Error::from)?
+        .to_owned();
+    let committer = repo
+        .committer()
+        .ok_or(crate::commit::Error::CommitterMissing)?
+        .map_err(crate::commit::Error::from)?
+        .to_owned();
+    // Like `git stash`, we never sign these commits.
+    let write_commit = |message: &str, tree: ObjectId, parents: &[ObjectId]| -> Result<ObjectId, Error> {
+        let commit = gix_object::Commit {
+            message: message.into(),
+            tree,
+            author: author.clone(),
+            committer: committer.clone(),
+            encoding: None,
+            parents: parents.iter().copied().collect(),
+            extra_headers: Default::default(),
+        };
+        Ok(repo.write_object(&commit).map_err(crate::commit::Error::from)?.detach())
+    };
+    let mut parents = vec![head_id.detach()];
+    parents.push(write_commit(&format!("index on {on}\n"), index_tree, &parents)?);
+    if !untracked.entries().is_empty() {
+        let untracked_tree = repo.write_tree_from_index(&mut untracked)?.detach();
+        parents.push(write_commit(
+            &format!("untracked files on {on}\n"),
+            untracked_tree,
+            &[],
+        )?);
+    }
+    let message = match &options.message {
+        Some(message) => format!("On {branch}: {message}"),
+        None => format!("WIP on {on}"),
+    };
+    // Like `git`, the message of the stash commit doesn't end with a newline, unlike the ones of the other commits.
+    let stash_id = write_commit(&message, worktree_tree, &parents)?;
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: true,
+                message: message.into(),
+            },
+            expected: PreviousValue::Any,
+            new: Target::Peeled(stash_id),
+        },
+        name: REFERENCE.try_into().expect("valid"),
+        deref: false,
+    })?;
+
+    // Reset the index and the working tree, only writing files that changed, and remove the stashed untracked files.
+    let target_tree = if options.keep_index { index_tree } else { head_tree };
+    let target = gix_index::State::from_tree(&target_tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())
+        .map_err(gix_index::merge::Error::from)?;
+    let mut files = gix_index::State::new(repo.object_hash());
+    for entry in target.entries() {
+        let path = entry.path(&target);
+        let is_unchanged = worktree
+            .entry_by_path_and_stage(path, 0)
+            .map_or(false, |current| current.id == entry.id && current.mode == entry.mode);
+        if !is_unchanged {
+            files.dangerously_push_entry(Stat::default(), entry.id, Flags::empty(), entry.mode, path);
+        }
+    }
+    for entry in worktree.entries() {
+        let path = entry.path(&worktree);
+        if target.entry_by_path_and_stage(path, 0).is_none() {
+            remove_from_worktree(workdir, path)?;
+        }
+    }
+    if options.untracked != push::Untracked::Exclude {
+        for entry in untracked.entries() {
+            remove_from_worktree(workdir, entry.path(&untracked))?;
+        }
+    }
+    let files = write_to_worktree(repo, workdir, files)?;
+
+    worktree.merge_one_way(&target_tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    for (entry, path) in worktree.entries_mut_with_paths() {
+        if let Some(file) = files.entry_by_path_and_stage(path, 0) {
+            entry.stat = file.stat;
+        }
+    }
+    write_index(gix_index::File::from_state(worktree, index.path().to_owned()), lock)?;
+    Ok(Some(stash_id))
+}
+
+pub(crate) fn list(repo: &Repository) -> Result<Vec<Entry>, Error> {
+    let mut buf = Vec::new();
+    let mut entries = match repo.refs.reflog_iter(REFERENCE, &mut buf)? {
+        Some(log) => log
+            .map(|line| {
+                line.map(|line| Entry {
+                    id: line.new_oid(),
+                    message: line.message.to_owned(),
+                    signature: line.signature.into(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => Vec::new(),
+    };
+    entries.reverse();
+    Ok(entries)
+}
+
+pub(crate) fn apply(repo: &Repository, index: usize, options: apply::Options) -> Result<apply::Outcome, Error> {
+    let workdir = repo.work_dir().ok_or(Error::BareRepository)?;
+    let stash_id = list(repo)?
+        .get(index)
+        .map(|entry| entry.id)
+        .ok_or(Error::NoSuchEntry { index })?;
+    let stash = repo
+        .find_object(stash_id)?
+        .peel_to_kind(gix_object::Kind::Commit)?
+        .into_commit();
+    let parents: Vec<_> = stash.parent_ids().map(crate::Id::detach).collect();
+    if parents.len() < 2 {
+        return Err(Error::NotAStash { id: stash_id });
+    }
+    let tree_of = |id: &ObjectId| -> Result<ObjectId, Error> { Ok(repo.find_object(*id)?.peel_to_tree()?.id) };
+    let stash_tree = stash.tree_id()?.detach();
+    let base_tree = tree_of(&parents[0])?;
+    let index_tree = tree_of(&parents[1])?;
+    let untracked_tree = parents.get(2).map(tree_of).transpose()?;
+
+    let index_path = repo.index_path();
+    let lock = gix_lock::File::acquire_to_update_resource(&index_path, gix_lock::acquire::Fail::Immediately, None)?;
+    let mut current = gix_index::File::at_or_default(index_path, repo.object_hash(), Default::default())?;
+    if let Some(entry) = current.entries().iter().find(|entry| entry.stage() != 0) {
+        return Err(Error::Unmerged {
+            path: entry.path(&current).to_owned(),
+        });
+    }
+    let current_tree = repo.write_tree_from_index(&mut current)?.detach();
+
+    let labels = merge::Labels {
+        ours: "Updated upstream".into(),
+        theirs: "Stashed changes".into(),
+    };
+    let restored_index = if options.index && index_tree != base_tree && index_tree != current_tree {
+        let merged = repo.merge_trees(base_tree, current_tree, index_tree, labels)?;
+        if !merged.conflicts.is_empty() {
+            return Err(Error::IndexConflicts);
+        }
+        Some(merged.index)
+    } else {
+        None
+    };
+    let merged = repo.merge_trees(base_tree, current_tree, stash_tree, labels)?;
+    let untracked = match untracked_tree {
+        Some(tree) => gix_index::State::from_tree(&tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())
+            .map_err(gix_index::merge::Error::from)?,
+        None => gix_index::State::new(repo.object_hash()),
+    };
+
+    // Learn what to write to the working tree, which is the merged version of files, or their version with conflict markers.
+    let previous: HashMap<BString, (ObjectId, Mode)> = current
+        .entries()
+        .iter()
+        .map(|entry| (entry.path(&current).to_owned(), (entry.id, entry.mode)))
+        .collect();
+    let mut files = gix_index::State::new(repo.object_hash());
+    for entry in merged.index.entries().iter().filter(|entry| entry.stage() == 0) {
+        let path = entry.path(&merged.index);
+        if previous.get(path) != Some(&(entry.id, entry.mode)) {
+            files.dangerously_push_entry(Stat::default(), entry.id, Flags::empty(), entry.mode, path);
+        }
+    }
+    for conflict in &merged.conflicts {
+        let path = conflict.path.as_ref();
+        let ours = merged.index.entry_by_path_and_stage(path, 2);
+        let theirs = merged.index.entry_by_path_and_stage(path, 3);
+        let file = match (conflict.merged_blob, ours, theirs) {
+            (Some(id), Some(entry), _) | (Some(id), None, Some(entry)) => Some((id, entry.mode)),
+            (None, None, Some(theirs)) => Some((theirs.id, theirs.mode)),
+            _ => None,
+        };
+        if let Some((id, mode)) = file {
+            files.dangerously_push_entry(Stat::default(), id, Flags::empty(), mode, path);
+        }
+    }
+    files.sort_entries();
+    let removed: Vec<BString> = current
+        .entries()
+        .iter()
+        .map(|entry| entry.path(&current))
+        .filter(|path| {
+            merged
+                .index
+                .entry_index_by_path_and_stage(path, 0)
+                .or_else(|| merged.index.entry_index_by_path_and_stage(path, 2))
+                .is_none()
+        })
+        .map(ToOwned::to_owned)
+        .collect();
+
+    // Assure that neither local changes nor untracked files are lost.
+    let mut modified = HashSet::new();
+    {
+        let mut recorder = gix_worktree::status::Recorder::default();
+        let options = repo.config.checkout_options(repo.git_dir())?;
+        let objects = repo.objects.clone().into_arc().map_err(Error::OpenArcOdb)?;
+        gix_worktree::status(
+            &mut current,
+            workdir,
+            &mut recorder,
+            gix_worktree::status::content::FastEq,
+            move |id, buf| objects.find_blob(id, buf),
+            gix_worktree::status::Options {
+                fs: options.fs,
+                thread_limit: options.thread_limit,
+                stat: options.stat_options,
+            },
+        )?;
+        for (path, change, _conflict) in recorder.records {
+            if matches!(change, Some(change) if !matches!(change, gix_worktree::status::Change::Removed)) {
+                modified.insert(path.to_owned());
+            }
+        }
+    }
+    let mut local_changes: Vec<BString> = files
+        .entries()
+        .iter()
+        .map(|entry| entry.path(&files))
+        .chain(removed.iter().map(AsRef::as_ref))
+        .filter(|path| modified.contains(*path))
+        .map(ToOwned::to_owned)
+        .collect();
+    if !local_changes.is_empty() {
+        local_changes.sort();
+        return Err(Error::LocalChanges { paths: local_changes });
+    }
+    let mut untracked_in_the_way_paths = Vec::new();
+    {
+        let mut excludes = repo
+            .worktree()
+            .expect("present as we have a working tree")
+            .excludes(&current, None)?;
+        for entry in files.entries() {
+            let path = entry.path(&files);
+            if !previous.contains_key(path) {
+                if let Some(in_the_way) =
+                    untracked_in_the_way(repo, workdir, path, entry.mode, &previous, &mut excludes)?
+                {
+                    untracked_in_the_way_paths.push(in_the_way);
+                }
+            }
+        }
+    }
+    for entry in untracked.entries() {
+        let path = entry.path(&untracked);
+        match workdir.join(gix_path::from_bstr(path)).symlink_metadata() {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            _ => untracked_in_the_way_paths.push(path.to_owned()),
+        }
+    }
+    if !untracked_in_the_way_paths.is_empty() {
+        return Err(Error::UntrackedFiles {
+            paths: untracked_in_the_way_paths,
+        });
+    }
+
+    for path in &removed {
+        remove_from_worktree(workdir, path.as_ref())?;
+    }
+    for entry in untracked.entries() {
+        files.dangerously_push_entry(
+            Stat::default(),
+            entry.id,
+            Flags::empty(),
+            entry.mode,
+            entry.path(&untracked),
+        );
+    }
+    files.sort_entries();
+    let files = write_to_worktree(repo, workdir, files)?;
+
+    let mut index = if !merged.conflicts.is_empty() {
+        merged.index
+    } else if let Some(restored_index) = restored_index {
+        restored_index
+    } else {
+        let mut index = gix_index::State::clone(&current);
+        if !options.index {
+            // Like `git`, stage files that were added, but nothing else.
+            for entry in merged.index.entries() {
+                let path = entry.path(&merged.index);
+                if !previous.contains_key(path) {
+                    index.dangerously_push_entry(Stat::default(), entry.id, Flags::empty(), entry.mode, path);
+                }
+            }
+            index.sort_entries();
+        }
+        index
+    };
+    for (entry, path) in index.entries_mut_with_paths() {
+        if entry.stage() != 0 {
+            continue;
+        }
+        let stat = files
+            .entry_by_path_and_stage(path, 0)
+            .filter(|file| file.id == entry.id)
+            .or_else(|| {
+                current
+                    .entry_by_path_and_stage(path, 0)
+                    .filter(|previous| previous.id == entry.id && previous.mode == entry.mode)
+            })
+            .map(|file| file.stat);
+        if let Some(stat) = stat {
+            entry.stat = stat;
+        }
+    }
+    write_index(gix_index::File::from_state(index, current.path().to_owned()), lock)?;
+
+    Ok(apply::Outcome {
+        conflicts: merged.conflicts,
+        untracked: untracked
+            .entries()
+            .iter()
+            .map(|entry| entry.path(&untracked).to_owned())
+            .collect(),
+    })
+}
+
+pub(crate) fn drop(repo: &Repository, index: usize) -> Result<ObjectId, Error> {
+    let name: &FullNameRef = REFERENCE.try_into().expect("valid");
+    let reflog_path = repo.refs.reflog_path(name);
+    let mut lock =
+        gix_lock::File::acquire_to_update_resource(&reflog_path, gix_lock::acquire::Fail::Immediately, None)?;
+    let mut buf = Vec::new();
+    let mut lines = match repo.refs.reflog_iter(name, &mut buf)? {
+        Some(log) => log
+            .map(|line| line.map(|line| line.to_owned()))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => Vec::new(),
+    };
+    if index >= lines.len() {
+        return Err(Error::NoSuchEntry { index });
+    }
+    let pos = lines.len() - 1 - index;
+    let dropped = lines.remove(pos);
+    if lines.is_empty() {
+        std::mem::drop(lock);
+        clear(repo)?;
+        return Ok(dropped.new_oid);
+    }
+
+    // Like `git reflog delete --rewrite --updateref`, keep the chain of ids intact and let the reference point to the last entry.
+    if let Some(next) = lines.get_mut(pos) {
+        next.previous_oid = dropped.previous_oid;
+    } else {
+        // The reflog is rewritten below while we hold its lock, so the reference must be updated without logging.
+        let mut repo = repo.clone();
+        repo.refs.write_reflog = gix_ref::store::WriteReflog::Disable;
+        repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: Default::default(),
+                expected: PreviousValue::MustExistAndMatch(Target::Peeled(dropped.new_oid)),
+                new: Target::Peeled(lines.last().expect("not empty").new_oid),
+            },
+            name: name.to_owned(),
+            deref: false,
+        })?;
+    }
+    lock.with_mut(|out| lines.iter().try_for_each(|line| line.write_to(&mut *out)))
+        .map_err(|source| Error::Io {
+            source,
+            path: lock.lock_path().to_owned(),
+        })?;
+    lock.commit()?;
+    Ok(dropped.new_oid)
+}
+
+pub(crate) fn clear(repo: &Repository) -> Result<(), Error> {
+    if repo.try_find_reference(REFERENCE)?.is_some() {
+        repo.edit_reference(RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::Any,
+                log: RefLog::AndReference,
+            },
+            name: REFERENCE.try_into().expect("valid"),
+            deref: false,
+        })?;
+    }
+    Ok(())
+}
+
+/// Write the blobs of `files` to their paths in the working tree, replacing the files that are there, and return `files`
+/// with the stat information of each written file. Submodules aren't checked out, only their directory is created.
+fn write_to_worktree(
+    repo: &Repository,
+    workdir: &Path,
+    mut files: gix_index::State,
+) -> Result<gix_index::State, Error> {
+    for (entry, path) in files.entries_mut_with_paths() {
+        let path = workdir.join(gix_path::from_bstr(path));
+        match path.symlink_metadata() {
+            Ok(meta) if !meta.is_dir() => std::fs::remove_file(&path).map_err(|err| Error::Io {
+                source: err,
+                path: path.clone(),
+            })?,
+            _ => {}
+        }
+        if entry.mode == Mode::COMMIT {
+            std::fs::create_dir_all(&path).map_err(|err| Error::Io { source: err, path })?;
+        }
+    }
+    files.remove_entries(|_, _, entry| entry.mode == Mode::COMMIT);
+
+    let mut options = repo.config.checkout_options(repo.git_dir())?;
+    options.destination_is_initially_empty = false;
+    options.overwrite_existing = true;
+    let objects = repo.objects.clone().into_arc().map_err(Error::OpenArcOdb)?;
+    gix_worktree::checkout(
+        &mut files,
+        workdir,
+        move |oid, buf| objects.find_blob(oid, buf),
+        &mut progress::Discard,
+        &mut progress::Discard,
+        &AtomicBool::default(),
+        options,
+    )?;
+    Ok(files)
+}
+
+fn write_index(index: gix_index::File, lock: gix_lock::File) -> Result<(), Error> {
+    let mut lock = std::io::BufWriter::new(lock);
+    index
+        .write_to(&mut lock, Default::default())
+        .map_err(Error::WriteIndex)?;
+    lock.into_inner()
+        .map_err(|err| Error::WriteIndex(err.into_error()))?
+        .commit()?;
+    Ok(())
+}
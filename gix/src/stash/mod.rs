@@ -0,0 +1,165 @@
+//! Save local changes of the index and the working tree as commits and restore them later, similar to `git stash`.
+//!
+//! Like with `git`, each stash entry is a commit of the working tree whose parents are the commit `HEAD` pointed to, a commit
+//! of the index and, if untracked files were saved, a parentless commit of these. The most recent entry is referenced
+//! by `refs/stash`, and all entries are found in its reflog, which makes them compatible with the ones `git` creates.
+use std::path::PathBuf;
+
+use gix_hash::ObjectId;
+
+use crate::bstr::BString;
+
+pub(crate) mod function;
+
+/// The name of the reference pointing to the most recent stash entry, with all entries being in its reflog.
+pub const REFERENCE: &str = "refs/stash";
+
+///
+pub mod push {
+    use crate::bstr::BString;
+
+    /// Determines which untracked files are saved in a stash entry and removed from the working tree.
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Untracked {
+        /// Untracked files are left alone, similar to `git stash push`.
+        #[default]
+        Exclude,
+        /// Untracked files that aren't ignored are saved, similar to `git stash push --include-untracked`.
+        Include,
+        /// Untracked files are saved along with ignored files, similar to `git stash push --all`.
+        IncludeIgnored,
+    }
+
+    /// Options to control how [`Repository::stash_push()`][crate::Repository::stash_push()] saves local changes.
+    #[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct Options {
+        /// The message describing the stash entry, similar to `git stash push --message`. If unset, it's the summary of the
+        /// commit `HEAD` points to.
+        pub message: Option<BString>,
+        /// Which untracked files to save in the stash entry as well.
+        pub untracked: Untracked,
+        /// If `true`, the changes in the index are kept in the index and in the working tree, similar to
+        /// `git stash push --keep-index`.
+        pub keep_index: bool,
+    }
+}
+
+///
+pub mod apply {
+    use crate::bstr::BString;
+
+    /// Options to control how [`Repository::stash_apply()`][crate::Repository::stash_apply()] restores a stash entry.
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Options {
+        /// If `true`, the changes to the index are restored as well, similar to `git stash apply --index`. Otherwise, only
+        /// files added to the index are restored into it.
+        pub index: bool,
+    }
+
+    /// The result of [`Repository::stash_apply()`][crate::Repository::stash_apply()].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Outcome {
+        /// All paths that couldn't be merged, which are unmerged in the index and contain conflict markers in the working
+        /// tree if they are text files changed by both sides.
+        pub conflicts: Vec<crate::merge::Conflict>,
+        /// The paths of all untracked files that were restored, in index order.
+        pub untracked: Vec<BString>,
+    }
+}
+
+/// A stash entry as listed by [`Repository::stash_list()`][crate::Repository::stash_list()].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    /// The id of the commit holding the working tree and referring to all other commits of the stash entry.
+    pub id: ObjectId,
+    /// The message describing the entry, like `WIP on main: 1234567 subject`.
+    pub message: BString,
+    /// The signature of the one who created the entry, along with the time of creation.
+    pub signature: gix_actor::Signature,
+}
+
+/// The error returned by the stash methods of [`Repository`][crate::Repository].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Stash entries can't be created or applied in a bare repository")]
+    BareRepository,
+    #[error("There is nothing to stash as there is no initial commit yet")]
+    NoInitialCommit,
+    #[error("There is no stash entry at index {index}")]
+    NoSuchEntry { index: usize },
+    #[error("The commit {id} isn't a stash entry as it doesn't have the index commit as second parent")]
+    NotAStash { id: ObjectId },
+    #[error("'{path}' is unmerged and needs to be resolved first")]
+    Unmerged { path: BString },
+    #[error("The changes of the stash entry to the index conflict with the index, try to apply it without the index")]
+    IndexConflicts,
+    #[error(
+        "Local changes to the following files would be overwritten by applying the stash entry: {}",
+        join(paths)
+    )]
+    LocalChanges { paths: Vec<BString> },
+    #[error(
+        "The following untracked working tree files would be overwritten by applying the stash entry: {}",
+        join(paths)
+    )]
+    UntrackedFiles { paths: Vec<BString> },
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    PeelHeadToId(#[from] crate::head::peel::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error("Could not read the reflog of '{}'", REFERENCE)]
+    ReadReflog(#[from] gix_ref::file::log::Error),
+    #[error("Could not decode the reflog of '{}'", REFERENCE)]
+    DecodeReflog(#[from] gix_ref::file::log::iter::decode::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToKind(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::commit::Error),
+    #[error(transparent)]
+    Add(#[from] crate::add::Error),
+    #[error(transparent)]
+    Merge(#[from] crate::merge::Error),
+    #[error(transparent)]
+    MergeIntoIndex(#[from] gix_index::merge::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error<crate::object::write::Error>),
+    #[error("Could not lock a file for writing")]
+    AcquireLock(#[from] gix_lock::acquire::Error),
+    #[error("Could not read the index")]
+    ReadIndex(#[from] gix_index::file::init::Error),
+    #[error("Could not write the index")]
+    WriteIndex(#[source] std::io::Error),
+    #[error("Could not commit the lock of a file")]
+    CommitLock(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    Status(#[from] gix_worktree::status::Error),
+    #[error(transparent)]
+    Excludes(#[from] crate::worktree::excludes::Error),
+    #[error("Could not access '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error(transparent)]
+    Checkout(#[from] gix_worktree::checkout::Error<gix_odb::find::existing_object::Error<gix_odb::store::find::Error>>),
+}
+
+impl From<crate::checkout::function::IoError> for Error {
+    fn from(crate::checkout::function::IoError { source, path }: crate::checkout::function::IoError) -> Self {
+        Error::Io { source, path }
+    }
+}
+
+fn join(paths: &[BString]) -> String {
+    paths.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
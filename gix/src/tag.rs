@@ -11,6 +11,10 @@ mod error {
         WriteObject(#[from] crate::object::write::Error),
         #[error(transparent)]
         ReferenceEdit(#[from] crate::reference::edit::Error),
+        #[error("Could not serialize the tag to sign it")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Sign(#[from] crate::gpg::sign::Error),
     }
 }
 pub use error::Error;
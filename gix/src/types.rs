@@ -153,6 +153,8 @@ pub struct Repository {
     pub(crate) options: crate::open::Options,
     pub(crate) index: crate::worktree::IndexStorage,
     pub(crate) shallow_commits: crate::shallow::CommitsStorage,
+    /// Hooks implemented as functions, to run in place of hook programs.
+    pub(crate) hooks: crate::hook::Functions,
 }
 
 /// An instance with access to everything a git repository entails, best imagined as container implementing `Sync + Send` for _most_
@@ -177,6 +179,8 @@ pub struct ThreadSafeRepository {
     /// The index of this instances worktree.
     pub(crate) index: crate::worktree::IndexStorage,
     pub(crate) shallow_commits: crate::shallow::CommitsStorage,
+    /// Hooks implemented as functions, to run in place of hook programs.
+    pub(crate) hooks: crate::hook::Functions,
 }
 
 /// A remote which represents a way to interact with hosts for remote clones of the parent repository.
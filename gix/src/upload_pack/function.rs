@@ -0,0 +1,448 @@
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+};
+
+use gix_hash::ObjectId;
+use gix_protocol::{
+    fetch::response::ShallowUpdate,
+    transport::packetline::{encode, PacketLineRef, StreamingPeekableIter},
+};
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    upload_pack::{
+        graph::{self, Graph, Negotiation},
+        invalid_request, pack,
+        refs::{self, Ref},
+        request::{Arguments, MultiAck},
+        Error, Options, Version,
+    },
+    Repository,
+};
+
+pub(crate) type Lines<R> = StreamingPeekableIter<R>;
+
+pub(crate) fn upload_pack(
+    repo: &Repository,
+    input: impl Read,
+    mut output: impl Write,
+    options: Options,
+) -> Result<(), Error> {
+    let mut lines = StreamingPeekableIter::new(input, &[PacketLineRef::Flush, PacketLineRef::Delimiter]);
+    let res = match options.version {
+        Version::V0 | Version::V1 => serve_v0(repo, &mut lines, &mut output, options),
+        Version::V2 => serve_v2(repo, &mut lines, &mut output, options),
+    };
+    if let Err(err) = &res {
+        if let Some(message) = err.client_message() {
+            encode::error_to_write(message.as_bytes(), &mut output).ok();
+        }
+    }
+    output.flush()?;
+    res
+}
+
+/// A line read from the client.
+pub(crate) enum Line {
+    /// A data line without trailing newline.
+    Data(BString),
+    /// A flush or delimiter packet.
+    Stop(PacketLineRef<'static>),
+    /// The client hung up.
+    Eof,
+}
+
+pub(crate) fn read_line<E>(lines: &mut Lines<impl Read>) -> Result<Line, E>
+where
+    E: From<std::io::Error> + From<gix_protocol::transport::packetline::decode::Error>,
+{
+    Ok(match lines.read_line() {
+        Some(Ok(line)) => {
+            let line = line?;
+            let data = line.as_slice().unwrap_or_default();
+            Line::Data(data.strip_suffix(b"\n").unwrap_or(data).into())
+        }
+        Some(Err(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => Line::Eof,
+        Some(Err(err)) => return Err(err.into()),
+        None => {
+            let stopped_at = lines.stopped_at().expect("stopped at flush or delimiter");
+            lines.reset();
+            Line::Stop(stopped_at)
+        }
+    })
+}
+
+/// Read data lines until the next flush or delimiter packet, and return them without trailing newline along with the
+/// packet that stopped the iteration, or `None` if the input is exhausted.
+fn read_lines(lines: &mut Lines<impl Read>) -> Result<(Vec<BString>, Option<PacketLineRef<'static>>), Error> {
+    let mut out = Vec::new();
+    loop {
+        match read_line::<Error>(lines)? {
+            Line::Data(line) => out.push(line),
+            Line::Stop(stopped_at) => return Ok((out, Some(stopped_at))),
+            Line::Eof => return Ok((out, None)),
+        }
+    }
+}
+
+pub(crate) fn text(out: &mut impl Write, line: impl AsRef<[u8]>) -> std::io::Result<()> {
+    encode::text_to_write(line.as_ref(), out).map(|_| ())
+}
+
+/// Return the name of `kind` as used in the `object-format` capability.
+pub(crate) fn object_format(kind: gix_hash::Kind) -> &'static str {
+    match kind {
+        gix_hash::Kind::Sha1 => "sha1",
+    }
+}
+
+/// Fail if one of the `wants` isn't the target of one of our `refs`.
+fn assure_wants_are_advertised(refs: &[Ref], wants: &[ObjectId]) -> Result<(), Error> {
+    let advertised: HashSet<_> = refs.iter().flat_map(|r| r.id.into_iter().chain(r.peeled)).collect();
+    match wants.iter().find(|id| !advertised.contains(*id)) {
+        Some(id) => Err(Error::NotOurRef { id: *id }),
+        None => Ok(()),
+    }
+}
+
+fn serve_v0(
+    repo: &Repository,
+    lines: &mut Lines<impl Read>,
+    out: &mut impl Write,
+    options: Options,
+) -> Result<(), Error> {
+    let refs = refs::collect(repo)?;
+    if !options.stateless_rpc || options.advertise_refs {
+        if options.version == Version::V1 {
+            text(out, "version 1")?;
+        }
+        let mut capabilities = String::from(
+            "multi_ack side-band side-band-64k ofs-delta shallow deepen-since deepen-not deepen-relative no-progress include-tag multi_ack_detailed",
+        );
+        if options.stateless_rpc {
+            capabilities.push_str(" no-done");
+        }
+        if let Some(target) = refs
+            .first()
+            .and_then(|head| head.symref_target.as_ref().filter(|_| head.id.is_some()))
+        {
+            capabilities.push_str(&format!(" symref=HEAD:{target}"));
+        }
+        capabilities.push_str(&format!(
+            " object-format={} agent={}",
+            object_format(repo.object_hash()),
+            repo.config.user_agent_tuple().1.expect("set")
+        ));
+        let shallow = Graph::new(repo)?.shallow_commits().to_vec();
+        refs::advertise(&refs, &capabilities, &shallow, repo.object_hash(), &mut *out)?;
+        out.flush()?;
+    }
+    if options.advertise_refs {
+        return Ok(());
+    }
+
+    let (request, _) = read_lines(lines)?;
+    if request.is_empty() {
+        // The client only wanted to see our refs, like `git ls-remote`.
+        return Ok(());
+    }
+    let mut arguments = Arguments::v0();
+    for line in &request {
+        arguments.parse_v0(line.as_ref())?;
+    }
+    assure_wants_are_advertised(&refs, &arguments.wants)?;
+
+    let mut graph = Graph::new(repo)?;
+    let wants = pack::Wants::peel(repo, &arguments.wants)?;
+    let shallow = graph::deepen(&mut graph, &arguments, &wants.commits)?;
+    if let Some(shallow) = &shallow {
+        write_shallow_updates(out, &shallow.updates)?;
+        encode::flush_to_write(&mut *out)?;
+        out.flush()?;
+    }
+
+    let mut negotiation = Negotiation::default();
+    if !negotiate_v0(
+        &mut graph,
+        &mut negotiation,
+        &arguments,
+        &wants.commits,
+        lines,
+        out,
+        options,
+    )? {
+        return Ok(());
+    }
+    send_pack(
+        repo,
+        &mut graph,
+        &refs,
+        &arguments,
+        &wants,
+        &negotiation,
+        shallow,
+        out,
+        options,
+    )?;
+    Ok(())
+}
+
+/// Negotiate with the client like `git` does, and return `true` if a pack should be sent afterwards.
+fn negotiate_v0(
+    graph: &mut Graph<'_>,
+    negotiation: &mut Negotiation,
+    arguments: &Arguments,
+    wants: &[ObjectId],
+    lines: &mut Lines<impl Read>,
+    out: &mut impl Write,
+    options: Options,
+) -> Result<bool, Error> {
+    let mut last_common = None::<ObjectId>;
+    let (mut got_common, mut got_other, mut sent_ready) = (false, false, false);
+    loop {
+        let line = match read_line::<Error>(lines)? {
+            Line::Data(line) => line,
+            Line::Eof => return Ok(false),
+            Line::Stop(_) => {
+                if arguments.multi_ack == MultiAck::Detailed
+                    && got_common
+                    && !got_other
+                    && negotiation.is_ready(graph, wants)?
+                {
+                    sent_ready = true;
+                    text(out, format!("ACK {} ready", last_common.expect("got common")))?;
+                }
+                if negotiation.common.is_empty() || arguments.multi_ack != MultiAck::None {
+                    text(out, "NAK")?;
+                }
+                out.flush()?;
+                if arguments.no_done && sent_ready {
+                    text(out, format!("ACK {}", last_common.expect("ready implies common")))?;
+                    return Ok(true);
+                }
+                if options.stateless_rpc {
+                    return Ok(false);
+                }
+                got_common = false;
+                got_other = false;
+                continue;
+            }
+        };
+
+        if line == "done" {
+            match last_common.filter(|_| !negotiation.common.is_empty()) {
+                Some(id) => {
+                    if arguments.multi_ack != MultiAck::None {
+                        text(out, format!("ACK {id}"))?;
+                    }
+                }
+                None => text(out, "NAK")?,
+            }
+            return Ok(true);
+        }
+        let id = line
+            .strip_prefix(b"have ")
+            .and_then(|hex| ObjectId::from_hex(hex).ok())
+            .ok_or_else(|| invalid_request(format!("expected have, got {line:?}")))?;
+        if negotiation.add_have(graph, id)? {
+            got_common = true;
+            last_common = Some(id);
+            match arguments.multi_ack {
+                MultiAck::Detailed => text(out, format!("ACK {id} common"))?,
+                MultiAck::Basic => text(out, format!("ACK {id} continue"))?,
+                MultiAck::None if negotiation.common.len() == 1 => text(out, format!("ACK {id}"))?,
+                MultiAck::None => {}
+            }
+        } else {
+            got_other = true;
+            if arguments.multi_ack != MultiAck::None && negotiation.is_ready(graph, wants)? {
+                if arguments.multi_ack == MultiAck::Detailed {
+                    sent_ready = true;
+                    text(out, format!("ACK {id} ready"))?;
+                } else {
+                    text(out, format!("ACK {id} continue"))?;
+                }
+            }
+        }
+    }
+}
+
+fn serve_v2(
+    repo: &Repository,
+    lines: &mut Lines<impl Read>,
+    out: &mut impl Write,
+    options: Options,
+) -> Result<(), Error> {
+    if !options.stateless_rpc || options.advertise_refs {
+        text(out, "version 2")?;
+        text(
+            out,
+            repo.config
+                .user_agent_tuple()
+                .1
+                .map(|agent| format!("agent={agent}"))
+                .expect("set"),
+        )?;
+        text(out, "ls-refs=unborn")?;
+        text(out, "fetch=shallow")?;
+        text(out, "server-option")?;
+        text(out, format!("object-format={}", object_format(repo.object_hash())))?;
+        encode::flush_to_write(&mut *out)?;
+        out.flush()?;
+    }
+    if options.advertise_refs {
+        return Ok(());
+    }
+
+    loop {
+        let (request, stopped_at) = read_lines(lines)?;
+        let command = match request.first().and_then(|line| line.strip_prefix(b"command=")) {
+            Some(command) => command.as_bstr().to_owned(),
+            None if request.is_empty() => return Ok(()),
+            None => return Err(invalid_request("expected a command")),
+        };
+        let arguments = match stopped_at {
+            Some(PacketLineRef::Delimiter) => read_lines(lines)?.0,
+            _ => Vec::new(),
+        };
+        match command.as_slice() {
+            b"ls-refs" => refs::ls_refs(&refs::collect(repo)?, &arguments, &mut *out)?,
+            b"fetch" => fetch_v2(repo, &arguments, out, options)?,
+            _ => return Err(Error::UnknownCommand { name: command }),
+        }
+        out.flush()?;
+        if options.stateless_rpc {
+            return Ok(());
+        }
+    }
+}
+
+fn fetch_v2(repo: &Repository, request: &[BString], out: &mut impl Write, options: Options) -> Result<(), Error> {
+    let mut arguments = Arguments::v2();
+    for line in request {
+        arguments.parse_v2(line.as_ref())?;
+    }
+    if arguments.wants.is_empty() {
+        return Err(invalid_request("expected at least one want"));
+    }
+    let refs = refs::collect(repo)?;
+    assure_wants_are_advertised(&refs, &arguments.wants)?;
+
+    let mut graph = Graph::new(repo)?;
+    let wants = pack::Wants::peel(repo, &arguments.wants)?;
+    let mut negotiation = Negotiation::default();
+    for have in &arguments.haves {
+        negotiation.add_have(&mut graph, *have)?;
+    }
+    if !arguments.done {
+        let is_ready = negotiation.is_ready(&mut graph, &wants.commits)?;
+        text(out, "acknowledgments")?;
+        if negotiation.common.is_empty() {
+            text(out, "NAK")?;
+        }
+        for id in &negotiation.common {
+            text(out, format!("ACK {id}"))?;
+        }
+        if !is_ready {
+            encode::flush_to_write(&mut *out)?;
+            return Ok(());
+        }
+        text(out, "ready")?;
+        encode::delim_to_write(&mut *out)?;
+    }
+
+    let shallow = graph::deepen(&mut graph, &arguments, &wants.commits)?;
+    send_pack(
+        repo,
+        &mut graph,
+        &refs,
+        &arguments,
+        &wants,
+        &negotiation,
+        shallow,
+        out,
+        options,
+    )
+}
+
+fn write_shallow_updates(out: &mut impl Write, updates: &[ShallowUpdate]) -> std::io::Result<()> {
+    for update in updates {
+        match update {
+            ShallowUpdate::Shallow(id) => text(out, format!("shallow {id}"))?,
+            ShallowUpdate::Unshallow(id) => text(out, format!("unshallow {id}"))?,
+        }
+    }
+    Ok(())
+}
+
+/// Send the pack with all objects the client needs, preceded by the sections of protocol version 2.
+#[allow(clippy::too_many_arguments)]
+fn send_pack(
+    repo: &Repository,
+    graph: &mut Graph<'_>,
+    refs: &[Ref],
+    arguments: &Arguments,
+    wants: &pack::Wants,
+    negotiation: &Negotiation,
+    shallow: Option<graph::Shallow>,
+    out: &mut impl Write,
+    options: Options,
+) -> Result<(), Error> {
+    let remaining_client_shallow: HashSet<_> = arguments
+        .shallow
+        .iter()
+        .filter(|id| {
+            !shallow
+                .as_ref()
+                .map_or(false, |s| s.unshallow().any(|unshallow| unshallow == *id))
+        })
+        .copied()
+        .collect();
+    let mut tips = wants.commits.clone();
+    let mut boundary = remaining_client_shallow.clone();
+    if let Some(shallow) = &shallow {
+        boundary.extend(shallow.boundary.iter().copied());
+        for id in shallow.unshallow() {
+            tips.extend(graph.commit(id)?.parents.iter().copied());
+        }
+    }
+    let common: Vec<_> = negotiation.common_commits().copied().collect();
+    let all_client_shallow: HashSet<_> = arguments.shallow.iter().copied().collect();
+    let selection = graph::select_commits(graph, &tips, &common, &boundary, &all_client_shallow)?;
+
+    if options.version == Version::V2 {
+        let server_shallow = graph.shallow_commits();
+        if shallow.is_some() || !arguments.shallow.is_empty() || !server_shallow.is_empty() {
+            text(out, "shallow-info")?;
+            match &shallow {
+                Some(shallow) => write_shallow_updates(out, &shallow.updates)?,
+                None => {
+                    let updates: Vec<_> = selection
+                        .commits
+                        .iter()
+                        .filter(|id| graph.is_shallow(id) && !all_client_shallow.contains(*id))
+                        .map(|id| ShallowUpdate::Shallow(*id))
+                        .collect();
+                    write_shallow_updates(out, &updates)?;
+                }
+            }
+            encode::delim_to_write(&mut *out)?;
+        }
+        text(out, "packfile")?;
+    }
+
+    let objects = pack::objects_to_send(repo, wants, &selection, arguments.include_tag.then_some(refs))?;
+    pack::write(
+        repo,
+        objects,
+        options.thread_limit,
+        arguments.sideband,
+        !arguments.no_progress,
+        &mut *out,
+    )?;
+    if arguments.sideband.is_some() {
+        encode::flush_to_write(&mut *out)?;
+    }
+    Ok(())
+}
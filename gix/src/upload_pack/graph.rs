@@ -0,0 +1,359 @@
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use gix_hash::{oid, ObjectId};
+use gix_odb::{Find, FindExt};
+use gix_protocol::fetch::response::ShallowUpdate;
+
+use crate::{
+    bstr::ByteSlice,
+    upload_pack::{request::Arguments, Error},
+    Repository,
+};
+
+/// The parts of a commit needed to traverse the commit graph.
+pub(crate) struct Commit {
+    pub parents: Vec<ObjectId>,
+    /// The commit time in seconds since the unix epoch.
+    pub time: u32,
+}
+
+/// Provide cached access to commits, taking into account that the parents of our own shallow commits are missing.
+pub(crate) struct Graph<'repo> {
+    pub repo: &'repo Repository,
+    shallow: Option<crate::shallow::Commits>,
+    commits: HashMap<ObjectId, Commit>,
+    buf: Vec<u8>,
+}
+
+impl<'repo> Graph<'repo> {
+    pub fn new(repo: &'repo Repository) -> Result<Self, Error> {
+        Ok(Graph {
+            repo,
+            shallow: repo.shallow_commits()?,
+            commits: HashMap::new(),
+            buf: Vec::new(),
+        })
+    }
+
+    /// Return the commit with `id`, which has no parents if it is one of our shallow commits.
+    pub fn commit(&mut self, id: &oid) -> Result<&Commit, Error> {
+        if !self.commits.contains_key(id) {
+            let commit = self.repo.objects.find_commit_iter(id, &mut self.buf)?;
+            let parents = commit.parent_ids().collect();
+            let time = commit.committer()?.time.seconds_since_unix_epoch;
+            let parents = if self.is_shallow(id) { Vec::new() } else { parents };
+            self.commits.insert(id.to_owned(), Commit { parents, time });
+        }
+        Ok(&self.commits[id])
+    }
+
+    /// Return `true` if `id` is one of our shallow commits.
+    pub fn is_shallow(&self, id: &oid) -> bool {
+        self.shallow.as_ref().map_or(false, |commits| {
+            commits.binary_search_by(|c| c.as_ref().cmp(id)).is_ok()
+        })
+    }
+
+    /// Return our shallow commits.
+    pub fn shallow_commits(&self) -> &[ObjectId] {
+        self.shallow.as_ref().map_or(&[], |commits| commits.as_slice())
+    }
+}
+
+/// Keep track of the objects the client has in common with us to determine when negotiation can stop.
+#[derive(Default)]
+pub(super) struct Negotiation {
+    /// All objects the client said it has and which we have as well, in order.
+    pub common: Vec<ObjectId>,
+    common_commits: HashSet<ObjectId>,
+    oldest_common_time: Option<u32>,
+    /// The wanted commits that are known to reach a common commit.
+    reaching_wants: HashSet<ObjectId>,
+}
+
+impl Negotiation {
+    /// Return `true` if we have the object with `id` that the client has, and remember it as common.
+    pub fn add_have(&mut self, graph: &mut Graph<'_>, id: ObjectId) -> Result<bool, Error> {
+        if !graph.repo.objects.contains(id) {
+            return Ok(false);
+        }
+        if self.common.contains(&id) {
+            return Ok(true);
+        }
+        self.common.push(id);
+        if graph.repo.find_object(id)?.kind == gix_object::Kind::Commit {
+            let time = graph.commit(&id)?.time;
+            self.oldest_common_time = Some(self.oldest_common_time.map_or(time, |oldest| oldest.min(time)));
+            self.common_commits.insert(id);
+        }
+        Ok(true)
+    }
+
+    /// Return all common commits.
+    pub fn common_commits(&self) -> impl Iterator<Item = &ObjectId> {
+        self.common_commits.iter()
+    }
+
+    /// Return `true` if all `wants` can reach a common commit, so the client has enough in common with us to send a pack.
+    ///
+    /// Like in `git`, commits older than the oldest common commit aren't traversed.
+    pub fn is_ready(&mut self, graph: &mut Graph<'_>, wants: &[ObjectId]) -> Result<bool, Error> {
+        let cutoff = match self.oldest_common_time {
+            Some(time) => time,
+            None => return Ok(false),
+        };
+        for want in wants {
+            if self.reaching_wants.contains(want) {
+                continue;
+            }
+            let mut seen = HashSet::new();
+            let mut stack = vec![*want];
+            let mut reaches_common = false;
+            while let Some(id) = stack.pop() {
+                if self.common_commits.contains(&id) {
+                    reaches_common = true;
+                    break;
+                }
+                let commit = graph.commit(&id)?;
+                if commit.time < cutoff {
+                    continue;
+                }
+                stack.extend(commit.parents.iter().filter(|id| seen.insert(**id)));
+            }
+            if !reaches_common {
+                return Ok(false);
+            }
+            self.reaching_wants.insert(*want);
+        }
+        Ok(true)
+    }
+}
+
+/// The outcome of [`deepen()`].
+pub(super) struct Shallow {
+    /// The commits whose parents must not be sent.
+    pub boundary: HashSet<ObjectId>,
+    /// The changes to the shallow commits of the client, to be sent to the client.
+    pub updates: Vec<ShallowUpdate>,
+}
+
+impl Shallow {
+    /// Return the commits of the client which now have their parents sent.
+    pub fn unshallow(&self) -> impl Iterator<Item = &ObjectId> {
+        self.updates.iter().filter_map(|update| match update {
+            ShallowUpdate::Unshallow(id) => Some(id),
+            ShallowUpdate::Shallow(_) => None,
+        })
+    }
+}
+
+/// Determine the history to send to the client and how its shallow commits change according to the deepen `arguments`,
+/// starting at `wants`, or return `None` if the client doesn't want to change the depth of its history.
+pub(super) fn deepen(
+    graph: &mut Graph<'_>,
+    arguments: &Arguments,
+    wants: &[ObjectId],
+) -> Result<Option<Shallow>, Error> {
+    let client_shallow: HashSet<_> = arguments.shallow.iter().copied().collect();
+    let (boundary, not_shallow) = if let Some(depth) = arguments.deepen {
+        let (starts, depth) = if arguments.deepen_relative {
+            let starts: Vec<_> = arguments
+                .shallow
+                .iter()
+                .filter(|id| graph.repo.objects.contains(id))
+                .copied()
+                .collect();
+            (starts, depth.saturating_add(1))
+        } else {
+            (wants.to_vec(), depth)
+        };
+        by_depth(graph, &starts, depth)?
+    } else if arguments.deepen_since.is_some() || !arguments.deepen_not.is_empty() {
+        by_exclusion(graph, arguments, wants)?
+    } else {
+        return Ok(None);
+    };
+
+    let mut updates: Vec<_> = boundary
+        .iter()
+        .filter(|id| !client_shallow.contains(*id))
+        .map(|id| ShallowUpdate::Shallow(*id))
+        .collect();
+    updates.extend(
+        arguments
+            .shallow
+            .iter()
+            .filter(|id| not_shallow.contains(*id) && !boundary.contains(*id))
+            .map(|id| ShallowUpdate::Unshallow(*id)),
+    );
+    Ok(Some(Shallow {
+        boundary: boundary.into_iter().collect(),
+        updates,
+    }))
+}
+
+/// Traverse the history from `starts` breadth-first and return the commits at `depth` that have parents, along with
+/// all commits which were reached earlier.
+fn by_depth(
+    graph: &mut Graph<'_>,
+    starts: &[ObjectId],
+    depth: u32,
+) -> Result<(Vec<ObjectId>, HashSet<ObjectId>), Error> {
+    let mut boundary = Vec::new();
+    let mut not_shallow = HashSet::new();
+    let mut seen: HashSet<_> = starts.iter().copied().collect();
+    let mut queue: VecDeque<_> = starts.iter().map(|id| (*id, 1)).collect();
+    while let Some((id, commit_depth)) = queue.pop_front() {
+        let is_shallow = graph.is_shallow(&id);
+        let commit = graph.commit(&id)?;
+        if commit_depth >= depth || is_shallow {
+            if !commit.parents.is_empty() || is_shallow {
+                boundary.push(id);
+            }
+            continue;
+        }
+        not_shallow.insert(id);
+        for parent in commit.parents.clone() {
+            if seen.insert(parent) {
+                queue.push_back((parent, commit_depth + 1));
+            }
+        }
+    }
+    Ok((boundary, not_shallow))
+}
+
+/// Return the commits reachable from `wants` that are not older than `deepen-since` and not reachable from any `deepen-not`
+/// ref and have parents that aren't, along with all commits that are selected that way.
+fn by_exclusion(
+    graph: &mut Graph<'_>,
+    arguments: &Arguments,
+    wants: &[ObjectId],
+) -> Result<(Vec<ObjectId>, HashSet<ObjectId>), Error> {
+    let mut excluded = HashSet::new();
+    for name in &arguments.deepen_not {
+        let mut reference = graph
+            .repo
+            .try_find_reference(name.as_bstr())?
+            .ok_or_else(|| Error::DeepenNotUnknownRef { name: name.clone() })?;
+        let tip = reference.peel_to_id_in_place()?.detach();
+        if graph.repo.find_object(tip)?.kind != gix_object::Kind::Commit {
+            continue;
+        }
+        let mut stack = vec![tip];
+        while let Some(id) = stack.pop() {
+            if excluded.insert(id) {
+                stack.extend(graph.commit(&id)?.parents.iter().copied());
+            }
+        }
+    }
+
+    let since = arguments.deepen_since.unwrap_or(0);
+    let mut included = HashSet::new();
+    let mut stack = wants.to_vec();
+    while let Some(id) = stack.pop() {
+        if excluded.contains(&id) || included.contains(&id) {
+            continue;
+        }
+        let commit = graph.commit(&id)?;
+        if commit.time < since {
+            continue;
+        }
+        included.insert(id);
+        stack.extend(commit.parents.iter().copied());
+    }
+    if included.is_empty() {
+        return Err(Error::NoCommitsSelected);
+    }
+
+    let mut boundary = Vec::new();
+    for id in &included {
+        let is_shallow = graph.is_shallow(id);
+        if is_shallow
+            || graph
+                .commit(id)?
+                .parents
+                .iter()
+                .any(|parent| !included.contains(parent))
+        {
+            boundary.push(*id);
+        }
+    }
+    boundary.sort();
+    Ok((boundary, included))
+}
+
+/// The commits to send to the client, as returned by [`select_commits()`].
+pub(crate) struct Selection {
+    /// The commits to send, most recent first.
+    pub commits: Vec<ObjectId>,
+    /// Commits that the client has and which are parents of commits that are sent.
+    pub edges: Vec<ObjectId>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Flag {
+    Interesting,
+    Uninteresting,
+}
+
+/// Select all commits reachable from `tips` but not from `common` commits, similar to `git rev-list <tips> --not <common>`.
+///
+/// Parents of commits in `boundary` are never traversed, while parents of commits in `client_shallow` are not considered
+/// to be known to the client.
+pub(crate) fn select_commits(
+    graph: &mut Graph<'_>,
+    tips: &[ObjectId],
+    common: &[ObjectId],
+    boundary: &HashSet<ObjectId>,
+    client_shallow: &HashSet<ObjectId>,
+) -> Result<Selection, Error> {
+    let mut flags = HashMap::<ObjectId, Flag>::new();
+    let mut queue = BinaryHeap::<(u32, ObjectId)>::new();
+    for (ids, flag) in [(common, Flag::Uninteresting), (tips, Flag::Interesting)] {
+        for id in ids {
+            if !flags.contains_key(id) {
+                flags.insert(*id, flag);
+                queue.push((graph.commit(id)?.time, *id));
+            }
+        }
+    }
+
+    let mut commits = Vec::new();
+    while queue.iter().any(|(_, id)| flags[id] == Flag::Interesting) {
+        let (_, id) = queue.pop().expect("not empty");
+        let flag = flags[&id];
+        let parents = match flag {
+            Flag::Uninteresting if client_shallow.contains(&id) => continue,
+            Flag::Interesting if boundary.contains(&id) => {
+                commits.push(id);
+                continue;
+            }
+            Flag::Interesting => {
+                commits.push(id);
+                graph.commit(&id)?.parents.clone()
+            }
+            Flag::Uninteresting => graph.commit(&id)?.parents.clone(),
+        };
+        for parent in parents {
+            match (flags.get(&parent).copied(), flag) {
+                (None, _) | (Some(Flag::Interesting), Flag::Uninteresting) => {
+                    flags.insert(parent, flag);
+                    queue.push((graph.commit(&parent)?.time, parent));
+                }
+                (Some(_), _) => {}
+            }
+        }
+    }
+
+    // Commits may be found to be uninteresting after they were selected if commit times are skewed.
+    commits.retain(|id| flags[id] == Flag::Interesting);
+    let mut edges = Vec::new();
+    for id in commits.iter().filter(|id| !boundary.contains(*id)) {
+        for parent in &graph.commit(id)?.parents {
+            if flags.get(parent) == Some(&Flag::Uninteresting) && !edges.contains(parent) {
+                edges.push(*parent);
+            }
+        }
+    }
+    Ok(Selection { commits, edges })
+}
@@ -0,0 +1,147 @@
+//! Serve fetches and clones of `git` clients similar to `git upload-pack`, using the pack protocol in version 0, 1 or 2.
+//!
+//! The transport isn't part of this, which makes it possible to serve clients over SSH, with `git daemon`-like servers
+//! or with HTTP servers using [stateless][Options::stateless_rpc] requests.
+//!
+//! ### Deviation
+//!
+//! * Only the objects of refs may be requested, similar to the defaults of `git`, and `uploadpack.*` configuration is ignored.
+//! * Packs are never thin and always use offset deltas, which all `git` clients understand even if they don't ask for it.
+//! * Object filters, `want-ref`, `packfile-uris` and `sideband-all` aren't supported and thus not advertised.
+use crate::bstr::{BStr, BString, ByteSlice};
+
+pub(crate) mod graph;
+pub(crate) mod pack;
+pub(crate) mod refs;
+mod request;
+
+pub(crate) mod function;
+
+/// The version of the pack protocol to serve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Version {
+    /// The original protocol, which begins with the advertisement of all refs and negotiates with `ACK` and `NAK` lines.
+    V0,
+    /// Like [`V0`][Version::V0], but the advertisement is preceded by a line with the version.
+    V1,
+    /// The command-based protocol, in which clients list refs with `ls-refs` and obtain packs with `fetch`.
+    V2,
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version::V0
+    }
+}
+
+impl Version {
+    /// Obtain the version the client asked for from the value of the `GIT_PROTOCOL` environment variable, which is set
+    /// by `git` for local clients and by servers like `git daemon` or `git http-backend` for remote ones.
+    ///
+    /// Like in `git`, the highest supported version wins, and [`V0`][Version::V0] is used if no version is mentioned.
+    pub fn from_git_protocol(value: &BStr) -> Self {
+        value
+            .split_str(":")
+            .filter_map(|param| param.strip_prefix(b"version="))
+            .filter_map(|version| match version {
+                b"1" => Some(Version::V1),
+                b"2" => Some(Version::V2),
+                _ => None,
+            })
+            .max_by_key(|version| *version as u8)
+            .unwrap_or_default()
+    }
+}
+
+/// Options for use in [`Repository::upload_pack()`][crate::Repository::upload_pack()].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The version of the protocol to serve.
+    pub version: Version,
+    /// If `true`, serve a single request and return, without advertising refs or capabilities first, similar to
+    /// `git upload-pack --stateless-rpc`.
+    ///
+    /// This is what HTTP servers need, as each request is answered by its own response.
+    pub stateless_rpc: bool,
+    /// If `true`, only advertise refs or capabilities and return, similar to `git upload-pack --advertise-refs`.
+    ///
+    /// This is what HTTP servers need to answer requests for `info/refs`.
+    pub advertise_refs: bool,
+    /// The amount of threads to use when creating packs, or `None` to use all logical cores.
+    pub thread_limit: Option<usize>,
+}
+
+/// The error returned by [`Repository::upload_pack()`][crate::Repository::upload_pack()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read from or write to the client")]
+    Io(#[from] std::io::Error),
+    #[error("The client sent an invalid packet line")]
+    DecodePacketLine(#[from] gix_protocol::transport::packetline::decode::Error),
+    #[error("The client sent an invalid request: {message}")]
+    InvalidRequest { message: BString },
+    #[error("The client requested the unknown command {name:?}")]
+    UnknownCommand { name: BString },
+    #[error("The client requested {id} which is not the target of one of our refs")]
+    NotOurRef { id: gix_hash::ObjectId },
+    #[error("The reference {name:?} to exclude with deepen-not could not be found")]
+    DeepenNotUnknownRef { name: BString },
+    #[error("No commits were selected by the shallow request")]
+    NoCommitsSelected,
+    #[error(transparent)]
+    ReferencesInit(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    ReferencesIter(#[from] crate::reference::iter::init::Error),
+    #[error("Could not read a reference")]
+    ReadReference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    FindExistingReference(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    PeelReference(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    ShallowCommits(#[from] crate::shallow::open::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    FindExistingCommit(#[from] gix_odb::find::existing_iter::Error<gix_odb::store::find::Error>),
+    #[error(transparent)]
+    DecodeObject(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    CountObjects(
+        #[from]
+        gix_pack::data::output::count::objects::Error<
+            gix_pack::find::existing::Error<gix_odb::store::find::Error>,
+            std::convert::Infallible,
+        >,
+    ),
+    #[error(transparent)]
+    WritePack(
+        #[from]
+        gix_pack::data::output::bytes::Error<
+            gix_pack::data::output::entry::iter_from_counts::Error<gix_odb::store::find::Error>,
+        >,
+    ),
+}
+
+impl Error {
+    /// Return the message to send to the client, which is only done for errors caused by the client.
+    fn client_message(&self) -> Option<String> {
+        match self {
+            Error::InvalidRequest { .. }
+            | Error::UnknownCommand { .. }
+            | Error::NotOurRef { .. }
+            | Error::DeepenNotUnknownRef { .. }
+            | Error::NoCommitsSelected => Some(format!("upload-pack: {self}")),
+            _ => None,
+        }
+    }
+}
+
+fn invalid_request(message: impl Into<BString>) -> Error {
+    Error::InvalidRequest {
+        message: message.into(),
+    }
+}
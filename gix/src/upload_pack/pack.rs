@@ -0,0 +1,225 @@
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+};
+
+use gix_hash::ObjectId;
+use gix_object::{tree::EntryMode, Kind};
+use gix_odb::FindExt;
+use gix_pack::data::output;
+use gix_protocol::transport::packetline::{encode, Channel};
+
+use crate::{
+    upload_pack::{graph::Selection, refs::Ref, Error},
+    Repository,
+};
+
+/// The objects the client asked for.
+pub(crate) struct Wants {
+    /// The wanted commits, or the commits the wanted tags point to.
+    pub commits: Vec<ObjectId>,
+    /// The wanted tags along with the tags they point to, as well as wanted trees and blobs, or the ones tags point to.
+    pub objects: Vec<(ObjectId, Kind)>,
+}
+
+impl Wants {
+    /// Peel `ids` to learn which commits and other objects the client wants.
+    pub fn peel(repo: &Repository, ids: &[ObjectId]) -> Result<Self, Error> {
+        let mut wants = Wants {
+            commits: Vec::new(),
+            objects: Vec::new(),
+        };
+        for id in ids {
+            let (tags, id, kind) = peel_tags(repo, *id)?;
+            wants.objects.extend(tags.into_iter().map(|tag| (tag, Kind::Tag)));
+            match kind {
+                Kind::Commit => wants.commits.push(id),
+                _ => wants.objects.push((id, kind)),
+            }
+        }
+        Ok(wants)
+    }
+}
+
+/// Return the tags in the chain of tags starting at `id`, along with the object and its kind at the end of it.
+fn peel_tags(repo: &Repository, mut id: ObjectId) -> Result<(Vec<ObjectId>, ObjectId, Kind), Error> {
+    let mut tags = Vec::new();
+    loop {
+        let object = repo.find_object(id)?;
+        if object.kind != Kind::Tag {
+            return Ok((tags, id, object.kind));
+        }
+        tags.push(id);
+        id = gix_object::TagRefIter::from_bytes(&object.data).target_id()?;
+    }
+}
+
+/// Return the ids of all objects to send, which are the objects in `wants`, the `selection` of commits and all trees and
+/// blobs they refer to, except for those in the trees of the edges of the `selection`, which the client has already.
+///
+/// If `tags` is set, annotated tags which point to a commit that is sent are sent as well.
+pub(crate) fn objects_to_send(
+    repo: &Repository,
+    wants: &Wants,
+    selection: &Selection,
+    tags: Option<&[Ref]>,
+) -> Result<Vec<ObjectId>, Error> {
+    let mut buf = Vec::new();
+    let mut seen = HashSet::new();
+    for edge in &selection.edges {
+        let tree = repo.objects.find_commit_iter(edge, &mut buf)?.tree_id()?;
+        add_tree(repo, tree, &mut seen, &mut Vec::new())?;
+    }
+
+    let mut out = Vec::new();
+    for (id, kind) in &wants.objects {
+        match kind {
+            Kind::Tree => add_tree(repo, *id, &mut seen, &mut out)?,
+            _ => {
+                if seen.insert(*id) {
+                    out.push(*id);
+                }
+            }
+        }
+    }
+    for commit in &selection.commits {
+        out.push(*commit);
+        let tree = repo.objects.find_commit_iter(commit, &mut buf)?.tree_id()?;
+        add_tree(repo, tree, &mut seen, &mut out)?;
+    }
+
+    if let Some(refs) = tags {
+        let commits: HashSet<_> = selection.commits.iter().collect();
+        for reference in refs.iter().filter(|r| r.name.starts_with(b"refs/tags/")) {
+            if let (Some(id), Some(peeled)) = (reference.id, reference.peeled) {
+                if commits.contains(&peeled) && !seen.contains(&id) {
+                    for tag in peel_tags(repo, id)?.0 {
+                        if seen.insert(tag) {
+                            out.push(tag);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Add the tree with `id` and all trees and blobs in it to `out`, unless they were `seen` already.
+///
+/// Submodule commits are skipped as they aren't part of our object database.
+fn add_tree(
+    repo: &Repository,
+    id: ObjectId,
+    seen: &mut HashSet<ObjectId>,
+    out: &mut Vec<ObjectId>,
+) -> Result<(), Error> {
+    let mut buf = Vec::new();
+    let mut trees = vec![id];
+    while let Some(id) = trees.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        out.push(id);
+        for entry in repo.objects.find_tree_iter(id, &mut buf)? {
+            let entry = entry?;
+            match entry.mode {
+                EntryMode::Tree => trees.push(entry.oid.to_owned()),
+                EntryMode::Commit => {}
+                EntryMode::Blob | EntryMode::BlobExecutable | EntryMode::Link => {
+                    if seen.insert(entry.oid.to_owned()) {
+                        out.push(entry.oid.to_owned());
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write a pack with all `objects` to `out`, on the data band of side-band packet lines with at most `sideband` bytes
+/// if it is set, along with progress messages if `progress` is `true`.
+pub(crate) fn write(
+    repo: &Repository,
+    objects: Vec<ObjectId>,
+    thread_limit: Option<usize>,
+    sideband: Option<usize>,
+    progress: bool,
+    out: impl Write,
+) -> Result<(), Error> {
+    let mut out = Band { out, sideband };
+    if progress {
+        out.progress(&format!("Enumerating objects: {}, done.\n", objects.len()))?;
+    }
+
+    let mut handle = repo.objects.clone().into_arc()?;
+    handle.prevent_pack_unload();
+    handle.ignore_replacements = true;
+    let (counts, _) = output::count::objects_unthreaded(
+        handle.clone(),
+        objects.into_iter().map(Ok::<_, std::convert::Infallible>),
+        gix_features::progress::Discard,
+        &crate::interrupt::IS_INTERRUPTED,
+        output::count::objects::ObjectExpansion::AsIs,
+    )?;
+    let num_objects = counts.len();
+    let entries = gix_features::parallel::InOrderIter::from(output::entry::iter_from_counts(
+        counts,
+        handle,
+        gix_features::progress::Discard,
+        output::entry::iter_from_counts::Options {
+            thread_limit,
+            mode: output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+            allow_thin_pack: false,
+            ..Default::default()
+        },
+    ));
+    let mut pack = output::bytes::FromEntriesIter::new(
+        entries,
+        &mut out,
+        num_objects as u32,
+        gix_pack::data::Version::V2,
+        repo.object_hash(),
+    );
+    for written in pack.by_ref() {
+        written?;
+    }
+    if progress {
+        out.progress(&format!("Total {num_objects}\n"))?;
+    }
+    Ok(())
+}
+
+/// Write data as is, or to the data band of side-band packet lines with at most `sideband` bytes.
+struct Band<W> {
+    out: W,
+    sideband: Option<usize>,
+}
+
+impl<W: Write> Band<W> {
+    /// Write `message` to the progress band if side-bands are used.
+    fn progress(&mut self, message: &str) -> io::Result<()> {
+        if self.sideband.is_some() {
+            encode::band_to_write(Channel::Progress, message.as_bytes(), &mut self.out)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Band<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.sideband {
+            Some(max_len) => {
+                for chunk in buf.chunks(max_len) {
+                    encode::band_to_write(Channel::Data, chunk, &mut self.out)?;
+                }
+            }
+            None => self.out.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::Write;
+
+use gix_hash::ObjectId;
+use gix_protocol::transport::packetline::encode;
+use gix_ref::TargetRef;
+
+use crate::{
+    bstr::{BString, ByteVec},
+    upload_pack::{invalid_request, Error},
+    Repository,
+};
+
+/// A ref as it is advertised to clients.
+pub(crate) struct Ref {
+    /// The full name of the ref.
+    pub name: BString,
+    /// The object the ref points to, or `None` if it is unborn, which is only possible for `HEAD`.
+    pub id: Option<ObjectId>,
+    /// The object at the end of the chain of tags `id` refers to, if it refers to a tag.
+    pub peeled: Option<ObjectId>,
+    /// The full name of the ref this one points to, if it is symbolic.
+    pub symref_target: Option<BString>,
+}
+
+/// Return `HEAD` followed by all refs in order of their name, with symbolic refs resolved to the object they point to.
+///
+/// Symbolic refs that don't resolve are skipped, like in `git`.
+pub(crate) fn collect(repo: &Repository) -> Result<Vec<Ref>, Error> {
+    let head = repo.head()?;
+    let id = head.id().map(crate::Id::detach);
+    let mut out = vec![Ref {
+        name: "HEAD".into(),
+        id,
+        peeled: id.map(|id| peel(repo, id)).transpose()?.flatten(),
+        symref_target: head.referent_name().map(|name| name.as_bstr().to_owned()),
+    }];
+    for reference in repo.references()?.all()? {
+        let mut reference = reference.map_err(Error::ReadReference)?;
+        let symref_target = match reference.target() {
+            TargetRef::Symbolic(name) => Some(name.as_bstr().to_owned()),
+            TargetRef::Peeled(_) => None,
+        };
+        let id = match &symref_target {
+            None => reference.id().detach(),
+            Some(_) => {
+                while let TargetRef::Symbolic(name) = reference.target() {
+                    let name = name.to_owned();
+                    reference = match repo.try_find_reference(name.as_ref())? {
+                        Some(reference) => reference,
+                        None => break,
+                    };
+                }
+                match reference.try_id() {
+                    Some(id) => id.detach(),
+                    None => continue,
+                }
+            }
+        };
+        out.push(Ref {
+            name: reference.name().as_bstr().to_owned(),
+            id: Some(id),
+            peeled: peel(repo, id)?,
+            symref_target,
+        });
+    }
+    Ok(out)
+}
+
+/// Return the object at the end of the tag chain starting at `id`, or `None` if `id` isn't a tag.
+fn peel(repo: &Repository, id: ObjectId) -> Result<Option<ObjectId>, Error> {
+    let object = repo.find_object(id)?;
+    Ok(if object.kind == gix_object::Kind::Tag {
+        Some(object.peel_tags_to_end()?.id)
+    } else {
+        None
+    })
+}
+
+/// Write the ref advertisement of protocol version 0 and 1 with the given `capabilities` to `out`, followed by the
+/// `shallow` commits of our repository.
+pub(crate) fn advertise(
+    refs: &[Ref],
+    capabilities: &str,
+    shallow: &[ObjectId],
+    object_hash: gix_hash::Kind,
+    mut out: impl Write,
+) -> std::io::Result<()> {
+    let mut capabilities = Some(capabilities);
+    for (reference, id) in refs.iter().filter_map(|r| r.id.map(|id| (r, id))) {
+        let mut line = BString::from(format!("{id} "));
+        line.push_str(&reference.name);
+        if let Some(capabilities) = capabilities.take() {
+            line.push_byte(0);
+            line.push_str(capabilities);
+        }
+        encode::text_to_write(&line, &mut out)?;
+        if let Some(peeled) = reference.peeled {
+            let mut line = BString::from(format!("{peeled} "));
+            line.push_str(&reference.name);
+            line.push_str("^{}");
+            encode::text_to_write(&line, &mut out)?;
+        }
+    }
+    if let Some(capabilities) = capabilities {
+        let line = format!("{} capabilities^{{}}\0{capabilities}", object_hash.null());
+        encode::text_to_write(line.as_bytes(), &mut out)?;
+    }
+    for id in shallow {
+        encode::text_to_write(format!("shallow {id}").as_bytes(), &mut out)?;
+    }
+    encode::flush_to_write(&mut out)?;
+    Ok(())
+}
+
+/// Answer the `ls-refs` command of protocol version 2 with the given `arguments` by writing the matching `refs` to `out`.
+pub(super) fn ls_refs(refs: &[Ref], arguments: &[BString], mut out: impl Write) -> Result<(), Error> {
+    let (mut symrefs, mut peel, mut unborn, mut prefixes) = (false, false, false, Vec::new());
+    for argument in arguments {
+        match argument.as_slice() {
+            b"symrefs" => symrefs = true,
+            b"peel" => peel = true,
+            b"unborn" => unborn = true,
+            _ => match argument.strip_prefix(b"ref-prefix ") {
+                Some(prefix) => prefixes.push(prefix),
+                None => return Err(invalid_request(format!("unexpected ls-refs argument {argument:?}"))),
+            },
+        }
+    }
+
+    for reference in refs {
+        if !prefixes.is_empty() && !prefixes.iter().any(|prefix| reference.name.starts_with(prefix)) {
+            continue;
+        }
+        let mut line = match reference.id {
+            Some(id) => BString::from(format!("{id} ")),
+            None if unborn => BString::from("unborn "),
+            None => continue,
+        };
+        line.push_str(&reference.name);
+        if let Some(target) = reference.symref_target.as_ref().filter(|_| symrefs) {
+            line.push_str(" symref-target:");
+            line.push_str(target);
+        }
+        if let Some(peeled) = reference.peeled.filter(|_| peel) {
+            line.push_str(format!(" peeled:{peeled}"));
+        }
+        encode::text_to_write(&line, &mut out)?;
+    }
+    encode::flush_to_write(&mut out)?;
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    upload_pack::{invalid_request, Error},
+};
+
+/// How `ACK` lines are sent during negotiation in protocol version 0 and 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum MultiAck {
+    /// Only the first common object is acknowledged.
+    None,
+    /// Each common object is acknowledged with `ACK <id> continue`.
+    Basic,
+    /// Each common object is acknowledged with `ACK <id> common`, and `ACK <id> ready` signals that negotiation may stop.
+    Detailed,
+}
+
+/// The wants, haves and options of a fetch, as sent by clients in version 0 and 1 before negotiating,
+/// or as arguments to the `fetch` command in version 2.
+#[derive(Debug, Clone)]
+pub(super) struct Arguments {
+    pub wants: Vec<ObjectId>,
+    pub haves: Vec<ObjectId>,
+    pub done: bool,
+    /// The commits at which the history of the client ends.
+    pub shallow: Vec<ObjectId>,
+    pub deepen: Option<u32>,
+    pub deepen_relative: bool,
+    /// The time in seconds since the unix epoch, commits older than which shouldn't be sent.
+    pub deepen_since: Option<u32>,
+    /// The names of refs whose history shouldn't be sent.
+    pub deepen_not: Vec<BString>,
+    pub multi_ack: MultiAck,
+    pub no_done: bool,
+    /// The maximum amount of bytes to send per side-band packet line, or `None` if the pack should be sent as is.
+    pub sideband: Option<usize>,
+    pub no_progress: bool,
+    pub include_tag: bool,
+}
+
+/// The amount of bytes that fit into packets of `side-band`, without the length prefix and band number.
+const SIDEBAND_MAX_DATA: usize = 1000 - 5;
+/// The amount of bytes that fit into packets of `side-band-64k` and protocol version 2, without the length prefix and band number.
+const SIDEBAND_64K_MAX_DATA: usize = 65520 - 5;
+
+impl Arguments {
+    /// Return arguments as needed for the `fetch` command of protocol version 2, which always uses side-bands.
+    pub fn v2() -> Self {
+        Arguments {
+            sideband: Some(SIDEBAND_64K_MAX_DATA),
+            ..Arguments::v0()
+        }
+    }
+
+    /// Return arguments to be filled from the requests of protocol version 0 and 1.
+    pub fn v0() -> Self {
+        Arguments {
+            wants: Vec::new(),
+            haves: Vec::new(),
+            done: false,
+            shallow: Vec::new(),
+            deepen: None,
+            deepen_relative: false,
+            deepen_since: None,
+            deepen_not: Vec::new(),
+            multi_ack: MultiAck::None,
+            no_done: false,
+            sideband: None,
+            no_progress: false,
+            include_tag: false,
+        }
+    }
+
+    /// Parse a line sent by a client before negotiating in protocol version 0 and 1, which may have capabilities
+    /// after the first `want`.
+    pub fn parse_v0(&mut self, line: &BStr) -> Result<(), Error> {
+        if let Some(want) = line.strip_prefix(b"want ") {
+            let mut tokens = want.split_str(" ");
+            self.wants.push(parse_id(tokens.next().unwrap_or_default())?);
+            for capability in tokens {
+                self.parse_capability(capability.as_bstr());
+            }
+            Ok(())
+        } else {
+            self.parse_shared(line)
+        }
+    }
+
+    /// Parse an argument of the `fetch` command in protocol version 2.
+    pub fn parse_v2(&mut self, line: &BStr) -> Result<(), Error> {
+        if let Some(want) = line.strip_prefix(b"want ") {
+            self.wants.push(parse_id(want)?);
+        } else if let Some(have) = line.strip_prefix(b"have ") {
+            self.haves.push(parse_id(have)?);
+        } else {
+            match line.as_bytes() {
+                b"done" => self.done = true,
+                b"deepen-relative" => self.deepen_relative = true,
+                b"no-progress" => self.no_progress = true,
+                b"include-tag" => self.include_tag = true,
+                b"thin-pack" | b"ofs-delta" => {}
+                _ => return self.parse_shared(line),
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse lines that are the same in all protocol versions.
+    fn parse_shared(&mut self, line: &BStr) -> Result<(), Error> {
+        if let Some(shallow) = line.strip_prefix(b"shallow ") {
+            self.shallow.push(parse_id(shallow)?);
+        } else if let Some(depth) = line.strip_prefix(b"deepen ") {
+            match parse_number(depth) {
+                Some(depth) if depth > 0 => self.deepen = Some(depth),
+                _ => return Err(invalid_request(format!("invalid deepen {:?}", depth.as_bstr()))),
+            }
+        } else if let Some(since) = line.strip_prefix(b"deepen-since ") {
+            self.deepen_since = Some(
+                parse_number(since)
+                    .ok_or_else(|| invalid_request(format!("invalid deepen-since {:?}", since.as_bstr())))?,
+            );
+        } else if let Some(name) = line.strip_prefix(b"deepen-not ") {
+            self.deepen_not.push(name.into());
+        } else {
+            return Err(invalid_request(format!("unexpected line {line:?}")));
+        }
+        if self.deepen.is_some() && (self.deepen_since.is_some() || !self.deepen_not.is_empty()) {
+            return Err(invalid_request(
+                "deepen and deepen-since or deepen-not cannot be used together",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Enable the given `capability` sent by clients of protocol version 0 and 1, ignoring unknown ones like `git` does.
+    fn parse_capability(&mut self, capability: &BStr) {
+        match capability.as_bytes() {
+            b"multi_ack" if self.multi_ack == MultiAck::None => self.multi_ack = MultiAck::Basic,
+            b"multi_ack_detailed" => self.multi_ack = MultiAck::Detailed,
+            b"no-done" => self.no_done = true,
+            b"side-band" if self.sideband.is_none() => self.sideband = Some(SIDEBAND_MAX_DATA),
+            b"side-band-64k" => self.sideband = Some(SIDEBAND_64K_MAX_DATA),
+            b"no-progress" => self.no_progress = true,
+            b"include-tag" => self.include_tag = true,
+            b"deepen-relative" => self.deepen_relative = true,
+            _ => {}
+        }
+    }
+}
+
+fn parse_id(hex: &[u8]) -> Result<ObjectId, Error> {
+    ObjectId::from_hex(hex).map_err(|_| invalid_request(format!("invalid object id {:?}", hex.as_bstr())))
+}
+
+fn parse_number(number: &[u8]) -> Option<u32> {
+    number.to_str().ok()?.parse().ok()
+}
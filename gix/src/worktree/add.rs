@@ -0,0 +1,349 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    convert::TryInto,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix_odb::FindExt;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    Repository,
+};
+
+/// Options to control how [`Repository::add_worktree()`][crate::Repository::add_worktree()] creates a linked worktree.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The commit to check out, or the branch to check out if it's the name of a local branch, similar to the `<commit-ish>`
+    /// argument of `git worktree add`.
+    ///
+    /// If unset, the branch named after the last component of the worktree path is checked out, and created at `HEAD` if it
+    /// doesn't exist yet.
+    pub commitish: Option<BString>,
+    /// If set, create a new branch with this name at the commit to check out, similar to `git worktree add -b <name>`.
+    /// It's an error if the branch already exists.
+    pub new_branch: Option<BString>,
+    /// If `true`, let `HEAD` of the new worktree point to the commit directly, similar to `git worktree add --detach`.
+    pub detach: bool,
+    /// If `true`, check out branches even if they are already checked out in another worktree, and reuse the registration
+    /// of a worktree that is missing but still registered at the same path, similar to `git worktree add --force`.
+    pub force: bool,
+    /// If `true`, only set up the worktree and `HEAD` without writing the index and files, similar to
+    /// `git worktree add --no-checkout`.
+    pub no_checkout: bool,
+    /// If set, keep the new worktree locked with the given reason, which may be empty, similar to
+    /// `git worktree add --lock --reason <reason>`.
+    pub lock_reason: Option<BString>,
+}
+
+/// The error returned by [`Repository::add_worktree()`][crate::Repository::add_worktree()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("'{}' already exists and isn't an empty directory", path.display())]
+    DestinationExists { path: PathBuf },
+    #[error("'{}' is a missing but already registered worktree, force to override or prune it", path.display())]
+    AlreadyRegistered { path: PathBuf },
+    #[error(transparent)]
+    Realpath(#[from] gix_path::realpath::Error),
+    #[error("The worktree path '{}' has no name that can be used as its identifier", path.display())]
+    MissingName { path: PathBuf },
+    #[error("The branch name '{name}' is invalid")]
+    InvalidBranchName {
+        name: BString,
+        source: gix_validate::refname::Error,
+    },
+    #[error("A branch named '{name}' already exists")]
+    BranchExists { name: BString },
+    #[error("The branch '{name}' is already checked out at '{}'", path.display())]
+    BranchCheckedOut { name: BString, path: PathBuf },
+    #[error(transparent)]
+    RevisionParse(#[from] crate::revision::spec::parse::single::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToKind(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error("Could not access '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error(transparent)]
+    Open(#[from] crate::open::Error),
+    #[error("Could not create index from tree at {id}")]
+    IndexFromTree {
+        id: gix_hash::ObjectId,
+        source: gix_traverse::tree::breadthfirst::Error,
+    },
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error(transparent)]
+    Checkout(#[from] gix_worktree::checkout::Error<gix_odb::find::existing_object::Error<gix_odb::store::find::Error>>),
+    #[error(transparent)]
+    Hook(#[from] crate::hook::Error),
+}
+
+pub(crate) fn add_worktree(repo: &Repository, path: &Path, options: Options) -> Result<Repository, Error> {
+    let io_err = |path: &Path| {
+        let path = path.to_owned();
+        move |source: std::io::Error| Error::Io { source, path }
+    };
+    let path = gix_path::realpath(path)?;
+    let name = path
+        .file_name()
+        .and_then(|name| gix_path::os_str_into_bstr(name).ok())
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| Error::MissingName { path: path.clone() })?;
+
+    let path_existed = match std::fs::read_dir(&path) {
+        Ok(mut entries) => {
+            if entries.next().is_some() {
+                return Err(Error::DestinationExists { path });
+            }
+            true
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => false,
+        Err(_) => return Err(Error::DestinationExists { path }),
+    };
+
+    let mut stale_registration = None;
+    for proxy in repo.worktrees().map_err(io_err(&repo.common_dir().join("worktrees")))? {
+        if proxy.base().ok().as_deref() == Some(path.as_path()) {
+            if !options.force {
+                return Err(Error::AlreadyRegistered { path });
+            }
+            stale_registration = Some(proxy.git_dir().to_owned());
+        }
+    }
+    let commitish = options.commitish.as_ref().map(|name| name.as_bstr());
+    let new_branch = match (&options.new_branch, commitish) {
+        (Some(name), _) => Some(name.clone()),
+        (None, None) if !options.detach => Some(name.clone()),
+        _ => None,
+    };
+    let commit_id = repo
+        .rev_parse_single(commitish.unwrap_or_else(|| "HEAD".into()))?
+        .object()?
+        .peel_to_kind(gix_object::Kind::Commit)?
+        .id;
+    let (branch, create_branch) = match new_branch {
+        Some(branch) => {
+            let full_name = branch_name(branch.as_bstr())?;
+            let exists = repo.try_find_reference(full_name.as_ref())?.is_some();
+            match (exists, options.new_branch.is_some()) {
+                (true, true) => return Err(Error::BranchExists { name: branch }),
+                (true, false) => (Some(full_name), false),
+                (false, _) => (Some(full_name), true),
+            }
+        }
+        None if options.detach => (None, false),
+        None => {
+            let commitish = commitish.expect("set as otherwise we would create a new branch");
+            match branch_name(commitish) {
+                Ok(name) if repo.try_find_reference(name.as_ref())?.is_some() => (Some(name), false),
+                _ => (None, false),
+            }
+        }
+    };
+    if let Some(branch) = branch.as_ref().filter(|_| !create_branch && !options.force) {
+        if let Some(path) = checked_out_at(repo, branch)? {
+            return Err(Error::BranchCheckedOut {
+                name: branch.shorten().to_owned(),
+                path,
+            });
+        }
+    }
+
+    let worktrees_dir = repo.common_dir().join("worktrees");
+    if let Some(stale_git_dir) = stale_registration {
+        std::fs::remove_dir_all(&stale_git_dir).map_err(io_err(&stale_git_dir))?;
+    }
+    std::fs::create_dir_all(&worktrees_dir).map_err(io_err(&worktrees_dir))?;
+    let mut git_dir = worktrees_dir.join(gix_path::from_bstr(name.as_bstr()));
+    let mut counter = 0;
+    loop {
+        match std::fs::create_dir(&git_dir) {
+            Ok(()) => break,
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                counter += 1;
+                git_dir = worktrees_dir.join(gix_path::from_bstr(format!("{name}{counter}").as_bytes().as_bstr()));
+            }
+            Err(err) => {
+                return Err(Error::Io {
+                    source: err,
+                    path: git_dir,
+                })
+            }
+        }
+    }
+
+    let res = (|| {
+        let locked = git_dir.join("locked");
+        std::fs::write(
+            &locked,
+            options
+                .lock_reason
+                .as_deref()
+                .map_or(&b"initializing"[..], |reason| reason.as_slice()),
+        )
+        .map_err(io_err(&locked))?;
+        std::fs::create_dir_all(&path).map_err(io_err(&path))?;
+        let git_dir = git_dir.canonicalize().map_err(io_err(&git_dir))?;
+        write_file(&git_dir.join("gitdir"), &path.join(gix_discover::DOT_GIT_DIR))?;
+        write_file(&git_dir.join("commondir"), Path::new("../.."))?;
+        std::fs::write(git_dir.join("HEAD"), format!("{}\n", repo.object_hash().null()))
+            .map_err(io_err(&git_dir.join("HEAD")))?;
+        std::fs::write(
+            path.join(gix_discover::DOT_GIT_DIR),
+            format!("gitdir: {}\n", git_dir.display()),
+        )
+        .map_err(io_err(&path.join(gix_discover::DOT_GIT_DIR)))?;
+
+        if let Some(branch) = branch.as_ref().filter(|_| create_branch) {
+            repo.edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: format!("branch: Created from {}", commitish.unwrap_or_else(|| "HEAD".into())).into(),
+                    },
+                    expected: PreviousValue::MustNotExist,
+                    new: Target::Peeled(commit_id),
+                },
+                name: branch.clone(),
+                deref: false,
+            })?;
+        }
+        let worktree = Repository::from(crate::ThreadSafeRepository::open_from_paths(
+            git_dir.clone(),
+            Some(path.clone()),
+            repo.options.clone(),
+        )?);
+        worktree.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: Default::default(),
+                },
+                expected: PreviousValue::Any,
+                new: match &branch {
+                    Some(branch) => Target::Symbolic(branch.clone()),
+                    None => Target::Peeled(commit_id),
+                },
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: false,
+        })?;
+
+        if !options.no_checkout {
+            let tree = worktree.find_object(commit_id)?.peel_to_tree()?.id;
+            let mut index = worktree
+                .index_from_tree(&tree)
+                .map_err(|err| Error::IndexFromTree { id: tree, source: err })?;
+            let mut checkout_options = worktree.config.checkout_options(worktree.git_dir())?;
+            checkout_options.destination_is_initially_empty = true;
+            let objects = worktree.objects.clone().into_arc().map_err(Error::OpenArcOdb)?;
+            gix_worktree::checkout(
+                &mut index,
+                &path,
+                move |oid, buf| objects.find_blob(oid, buf),
+                &mut gix_features::progress::Discard,
+                &mut gix_features::progress::Discard,
+                &AtomicBool::default(),
+                checkout_options,
+            )?;
+            index.write(Default::default())?;
+        }
+        if options.lock_reason.is_none() {
+            std::fs::remove_file(&locked).map_err(io_err(&locked))?;
+        }
+        Ok(worktree)
+    })();
+
+    let worktree = match res {
+        Ok(worktree) => worktree,
+        Err(err) => {
+            if !path_existed {
+                std::fs::remove_dir_all(&path).ok();
+            } else if let Ok(entries) = std::fs::read_dir(&path) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if path.is_dir() {
+                        std::fs::remove_dir_all(path).ok();
+                    } else {
+                        std::fs::remove_file(path).ok();
+                    }
+                }
+            }
+            std::fs::remove_dir_all(&git_dir).ok();
+            return Err(err);
+        }
+    };
+    if !options.no_checkout {
+        worktree.run_hook(
+            crate::hook::name::POST_CHECKOUT,
+            &crate::hook::Input {
+                args: vec![
+                    repo.object_hash().null().to_string().into(),
+                    commit_id.to_string().into(),
+                    "1".into(),
+                ],
+                ..Default::default()
+            },
+        )?;
+    }
+    Ok(worktree)
+}
+
+fn branch_name(name: &BStr) -> Result<FullName, Error> {
+    FullName::try_from(format!("refs/heads/{name}")).map_err(|source| Error::InvalidBranchName {
+        name: name.to_owned(),
+        source,
+    })
+}
+
+/// Write `path` followed by a newline into the file at `file`, like git does for the files that link worktrees.
+fn write_file(file: &Path, path: &Path) -> Result<(), Error> {
+    let mut content = gix_path::into_bstr(path).into_owned();
+    content.push(b'\n');
+    std::fs::write(file, content).map_err(|source| Error::Io {
+        source,
+        path: file.to_owned(),
+    })
+}
+
+/// Return the path of the worktree in which `branch` is checked out, if there is one.
+fn checked_out_at(repo: &Repository, branch: &FullName) -> Result<Option<PathBuf>, Error> {
+    let main = repo.main_repo()?;
+    if let Some(workdir) = main.work_dir() {
+        if main.head_name()?.as_ref() == Some(branch) {
+            return Ok(Some(workdir.to_owned()));
+        }
+    }
+    for proxy in repo.worktrees().map_err(|source| Error::Io {
+        source,
+        path: repo.common_dir().join("worktrees"),
+    })? {
+        let base = proxy.base().ok();
+        let worktree = proxy.into_repo_with_possibly_inaccessible_worktree()?;
+        if worktree.head_name()?.as_ref() == Some(branch) {
+            return Ok(Some(base.unwrap_or_else(|| worktree.git_dir().to_owned())));
+        }
+    }
+    Ok(None)
+}
@@ -69,6 +69,15 @@ pub(crate) fn id(git_dir: &std::path::Path, has_common_dir: bool) -> Option<&BSt
 ///
 pub mod proxy;
 
+///
+pub mod add;
+
+///
+pub mod prune;
+
+///
+pub mod repair;
+
 ///
 pub mod open_index {
     use crate::bstr::BString;
@@ -122,7 +131,7 @@ pub mod excludes {
         /// This takes into consideration all the usual repository configuration, namely:
         ///
         /// * `$XDG_CONFIG_HOME/…/ignore` if `core.excludesFile` is *not* set, otherwise use the configured file.
-        /// * `$GIT_DIR/info/exclude` if present.
+        /// * `$GIT_COMMON_DIR/info/exclude` if present, which is shared by all worktrees.
         ///
         /// `index` may be used to obtain `.gitignore` files directly from the index under certain conditions.
         // TODO: test, provide higher-level interface that is much easier to use and doesn't panic when accessing entries
@@ -147,7 +156,7 @@ pub mod excludes {
             };
             let state = gix_worktree::cache::State::IgnoreStack(gix_worktree::cache::state::Ignore::new(
                 overrides.unwrap_or_default(),
-                gix_ignore::Search::from_git_dir(repo.git_dir(), excludes_file, &mut buf)?,
+                gix_ignore::Search::from_git_dir(repo.common_dir(), excludes_file, &mut buf)?,
                 None,
                 case,
             ));
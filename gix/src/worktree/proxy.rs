@@ -1,5 +1,9 @@
 #![allow(clippy::result_large_err)]
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
 use crate::{
     bstr::{BStr, BString, ByteSlice},
@@ -24,6 +28,89 @@ pub mod into_repo {
     }
 }
 
+///
+pub mod lock {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Proxy::lock()`][super::Proxy::lock()] and [`Proxy::unlock()`][super::Proxy::unlock()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Worktree '{id}' is already locked")]
+        AlreadyLocked { id: BString },
+        #[error("Worktree '{id}' is not locked")]
+        NotLocked { id: BString },
+        #[error("Could not access '{}'", path.display())]
+        Io { source: std::io::Error, path: PathBuf },
+    }
+}
+
+///
+pub mod move_to {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Proxy::move_to()`][super::Proxy::move_to()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Worktree '{id}' is locked and cannot be moved unless forced")]
+        Locked { id: BString },
+        #[error("Worktree at '{}' is inaccessible", .base.display())]
+        MissingWorktree { base: PathBuf },
+        #[error("The destination '{}' already exists", path.display())]
+        DestinationExists { path: PathBuf },
+        #[error(transparent)]
+        Realpath(#[from] gix_path::realpath::Error),
+        #[error("Could not access '{}'", path.display())]
+        Io { source: std::io::Error, path: PathBuf },
+    }
+}
+
+///
+pub mod remove {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Proxy::remove()`][super::Proxy::remove()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Worktree '{id}' is locked and cannot be removed unless forced")]
+        Locked { id: BString },
+        #[error("'{}' contains modified or untracked files, and cannot be removed unless forced", base.display())]
+        Dirty { base: PathBuf },
+        #[error(transparent)]
+        IntoRepo(#[from] super::into_repo::Error),
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        PeelHeadToId(#[from] crate::head::peel::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToKind(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        ReadIndex(#[from] gix_index::file::init::Error),
+        #[error(transparent)]
+        IndexFromTree(#[from] gix_traverse::tree::breadthfirst::Error),
+        #[error(transparent)]
+        CheckoutOptions(#[from] crate::config::checkout_options::Error),
+        #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+        OpenArcOdb(#[source] std::io::Error),
+        #[error(transparent)]
+        Status(#[from] gix_worktree::status::Error),
+        #[error(transparent)]
+        Excludes(#[from] crate::worktree::excludes::Error),
+        #[error("Could not access '{}'", path.display())]
+        Io { source: std::io::Error, path: PathBuf },
+    }
+}
+
 impl<'repo> Proxy<'repo> {
     pub(crate) fn new(parent: &'repo Repository, git_dir: impl Into<PathBuf>) -> Self {
         Proxy {
@@ -98,4 +185,200 @@ impl<'repo> Proxy<'repo> {
         let repo = ThreadSafeRepository::open_from_paths(self.git_dir, base.into(), self.parent.options.clone())?;
         Ok(repo.into())
     }
+
+    /// Return the reason for this worktree to be pruned by [`Repository::prune_worktrees()`], or `None` if it is not
+    /// prunable, similar to the `prunable` information of `git worktree list`.
+    ///
+    /// Worktrees whose location doesn't exist anymore are only prunable if their `gitdir` file wasn't modified after `expire`,
+    /// which never is the case if it's `None`.
+    pub fn prune_reason(&self, expire: Option<SystemTime>) -> Option<crate::worktree::prune::Reason> {
+        crate::worktree::prune::reason(&self.git_dir, expire)
+    }
+}
+
+/// Lifecycle
+impl<'repo> Proxy<'repo> {
+    /// Lock this worktree with an optional `reason` so it cannot be pruned, moved or removed, similar to `git worktree lock`.
+    ///
+    /// It's an error if it is already locked.
+    pub fn lock(&self, reason: Option<&BStr>) -> Result<(), lock::Error> {
+        if self.is_locked() {
+            return Err(lock::Error::AlreadyLocked {
+                id: self.id().to_owned(),
+            });
+        }
+        let path = self.git_dir.join("locked");
+        std::fs::write(&path, reason.unwrap_or_default()).map_err(|source| lock::Error::Io { source, path })
+    }
+
+    /// Unlock this worktree, similar to `git worktree unlock`.
+    ///
+    /// It's an error if it isn't locked.
+    pub fn unlock(&self) -> Result<(), lock::Error> {
+        if !self.is_locked() {
+            return Err(lock::Error::NotLocked {
+                id: self.id().to_owned(),
+            });
+        }
+        let path = self.git_dir.join("locked");
+        std::fs::remove_file(&path).map_err(|source| lock::Error::Io { source, path })
+    }
+
+    /// Move the working tree to `destination` and let the repository know about its new location, similar to
+    /// `git worktree move`. If `destination` is an existing directory, the worktree is moved into it.
+    /// Return the new location of the working tree.
+    ///
+    /// Locked worktrees are only moved if `force` is `true`.
+    pub fn move_to(&self, destination: impl AsRef<Path>, force: bool) -> Result<PathBuf, move_to::Error> {
+        let io_err = |path: &Path| {
+            let path = path.to_owned();
+            move |source: std::io::Error| move_to::Error::Io { source, path }
+        };
+        if self.is_locked() && !force {
+            return Err(move_to::Error::Locked {
+                id: self.id().to_owned(),
+            });
+        }
+        let base = self.base().map_err(io_err(&self.git_dir.join("gitdir")))?;
+        if !base.is_dir() {
+            return Err(move_to::Error::MissingWorktree { base });
+        }
+        let mut destination = gix_path::realpath(destination)?;
+        if destination.is_dir() {
+            destination.push(base.file_name().expect("a worktree is never the root"));
+        }
+        if destination.symlink_metadata().is_ok() {
+            return Err(move_to::Error::DestinationExists { path: destination });
+        }
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err(parent))?;
+        }
+        std::fs::rename(&base, &destination).map_err(io_err(&base))?;
+
+        let gitdir_file = self.git_dir.join("gitdir");
+        let mut content = gix_path::into_bstr(destination.join(gix_discover::DOT_GIT_DIR)).into_owned();
+        content.push(b'\n');
+        std::fs::write(&gitdir_file, content).map_err(io_err(&gitdir_file))?;
+        Ok(destination)
+    }
+
+    /// Remove the working tree and the administrative files of this worktree, similar to `git worktree remove`.
+    ///
+    /// Unless `force` is `true`, locked worktrees and those with changes in the index or the working tree, including
+    /// untracked files which aren't ignored, are not removed.
+    pub fn remove(self, force: bool) -> Result<(), remove::Error> {
+        let io_err = |path: &Path| {
+            let path = path.to_owned();
+            move |source: std::io::Error| remove::Error::Io { source, path }
+        };
+        if self.is_locked() && !force {
+            return Err(remove::Error::Locked {
+                id: self.id().to_owned(),
+            });
+        }
+        let base = self.base().ok().filter(|base| base.is_dir());
+        if let Some(base) = base {
+            if !force && !is_clean(&self.clone().into_repo()?)? {
+                return Err(remove::Error::Dirty { base });
+            }
+            std::fs::remove_dir_all(&base).map_err(io_err(&base))?;
+        }
+        std::fs::remove_dir_all(&self.git_dir).map_err(io_err(&self.git_dir))?;
+        if let Some(worktrees_dir) = self.git_dir.parent() {
+            std::fs::remove_dir(worktrees_dir).ok();
+        }
+        Ok(())
+    }
+}
+
+/// Return `true` if the index of `repo` matches its `HEAD` commit and its working tree matches the index,
+/// without any untracked files that aren't ignored.
+fn is_clean(repo: &Repository) -> Result<bool, remove::Error> {
+    use gix_odb::FindExt;
+
+    let workdir = repo.work_dir().expect("opened with worktree");
+    let mut index = gix_index::File::at_or_default(repo.index_path(), repo.object_hash(), Default::default())?;
+    let head_index = match repo.head()?.peel_to_id_in_place().transpose()? {
+        Some(id) => {
+            let tree = id.object()?.peel_to_tree()?.id;
+            gix_index::State::from_tree(&tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?
+        }
+        None => gix_index::State::new(repo.object_hash()),
+    };
+    let entries = |state: &gix_index::State| {
+        state
+            .entries()
+            .iter()
+            .map(|entry| (entry.path(state).to_owned(), entry.id, entry.mode, entry.stage()))
+            .collect::<Vec<_>>()
+    };
+    if entries(&head_index) != entries(&index) {
+        return Ok(false);
+    }
+
+    let options = repo.config.checkout_options(repo.git_dir())?;
+    let objects = repo.objects.clone().into_arc().map_err(remove::Error::OpenArcOdb)?;
+    let mut recorder = gix_worktree::status::Recorder::default();
+    gix_worktree::status(
+        &mut index,
+        workdir,
+        &mut recorder,
+        gix_worktree::status::content::FastEq,
+        move |id, buf| objects.find_blob(id, buf),
+        gix_worktree::status::Options {
+            fs: options.fs,
+            thread_limit: options.thread_limit,
+            stat: options.stat_options,
+        },
+    )?;
+    if recorder.records.iter().any(|(_, change, _)| change.is_some()) {
+        return Ok(false);
+    }
+
+    let tracked: HashSet<&BStr> = index.entries().iter().map(|entry| entry.path(&index)).collect();
+    let mut excludes = repo.worktree().expect("opened with worktree").excludes(&index, None)?;
+    let mut dirs = vec![BString::default()];
+    while let Some(dir) = dirs.pop() {
+        let path = workdir.join(gix_path::from_bstr(dir.as_bstr()));
+        for entry in std::fs::read_dir(&path).map_err(|source| remove::Error::Io {
+            source,
+            path: path.clone(),
+        })? {
+            let entry = entry.map_err(|source| remove::Error::Io {
+                source,
+                path: path.clone(),
+            })?;
+            let name = gix_path::os_string_into_bstring(entry.file_name()).expect("no illformed UTF-8");
+            if dir.is_empty() && name == gix_discover::DOT_GIT_DIR {
+                continue;
+            }
+            let mut rela_path = dir.clone();
+            if !rela_path.is_empty() {
+                rela_path.push(b'/');
+            }
+            rela_path.extend_from_slice(&name);
+            if tracked.contains(rela_path.as_bstr()) {
+                continue;
+            }
+            let is_dir = entry.file_type().map_or(false, |file_type| file_type.is_dir());
+            let is_excluded = excludes
+                .at_entry(rela_path.as_bstr(), Some(is_dir), |id, buf| {
+                    repo.objects.find_blob(id, buf)
+                })
+                .map_err(|source| remove::Error::Io {
+                    source,
+                    path: entry.path(),
+                })?
+                .is_excluded();
+            if is_excluded {
+                continue;
+            }
+            if is_dir {
+                dirs.push(rela_path);
+            } else {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
 }
@@ -0,0 +1,224 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    config::tree::{Gc, Key},
+    Repository,
+};
+
+/// Options to control which worktrees are removed by [`Repository::prune_worktrees()`][crate::Repository::prune_worktrees()].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// If `true`, only report what would be pruned without removing anything, similar to `git worktree prune --dry-run`.
+    pub dry_run: bool,
+    /// Prune worktrees whose location doesn't exist anymore only if their `gitdir` file wasn't modified after this time,
+    /// similar to `git worktree prune --expire <time>`.
+    ///
+    /// If unset, `gc.worktreePruneExpire` is used, which defaults to 3 months ago.
+    pub expire: Option<SystemTime>,
+}
+
+/// The reason for a worktree to be prunable, as returned by [`Proxy::prune_reason()`][crate::worktree::Proxy::prune_reason()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Reason {
+    /// The entry in the `worktrees` directory isn't a directory.
+    NotADirectory,
+    /// The `gitdir` file which points to the worktree is missing.
+    GitDirFileMissing,
+    /// The `gitdir` file could not be read.
+    GitDirFileUnreadable,
+    /// The `gitdir` file is empty.
+    InvalidGitDirFile,
+    /// The `gitdir` file points to a location that doesn't exist, and it wasn't modified after the expiry date.
+    GitDirFilePointsToNonExistentLocation,
+}
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Reason::NotADirectory => "not a valid directory",
+            Reason::GitDirFileMissing => "gitdir file does not exist",
+            Reason::GitDirFileUnreadable => "unable to read gitdir file",
+            Reason::InvalidGitDirFile => "invalid gitdir file",
+            Reason::GitDirFilePointsToNonExistentLocation => "gitdir file points to non-existent location",
+        })
+    }
+}
+
+/// The result of [`Repository::prune_worktrees()`][crate::Repository::prune_worktrees()].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The ids of all pruned worktrees along with the reason for pruning them, sorted by id.
+    pub pruned: Vec<(BString, Reason)>,
+}
+
+/// The error returned by [`Repository::prune_worktrees()`][crate::Repository::prune_worktrees()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    ConfigExpiry(#[from] crate::config::time::Error),
+    #[error("Could not access '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+}
+
+pub(crate) fn prune_worktrees(repo: &Repository, options: Options) -> Result<Outcome, Error> {
+    let expire = match options.expire {
+        Some(expire) => Some(expire),
+        None => expiry_from_config(repo)?,
+    };
+    let worktrees_dir = repo.common_dir().join("worktrees");
+    let io_err = |path: &Path| {
+        let path = path.to_owned();
+        move |source: std::io::Error| Error::Io { source, path }
+    };
+    let entries = match std::fs::read_dir(&worktrees_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Outcome::default()),
+        Err(err) => {
+            return Err(Error::Io {
+                source: err,
+                path: worktrees_dir,
+            })
+        }
+    };
+    let mut pruned = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(io_err(&worktrees_dir))?;
+        let git_dir = entry.path();
+        let reason = match reason(&git_dir, expire) {
+            Some(reason) => reason,
+            None => continue,
+        };
+        if !options.dry_run {
+            if git_dir.is_dir() {
+                std::fs::remove_dir_all(&git_dir)
+            } else {
+                std::fs::remove_file(&git_dir)
+            }
+            .map_err(io_err(&git_dir))?;
+        }
+        pruned.push((
+            gix_path::os_string_into_bstring(entry.file_name()).unwrap_or_else(|name| name.to_string().into()),
+            reason,
+        ));
+    }
+    if !options.dry_run {
+        std::fs::remove_dir(&worktrees_dir).ok();
+    }
+    pruned.sort();
+    Ok(Outcome { pruned })
+}
+
+/// Return the point in time before which stale worktrees may be pruned as configured by `gc.worktreePruneExpire`,
+/// or `None` if they should never be pruned.
+pub(crate) fn expiry_from_config(repo: &Repository) -> Result<Option<SystemTime>, crate::config::time::Error> {
+    let now = SystemTime::now();
+    match repo
+        .config
+        .resolved
+        .string_by_key(Gc::WORKTREE_PRUNE_EXPIRE.logical_name().as_str())
+    {
+        Some(value) => Gc::WORKTREE_PRUNE_EXPIRE.try_into_expiry(value, now),
+        None => Ok(Some(months_before(now, 3))),
+    }
+}
+
+/// Return the same time of day as `now` but `months` calendar months earlier, in UTC, just like `git` computes `3.months.ago`.
+/// Days past the end of the resulting month roll over into the next one, as with `mktime()`.
+fn months_before(now: SystemTime, months: i64) -> SystemTime {
+    const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+    let seconds = now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    let month = month - 1 - months;
+    let days = days_from_civil(year + month.div_euclid(12), month.rem_euclid(12) + 1, day);
+    let seconds = days * SECONDS_PER_DAY + seconds.rem_euclid(SECONDS_PER_DAY);
+    SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(seconds.max(0) as u64)
+}
+
+/// Convert `days` since the unix epoch into year, month and day of the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// The inverse of [`civil_from_days()`], which accepts days beyond the end of `month`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Return the reason for the worktree with the private `git_dir` to be pruned, if it is prunable at all.
+/// Stale worktrees are only prunable if their `gitdir` file wasn't modified after `expire`.
+pub(crate) fn reason(git_dir: &Path, expire: Option<SystemTime>) -> Option<Reason> {
+    if !git_dir.is_dir() {
+        return Some(Reason::NotADirectory);
+    }
+    if git_dir.join("locked").is_file() {
+        return None;
+    }
+    let gitdir_file = git_dir.join("gitdir");
+    if !gitdir_file.is_file() {
+        return Some(Reason::GitDirFileMissing);
+    }
+    let content = match std::fs::read(&gitdir_file) {
+        Ok(content) => content,
+        Err(_) => return Some(Reason::GitDirFileUnreadable),
+    };
+    let path = content.trim();
+    if path.is_empty() {
+        return Some(Reason::InvalidGitDirFile);
+    }
+    if git_dir.join(gix_path::from_byte_slice(path)).exists() {
+        return None;
+    }
+    let modified = gitdir_file.metadata().and_then(|meta| meta.modified()).ok()?;
+    expire
+        .filter(|expire| modified <= *expire)
+        .map(|_| Reason::GitDirFilePointsToNonExistentLocation)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::months_before;
+
+    fn at(seconds: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn months_before_uses_calendar_months() {
+        assert_eq!(
+            months_before(at(1705322096), 3),
+            at(1697373296),
+            "2024-01-15 12:34:56 goes back into the previous year"
+        );
+        assert_eq!(
+            months_before(at(1685527200), 3),
+            at(1677837600),
+            "2023-05-31 becomes 2023-02-31, which is 2023-03-03 like with git"
+        );
+    }
+}
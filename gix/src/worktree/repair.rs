@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use crate::{bstr::BString, Repository};
+
+/// A link between a linked worktree and its private git directory that was repaired by
+/// [`Repository::repair_worktrees()`][crate::Repository::repair_worktrees()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Repaired {
+    /// The `.git` file in the worktree was rewritten to point to the private git directory of the worktree.
+    GitFile,
+    /// The `gitdir` file in the private git directory of the worktree was rewritten to point to the worktree.
+    GitDir,
+}
+
+/// The result of [`Repository::repair_worktrees()`][crate::Repository::repair_worktrees()].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The ids of all repaired worktrees along with the link that was repaired, in the order of repair.
+    pub repaired: Vec<(BString, Repaired)>,
+}
+
+/// The error returned by [`Repository::repair_worktrees()`][crate::Repository::repair_worktrees()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Realpath(#[from] gix_path::realpath::Error),
+    #[error("'{}' is not a linked worktree of this repository", path.display())]
+    ForeignWorktree { path: PathBuf },
+    #[error("Could not access '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+}
+
+pub(crate) fn repair_worktrees(
+    repo: &Repository,
+    paths: impl IntoIterator<Item = impl AsRef<Path>>,
+) -> Result<Outcome, Error> {
+    let worktrees_dir = repo.common_dir().join("worktrees");
+    let mut repaired = Vec::new();
+    for proxy in repo.worktrees().map_err(|source| Error::Io {
+        source,
+        path: worktrees_dir.clone(),
+    })? {
+        let base = match proxy.base() {
+            Ok(base) if base.is_dir() => base,
+            _ => continue,
+        };
+        let git_dir = canonicalize(proxy.git_dir())?;
+        if repair_git_file(&base, &git_dir)? {
+            repaired.push((proxy.id().to_owned(), Repaired::GitFile));
+        }
+    }
+
+    for path in paths {
+        let path = gix_path::realpath(path.as_ref())?;
+        let dot_git = path.join(gix_discover::DOT_GIT_DIR);
+        let foreign = || Error::ForeignWorktree { path: path.clone() };
+        let backlink = gix_discover::path::from_gitdir_file(&dot_git).map_err(|_| foreign())?;
+        // Infer the private git directory by name if both the repository and the worktree were moved.
+        let git_dir = if backlink.join("gitdir").is_file() {
+            backlink
+        } else {
+            worktrees_dir.join(backlink.file_name().ok_or_else(foreign)?)
+        };
+        if !git_dir.join("gitdir").is_file() {
+            return Err(foreign());
+        }
+        let git_dir = canonicalize(&git_dir)?;
+        if git_dir.parent() != Some(canonicalize(&worktrees_dir)?.as_path()) {
+            return Err(foreign());
+        }
+        let id = gix_path::into_bstr(Path::new(git_dir.file_name().expect("inside worktrees dir"))).into_owned();
+        if repair_git_file(&path, &git_dir)? {
+            repaired.push((id.clone(), Repaired::GitFile));
+        }
+        let gitdir_file = git_dir.join("gitdir");
+        let points_to_worktree = gix_discover::path::from_plain_file(&gitdir_file)
+            .and_then(Result::ok)
+            .map_or(false, |gitdir| gitdir == dot_git);
+        if !points_to_worktree {
+            let mut content = gix_path::into_bstr(dot_git.as_path()).into_owned();
+            content.push(b'\n');
+            std::fs::write(&gitdir_file, content).map_err(|source| Error::Io {
+                source,
+                path: gitdir_file.clone(),
+            })?;
+            repaired.push((id, Repaired::GitDir));
+        }
+    }
+    Ok(Outcome { repaired })
+}
+
+/// Let the `.git` file of the worktree at `base` point to `git_dir` if it doesn't do so already, and return `true` if it was
+/// rewritten.
+fn repair_git_file(base: &Path, git_dir: &Path) -> Result<bool, Error> {
+    let dot_git = base.join(gix_discover::DOT_GIT_DIR);
+    let points_to_git_dir = gix_discover::path::from_gitdir_file(&dot_git)
+        .ok()
+        .and_then(|path| path.canonicalize().ok())
+        .map_or(false, |path| path == git_dir);
+    if points_to_git_dir {
+        return Ok(false);
+    }
+    std::fs::write(&dot_git, format!("gitdir: {}\n", git_dir.display()))
+        .map_err(|source| Error::Io { source, path: dot_git })?;
+    Ok(true)
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, Error> {
+    path.canonicalize().map_err(|source| Error::Io {
+        source,
+        path: path.to_owned(),
+    })
+}
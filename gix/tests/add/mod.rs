@@ -0,0 +1,255 @@
+use std::path::Path;
+
+use gix::{
+    add::{Error, Options},
+    bstr::BString,
+};
+
+use crate::{git_output, repo_rw_executed};
+
+/// The staged entries along with the status of the repository at `dir`, as seen by `git`.
+fn state(dir: &Path) -> crate::Result<String> {
+    let mut out = String::from_utf8(git_output(dir, &["ls-files", "--stage"])?.stdout)?;
+    out.push_str(&String::from_utf8(
+        git_output(dir, &["status", "--porcelain", "--untracked-files=all"])?.stdout,
+    )?);
+    Ok(out)
+}
+
+/// Return the paths of all entries whose stat information doesn't match their files, without refreshing the index.
+fn stat_dirty(dir: &Path) -> crate::Result<String> {
+    Ok(String::from_utf8(
+        git_output(dir, &["diff-files", "--name-only"])?.stdout,
+    )?)
+}
+
+/// Add `pathspecs` with `options`, and assert the result is the same as with `git add <git_args> -- <pathspecs>`.
+fn assert_same_as_git(pathspecs: &[&str], options: Options, git_args: &[&str]) -> crate::Result<gix::add::Outcome> {
+    let (repo, _tmp) = repo_rw_executed("make_add_repo.sh")?;
+    let outcome = repo.add_to_index(pathspecs, options)?;
+
+    let (_, git_tmp) = repo_rw_executed("make_add_repo.sh")?;
+    let mut args = vec!["add"];
+    args.extend_from_slice(git_args);
+    args.push("--");
+    args.extend_from_slice(pathspecs);
+    let output = git_output(git_tmp.path(), &args)?;
+    assert!(!output.stderr.starts_with(b"fatal"), "{output:?}");
+
+    let workdir = repo.work_dir().expect("present");
+    assert_eq!(
+        state(workdir)?,
+        state(git_tmp.path())?,
+        "{pathspecs:?} with {options:?} differs from git"
+    );
+    Ok(outcome)
+}
+
+fn paths(paths: &[&str]) -> Vec<BString> {
+    paths.iter().map(|path| (*path).into()).collect()
+}
+
+#[test]
+fn all_files() -> crate::Result {
+    let outcome = assert_same_as_git(&[], Options::default(), &["--all"])?;
+    assert_eq!(
+        outcome.added,
+        paths(&[
+            "becomes-dir/file/nested",
+            "committed.auto",
+            "data.bin",
+            "dir/new",
+            "embedded",
+            "file.broken",
+            "file.id",
+            "file.upper",
+            "lines.crlf",
+            "link",
+            "new",
+            "new-dir/deep/file",
+            "new.auto",
+            "script",
+            "tracked"
+        ]),
+        "committed.auto keeps its CRLF line endings as the version in the index has them as well"
+    );
+    assert_eq!(outcome.removed, paths(&["becomes-dir/file", "gone"]));
+    assert!(outcome.ignored.is_empty());
+    Ok(())
+}
+
+#[test]
+fn pathspecs() -> crate::Result {
+    let outcome = assert_same_as_git(
+        &["dir", "./*.crlf", ":!dir/new", "gone", ":(glob)new-dir/*/*"],
+        Options::default(),
+        &[],
+    )?;
+    assert_eq!(outcome.added, paths(&["lines.crlf", "new-dir/deep/file"]));
+    assert_eq!(outcome.removed, paths(&["gone"]));
+    Ok(())
+}
+
+#[test]
+fn update() -> crate::Result {
+    let outcome = assert_same_as_git(
+        &[],
+        Options {
+            update: true,
+            ..Default::default()
+        },
+        &["--update"],
+    )?;
+    assert_eq!(outcome.added, paths(&["committed.auto", "script", "tracked"]));
+    assert_eq!(outcome.removed, paths(&["becomes-dir/file", "gone"]));
+    Ok(())
+}
+
+#[test]
+fn intent_to_add() -> crate::Result {
+    let outcome = assert_same_as_git(
+        &["new", "dir", "tracked", "gone"],
+        Options {
+            intent_to_add: true,
+            ..Default::default()
+        },
+        &["--intent-to-add"],
+    )?;
+    assert_eq!(outcome.added, paths(&["dir/new", "new"]));
+    assert_eq!(outcome.removed, paths(&["gone"]));
+
+    let (repo, _tmp) = repo_rw_executed("make_add_repo.sh")?;
+    repo.add_to_index(
+        Some("new"),
+        Options {
+            intent_to_add: true,
+            ..Default::default()
+        },
+    )?;
+    let outcome = repo.add_to_index(Some("new"), Options::default())?;
+    assert_eq!(
+        outcome.added,
+        paths(&["new"]),
+        "intent-to-add entries are filled in later"
+    );
+    Ok(())
+}
+
+#[test]
+fn ignore_removal() -> crate::Result {
+    let outcome = assert_same_as_git(
+        &["."],
+        Options {
+            ignore_removal: true,
+            ..Default::default()
+        },
+        &["--ignore-removal"],
+    )?;
+    assert_eq!(
+        outcome.removed,
+        paths(&["becomes-dir/file"]),
+        "entries are still removed if they are in the way of new ones"
+    );
+    assert!(outcome.added.contains(&"becomes-dir/file/nested".into()));
+    Ok(())
+}
+
+#[test]
+fn ignored_files_are_reported_if_named_and_added_with_force() -> crate::Result {
+    let outcome = assert_same_as_git(&["a.log", "new", "build/output"], Options::default(), &[])?;
+    assert_eq!(outcome.added, paths(&["new"]));
+    assert_eq!(
+        outcome.ignored,
+        paths(&["a.log", "build"]),
+        "ignored directories are reported instead of the files within them"
+    );
+
+    let outcome = assert_same_as_git(
+        &["*.log", "build"],
+        Options {
+            force: true,
+            ..Default::default()
+        },
+        &["--force"],
+    )?;
+    assert_eq!(outcome.added, paths(&["a.log", "build/output"]));
+    assert!(outcome.ignored.is_empty());
+    Ok(())
+}
+
+#[test]
+fn pathspecs_must_match() -> crate::Result {
+    let (repo, _tmp) = repo_rw_executed("make_add_repo.sh")?;
+    let workdir = repo.work_dir().expect("present").to_owned();
+    let before = state(&workdir)?;
+    for spec in ["does-not-exist", "*.log"] {
+        let err = repo.add_to_index(["new", spec], Options::default()).unwrap_err();
+        assert!(
+            matches!(&err, Error::PathspecMatchedNothing { spec: actual } if actual == spec),
+            "{err:?}"
+        );
+    }
+    assert_eq!(state(&workdir)?, before, "nothing is changed");
+    assert!(
+        !git_output(&workdir, &["add", "--", "new", "*.log"])?.status.success(),
+        "git fails as well"
+    );
+    Ok(())
+}
+
+#[test]
+fn required_filters_must_succeed() -> crate::Result {
+    let (repo, _tmp) = repo_rw_executed("make_add_repo.sh")?;
+    let workdir = repo.work_dir().expect("present");
+    std::fs::write(workdir.join("file.required"), "content")?;
+    let err = repo
+        .add_to_index(Some("file.required"), Options::default())
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::RequiredFilterFailed { driver, path } if driver == "broken-required" && path == "file.required"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn autocrlf() -> crate::Result {
+    for value in ["true", "input"] {
+        let (_, tmp) = repo_rw_executed("make_add_repo.sh")?;
+        let (_, git_tmp) = repo_rw_executed("make_add_repo.sh")?;
+        for dir in [tmp.path(), git_tmp.path()] {
+            assert!(git_output(dir, &["config", "core.autocrlf", value])?.status.success());
+        }
+        let repo = gix::open_opts(tmp.path(), crate::restricted())?;
+        repo.add_to_index(None::<&str>, Options::default())?;
+        assert!(git_output(git_tmp.path(), &["add", "--all"])?.status.success());
+        assert_eq!(state(tmp.path())?, state(git_tmp.path())?, "core.autocrlf = {value}");
+    }
+    Ok(())
+}
+
+#[test]
+fn stat_information_is_updated() -> crate::Result {
+    let (repo, _tmp) = repo_rw_executed("make_add_repo.sh")?;
+    let workdir = repo.work_dir().expect("present").to_owned();
+    assert_ne!(stat_dirty(&workdir)?, "");
+
+    repo.add_to_index(None::<&str>, Options::default())?;
+    assert_eq!(stat_dirty(&workdir)?, "", "all entries match their files");
+
+    let outcome = repo.add_to_index(None::<&str>, Options::default())?;
+    assert!(
+        outcome.added.is_empty() && outcome.removed.is_empty(),
+        "nothing changes the second time"
+    );
+    Ok(())
+}
+
+#[test]
+fn the_index_is_locked() -> crate::Result {
+    let (repo, _tmp) = repo_rw_executed("make_add_repo.sh")?;
+    std::fs::write(repo.git_dir().join("index.lock"), "")?;
+    let err = repo.add_to_index(None::<&str>, Options::default()).unwrap_err();
+    assert!(matches!(err, Error::AcquireLock(_)), "{err:?}");
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use gix::{
+    apply::{mailbox, patch, Options, Target},
+    bstr::ByteSlice,
+};
+
+use crate::{named_repo, repo_rw_executed};
+
+fn patch_file(repo: &gix::Repository, name: &str) -> crate::Result<Vec<patch::File>> {
+    Ok(patch::parse(&std::fs::read(repo.git_dir().join(name))?, 1)?)
+}
+
+fn tree_of(repo: &gix::Repository, spec: &str) -> crate::Result<gix_hash::ObjectId> {
+    Ok(repo.rev_parse_single(format!("{spec}^{{tree}}").as_str())?.detach())
+}
+
+#[test]
+fn git_patches_with_extended_headers_are_parsed() -> crate::Result {
+    let repo = named_repo("make_apply_repo.sh")?;
+    let files = patch_file(&repo, "ab.patch")?;
+    let paths: Vec<_> = files.iter().map(|f| f.path().to_str_lossy().into_owned()).collect();
+    assert_eq!(
+        paths,
+        [
+            "binary",
+            "dir/nested",
+            "dir/new-file",
+            "file",
+            "link",
+            "new-binary",
+            "renamed",
+            "script",
+            "to-delete"
+        ]
+    );
+
+    let binary = &files[0];
+    assert!(
+        matches!(&binary.content, patch::Content::Binary(b) if matches!(b.forward, patch::BinaryData::Delta(_))),
+        "small changes to large binary files are encoded as delta"
+    );
+    assert!(files[2].is_addition());
+    assert!(matches!(&files[3].content, patch::Content::Hunks(hunks) if hunks.len() == 2));
+    assert_eq!(files[4].old_mode, Some(gix_object::tree::EntryMode::Link));
+    assert!(
+        matches!(&files[5].content, patch::Content::Binary(b) if matches!(b.forward, patch::BinaryData::Literal(_))),
+        "new binary files are always literal"
+    );
+
+    let renamed = &files[6];
+    assert_eq!(renamed.old_path.as_ref().map(|p| p.as_bstr()), Some("to-rename".into()));
+    assert_eq!(
+        renamed.rewrite,
+        Some(patch::Rewrite {
+            kind: patch::RewriteKind::Rename,
+            similarity: Some(91)
+        })
+    );
+    assert_eq!(
+        (files[7].old_mode, files[7].new_mode),
+        (
+            Some(gix_object::tree::EntryMode::Blob),
+            Some(gix_object::tree::EntryMode::BlobExecutable)
+        ),
+        "mode changes don't have content"
+    );
+    assert!(files[8].is_deletion());
+    Ok(())
+}
+
+#[test]
+fn applying_to_a_tree_reproduces_the_original_change() -> crate::Result {
+    let repo = named_repo("make_apply_repo.sh")?;
+    let files = patch_file(&repo, "ab.patch")?;
+    let outcome = repo.apply_patch(&files, Target::Tree(tree_of(&repo, "first")?), Options::default())?;
+    assert!(outcome.applied);
+    assert!(outcome.rejected.is_empty());
+    assert_eq!(outcome.tree, Some(tree_of(&repo, "second")?));
+    Ok(())
+}
+
+#[test]
+fn rejected_hunks_are_reported_and_can_be_skipped() -> crate::Result {
+    let repo = named_repo("make_apply_repo.sh")?;
+    let files = patch_file(&repo, "conflict.patch")?;
+    let b_tree = tree_of(&repo, "second")?;
+    let outcome = repo.apply_patch(&files, Target::Tree(b_tree), Options::default())?;
+    assert!(!outcome.applied, "nothing is applied if a hunk doesn't apply");
+    assert_eq!(outcome.tree, None);
+    assert_eq!(outcome.rejected.len(), 1);
+    assert_eq!(outcome.rejected[0].path, "file");
+    assert_eq!(outcome.rejected[0].hunks.len(), 1);
+    assert_eq!(
+        outcome.rejected[0].hunks[0].index, 0,
+        "the first hunk changes a line that was changed in the second commit"
+    );
+
+    let outcome = repo.apply_patch(
+        &files,
+        Target::Tree(b_tree),
+        Options {
+            partial: true,
+            ..Default::default()
+        },
+    )?;
+    assert!(outcome.applied);
+    assert_eq!(outcome.rejected.len(), 1);
+    let tree = repo.find_object(outcome.tree.expect("applied"))?.into_tree();
+    let blob = tree.lookup_entry_by_path("file")?.expect("present").object()?;
+    assert!(
+        blob.data.lines().any(|line| line == b"zehn") && blob.data.lines().any(|line| line == b"two"),
+        "the second hunk applied on top of the second commit"
+    );
+    Ok(())
+}
+
+#[test]
+fn applying_to_index_and_worktree() -> crate::Result {
+    let (repo, _tmp) = repo_rw_executed("make_apply_repo.sh")?;
+    let files = patch_file(&repo, "ab.patch")?;
+    let outcome = repo.apply_patch(&files, Target::IndexAndWorkTree, Options::default())?;
+    assert!(outcome.applied);
+
+    let workdir = repo.work_dir().expect("non-bare");
+    assert!(!workdir.join("to-delete").exists());
+    assert!(!workdir.join("to-rename").exists());
+    let b_tree = repo.find_object(tree_of(&repo, "second")?)?.into_tree();
+    let index = repo.open_index()?;
+    for entry in b_tree.traverse().breadthfirst.files()? {
+        if entry.mode.is_tree() {
+            continue;
+        }
+        let index_entry = index
+            .entry_by_path_and_stage(entry.filepath.as_bstr(), 0)
+            .expect("all files of the second commit are in the index");
+        assert_eq!(index_entry.id, entry.oid, "{}", entry.filepath);
+        let path = workdir.join(gix::path::from_bstr(entry.filepath.as_bstr()));
+        let data = if entry.mode == gix_object::tree::EntryMode::Link {
+            gix::path::into_bstr(std::fs::read_link(path)?).into_owned().into()
+        } else {
+            std::fs::read(path)?
+        };
+        assert_eq!(
+            gix_object::compute_hash(repo.object_hash(), gix_object::Kind::Blob, &data),
+            entry.oid,
+            "{}",
+            entry.filepath
+        );
+    }
+    assert_eq!(
+        index.entries().len(),
+        8,
+        "only the files of the second commit remain in the index"
+    );
+
+    let additions: Vec<_> = files.iter().filter(|f| f.is_addition()).cloned().collect();
+    assert!(
+        matches!(
+            repo.apply_patch(&additions, Target::WorkTree, Options::default()),
+            Err(gix::apply::Error::AlreadyExists { .. })
+        ),
+        "added files can't be added again"
+    );
+    Ok(())
+}
+
+#[test]
+fn mailboxes_are_turned_into_commits() -> crate::Result {
+    let (repo, _tmp) = repo_rw_executed("make_apply_repo.sh")?;
+    let messages = mailbox::parse(&std::fs::read(repo.git_dir().join("series.mbox"))?)?;
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].author.name, "Jörg Ünicode", "encoded words are decoded");
+    assert_eq!(messages[1].subject, "C with ümlaut", "the [PATCH] prefix is removed");
+
+    let outcome = repo.apply_mailbox(&messages, Options::default())?;
+    assert_eq!(outcome.rejected, None);
+    assert_eq!(outcome.commits.len(), 2);
+    assert_eq!(repo.head_id()?, outcome.commits[1]);
+
+    for (commit_id, original) in outcome.commits.iter().zip(["second", "third"]) {
+        let commit = repo.find_object(*commit_id)?.into_commit();
+        let original = repo.rev_parse_single(original)?.object()?.into_commit();
+        assert_eq!(commit.tree_id()?, original.tree_id()?);
+        assert_eq!(commit.message_raw()?, original.message_raw()?);
+        assert_eq!(commit.author()?, original.author()?);
+    }
+    let reflog_message = repo
+        .head_ref()?
+        .expect("on a branch")
+        .log_iter()
+        .rev()?
+        .expect("reflog exists")
+        .next()
+        .expect("one line")?
+        .message;
+    assert_eq!(reflog_message, "am: C with ümlaut");
+    assert!(
+        std::fs::read(repo.work_dir().expect("non-bare").join("file"))?.ends_with(b"more\n"),
+        "the working tree is updated"
+    );
+    Ok(())
+}
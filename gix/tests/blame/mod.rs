@@ -0,0 +1,161 @@
+use gix::bstr::ByteSlice;
+use gix_hash::ObjectId;
+
+use crate::named_repo;
+
+/// Return `(commit_id, line_in_source, line_in_blamed_file)` for each line, with one-based line numbers.
+fn baseline(repo: &gix::Repository, name: &str) -> crate::Result<Vec<(ObjectId, u32, u32)>> {
+    let data = std::fs::read(repo.git_dir().join(name))?;
+    let mut out = Vec::new();
+    for line in data.lines() {
+        let mut tokens = line.split(|b| *b == b' ');
+        let id = match tokens.next().and_then(|hex| ObjectId::from_hex(hex).ok()) {
+            Some(id) => id,
+            None => continue,
+        };
+        let mut number =
+            || -> crate::Result<u32> { Ok(tokens.next().ok_or("missing line number")?.to_str()?.parse()?) };
+        out.push((id, number()?, number()?));
+    }
+    Ok(out)
+}
+
+fn lines(outcome: &gix::blame::Outcome) -> Vec<(ObjectId, u32, u32)> {
+    outcome
+        .entries
+        .iter()
+        .flat_map(|e| {
+            e.range_in_source_file
+                .clone()
+                .zip(e.range_in_blamed_file.clone())
+                .map(move |(source, blamed)| (e.commit_id, source + 1, blamed + 1))
+        })
+        .collect()
+}
+
+fn head_id(repo: &gix::Repository) -> crate::Result<ObjectId> {
+    Ok(repo.head_id()?.detach())
+}
+
+#[test]
+fn all_lines_match_git_and_follow_renames() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let outcome = repo.blame_file("renamed.txt", head_id(&repo)?).compute()?;
+
+    assert_eq!(lines(&outcome), baseline(&repo, "blame-baseline-all")?);
+    assert_eq!(outcome.blob, b"A\nb\nC\nD\nE\nf\ng\n");
+    let initial = repo.rev_parse_single("main~7")?.detach();
+    let source_paths: Vec<_> = outcome
+        .entries
+        .iter()
+        .filter(|e| e.commit_id == initial)
+        .map(|e| e.source_path.as_bstr())
+        .collect();
+    assert_eq!(
+        source_paths,
+        ["file.txt", "file.txt"],
+        "b and d come from before the rename"
+    );
+    assert_eq!(
+        outcome.entries.iter().map(|e| e.len()).sum::<u32>(),
+        7,
+        "each line is accounted for"
+    );
+    Ok(())
+}
+
+#[test]
+fn line_ranges_only_blame_the_selected_lines() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let outcome = repo
+        .blame_file("renamed.txt", head_id(&repo)?)
+        .with_range(1..4)
+        .compute()?;
+    assert_eq!(lines(&outcome), baseline(&repo, "blame-baseline-range")?);
+
+    let err = repo
+        .blame_file("renamed.txt", head_id(&repo)?)
+        .with_range(5..8)
+        .compute()
+        .unwrap_err();
+    assert!(matches!(err, gix::blame::Error::InvalidRange { line_count: 7, .. }));
+    Ok(())
+}
+
+#[test]
+fn ignored_revisions_pass_their_modified_lines_to_parents() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let reformat = repo.rev_parse_single("reformat")?.detach();
+    let outcome = repo
+        .blame_file("renamed.txt", head_id(&repo)?)
+        .ignore_revs(Some(reformat))
+        .compute()?;
+    assert!(outcome.entries.iter().all(|e| e.commit_id != reformat));
+    assert_eq!(lines(&outcome), baseline(&repo, "blame-baseline-ignore-rev")?);
+    Ok(())
+}
+
+#[test]
+fn ignored_revisions_can_be_read_from_configuration() -> crate::Result {
+    let mut repo = named_repo("make_blame_repo.sh")?;
+    {
+        let mut config = repo.config_snapshot_mut();
+        config.set_raw_value("blame", None, "ignoreRevsFile", "does-not-exist-and-is-skipped")?;
+        config.set_raw_value("blame", None, "ignoreRevsFile", ".git/ignore-revs")?;
+    }
+    let outcome = repo.blame_file("renamed.txt", head_id(&repo)?).compute()?;
+    assert_eq!(lines(&outcome), baseline(&repo, "blame-baseline-ignore-rev")?);
+
+    let outcome = repo
+        .blame_file("renamed.txt", head_id(&repo)?)
+        .ignore_revs_from_config(false)
+        .compute()?;
+    assert_eq!(lines(&outcome), baseline(&repo, "blame-baseline-all")?);
+    Ok(())
+}
+
+#[test]
+fn commit_graph_bloom_filters_skip_unrelated_commits() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let with_graph = repo.blame_file("renamed.txt", head_id(&repo)?).compute()?;
+    assert!(with_graph.statistics.bloom_filter_queries > 0);
+    assert!(
+        with_graph.statistics.bloom_filter_skips > 0,
+        "the unrelated commits can be skipped"
+    );
+
+    let without_graph = repo
+        .blame_file("renamed.txt", head_id(&repo)?)
+        .use_commit_graph(false)
+        .compute()?;
+    assert_eq!(without_graph.statistics.bloom_filter_queries, 0);
+    assert_eq!(with_graph.entries, without_graph.entries);
+    Ok(())
+}
+
+#[test]
+fn renames_are_not_followed_if_rewrite_tracking_is_disabled() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let outcome = repo
+        .blame_file("renamed.txt", head_id(&repo)?)
+        .track_rewrites(None)
+        .compute()?;
+    let rename = repo.rev_parse_single("main~4")?.detach();
+    assert!(outcome
+        .entries
+        .iter()
+        .any(|e| e.commit_id == rename && e.source_path == "renamed.txt"));
+    assert_eq!(outcome.statistics.tree_diffs, 0);
+    Ok(())
+}
+
+#[test]
+fn missing_files_are_an_error() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let err = repo
+        .blame_file("does-not-exist", head_id(&repo)?)
+        .compute()
+        .unwrap_err();
+    assert!(matches!(err, gix::blame::Error::FileNotFound { .. }));
+    Ok(())
+}
@@ -0,0 +1,261 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix::{
+    bstr::ByteSlice,
+    bundle::{create, verify, Version},
+    remote::Direction,
+};
+
+use crate::{
+    upload_pack::{assert_success, git, rev_parse, stdout},
+    util::hex_to_id,
+};
+
+fn fixture() -> crate::Result<PathBuf> {
+    Ok(gix_testtools::scripted_fixture_read_only("make_bundle_repo.sh")?.canonicalize()?)
+}
+
+fn open(dir: &Path) -> crate::Result<gix::Repository> {
+    Ok(gix::open_opts(dir, crate::restricted())?)
+}
+
+fn fsck(dir: &Path) -> crate::Result {
+    let output = git(dir).args(["fsck", "--connectivity-only"]).output()?;
+    assert_success(&output);
+    Ok(())
+}
+
+fn create(
+    repo: &gix::Repository,
+    specs: &[&str],
+    path: &Path,
+    options: create::Options,
+) -> crate::Result<gix::bundle::Header> {
+    let mut file = std::fs::File::create(path)?;
+    Ok(repo.create_bundle(specs.iter().copied(), &mut file, options)?)
+}
+
+#[test]
+fn clone_from_bundle_created_by_git() -> crate::Result {
+    let fixture = fixture()?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let (mut checkout, _) = gix::prepare_clone(fixture.join("all.bundle"), tmp.path())?
+        .fetch_then_checkout(gix::progress::Discard, &AtomicBool::default())?;
+    let (repo, _) = checkout.main_worktree(gix::progress::Discard, &AtomicBool::default())?;
+
+    assert_eq!(
+        repo.head_name()?.expect("not detached").as_bstr(),
+        "refs/heads/main",
+        "HEAD is assumed to point to the branch with the same commit"
+    );
+    for (local, remote) in [
+        ("refs/remotes/origin/main", "main"),
+        ("refs/remotes/origin/other", "other"),
+        ("refs/tags/v1", "v1"),
+    ] {
+        assert_eq!(
+            repo.find_reference(local)?.id().to_string(),
+            rev_parse(&fixture.join("repo"), remote)?,
+            "{local} was received"
+        );
+    }
+    assert_eq!(
+        std::fs::read(repo.work_dir().expect("non-bare").join("file4"))?,
+        b"content 4\n"
+    );
+    fsck(repo.work_dir().expect("non-bare"))
+}
+
+#[test]
+fn fetch_bundle_with_prerequisites_created_by_git() -> crate::Result {
+    let fixture = fixture()?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let base = tmp.path().join("base");
+    assert_success(
+        &git(tmp.path())
+            .args(["clone", "-q", "--bare", "--no-local"])
+            .arg(fixture.join("base"))
+            .arg(&base)
+            .output()?,
+    );
+    let bundle = fixture.join("incremental.bundle");
+
+    let repo = open(&base)?;
+    let outcome = repo.verify_bundle(std::io::BufReader::new(std::fs::File::open(&bundle)?))?;
+    assert_eq!(outcome.header.prerequisites.len(), 1);
+    assert_eq!(outcome.header.prerequisites[0].comment, "commit 2");
+    assert_eq!(outcome.num_objects, 6, "two commits, their trees and new files");
+
+    let remote = repo
+        .remote_at(gix::Url::from_bytes(bundle.to_str().expect("valid UTF-8").into())?)?
+        .with_refspecs(Some("+refs/heads/*:refs/remotes/bundle/*"), Direction::Fetch)?;
+    remote
+        .connect(Direction::Fetch)?
+        .prepare_fetch(gix::progress::Discard, Default::default())?
+        .receive(gix::progress::Discard, &AtomicBool::default())?;
+    assert_eq!(
+        repo.find_reference("refs/remotes/bundle/main")?.id().to_string(),
+        rev_parse(&fixture.join("repo"), "main")?,
+        "the thin pack could be resolved with the prerequisites"
+    );
+    fsck(&base)
+}
+
+#[test]
+fn verify_bundles_of_all_versions() -> crate::Result {
+    let fixture = fixture()?;
+    let repo = open(&fixture.join("repo"))?;
+    for (name, version) in [("all.bundle", Version::V2), ("v3.bundle", Version::V3)] {
+        let outcome = repo.verify_bundle(std::io::BufReader::new(std::fs::File::open(fixture.join(name))?))?;
+        assert_eq!(outcome.header.version, version);
+        assert!(outcome.header.prerequisites.is_empty());
+    }
+
+    open(&fixture.join("base"))?.verify_bundle(std::io::BufReader::new(std::fs::File::open(
+        fixture.join("incremental.bundle"),
+    )?))?;
+
+    let empty = gix_testtools::tempfile::TempDir::new()?;
+    let empty = gix::init_bare(empty.path())?;
+    let err = empty
+        .verify_bundle(std::io::BufReader::new(std::fs::File::open(
+            fixture.join("incremental.bundle"),
+        )?))
+        .unwrap_err();
+    assert!(matches!(err, verify::Error::MissingPrerequisites { .. }));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "The repository lacks the prerequisite commits {}",
+            rev_parse(&fixture.join("repo"), "v1^{commit}")?
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn create_complete_bundle_and_clone_it_with_git() -> crate::Result {
+    let fixture = fixture()?;
+    let source = fixture.join("repo");
+    let repo = open(&source)?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let bundle = tmp.path().join("complete.bundle");
+
+    let header = create(&repo, &["HEAD", "main", "other", "v1"], &bundle, Default::default())?;
+    assert_eq!(header.version, Version::V2);
+    assert!(header.prerequisites.is_empty());
+    assert_eq!(
+        header.refs.iter().map(|r| r.name.to_str_lossy()).collect::<Vec<_>>(),
+        ["HEAD", "refs/heads/main", "refs/heads/other", "refs/tags/v1"]
+    );
+    assert_eq!(
+        header.find_ref(b"refs/tags/v1").expect("present").id,
+        hex_to_id(&rev_parse(&source, "v1")?),
+        "annotated tags are stored as such"
+    );
+
+    let output = git(tmp.path()).arg("bundle").arg("list-heads").arg(&bundle).output()?;
+    assert_success(&output);
+    assert_eq!(stdout(&output).lines().count(), 4);
+    assert_success(&git(&source).arg("bundle").arg("verify").arg(&bundle).output()?);
+    assert_success(
+        &git(tmp.path())
+            .arg("clone")
+            .arg("-q")
+            .arg(&bundle)
+            .arg("clone")
+            .output()?,
+    );
+    let clone = tmp.path().join("clone");
+    for (cloned, original) in [("HEAD", "HEAD"), ("origin/other", "other"), ("v1", "v1")] {
+        assert_eq!(rev_parse(&clone, cloned)?, rev_parse(&source, original)?);
+    }
+    fsck(&clone)?;
+
+    let outcome = repo.verify_bundle(std::io::BufReader::new(std::fs::File::open(&bundle)?))?;
+    assert_eq!(outcome.header, header);
+    assert_eq!(outcome.num_objects, 16, "5 commits, 5 trees, 5 blobs and a tag");
+    Ok(())
+}
+
+#[test]
+fn create_incremental_bundle_and_unbundle_it_with_git() -> crate::Result {
+    let fixture = fixture()?;
+    let source = fixture.join("repo");
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let bundle = tmp.path().join("incremental.bundle");
+
+    let header = create(
+        &open(&source)?,
+        &["v1..main"],
+        &bundle,
+        create::Options {
+            version: Version::V3,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(header.version, Version::V3);
+    assert_eq!(header.prerequisites.len(), 1);
+    assert_eq!(
+        header.prerequisites[0].id,
+        hex_to_id(&rev_parse(&source, "v1^{commit}")?)
+    );
+    assert_eq!(header.prerequisites[0].comment, "commit 2");
+    assert_eq!(header.refs.len(), 1);
+    assert_eq!(header.refs[0].name, "refs/heads/main");
+
+    let base = tmp.path().join("base");
+    assert_success(
+        &git(tmp.path())
+            .args(["clone", "-q", "--no-local"])
+            .arg(fixture.join("base"))
+            .arg(&base)
+            .output()?,
+    );
+    assert_success(&git(&base).arg("bundle").arg("verify").arg(&bundle).output()?);
+    assert_success(
+        &git(&base)
+            .arg("fetch")
+            .arg("-q")
+            .arg(&bundle)
+            .arg("main:refs/heads/from-bundle")
+            .output()?,
+    );
+    assert_eq!(rev_parse(&base, "from-bundle")?, rev_parse(&source, "main")?);
+    fsck(&base)?;
+
+    let output = git(tmp.path()).arg("init").arg("-q").arg("empty").output()?;
+    assert_success(&output);
+    let output = git(&tmp.path().join("empty"))
+        .arg("bundle")
+        .arg("verify")
+        .arg(&bundle)
+        .output()?;
+    assert!(!output.status.success(), "prerequisites are missing");
+    Ok(())
+}
+
+#[test]
+fn create_errors() -> crate::Result {
+    let fixture = fixture()?;
+    let repo = open(&fixture.join("repo"))?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let bundle = tmp.path().join("out.bundle");
+    for (specs, expected) in [
+        (&["^main"][..], "Refusing to create an empty bundle as none of the revisions is a reference"),
+        (
+            &["main...other"],
+            "The revision \"main...other\" isn't supported, only single revisions, exclusions like '^a' and ranges like 'a..b' are",
+        ),
+    ] {
+        let err = repo
+            .create_bundle(specs.iter().copied(), std::io::sink(), Default::default())
+            .unwrap_err();
+        assert_eq!(err.to_string(), expected);
+    }
+    assert!(!bundle.exists());
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use std::{
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use gix::{
+    bstr::BString,
+    checkout::{Error, Options},
+};
+
+use crate::{git, repo_rw_executed};
+
+/// The index, status, `HEAD` with its reflog messages and all files of the working tree of the repository at `dir`.
+fn state(dir: &Path) -> crate::Result<String> {
+    let mut out = git(dir, &["ls-files", "--stage"])?;
+    out += &git(dir, &["status", "--porcelain", "--untracked-files=all", "--ignored"])?;
+    out += &git(dir, &["rev-parse", "--symbolic-full-name", "HEAD"])?;
+    out += &git(dir, &["rev-parse", "HEAD"])?;
+    out += &git(dir, &["log", "-g", "--format=%H %gs", "HEAD"])?;
+    out += &git(
+        dir,
+        &["for-each-ref", "--format=%(refname) %(objectname)", "refs/heads"],
+    )?;
+    out += &files(dir, dir)?;
+    Ok(out)
+}
+
+/// List all files below `dir` with their mode and content, or the target of symlinks.
+fn files(root: &Path, dir: &Path) -> crate::Result<String> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    let mut out = String::new();
+    for entry in entries {
+        let path = entry.path();
+        let rela_path = path.strip_prefix(root)?.display().to_string();
+        let meta = path.symlink_metadata()?;
+        if meta.is_dir() {
+            if entry.file_name() != ".git" {
+                out += &files(root, &path)?;
+            }
+        } else if meta.file_type().is_symlink() {
+            out += &format!("{rela_path} -> {}\n", std::fs::read_link(&path)?.display());
+        } else {
+            out += &format!(
+                "{rela_path} {:o} {:?}\n",
+                meta.permissions().mode() & 0o111,
+                std::fs::read_to_string(&path)?
+            );
+        }
+    }
+    Ok(out)
+}
+
+/// Run `change` on a fresh fixture, checkout `target` with `options` and assert the result is the same as
+/// with `git checkout <git_args> <target>` after the same `change`.
+fn assert_same_as_git(
+    change: impl Fn(&Path) -> std::io::Result<()>,
+    target: &str,
+    options: Options,
+    git_args: &[&str],
+) -> crate::Result<gix::checkout::Outcome> {
+    let (repo, _tmp) = repo_rw_executed("make_checkout_repo.sh")?;
+    let workdir = repo.work_dir().expect("present");
+    change(workdir)?;
+    let outcome = repo.checkout(target, options.clone())?;
+
+    let (_, git_tmp) = repo_rw_executed("make_checkout_repo.sh")?;
+    change(git_tmp.path())?;
+    let mut args = vec!["checkout", "-q"];
+    args.extend_from_slice(git_args);
+    args.push(target);
+    let status = std::process::Command::new("git")
+        .args(args)
+        .current_dir(git_tmp.path())
+        .status()?;
+    assert!(status.success(), "git could perform the checkout as well");
+
+    assert_eq!(
+        state(workdir)?,
+        state(git_tmp.path())?,
+        "checkout of {target:?} with {options:?} differs from git"
+    );
+    assert_eq!(
+        git(workdir, &["diff-files", "--name-only"])?,
+        git(git_tmp.path(), &["diff-files", "--name-only"])?,
+        "the stat information of all entries is up to date"
+    );
+    Ok(outcome)
+}
+
+fn paths(paths: &[&str]) -> Vec<BString> {
+    paths.iter().map(|path| (*path).into()).collect()
+}
+
+fn unchanged(_: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[test]
+fn switch_branch_and_back() -> crate::Result {
+    let outcome = assert_same_as_git(unchanged, "other", Options::default(), &[])?;
+    assert_eq!(
+        outcome.updated,
+        paths(&["a", "becomes-dir/nested", "dir/d", "link", "new", "new.log", "script"])
+    );
+    assert_eq!(outcome.removed, paths(&["becomes-dir", "dir/c", "removed"]));
+    assert_eq!(outcome.branch.expect("attached").as_bstr(), "refs/heads/other");
+    assert_ne!(outcome.previous_head, Some(outcome.head));
+
+    let (repo, _tmp) = repo_rw_executed("make_checkout_repo.sh")?;
+    let workdir = repo.work_dir().expect("present");
+    let mtime_of_unchanged_file = std::fs::metadata(workdir.join("b"))?.modified()?;
+    let first = repo.checkout("other", Options::default())?;
+    let second = repo.checkout("main", Options::default())?;
+    assert_eq!(second.previous_head, Some(first.head));
+    assert_eq!(second.head, first.previous_head.expect("born"));
+    assert_eq!(
+        second.updated,
+        paths(&["a", "becomes-dir", "dir/c", "link", "removed", "script"])
+    );
+    assert_eq!(
+        second.removed,
+        paths(&["becomes-dir/nested", "dir/d", "new", "new.log"])
+    );
+    assert_eq!(
+        std::fs::metadata(workdir.join("b"))?.modified()?,
+        mtime_of_unchanged_file,
+        "files that are the same in both commits aren't touched"
+    );
+    assert_eq!(
+        git(
+            workdir,
+            &["status", "--porcelain", "--untracked-files=all", "--ignored"]
+        )?,
+        "",
+        "the worktree is exactly as it was"
+    );
+    Ok(())
+}
+
+#[test]
+fn detach_at_branches_and_tags() -> crate::Result {
+    let outcome = assert_same_as_git(
+        unchanged,
+        "other",
+        Options {
+            detach: true,
+            ..Default::default()
+        },
+        &["--detach"],
+    )?;
+    assert_eq!(outcome.branch, None);
+
+    let outcome = assert_same_as_git(unchanged, "v1", Options::default(), &[])?;
+    assert_eq!(outcome.branch, None, "tags are peeled to their commit");
+    assert_same_as_git(unchanged, "other~1", Options::default(), &[])?;
+    Ok(())
+}
+
+#[test]
+fn new_branch() -> crate::Result {
+    let options = Options {
+        new_branch: Some("feature".into()),
+        ..Default::default()
+    };
+    let outcome = assert_same_as_git(unchanged, "other", options.clone(), &["-b", "feature"])?;
+    assert_eq!(outcome.branch.expect("attached").as_bstr(), "refs/heads/feature");
+
+    let (repo, _tmp) = repo_rw_executed("make_checkout_repo.sh")?;
+    assert!(matches!(
+        repo.checkout("other", Options {
+            new_branch: Some("main".into()),
+            ..Default::default()
+        }),
+        Err(Error::BranchExists { name }) if name == "main"
+    ));
+    assert!(matches!(
+        repo.checkout(
+            "other",
+            Options {
+                new_branch: Some("in..valid".into()),
+                ..Default::default()
+            }
+        ),
+        Err(Error::InvalidBranchName { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn local_changes_are_kept_if_not_in_the_way() -> crate::Result {
+    assert_same_as_git(
+        |dir| {
+            std::fs::write(dir.join("b"), "changed b")?;
+            std::fs::write(dir.join("dir/keep"), "changed keep")?;
+            std::fs::remove_file(dir.join("removed"))?;
+            std::fs::write(dir.join("untracked"), "untracked")?;
+            std::fs::write(dir.join("new.log"), "ignored and overwritten")
+        },
+        "other",
+        Options::default(),
+        &[],
+    )?;
+    assert_same_as_git(
+        |dir| {
+            std::fs::write(dir.join("b"), "changed b")?;
+            let status = std::process::Command::new("git")
+                .args(["add", "b"])
+                .current_dir(dir)
+                .status()?;
+            assert!(status.success());
+            Ok(())
+        },
+        "other",
+        Options::default(),
+        &[],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn local_changes_in_the_way_prevent_the_checkout_unless_forced() -> crate::Result {
+    let (repo, _tmp) = repo_rw_executed("make_checkout_repo.sh")?;
+    let workdir = repo.work_dir().expect("present");
+    std::fs::write(workdir.join("a"), "changed a")?;
+    std::fs::write(workdir.join("dir/c"), "changed c")?;
+    let before = state(workdir)?;
+    match repo.checkout("other", Options::default()) {
+        Err(Error::LocalChanges { paths: changed }) => assert_eq!(changed, paths(&["a", "dir/c"])),
+        res => unreachable!("{res:?}"),
+    }
+    assert_eq!(state(workdir)?, before, "nothing was changed");
+
+    let status = std::process::Command::new("git")
+        .args(["add", "a"])
+        .current_dir(workdir)
+        .status()?;
+    assert!(status.success());
+    match repo.checkout("other", Options::default()) {
+        Err(Error::LocalChanges { paths: changed }) => {
+            assert_eq!(changed, paths(&["a"]), "staged changes are detected as well")
+        }
+        res => unreachable!("{res:?}"),
+    }
+
+    assert_same_as_git(
+        |dir| {
+            std::fs::write(dir.join("a"), "changed a")?;
+            std::fs::write(dir.join("b"), "changed b")?;
+            std::fs::remove_file(dir.join("dir/keep"))
+        },
+        "other",
+        Options {
+            force: true,
+            ..Default::default()
+        },
+        &["--force"],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn untracked_files_in_the_way_prevent_the_checkout_unless_forced() -> crate::Result {
+    let (repo, _tmp) = repo_rw_executed("make_checkout_repo.sh")?;
+    let workdir = repo.work_dir().expect("present");
+    std::fs::write(workdir.join("new"), "untracked")?;
+    let before = state(workdir)?;
+    match repo.checkout("other", Options::default()) {
+        Err(Error::UntrackedFiles { paths: untracked }) => assert_eq!(untracked, paths(&["new"])),
+        res => unreachable!("{res:?}"),
+    }
+    assert_eq!(state(workdir)?, before, "nothing was changed");
+
+    assert_same_as_git(
+        |dir| std::fs::write(dir.join("new"), "untracked"),
+        "other",
+        Options {
+            force: true,
+            ..Default::default()
+        },
+        &["--force"],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn post_checkout_hook_runs_afterwards() -> crate::Result {
+    let (mut repo, _tmp) = repo_rw_executed("make_checkout_repo.sh")?;
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    repo.set_hook(
+        "post-checkout",
+        Some(Arc::new({
+            let calls = calls.clone();
+            move |repo: &gix::Repository, _: &str, input: &gix::hook::Input| {
+                assert_eq!(
+                    repo.head_name()?.expect("attached").as_bstr(),
+                    "refs/heads/other",
+                    "HEAD is already updated"
+                );
+                calls.lock().unwrap().push(input.args.clone());
+                Err("failure".into())
+            }
+        })),
+    );
+    let previous = repo.head_id()?.detach();
+    let err = repo.checkout("other", Options::default()).unwrap_err();
+    assert!(matches!(err, Error::Hook(_)), "{err:?}");
+    let head = repo.head_id()?.detach();
+    assert_ne!(head, previous, "the checkout remains even if the hook fails");
+    assert_eq!(
+        *calls.lock().unwrap(),
+        [vec![previous.to_string().into(), head.to_string().into(), "1".into()]] as [Vec<std::ffi::OsString>; 1]
+    );
+    Ok(())
+}
@@ -0,0 +1,278 @@
+use std::path::Path;
+
+use gix::{
+    bstr::ByteSlice,
+    cherry_pick::{Error, Options},
+    merge::conflict::Kind,
+    revert, ObjectId,
+};
+
+use crate::{git, repo_rw};
+
+fn id(repo: &gix::Repository, spec: &str) -> crate::Result<ObjectId> {
+    Ok(repo.rev_parse_single(spec)?.detach())
+}
+
+/// All of `commit` that is independent of the time it was created at, while the author is only ignored if it's the current one.
+fn commit_state(dir: &Path, commit: &str) -> crate::Result<String> {
+    Ok(
+        git(dir, &["log", "-1", "--format=%T%n%P%n%an <%ae>%n%cn <%ce>%n%B", commit])?
+            + &git(dir, &["log", "-1", "--format=%ad", commit])?
+                .replace(&git(dir, &["log", "-1", "--format=%cd", commit])?, "now"),
+    )
+}
+
+/// Checkout `onto` in a fresh fixture, and run `git <args>` to compare the resulting commit with `actual` in `dir`.
+fn assert_same_as_git(dir: &Path, actual: ObjectId, onto: &str, args: &[&str]) -> crate::Result {
+    let (_, git_tmp) = repo_rw("make_cherry_pick_revert_repo.sh")?;
+    git(git_tmp.path(), &["checkout", "-q", onto])?;
+    let status = std::process::Command::new("git")
+        .args(args)
+        .current_dir(git_tmp.path())
+        .output()?
+        .status;
+    assert!(status.success(), "git {args:?} succeeds");
+    assert_eq!(
+        commit_state(dir, &actual.to_string())?,
+        commit_state(git_tmp.path(), "HEAD")?,
+        "the new commit is the same as the one created by git {args:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn cherry_pick_is_the_same_as_with_git() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_cherry_pick_revert_repo.sh")?;
+    let release = id(&repo, "release")?;
+    let outcome = repo.cherry_pick(id(&repo, "feature~1")?, release, Options::default())?;
+    assert!(outcome.merge.conflicts.is_empty());
+    assert_same_as_git(
+        tmp.path(),
+        outcome.commit.expect("no conflicts"),
+        "release",
+        &["cherry-pick", "feature~1"],
+    )?;
+
+    let outcome = repo.cherry_pick(
+        id(&repo, "feature~1")?,
+        release,
+        Options {
+            record_origin: true,
+            ..Default::default()
+        },
+    )?;
+    let commit = outcome.commit.expect("no conflicts");
+    assert_same_as_git(tmp.path(), commit, "release", &["cherry-pick", "-x", "feature~1"])?;
+    assert_eq!(
+        git(tmp.path(), &["log", "-1", "--format=%B", &commit.to_string()])?,
+        format!(
+            "fix line 2\n\nSigned-off-by: author <author@example.com>\n(cherry picked from commit {})\n\n",
+            id(&repo, "feature~1")?
+        ),
+        "the line is part of the trailers"
+    );
+
+    let commit = repo
+        .cherry_pick(
+            id(&repo, "side")?,
+            id(&repo, "feature")?,
+            Options {
+                record_origin: true,
+                ..Default::default()
+            },
+        )?
+        .commit
+        .expect("no conflicts");
+    assert_same_as_git(tmp.path(), commit, "feature", &["cherry-pick", "-x", "side"])?;
+    assert_eq!(
+        git(tmp.path(), &["rev-parse", "release"])?.trim(),
+        release.to_string(),
+        "references aren't changed"
+    );
+    Ok(())
+}
+
+#[test]
+fn cherry_pick_with_conflicts() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_cherry_pick_revert_repo.sh")?;
+    let feature = id(&repo, "feature")?;
+    let outcome = repo.cherry_pick(feature, id(&repo, "release")?, Options::default())?;
+    assert_eq!(outcome.commit, None, "nothing is written if there are conflicts");
+    assert_eq!(outcome.merge.conflicts.len(), 1);
+    let conflict = &outcome.merge.conflicts[0];
+    assert_eq!(conflict.path, "a");
+    assert_eq!(conflict.kind, Kind::Content);
+
+    let (_, git_tmp) = repo_rw("make_cherry_pick_revert_repo.sh")?;
+    git(git_tmp.path(), &["checkout", "-q", "release"])?;
+    git(git_tmp.path(), &["cherry-pick", "feature"])?;
+    let unmerged: String = outcome
+        .merge
+        .index
+        .entries()
+        .iter()
+        .filter(|entry| entry.stage() != 0)
+        .map(|entry| {
+            format!(
+                "{:o} {} {}\t{}\n",
+                entry.mode.bits(),
+                entry.id,
+                entry.stage(),
+                entry.path(&outcome.merge.index)
+            )
+        })
+        .collect();
+    assert_eq!(unmerged, git(git_tmp.path(), &["ls-files", "--unmerged"])?);
+
+    let merged = repo.find_object(conflict.merged_blob.expect("text file"))?;
+    let release_short = repo.rev_parse_single("release")?.shorten()?.to_string();
+    assert_eq!(
+        merged.data.to_str()?,
+        std::fs::read_to_string(git_tmp.path().join("a"))?.replace("<<<<<<< HEAD", &format!("<<<<<<< {release_short}")),
+        "the conflict markers are labelled with the commit the changes are replayed onto instead of HEAD"
+    );
+    assert_eq!(
+        git(tmp.path(), &["status", "--porcelain"])?,
+        "",
+        "the worktree isn't touched"
+    );
+    Ok(())
+}
+
+#[test]
+fn cherry_pick_merge_commits_with_mainline() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_cherry_pick_revert_repo.sh")?;
+    let (merged, main) = (id(&repo, "merged")?, id(&repo, "main")?);
+    assert!(matches!(
+        repo.cherry_pick(merged, main, Options::default()),
+        Err(Error::MainlineMissing { id }) if id == merged
+    ));
+    for mainline in [0, 3] {
+        assert!(matches!(
+            repo.cherry_pick(
+                merged,
+                main,
+                Options {
+                    mainline: Some(mainline),
+                    ..Default::default()
+                }
+            ),
+            Err(Error::InvalidMainline { parents: 2, .. })
+        ));
+    }
+    assert!(matches!(
+        repo.cherry_pick(
+            id(&repo, "side")?,
+            main,
+            Options {
+                mainline: Some(1),
+                ..Default::default()
+            }
+        ),
+        Err(Error::NotAMerge { .. })
+    ));
+
+    for mainline in [1, 2] {
+        let commit = repo
+            .cherry_pick(
+                merged,
+                main,
+                Options {
+                    mainline: Some(mainline),
+                    ..Default::default()
+                },
+            )?
+            .commit
+            .expect("no conflicts");
+        assert_same_as_git(
+            tmp.path(),
+            commit,
+            "main",
+            &["cherry-pick", "-m", &mainline.to_string(), "merged"],
+        )?;
+    }
+    Ok(())
+}
+
+#[test]
+fn empty_changes_need_to_be_allowed() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_cherry_pick_revert_repo.sh")?;
+    let (fix, feature) = (id(&repo, "feature~1")?, id(&repo, "feature")?);
+    assert!(matches!(
+        repo.cherry_pick(fix, feature, Options::default()),
+        Err(Error::Empty { id }) if id == fix
+    ));
+    let commit = repo
+        .cherry_pick(
+            fix,
+            feature,
+            Options {
+                allow_empty: true,
+                ..Default::default()
+            },
+        )?
+        .commit
+        .expect("no conflicts");
+    assert_eq!(
+        repo.find_object(commit)?.into_commit().tree_id()?,
+        repo.find_object(feature)?.into_commit().tree_id()?
+    );
+    Ok(())
+}
+
+#[test]
+fn revert_is_the_same_as_with_git() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_cherry_pick_revert_repo.sh")?;
+    let commit = repo
+        .revert(
+            id(&repo, "feature~1")?,
+            id(&repo, "feature")?,
+            revert::Options::default(),
+        )?
+        .commit
+        .expect("no conflicts");
+    assert_same_as_git(tmp.path(), commit, "feature", &["revert", "--no-edit", "feature~1"])?;
+
+    let commit = repo
+        .revert(
+            id(&repo, "merged")?,
+            id(&repo, "merged")?,
+            revert::Options {
+                mainline: Some(1),
+                ..Default::default()
+            },
+        )?
+        .commit
+        .expect("no conflicts");
+    assert_same_as_git(
+        tmp.path(),
+        commit,
+        "merged",
+        &["revert", "--no-edit", "-m", "1", "merged"],
+    )?;
+
+    let root = id(&repo, "main")?;
+    let commit = repo
+        .revert(root, root, revert::Options::default())?
+        .commit
+        .expect("no conflicts");
+    assert_same_as_git(tmp.path(), commit, "main", &["revert", "--no-edit", "main"])?;
+    assert_eq!(
+        repo.find_object(commit)?.into_commit().tree_id()?,
+        repo.empty_tree().id,
+        "reverting the root commit removes everything"
+    );
+
+    assert!(
+        matches!(
+            repo.revert(
+                id(&repo, "feature~1")?,
+                id(&repo, "release")?,
+                revert::Options::default()
+            ),
+            Err(Error::Empty { .. })
+        ),
+        "the change to revert isn't present"
+    );
+    Ok(())
+}
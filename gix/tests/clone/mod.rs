@@ -523,6 +523,134 @@ mod blocking_io {
         Ok(())
     }
 
+    mod partial {
+        use std::sync::atomic::AtomicBool;
+
+        use gix::remote::fetch::{Filter, Status};
+        use gix_object::bstr::ByteSlice;
+
+        use crate::util::restricted;
+
+        fn server() -> crate::Result<std::path::PathBuf> {
+            Ok(gix_testtools::scripted_fixture_read_only("make_partial_clone_remote.sh")?.join("server"))
+        }
+
+        fn num_received_objects(status: &Status) -> u32 {
+            match status {
+                Status::Change { write_pack_bundle, .. } => write_pack_bundle.index.num_objects,
+                _ => unreachable!("a clone always carries a change"),
+            }
+        }
+
+        fn promisor_files(repo: &gix::Repository) -> crate::Result<usize> {
+            Ok(std::fs::read_dir(repo.objects.store_ref().path().join("pack"))?
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "promisor"))
+                .count())
+        }
+
+        #[test]
+        fn blob_none_then_checkout_fetches_blobs_on_demand() -> crate::Result {
+            let tmp = gix_testtools::tempfile::TempDir::new()?;
+            let mut prepare = gix::clone::PrepareFetch::new(
+                server()?,
+                tmp.path(),
+                gix::create::Kind::WithWorktree,
+                Default::default(),
+                restricted(),
+            )?
+            .with_filter(Filter::BlobNone);
+            let (mut checkout, out) = prepare.fetch_then_checkout(gix::progress::Discard, &AtomicBool::default())?;
+            assert_eq!(
+                num_received_objects(&out.status),
+                2 + 4,
+                "two commits with their trees, but no blob"
+            );
+            let (repo, _) = checkout.main_worktree(gix::progress::Discard, &AtomicBool::default())?;
+
+            let work_dir = repo.work_dir().expect("non-bare");
+            assert_eq!(std::fs::read(work_dir.join("a"))?, b"a\n");
+            assert_eq!(std::fs::read(work_dir.join("dir").join("c"))?, b"c\n");
+            assert_eq!(
+                promisor_files(&repo)?,
+                1 + 1,
+                "the pack of the clone and the one with all blobs of the checkout, fetched at once, are marked"
+            );
+
+            let config = repo.config_snapshot();
+            assert_eq!(
+                config.string("extensions.partialClone").as_deref(),
+                Some("origin".into())
+            );
+            assert_eq!(config.boolean("remote.origin.promisor"), Some(true));
+            assert_eq!(
+                config.string("remote.origin.partialCloneFilter").as_deref(),
+                Some("blob:none".into())
+            );
+            assert_eq!(config.integer("core.repositoryFormatVersion"), Some(1));
+
+            let repo = gix::open_opts(repo.path(), restricted())?;
+            let removed = repo.rev_parse_single("HEAD~1:removed")?;
+            assert_eq!(
+                removed.object()?.data.as_bstr(),
+                "removed\n",
+                "objects are fetched on demand after opening a partial clone"
+            );
+
+            let out = std::process::Command::new("git")
+                .args(["cat-file", "-p", "HEAD:dir/b"])
+                .current_dir(work_dir)
+                .output()?;
+            assert_eq!(out.stdout.as_bstr(), "b\n", "git understands our partial clone");
+            Ok(())
+        }
+
+        #[test]
+        fn tree_depth_zero_with_protocol_v1() -> crate::Result {
+            let tmp = gix_testtools::tempfile::TempDir::new()?;
+            let (repo, out) = gix::clone::PrepareFetch::new(
+                server()?,
+                tmp.path(),
+                gix::create::Kind::Bare,
+                Default::default(),
+                restricted().config_overrides(Some("protocol.version=1")),
+            )?
+            .with_filter(Filter::TreeDepth(0))
+            .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+            assert_eq!(num_received_objects(&out.status), 2, "only commits");
+            assert_eq!(promisor_files(&repo)?, 1);
+
+            let tree = repo.head_commit()?.tree()?;
+            assert_eq!(tree.iter().count(), 2, "trees are fetched on demand");
+            assert_eq!(promisor_files(&repo)?, 2);
+            Ok(())
+        }
+
+        #[test]
+        fn filters_can_be_parsed_and_displayed() -> crate::Result {
+            for (spec, filter) in [
+                ("blob:none", Filter::BlobNone),
+                ("blob:limit=1024", Filter::BlobLimit(1024)),
+                ("tree:3", Filter::TreeDepth(3)),
+                ("object:type=commit", Filter::ObjectType(gix::object::Kind::Commit)),
+            ] {
+                assert_eq!(Filter::from_spec(spec.into())?, filter);
+                assert_eq!(filter.to_string(), spec);
+            }
+            assert_eq!(Filter::from_spec("blob:limit=2k".into())?, Filter::BlobLimit(2048));
+            for invalid in [
+                "blob:some",
+                "blob:limit=",
+                "tree:-1",
+                "combine:blob:none+tree:1",
+                "object:type=other",
+            ] {
+                assert!(Filter::from_spec(invalid.into()).is_err(), "{invalid}");
+            }
+            Ok(())
+        }
+    }
+
     #[test]
     fn fetch_only_without_configuration() -> crate::Result {
         let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -117,6 +117,32 @@ mod ssh {
     }
 }
 
+mod gpg {
+    use gix::config::tree::{Gpg, Key};
+
+    use crate::config::tree::bcow;
+
+    #[test]
+    fn format() -> crate::Result {
+        for (actual, expected) in [
+            ("openpgp", gix::gpg::Format::OpenPgp),
+            ("x509", gix::gpg::Format::X509),
+            ("ssh", gix::gpg::Format::Ssh),
+        ] {
+            assert_eq!(Gpg::FORMAT.try_into_format(bcow(actual))?, expected);
+            assert!(Gpg::FORMAT.validate(actual.into()).is_ok());
+        }
+
+        assert_eq!(
+            Gpg::FORMAT.try_into_format(bcow("SSH")).unwrap_err().to_string(),
+            "The key \"gpg.format=SSH\" was invalid",
+            "case-sensitive comparisons"
+        );
+        assert!(Gpg::FORMAT.validate("gpg".into()).is_err());
+        Ok(())
+    }
+}
+
 mod diff {
     use gix::{
         config::tree::{Diff, Key},
@@ -170,17 +196,12 @@ mod diff {
             ("Default", Algorithm::Myers),
             ("minimal", Algorithm::MyersMinimal),
             ("histogram", Algorithm::Histogram),
+            ("patience", Algorithm::Patience),
+            ("Patience", Algorithm::Patience),
         ] {
             assert_eq!(Diff::ALGORITHM.try_into_algorithm(bcow(actual))?, expected);
             assert!(Diff::ALGORITHM.validate(actual.into()).is_ok());
         }
-        assert_eq!(
-            Diff::ALGORITHM
-                .try_into_algorithm(bcow("patience"))
-                .unwrap_err()
-                .to_string(),
-            "The 'patience' algorithm is not yet implemented"
-        );
         assert_eq!(
             Diff::ALGORITHM.try_into_algorithm(bcow("foo")).unwrap_err().to_string(),
             "Unknown diff algorithm named 'foo'"
@@ -286,6 +307,35 @@ mod core {
         Ok(())
     }
 
+    #[test]
+    fn auto_crlf() -> crate::Result {
+        use gix::filter::eol::AutoCrlf;
+        assert_eq!(
+            Core::AUTO_CRLF.try_into_autocrlf(Some(Ok(true)), || None)?,
+            Some(AutoCrlf::Enabled)
+        );
+        assert!(Core::AUTO_CRLF.validate("true".into()).is_ok());
+        assert_eq!(
+            Core::AUTO_CRLF.try_into_autocrlf(Some(Ok(false)), || None)?,
+            Some(AutoCrlf::Disabled)
+        );
+        assert!(Core::AUTO_CRLF.validate("off".into()).is_ok());
+        assert_eq!(
+            Core::AUTO_CRLF.try_into_autocrlf(None, || Some(bcow("input")))?,
+            Some(AutoCrlf::Input)
+        );
+        assert!(Core::AUTO_CRLF.validate("input".into()).is_ok());
+        assert_eq!(
+            Core::AUTO_CRLF
+                .try_into_autocrlf(None, || Some(bcow("invalid")))
+                .unwrap_err()
+                .to_string(),
+            "The key \"core.autocrlf=invalid\" was invalid"
+        );
+        assert!(Core::AUTO_CRLF.validate("invalid".into()).is_err());
+        Ok(())
+    }
+
     #[test]
     fn abbrev() -> crate::Result {
         let object_hash = gix_hash::Kind::Sha1;
@@ -378,6 +428,56 @@ mod extensions {
         assert!(Extensions::OBJECT_FORMAT.validate("invalid".into()).is_err());
         Ok(())
     }
+
+    #[test]
+    fn ref_storage() -> crate::Result {
+        Extensions::REF_STORAGE.try_into_ref_storage(bcow("files"))?;
+        Extensions::REF_STORAGE.try_into_ref_storage(bcow("FILES"))?;
+        assert_eq!(
+            Extensions::REF_STORAGE
+                .try_into_ref_storage(bcow("reftable"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"extensions.refStorage=reftable\" was invalid",
+            "only the files backend is supported"
+        );
+        assert!(Extensions::REF_STORAGE.validate("files".into()).is_ok());
+        assert!(Extensions::REF_STORAGE.validate("reftable".into()).is_err());
+        Ok(())
+    }
+}
+
+mod gc {
+    use std::time::{Duration, SystemTime};
+
+    use gix::config::tree::{Gc, Key};
+
+    use crate::config::tree::bcow;
+
+    #[test]
+    fn worktree_prune_expire() -> crate::Result {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100 * 24 * 60 * 60);
+        assert_eq!(Gc::WORKTREE_PRUNE_EXPIRE.try_into_expiry(bcow("never"), now)?, None);
+        assert_eq!(Gc::WORKTREE_PRUNE_EXPIRE.try_into_expiry(bcow("now"), now)?, Some(now));
+        assert_eq!(
+            Gc::WORKTREE_PRUNE_EXPIRE.try_into_expiry(bcow("2 weeks ago"), now)?,
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(86 * 24 * 60 * 60))
+        );
+        assert_eq!(
+            Gc::WORKTREE_PRUNE_EXPIRE.try_into_expiry(bcow("1 day ago"), now)?,
+            Some(now - Duration::from_secs(24 * 60 * 60))
+        );
+        assert_eq!(
+            Gc::WORKTREE_PRUNE_EXPIRE
+                .try_into_expiry(bcow("invalid"), now)
+                .unwrap_err()
+                .to_string(),
+            "The date format at key \"gc.worktreePruneExpire=invalid\" was invalid"
+        );
+        assert!(Gc::WORKTREE_PRUNE_EXPIRE.validate("never".into()).is_ok());
+        assert!(Gc::WORKTREE_PRUNE_EXPIRE.validate("invalid".into()).is_err());
+        Ok(())
+    }
 }
 
 mod checkout {
@@ -0,0 +1,2 @@
+mod patch;
+mod textconv;
@@ -0,0 +1,183 @@
+use std::convert::Infallible;
+
+use gix::{
+    bstr::{BString, ByteSlice},
+    diff::patch::{stat, WordDiff, Writer},
+};
+
+use crate::{named_repo, repo_opts, restricted};
+
+pub(super) fn baseline(repo: &gix::Repository, name: &str) -> crate::Result<BString> {
+    Ok(std::fs::read(repo.git_dir().join(name))?.into())
+}
+
+/// Call `for_each` with each change between the trees of the first and the second commit, ordered by location like `git` does.
+pub(super) fn for_each_change(
+    repo: &gix::Repository,
+    mut for_each: impl FnMut(&gix::object::tree::diff::Change<'_, '_, '_>) -> crate::Result,
+) -> crate::Result {
+    let from = repo.rev_parse_single("HEAD~1^{tree}")?.object()?.into_tree();
+    let to = repo.rev_parse_single("HEAD^{tree}")?.object()?.into_tree();
+    let mut locations = Vec::new();
+    from.changes()?
+        .track_path()
+        .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+            locations.push(change.location.to_owned());
+            Ok(Default::default())
+        })?;
+    locations.sort();
+    for location in locations {
+        let mut result = Ok(());
+        from.changes()?
+            .track_path()
+            .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+                if change.location == location {
+                    result = for_each(&change);
+                }
+                Ok(Default::default())
+            })?;
+        result?;
+    }
+    Ok(())
+}
+
+pub(super) fn patch(repo: &gix::Repository, writer: &mut Writer<'_>) -> crate::Result<BString> {
+    let mut out = Vec::new();
+    for_each_change(repo, |change| Ok(writer.write_change(change, &mut out)?))?;
+    Ok(out.into())
+}
+
+fn stats(repo: &gix::Repository) -> crate::Result<stat::Stats> {
+    let mut writer = repo.diff_patch_writer()?;
+    let mut stats = writer.stats();
+    for_each_change(repo, |change| {
+        stats.files.extend(writer.stat_change(change)?);
+        Ok(())
+    })?;
+    Ok(stats)
+}
+
+#[test]
+fn patch_matches_git() -> crate::Result {
+    let repo = named_repo("make_diff_patch_repo.sh")?;
+    let actual = patch(&repo, &mut repo.diff_patch_writer()?)?;
+    let expected = if cfg!(feature = "regex") {
+        baseline(&repo, "patch.baseline")?
+    } else {
+        baseline(&repo, "patch-default-funcname.baseline")?
+    };
+    assert_eq!(
+        actual, expected,
+        "function names use the xfuncname of the driver if regular expressions are supported, \
+         and git's default heuristic otherwise"
+    );
+    Ok(())
+}
+
+#[test]
+fn patch_with_word_diff_matches_git() -> crate::Result {
+    let repo = named_repo("make_diff_patch_repo.sh")?;
+    for (mode, name) in [
+        (WordDiff::Plain, "word-diff-plain.baseline"),
+        (WordDiff::Porcelain, "word-diff-porcelain.baseline"),
+    ] {
+        let mut writer = repo.diff_patch_writer()?;
+        writer.options.word_diff = Some(mode);
+        let actual = patch(&repo, &mut writer)?;
+        let expected = baseline(&repo, name)?;
+        if cfg!(feature = "regex") {
+            assert_eq!(actual, expected, "{mode:?}");
+        } else {
+            let without_hunk_headers = |patch: &BString| -> Vec<BString> {
+                patch
+                    .lines()
+                    .filter(|l| !l.starts_with(b"@@"))
+                    .map(Into::into)
+                    .collect()
+            };
+            assert_eq!(
+                without_hunk_headers(&actual),
+                without_hunk_headers(&expected),
+                "{mode:?}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn patch_options_are_read_from_configuration() -> crate::Result {
+    for (overrides, name) in [
+        (&["diff.noprefix=true"][..], "patch-no-prefix.baseline"),
+        (
+            &["diff.context=1", "diff.interHunkContext=2"][..],
+            "patch-context.baseline",
+        ),
+    ] {
+        let repo = repo_opts(
+            "make_diff_patch_repo.sh",
+            restricted().config_overrides(overrides.iter().copied()),
+        )?
+        .to_thread_local();
+        let mut writer = repo.diff_patch_writer()?;
+        let actual = patch(&repo, &mut writer)?;
+        let expected = baseline(&repo, name)?;
+        if cfg!(feature = "regex") {
+            assert_eq!(actual, expected, "{overrides:?}");
+        } else {
+            assert_eq!(actual.lines().count(), expected.lines().count(), "{overrides:?}");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn mnemonic_prefixes_apply_unless_both_sides_are_commits() -> crate::Result {
+    use gix::diff::patch::{Prefix, Side};
+    let repo = repo_opts(
+        "make_diff_patch_repo.sh",
+        restricted().config_overrides(["diff.mnemonicPrefix=true"]),
+    )?
+    .to_thread_local();
+    let writer = repo.diff_patch_writer()?.with_sides(Side::Commit, Side::Commit);
+    assert_eq!(
+        writer.options.prefix,
+        Prefix::default(),
+        "git ignores mnemonic prefixes here"
+    );
+    let writer = writer.with_sides(Side::Commit, Side::WorkTree);
+    assert_eq!(writer.options.prefix, Prefix::mnemonic(Side::Commit, Side::WorkTree));
+    assert_eq!(writer.options.prefix.old, "c/");
+    assert_eq!(writer.options.prefix.new, "w/");
+    Ok(())
+}
+
+#[test]
+fn stats_match_git() -> crate::Result {
+    let repo = named_repo("make_diff_patch_repo.sh")?;
+    let stats = stats(&repo)?;
+
+    for (name, width) in [("stat.baseline", 80), ("stat-narrow.baseline", 40)] {
+        let mut out = Vec::new();
+        stats.write_stat(&mut out, width)?;
+        assert_eq!(out.as_bstr(), baseline(&repo, name)?, "{name}");
+    }
+
+    let mut out = Vec::new();
+    stats.write_numstat(&mut out)?;
+    assert_eq!(out.as_bstr(), baseline(&repo, "numstat.baseline")?);
+
+    let mut out = Vec::new();
+    stats.write_summary(&mut out)?;
+    assert_eq!(out.as_bstr(), baseline(&repo, "shortstat.baseline")?);
+
+    assert_eq!(
+        stats.totals(),
+        stat::Totals {
+            files: 12,
+            insertions: 14,
+            deletions: 16
+        }
+    );
+    Ok(())
+}
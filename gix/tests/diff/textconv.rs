@@ -0,0 +1,105 @@
+// The fixture caches `textconv` output when diffing, which is why all tests use a writable copy of it.
+use gix::{bstr::ByteSlice, diff::driver::Diffable};
+
+use super::patch::{baseline, for_each_change, patch};
+use crate::repo_rw;
+
+#[test]
+fn patch_and_numstat_match_git() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_diff_textconv_repo.sh")?;
+    let actual = patch(&repo, &mut repo.diff_patch_writer()?)?;
+    assert_eq!(
+        actual,
+        baseline(&repo, "patch.baseline")?,
+        "binary attributes are respected and textconv output is diffed"
+    );
+
+    let mut writer = repo.diff_patch_writer()?;
+    let mut stats = writer.stats();
+    for_each_change(&repo, |change| {
+        stats.files.extend(writer.stat_change(change)?);
+        Ok(())
+    })?;
+    let mut out = Vec::new();
+    stats.write_numstat(&mut out)?;
+    assert_eq!(out.as_bstr(), baseline(&repo, "numstat.baseline")?);
+    Ok(())
+}
+
+#[test]
+fn blob_diff_platform_respects_attributes() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_diff_textconv_repo.sh")?;
+    for_each_change(&repo, |change| {
+        let location = change.location.to_str_lossy();
+        let diff = change.diff().expect("all changes are modifications")?;
+        let expect_binary = ["file.bin", "file.nodiff", "file.forced-binary", "file.dat"].contains(&location.as_ref());
+        assert_eq!(diff.is_binary, expect_binary, "{location}");
+        if ["file.upper", "file.cached"].contains(&location.as_ref()) {
+            let (old, new) = diff.textconv.as_ref().expect("converted");
+            assert_eq!(old.as_bstr(), "HELLO\nWORLD\n");
+            assert_eq!(new.as_bstr(), "HELLO\nTHERE\n");
+        } else {
+            assert!(diff.textconv.is_none(), "{location}");
+        }
+        let counts = diff.line_counts();
+        assert_eq!((counts.insertions, counts.removals), (1, 1), "{location}");
+        Ok(())
+    })
+}
+
+#[test]
+fn textconv_output_is_cached_in_notes() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_diff_textconv_repo.sh")?;
+    let mut drivers = repo.diff_drivers()?;
+    let data = b"hello\n";
+    let id = repo.write_blob(data)?.detach();
+    assert_eq!(
+        drivers.diffable("file.cached".into(), &id, data)?,
+        Diffable::Text(b"HELLO\n".as_slice().into())
+    );
+
+    let commit = repo
+        .find_reference("refs/notes/textconv/cached")?
+        .peel_to_id_in_place()?
+        .object()?
+        .into_commit();
+    assert_eq!(
+        commit.message_raw()?,
+        "tr a-z A-Z <",
+        "the program is stored to validate the cache"
+    );
+    let tree = commit.tree()?;
+    let note = tree
+        .lookup_entry_by_path(id.to_hex().to_string())?
+        .expect("note for the converted blob");
+    assert_eq!(note.object()?.data.as_bstr(), "HELLO\n");
+    assert!(
+        repo.try_find_reference("refs/notes/textconv/upper")?.is_none(),
+        "only drivers with `cachetextconv` use the cache"
+    );
+
+    let cached_output = repo.write_blob(b"FROM CACHE\n")?.detach();
+    let tree = repo.write_object(gix::objs::Tree {
+        entries: vec![gix::objs::tree::Entry {
+            mode: gix::objs::tree::EntryMode::Blob,
+            filename: id.to_hex().to_string().into(),
+            oid: cached_output,
+        }],
+    })?;
+    let mut cache = gix::objs::Commit::from(commit.decode()?);
+    cache.tree = tree.detach();
+    let cache = repo.write_object(&cache)?.detach();
+    repo.reference(
+        "refs/notes/textconv/cached",
+        cache,
+        gix_ref::transaction::PreviousValue::Any,
+        "test",
+    )?;
+    let mut drivers = repo.diff_drivers()?;
+    assert_eq!(
+        drivers.diffable("file.cached".into(), &id, data)?,
+        Diffable::Text(b"FROM CACHE\n".as_slice().into()),
+        "the cached output is used if present"
+    );
+    Ok(())
+}
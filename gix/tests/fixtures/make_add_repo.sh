@@ -0,0 +1,54 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config filter.upper.clean 'tr a-z A-Z'
+git config filter.broken.clean false
+git config filter.broken-required.clean false
+git config filter.broken-required.required true
+
+cat <<ATTRS > .gitattributes
+*.crlf text
+*.bin binary
+*.auto text=auto
+*.id ident
+*.upper filter=upper
+*.broken filter=broken
+*.required filter=broken-required
+ATTRS
+printf '*.log\nbuild/\n' > .gitignore
+echo tracked > tracked
+echo unchanged > unchanged
+echo gone > gone
+mkdir dir becomes-dir
+echo nested > dir/nested
+echo file > becomes-dir/file
+echo "echo hello" > script
+printf 'committed\r\nwith crlf\r\n' > committed.auto
+git add .
+git commit -q -m initial
+
+echo changed >> tracked
+rm gone
+chmod +x script
+rm -r becomes-dir
+mkdir -p becomes-dir/file
+echo now-nested > becomes-dir/file/nested
+echo new > new
+echo new > dir/new
+mkdir -p new-dir/deep
+echo deep > new-dir/deep/file
+ln -s tracked link
+echo log > a.log
+mkdir build
+echo build > build/output
+printf 'text\r\nwith crlf\r\nand lone\rcr\r\n' > lines.crlf
+printf 'binary\r\n' > data.bin
+printf 'auto\r\nconverted\r\n' > new.auto
+printf 'still\r\ncommitted\r\n' > committed.auto
+printf '$Id: 0123456789abcdef $\n' > file.id
+echo lower > file.upper
+echo content > file.broken
+
+git init -q embedded
+(cd embedded && git commit -q --allow-empty -m "embedded")
@@ -0,0 +1,46 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+seq 1 20 > file
+seq 1 5 > to-delete
+seq 1 30 > to-rename
+echo "echo hello" > script
+ln -s file link
+mkdir dir
+echo nested > dir/nested
+for i in $(seq 1 200); do printf "binary\0data %d\n" $i; done > binary
+git add .
+git commit -q -m A
+git tag first
+
+sed -i 's/^2$/two/; s/^15$/fifteen/' file
+git rm -q to-delete
+git mv to-rename renamed
+sed -i 's/^30$/thirty/' renamed
+chmod +x script
+ln -sf dir/nested link
+echo "changed" >> dir/nested
+for i in $(seq 1 200); do printf "binary\0data %d\n" $((i == 100 ? 1000 : i)); done > binary
+printf "new\0binary" > new-binary
+echo "new file" > dir/new-file
+git add .
+git commit -q -m B -m "with a body"
+git tag second
+
+echo "more" >> file
+git add file
+GIT_AUTHOR_NAME="Jörg Ünicode" GIT_AUTHOR_EMAIL="joerg@example.com" git commit -q -m "C with ümlaut"
+git tag third
+
+git diff --binary --full-index first second > .git/ab.patch
+git format-patch -q --stdout first..third > .git/series.mbox
+
+git checkout -q -b conflict first
+sed -i 's/^2$/zwei/; s/^10$/zehn/' file
+git commit -q -am conflict
+git diff first conflict > .git/conflict.patch
+
+git checkout -q main 2>/dev/null || git checkout -q master
+git reset -q --hard first
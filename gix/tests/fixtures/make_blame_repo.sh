@@ -0,0 +1,53 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+time=1000000000
+function commit() {
+  time=$((time + 60))
+  GIT_AUTHOR_DATE="@$time +0000" GIT_COMMITTER_DATE="@$time +0000" git commit -q "$@"
+}
+
+printf 'a\nb\nc\nd\ne\n' > file.txt
+git add file.txt
+commit -m "initial"
+
+printf 'a\nb\nC\nd\ne\nf\n' > file.txt
+commit -am "modify c and append f"
+
+echo other > other.txt
+git add other.txt
+commit -m "unrelated"
+
+git mv file.txt renamed.txt
+commit -m "rename"
+
+printf 'A\nb\nC\nd\ne\nf\n' > renamed.txt
+commit -am "modify a"
+
+printf 'A\nb\nC\nD\ne\nf\n' > renamed.txt
+commit -am "reformat d"
+git tag reformat
+
+git checkout -q -b side
+printf 'A\nb\nC\nD\nE\nf\n' > renamed.txt
+commit -am "modify e on side"
+
+git checkout -q main
+printf 'A\nb\nC\nD\ne\nf\ng\n' > renamed.txt
+commit -am "append g on main"
+
+GIT_AUTHOR_DATE="@$((time + 60)) +0000" GIT_COMMITTER_DATE="@$((time + 60)) +0000" git merge -q --no-edit side
+time=$((time + 60))
+
+echo more >> other.txt
+commit -am "unrelated again"
+
+git commit-graph write --no-progress --reachable --changed-paths
+
+git rev-parse reformat > .git/ignore-revs
+git blame --porcelain renamed.txt > .git/blame-baseline-all
+git blame --porcelain -L 2,4 renamed.txt > .git/blame-baseline-range
+git blame --porcelain --ignore-rev reformat renamed.txt > .git/blame-baseline-ignore-rev
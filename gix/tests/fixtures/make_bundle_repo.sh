@@ -0,0 +1,30 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit() {
+  local n=${1:?need number}
+  echo "content $n" > "file$n"
+  git add .
+  git commit -q -m "commit $n"
+}
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  commit 1
+  commit 2
+  git tag -a -m "annotated" v1
+  git checkout -q -b other
+  echo other > other
+  git add other
+  git commit -q -m "other"
+  git checkout -q main
+  commit 3
+  commit 4
+
+  git bundle create -q ../all.bundle --all
+  git bundle create -q ../incremental.bundle v1..main
+  git bundle create -q --version=3 ../v3.bundle main
+)
+
+git -c advice.detachedHead=false clone -q --no-local --single-branch --branch v1 repo base
@@ -0,0 +1,35 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+echo '*.log' > .gitignore
+echo a > a
+echo b > b
+mkdir dir
+echo c > dir/c
+echo keep > dir/keep
+echo "echo hello" > script
+echo file > becomes-dir
+echo removed > removed
+ln -s a link
+git add .
+git commit -q -m main
+
+git checkout -q -b other
+echo "a changed" > a
+git rm -q dir/c removed becomes-dir
+mkdir becomes-dir
+echo nested > becomes-dir/nested
+echo d > dir/d
+echo new > new
+echo ignored > new.log
+chmod +x script
+ln -sf b link
+git add .
+git add -f new.log
+git commit -q -m other
+git tag -m "annotated" v1
+
+git checkout -q main
@@ -0,0 +1,35 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+git config user.name gitoxide
+git config user.email gitoxide@localhost
+
+printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > lines
+echo a > a
+git add .
+git commit -q -m "initial commit"
+
+git checkout -q -b feature
+sed -i.bak 's/^2$/2 fixed/' lines && rm lines.bak
+git commit -q -am "fix line 2" -m "Signed-off-by: author <author@example.com>"
+echo "a changed on feature" > a
+git commit -q -am "change a"
+
+git checkout -q -b release main
+sed -i.bak 's/^9$/9 release/' lines && rm lines.bak
+echo "a changed on release" > a
+git commit -q -am "prepare release"
+
+git checkout -q -b side main
+echo side > side
+git add side
+git commit -q -m "add side"
+
+git checkout -q -b merged main
+sed -i.bak 's/^5$/5 merged/' lines && rm lines.bak
+git commit -q -am "change line 5"
+git merge -q --no-ff side -m "Merge branch 'side'"
+
+git checkout -q main
@@ -0,0 +1,20 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+cat >>.git/config <<EOF2
+# a comment that is kept
+[remote "origin"]
+	url = https://example.com/repo ; a trailing comment
+	fetch = +refs/heads/*:refs/remotes/origin/*
+[multi]
+	value = 1
+	value = 2
+[multi]
+	value = 3
+[old "name"]
+	key = value
+[removed]
+	key = value
+EOF2
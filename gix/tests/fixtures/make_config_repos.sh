@@ -163,3 +163,27 @@ mkdir not-a-repo-with-files;
 (cd not-a-repo-with-files
   touch this that
 )
+
+git init http-url-match
+(cd http-url-match
+  git config http.extraHeader "X-Global: 1"
+  git config --add http.https://example.com.extraHeader "X-Host: 1"
+  git config --add http.https://example.com/org.extraHeader "X-Org: 1"
+  git config --add http.https://example.com/organisation.extraHeader "X-Organisation: 1"
+  git config http.https://*.example.com.proxy http://subdomain-proxy
+  git config http.https://internal.example.com.proxy http://internal-proxy
+  git config http.proxy http://global-proxy
+  git config http.https://example.com/org.lowSpeedLimit 10
+  git config http.lowSpeedLimit 5
+  git config http.sslCAInfo ./global.pem
+  git config http.https://example.com.sslCAInfo ./host.pem
+  git config http.https://user@example.com.sslVerify false
+  git config http.http://example.com.sslVerify false
+  git config http.https://example.com:8443.cookieFile ./cookies.txt
+  cat <<EOF >>.git/config
+[http "https://example.com"]
+  extraHeader = "X-Host-Late: 1"
+[http]
+  lowSpeedLimit = 1
+EOF
+)
@@ -0,0 +1,73 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config diff.algorithm myers
+git config diff.funcs.xfuncname '^fn ([a-z_]+)'
+echo '*.rs diff=funcs' > .gitattributes
+
+cat <<EOT > lib.rs
+struct Config {
+    verbose: bool,
+}
+
+fn parse_arguments() {
+    let mut config = Config { verbose: false };
+    config.verbose = true;
+    println!("parsed");
+    drop(config);
+}
+
+fn run() {
+    let first = 1;
+    let second = 2;
+    let third = 3;
+    let fourth = 4;
+    println!("{first} {second} {third} {fourth}");
+}
+
+struct Unused;
+
+fn main() {
+    parse_arguments();
+    run();
+}
+EOT
+seq 20 > plain.txt
+seq 5 > removed.txt
+printf 'echo hello\n' > mode-only.sh
+printf 'echo before\n' > mode-and-content.sh
+printf 'a file which is going to be renamed\nwith two lines\n' > old-name.txt
+printf 'binary\0content\n' > binary.bin
+printf 'first\nlast line without newline' > no-newline.txt
+printf 'one two three\nfour five six\n' > 'with space.txt'
+printf 'the quick brown fox\njumps over the lazy dog\nand runs away\n' > words.txt
+ln -s plain.txt link
+git add . && git commit -q -m c1
+
+sed -i 's/config.verbose = true;/config.verbose = !config.verbose;/' lib.rs
+sed -i 's/let fourth = 4;/let fourth = 40;/' lib.rs
+sed -i 's/    run();/    run();\n    println!("done");/' lib.rs
+sed -i 's/^10$/ten/; s/^11$/eleven/; s/^18$/eighteen/' plain.txt
+git rm -q removed.txt
+chmod +x mode-only.sh mode-and-content.sh
+printf 'echo after\n' > mode-and-content.sh
+git mv old-name.txt new-name.txt
+printf 'binary\0changed\n' > binary.bin
+printf 'first\nchanged last line without newline' > no-newline.txt
+printf 'one 2 three\nfour five six\n' > 'with space.txt'
+printf 'the slow brown fox\njumps over the lazy dog\nand walks away slowly\n' > words.txt
+printf 'new\nfile\n' > 'ünicode.txt'
+rm link && ln -s no-newline.txt link
+git add . && git commit -q -m c2
+
+git diff HEAD~1 HEAD > .git/patch.baseline
+git diff -U1 --inter-hunk-context=2 HEAD~1 HEAD > .git/patch-context.baseline
+git diff --no-prefix HEAD~1 HEAD > .git/patch-no-prefix.baseline
+git -c diff.funcs.xfuncname='^([a-zA-Z_$].*)$' diff HEAD~1 HEAD > .git/patch-default-funcname.baseline
+git diff --word-diff=plain HEAD~1 HEAD > .git/word-diff-plain.baseline
+git diff --word-diff=porcelain HEAD~1 HEAD > .git/word-diff-porcelain.baseline
+git diff --stat HEAD~1 HEAD > .git/stat.baseline
+git diff --stat=40 HEAD~1 HEAD > .git/stat-narrow.baseline
+git diff --numstat HEAD~1 HEAD > .git/numstat.baseline
+git diff --shortstat HEAD~1 HEAD > .git/shortstat.baseline
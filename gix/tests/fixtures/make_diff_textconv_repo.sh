@@ -0,0 +1,46 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config diff.upper.textconv 'tr a-z A-Z <'
+git config diff.cached.textconv 'tr a-z A-Z <'
+git config diff.forced-binary.binary true
+git config diff.forced-text.binary false
+
+cat <<EOT > .gitattributes
+*.upper diff=upper
+*.cached diff=cached
+*.bin binary
+*.nodiff -diff
+*.text diff
+*.forced-binary diff=forced-binary
+*.forced-text diff=forced-text
+EOT
+
+printf 'hello\nworld\n' > file.upper
+printf 'hello\nworld\n' > file.cached
+printf 'text\n' > file.bin
+printf 'text\n' > file.nodiff
+printf 'text\0with null\n' > file.text
+printf 'text\n' > file.forced-binary
+printf 'text\0with null\n' > file.forced-text
+printf 'binary\0content\n' > file.dat
+printf 'plain\n' > file.txt
+git add . && git commit -q -m c1
+
+printf 'hello\nthere\n' > file.upper
+printf 'hello\nthere\n' > file.cached
+printf 'changed\n' > file.bin
+printf 'changed\n' > file.nodiff
+printf 'changed\0with null\n' > file.text
+printf 'changed\n' > file.forced-binary
+printf 'changed\0with null\n' > file.forced-text
+printf 'binary\0changed\n' > file.dat
+printf 'changed\n' > file.txt
+git add . && git commit -q -m c2
+
+git diff HEAD~1 HEAD > .git/patch.baseline
+git diff --numstat HEAD~1 HEAD > .git/numstat.baseline
+
+# Enabled only now as `git diff` would otherwise fill the cache.
+git config diff.cached.cachetextconv true
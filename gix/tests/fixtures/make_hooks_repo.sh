@@ -0,0 +1,45 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+echo content > file
+git add file
+git commit -q -m "initial"
+
+# All hooks log how they were invoked, and fail if there is a file named `fail-<hook>` in the git directory.
+mkdir custom-hooks
+for name in pre-commit prepare-commit-msg commit-msg post-commit post-checkout reference-transaction; do
+  cat <<'EOF' > .git/hooks/$name
+#!/bin/sh
+name=$(basename "$0")
+{
+  echo "$name $*"
+  case "$name" in
+    pre-commit)
+      echo "cwd=$(pwd -P) GIT_DIR=$GIT_DIR GIT_INDEX_FILE=$GIT_INDEX_FILE GIT_EDITOR=$GIT_EDITOR"
+      echo "GIT_AUTHOR_NAME=$GIT_AUTHOR_NAME GIT_AUTHOR_EMAIL=$GIT_AUTHOR_EMAIL GIT_AUTHOR_DATE=$GIT_AUTHOR_DATE"
+      ;;
+    reference-transaction)
+      cat
+      ;;
+  esac
+} >> "$GIT_DIR/hooks.log"
+if [ "$name" = commit-msg ]; then
+  printf '\n\nReviewed-by: %s\n' "$name" >> "$1"
+fi
+echo "$name says hello"
+test ! -e "$GIT_DIR/fail-$name"
+EOF
+  chmod +x .git/hooks/$name
+done
+
+cat <<'EOF' > custom-hooks/pre-commit
+#!/bin/sh
+echo "custom pre-commit" >> "$GIT_DIR/hooks.log"
+exit 1
+EOF
+chmod +x custom-hooks/pre-commit
+# Not executable, and thus ignored.
+cp custom-hooks/pre-commit custom-hooks/commit-msg
+chmod -x custom-hooks/commit-msg
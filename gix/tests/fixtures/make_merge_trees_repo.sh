@@ -0,0 +1,43 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > lines
+printf 'one\ntwo\nthree\n' > conflict
+echo unchanged > unchanged
+echo modified > modify-delete
+echo deleted > delete-unchanged
+echo binary > binary
+echo "echo hello" > script
+git add .
+git commit -q -m base
+
+git checkout -q -b ours
+sed -i.bak 's/^2$/2 ours/' lines && rm lines.bak
+printf 'one\ntwo ours\nthree\n' > conflict
+echo "modified by ours" > modify-delete
+git rm -q delete-unchanged
+printf 'ours\0' > binary
+echo added by ours > added
+echo both > both-added-same
+git add .
+git commit -q -m ours
+
+git checkout -q -b theirs main
+sed -i.bak 's/^8$/8 theirs/' lines && rm lines.bak
+printf 'one\ntwo theirs\nthree\n' > conflict
+git rm -q modify-delete
+printf 'theirs\0' > binary
+echo added by theirs > added
+echo both > both-added-same
+chmod +x script
+echo new > new
+git add .
+git commit -q -m theirs
+
+git checkout -q -b clean main
+sed -i.bak 's/^9$/9 clean/' lines && rm lines.bak
+echo "echo hello world" > script
+git commit -q -am clean
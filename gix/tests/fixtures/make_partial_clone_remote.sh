@@ -0,0 +1,18 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q server
+(cd server
+  git config uploadpack.allowFilter true
+  git config uploadpack.allowAnySHA1InWant true
+
+  mkdir dir
+  echo a >a
+  echo b >dir/b
+  echo removed >removed
+  git add . && git commit -q -m "first"
+
+  echo c >dir/c
+  git rm -q removed
+  git add . && git commit -q -m "second"
+)
@@ -0,0 +1,38 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit() {
+  local name=${1:?need name}
+  echo "content $name" > "file-$name"
+  git add .
+  git commit -q -m "$name"
+}
+
+git init -q client
+(cd client
+  git checkout -q -b main
+  commit 1
+  commit 2
+)
+
+git clone -q --bare client server.git
+git -C server.git branch to-delete main
+git clone -q client worktree
+
+(cd client
+  commit 3
+  commit 4
+  git tag -a -m "annotated" annotated
+  git checkout -q -b feature HEAD~2
+  commit feature
+  git checkout -q main
+)
+
+git pack-objects -q --stdout </dev/null >empty.pack
+
+# a commit that isn't reachable from any ref and whose tree refers to a blob the server doesn't have
+(cd server.git
+  missing_blob=$(echo "never stored" | git hash-object --stdin)
+  tree=$(printf '100644 blob %s\tfile\n' "$missing_blob" | git mktree --missing)
+  git commit-tree -m "incomplete" "$tree" > ../incomplete-commit
+)
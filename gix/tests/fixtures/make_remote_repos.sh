@@ -265,6 +265,10 @@ git clone --shared base credential-helpers
     git config credential.ssh://host:21/path.helper ssh://host:21/path
     git config credential.ssh://host/path.helper ssh://host/path
     git config credential.git://host.org.helper git://host.org
+    git config credential.https://example.com/org.helper https://example.com/org
+    git config credential.example.org.helper example.org
+    git config credential.example.org/partial.helper example.org/partial
+    git config credential.ftp://.helper ftp://
 
     git config credential.https://dev.azure.com.usehttppath true
 
@@ -302,6 +306,14 @@ git clone --shared base credential-helpers
     baseline "ssh://host:21"
     baseline "ssh://host"
     baseline "git://host.org"
+    baseline "https://example.com/org"
+    baseline "https://example.com/org/repo"
+    baseline "https://example.com/organisation"
+    baseline "https://example.org"
+    baseline "http://example.org/partial"
+    baseline "https://example.org/partial/repo"
+    baseline "https://example.org:8080"
+    baseline "ftp://example.net/path"
 )
 
 git clone --shared base detached-head
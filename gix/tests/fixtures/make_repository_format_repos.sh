@@ -0,0 +1,27 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function repo() {
+  local name=${1:?}
+  git init -q "$name"
+  shift
+  while [ $# -gt 0 ]; do
+    git config -f "$name/.git/config" "$1" "$2"
+    shift 2
+  done
+}
+
+repo v0-with-unknown-extension extensions.unknownThing true
+repo v0-with-v1-only-extension extensions.refStorage files
+repo v1-with-unknown-extensions core.repositoryFormatVersion 1 extensions.unknownThing true
+cat >>v1-with-unknown-extensions/.git/config <<EOF2
+[extensions]
+	UNKNOWNTHING = false
+	other
+EOF2
+repo v1-with-reftable core.repositoryFormatVersion 1 extensions.refStorage reftable
+repo v1-with-sha256 core.repositoryFormatVersion 1 extensions.objectFormat sha256
+repo v1-with-known-extensions core.repositoryFormatVersion 1 \
+  extensions.objectFormat sha1 extensions.refStorage files extensions.partialClone origin \
+  extensions.preciousObjects true extensions.worktreeConfig true extensions.noop true extensions.noop-v1 true
+repo v2 core.repositoryFormatVersion 2
@@ -0,0 +1,55 @@
+#!/bin/bash
+set -eu -o pipefail
+
+ssh-keygen -q -t ed25519 -N "" -C "" -f signing-key
+ssh-keygen -q -t ed25519 -N "" -C "" -f untrusted-key
+echo "signer@example.com $(cat signing-key.pub)" > allowed-signers
+
+# Keep the keys of gpg in a directory of our own, made accessible to git and gitoxide with a wrapper script.
+# As the path to the socket of its agent must be short, gpg always runs in a temporary copy of it.
+home=$(mktemp -d /tmp/gix-gnupg.XXXXXX)
+gpg --homedir "$home" --batch --quiet --passphrase '' --quick-gen-key "Signer <signer@example.com>" ed25519 sign never 2>/dev/null
+gpgconf --homedir "$home" --kill gpg-agent
+mkdir -m 700 gnupg
+cp -R "$home/." gnupg
+rm -rf "$home"
+
+cat <<EOF > gpg
+#!/bin/sh
+home=\$(mktemp -d /tmp/gix-gnupg.XXXXXX)
+cp -R "$PWD/gnupg/." "\$home"
+gpg --homedir "\$home" --batch --no-tty "\$@"
+status=\$?
+gpgconf --homedir "\$home" --kill gpg-agent
+rm -rf "\$home"
+exit \$status
+EOF
+chmod +x gpg
+
+function commit() {
+  local message=${1:?need message}
+  shift
+  git "$@" commit -q --allow-empty -m "$message"
+}
+
+ssh=(-c gpg.format=ssh -c user.signingKey="$PWD/signing-key")
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  commit unsigned
+  git branch unsigned
+
+  commit "signed with ssh" "${ssh[@]}" -c commit.gpgSign=true
+  git branch ssh
+  git "${ssh[@]}" tag -s -m "signed with ssh" ssh-tag
+  git tag -a -m "unsigned" unsigned-tag
+
+  commit "signed with an untrusted key" -c gpg.format=ssh -c user.signingKey="$PWD/../untrusted-key" -c commit.gpgSign=true
+  git branch untrusted
+
+  commit "signed with gpg" -c gpg.program="$PWD/../gpg" -c user.signingKey=signer@example.com -c commit.gpgSign=true
+  git branch openpgp
+
+  git cat-file commit ssh | sed 's/^signed with ssh$/tampered/' | git hash-object -t commit -w --stdin > ../tampered-commit
+  git cat-file tag ssh-tag | sed 's/^signed with ssh$/tampered/' | git hash-object -t tag -w --stdin > ../tampered-tag
+)
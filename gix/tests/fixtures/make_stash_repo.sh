@@ -0,0 +1,24 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+git config user.name gitoxide
+git config user.email gitoxide@localhost
+
+echo '*.log' > .gitignore
+printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > lines
+echo a > a
+echo b > b
+mkdir dir
+echo c > dir/c
+echo "echo hello" > script
+git add .
+git commit -q -m "initial commit"
+
+git checkout -q -b other
+sed -i.bak 's/^2$/2 changed on other/' lines && rm lines.bak
+echo "a changed on other" > a
+git commit -q -am "change lines and a"
+
+git checkout -q main
@@ -0,0 +1,33 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit() {
+  local n=${1:?need number}
+  echo "content $n" > "file$n"
+  echo "nested $n" > dir/nested
+  git add .
+  git commit -q -m "commit $n"
+}
+
+git init -q server
+(cd server
+  git checkout -q -b main
+  mkdir dir
+  commit 1
+  commit 2
+  commit 3
+)
+
+git clone -q --no-local server client
+
+(cd server
+  commit 4
+  commit 5
+  git tag -a -m "annotated" annotated HEAD~1
+  git tag lightweight HEAD~3
+  git checkout -q -b other HEAD~3
+  echo other > other
+  git add other
+  git commit -q -m "other"
+  git checkout -q main
+)
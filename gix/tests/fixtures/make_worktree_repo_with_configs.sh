@@ -27,3 +27,16 @@ git init repo
   git config --worktree worktree.setting "set in wt-2"
   git config --worktree override.setting "override in wt-2"
 )
+
+git init moved-bare
+(cd moved-bare
+  git config extensions.worktreeConfig true
+  git config --unset core.bare
+  git config --worktree core.bare false
+)
+
+git init without-extension
+(cd without-extension
+  git commit --allow-empty -m initial
+  git worktree add ../without-extension-wt
+)
@@ -1,14 +1,37 @@
 mod util;
 use util::*;
 
+#[cfg(unix)]
+mod add;
+mod apply;
+mod blame;
+#[cfg(all(unix, feature = "blocking-network-client"))]
+mod bundle;
+#[cfg(unix)]
+mod checkout;
+#[cfg(unix)]
+mod cherry_pick;
 mod clone;
 mod commit;
 mod config;
+mod diff;
+#[cfg(unix)]
+mod gpg;
 mod head;
+#[cfg(unix)]
+mod hook;
 mod id;
 mod init;
+#[cfg(unix)]
+mod merge;
 mod object;
+#[cfg(all(unix, feature = "blocking-network-client"))]
+mod receive_pack;
 mod reference;
 mod remote;
 mod repository;
 mod revision;
+#[cfg(unix)]
+mod stash;
+#[cfg(all(unix, feature = "blocking-network-client"))]
+mod upload_pack;
@@ -3,6 +3,18 @@ mod util;
 #[cfg(not(feature = "regex"))]
 use util::*;
 
+#[cfg(all(not(feature = "regex"), unix))]
+mod add;
+#[cfg(not(feature = "regex"))]
+mod apply;
+#[cfg(not(feature = "regex"))]
+mod blame;
+#[cfg(all(not(feature = "regex"), unix, feature = "blocking-network-client"))]
+mod bundle;
+#[cfg(all(not(feature = "regex"), unix))]
+mod checkout;
+#[cfg(all(not(feature = "regex"), unix))]
+mod cherry_pick;
 #[cfg(not(feature = "regex"))]
 mod clone;
 #[cfg(not(feature = "regex"))]
@@ -10,13 +22,23 @@ mod commit;
 #[cfg(not(feature = "regex"))]
 mod config;
 #[cfg(not(feature = "regex"))]
+mod diff;
+#[cfg(all(not(feature = "regex"), unix))]
+mod gpg;
+#[cfg(not(feature = "regex"))]
 mod head;
+#[cfg(all(not(feature = "regex"), unix))]
+mod hook;
 #[cfg(not(feature = "regex"))]
 mod id;
 #[cfg(not(feature = "regex"))]
 mod init;
+#[cfg(all(not(feature = "regex"), unix))]
+mod merge;
 #[cfg(not(feature = "regex"))]
 mod object;
+#[cfg(all(not(feature = "regex"), unix, feature = "blocking-network-client"))]
+mod receive_pack;
 #[cfg(not(feature = "regex"))]
 mod reference;
 #[cfg(not(feature = "regex"))]
@@ -25,3 +47,7 @@ mod remote;
 mod repository;
 #[cfg(not(feature = "regex"))]
 mod revision;
+#[cfg(all(not(feature = "regex"), unix))]
+mod stash;
+#[cfg(all(not(feature = "regex"), unix, feature = "blocking-network-client"))]
+mod upload_pack;
@@ -0,0 +1,319 @@
+use std::path::{Path, PathBuf};
+
+use gix::{
+    bstr::ByteSlice,
+    gpg::{verify::Status, Format},
+};
+
+fn fixture() -> crate::Result<PathBuf> {
+    Ok(gix_testtools::scripted_fixture_read_only("make_signed_objects_repo.sh")?.canonicalize()?)
+}
+
+/// Open the repository in `fixture` with `config` applied, along with the configuration to use our own `gpg` and
+/// the allowed SSH signers.
+fn open(fixture: &Path, config: &[&str]) -> crate::Result<gix::Repository> {
+    open_opts(fixture, config, gix::open::Options::isolated())
+}
+
+fn open_opts(fixture: &Path, config: &[&str], opts: gix::open::Options) -> crate::Result<gix::Repository> {
+    let mut overrides = vec![
+        "user.name=gitoxide".to_string(),
+        "user.email=gitoxide@localhost".into(),
+        format!("gpg.program={}", fixture.join("gpg").display()),
+        format!(
+            "gpg.ssh.allowedSignersFile={}",
+            fixture.join("allowed-signers").display()
+        ),
+    ];
+    overrides.extend(config.iter().map(ToString::to_string));
+    Ok(gix::open_opts(fixture.join("repo"), opts.config_overrides(overrides))?)
+}
+
+/// Run `git verify-<kind>` on `id` and return `true` if it succeeds.
+fn git_verifies(fixture: &Path, kind: &str, id: impl std::fmt::Display) -> crate::Result<bool> {
+    Ok(std::process::Command::new("git")
+        .current_dir(fixture.join("repo"))
+        .arg("-c")
+        .arg(format!("gpg.program={}", fixture.join("gpg").display()))
+        .arg("-c")
+        .arg(format!(
+            "gpg.ssh.allowedSignersFile={}",
+            fixture.join("allowed-signers").display()
+        ))
+        .arg(format!("verify-{kind}"))
+        .arg(id.to_string())
+        .output()?
+        .status
+        .success())
+}
+
+fn commit<'repo>(repo: &'repo gix::Repository, spec: &str) -> crate::Result<gix::Commit<'repo>> {
+    Ok(repo.rev_parse_single(spec)?.object()?.try_into_commit()?)
+}
+
+fn tag<'repo>(repo: &'repo gix::Repository, spec: &str) -> crate::Result<gix::Tag<'repo>> {
+    Ok(repo.rev_parse_single(spec)?.object()?.try_into_tag()?)
+}
+
+fn id_in(fixture: &Path, file: &str) -> crate::Result<gix_hash::ObjectId> {
+    Ok(gix_hash::ObjectId::from_hex(std::fs::read(fixture.join(file))?.trim())?)
+}
+
+mod verify {
+    use gix::{bstr::ByteSlice, gpg::verify};
+
+    use super::{commit, fixture, id_in, open, tag, Format, Status};
+
+    #[test]
+    fn ssh_signatures_created_by_git() -> crate::Result {
+        let fixture = fixture()?;
+        let repo = open(&fixture, &[])?;
+
+        let outcome = commit(&repo, "ssh")?.verify_signature()?.expect("signed");
+        assert_eq!(outcome.format, Format::Ssh);
+        assert_eq!(outcome.status, Status::Good);
+        assert!(outcome.is_good());
+        assert_eq!(outcome.signer.as_ref().expect("known signer"), "signer@example.com");
+        assert_eq!(outcome.key, None, "SSH keys are identified by their fingerprint");
+        let fingerprint = outcome.fingerprint.expect("present");
+        assert!(fingerprint.starts_with(b"SHA256:"));
+
+        let outcome = tag(&repo, "ssh-tag")?.verify_signature()?.expect("signed");
+        assert_eq!(outcome.status, Status::Good);
+        assert_eq!(
+            outcome.fingerprint.expect("present"),
+            fingerprint,
+            "the same key signed both"
+        );
+
+        let outcome = commit(&repo, "untrusted")?.verify_signature()?.expect("signed");
+        assert_eq!(
+            outcome.status,
+            Status::UnknownKey,
+            "the signature is valid, but the key isn't an allowed signer"
+        );
+        assert_eq!(outcome.signer, None);
+        assert_ne!(outcome.fingerprint.expect("present"), fingerprint);
+
+        let outcome = repo
+            .find_object(id_in(&fixture, "tampered-commit")?)?
+            .into_commit()
+            .verify_signature()?
+            .expect("signed");
+        assert_eq!(outcome.status, Status::Bad);
+        assert!(!outcome.is_good());
+        let outcome = repo
+            .find_object(id_in(&fixture, "tampered-tag")?)?
+            .into_tag()
+            .verify_signature()?
+            .expect("signed");
+        assert_eq!(outcome.status, Status::Bad);
+
+        assert!(commit(&repo, "unsigned")?.verify_signature()?.is_none());
+        assert!(tag(&repo, "unsigned-tag")?.verify_signature()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn openpgp_signature_created_by_git() -> crate::Result {
+        let repo = open(&fixture()?, &[])?;
+        let outcome = commit(&repo, "openpgp")?.verify_signature()?.expect("signed");
+        assert_eq!(outcome.format, Format::OpenPgp);
+        assert_eq!(outcome.status, Status::Good);
+        assert_eq!(outcome.signer.as_ref().expect("present"), "Signer <signer@example.com>");
+        let key = outcome.key.expect("present");
+        assert_eq!(key.len(), 16, "long key ids are used");
+        let fingerprint = outcome.fingerprint.expect("present");
+        assert_eq!(fingerprint.len(), 40);
+        assert!(fingerprint.ends_with(&key));
+        Ok(())
+    }
+
+    #[test]
+    fn signature_and_signed_data() -> crate::Result {
+        let repo = open(&fixture()?, &[])?;
+        let commit = commit(&repo, "ssh")?;
+        let (signature, payload) = commit.signature()?.expect("signed");
+        assert!(signature.starts_with(b"-----BEGIN SSH SIGNATURE-----\n"));
+        assert!(signature.ends_with(b"-----END SSH SIGNATURE-----\n"));
+        assert_eq!(
+            payload.find("gpgsig"),
+            None,
+            "the signature isn't part of what's signed"
+        );
+        assert_eq!(
+            payload.len() + b"gpgsig ".len() + signature.len() + signature.lines().count() - 1,
+            commit.data.len(),
+            "only the signature header was removed, along with the space starting each of its continuation lines"
+        );
+
+        let tag = tag(&repo, "ssh-tag")?;
+        let (signature, payload) = tag.signature()?.expect("signed");
+        assert!(payload.ends_with(b"\n\nsigned with ssh\n"));
+        assert_eq!(Format::from_signature(signature), Some(Format::Ssh));
+        Ok(())
+    }
+
+    #[test]
+    fn ssh_signatures_need_allowed_signers() -> crate::Result {
+        let repo = open(&fixture()?, &["gpg.ssh.allowedSignersFile=does-not-exist"])?;
+        let err = commit(&repo, "ssh")?.verify_signature().unwrap_err();
+        assert!(matches!(err, verify::Error::MissingAllowedSignersFile));
+        assert_eq!(
+            err.to_string(),
+            "gpg.ssh.allowedSignersFile needs to be configured and exist to verify SSH signatures"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_signature_format() -> crate::Result {
+        let repo = open(&fixture()?, &[])?;
+        assert!(matches!(
+            repo.verify_signature(b"data", b"not a signature"),
+            Err(verify::Error::UnknownFormat)
+        ));
+        Ok(())
+    }
+}
+
+mod sign {
+    use gix::gpg::sign;
+    use gix_ref::transaction::PreviousValue;
+
+    use super::{fixture, git_verifies, open, open_opts, Format, Status};
+
+    /// Create a commit on top of `HEAD` and a tag pointing to it, both signed if `sign` is `true`.
+    fn commit_and_tag(repo: &gix::Repository, sign: bool) -> crate::Result<(gix::Id<'_>, gix::Id<'_>)> {
+        let head = repo.head_id()?;
+        let tree = head.object()?.into_commit().tree_id()?;
+        let committer = repo.committer().expect("configured")?;
+        let author = repo.author().expect("configured")?;
+        let message = "signed by gitoxide";
+        let commit = if sign {
+            repo.commit_as_signed(committer, author, "HEAD", message, tree, Some(head))?
+        } else {
+            repo.commit_as(committer, author, "HEAD", message, tree, Some(head))?
+        };
+        let (name, kind) = (format!("gix-{commit}"), gix_object::Kind::Commit);
+        let tag = if sign {
+            repo.tag_signed(
+                name,
+                commit,
+                kind,
+                Some(committer),
+                message,
+                PreviousValue::MustNotExist,
+            )?
+        } else {
+            repo.tag(
+                name,
+                commit,
+                kind,
+                Some(committer),
+                message,
+                PreviousValue::MustNotExist,
+            )?
+        }
+        .id();
+        Ok((commit, tag))
+    }
+
+    #[test]
+    fn commits_and_tags_with_ssh() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable("make_signed_objects_repo.sh")?;
+        let fixture = tmp.path().canonicalize()?;
+        let signing_key = format!("user.signingKey={}", fixture.join("signing-key").display());
+        let repo = open(&fixture, &["gpg.format=ssh", &signing_key])?;
+
+        let (commit, tag) = commit_and_tag(&repo, true)?;
+        assert!(git_verifies(&fixture, "commit", commit)?);
+        assert!(git_verifies(&fixture, "tag", tag)?);
+
+        let commit = commit.object()?.into_commit();
+        assert_eq!(commit.message_raw()?, "signed by gitoxide");
+        let outcome = commit.verify_signature()?.expect("signed");
+        assert_eq!(outcome.status, Status::Good);
+        assert_eq!(outcome.format, Format::Ssh);
+        let outcome = tag.object()?.into_tag().verify_signature()?.expect("signed");
+        assert_eq!(outcome.status, Status::Good);
+        assert_eq!(
+            tag.object()?.into_tag().decode()?.message,
+            "signed by gitoxide",
+            "the message is unaffected by the signature"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn commits_and_tags_with_openpgp() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable("make_signed_objects_repo.sh")?;
+        let fixture = tmp.path().canonicalize()?;
+        let repo = open(&fixture, &["user.signingKey=signer@example.com"])?;
+
+        let (commit, tag) = commit_and_tag(&repo, true)?;
+        assert!(git_verifies(&fixture, "commit", commit)?);
+        assert!(git_verifies(&fixture, "tag", tag)?);
+        let outcome = commit.object()?.into_commit().verify_signature()?.expect("signed");
+        assert_eq!(outcome.status, Status::Good);
+        assert_eq!(outcome.format, Format::OpenPgp);
+        Ok(())
+    }
+
+    #[test]
+    fn unsigned_unless_requested() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable("make_signed_objects_repo.sh")?;
+        let fixture = tmp.path().canonicalize()?;
+        let signing_key = format!("user.signingKey={}", fixture.join("signing-key").display());
+        let repo = open(
+            &fixture,
+            &[
+                "commit.gpgSign=true",
+                "tag.gpgSign=true",
+                "gpg.format=ssh",
+                &signing_key,
+            ],
+        )?;
+        let (commit, tag) = commit_and_tag(&repo, false)?;
+        assert!(
+            commit.object()?.into_commit().signature()?.is_none(),
+            "the configuration alone doesn't cause objects to be signed"
+        );
+        assert!(tag.object()?.into_tag().signature()?.is_none());
+
+        let (head, onto) = (repo.head_id()?.detach(), repo.rev_parse_single("HEAD~1")?.detach());
+        let options = gix::cherry_pick::Options {
+            allow_empty: true,
+            ..Default::default()
+        };
+        let picked = repo.cherry_pick(head, onto, options)?.commit.expect("no conflict");
+        assert!(repo.find_object(picked)?.into_commit().signature()?.is_none());
+
+        let options = gix::cherry_pick::Options { sign: true, ..options };
+        let picked = repo.cherry_pick(head, onto, options)?.commit.expect("no conflict");
+        assert!(git_verifies(&fixture, "commit", picked)?);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> crate::Result {
+        let fixture = fixture()?;
+        let repo = open(&fixture, &["gpg.format=ssh"])?;
+        let err = repo.sign(b"data").unwrap_err();
+        assert!(matches!(err, sign::Error::MissingSigningKey));
+
+        let repo = open_opts(
+            &fixture,
+            &["gpg.format=unknown"],
+            gix::open::Options::isolated().strict_config(true),
+        )?;
+        assert_eq!(
+            repo.sign(b"data").unwrap_err().to_string(),
+            "The key \"gpg.format=unknown\" was invalid"
+        );
+
+        let repo = open(&fixture, &["user.signingKey=unknown@example.com"])?;
+        assert!(matches!(repo.sign(b"data").unwrap_err(), sign::Error::Failed { .. }));
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+use gix::bstr::BString;
+
+use crate::util::repo_rw;
+
+fn log(repo: &gix::Repository) -> crate::Result<BString> {
+    Ok(std::fs::read(repo.git_dir().join("hooks.log"))
+        .or_else(|err| {
+            if err.kind() == std::io::ErrorKind::NotFound {
+                Ok(Vec::new())
+            } else {
+                Err(err)
+            }
+        })?
+        .into())
+}
+
+fn fail(repo: &gix::Repository, hook: &str) -> std::io::Result<()> {
+    std::fs::write(repo.git_dir().join(format!("fail-{hook}")), b"")
+}
+
+#[allow(clippy::result_large_err)]
+fn commit<'repo>(repo: &'repo gix::Repository, message: &str) -> Result<gix::Id<'repo>, gix::commit::Error> {
+    repo.commit_index(message)
+}
+
+mod commit {
+    use std::sync::{Arc, Mutex};
+
+    use gix::bstr::ByteSlice;
+
+    use super::{commit, fail, log, repo_rw};
+
+    #[test]
+    fn hooks_run_in_order_and_may_alter_the_message() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_hooks_repo.sh")?;
+        let previous = repo.head_id()?;
+        let id = commit(&repo, "message")?;
+
+        let commit = id.object()?.into_commit();
+        assert_eq!(
+            commit.message_raw()?,
+            "message\n\nReviewed-by: commit-msg\n",
+            "the message can be altered by hooks"
+        );
+        let author = commit.author()?;
+        let git_dir = repo.git_dir();
+        let message_path = git_dir.join("COMMIT_EDITMSG");
+        assert_eq!(
+            log(&repo)?,
+            format!(
+                "pre-commit \n\
+                 cwd={cwd} GIT_DIR={git_dir} GIT_INDEX_FILE={index} GIT_EDITOR=:\n\
+                 GIT_AUTHOR_NAME=gitoxide GIT_AUTHOR_EMAIL=gitoxide@localhost GIT_AUTHOR_DATE=@{time}\n\
+                 prepare-commit-msg {message} message\n\
+                 commit-msg {message}\n\
+                 reference-transaction prepared\n\
+                 {previous} {id} refs/heads/main\n\
+                 reference-transaction committed\n\
+                 {previous} {id} refs/heads/main\n\
+                 post-commit \n",
+                cwd = repo.work_dir().expect("non-bare").canonicalize()?.display(),
+                git_dir = git_dir.display(),
+                index = repo.index_path().display(),
+                time = author.time.to_bstring(),
+                message = message_path.display(),
+            ),
+            "pre-commit runs in the work tree with the same environment as in git"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn plain_commits_run_no_commit_hooks() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_hooks_repo.sh")?;
+        let previous = repo.head_id()?;
+        let tree = previous.object()?.into_commit().tree_id()?;
+        let id = repo.commit("HEAD", "message", tree, Some(previous))?;
+        assert_eq!(id.object()?.into_commit().message_raw()?, "message");
+        assert_eq!(
+            log(&repo)?,
+            format!(
+                "reference-transaction prepared\n\
+                 {previous} {id} refs/heads/main\n\
+                 reference-transaction committed\n\
+                 {previous} {id} refs/heads/main\n"
+            ),
+            "only the reference is edited, the tree may have nothing to do with the index"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn the_index_is_read_after_pre_commit_ran() -> crate::Result {
+        let (mut repo, tmp) = repo_rw("make_hooks_repo.sh")?;
+        repo.set_hook(
+            "pre-commit",
+            Some(Arc::new(|repo: &gix::Repository, _: &str, input: &gix::hook::Input| {
+                let work_dir = repo.work_dir().expect("non-bare");
+                std::fs::write(work_dir.join("file"), "changed by pre-commit\n")?;
+                let mut add = std::process::Command::new("git");
+                add.args(["add", "file"]).current_dir(work_dir);
+                for (key, value) in &input.env {
+                    add.env(key, value);
+                }
+                assert!(add.status()?.success());
+                Ok(())
+            })),
+        );
+        let id = commit(&repo, "message")?;
+        assert_eq!(
+            repo.rev_parse_single(format!("{id}:file").as_str())?
+                .object()?
+                .data
+                .as_bstr(),
+            "changed by pre-commit\n",
+            "changes staged by the hook are committed"
+        );
+        let status = std::process::Command::new("git")
+            .args(["status", "--porcelain", "--untracked-files=no"])
+            .current_dir(tmp.path())
+            .output()?;
+        assert_eq!(status.stdout.as_bstr(), "", "the commit matches the index");
+        Ok(())
+    }
+
+    #[test]
+    fn failing_hooks_may_prevent_the_commit() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_hooks_repo.sh")?;
+        let head = repo.head_id()?;
+        fail(&repo, "pre-commit")?;
+        let err = commit(&repo, "message").unwrap_err();
+        assert!(matches!(
+            err,
+            gix::commit::Error::Hook(gix::hook::Error::Failed { ref name, .. }) if name == "pre-commit"
+        ));
+        assert_eq!(repo.head_id()?, head, "nothing was committed");
+        assert!(log(&repo)?.lines().all(|line| !line.starts_with(b"prepare-commit-msg")));
+
+        std::fs::remove_file(repo.git_dir().join("fail-pre-commit"))?;
+        fail(&repo, "commit-msg")?;
+        assert!(commit(&repo, "message").is_err());
+        assert_eq!(repo.head_id()?, head, "nothing was committed");
+
+        std::fs::remove_file(repo.git_dir().join("fail-commit-msg"))?;
+        fail(&repo, "post-commit")?;
+        assert_ne!(
+            commit(&repo, "message")?,
+            head,
+            "post-commit can't affect the commit anymore"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn hooks_path_is_respected() -> crate::Result {
+        let (mut repo, _tmp) = repo_rw("make_hooks_repo.sh")?;
+        let work_dir = repo.work_dir().expect("non-bare").to_owned();
+        assert_eq!(repo.hooks_dir()?, repo.git_dir().join("hooks"));
+        assert_eq!(
+            repo.find_hook("pre-commit")?,
+            Some(repo.git_dir().join("hooks").join("pre-commit"))
+        );
+        assert_eq!(repo.find_hook("pre-push")?, None, "there is no such hook");
+
+        repo.config_snapshot_mut()
+            .set_raw_value("core", None, "hooksPath", "custom-hooks")?;
+        assert_eq!(
+            repo.hooks_dir()?,
+            work_dir.join("custom-hooks"),
+            "relative to the work tree"
+        );
+        assert_eq!(repo.find_hook("commit-msg")?, None, "hooks must be executable");
+
+        assert!(commit(&repo, "message").is_err());
+        assert_eq!(log(&repo)?, "custom pre-commit\n");
+        Ok(())
+    }
+
+    #[test]
+    fn functions_run_in_place_of_programs() -> crate::Result {
+        let (mut repo, _tmp) = repo_rw("make_hooks_repo.sh")?;
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let previous = repo.set_hook(
+            "pre-commit",
+            Some(Arc::new({
+                let calls = calls.clone();
+                move |repo: &gix::Repository, name: &str, input: &gix::hook::Input| {
+                    assert!(repo.work_dir().is_some());
+                    assert!(input.args.is_empty());
+                    assert!(input
+                        .env
+                        .iter()
+                        .any(|(key, value)| *key == "GIT_EDITOR" && value == ":"));
+                    calls.lock().unwrap().push(name.to_owned());
+                    Ok(())
+                }
+            })),
+        );
+        assert!(previous.is_none());
+
+        commit(&repo, "message")?;
+        assert_eq!(*calls.lock().unwrap(), ["pre-commit"]);
+        assert!(
+            log(&repo)?.lines().all(|line| !line.starts_with(b"pre-commit")),
+            "the program didn't run"
+        );
+
+        repo.set_hook(
+            "commit-msg",
+            Some(Arc::new(|_: &gix::Repository, _: &str, _: &gix::hook::Input| {
+                Err("rejected".into())
+            })),
+        );
+        let repo = repo.into_sync().to_thread_local();
+        let err = commit(&repo, "message").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The 'commit-msg' hook failed",
+            "registered hooks are kept when converting repositories"
+        );
+        Ok(())
+    }
+}
+
+mod reference_transaction {
+    use std::convert::TryInto;
+
+    use gix_ref::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        Target,
+    };
+
+    use super::{fail, log, repo_rw};
+
+    #[test]
+    fn failures_in_the_prepared_state_abort_the_transaction() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_hooks_repo.sh")?;
+        let id = repo.head_id()?;
+        fail(&repo, "reference-transaction")?;
+
+        let err = repo
+            .reference("refs/heads/new", id, PreviousValue::MustNotExist, "created")
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            gix::reference::edit::Error::Hook(gix::hook::Error::Failed { .. })
+        ));
+        assert!(repo.try_find_reference("refs/heads/new")?.is_none());
+        let null = repo.object_hash().null();
+        assert_eq!(
+            log(&repo)?,
+            format!(
+                "reference-transaction prepared\n\
+                 {null} {id} refs/heads/new\n\
+                 reference-transaction aborted\n\
+                 {null} {id} refs/heads/new\n"
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn previous_values_are_reported_whatever_was_expected() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_hooks_repo.sh")?;
+        let id = repo.head_id()?;
+        let tree = id.object()?.into_commit().tree_id()?;
+        repo.reference("refs/heads/main", tree, PreviousValue::Any, "moved")?;
+        repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::MustNotExist,
+                new: Target::Symbolic("refs/heads/main".try_into()?),
+            },
+            name: "refs/heads/symbolic".try_into()?,
+            deref: false,
+        })?;
+        repo.edit_reference(RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::Any,
+                log: RefLog::AndReference,
+            },
+            name: "refs/heads/symbolic".try_into()?,
+            deref: false,
+        })?;
+        let null = repo.object_hash().null();
+        assert_eq!(
+            log(&repo)?,
+            format!(
+                "reference-transaction prepared\n\
+                 {id} {tree} refs/heads/main\n\
+                 reference-transaction committed\n\
+                 {id} {tree} refs/heads/main\n\
+                 reference-transaction prepared\n\
+                 reference-transaction committed\n\
+                 reference-transaction prepared\n\
+                 {tree} {null} refs/heads/symbolic\n\
+                 reference-transaction committed\n\
+                 {tree} {null} refs/heads/symbolic\n"
+            ),
+            "symbolic references are resolved, and updates to them aren't reported"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deletions() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_hooks_repo.sh")?;
+        let id = repo.head_id()?;
+        repo.find_reference("main")?.delete()?;
+        let null = repo.object_hash().null();
+        assert_eq!(
+            log(&repo)?,
+            format!(
+                "reference-transaction prepared\n\
+                 {id} {null} refs/heads/main\n\
+                 reference-transaction committed\n\
+                 {id} {null} refs/heads/main\n"
+            )
+        );
+        Ok(())
+    }
+}
+
+#[cfg(feature = "blocking-network-client")]
+mod clone {
+    use std::sync::atomic::AtomicBool;
+
+    use gix::bstr::ByteSlice;
+
+    use super::log;
+
+    #[test]
+    fn post_checkout_runs_after_the_checkout() -> crate::Result {
+        let remote = gix_testtools::scripted_fixture_read_only("make_hooks_repo.sh")?.canonicalize()?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            remote.as_path(),
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            gix::open::Options::isolated().config_overrides([
+                "user.name=gitoxide".to_string(),
+                "user.email=gitoxide@localhost".into(),
+                format!("core.hooksPath={}", remote.join(".git").join("hooks").display()),
+            ]),
+        )?;
+        let (mut checkout, _out) = prepare.fetch_then_checkout(gix::progress::Discard, &AtomicBool::default())?;
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &AtomicBool::default())?;
+
+        let log = log(&repo)?;
+        let head = repo.head_id()?;
+        assert_eq!(
+            log.lines().next_back().expect("at least one line").as_bstr(),
+            format!("post-checkout {} {head} 1", repo.object_hash().null()),
+            "it runs last"
+        );
+        assert!(
+            log.lines().any(|line| line.ends_with(b" refs/heads/main")),
+            "the initial reference transaction is visible as well"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use gix::{
+    bstr::{BStr, ByteSlice},
+    merge::{conflict::Kind, Labels},
+    ObjectId,
+};
+
+use crate::{git, repo_rw};
+
+fn tree(repo: &gix::Repository, spec: &str) -> crate::Result<ObjectId> {
+    Ok(repo.rev_parse_single(format!("{spec}^{{tree}}").as_str())?.detach())
+}
+
+fn labels() -> Labels<'static> {
+    Labels {
+        ours: "ours".into(),
+        theirs: "theirs".into(),
+    }
+}
+
+/// The unmerged entries of `index` in the format of `git merge-tree --write-tree`.
+fn unmerged_entries(index: &gix_index::State) -> String {
+    index
+        .entries()
+        .iter()
+        .filter(|entry| entry.stage() != 0)
+        .map(|entry| {
+            format!(
+                "{:o} {} {}\t{}\n",
+                entry.mode.bits(),
+                entry.id,
+                entry.stage(),
+                entry.path(index)
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn clean_merge_is_the_same_as_with_git() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_merge_trees_repo.sh")?;
+    let outcome = repo.merge_trees(
+        tree(&repo, "main")?,
+        tree(&repo, "ours")?,
+        tree(&repo, "clean")?,
+        labels(),
+    )?;
+    assert!(outcome.conflicts.is_empty());
+    assert!(outcome.index.entries().iter().all(|entry| entry.stage() == 0));
+
+    let mut index = gix_index::File::from_state(outcome.index, repo.index_path());
+    let merged = repo.write_tree_from_index(&mut index)?;
+    assert_eq!(
+        merged.to_string(),
+        git(tmp.path(), &["merge-tree", "--write-tree", "ours", "clean"])?.trim(),
+        "both sides changed different lines of the same file, and the mode of a file changed on one side"
+    );
+    Ok(())
+}
+
+#[test]
+fn conflicts_are_the_same_as_with_git() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_merge_trees_repo.sh")?;
+    let outcome = repo.merge_trees(
+        tree(&repo, "main")?,
+        tree(&repo, "ours")?,
+        tree(&repo, "theirs")?,
+        labels(),
+    )?;
+    let conflicts: Vec<_> = outcome
+        .conflicts
+        .iter()
+        .map(|conflict| (conflict.path.as_bstr(), conflict.kind, conflict.merged_blob.is_some()))
+        .collect();
+    let path = |path: &'static str| -> &BStr { path.into() };
+    assert_eq!(
+        conflicts,
+        vec![
+            (path("added"), Kind::BothAdded, true),
+            (path("binary"), Kind::Content, false),
+            (path("conflict"), Kind::Content, true),
+            (path("modify-delete"), Kind::DeletedByThem, false),
+        ]
+    );
+
+    let git_merge = git(tmp.path(), &["merge-tree", "--write-tree", "ours", "theirs"])?;
+    let mut sections = git_merge.split("\n\n");
+    let mut git_lines = sections.next().expect("present").lines();
+    let git_tree = git_lines.next().expect("tree");
+    let git_unmerged: String = git_lines.map(|line| format!("{line}\n")).collect();
+    assert_eq!(unmerged_entries(&outcome.index), git_unmerged);
+
+    for entry in outcome.index.entries().iter().filter(|entry| entry.stage() == 0) {
+        let path = entry.path(&outcome.index);
+        assert_eq!(
+            entry.id.to_string(),
+            git(tmp.path(), &["rev-parse", &format!("{git_tree}:{path}")])?.trim(),
+            "{path} was merged like git does it"
+        );
+    }
+    for conflict in outcome
+        .conflicts
+        .iter()
+        .filter(|conflict| conflict.merged_blob.is_some())
+    {
+        let path = &conflict.path;
+        assert_eq!(
+            conflict.merged_blob.expect("set").to_string(),
+            git(tmp.path(), &["rev-parse", &format!("{git_tree}:{path}")])?.trim(),
+            "{path} has the same conflict markers as written by git"
+        );
+    }
+    Ok(())
+}
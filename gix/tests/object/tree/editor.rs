@@ -0,0 +1,142 @@
+use gix::object::tree::editor::Error;
+use gix_object::tree::EntryMode;
+
+use crate::util::basic_rw_repo;
+
+fn ls_tree(repo: &gix::Repository, tree: gix::Id<'_>) -> crate::Result<String> {
+    let out = std::process::Command::new("git")
+        .args(["ls-tree", "-r", "-t", "--format=%(objectmode) %(path)"])
+        .arg(tree.to_string())
+        .current_dir(repo.git_dir())
+        .output()?;
+    assert!(out.status.success(), "{out:?}");
+    Ok(String::from_utf8(out.stdout)?)
+}
+
+#[test]
+fn build_tree_from_scratch_in_git_order() -> crate::Result {
+    let (repo, _tmp) = basic_rw_repo()?;
+    let blob = repo.write_blob("content")?.detach();
+    let mut editor = repo.edit_tree(gix::ObjectId::empty_tree(repo.object_hash()))?;
+    editor
+        .upsert("a/b/c", EntryMode::Blob, blob)?
+        .upsert("a-b", EntryMode::BlobExecutable, blob)?
+        .upsert("a/b.c", EntryMode::Blob, blob)?
+        .upsert("link", EntryMode::Link, blob)?;
+    let tree = editor.write()?;
+    assert_eq!(
+        ls_tree(&repo, tree)?,
+        "100755 a-b\n040000 a\n100644 a/b.c\n040000 a/b\n100644 a/b/c\n120000 link\n",
+        "trees are sorted as if their name had a trailing slash"
+    );
+
+    let status = std::process::Command::new("git")
+        .args(["fsck", "--strict", "--no-dangling"])
+        .current_dir(repo.git_dir())
+        .status()?;
+    assert!(status.success(), "the written trees are valid to git");
+    Ok(())
+}
+
+#[test]
+fn only_changed_trees_are_written() -> crate::Result {
+    let (repo, _tmp) = basic_rw_repo()?;
+    let blob = repo.write_blob("content")?.detach();
+    let other_blob = repo.write_blob("other")?.detach();
+    let mut editor = repo.empty_tree().edit()?;
+    editor
+        .upsert("a/b/c", EntryMode::Blob, blob)?
+        .upsert("d/e", EntryMode::Blob, blob)?;
+    let base = editor.write()?.detach();
+
+    let subtree_id = |tree: gix::ObjectId, path: &str| -> crate::Result<gix::ObjectId> {
+        Ok(repo
+            .find_object(tree)?
+            .into_tree()
+            .lookup_entry_by_path(path)?
+            .expect("present")
+            .object_id())
+    };
+
+    let mut editor = repo.edit_tree(base)?;
+    let unchanged = editor.write()?.detach();
+    assert_eq!(unchanged, base, "without edits, the tree stays the same");
+
+    editor.upsert("a/b/c", EntryMode::Blob, other_blob)?;
+    let changed = editor.write()?.detach();
+    assert_ne!(changed, base);
+    assert_eq!(
+        subtree_id(changed, "d")?,
+        subtree_id(base, "d")?,
+        "untouched trees are kept"
+    );
+    assert_eq!(subtree_id(changed, "a/b/c")?, other_blob);
+    assert_eq!(
+        ls_tree(&repo, repo.edit_tree(changed)?.write()?)?,
+        "040000 a\n040000 a/b\n100644 a/b/c\n040000 d\n100644 d/e\n"
+    );
+
+    editor.remove("a/b/c")?.remove("does-not-exist")?.remove("d/e/f")?;
+    let removed = editor.write()?;
+    assert_eq!(
+        ls_tree(&repo, removed)?,
+        "040000 d\n100644 d/e\n",
+        "trees that become empty are removed, and removing what doesn't exist is fine"
+    );
+
+    editor.remove("d")?;
+    assert_eq!(
+        editor.write()?,
+        gix::ObjectId::empty_tree(repo.object_hash()),
+        "the root tree is written even if empty"
+    );
+    Ok(())
+}
+
+#[test]
+fn entries_in_the_way_are_replaced() -> crate::Result {
+    let (repo, _tmp) = basic_rw_repo()?;
+    let blob = repo.write_blob("content")?.detach();
+    let head_tree = repo.head_commit()?.tree_id()?;
+    let mut editor = repo.edit_tree(head_tree)?;
+    editor.upsert("this/is/now/a/tree", EntryMode::Blob, blob)?;
+    let tree = editor.write()?;
+    assert_eq!(
+        ls_tree(&repo, tree)?,
+        "040000 this\n040000 this/is\n040000 this/is/now\n040000 this/is/now/a\n100644 this/is/now/a/tree\n"
+    );
+
+    editor.upsert("this", EntryMode::Blob, blob)?;
+    assert_eq!(
+        ls_tree(&repo, editor.write()?)?,
+        "100644 this\n",
+        "and trees can become blobs"
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_paths_are_rejected() -> crate::Result {
+    let (repo, _tmp) = basic_rw_repo()?;
+    let blob = repo.write_blob("content")?.detach();
+    let mut editor = repo.empty_tree().edit()?;
+    for path in ["", "a//b", "/a", "a/", "a/./b", "../a", ".git/config", "a/.GIT"] {
+        assert!(
+            matches!(
+                editor.upsert(path, EntryMode::Blob, blob),
+                Err(Error::InvalidPath { .. })
+            ),
+            "{path:?}"
+        );
+        assert!(
+            matches!(editor.remove(path), Err(Error::InvalidPath { .. })),
+            "{path:?}"
+        );
+    }
+    assert_eq!(
+        editor.write()?,
+        gix::ObjectId::empty_tree(repo.object_hash()),
+        "nothing was changed"
+    );
+    Ok(())
+}
@@ -1 +1,2 @@
 mod diff;
+mod editor;
@@ -0,0 +1,421 @@
+use std::{path::Path, process::Output};
+
+use gix::{
+    bstr::BString,
+    protocol::transport::packetline::encode,
+    receive_pack::{Command, Hooks, Options, Outcome},
+};
+
+use crate::upload_pack::{assert_success, git, rev_parse, serve_git, url};
+
+fn fixture() -> crate::Result<gix_testtools::tempfile::TempDir> {
+    gix_testtools::scripted_fixture_writable("make_receive_pack_repo.sh")
+}
+
+/// Reject updates of `refs/heads/feature` or all updates, and remember what we were called with.
+#[derive(Default)]
+struct Recorder {
+    reject_feature: bool,
+    reject_all: bool,
+    push_options: Vec<BString>,
+    pre_receive: Vec<Command>,
+    new_objects_were_visible: bool,
+    post_receive: Vec<Command>,
+}
+
+impl Hooks for Recorder {
+    fn pre_receive(
+        &mut self,
+        repo: &gix::Repository,
+        commands: &[Command],
+        push_options: &[BString],
+    ) -> Result<(), BString> {
+        self.pre_receive = commands.to_vec();
+        self.push_options = push_options.to_vec();
+        self.new_objects_were_visible = commands
+            .iter()
+            .filter(|cmd| !cmd.is_delete())
+            .all(|cmd| repo.find_object(cmd.new).is_ok());
+        if self.reject_all {
+            Err("nothing may change".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn update(&mut self, _repo: &gix::Repository, command: &Command) -> Result<(), BString> {
+        if self.reject_feature && command.name.as_bstr() == "refs/heads/feature" {
+            Err("feature is frozen".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn post_receive(&mut self, _repo: &gix::Repository, commands: &[Command], _push_options: &[BString]) {
+        self.post_receive = commands.to_vec();
+    }
+}
+
+/// Run `git push <args>` from `client` to `server` with `hooks`.
+fn push(
+    client: &Path,
+    server: &Path,
+    version: u8,
+    args: &[&str],
+    hooks: Recorder,
+) -> crate::Result<(Output, Result<Outcome, gix::receive_pack::Error>, Recorder)> {
+    let url = url(server);
+    let mut all_args = vec!["push", "--porcelain"];
+    all_args.extend(args);
+    all_args.push(&url);
+    all_args.extend(["main", "feature", "annotated", ":to-delete"]);
+    let (output, (res, hooks)) = serve_git(
+        server,
+        version,
+        client,
+        &all_args,
+        "--receive-pack",
+        move |repo, input, output, version| {
+            let mut hooks = hooks;
+            let options = Options {
+                version,
+                ..Default::default()
+            };
+            (repo.receive_pack(input, output, options, &mut hooks), hooks)
+        },
+    )?;
+    Ok((output, res, hooks))
+}
+
+fn incoming_dirs(server: &Path) -> crate::Result<usize> {
+    Ok(std::fs::read_dir(server.join("objects"))?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("incoming-"))
+        .count())
+}
+
+fn report<'a>(outcome: &'a Outcome, name: &str) -> &'a gix::receive_pack::Report {
+    outcome
+        .reports
+        .iter()
+        .find(|report| report.command.name.as_bstr() == name)
+        .expect("all refs are reported")
+}
+
+fn rejection(outcome: &Outcome, name: &str) -> Option<String> {
+    report(outcome, name).rejection.as_ref().map(ToString::to_string)
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn push_creates_updates_and_deletes_refs() -> crate::Result {
+    for version in [0, 1, 2] {
+        let dir = fixture()?;
+        let (client, server) = (dir.path().join("client"), dir.path().join("server.git"));
+        let (output, res, hooks) = push(&client, &server, version, &[], Recorder::default())?;
+        let outcome = res?;
+        assert_success(&output);
+
+        for spec in ["main", "feature", "annotated"] {
+            assert_eq!(
+                rev_parse(&server, spec)?,
+                rev_parse(&client, spec)?,
+                "{spec}, v{version}"
+            );
+        }
+        let deleted = git(&server)
+            .args(["rev-parse", "--verify", "-q", "to-delete"])
+            .output()?;
+        assert!(!deleted.status.success(), "v{version}");
+        assert_success(&git(&server).args(["fsck", "--connectivity-only"]).output()?);
+        assert_eq!(incoming_dirs(&server)?, 0, "the quarantine is removed, v{version}");
+
+        assert_eq!(outcome.reports.len(), 4);
+        assert!(outcome.reports.iter().all(|report| report.rejection.is_none()));
+        assert!(report(&outcome, "refs/heads/feature").command.is_create());
+        assert!(report(&outcome, "refs/heads/to-delete").command.is_delete());
+        assert!(hooks.new_objects_were_visible, "hooks can see quarantined objects");
+        assert_eq!(hooks.pre_receive.len(), 4);
+        assert_eq!(hooks.post_receive.len(), 4);
+    }
+    Ok(())
+}
+
+#[test]
+fn hooks_can_reject_individual_updates() -> crate::Result {
+    let dir = fixture()?;
+    let (client, server) = (dir.path().join("client"), dir.path().join("server.git"));
+    let feature_before = rev_parse(&server, "main")?;
+    let (output, res, hooks) = push(
+        &client,
+        &server,
+        0,
+        &["--push-option=ci.skip"],
+        Recorder {
+            reject_feature: true,
+            ..Default::default()
+        },
+    )?;
+    let outcome = res?;
+    assert!(!output.status.success(), "one ref was rejected");
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("!\trefs/heads/feature:refs/heads/feature\t[remote rejected] (feature is frozen)"),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    assert_eq!(rev_parse(&server, "main")?, rev_parse(&client, "main")?);
+    let feature = git(&server).args(["rev-parse", "--verify", "-q", "feature"]).output()?;
+    assert!(!feature.status.success(), "feature wasn't created");
+    assert_ne!(rev_parse(&server, "main")?, feature_before);
+    assert_eq!(outcome.push_options, ["ci.skip"]);
+    assert_eq!(hooks.push_options, ["ci.skip"]);
+    assert_eq!(
+        rejection(&outcome, "refs/heads/feature").as_deref(),
+        Some("feature is frozen")
+    );
+    assert_eq!(hooks.post_receive.len(), 3, "all but the rejected ref were updated");
+    Ok(())
+}
+
+#[test]
+fn atomic_pushes_are_rejected_entirely() -> crate::Result {
+    let dir = fixture()?;
+    let (client, server) = (dir.path().join("client"), dir.path().join("server.git"));
+    let main_before = rev_parse(&server, "main")?;
+    let (output, res, hooks) = push(
+        &client,
+        &server,
+        0,
+        &["--atomic"],
+        Recorder {
+            reject_feature: true,
+            ..Default::default()
+        },
+    )?;
+    let outcome = res?;
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("atomic push failure") || stdout_contains(&output, "atomic push failure"));
+    assert_eq!(rev_parse(&server, "main")?, main_before);
+    assert_eq!(
+        rev_parse(&server, "to-delete")?,
+        main_before,
+        "nothing was deleted either"
+    );
+    assert!(outcome.reports.iter().all(|report| report.rejection.is_some()));
+    assert!(hooks.post_receive.is_empty());
+    assert_eq!(incoming_dirs(&server)?, 0);
+    Ok(())
+}
+
+fn stdout_contains(output: &Output, needle: &str) -> bool {
+    String::from_utf8_lossy(&output.stdout).contains(needle)
+}
+
+#[test]
+fn rejected_pushes_leave_no_objects_behind() -> crate::Result {
+    let dir = fixture()?;
+    let (client, server) = (dir.path().join("client"), dir.path().join("server.git"));
+    let packs_before = std::fs::read_dir(server.join("objects").join("pack"))?.count();
+    let (output, res, _hooks) = push(
+        &client,
+        &server,
+        0,
+        &[],
+        Recorder {
+            reject_all: true,
+            ..Default::default()
+        },
+    )?;
+    let outcome = res?;
+    assert!(!output.status.success());
+    assert!(stdout_contains(&output, "[remote rejected] (nothing may change)"));
+    assert!(outcome.reports.iter().all(|report| report.rejection.is_some()));
+    assert_eq!(
+        std::fs::read_dir(server.join("objects").join("pack"))?.count(),
+        packs_before,
+        "the received pack was discarded"
+    );
+    assert_eq!(incoming_dirs(&server)?, 0);
+    Ok(())
+}
+
+#[test]
+fn the_checked_out_branch_is_not_updated() -> crate::Result {
+    let dir = fixture()?;
+    let (client, server) = (dir.path().join("client"), dir.path().join("worktree"));
+    let url = url(&server);
+    let (output, res) = serve_git(
+        &server,
+        0,
+        &client,
+        &["push", "--porcelain", &url, "main"],
+        "--receive-pack",
+        |repo, input, output, version| {
+            let options = Options {
+                version,
+                ..Default::default()
+            };
+            repo.receive_pack(input, output, options, ())
+        },
+    )?;
+    let outcome = res?;
+    assert!(!output.status.success());
+    assert!(stdout_contains(
+        &output,
+        "[remote rejected] (branch is currently checked out)"
+    ));
+    assert_eq!(
+        rejection(&outcome, "refs/heads/main").as_deref(),
+        Some("branch is currently checked out")
+    );
+    Ok(())
+}
+
+/// Send `commands` along with the `pack` to `server` and return the status report.
+fn receive(server: &Path, commands: &[String], pack: &[u8]) -> crate::Result<(Outcome, Vec<u8>)> {
+    let mut input = Vec::new();
+    for (index, command) in commands.iter().enumerate() {
+        let line = if index == 0 {
+            format!("{command}\0report-status")
+        } else {
+            command.clone()
+        };
+        encode::text_to_write(line.as_bytes(), &mut input)?;
+    }
+    encode::flush_to_write(&mut input)?;
+    input.extend_from_slice(pack);
+
+    let repo = gix::open_opts(server, crate::restricted())?;
+    let mut out = Vec::new();
+    let options = Options {
+        stateless_rpc: true,
+        ..Default::default()
+    };
+    let outcome = repo.receive_pack(input.as_slice(), &mut out, options, ())?;
+    Ok((outcome, out))
+}
+
+#[test]
+fn updates_without_the_objects_they_need_are_rejected() -> crate::Result {
+    let dir = fixture()?;
+    let (client, server) = (dir.path().join("client"), dir.path().join("server.git"));
+    let missing = rev_parse(&client, "main")?;
+    let null = gix::hash::Kind::Sha1.null();
+    let (outcome, out) = receive(
+        &server,
+        &[format!("{null} {missing} refs/heads/new")],
+        &std::fs::read(dir.path().join("empty.pack"))?,
+    )?;
+    assert_eq!(
+        rejection(&outcome, "refs/heads/new").as_deref(),
+        Some("missing necessary objects")
+    );
+    assert_eq!(
+        out.as_slice(),
+        b"000eunpack ok\n0030ng refs/heads/new missing necessary objects\n0000".as_ref()
+    );
+    let new = git(&server).args(["rev-parse", "--verify", "-q", "new"]).output()?;
+    assert!(!new.status.success());
+    Ok(())
+}
+
+#[test]
+fn only_objects_reachable_from_refs_are_assumed_to_be_complete() -> crate::Result {
+    let dir = fixture()?;
+    let server = dir.path().join("server.git");
+    let incomplete = std::fs::read_to_string(dir.path().join("incomplete-commit"))?;
+    let reachable = rev_parse(&server, "main~1")?;
+    let null = gix::hash::Kind::Sha1.null();
+    let (outcome, _out) = receive(
+        &server,
+        &[
+            format!("{null} {} refs/heads/incomplete", incomplete.trim()),
+            format!("{null} {reachable} refs/heads/reachable"),
+        ],
+        &std::fs::read(dir.path().join("empty.pack"))?,
+    )?;
+    assert_eq!(
+        rejection(&outcome, "refs/heads/incomplete").as_deref(),
+        Some("missing necessary objects"),
+        "the commit exists, but it isn't reachable from a ref and its tree lacks a blob"
+    );
+    assert_eq!(
+        rejection(&outcome, "refs/heads/reachable"),
+        None,
+        "existing history that is reachable from refs is complete"
+    );
+    assert_eq!(rev_parse(&server, "reachable")?, reachable);
+    Ok(())
+}
+
+#[test]
+fn updates_of_refs_that_changed_in_the_meantime_fail() -> crate::Result {
+    let dir = fixture()?;
+    let server = dir.path().join("server.git");
+    let main = rev_parse(&server, "main")?;
+    let wrong = rev_parse(&server, "main~1")?;
+    let null = gix::hash::Kind::Sha1.null();
+    let (outcome, out) = receive(
+        &server,
+        &[
+            format!("{wrong} {null} refs/heads/main"),
+            format!("{main} {null} refs/heads/to-delete"),
+        ],
+        &[],
+    )?;
+    assert_eq!(
+        rejection(&outcome, "refs/heads/main").as_deref(),
+        Some("failed to update ref")
+    );
+    assert_eq!(
+        rejection(&outcome, "refs/heads/to-delete"),
+        None,
+        "updates are independent"
+    );
+    assert_eq!(
+        out.as_slice(),
+        b"000eunpack ok\n002cng refs/heads/main failed to update ref\n001cok refs/heads/to-delete\n0000".as_ref()
+    );
+    assert_eq!(rev_parse(&server, "main")?, main);
+    Ok(())
+}
+
+#[test]
+fn refs_are_advertised_like_git_does() -> crate::Result {
+    let dir = fixture()?;
+    let server = dir.path().join("server.git");
+    let repo = gix::open_opts(&server, crate::restricted())?;
+    let mut out = Vec::new();
+    repo.receive_pack(
+        std::io::empty(),
+        &mut out,
+        Options {
+            advertise_refs: true,
+            ..Default::default()
+        },
+        (),
+    )?;
+
+    let expected = git(&server).args(["receive-pack", "--advertise-refs", "."]).output()?;
+    assert_success(&expected);
+    let refs = |data: &[u8]| -> Vec<String> {
+        String::from_utf8_lossy(data)
+            .split('\n')
+            .map(|line| {
+                line.split('\0')
+                    .next()
+                    .expect("one part")
+                    .get(4..)
+                    .unwrap_or_default()
+                    .to_owned()
+            })
+            .collect()
+    };
+    assert_eq!(refs(&out), refs(&expected.stdout));
+    Ok(())
+}
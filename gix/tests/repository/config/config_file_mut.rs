@@ -0,0 +1,153 @@
+use std::path::Path;
+
+use gix::config::Source;
+
+use crate::repo_rw;
+
+fn git_config_list(dir: &Path, file: &Path) -> crate::Result<String> {
+    let out = std::process::Command::new("git")
+        .args(["config", "--list", "--file"])
+        .arg(file)
+        .current_dir(dir)
+        .output()?;
+    assert!(out.status.success(), "git can read the file");
+    Ok(String::from_utf8(out.stdout)?)
+}
+
+#[test]
+fn edit_local_config_and_keep_formatting() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_config_file_mut_repo.sh")?;
+    let mut config = repo.config_file_mut(Source::Local)?;
+    assert_eq!(config.path(), repo.common_dir().join("config"));
+    assert_eq!(config.meta().source, Source::Local);
+
+    config.set_raw_value("core", None, "ignoreCase", "true")?;
+    config
+        .section_mut("multi", None)?
+        .push("value".try_into()?, Some("4".into()));
+    config.raw_values_mut("multi", None, "value")?.delete(0);
+    config.section_mut("remote", Some("origin".into()))?.remove("fetch");
+    config.rename_section(
+        "old",
+        Some("name".into()),
+        "new",
+        Some(gix::bstr::BStr::new("name").into()),
+    )?;
+    config.remove_section("removed", None).expect("present");
+    let written = config.commit()?;
+
+    let on_disk = std::fs::read_to_string(repo.common_dir().join("config"))?;
+    assert_eq!(on_disk, written.to_string(), "what's returned is what was written");
+    assert!(
+        on_disk.contains(
+            "# a comment that is kept\n[remote \"origin\"]\n\turl = https://example.com/repo ; a trailing comment\n"
+        ),
+        "comments and unchanged values are preserved: {on_disk}"
+    );
+    assert!(!repo.common_dir().join("config.lock").exists());
+
+    let list = git_config_list(tmp.path(), &repo.common_dir().join("config"))?;
+    let list: Vec<_> = list
+        .lines()
+        .filter(|line| !line.starts_with("core.") || line.starts_with("core.ignorecase"))
+        .collect();
+    assert_eq!(
+        list,
+        [
+            "core.ignorecase=true",
+            "remote.origin.url=https://example.com/repo",
+            "multi.value=2",
+            "multi.value=3",
+            "multi.value=4",
+            "new.name.key=value"
+        ],
+        "git sees all changes, with multi-valued keys being added to the last section and removed selectively"
+    );
+
+    let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+    let snapshot = repo.config_snapshot();
+    assert_eq!(snapshot.boolean("core.ignoreCase"), Some(true));
+    assert_eq!(
+        snapshot
+            .plumbing()
+            .raw_values("multi", None, "value")?
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>(),
+        ["2", "3", "4"]
+    );
+    Ok(())
+}
+
+#[test]
+fn changes_are_discarded_if_not_committed() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_config_file_mut_repo.sh")?;
+    let path = repo.common_dir().join("config");
+    let before = std::fs::read(&path)?;
+    {
+        let mut config = repo.config_file_mut(Source::Local)?;
+        config.remove_section("multi", None);
+        assert!(
+            repo.config_file_mut(Source::Local).is_err(),
+            "the file is locked while it is edited"
+        );
+    }
+    assert_eq!(std::fs::read(&path)?, before);
+    assert!(!repo.common_dir().join("config.lock").exists());
+    Ok(())
+}
+
+#[test]
+fn arbitrary_files_are_created_if_they_dont_exist() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let path = tmp.path().join("new-config");
+    let mut config = gix::config::LockedFile::open(&path, Source::Api)?;
+    assert_eq!(config.sections().count(), 0, "it starts out empty");
+    config.set_raw_value("user", None, "name", "name")?;
+    config
+        .section_mut("user", None)?
+        .push("email".try_into()?, Some("email".into()));
+    config.commit()?;
+    assert_eq!(
+        git_config_list(tmp.path(), &path)?,
+        "user.name=name\nuser.email=email\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn sources_without_storage_location_cannot_be_edited() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_config_file_mut_repo.sh")?;
+    for source in [Source::Api, Source::Cli, Source::Env, Source::EnvOverride] {
+        assert!(matches!(
+            repo.config_file_mut(source),
+            Err(gix::config::config_file_mut::Error::NoStorageLocation(actual)) if actual == source
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn global_config_respects_the_environment() -> crate::Result {
+    let (_, tmp) = repo_rw("make_config_file_mut_repo.sh")?;
+    let global = tmp.path().join("global-config");
+    let _env = gix_testtools::Env::new().set("GIT_CONFIG_GLOBAL", global.to_str().expect("valid UTF-8"));
+    let repo = gix::open(tmp.path())?;
+    let mut config = repo.config_file_mut(Source::User)?;
+    assert_eq!(config.path(), global);
+    config
+        .section_mut_or_create_new("url", Some("https://example.com/".into()))?
+        .push("insteadOf".try_into()?, Some("ex:".into()));
+    config.commit()?;
+
+    let repo = gix::open(tmp.path())?;
+    assert_eq!(
+        repo.config_snapshot()
+            .string("url.https://example.com/.insteadOf")
+            .as_deref(),
+        Some("ex:".into()),
+        "like `git config --global --add`, the value is picked up by repositories"
+    );
+    Ok(())
+}
@@ -204,6 +204,68 @@ fn ssh_host_and_port_with_path_via_url_match() {
     baseline::agrees_with("ssh://host:21/path");
 }
 
+#[test]
+fn paths_match_as_prefix_on_component_boundary() {
+    baseline::agrees_with("https://example.com/org");
+    baseline::agrees_with("https://example.com/org/repo");
+    baseline::agrees_with("https://example.com/organisation");
+}
+
+#[test]
+fn partial_urls_match_the_fields_they_specify() {
+    baseline::agrees_with("https://example.org");
+    baseline::agrees_with("http://example.org/partial");
+    baseline::agrees_with("https://example.org/partial/repo");
+    baseline::agrees_with("https://example.org:8080");
+    baseline::agrees_with("ftp://example.net/path");
+}
+
+#[test]
+fn interactive_false_or_never_disables_prompts() -> crate::Result {
+    for (value, expect_disabled) in [("never", true), ("false", true), ("auto", false), ("true", false)] {
+        let repo = repo_with_overrides([format!("credential.interactive={value}")])?;
+        let (_, _, prompt_options) = repo
+            .config_snapshot()
+            .credential_helpers(gix::url::parse("https://example.com".into())?)?;
+        assert_eq!(
+            prompt_options.mode == gix_prompt::Mode::Disable,
+            expect_disabled,
+            "{value}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn sanitize_prompt_and_protect_protocol_are_configurable_per_url() -> crate::Result {
+    let repo = repo_with_overrides([
+        "credential.sanitizePrompt=false",
+        "credential.https://example.com.protectProtocol=false",
+    ])?;
+    let (cascade, _, _) = repo
+        .config_snapshot()
+        .credential_helpers(gix::url::parse("https://example.com/repo".into())?)?;
+    assert!(!cascade.sanitize_prompt);
+    assert!(!cascade.protect_protocol);
+
+    let (cascade, _, _) = repo
+        .config_snapshot()
+        .credential_helpers(gix::url::parse("https://example.org/repo".into())?)?;
+    assert!(!cascade.sanitize_prompt);
+    assert!(cascade.protect_protocol, "only the matching section applies");
+    Ok(())
+}
+
+fn repo_with_overrides(
+    overrides: impl IntoIterator<Item = impl Into<gix::bstr::BString>>,
+) -> crate::Result<gix::Repository> {
+    Ok(crate::util::repo_opts(
+        "make_basic_repo.sh",
+        gix::open::Options::isolated().config_overrides(overrides),
+    )?
+    .to_thread_local())
+}
+
 #[test]
 fn invalid_urls_are_rejected_early() {
     baseline::works_but_we_dont_parse_invalid_url("ssh://host");
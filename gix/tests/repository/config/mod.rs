@@ -1,6 +1,8 @@
+mod config_file_mut;
 mod config_snapshot;
 mod identity;
 mod remote;
+mod worktree_config_mut;
 
 #[cfg(feature = "blocking-network-client")]
 mod ssh_options {
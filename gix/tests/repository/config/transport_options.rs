@@ -54,6 +54,8 @@ mod http {
             connect_timeout,
             verbose,
             ssl_ca_info,
+            ssl_verify,
+            cookie_file,
             ssl_version,
             http_version,
             backend,
@@ -77,13 +79,35 @@ mod http {
         assert_eq!(no_proxy, None);
         assert!(!verbose, "verbose is disabled by default");
         assert_eq!(ssl_ca_info.as_deref(), Some(std::path::Path::new("./CA.pem")));
-        #[cfg(feature = "blocking-http-transport-reqwest")]
+        assert!(ssl_verify, "peers are verified by default");
+        assert_eq!(cookie_file, None);
+        #[cfg(all(
+            feature = "blocking-http-transport-reqwest",
+            not(any(
+                feature = "blocking-http-transport-reqwest-rust-tls",
+                feature = "blocking-http-transport-reqwest-native-tls"
+            ))
+        ))]
         {
             assert!(
                 backend.is_none(),
                 "backed is never set as it's backend specific, rather custom options typically"
             )
         }
+        #[cfg(any(
+            feature = "blocking-http-transport-reqwest-rust-tls",
+            feature = "blocking-http-transport-reqwest-native-tls"
+        ))]
+        {
+            let backend = backend
+                .as_ref()
+                .map(|b| b.lock().expect("not poisoned"))
+                .expect("backend is set to apply TLS options");
+            match backend.downcast_ref::<gix_protocol::transport::client::http::reqwest::Options>() {
+                Some(opts) => assert!(opts.configure_client.is_some()),
+                None => panic!("Correct backend option type is used"),
+            }
+        }
         #[cfg(feature = "blocking-http-transport-curl")]
         {
             let backend = backend
@@ -109,6 +133,52 @@ mod http {
         assert_eq!(http_version, Some(HttpVersion::V1_1));
     }
 
+    #[test]
+    fn url_specific_configuration_uses_the_best_match() {
+        let repo = repo("http-url-match");
+        let opts = http_options(&repo, None, "https://example.com/org/repo");
+        assert_eq!(
+            opts.extra_headers,
+            &["X-Global: 1", "X-Host: 1", "X-Org: 1"],
+            "values of all matching sections are used, unless a more specific one was seen before"
+        );
+        assert_eq!(opts.low_speed_limit_bytes_per_second, 10);
+        assert_eq!(opts.proxy.as_deref(), Some("http://global-proxy"));
+        assert_eq!(opts.ssl_ca_info.as_deref(), Some(std::path::Path::new("./host.pem")));
+        assert!(opts.ssl_verify);
+        assert_eq!(opts.cookie_file, None);
+
+        let opts = http_options(&repo, None, "https://example.com/organisation");
+        assert_eq!(
+            opts.extra_headers,
+            &["X-Global: 1", "X-Host: 1", "X-Organisation: 1"],
+            "paths match on component boundaries"
+        );
+        assert_eq!(
+            opts.low_speed_limit_bytes_per_second, 1,
+            "later values of sections without URL override earlier ones"
+        );
+
+        let opts = http_options(&repo, None, "https://internal.example.com/repo");
+        assert_eq!(opts.proxy.as_deref(), Some("http://internal-proxy"), "longer hosts win");
+        assert_eq!(opts.extra_headers, &["X-Global: 1"]);
+        assert_eq!(opts.ssl_ca_info.as_deref(), Some(std::path::Path::new("./global.pem")));
+
+        let opts = http_options(&repo, None, "https://a.example.com/repo");
+        assert_eq!(opts.proxy.as_deref(), Some("http://subdomain-proxy"));
+
+        let opts = http_options(&repo, None, "https://user@example.com/org");
+        assert!(!opts.ssl_verify, "the user has to match if specified");
+
+        let opts = http_options(&repo, None, "https://example.com:8443/repo");
+        assert_eq!(opts.cookie_file.as_deref(), Some(std::path::Path::new("./cookies.txt")));
+        assert_eq!(opts.ssl_ca_info.as_deref(), Some(std::path::Path::new("./global.pem")));
+
+        let opts = http_options(&repo, None, "http://example.com/repo");
+        assert!(!opts.ssl_verify, "the scheme has to match");
+        assert_eq!(opts.extra_headers, &["X-Global: 1"]);
+    }
+
     #[test]
     fn http_ssl_cainfo_suppressed_by_() {
         let repo = repo("http-disabled-cainfo");
@@ -159,6 +229,31 @@ mod http {
         assert!(opts.verbose);
     }
 
+    #[test]
+    #[cfg(any(
+        feature = "blocking-http-transport-reqwest-rust-tls",
+        feature = "blocking-http-transport-reqwest-native-tls"
+    ))]
+    fn reqwest_tls_is_only_configured_if_needed() {
+        let repo = repo("http-verbose");
+        let opts = http_options(&repo, None, "https://example.com/does/not/matter");
+        assert!(
+            opts.backend.is_none(),
+            "without TLS options there is nothing to configure, so the backend is left alone"
+        );
+
+        let repo = repo_opts("http-verbose", |opts| opts.config_overrides(["http.sslVerify=false"]));
+        let opts = http_options(&repo, None, "https://example.com/does/not/matter");
+        let backend = opts
+            .backend
+            .as_ref()
+            .map(|b| b.lock().expect("not poisoned"))
+            .expect("backend is set to disable verification");
+        assert!(backend
+            .downcast_ref::<gix_protocol::transport::client::http::reqwest::Options>()
+            .map_or(false, |opts| opts.configure_client.is_some()));
+    }
+
     #[test]
     fn http_no_proxy() {
         let repo = repo("http-no-proxy");
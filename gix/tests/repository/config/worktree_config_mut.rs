@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use gix::config::worktree_config_mut::Error;
+
+fn fixture() -> crate::Result<gix_testtools::tempfile::TempDir> {
+    gix_testtools::scripted_fixture_writable_with_args(
+        "make_worktree_repo_with_configs.sh",
+        None::<String>,
+        gix_testtools::Creation::ExecuteScript,
+    )
+}
+
+fn git_config(dir: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = std::process::Command::new("git")
+        .arg("config")
+        .args(args)
+        .current_dir(dir)
+        .output()?;
+    assert!(out.status.success(), "git config {args:?} failed");
+    Ok(String::from_utf8(out.stdout)?)
+}
+
+#[test]
+fn writes_config_worktree_if_the_extension_is_enabled() -> crate::Result {
+    let tmp = fixture()?;
+    let wt1 = gix::open(tmp.path().join("wt-1"))?;
+    let mut config = wt1.worktree_config_mut()?;
+    assert_eq!(config.meta().source, gix::config::Source::Worktree);
+    assert_eq!(config.path(), wt1.git_dir().join("config.worktree"));
+    assert!(config.lock_path().is_file(), "the file is locked while it is edited");
+    assert!(
+        matches!(
+            wt1.worktree_config_mut(),
+            Err(Error::Open(gix::config::locked_file::Error::Lock(_)))
+        ),
+        "no other writer can interfere"
+    );
+
+    config.set_raw_value("worktree", None, "setting", "changed in wt-1")?;
+    config
+        .section_mut_or_create_new("new", None)?
+        .push("key".try_into()?, Some("value".into()));
+    let written = config.commit()?;
+    assert_eq!(
+        written.to_string(),
+        "[worktree]\n\tsetting = changed in wt-1\n[new]\n\tkey = value\n",
+        "existing formatting is kept"
+    );
+    assert!(
+        !wt1.git_dir().join("config.worktree.lock").exists(),
+        "the lock is gone after committing"
+    );
+
+    assert_eq!(
+        wt1.config_snapshot()
+            .string("worktree.setting")
+            .expect("present")
+            .as_ref(),
+        "set in wt-1",
+        "the repository that was already open doesn't see the changes"
+    );
+    let wt1 = gix::open(tmp.path().join("wt-1"))?;
+    let snapshot = wt1.config_snapshot();
+    assert_eq!(
+        snapshot.string("worktree.setting").expect("present").as_ref(),
+        "changed in wt-1"
+    );
+    assert_eq!(snapshot.string("new.key").expect("present").as_ref(), "value");
+    assert_eq!(
+        git_config(&tmp.path().join("wt-1"), &["--worktree", "new.key"])?,
+        "value\n",
+        "git sees the change in the same place"
+    );
+
+    let wt2 = gix::open(tmp.path().join("wt-2"))?;
+    assert_eq!(
+        wt2.config_snapshot()
+            .string("worktree.setting")
+            .expect("present")
+            .as_ref(),
+        "set in wt-2",
+        "other worktrees are unaffected"
+    );
+    assert!(wt2.config_snapshot().string("new.key").is_none());
+    Ok(())
+}
+
+#[test]
+fn dropping_without_commit_leaves_the_file_unchanged() -> crate::Result {
+    let tmp = fixture()?;
+    let repo = gix::open(tmp.path().join("repo"))?;
+    let path = repo.git_dir().join("config.worktree");
+    let before = std::fs::read(&path)?;
+    {
+        let mut config = repo.worktree_config_mut()?;
+        config.set_raw_value("worktree", None, "setting", "discarded")?;
+    }
+    assert_eq!(std::fs::read(&path)?, before);
+    assert!(
+        repo.worktree_config_mut().is_ok(),
+        "the lock was released and can be acquired again"
+    );
+    Ok(())
+}
+
+#[test]
+fn writes_local_config_without_extension_only_if_there_are_no_linked_worktrees() -> crate::Result {
+    let tmp = fixture()?;
+    let repo = gix::open(tmp.path().join("without-extension"))?;
+    assert!(matches!(repo.worktree_config_mut(), Err(Error::WorktreeConfigDisabled)));
+
+    for proxy in repo.worktrees()? {
+        proxy.remove(true)?;
+    }
+    let mut config = repo.worktree_config_mut()?;
+    assert_eq!(config.meta().source, gix::config::Source::Local);
+    assert_eq!(config.path(), repo.common_dir().join("config"));
+    config.set_raw_value("core", None, "ignoreCase", "true")?;
+    config.commit()?;
+    assert_eq!(
+        git_config(&tmp.path().join("without-extension"), &["--local", "core.ignoreCase"])?,
+        "true\n"
+    );
+    Ok(())
+}
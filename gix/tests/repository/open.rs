@@ -496,4 +496,104 @@ mod worktree {
 
         Ok(())
     }
+
+    #[test]
+    fn core_bare_from_worktree_config() -> gix_testtools::Result {
+        let fixture_dir = gix_testtools::scripted_fixture_read_only("make_worktree_repo_with_configs.sh")?;
+        let repo = open(fixture_dir.join("moved-bare/.git"))?;
+        assert!(
+            !repo.is_bare(),
+            "`core.bare` was moved into `config.worktree`, which is read before deciding if the repository is bare"
+        );
+        assert_eq!(
+            repo.work_dir(),
+            Some(fixture_dir.join("moved-bare").as_path()),
+            "hence it has a worktree"
+        );
+        Ok(())
+    }
+}
+
+mod repository_format {
+    use gix::{config::Error as ConfigError, open::Error};
+
+    fn open(name: &str) -> Result<gix::Repository, Error> {
+        let dir = gix_testtools::scripted_fixture_read_only("make_repository_format_repos.sh").expect("valid fixture");
+        gix::open_opts(dir.join(name), gix::open::Options::isolated())
+    }
+
+    #[test]
+    fn known_extensions_are_parsed() -> crate::Result {
+        let repo = open("v1-with-known-extensions")?;
+        assert_eq!(
+            repo.extensions(),
+            &gix::Extensions {
+                repository_format_version: 1,
+                object_format: gix_hash::Kind::Sha1,
+                partial_clone: Some("origin".into()),
+                precious_objects: true,
+                worktree_config: true,
+            }
+        );
+
+        let repo = open("v0-with-unknown-extension")?;
+        assert_eq!(
+            repo.extensions(),
+            &gix::Extensions::default(),
+            "unknown extensions are ignored in version 0, just like git does"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_format_versions_are_refused() {
+        match open("v2") {
+            Err(Error::Config(err @ ConfigError::UnsupportedRepositoryFormatVersion { version: 2 })) => {
+                assert_eq!(err.to_string(), "Expected repository format version 0 or 1, found 2")
+            }
+            res => unreachable!("{res:?}"),
+        }
+    }
+
+    #[test]
+    fn unknown_extensions_are_refused_with_version_1() {
+        match open("v1-with-unknown-extensions") {
+            Err(Error::Config(err @ ConfigError::UnknownExtensions { .. })) => assert_eq!(
+                err.to_string(),
+                "Refusing to open repository with unknown extension(s) 'unknownThing', 'other'",
+                "names are listed once, in the order of appearance"
+            ),
+            res => unreachable!("{res:?}"),
+        }
+    }
+
+    #[test]
+    fn v1_only_extensions_are_refused_with_version_0() {
+        match open("v0-with-v1-only-extension") {
+            Err(Error::Config(err @ ConfigError::ExtensionsRequireFormatVersion1 { .. })) => assert_eq!(
+                err.to_string(),
+                "The repository format version is 0, but extension(s) 'refStorage' require version 1"
+            ),
+            res => unreachable!("{res:?}"),
+        }
+    }
+
+    #[test]
+    fn unsupported_values_of_known_extensions_are_refused() {
+        for (name, expected) in [
+            (
+                "v1-with-reftable",
+                "The key \"extensions.refStorage=reftable\" was invalid",
+            ),
+            (
+                "v1-with-sha256",
+                "The key \"extensions.objectFormat=sha256\" was invalid",
+            ),
+        ] {
+            match open(name) {
+                Err(Error::Config(err @ ConfigError::ConfigTypedString(_))) => assert_eq!(err.to_string(), expected),
+                res => unreachable!("{res:?}"),
+            }
+        }
+    }
 }
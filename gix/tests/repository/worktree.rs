@@ -301,3 +301,354 @@ fn run_assertions(main_repo: gix::Repository, should_be_bare: bool) {
         );
     }
 }
+
+mod index {
+    use gix_index::entry::{Flags, Mode};
+    use gix_object::tree::EntryMode;
+
+    #[test]
+    fn from_tree_and_back() -> crate::Result {
+        let (repo, _tmp) = crate::util::basic_rw_repo()?;
+        let tree = repo.head_commit()?.tree_id()?;
+        let mut index = repo.index_from_tree(&tree)?;
+        assert_eq!(index.path(), repo.index_path());
+        assert_eq!(repo.write_tree_from_index(&mut index)?, tree, "nothing changed");
+
+        let blob = repo.write_blob("new")?.detach();
+        index.dangerously_push_entry(Default::default(), blob, Flags::empty(), Mode::FILE, "new/file".into());
+        index.sort_entries();
+        index.remove_tree();
+        let expected = repo
+            .edit_tree(tree)?
+            .upsert("new/file", EntryMode::Blob, blob)?
+            .write()?;
+        assert_eq!(repo.write_tree_from_index(&mut index)?, expected);
+        Ok(())
+    }
+}
+
+mod lifecycle {
+    use std::path::Path;
+
+    use gix::{
+        bstr::BString,
+        worktree::{add, prune, repair},
+    };
+
+    use crate::{fixture_rw_executed, git_ok};
+
+    /// The repository with the main worktree, which is next to the directory of its linked worktrees.
+    fn main_repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+        let dir = fixture_rw_executed("make_worktree_repo.sh")?;
+        let repo = gix::open_opts(dir.path().join("repo"), crate::restricted())?;
+        Ok((repo, dir))
+    }
+
+    fn proxy<'repo>(repo: &'repo gix::Repository, id: &str) -> crate::Result<gix::worktree::Proxy<'repo>> {
+        Ok(repo
+            .worktrees()?
+            .into_iter()
+            .find(|proxy| proxy.id() == id)
+            .expect("worktree exists"))
+    }
+
+    fn ids(repo: &gix::Repository) -> crate::Result<Vec<BString>> {
+        Ok(repo.worktrees()?.iter().map(|proxy| proxy.id().to_owned()).collect())
+    }
+
+    #[test]
+    fn add_creates_a_branch_named_after_the_worktree_by_default() -> crate::Result {
+        let (repo, tmp) = main_repo_rw()?;
+        let path = tmp.path().join("new");
+        let worktree = repo.add_worktree(&path, Default::default())?;
+        assert_eq!(worktree.head_name()?.expect("attached").as_bstr(), "refs/heads/new");
+        assert_eq!(worktree.head_id()?, repo.head_id()?);
+        assert_eq!(worktree.work_dir(), Some(path.canonicalize()?.as_path()));
+        assert_eq!(
+            git_ok(&path, &["status", "--porcelain"])?,
+            "",
+            "index and files are checked out like git would"
+        );
+        assert_eq!(git_ok(&path, &["rev-parse", "--abbrev-ref", "HEAD"])?, "new\n");
+        assert_eq!(
+            git_ok(&path, &["rev-parse", "--git-common-dir"])?.trim(),
+            repo.common_dir().canonicalize()?.display().to_string()
+        );
+        let new = proxy(&repo, "new")?;
+        assert!(!new.is_locked(), "the lock is only held while initializing");
+        assert_eq!(new.base()?, path.canonicalize()?);
+        Ok(())
+    }
+
+    #[test]
+    fn add_detached_branches_and_locked() -> crate::Result {
+        let (repo, tmp) = main_repo_rw()?;
+        let previous = repo.rev_parse_single("HEAD~1")?;
+        let worktree = repo.add_worktree(
+            tmp.path().join("other/wt-a"),
+            add::Options {
+                commitish: Some("HEAD~1".into()),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(worktree.head_name()?, None, "commits are checked out detached");
+        assert_eq!(worktree.head_id()?, previous);
+        assert!(
+            ids(&repo)?.contains(&"wt-a2".into()),
+            "ids are made unique: {:?}",
+            ids(&repo)?
+        );
+
+        let err = repo
+            .add_worktree(
+                tmp.path().join("main"),
+                add::Options {
+                    commitish: Some("main".into()),
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+        assert!(matches!(err, add::Error::BranchCheckedOut { ref name, .. } if name == "main"));
+        assert!(!tmp.path().join("main").exists(), "nothing was created");
+
+        let worktree = repo.add_worktree(
+            tmp.path().join("main"),
+            add::Options {
+                commitish: Some("main".into()),
+                detach: true,
+                lock_reason: Some("on a stick".into()),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(worktree.head_name()?, None);
+        let main = proxy(&repo, "main")?;
+        assert_eq!(main.lock_reason(), Some("on a stick".into()));
+
+        let worktree = repo.add_worktree(
+            tmp.path().join("feature"),
+            add::Options {
+                new_branch: Some("topic".into()),
+                commitish: Some("HEAD~1".into()),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(worktree.head_name()?.expect("attached").as_bstr(), "refs/heads/topic");
+        assert_eq!(repo.find_reference("topic")?.id(), previous);
+
+        assert!(matches!(
+            repo.add_worktree(tmp.path().join("wt-a"), Default::default()),
+            Err(add::Error::DestinationExists { .. })
+        ));
+        assert!(matches!(
+            repo.add_worktree(tmp.path().join("wt-deleted"), Default::default()),
+            Err(add::Error::AlreadyRegistered { .. })
+        ));
+        let worktree = repo.add_worktree(
+            tmp.path().join("wt-deleted"),
+            add::Options {
+                force: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            worktree.head_name()?.expect("attached").as_bstr(),
+            "refs/heads/wt-deleted",
+            "the existing branch is checked out"
+        );
+        assert_eq!(
+            ids(&repo)?.iter().filter(|id| *id == "wt-deleted").count(),
+            1,
+            "the registration is reused"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lock_and_unlock() -> crate::Result {
+        let (repo, _tmp) = main_repo_rw()?;
+        let wt = proxy(&repo, "wt-b")?;
+        wt.lock(Some("reason".into()))?;
+        assert_eq!(wt.lock_reason(), Some("reason".into()));
+        assert!(matches!(
+            wt.lock(None),
+            Err(gix::worktree::proxy::lock::Error::AlreadyLocked { .. })
+        ));
+        wt.unlock()?;
+        assert!(!wt.is_locked());
+        assert!(matches!(
+            wt.unlock(),
+            Err(gix::worktree::proxy::lock::Error::NotLocked { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn move_to() -> crate::Result {
+        let (repo, tmp) = main_repo_rw()?;
+        let wt = proxy(&repo, "wt-b")?;
+        let destination = wt.move_to(tmp.path().join("moved"), false)?;
+        assert_eq!(destination, tmp.path().canonicalize()?.join("moved"));
+        assert_eq!(wt.base()?, destination);
+        assert_eq!(git_ok(&destination, &["status", "--porcelain"])?, "");
+
+        let destination = wt.move_to(tmp.path().join("wt-a"), false)?;
+        assert_eq!(
+            destination,
+            tmp.path().canonicalize()?.join("wt-a").join("moved"),
+            "existing directories receive the worktree"
+        );
+        assert!(matches!(
+            wt.move_to(tmp.path().join("wt-a"), false),
+            Err(gix::worktree::proxy::move_to::Error::DestinationExists { .. })
+        ));
+
+        let locked = proxy(&repo, "wt-c-locked")?;
+        assert!(matches!(
+            locked.move_to(tmp.path().join("elsewhere"), false),
+            Err(gix::worktree::proxy::move_to::Error::Locked { .. })
+        ));
+        locked.move_to(tmp.path().join("elsewhere"), true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn remove() -> crate::Result {
+        let (repo, tmp) = main_repo_rw()?;
+        let base = tmp.path().join("wt-b");
+        std::fs::write(base.join("untracked"), "")?;
+        assert!(matches!(
+            proxy(&repo, "wt-b")?.remove(false),
+            Err(gix::worktree::proxy::remove::Error::Dirty { .. })
+        ));
+        std::fs::remove_file(base.join("untracked"))?;
+        std::fs::write(base.join("a"), "changed")?;
+        assert!(matches!(
+            proxy(&repo, "wt-b")?.remove(false),
+            Err(gix::worktree::proxy::remove::Error::Dirty { .. })
+        ));
+        git_ok(&base, &["add", "a"])?;
+        assert!(
+            matches!(
+                proxy(&repo, "wt-b")?.remove(false),
+                Err(gix::worktree::proxy::remove::Error::Dirty { .. })
+            ),
+            "staged changes count as well"
+        );
+        git_ok(&base, &["reset", "--hard"])?;
+        std::fs::write(base.join("ignored"), "")?;
+        std::fs::write(repo.common_dir().join("info/exclude"), "ignored\n")?;
+        proxy(&repo, "wt-b")?.remove(false)?;
+        assert!(!base.exists());
+        assert!(!ids(&repo)?.contains(&"wt-b".into()));
+
+        let base = tmp.path().join("prev/wt-a");
+        std::fs::write(base.join("a"), "changed")?;
+        proxy(&repo, "wt-a1")?.remove(true)?;
+        assert!(!base.exists(), "dirty worktrees are removed if forced");
+
+        assert!(matches!(
+            proxy(&repo, "wt-c-locked")?.remove(false),
+            Err(gix::worktree::proxy::remove::Error::Locked { .. })
+        ));
+        proxy(&repo, "wt-deleted")?.remove(false)?;
+        assert!(
+            !ids(&repo)?.contains(&"wt-deleted".into()),
+            "missing worktrees are removed as well"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn prune() -> crate::Result {
+        let (mut repo, _tmp) = main_repo_rw()?;
+        let deleted = proxy(&repo, "wt-deleted")?;
+        assert_eq!(deleted.prune_reason(None), None, "stale worktrees never expire");
+        assert_eq!(
+            deleted.prune_reason(Some(std::time::SystemTime::now())),
+            Some(prune::Reason::GitDirFilePointsToNonExistentLocation)
+        );
+        assert_eq!(
+            proxy(&repo, "wt-b")?.prune_reason(Some(std::time::SystemTime::now())),
+            None
+        );
+
+        assert_eq!(
+            repo.prune_worktrees(Default::default())?,
+            prune::Outcome::default(),
+            "worktrees are pruned only after 3 months by default"
+        );
+        let expected = prune::Outcome {
+            pruned: vec![(
+                "wt-deleted".into(),
+                prune::Reason::GitDirFilePointsToNonExistentLocation,
+            )],
+        };
+        let now = prune::Options {
+            dry_run: true,
+            expire: Some(std::time::SystemTime::now()),
+        };
+        assert_eq!(repo.prune_worktrees(now)?, expected);
+        assert!(ids(&repo)?.contains(&"wt-deleted".into()), "nothing was removed");
+
+        let locked = proxy(&repo, "wt-c-locked")?;
+        std::fs::remove_dir_all(locked.base()?)?;
+        std::fs::create_dir(repo.common_dir().join("worktrees/empty"))?;
+        std::fs::write(repo.common_dir().join("worktrees/file"), "")?;
+        repo.config_snapshot_mut()
+            .set_raw_value("gc", None, "worktreePruneExpire", "now")?;
+        assert_eq!(
+            repo.prune_worktrees(Default::default())?,
+            prune::Outcome {
+                pruned: vec![
+                    ("empty".into(), prune::Reason::GitDirFileMissing),
+                    ("file".into(), prune::Reason::NotADirectory),
+                    (
+                        "wt-deleted".into(),
+                        prune::Reason::GitDirFilePointsToNonExistentLocation
+                    ),
+                ]
+            },
+            "locked worktrees are never pruned"
+        );
+        assert_eq!(
+            ids(&repo)?,
+            ["nested-wt-b", "wt-a", "wt-a1", "wt-b", "wt-c-locked"]
+                .map(BString::from)
+                .to_vec()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn repair() -> crate::Result {
+        let (repo, tmp) = main_repo_rw()?;
+        assert_eq!(repo.repair_worktrees(None::<&Path>)?, repair::Outcome::default());
+
+        let base = tmp.path().join("wt-b");
+        std::fs::write(base.join(".git"), "gitdir: /somewhere/else\n")?;
+        assert_eq!(
+            repo.repair_worktrees(None::<&Path>)?,
+            repair::Outcome {
+                repaired: vec![("wt-b".into(), repair::Repaired::GitFile)]
+            }
+        );
+        assert_eq!(git_ok(&base, &["status", "--porcelain"])?, "");
+
+        let moved = tmp.path().join("moved");
+        std::fs::rename(&base, &moved)?;
+        assert_eq!(
+            repo.repair_worktrees(Some(&moved))?,
+            repair::Outcome {
+                repaired: vec![("wt-b".into(), repair::Repaired::GitDir)]
+            }
+        );
+        assert_eq!(proxy(&repo, "wt-b")?.base()?, moved.canonicalize()?);
+        assert_eq!(git_ok(&moved, &["status", "--porcelain"])?, "");
+
+        assert!(matches!(
+            repo.repair_worktrees(Some(repo.work_dir().expect("non-bare"))),
+            Err(repair::Error::ForeignWorktree { .. })
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,387 @@
+use std::{os::unix::fs::PermissionsExt, path::Path};
+
+use gix::{
+    bstr::ByteSlice,
+    stash::{apply, push, Error},
+};
+
+use crate::{git, git_ok, repo_rw};
+
+/// The index, status and all files of the working tree of the repository at `dir`.
+fn state(dir: &Path) -> crate::Result<String> {
+    let mut out = git(dir, &["ls-files", "--stage"])?;
+    out += &git(dir, &["status", "--porcelain", "--untracked-files=all", "--ignored"])?;
+    out += &files(dir, dir)?;
+    Ok(out)
+}
+
+/// The trees of all commits of the most recent stash entry, along with its message and the messages of its reflog.
+fn stash_state(dir: &Path) -> crate::Result<String> {
+    let mut out = git(dir, &["log", "-g", "--format=%gs", "refs/stash"])?;
+    out += &git(dir, &["log", "-1", "--format=%B", "refs/stash"])?;
+    out += &git(dir, &["log", "-1", "--format=%P", "refs/stash"])?
+        .split_whitespace()
+        .count()
+        .to_string();
+    out += "\n";
+    out += &git(
+        dir,
+        &[
+            "rev-parse",
+            "refs/stash^{tree}",
+            "refs/stash^2^{tree}",
+            "refs/stash^1",
+            "refs/stash^2^1",
+        ],
+    )?;
+    out += &git(dir, &["log", "-1", "--format=%B", "refs/stash^2"])?;
+    out += &git(dir, &["ls-tree", "-r", "refs/stash^3"])?;
+    Ok(out)
+}
+
+/// List all files below `dir` with their mode and content.
+fn files(root: &Path, dir: &Path) -> crate::Result<String> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    let mut out = String::new();
+    for entry in entries {
+        let path = entry.path();
+        let rela_path = path.strip_prefix(root)?.display().to_string();
+        let meta = path.symlink_metadata()?;
+        if meta.is_dir() {
+            if entry.file_name() != ".git" {
+                out += &files(root, &path)?;
+            }
+        } else {
+            out += &format!(
+                "{rela_path} {:o} {:?}\n",
+                meta.permissions().mode() & 0o111,
+                std::fs::read_to_string(&path)?
+            );
+        }
+    }
+    Ok(out)
+}
+
+/// Change a file in the index and in the working tree, a file only in the working tree, and delete and add files.
+fn change(dir: &Path) -> crate::Result {
+    std::fs::write(dir.join("a"), "a staged\n")?;
+    git_ok(dir, &["add", "a"])?;
+    std::fs::write(dir.join("a"), "a staged and changed\n")?;
+    std::fs::write(dir.join("lines"), "1\n2 stashed\n3\n4\n5\n6\n7\n8\n9\n")?;
+    std::fs::write(dir.join("added"), "added\n")?;
+    git_ok(dir, &["add", "added"])?;
+    std::fs::remove_file(dir.join("dir/c"))?;
+    std::fs::set_permissions(dir.join("script"), std::fs::Permissions::from_mode(0o755))?;
+    std::fs::create_dir(dir.join("untracked-dir"))?;
+    std::fs::write(dir.join("untracked-dir/untracked"), "untracked\n")?;
+    std::fs::write(dir.join("ignored.log"), "ignored\n")?;
+    Ok(())
+}
+
+/// Apply `change` to two fresh fixtures, and stash the changes with `options` and `git stash push <git_args>` respectively
+/// to assert both leave the same state behind. Return the repository which used `gitoxide` along with the one using `git`.
+fn assert_push_same_as_git(
+    options: push::Options,
+    git_args: &[&str],
+) -> crate::Result<(
+    (gix::Repository, gix_testtools::tempfile::TempDir),
+    gix_testtools::tempfile::TempDir,
+)> {
+    let (repo, tmp) = repo_rw("make_stash_repo.sh")?;
+    change(tmp.path())?;
+    let id = repo.stash_push(options.clone())?.expect("there are changes to stash");
+
+    let (_, git_tmp) = repo_rw("make_stash_repo.sh")?;
+    change(git_tmp.path())?;
+    let mut args = vec!["stash", "push", "-q"];
+    args.extend_from_slice(git_args);
+    git_ok(git_tmp.path(), &args)?;
+
+    assert_eq!(git(tmp.path(), &["rev-parse", "refs/stash"])?.trim(), id.to_string());
+    assert_eq!(
+        state(tmp.path())?,
+        state(git_tmp.path())?,
+        "stashing with {options:?} leaves the same index and working tree behind"
+    );
+    assert_eq!(
+        git(tmp.path(), &["diff-files", "--name-only"])?,
+        "",
+        "the stat information of all entries is up to date"
+    );
+    assert_eq!(
+        stash_state(tmp.path())?,
+        stash_state(git_tmp.path())?,
+        "the stash entry is the same as the one git creates with {options:?}"
+    );
+    Ok(((repo, tmp), git_tmp))
+}
+
+#[test]
+fn push_is_the_same_as_with_git() -> crate::Result {
+    assert_push_same_as_git(push::Options::default(), &[])?;
+    assert_push_same_as_git(
+        push::Options {
+            message: Some("custom message".into()),
+            keep_index: true,
+            ..Default::default()
+        },
+        &["--keep-index", "-m", "custom message"],
+    )?;
+    assert_push_same_as_git(
+        push::Options {
+            untracked: push::Untracked::Include,
+            ..Default::default()
+        },
+        &["--include-untracked"],
+    )?;
+    assert_push_same_as_git(
+        push::Options {
+            untracked: push::Untracked::IncludeIgnored,
+            ..Default::default()
+        },
+        &["--all"],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn push_without_changes_or_on_detached_head() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_stash_repo.sh")?;
+    std::fs::write(tmp.path().join("untracked"), "untracked\n")?;
+    assert_eq!(
+        repo.stash_push(Default::default())?,
+        None,
+        "untracked files aren't stashed by default"
+    );
+    assert!(repo.try_find_reference("refs/stash")?.is_none());
+
+    git_ok(tmp.path(), &["checkout", "-q", "--detach", "other"])?;
+    std::fs::write(tmp.path().join("b"), "b changed\n")?;
+    repo.stash_push(Default::default())?.expect("changes");
+    let entries = repo.stash_list()?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].message, "WIP on (no branch): 72a2f3b change lines and a");
+    assert_eq!(entries[0].signature.name, "gitoxide");
+    assert_eq!(
+        git(tmp.path(), &["stash", "list", "--format=%gs"])?,
+        "WIP on (no branch): 72a2f3b change lines and a\n"
+    );
+    Ok(())
+}
+
+/// Stash the changes with `git`, and apply them with `options` and `git stash apply <git_args>` after running `prepare`
+/// to assert both leave the same state behind.
+fn assert_apply_same_as_git(
+    git_push_args: &[&str],
+    prepare: impl Fn(&Path) -> crate::Result,
+    options: apply::Options,
+    git_args: &[&str],
+) -> crate::Result<apply::Outcome> {
+    let (repo, tmp) = repo_rw("make_stash_repo.sh")?;
+    change(tmp.path())?;
+    let mut args = vec!["stash", "push", "-q"];
+    args.extend_from_slice(git_push_args);
+    git_ok(tmp.path(), &args)?;
+    let (_, git_tmp) = repo_rw("make_stash_repo.sh")?;
+    change(git_tmp.path())?;
+    git_ok(git_tmp.path(), &args)?;
+
+    prepare(tmp.path())?;
+    let outcome = repo.stash_apply(0, options)?;
+    prepare(git_tmp.path())?;
+    let mut args = vec!["stash", "apply", "-q"];
+    args.extend_from_slice(git_args);
+    // `git` fails if there are conflicts, which is fine as we compare the outcome.
+    std::process::Command::new("git")
+        .args(args)
+        .current_dir(git_tmp.path())
+        .output()?;
+
+    assert_eq!(
+        state(tmp.path())?,
+        state(git_tmp.path())?,
+        "applying with {options:?} leaves the same index and working tree behind"
+    );
+    assert_eq!(
+        git(tmp.path(), &["stash", "list"])?.lines().count(),
+        1,
+        "the entry is kept"
+    );
+    Ok(outcome)
+}
+
+fn nothing(_: &Path) -> crate::Result {
+    Ok(())
+}
+
+#[test]
+fn apply_is_the_same_as_with_git() -> crate::Result {
+    let outcome = assert_apply_same_as_git(&[], nothing, Default::default(), &[])?;
+    assert!(outcome.conflicts.is_empty());
+    assert!(outcome.untracked.is_empty());
+    assert_apply_same_as_git(&[], nothing, apply::Options { index: true }, &["--index"])?;
+    let outcome = assert_apply_same_as_git(&["--include-untracked"], nothing, Default::default(), &[])?;
+    assert_eq!(outcome.untracked, vec!["untracked-dir/untracked"]);
+    assert_apply_same_as_git(&["--all"], nothing, apply::Options { index: true }, &["--index"])?;
+    Ok(())
+}
+
+#[test]
+fn apply_onto_other_changes_is_the_same_as_with_git() -> crate::Result {
+    let outcome = assert_apply_same_as_git(
+        &[],
+        |dir| {
+            std::fs::write(dir.join("b"), "b changed\n")?;
+            std::fs::write(dir.join("dir/new"), "new\n")?;
+            git_ok(dir, &["add", "dir/new"]).map(drop)
+        },
+        Default::default(),
+        &[],
+    )?;
+    assert!(outcome.conflicts.is_empty(), "the changes are merged");
+
+    let outcome = assert_apply_same_as_git(
+        &[],
+        |dir| git_ok(dir, &["merge", "-q", "--ff-only", "other"]).map(drop),
+        Default::default(),
+        &[],
+    )?;
+    let conflicts: Vec<_> = outcome
+        .conflicts
+        .iter()
+        .map(|conflict| (conflict.path.as_bstr(), conflict.kind))
+        .collect();
+    assert_eq!(
+        conflicts,
+        vec![
+            ("a".into(), gix::merge::conflict::Kind::Content),
+            ("lines".into(), gix::merge::conflict::Kind::Content)
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn apply_fails_without_changing_anything_if_local_changes_are_in_the_way() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_stash_repo.sh")?;
+    change(tmp.path())?;
+    git_ok(tmp.path(), &["stash", "push", "-q", "--include-untracked"])?;
+
+    std::fs::write(tmp.path().join("lines"), "changed\n")?;
+    let before = state(tmp.path())?;
+    assert!(matches!(
+        repo.stash_apply(0, Default::default()),
+        Err(Error::LocalChanges { paths }) if paths == vec!["lines"]
+    ));
+    assert_eq!(state(tmp.path())?, before);
+
+    git_ok(tmp.path(), &["checkout", "-q", "lines"])?;
+    std::fs::create_dir(tmp.path().join("untracked-dir"))?;
+    std::fs::write(tmp.path().join("untracked-dir/untracked"), "in the way\n")?;
+    let before = state(tmp.path())?;
+    assert!(matches!(
+        repo.stash_apply(0, Default::default()),
+        Err(Error::UntrackedFiles { paths }) if paths == vec!["untracked-dir/untracked"]
+    ));
+    assert_eq!(state(tmp.path())?, before);
+    Ok(())
+}
+
+#[test]
+fn git_applies_entries_created_by_gitoxide() -> crate::Result {
+    let ((repo, tmp), git_tmp) = assert_push_same_as_git(
+        push::Options {
+            untracked: push::Untracked::Include,
+            ..Default::default()
+        },
+        &["--include-untracked"],
+    )?;
+    drop(repo);
+    git_ok(tmp.path(), &["stash", "pop", "-q", "--index"])?;
+    git_ok(git_tmp.path(), &["stash", "pop", "-q", "--index"])?;
+    assert_eq!(state(tmp.path())?, state(git_tmp.path())?);
+    Ok(())
+}
+
+#[test]
+fn pop_drops_the_entry_only_without_conflicts() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_stash_repo.sh")?;
+    change(tmp.path())?;
+    let id = repo.stash_push(Default::default())?.expect("changes");
+    git_ok(tmp.path(), &["merge", "-q", "--ff-only", "other"])?;
+    let outcome = repo.stash_pop(0, Default::default())?;
+    assert_eq!(outcome.conflicts.len(), 2);
+    assert_eq!(repo.stash_list()?.len(), 1, "the entry is kept if there are conflicts");
+    assert!(
+        std::fs::read_to_string(tmp.path().join("lines"))?
+            .contains("<<<<<<< Updated upstream\n2 changed on other\n=======\n2 stashed\n>>>>>>> Stashed changes\n"),
+        "conflict markers are written with the same labels as git uses"
+    );
+
+    git_ok(tmp.path(), &["reset", "-q", "--hard", "ORIG_HEAD"])?;
+    let outcome = repo.stash_pop(0, Default::default())?;
+    assert!(outcome.conflicts.is_empty());
+    assert!(repo.stash_list()?.is_empty());
+    assert!(repo.try_find_reference("refs/stash")?.is_none());
+    assert_eq!(
+        git(tmp.path(), &["cat-file", "-t", &id.to_string()])?,
+        "commit\n",
+        "the entry itself is still present"
+    );
+    Ok(())
+}
+
+#[test]
+fn drop_and_clear_entries_created_by_git() -> crate::Result {
+    let (repo, tmp) = repo_rw("make_stash_repo.sh")?;
+    for content in ["first", "second", "third"] {
+        std::fs::write(tmp.path().join("a"), content)?;
+        git_ok(tmp.path(), &["stash", "push", "-q", "-m", content])?;
+    }
+    let entries = repo.stash_list()?;
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| entry.message.to_string())
+            .collect::<Vec<_>>(),
+        ["On main: third", "On main: second", "On main: first"]
+    );
+    let expected = git(tmp.path(), &["stash", "list", "--format=%H %gs"])?;
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| format!("{} {}\n", entry.id, entry.message))
+            .collect::<String>(),
+        expected
+    );
+
+    assert!(matches!(repo.stash_drop(3), Err(Error::NoSuchEntry { index: 3 })));
+    assert_eq!(repo.stash_drop(1)?, entries[1].id);
+    assert_eq!(
+        git(tmp.path(), &["stash", "list", "--format=%gs"])?,
+        "On main: third\nOn main: first\n"
+    );
+    assert_eq!(repo.stash_drop(0)?, entries[0].id);
+    assert_eq!(git(tmp.path(), &["stash", "list", "--format=%gs"])?, "On main: first\n");
+    assert_eq!(
+        std::fs::read_to_string(tmp.path().join(".git/logs/refs/stash"))?
+            .lines()
+            .count(),
+        1,
+        "updating the reference doesn't add to the reflog"
+    );
+    assert_eq!(
+        git(tmp.path(), &["rev-parse", "refs/stash"])?.trim(),
+        entries[2].id.to_string(),
+        "the reference points to the most recent remaining entry"
+    );
+    git_ok(tmp.path(), &["stash", "apply", "-q"])?;
+    assert_eq!(std::fs::read_to_string(tmp.path().join("a"))?, "first");
+
+    repo.stash_clear()?;
+    assert!(repo.stash_list()?.is_empty());
+    assert_eq!(git(tmp.path(), &["stash", "list"])?, "");
+    repo.stash_clear()?;
+    Ok(())
+}
@@ -0,0 +1,380 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use gix::{
+    protocol::transport::packetline::encode,
+    upload_pack::{Options, Version},
+};
+
+fn fixture() -> crate::Result<PathBuf> {
+    Ok(gix_testtools::scripted_fixture_read_only("make_upload_pack_repo.sh")?.canonicalize()?)
+}
+
+pub(crate) fn git(dir: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(dir)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_GLOBAL", if cfg!(windows) { "NUL" } else { "/dev/null" })
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env_remove("GIT_PROTOCOL");
+    cmd
+}
+
+pub(crate) fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+pub(crate) fn url(server: &Path) -> String {
+    format!("file://{}", server.display())
+}
+
+/// Run `git -c protocol.version=<version> <subcommand> <args>` in `dir` with our implementation serving `server` as upload-pack,
+/// and return the output of `git` along with the result of serving it. `args` start with the subcommand and are expected
+/// to contain the [`url()`] of `server`.
+fn with_server(
+    server: &Path,
+    version: u8,
+    dir: &Path,
+    args: &[&str],
+) -> crate::Result<(Output, Result<(), gix::upload_pack::Error>)> {
+    serve_git(
+        server,
+        version,
+        dir,
+        args,
+        "--upload-pack",
+        |repo, input, output, version| {
+            repo.upload_pack(
+                input,
+                output,
+                Options {
+                    version,
+                    ..Default::default()
+                },
+            )
+        },
+    )
+}
+
+/// Run `git -c protocol.version=<version> <subcommand> <args>` in `dir` with `serve` being called with the repository at
+/// `server` and the input, output and protocol version of the program `git` invokes through `program_option`, and return
+/// the output of `git` along with the result of `serve`.
+///
+/// `git` talks to us through two named pipes, with the protocol version passed through a file. Note that the standard
+/// input of background processes is `/dev/null` unless it is redirected explicitly.
+pub(crate) fn serve_git<T: Send + 'static>(
+    server: &Path,
+    version: u8,
+    dir: &Path,
+    args: &[&str],
+    program_option: &str,
+    serve: impl FnOnce(gix::Repository, std::fs::File, std::fs::File, Version) -> T + Send + 'static,
+) -> crate::Result<(Output, T)> {
+    let bridge = gix_testtools::tempfile::tempdir()?;
+    for fifo in ["in", "out"] {
+        assert!(Command::new("mkfifo").arg(bridge.path().join(fifo)).status()?.success());
+    }
+    let bridge_dir = bridge.path().to_str().expect("valid UTF-8");
+    let program = format!(
+        "f() {{ printf '%s' \"$GIT_PROTOCOL\" >'{bridge_dir}/protocol'; exec 3<&0; cat <&3 >'{bridge_dir}/in' & cat <'{bridge_dir}/out'; }}; f"
+    );
+
+    let server_thread = std::thread::spawn({
+        let (server, bridge) = (server.to_owned(), bridge.path().to_owned());
+        move || {
+            let input = std::fs::File::open(bridge.join("in")).expect("fifo can be opened");
+            let protocol = std::fs::read(bridge.join("protocol")).expect("written before the fifo is opened");
+            let output = std::fs::OpenOptions::new()
+                .write(true)
+                .open(bridge.join("out"))
+                .expect("fifo can be opened");
+            let repo = gix::open_opts(server, crate::restricted()).expect("valid repository");
+            serve(
+                repo,
+                input,
+                output,
+                Version::from_git_protocol(protocol.as_slice().into()),
+            )
+        }
+    });
+
+    let output = git(dir)
+        .arg("-c")
+        .arg(format!("protocol.version={version}"))
+        .arg(args[0])
+        .arg(format!("{program_option}={program}"))
+        .args(&args[1..])
+        .output()?;
+    let start = std::time::Instant::now();
+    while !server_thread.is_finished() {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(10),
+            "git didn't connect or the server got stuck: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    let res = server_thread.join().expect("no panic");
+    Ok((output, res))
+}
+
+pub(crate) fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "git failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+pub(crate) fn rev_parse(dir: &Path, spec: &str) -> crate::Result<String> {
+    let output = git(dir).args(["rev-parse", spec]).output()?;
+    assert_success(&output);
+    Ok(stdout(&output).trim().to_owned())
+}
+
+fn commit_count(dir: &Path) -> crate::Result<usize> {
+    let output = git(dir).args(["rev-list", "--count", "HEAD"]).output()?;
+    assert_success(&output);
+    Ok(stdout(&output).trim().parse()?)
+}
+
+fn fsck(dir: &Path) -> crate::Result {
+    let output = git(dir).args(["fsck", "--connectivity-only"]).output()?;
+    assert_success(&output);
+    Ok(())
+}
+
+#[test]
+fn clone_with_all_protocol_versions() -> crate::Result {
+    let fixture = fixture()?;
+    let server = fixture.join("server");
+    for version in [0, 1, 2] {
+        let tmp = gix_testtools::tempfile::tempdir()?;
+        let (output, res) = with_server(&server, version, tmp.path(), &["clone", "--bare", "-q", &url(&server)])?;
+        res?;
+        assert_success(&output);
+
+        let clone = tmp.path().join("server.git");
+        fsck(&clone)?;
+        for spec in ["main", "other", "annotated", "lightweight", "annotated^{commit}"] {
+            assert_eq!(
+                rev_parse(&clone, spec)?,
+                rev_parse(&server, spec)?,
+                "{spec}, v{version}"
+            );
+        }
+        let head = git(&clone).args(["symbolic-ref", "HEAD"]).output()?;
+        assert_eq!(stdout(&head).trim(), "refs/heads/main", "v{version}");
+    }
+    Ok(())
+}
+
+#[test]
+fn fetch_only_sends_objects_the_client_does_not_have() -> crate::Result {
+    for version in [0, 1, 2] {
+        let dir = gix_testtools::scripted_fixture_writable("make_upload_pack_repo.sh")?;
+        let client = dir.path().join("client");
+        let server = dir.path().join("server");
+        let (output, res) = with_server(
+            &server,
+            version,
+            &client,
+            &[
+                "fetch",
+                "--progress",
+                &url(&server),
+                "+refs/heads/*:refs/remotes/origin/*",
+            ],
+        )?;
+        res?;
+        assert_success(&output);
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("remote: Total 14"),
+            "3 commits, 5 trees, 5 blobs and the tag that is followed automatically, v{version}: {stderr}"
+        );
+        fsck(&client)?;
+        assert_eq!(
+            rev_parse(&client, "refs/tags/annotated")?,
+            rev_parse(&server, "annotated")?
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn fetch_without_progress_sends_no_messages() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_writable("make_upload_pack_repo.sh")?;
+    let client = dir.path().join("client");
+    let server = dir.path().join("server");
+    let (output, res) = with_server(&server, 2, &client, &["fetch", "-q", &url(&server)])?;
+    res?;
+    assert_success(&output);
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    Ok(())
+}
+
+#[test]
+fn shallow_clones_can_be_deepened_and_unshallowed() -> crate::Result {
+    let fixture = fixture()?;
+    let server = fixture.join("server");
+    for version in [0, 2] {
+        let tmp = gix_testtools::tempfile::tempdir()?;
+        let (output, res) = with_server(
+            &server,
+            version,
+            tmp.path(),
+            &["clone", "-q", "--depth", "2", "--no-tags", &url(&server)],
+        )?;
+        res?;
+        assert_success(&output);
+        let clone = tmp.path().join("server");
+        assert_eq!(commit_count(&clone)?, 2, "v{version}");
+        fsck(&clone)?;
+
+        let (output, res) = with_server(
+            &server,
+            version,
+            &clone,
+            &["fetch", "-q", "--deepen", "1", &url(&server)],
+        )?;
+        res?;
+        assert_success(&output);
+        assert_eq!(commit_count(&clone)?, 3, "v{version}");
+        fsck(&clone)?;
+
+        let (output, res) = with_server(&server, version, &clone, &["fetch", "-q", "--unshallow", &url(&server)])?;
+        res?;
+        assert_success(&output);
+        assert_eq!(commit_count(&clone)?, 5, "v{version}");
+        assert!(!clone.join(".git").join("shallow").exists(), "v{version}");
+        fsck(&clone)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn shallow_clone_excluding_the_history_of_a_ref() -> crate::Result {
+    let fixture = fixture()?;
+    let server = fixture.join("server");
+    for version in [0, 2] {
+        let tmp = gix_testtools::tempfile::tempdir()?;
+        let (output, res) = with_server(
+            &server,
+            version,
+            tmp.path(),
+            &[
+                "clone",
+                "-q",
+                "--single-branch",
+                "--shallow-exclude",
+                "other",
+                &url(&server),
+            ],
+        )?;
+        res?;
+        assert_success(&output);
+        let clone = tmp.path().join("server");
+        assert_eq!(
+            commit_count(&clone)?,
+            3,
+            "commits 3 to 5 aren't reachable from `other`, v{version}"
+        );
+        fsck(&clone)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn ls_remote_matches_git() -> crate::Result {
+    let fixture = fixture()?;
+    let server = fixture.join("server");
+    for version in [0, 1, 2] {
+        let args = ["ls-remote", "--symref", &url(&server)];
+        let (output, res) = with_server(&server, version, &fixture, &args)?;
+        res?;
+        assert_success(&output);
+
+        let expected = git(&fixture)
+            .arg("-c")
+            .arg(format!("protocol.version={version}"))
+            .args(args)
+            .output()?;
+        assert_success(&expected);
+        assert_eq!(stdout(&output), stdout(&expected), "v{version}");
+    }
+    Ok(())
+}
+
+#[test]
+fn refs_are_advertised_like_git_does() -> crate::Result {
+    let fixture = fixture()?;
+    let server = fixture.join("server");
+    let repo = gix::open_opts(&server, crate::restricted())?;
+    let mut out = Vec::new();
+    repo.upload_pack(
+        std::io::empty(),
+        &mut out,
+        Options {
+            advertise_refs: true,
+            ..Default::default()
+        },
+    )?;
+
+    let expected = git(&server).args(["upload-pack", "--advertise-refs", "."]).output()?;
+    assert_success(&expected);
+    let without_capabilities = |data: &[u8]| -> Vec<String> {
+        let mut lines = gix::protocol::transport::packetline::StreamingPeekableIter::new(
+            data,
+            &[gix::protocol::transport::packetline::PacketLineRef::Flush],
+        );
+        let mut out = Vec::new();
+        while let Some(line) = lines.read_line() {
+            let line = line.expect("valid").expect("valid");
+            let text = line.as_bstr().expect("data line").to_string();
+            out.push(text.split('\0').next().expect("one part").trim_end().to_owned());
+        }
+        out
+    };
+    assert_eq!(without_capabilities(&out), without_capabilities(&expected.stdout));
+    Ok(())
+}
+
+#[test]
+fn objects_that_are_not_advertised_cannot_be_fetched() -> crate::Result {
+    let fixture = fixture()?;
+    let server = fixture.join("server");
+    let blob = rev_parse(&server, "main:file4")?;
+
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    assert_success(&git(tmp.path()).args(["init", "-q"]).output()?);
+    let (output, res) = with_server(&server, 2, tmp.path(), &["fetch", "-q", &url(&server), &blob])?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&format!("remote error: upload-pack: The client requested {blob}")),
+        "the error is shown to the user: {stderr}"
+    );
+    assert!(matches!(res, Err(gix::upload_pack::Error::NotOurRef { .. })));
+
+    // `git` doesn't send unadvertised wants in version 0, so we do it ourselves.
+    let mut input = Vec::new();
+    encode::text_to_write(format!("want {blob} side-band-64k").as_bytes(), &mut input)?;
+    encode::flush_to_write(&mut input)?;
+    encode::text_to_write(b"done", &mut input)?;
+    let repo = gix::open_opts(&server, crate::restricted())?;
+    let mut out = Vec::new();
+    let res = repo.upload_pack(input.as_slice(), &mut out, Options::default());
+    assert!(matches!(res, Err(gix::upload_pack::Error::NotOurRef { .. })));
+    assert!(
+        out.ends_with(
+            format!("ERR upload-pack: The client requested {blob} which is not the target of one of our refs")
+                .as_bytes()
+        ),
+        "the error is sent after the advertisement"
+    );
+    Ok(())
+}
@@ -1,4 +1,6 @@
 #![allow(clippy::result_large_err)]
+use std::path::Path;
+
 use gix::{open, Repository, ThreadSafeRepository};
 use gix_testtools::tempfile;
 
@@ -70,6 +72,35 @@ pub fn repo_rw_opts(name: &str, opts: gix::open::Options) -> Result<(Repository,
     ))
 }
 
+/// Like [`repo_rw()`], but runs the script in the writable location instead of copying its result there, which is needed
+/// for fixtures that can't be copied, like those containing symlinks or absolute paths.
+pub fn repo_rw_executed(name: &str) -> Result<(Repository, tempfile::TempDir)> {
+    let dir = fixture_rw_executed(name)?;
+    Ok((gix::open_opts(dir.path(), restricted())?, dir))
+}
+
+/// The writable directory in which the script `name` was run, see [`repo_rw_executed()`].
+pub fn fixture_rw_executed(name: &str) -> Result<tempfile::TempDir> {
+    gix_testtools::scripted_fixture_writable_with_args(name, None::<String>, gix_testtools::Creation::ExecuteScript)
+}
+
+/// Run `git` with `args` in `dir` and return its output, whether it succeeded or not.
+pub fn git_output(dir: &Path, args: &[&str]) -> Result<std::process::Output> {
+    Ok(std::process::Command::new("git").args(args).current_dir(dir).output()?)
+}
+
+/// Run `git` with `args` in `dir` and return what it printed to stdout, whether it succeeded or not.
+pub fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    Ok(String::from_utf8(git_output(dir, args)?.stdout)?)
+}
+
+/// Like [`git()`], but assert that it succeeds.
+pub fn git_ok(dir: &Path, args: &[&str]) -> Result<String> {
+    let out = git_output(dir, args)?;
+    assert!(out.status.success(), "git {args:?} failed in {dir:?}");
+    Ok(String::from_utf8(out.stdout)?)
+}
+
 pub fn basic_repo() -> Result<Repository> {
     repo("make_basic_repo.sh").map(|r| r.to_thread_local())
 }
@@ -12,6 +12,11 @@ mod shared;
 use anyhow::Result;
 
 fn main() -> Result<()> {
+    // We are the default daemon of the credential cache, which runs us again to serve credentials.
+    #[cfg(unix)]
+    if let Some(res) = gix::credentials::cache::daemon::serve_if_requested() {
+        return Ok(res?);
+    }
     porcelain::main()
 }
 
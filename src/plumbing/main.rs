@@ -132,6 +132,7 @@ pub fn main() -> Result<()> {
             no_tags,
             remote,
             shallow,
+            filter,
             directory,
         }) => {
             let opts = core::repository::clone::Options {
@@ -140,6 +141,7 @@ pub fn main() -> Result<()> {
                 handshake_info,
                 no_tags,
                 shallow: shallow.into(),
+                filter,
             };
             prepare_and_run(
                 "clone",
@@ -156,6 +158,7 @@ pub fn main() -> Result<()> {
             handshake_info,
             remote,
             shallow,
+            filter,
             ref_spec,
         }) => {
             let opts = core::repository::fetch::Options {
@@ -164,6 +167,7 @@ pub fn main() -> Result<()> {
                 remote,
                 handshake_info,
                 shallow: shallow.into(),
+                filter,
                 ref_specs: ref_spec,
             };
             prepare_and_run(
@@ -177,6 +181,18 @@ pub fn main() -> Result<()> {
                 },
             )
         }
+        #[cfg(feature = "gitoxide-core-blocking-client")]
+        Subcommands::UploadPack(crate::plumbing::options::upload_pack::Platform {
+            stateless_rpc,
+            advertise_refs,
+            directory,
+        }) => core::repository::upload_pack(directory, stateless_rpc, advertise_refs, thread_limit),
+        #[cfg(feature = "gitoxide-core-blocking-client")]
+        Subcommands::ReceivePack(crate::plumbing::options::receive_pack::Platform {
+            stateless_rpc,
+            advertise_refs,
+            directory,
+        }) => core::repository::receive_pack(directory, stateless_rpc, advertise_refs, thread_limit),
         Subcommands::ConfigTree => show_progress(),
         Subcommands::Credential(cmd) => core::repository::credential(
             repository(Mode::StrictWithGitInstallConfig)?,
@@ -186,6 +202,10 @@ pub fn main() -> Result<()> {
                 credential::Subcommands::Reject => gix::credentials::program::main::Action::Erase,
             },
         ),
+        #[cfg(unix)]
+        Subcommands::CredentialCacheDaemon { socket } => {
+            Ok(gix::credentials::cache::daemon::serve(&socket, std::io::stdout())?)
+        }
         #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
         Subcommands::Remote(crate::plumbing::options::remote::Platform {
             name,
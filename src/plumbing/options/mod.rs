@@ -92,11 +92,24 @@ pub enum Subcommands {
     /// A program just like `git credential`.
     #[clap(subcommand)]
     Credential(credential::Subcommands),
+    /// Run the daemon of the `cache` credential helper, which is started by the helper as needed.
+    #[cfg(unix)]
+    #[clap(name = "credential-cache--daemon", hide = true)]
+    CredentialCacheDaemon {
+        /// The path to the socket to listen on.
+        socket: PathBuf,
+    },
     /// Fetch data from remotes and store it in the repository
     #[cfg(feature = "gitoxide-core-blocking-client")]
     Fetch(fetch::Platform),
     #[cfg(feature = "gitoxide-core-blocking-client")]
     Clone(clone::Platform),
+    /// Send packs to clients fetching from the repository at the given directory, a program just like `git upload-pack`.
+    #[cfg(feature = "gitoxide-core-blocking-client")]
+    UploadPack(upload_pack::Platform),
+    /// Receive packs from clients pushing to the repository at the given directory, a program just like `git receive-pack`.
+    #[cfg(feature = "gitoxide-core-blocking-client")]
+    ReceivePack(receive_pack::Platform),
     /// Interact with the mailmap.
     #[clap(subcommand)]
     Mailmap(mailmap::Subcommands),
@@ -152,6 +165,12 @@ pub mod fetch {
         #[clap(flatten)]
         pub shallow: ShallowOptions,
 
+        /// Omit objects from the received pack according to the given filter spec, like `blob:none` or `tree:0`.
+        ///
+        /// If unset, the `remote.<name>.partialCloneFilter` configuration of promisor remotes is used.
+        #[clap(long, value_parser = crate::shared::AsFilter, value_name = "FILTER_SPEC")]
+        pub filter: Option<gix::remote::fetch::Filter>,
+
         /// The name of the remote to connect to, or the url of the remote to connect to directly.
         ///
         /// If unset, the current branch will determine the remote.
@@ -231,6 +250,12 @@ pub mod clone {
         #[clap(flatten)]
         pub shallow: ShallowOptions,
 
+        /// Create a partial clone which omits objects according to the given filter spec, like `blob:none` or `tree:0`.
+        ///
+        /// Missing objects will be fetched on demand from the remote.
+        #[clap(long, value_parser = crate::shared::AsFilter, value_name = "FILTER_SPEC")]
+        pub filter: Option<gix::remote::fetch::Filter>,
+
         /// The url of the remote to connect to, like `https://github.com/byron/gitoxide`.
         pub remote: OsString,
 
@@ -394,6 +419,44 @@ pub mod commit {
     }
 }
 
+#[cfg(feature = "gitoxide-core-blocking-client")]
+pub mod upload_pack {
+    use std::path::PathBuf;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Serve a single request and response, as needed for stateless transports like HTTP.
+        #[clap(long)]
+        pub stateless_rpc: bool,
+
+        /// Only advertise our refs and capabilities, and exit right after.
+        #[clap(long, visible_alias = "http-backend-info-refs")]
+        pub advertise_refs: bool,
+
+        /// The repository to serve.
+        pub directory: PathBuf,
+    }
+}
+
+#[cfg(feature = "gitoxide-core-blocking-client")]
+pub mod receive_pack {
+    use std::path::PathBuf;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Serve a single request and response, as needed for stateless transports like HTTP.
+        #[clap(long)]
+        pub stateless_rpc: bool,
+
+        /// Only advertise our refs and capabilities, and exit right after.
+        #[clap(long, visible_alias = "http-backend-info-refs")]
+        pub advertise_refs: bool,
+
+        /// The repository to receive pushes into.
+        pub directory: PathBuf,
+    }
+}
+
 pub mod credential {
     #[derive(Debug, clap::Subcommand)]
     pub enum Subcommands {
@@ -450,18 +450,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "sparse.expectFilesOutsideOfPatterns",
         usage: NotPlanned { reason: "todo" },
     },
-    Record {
-        config: "remote.<name>.promisor",
-        usage: Planned {
-            note: Some("required for big monorepos, and typically used in conjunction with sparse indices")
-        }
-    },
-    Record {
-        config: "remote.<name>.partialCloneFilter",
-        usage: Planned {
-            note: Some("required for big monorepos, and typically used in conjunction with sparse indices")
-        }
-    },
     Record {
         config: "merge.renameLimit",
         usage: Planned { note: Some("The same as diff.renameLimit") }
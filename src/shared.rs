@@ -332,5 +332,18 @@ mod clap {
                 .parse_ref(cmd, arg, value)
         }
     }
+
+    #[derive(Clone)]
+    pub struct AsFilter;
+
+    impl TypedValueParser for AsFilter {
+        type Value = gix::remote::fetch::Filter;
+
+        fn parse_ref(&self, cmd: &Command, arg: Option<&Arg>, value: &OsStr) -> Result<Self::Value, Error> {
+            AsBString
+                .try_map(|spec| gix::remote::fetch::Filter::from_spec(spec.as_ref()))
+                .parse_ref(cmd, arg, value)
+        }
+    }
 }
-pub use self::clap::{AsBString, AsHashKind, AsOutputFormat, AsPartialRefName, AsPathSpec, AsTime};
+pub use self::clap::{AsBString, AsFilter, AsHashKind, AsOutputFormat, AsPartialRefName, AsPathSpec, AsTime};